@@ -0,0 +1,65 @@
+use criterion::{criterion_group, criterion_main, Criterion, black_box};
+
+use fluffy_tribble::nalgebra::Vector3;
+use fluffy_tribble::bench::*;
+
+type Vec3 = Vector3<f32>;
+
+/// A cloud of small, slow spheres (so most pairs miss, like in a big pile), as center, movement, and radius.
+fn sphere_cloud() -> Vec<(Vec3, Vec3, f32)> {
+	(0..1024).map(|index| {
+		let value = index as f32;
+		(Vec3::new((value * 0.37).sin() * 20.0, (value * 0.11).cos() * 20.0, (value * 0.73).sin() * 20.0), Vec3::new(0.0, -0.1, (value * 0.5).cos() * 0.1), 0.5)
+	}).collect()
+}
+
+/// The ground plane under the cloud, as position, normal, and movement.
+fn ground() -> (Vec3, Vec3, Vec3) {
+	(Vec3::new(0.0, -10.0, 0.0), Vec3::new(0.0, 1.0, 0.0), Vec3::zeros())
+}
+
+/// Collides every sphere with every other sphere and the ground, one pair at a time. Returns how many hits there were.
+fn full_checks(spheres : &[(Vec3, Vec3, f32)]) -> usize {
+	let (plane_position, plane_normal, plane_movement) = ground();
+	let mut hits = 0;
+	for (center, movement, radius) in spheres {
+		for (other_center, other_movement, other_radius) in spheres {
+			hits += collide_sphere_with_sphere(*radius, center, movement, *other_radius, other_center, other_movement).is_some() as usize;
+		}
+		hits += collide_sphere_with_plane(*radius, center, movement, &plane_position, &plane_normal, &plane_movement).is_some() as usize;
+	}
+	hits
+}
+
+/// The same as [full_checks], but a batch of spheres at a time.
+fn batched_checks(spheres : &[(Vec3, Vec3, f32)], batches : &[SphereLanes]) -> usize {
+	let (plane_position, plane_normal, plane_movement) = ground();
+	let mut hits = 0;
+	for (center, movement, radius) in spheres {
+		for batch in batches {
+			let times = collide_sphere_with_spheres(*radius, center, movement, batch);
+			hits += (0..LANES).filter(|lane| sphere_with_spheres_collision(*radius, center, movement, batch, &times, *lane).is_some()).count();
+		}
+	}
+	for batch in batches {
+		let times = collide_plane_with_spheres(&plane_position, &plane_normal, &plane_movement, batch);
+		hits += (0..LANES).filter(|lane| plane_with_spheres_collision(&plane_position, &plane_normal, &plane_movement, batch, &times, *lane).is_some()).count();
+	}
+	hits
+}
+
+fn sphere_cloud_checks(criterion : &mut Criterion) {
+	let spheres = sphere_cloud();
+	let mut batches = Vec::new();
+	SphereLanes::pack_into(&spheres, &mut batches);
+	// Only worth comparing if they find the same hits.
+	assert_eq!(full_checks(&spheres), batched_checks(&spheres, &batches));
+
+	let mut group = criterion.benchmark_group("sphere_cloud");
+	group.bench_function("full", |bencher| bencher.iter(|| full_checks(black_box(&spheres))));
+	group.bench_function("batched", |bencher| bencher.iter(|| batched_checks(black_box(&spheres), black_box(&batches))));
+	group.finish();
+}
+
+criterion_group!(benches, sphere_cloud_checks);
+criterion_main!(benches);
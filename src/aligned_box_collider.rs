@@ -1,14 +1,18 @@
 use crate::consts::*;
-use crate::types::{Vec3, Mat3, EntityHandle, min, max};
+use crate::types::{Vec3, Mat3, EntityHandle, MaterialHandle, min, max};
 use crate::collider::{ColliderType, Collider, InternalCollider};
+use crate::anisotropic_friction::AnisotropicFriction;
 use crate::orientation::Orientation;
 
 /// The internal representation of an axis-aligned rectangular prism collider.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct InternalAlignedBoxCollider {
 	/// The entity that this is linked to (if any).
 	entity : Option<EntityHandle>,
 
+	/// If this takes part in collisions and queries. Only changed with [crate::PhysicsSystem::set_collider_enabled].
+	enabled : bool,
+
 	/// The position of this collider's origin.
 	///
 	/// This is in the parent entity's local space.
@@ -33,6 +37,21 @@ pub struct InternalAlignedBoxCollider {
 
 	/// The dynamic friction coefficient. Should always at or between 0.0 and 1.0.
 	pub dynamic_friction_coefficient : f32,
+
+	/// How friction changes with the sliding direction (if at all).
+	pub anisotropic_friction : Option<AnisotropicFriction>,
+
+	/// Extra thickness around the surface, used for collision detection.
+	pub margin : f32,
+
+	/// What this is made of.
+	pub material : MaterialHandle,
+
+	/// If this is only seen by queries (i.e. ray casts), never colliding with anything nor adding any mass.
+	pub query_only : bool,
+
+	/// A tag copied into collision records.
+	pub surface_tag : u32,
 }
 
 impl InternalAlignedBoxCollider {
@@ -42,6 +61,7 @@ impl InternalAlignedBoxCollider {
 		} else {
 			Ok(Box::new(InternalAlignedBoxCollider {
 				entity: None,
+				enabled: true,
 				position: source.position.clone(),
 				min_corner: Vec3::new(
 					min(source.min_corner.x, source.max_corner.x),
@@ -58,6 +78,11 @@ impl InternalAlignedBoxCollider {
 				friction_threshold: source.friction_threshold,
 				static_friction_coefficient: source.static_friction_coefficient,
 				dynamic_friction_coefficient: source.dynamic_friction_coefficient,
+				anisotropic_friction: source.anisotropic_friction,
+				margin: source.margin,
+				material: source.material,
+				query_only: source.query_only,
+				surface_tag: source.surface_tag,
 			}))
 		}
 	}
@@ -74,6 +99,11 @@ impl InternalAlignedBoxCollider {
 			friction_threshold: self.friction_threshold,
 			static_friction_coefficient: self.static_friction_coefficient,
 			dynamic_friction_coefficient: self.dynamic_friction_coefficient,
+			anisotropic_friction: self.anisotropic_friction,
+			margin: self.margin,
+			material: self.material,
+			query_only: self.query_only,
+			surface_tag: self.surface_tag,
 		}
 	}
 
@@ -98,6 +128,11 @@ impl InternalAlignedBoxCollider {
 			self.friction_threshold = source.friction_threshold;
 			self.static_friction_coefficient = source.static_friction_coefficient;
 			self.dynamic_friction_coefficient = source.dynamic_friction_coefficient;
+			self.anisotropic_friction = source.anisotropic_friction;
+			self.margin = source.margin;
+			self.material = source.material;
+			self.query_only = source.query_only;
+			self.surface_tag = source.surface_tag;
 			Ok(())
 		}
 	}
@@ -107,6 +142,9 @@ impl InternalCollider for InternalAlignedBoxCollider {
 	/// The specific type.
 	fn get_type(&self) -> ColliderType { ColliderType::ALIGNED_BOX }
 
+	/// Makes a boxed copy.
+	fn clone_boxed(&self) -> Box<dyn InternalCollider> { Box::new(self.clone()) }
+
 	/// Sets the entity this is attached to, returning the previous one.
 	fn set_entity(&mut self, handle : Option<EntityHandle>) -> Option<EntityHandle> {
 		let old = self.entity;
@@ -117,11 +155,19 @@ impl InternalCollider for InternalAlignedBoxCollider {
 	/// Retrieves the stored entity handle that this is attached to.
 	fn get_entity(&mut self) -> Option<EntityHandle> { self.entity }
 
+	fn set_enabled(&mut self, enabled : bool) { self.enabled = enabled; }
+
+	fn is_enabled(&self) -> bool { self.enabled }
+
 	/// Gets the center of mass for this collider.
 	/// This is relative to this collider's owning/linked/attached entity.
 	/// This IS NOT relative to this collider's "position" property.
 	fn get_local_center_of_mass(&self) -> Vec3 { self.position + 0.5 * (self.min_corner + self.max_corner) }
 
+	fn get_local_position(&self) -> Vec3 { self.position }
+
+	fn set_local_position(&mut self, position : &Vec3) { self.position = *position; }
+
 	fn get_mass(&self) -> f32 { self.mass }
 
 	fn get_moment_of_inertia_tensor(&self) -> Mat3 {
@@ -142,10 +188,20 @@ impl InternalCollider for InternalAlignedBoxCollider {
 	fn get_static_friction_coefficient(&self) -> f32 { self.static_friction_coefficient }
 
 	fn get_dynamic_friction_coefficient(&self) -> f32 { self.dynamic_friction_coefficient }
+
+	fn get_anisotropic_friction(&self) -> Option<AnisotropicFriction> { self.anisotropic_friction }
+
+	fn get_margin(&self) -> f32 { self.margin }
+	fn get_material(&self) -> MaterialHandle { self.material }
+
+	fn is_query_only(&self) -> bool { self.query_only }
+
+	fn get_surface_tag(&self) -> u32 { self.surface_tag }
 }
 
 /// A copy of all of the publicly-accessible properties of an axis-aligned rectangular prism collider.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AlignedBoxCollider {
 	/// The entity that this is linked to (if any).
 	///
@@ -161,14 +217,14 @@ pub struct AlignedBoxCollider {
 
 	/// The corner with all of the smaller values.
 	///
-	/// This doesn't need to store the min corner for this to be valid; it only needs to be more than `EPSILON` from `max_corner`.
+	/// This doesn't need to store the min corner for this to be valid; it only needs to be more than `EPSILON` from `max_corner` along every axis.
 	///
 	/// Defaults to origin.
 	pub min_corner : Vec3,
 
 	/// The corner with all of the larger values.
 	///
-	/// This doesn't need to store the max corner for this to be valid; it only needs to be more than `EPSILON` from `min_corner`.
+	/// This doesn't need to store the max corner for this to be valid; it only needs to be more than `EPSILON` from `min_corner` along every axis.
 	///
 	/// Defaults to `(1.0, 1.0, 1.0)`.
 	pub max_corner : Vec3,
@@ -197,6 +253,31 @@ pub struct AlignedBoxCollider {
 	///
 	/// Defaults to `0.3`.
 	pub dynamic_friction_coefficient : f32,
+
+	/// How friction changes with the sliding direction. If this is None, then friction is the same in all directions.
+	///
+	/// Defaults to None.
+	pub anisotropic_friction : Option<AnisotropicFriction>,
+
+	/// Extra thickness around the surface, used for collision detection. Two colliders count as touching once their surfaces are as close as their margins added together (so things resting on each other stay that far apart). Must not be negative.
+	///
+	/// Defaults to `0.0`.
+	pub margin : f32,
+
+	/// What this is made of. Only used to look up any properties set for specific pairs of materials (see [crate::PhysicsSystem::set_material_pair_properties]).
+	///
+	/// Defaults to `0`.
+	pub material : MaterialHandle,
+
+	/// If true, this never collides with anything and adds no mass to its entity, but still moves with it and is hit by queries like [crate::PhysicsSystem::cast_ray]. i.e. for a large detection sphere around an enemy.
+	///
+	/// Defaults to `false`.
+	pub query_only : bool,
+
+	/// A tag that's copied into every [crate::CollisionRecord] this is a part of, but otherwise ignored. i.e. for picking which sound to play.
+	///
+	/// Defaults to `0`.
+	pub surface_tag : u32,
 }
 
 impl AlignedBoxCollider {
@@ -212,13 +293,38 @@ impl AlignedBoxCollider {
 			friction_threshold: 0.25,
 			static_friction_coefficient: 1.0,
 			dynamic_friction_coefficient: 0.3,
+			anisotropic_friction: None,
+			margin: 0.0,
+			material: 0,
+			query_only: false,
+			surface_tag: 0,
 		}
 	}
 
+	/// Creates a box centered on `center` that reaches out `half_extents` along each axis (the signs of `half_extents` are ignored). Everything else is at default.
+	pub fn from_center_half_extents(center : impl Into<Vec3>, half_extents : impl Into<Vec3>) -> AlignedBoxCollider {
+		let center = center.into();
+		let half_extents = half_extents.into().abs();
+		let mut collider = AlignedBoxCollider::new();
+		collider.min_corner = center - half_extents;
+		collider.max_corner = center + half_extents;
+		collider
+	}
+
+	/// The center of the box (relative to `position`).
+	pub fn center(&self) -> Vec3 { 0.5 * (self.min_corner + self.max_corner) }
+
+	/// How far the box reaches out from its center along each axis. Always positive (even if the corners are swapped).
+	pub fn half_extents(&self) -> Vec3 { 0.5 * (self.max_corner - self.min_corner).abs() }
+
 	/// If this is in a valid state.
+	///
+	/// Every side must be more than `EPSILON` long (with the corners in either order). So a box whose corners are the same, or only differ along some of the axes, is rejected. Using [AlignedBoxCollider::from_center_half_extents] with non-zero half extents avoids this.
+	///
+	/// Inverted boxes (where `min_corner` is past `max_corner` along some axes) ARE accepted. They're treated as the same box with the corners swapped back (which is what the system stores).
 	pub fn is_valid(&self) -> bool {
-		let size = self.max_corner - self.min_corner;
-		EPSILON < size.x && EPSILON < size.y && EPSILON < size.z && 0.0 <= self.mass
+		let size = 2.0 * self.half_extents();
+		EPSILON < size.x && EPSILON < size.y && EPSILON < size.z && 0.0 <= self.mass && self.anisotropic_friction.iter().all(|friction| friction.is_valid()) && 0.0 <= self.margin
 	}
 }
 
@@ -227,5 +333,47 @@ impl Collider for AlignedBoxCollider {
 
 	fn get_entity(&self) -> Option<EntityHandle> { self.entity }
 
-	fn get_center_of_mass(&self) -> Vec3 { self.position + 0.5 * (self.min_corner + self.max_corner) }
+	fn get_center_of_mass(&self) -> Vec3 { self.position + self.center() }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Verify the center and half extents round trip.
+	#[test]
+	fn center_half_extents() {
+		let collider = AlignedBoxCollider::from_center_half_extents(Vec3::new(1.0, -2.0, 3.0), Vec3::new(0.5, 1.0, 2.0));
+		assert_eq!(collider.min_corner, Vec3::new(0.5, -3.0, 1.0));
+		assert_eq!(collider.max_corner, Vec3::new(1.5, -1.0, 5.0));
+		assert_eq!(collider.center(), Vec3::new(1.0, -2.0, 3.0));
+		assert_eq!(collider.half_extents(), Vec3::new(0.5, 1.0, 2.0));
+		assert!(collider.is_valid());
+
+		// The signs of the half extents are ignored.
+		let negative = AlignedBoxCollider::from_center_half_extents(Vec3::new(1.0, -2.0, 3.0), Vec3::new(-0.5, 1.0, -2.0));
+		assert_eq!(negative.min_corner, collider.min_corner);
+		assert_eq!(negative.max_corner, collider.max_corner);
+
+		// Can't be flat.
+		assert!(!AlignedBoxCollider::from_center_half_extents(Vec3::zeros(), Vec3::new(1.0, 0.0, 1.0)).is_valid());
+	}
+
+	/// Verify swapped corners are still a valid box (with the same center and half extents), and are sorted once added.
+	#[test]
+	fn swapped_corners() {
+		let mut collider = AlignedBoxCollider::new();
+		collider.min_corner = Vec3::new(2.0, 0.0, 1.0);
+		collider.max_corner = Vec3::new(0.0, 1.0, -1.0);
+		assert!(collider.is_valid());
+		assert_eq!(collider.center(), Vec3::new(1.0, 0.5, 0.0));
+		assert_eq!(collider.half_extents(), Vec3::new(1.0, 0.5, 1.0));
+
+		let internal = InternalAlignedBoxCollider::new_from(&collider).unwrap();
+		let stored = internal.downcast_ref::<InternalAlignedBoxCollider>().unwrap().make_pub();
+		assert_eq!(stored.min_corner, Vec3::new(0.0, 0.0, -1.0));
+		assert_eq!(stored.max_corner, Vec3::new(2.0, 1.0, 1.0));
+		assert_eq!(stored.center(), collider.center());
+		assert_eq!(stored.half_extents(), collider.half_extents());
+	}
 }
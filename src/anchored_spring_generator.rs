@@ -0,0 +1,133 @@
+use crate::consts::EPSILON;
+use crate::types::{Vec3, EntityHandle};
+use crate::world_query::WorldQuery;
+use crate::entity_view::EntityView;
+use crate::force::Force;
+use crate::unary_force_generator::UnaryForceGenerator;
+
+/// A damped spring between a point on one entity and a fixed point in the world. i.e. for dragging things around with the mouse cursor.
+///
+/// Only pushes on `entity`: every other entity gets no force from this.
+#[derive(Debug, Clone)]
+pub struct AnchoredSpringGenerator {
+	/// The entity being pulled on.
+	pub entity : EntityHandle,
+
+	/// Where the spring attaches to the entity, in the entity's LOCAL space.
+	///
+	/// Defaults to the local origin.
+	pub local_anchor : Vec3,
+
+	/// Where the other end of the spring is, in WORLD space. Move this around to drag the entity.
+	///
+	/// Defaults to origin.
+	pub world_anchor : Vec3,
+
+	/// How hard the spring pulls per unit of stretch (or pushes per unit of squish). Must not be negative.
+	///
+	/// Defaults to 1.0.
+	pub stiffness : f32,
+
+	/// How hard the spring resists the ends moving apart or together, per unit of speed. Must not be negative.
+	///
+	/// Defaults to 0.0.
+	pub damping : f32,
+
+	/// How long the spring is when it isn't pulling or pushing. Must not be negative.
+	///
+	/// Defaults to 0.0.
+	pub rest_length : f32,
+}
+
+impl AnchoredSpringGenerator {
+	/// Creates a new spring on the given entity, with everything else at its defaults.
+	pub fn new(entity : EntityHandle) -> AnchoredSpringGenerator {
+		AnchoredSpringGenerator {
+			entity,
+			local_anchor: Vec3::zeros(),
+			world_anchor: Vec3::zeros(),
+			stiffness: 1.0,
+			damping: 0.0,
+			rest_length: 0.0,
+		}
+	}
+
+	/// If this is in a valid state.
+	pub fn is_valid(&self) -> bool {
+		0.0 <= self.stiffness && 0.0 <= self.damping && 0.0 <= self.rest_length
+	}
+}
+
+impl UnaryForceGenerator for AnchoredSpringGenerator {
+	fn make_force(&mut self, _dt : f32, _world : &dyn WorldQuery, entity : &EntityView) -> Force {
+		if entity.handle != self.entity { return Force::new(Vec3::zeros(), Vec3::zeros()); }
+		let position = entity.orientation.position_into_world(&self.local_anchor);
+		let velocity = entity.get_velocity_at_world_position(&position);
+		let offset = position - self.world_anchor;
+		let length = offset.magnitude();
+		let force = if length < EPSILON {
+			// No direction to pull along, so only the damping does anything.
+			-velocity * self.damping
+		} else {
+			let direction = offset / length;
+			-direction * (self.stiffness * (length - self.rest_length) + self.damping * velocity.dot(&direction))
+		};
+		Force::new(force, position)
+	}
+
+	fn clone_boxed(&self) -> Option<Box<dyn UnaryForceGenerator>> { Some(Box::new(self.clone())) }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::entity::Entity;
+	use crate::sphere_collider::SphereCollider;
+	use crate::collider_wrapper::ColliderWrapper;
+	use crate::gravity_generator::GravityGenerator;
+	use crate::physics_system::PhysicsSystem;
+
+	/// Verify a ball hung off of the spring settles where the spring holds up its weight, and that nothing else is pulled on.
+	#[test]
+	fn hangs() {
+		let mut system = PhysicsSystem::new();
+		system.energy_sleep_threshold = 0.0; // So it doesn't fall asleep before it's finished settling.
+		let mut add_ball = |position : Vec3| {
+			let mut entity = Entity::new();
+			entity.position = position;
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 1.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		let ball = add_ball(Vec3::new(0.0, 5.0, 0.0));
+		let bystander = add_ball(Vec3::new(10.0, 5.0, 0.0));
+		let mut spring = AnchoredSpringGenerator::new(ball);
+		spring.local_anchor = Vec3::new(0.0, 0.5, 0.0); // The top of the ball.
+		spring.world_anchor = Vec3::new(0.0, 10.0, 0.0);
+		spring.stiffness = 20.0;
+		spring.damping = 4.0;
+		spring.rest_length = 2.0;
+		assert!(spring.is_valid());
+		system.add_unary_force_generator(Box::new(spring.clone())).unwrap();
+		let gravity = Vec3::new(0.0, -10.0, 0.0);
+		system.add_unary_force_generator(Box::new(GravityGenerator::new(gravity))).unwrap();
+		for _ in 0..500 {
+			system.step(0.01);
+		}
+		// Stretched by 10 / 20, straight below the anchor. The ball hangs from its top, so its center is another half below that.
+		let ball = system.get_entity(ball).unwrap();
+		assert!((ball.position - Vec3::new(0.0, 7.0, 0.0)).magnitude() < 0.01, "{:?}", ball.position);
+		assert!(ball.velocity.magnitude() < 0.01);
+		let top = ball.get_last_orientation().position_into_world(&spring.local_anchor);
+		assert!((top - Vec3::new(0.0, 7.5, 0.0)).magnitude() < 0.01, "{:?}", top);
+		// The bystander just falls.
+		let bystander = system.get_entity(bystander).unwrap();
+		assert!(bystander.position.x == 10.0 && bystander.position.y < 0.0);
+
+		spring.rest_length = -1.0;
+		assert!(!spring.is_valid());
+	}
+}
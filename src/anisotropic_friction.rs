@@ -0,0 +1,62 @@
+use crate::types::Vec3;
+use crate::consts::EPSILON;
+
+/// Makes a collider's friction depend on which way things slide over it (e.g. skis or treads).
+///
+/// The collider's static and dynamic friction coefficients are scaled by `along_scale` for sliding along `direction`, and by `across_scale` for sliding perpendicular to it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnisotropicFriction {
+	/// The direction that `along_scale` applies to. This is in the parent entity's local space.
+	///
+	/// Doesn't need to be normalized, but must not be zero.
+	pub direction : Vec3,
+
+	/// The amount to scale friction by when sliding along `direction`. Must not be negative.
+	pub along_scale : f32,
+
+	/// The amount to scale friction by when sliding perpendicular to `direction`. Must not be negative.
+	pub across_scale : f32,
+}
+
+impl AnisotropicFriction {
+	/// Creates an instance.
+	pub fn new(direction : impl Into<Vec3>, along_scale : f32, across_scale : f32) -> AnisotropicFriction {
+		AnisotropicFriction {
+			direction: direction.into(),
+			along_scale,
+			across_scale,
+		}
+	}
+
+	/// If this is in a valid state.
+	pub fn is_valid(&self) -> bool {
+		EPSILON < self.direction.magnitude() && 0.0 <= self.along_scale && 0.0 <= self.across_scale
+	}
+
+	/// Gets the amount to scale friction by for sliding in the given direction.
+	///
+	/// The passed in direction must be in the same space as `direction`, and be normalized.
+	pub fn get_scale(&self, sliding_direction : &Vec3) -> f32 {
+		let along = sliding_direction.dot(&self.direction.normalize());
+		let across = (1.0 - along * along).max(0.0).sqrt();
+		((self.along_scale * along).powi(2) + (self.across_scale * across).powi(2)).sqrt()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Verify the scale blends between the along and across values.
+	#[test]
+	fn scale() {
+		let friction = AnisotropicFriction::new(Vec3::new(2.0, 0.0, 0.0), 0.1, 1.0);
+		assert!(friction.is_valid());
+		assert!((friction.get_scale(&Vec3::new(1.0, 0.0, 0.0)) - 0.1).abs() < EPSILON);
+		assert!((friction.get_scale(&Vec3::new(0.0, 0.0, -1.0)) - 1.0).abs() < EPSILON);
+		let diagonal = friction.get_scale(&Vec3::new(1.0, 1.0, 0.0).normalize());
+		assert!(0.1 < diagonal && diagonal < 1.0);
+		assert!(!AnisotropicFriction::new(Vec3::zeros(), 0.1, 1.0).is_valid());
+	}
+}
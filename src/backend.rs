@@ -0,0 +1,38 @@
+use crate::physics_world::{PhysicsWorld, ImpulseWorld};
+use crate::xpbd_world::XpbdWorld;
+
+/// Which solver backend to use. Pick one when creating a world with [Backend::create].
+///
+/// The created world can only be used through [PhysicsWorld]. For anything backend-specific (i.e. everything else on [crate::PhysicsSystem], or the rest of [XpbdWorld]'s joint functions), create the backend's type directly instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+	/// The original backend. See [ImpulseWorld].
+	Impulse,
+	/// The experimental position-based backend. See [XpbdWorld].
+	Xpbd,
+}
+
+impl Backend {
+	/// Creates a new (empty) world that uses this backend.
+	pub fn create(self) -> Box<dyn PhysicsWorld> {
+		match self {
+			Backend::Impulse => Box::new(ImpulseWorld::new()),
+			Backend::Xpbd => Box::new(XpbdWorld::new()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::physics_world::shared_tests;
+
+	/// Verify both backends can be picked, and work the same way through the shared interface.
+	#[test]
+	fn create() {
+		for backend in [Backend::Impulse, Backend::Xpbd] {
+			shared_tests::ball_lands_on_floor(backend.create().as_mut());
+			shared_tests::ball_rests_inside_room(backend.create().as_mut());
+		}
+	}
+}
@@ -0,0 +1,52 @@
+use crate::types::{Vec3, EntityHandle};
+
+/// A ball-and-socket joint: holds a point on one entity at a point on another, but lets them rotate freely about it.
+///
+/// Only supported by [crate::XpbdWorld]. See [crate::XpbdWorld::add_joint].
+#[derive(Debug, Clone)]
+pub struct BallJoint {
+	/// The first entity.
+	pub first : EntityHandle,
+
+	/// Where the joint is on the first entity. This is in the first entity's local space.
+	///
+	/// Defaults to origin.
+	pub first_anchor : Vec3,
+
+	/// The second entity. Must not be the same as `first`.
+	pub second : EntityHandle,
+
+	/// Where the joint is on the second entity. This is in the second entity's local space.
+	///
+	/// Defaults to origin.
+	pub second_anchor : Vec3,
+
+	/// How much the joint can stretch per unit of force (i.e. the inverse of stiffness). Zero is perfectly rigid. Must not be negative.
+	///
+	/// Defaults to zero.
+	pub compliance : f32,
+
+	/// How much impulse can go through the joint in a single step before it breaks. Once more does, the joint is removed and a [crate::BreakEvent] is reported. Must not be negative.
+	///
+	/// Defaults to infinity (so it never breaks).
+	pub break_impulse : f32,
+}
+
+impl BallJoint {
+	/// Creates a rigid joint between the two entities' origins.
+	pub fn new(first : EntityHandle, second : EntityHandle) -> BallJoint {
+		BallJoint {
+			first,
+			first_anchor: Vec3::zeros(),
+			second,
+			second_anchor: Vec3::zeros(),
+			compliance: 0.0,
+			break_impulse: f32::INFINITY,
+		}
+	}
+
+	/// If this is in a valid state.
+	pub fn is_valid(&self) -> bool {
+		self.first != self.second && 0.0 <= self.compliance && 0.0 <= self.break_impulse
+	}
+}
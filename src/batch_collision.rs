@@ -0,0 +1,350 @@
+//! Batched versions of the simplest collision checks.
+//!
+//! Everything is stored lane-by-lane (i.e. one array per coordinate, rather than one Vec3 per sphere) and the quick checks run on all of the lanes at once as SIMD instructions (using the `wide` crate, which picks the right instructions for the platform, or falls back to plain math).
+//!
+//! The full checks ([collide_sphere_with_spheres] and [collide_plane_with_spheres]) find the exact same hits as [crate::collision::collide_sphere_with_sphere] and [crate::collision::collide_sphere_with_plane] for every lane. Most pairs miss, so they quickly rule out a whole batch at a time, and only go through the full (scalar) math for the lanes that could hit. Where (and which way) things hit is only worked out for the lanes that are actually needed.
+
+use wide::{f32x4, CmpLe, CmpLt};
+
+use crate::consts::EPSILON;
+use crate::types::Vec3;
+use crate::range::Range;
+use crate::collision::{Collision, sphere_plane_overlap_time};
+
+/// How many spheres are checked at once.
+pub const LANES : usize = 4;
+
+/// A batch of moving spheres.
+#[derive(Debug, Clone, Copy)]
+pub struct SphereLanes {
+	/// The X, Y, and Z coordinates of each sphere's center at the start.
+	pub center : [[f32; LANES]; 3],
+	/// The X, Y, and Z components of how far each sphere's center moves.
+	pub movement : [[f32; LANES]; 3],
+	/// The radius of each sphere. Negative infinity for unused lanes (so they never touch anything), and infinite for things that can't be bounded.
+	pub radius : [f32; LANES],
+}
+
+impl SphereLanes {
+	/// Creates a batch where every lane is unused.
+	pub fn new() -> SphereLanes {
+		SphereLanes {
+			center: [[0.0; LANES]; 3],
+			movement: [[0.0; LANES]; 3],
+			radius: [f32::NEG_INFINITY; LANES],
+		}
+	}
+
+	/// Sets one of the lanes.
+	pub fn set(&mut self, lane : usize, center : &Vec3, movement : &Vec3, radius : f32) {
+		for axis in 0..3 {
+			self.center[axis][lane] = center[axis];
+			self.movement[axis][lane] = movement[axis];
+		}
+		self.radius[lane] = radius;
+	}
+
+	/// Gets where a lane's center is at the given time (as a fraction of its movement).
+	fn center_at(&self, lane : usize, time : f32) -> Vec3 {
+		Vec3::new(self.center[0][lane], self.center[1][lane], self.center[2][lane]) + Vec3::new(self.movement[0][lane], self.movement[1][lane], self.movement[2][lane]).scale(time)
+	}
+
+	/// Packs a list of moving spheres (as center, movement, and radius) into as many batches as needed, replacing whatever was in `batches` (but reusing its memory).
+	pub fn pack_into(spheres : &[(Vec3, Vec3, f32)], batches : &mut Vec<SphereLanes>) {
+		batches.clear();
+		batches.extend(spheres.chunks(LANES).map(|chunk| {
+			let mut lanes = SphereLanes::new();
+			for (lane, (center, movement, radius)) in chunk.iter().enumerate() {
+				lanes.set(lane, center, movement, *radius);
+			}
+			lanes
+		}));
+	}
+}
+
+impl Default for SphereLanes {
+	fn default() -> SphereLanes { SphereLanes::new() }
+}
+
+/// Checks whether a moving sphere touches each sphere in a batch at any point during their (linear) movement.
+///
+/// This only says whether they touch, not when or where; it's meant for quickly ruling out pairs before doing the full check.
+pub fn spheres_touch(center : &Vec3, movement : &Vec3, radius : f32, others : &SphereLanes) -> [bool; LANES] {
+	// Work relative to the other sphere, so only this one is moving.
+	let offset_x = f32x4::splat(center.x) - f32x4::new(others.center[0]);
+	let offset_y = f32x4::splat(center.y) - f32x4::new(others.center[1]);
+	let offset_z = f32x4::splat(center.z) - f32x4::new(others.center[2]);
+	let delta_x = f32x4::splat(movement.x) - f32x4::new(others.movement[0]);
+	let delta_y = f32x4::splat(movement.y) - f32x4::new(others.movement[1]);
+	let delta_z = f32x4::splat(movement.z) - f32x4::new(others.movement[2]);
+	// Find the time (clamped to the movement) when they're closest.
+	let along = offset_x * delta_x + offset_y * delta_y + offset_z * delta_z;
+	let length_squared = delta_x * delta_x + delta_y * delta_y + delta_z * delta_z;
+	let zero = f32x4::ZERO;
+	let time = zero.cmp_lt(length_squared).blend((zero - along / length_squared).max(zero).min(f32x4::ONE), zero);
+	let closest_x = offset_x + delta_x * time;
+	let closest_y = offset_y + delta_y * time;
+	let closest_z = offset_z + delta_z * time;
+	let reach = f32x4::splat(radius) + f32x4::new(others.radius);
+	let touching = (zero.cmp_le(reach) & (closest_x * closest_x + closest_y * closest_y + closest_z * closest_z).cmp_le(reach * reach)).move_mask();
+	std::array::from_fn(|lane| touching & (1 << lane) != 0)
+}
+
+/// When each lane of a batched check hits, as the start and end of the range of times (within the movement). Lanes that never hit have a start after their end.
+#[derive(Debug, Clone, Copy)]
+pub struct HitTimes {
+	/// When each lane starts touching.
+	pub start : [f32; LANES],
+	/// When each lane stops touching.
+	pub end : [f32; LANES],
+}
+
+impl HitTimes {
+	/// Creates an instance where no lane hits anything.
+	pub fn new() -> HitTimes {
+		HitTimes { start: [f32::INFINITY; LANES], end: [f32::NEG_INFINITY; LANES] }
+	}
+
+	/// Whether a lane hits anything.
+	pub fn hits(&self, lane : usize) -> bool {
+		self.start[lane] <= self.end[lane]
+	}
+
+	/// Gets a lane's range of times.
+	fn times(&self, lane : usize) -> Range {
+		Range::range(self.start[lane], self.end[lane])
+	}
+
+	/// Sets a lane's range of times. An empty range means it doesn't hit anything.
+	fn set(&mut self, lane : usize, times : &Range) {
+		if times.is_empty() { return; }
+		self.start[lane] = times.min();
+		self.end[lane] = times.max();
+	}
+}
+
+/// How much leeway the quick checks give, relative to the size of the numbers involved. The quick checks must never rule out anything the full checks would hit, even though they don't round the exact same way.
+const LEEWAY : f32 = 1e-4;
+
+/// Works out when a moving sphere hits each sphere in a batch (if ever). The same times as calling [crate::collision::collide_sphere_with_sphere] on each lane, with the batch as the second sphere. See [sphere_with_spheres_collision] for the rest of the hit.
+///
+/// All of the lanes are first quickly checked (at once) for whether they could possibly hit, without any branches or divisions. Only the lanes that pass go through the full math.
+///
+/// Unused lanes (i.e. with a negative radius) never hit anything.
+pub fn collide_sphere_with_spheres(radius : f32, center : &Vec3, movement : &Vec3, others : &SphereLanes) -> HitTimes {
+	let delta_x = f32x4::splat(movement.x) - f32x4::new(others.movement[0]);
+	let delta_y = f32x4::splat(movement.y) - f32x4::new(others.movement[1]);
+	let delta_z = f32x4::splat(movement.z) - f32x4::new(others.movement[2]);
+	let offset_x = f32x4::splat(center.x) - f32x4::new(others.center[0]);
+	let offset_y = f32x4::splat(center.y) - f32x4::new(others.center[1]);
+	let offset_z = f32x4::splat(center.z) - f32x4::new(others.center[2]);
+	let other_radius = f32x4::new(others.radius);
+	let reach = f32x4::splat(radius) + other_radius;
+	let (zero, two, epsilon, leeway) = (f32x4::ZERO, f32x4::splat(2.0), f32x4::splat(EPSILON), f32x4::splat(LEEWAY));
+	// They touch whenever `a * t^2 + b * t + c` is at or below zero (see Range::quadratic_zeros()).
+	let a = delta_x * delta_x + delta_y * delta_y + delta_z * delta_z;
+	let b = two * (delta_x * offset_x + delta_y * offset_y + delta_z * offset_z);
+	let offset_squared = offset_x * offset_x + offset_y * offset_y + offset_z * offset_z;
+	let c = offset_squared - reach * reach;
+	// Barely moving relative to each other: it's (nearly) linear, so can only touch at or before where the line crosses zero.
+	let linear = (c - leeway * c).cmp_le(b.abs() + epsilon);
+	// Otherwise it needs to cross zero at all (with the same tolerance as the full check), and not only before the start or after the end.
+	let det = b * b - f32x4::splat(4.0) * a * c;
+	let crosses = zero.cmp_le(det + epsilon + leeway * (b * b + f32x4::splat(4.0) * (a * c).abs()));
+	let before = (leeway * (offset_squared + reach * reach)).cmp_lt(c) & zero.cmp_lt(b);
+	let after = (leeway * (a + b.abs() + c.abs()) + epsilon).cmp_lt(a + b + c) & (two * a + leeway * (a + b.abs()) + b).cmp_lt(zero);
+	let maybe = zero.cmp_le(other_radius) & a.cmp_lt(epsilon).blend(linear, crosses & !before & !after);
+	let maybe = maybe.move_mask();
+	let mut hits = HitTimes::new();
+	if maybe == 0 { return hits; }
+	for lane in (0..LANES).filter(|lane| maybe & (1 << lane) != 0) {
+		// The exact same math as collide_sphere_with_sphere().
+		let other_center = Vec3::new(others.center[0][lane], others.center[1][lane], others.center[2][lane]);
+		let other_movement = Vec3::new(others.movement[0][lane], others.movement[1][lane], others.movement[2][lane]);
+		let dv = movement - other_movement;
+		let dc = center - other_center;
+		let reach = radius + others.radius[lane];
+		let times = Range::quadratic_zeros(
+			dv.dot(&dv),
+			2.0 * dv.dot(&dc),
+			dc.dot(&dc) - reach * reach,
+		).intersect(&Range::range(0.0, 1.0));
+		hits.set(lane, &times);
+	}
+	hits
+}
+
+/// Gets the full hit for one lane of [collide_sphere_with_spheres] (which must be passed the same spheres). The same as [crate::collision::collide_sphere_with_sphere] would give.
+pub fn sphere_with_spheres_collision(radius : f32, center : &Vec3, movement : &Vec3, others : &SphereLanes, hits : &HitTimes, lane : usize) -> Option<Collision> {
+	if !hits.hits(lane) { return None; }
+	let time = hits.start[lane];
+	let other_radius = others.radius[lane];
+	let here = center + movement.scale(time);
+	let there = others.center_at(lane, time);
+	let position = (here * other_radius + there * radius).scale(1.0 / (radius + other_radius));
+	Some(Collision {
+		times: hits.times(lane),
+		position,
+		normal: (position - here).normalize(),
+		features: [None, None],
+	})
+}
+
+/// Works out when a moving (infinite) plane hits each sphere in a batch (if ever). The same times as calling [crate::collision::collide_sphere_with_plane] on each lane. See [plane_with_spheres_collision] for the rest of the hit.
+///
+/// Like [collide_sphere_with_spheres], all of the lanes are quickly checked first, and only the ones that could hit go through the full math.
+///
+/// Unused lanes (i.e. with a negative radius) never hit anything.
+pub fn collide_plane_with_spheres(position : &Vec3, normal : &Vec3, movement : &Vec3, spheres : &SphereLanes) -> HitTimes {
+	let plane_value = f32x4::splat(position.dot(normal));
+	let (normal_x, normal_y, normal_z) = (f32x4::splat(normal.x), f32x4::splat(normal.y), f32x4::splat(normal.z));
+	let radius = f32x4::new(spheres.radius);
+	let center = f32x4::new(spheres.center[0]) * normal_x + f32x4::new(spheres.center[1]) * normal_y + f32x4::new(spheres.center[2]) * normal_z;
+	let speed = f32x4::splat(movement.dot(normal)) - (f32x4::new(spheres.movement[0]) * normal_x + f32x4::new(spheres.movement[1]) * normal_y + f32x4::new(spheres.movement[2]) * normal_z);
+	// The sphere's nearest point (along the normal) needs to get behind the plane before the end.
+	let nearest = center - radius * f32x4::splat(normal.dot(normal));
+	let gap = nearest - plane_value;
+	let zero = f32x4::ZERO;
+	let maybe = zero.cmp_le(radius) & gap.cmp_le(speed.max(zero) + f32x4::splat(LEEWAY) * (nearest.abs() + plane_value.abs() + speed.abs()) + f32x4::splat(EPSILON));
+	let maybe = maybe.move_mask();
+	let mut hits = HitTimes::new();
+	if maybe == 0 { return hits; }
+	for lane in (0..LANES).filter(|lane| maybe & (1 << lane) != 0) {
+		// The exact same math as collide_sphere_with_plane().
+		let center = Vec3::new(spheres.center[0][lane], spheres.center[1][lane], spheres.center[2][lane]);
+		let sphere_movement = Vec3::new(spheres.movement[0][lane], spheres.movement[1][lane], spheres.movement[2][lane]);
+		let times = sphere_plane_overlap_time(spheres.radius[lane], &center, &sphere_movement, position, normal, movement, true).intersect(&Range::range(0.0, 1.0));
+		hits.set(lane, &times);
+	}
+	hits
+}
+
+/// Gets the full hit for one lane of [collide_plane_with_spheres] (which must be passed the same plane and spheres). The same as [crate::collision::collide_sphere_with_plane] would give, except the normal points off of the plane (as it's first).
+pub fn plane_with_spheres_collision(position : &Vec3, normal : &Vec3, movement : &Vec3, spheres : &SphereLanes, hits : &HitTimes, lane : usize) -> Option<Collision> {
+	if !hits.hits(lane) { return None; }
+	let time = hits.start[lane];
+	// The contact is the point on the plane right under the sphere's center when they hit.
+	let center = spheres.center_at(lane, time);
+	let plane_position = position + movement.scale(time);
+	Some(Collision {
+		times: hits.times(lane),
+		position: center - normal.scale((center - plane_position).dot(normal)),
+		normal: *normal,
+		features: [None, None],
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use proptest::prelude::*;
+	use crate::collision::{collide_sphere_with_sphere, collide_sphere_with_plane};
+
+	/// How far off the batched hits are allowed to be from the full checks' hits.
+	const TOLERANCE : f32 = 0.0001;
+
+	/// Makes a random vector.
+	fn vector(size : f32) -> impl Strategy<Value = Vec3> {
+		(-size..size, -size..size, -size..size).prop_map(|(x, y, z)| Vec3::new(x, y, z))
+	}
+
+	/// Makes a random moving sphere (as center, movement, and radius). Some of them don't move at all.
+	fn moving_sphere() -> impl Strategy<Value = (Vec3, Vec3, f32)> {
+		(vector(5.0), prop_oneof![Just(Vec3::zeros()), vector(5.0)], 0.1f32..2.0)
+	}
+
+	/// Checks that the batched hit matches the full check's hit.
+	fn assert_same(batched : &Option<Collision>, full : &Option<Collision>) -> Result<(), TestCaseError> {
+		prop_assert_eq!(full.is_some(), batched.is_some(), "{:?} vs {:?}", batched, full);
+		if let (Some(batched), Some(full)) = (batched, full) {
+			prop_assert!((batched.times.min() - full.times.min()).abs() < TOLERANCE, "{:?} vs {:?}", batched, full);
+			prop_assert!((batched.times.max() - full.times.max()).abs() < TOLERANCE, "{:?} vs {:?}", batched, full);
+			prop_assert!((batched.position - full.position).magnitude() < TOLERANCE, "{:?} vs {:?}", batched, full);
+			prop_assert!((batched.normal - full.normal).magnitude() < TOLERANCE, "{:?} vs {:?}", batched, full);
+		}
+		Ok(())
+	}
+
+	proptest! {
+		/// Verify batched sphere-sphere checks find the same hits as the full check.
+		#[test]
+		fn spheres_match_full_check((center, movement, radius) in moving_sphere(), others in prop::collection::vec(moving_sphere(), 1..=LANES)) {
+			let mut batches = Vec::new();
+			SphereLanes::pack_into(&others, &mut batches);
+			let hits = collide_sphere_with_spheres(radius, &center, &movement, &batches[0]);
+			for (lane, (other_center, other_movement, other_radius)) in others.iter().enumerate() {
+				let batched = sphere_with_spheres_collision(radius, &center, &movement, &batches[0], &hits, lane);
+				assert_same(&batched, &collide_sphere_with_sphere(radius, &center, &movement, *other_radius, other_center, other_movement))?;
+			}
+			// Unused lanes never hit anything.
+			prop_assert!((others.len()..LANES).all(|lane| !hits.hits(lane)));
+		}
+
+		/// Verify batched plane-sphere checks find the same hits as the full check (other than the normal being flipped).
+		#[test]
+		fn planes_match_full_check(position in vector(2.0), normal in vector(1.0), movement in prop_oneof![Just(Vec3::zeros()), vector(2.0)], spheres in prop::collection::vec(moving_sphere(), 1..=LANES)) {
+			prop_assume!(0.1 < normal.magnitude());
+			let normal = normal.normalize();
+			let mut batches = Vec::new();
+			SphereLanes::pack_into(&spheres, &mut batches);
+			let hits = collide_plane_with_spheres(&position, &normal, &movement, &batches[0]);
+			for (lane, (center, sphere_movement, radius)) in spheres.iter().enumerate() {
+				let batched = plane_with_spheres_collision(&position, &normal, &movement, &batches[0], &hits, lane);
+				let full = collide_sphere_with_plane(*radius, center, sphere_movement, &position, &normal, &movement).map(|mut hit| { hit.normal *= -1.0; hit });
+				assert_same(&batched, &full)?;
+			}
+			prop_assert!((spheres.len()..LANES).all(|lane| !hits.hits(lane)));
+		}
+	}
+
+	/// Check the batched checks agree with the full checks when things aren't moving relative to each other.
+	#[test]
+	fn not_moving() {
+		let spheres = vec![
+			(Vec3::new(0.0, 0.5, 0.0), Vec3::zeros(), 1.0), // Already overlapping.
+			(Vec3::new(0.0, 3.0, 0.0), Vec3::zeros(), 1.0), // Apart.
+			(Vec3::new(0.0, 2.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 1.0), // Apart, and moving along with the other.
+		];
+		let mut batches = Vec::new();
+		SphereLanes::pack_into(&spheres, &mut batches);
+		let movement = Vec3::new(1.0, 0.0, 0.0);
+		let batched = collide_sphere_with_spheres(0.5, &Vec3::zeros(), &Vec3::zeros(), &batches[0]);
+		let moving = collide_plane_with_spheres(&Vec3::zeros(), &Vec3::new(0.0, 1.0, 0.0), &movement, &batches[0]);
+		for (lane, (center, sphere_movement, radius)) in spheres.iter().enumerate() {
+			let full = collide_sphere_with_sphere(0.5, &Vec3::zeros(), &Vec3::zeros(), *radius, center, sphere_movement);
+			assert_eq!(full.is_some(), batched.hits(lane), "Mismatch for sphere {}", lane);
+			let full = collide_sphere_with_plane(*radius, center, sphere_movement, &Vec3::zeros(), &Vec3::new(0.0, 1.0, 0.0), &movement);
+			assert_eq!(full.is_some(), moving.hits(lane), "Mismatch for sphere {} and the plane", lane);
+		}
+		assert!(batched.hits(0) && !batched.hits(1));
+		assert!(moving.hits(0) && !moving.hits(1) && !moving.hits(2));
+	}
+
+	/// Check that the batched check agrees with the full sphere-sphere check.
+	#[test]
+	fn matches_full_check() {
+		let center = Vec3::new(0.0, 0.0, 0.0);
+		let movement = Vec3::new(2.0, 0.0, 0.0);
+		let others = vec![
+			(Vec3::new( 4.0, 0.0, 0.0), Vec3::new(-1.0, 0.0, 0.0), 0.5), // Meets it head on.
+			(Vec3::new( 1.0, 3.0, 0.0), Vec3::zeros(), 1.0), // Passes by.
+			(Vec3::new( 1.0, 1.5, 0.0), Vec3::zeros(), 1.0), // Grazes it.
+			(Vec3::new(-3.0, 0.0, 0.0), Vec3::zeros(), 1.0), // Moving away from it.
+			(Vec3::new( 3.0, 2.0, 0.0), Vec3::new(0.0, -4.0, 0.0), 0.5), // Crosses its path.
+			(Vec3::new( 0.5, 0.0, 0.0), Vec3::new(2.0, 0.0, 0.0), 0.1), // Already inside it, moving along with it.
+		];
+		let mut batches = vec![SphereLanes::new(); 5];
+		SphereLanes::pack_into(&others, &mut batches);
+		assert_eq!(2, batches.len());
+		for (index, (other_center, other_movement, other_radius)) in others.iter().enumerate() {
+			let batched = spheres_touch(&center, &movement, 1.0, &batches[index / LANES])[index % LANES];
+			let full = collide_sphere_with_sphere(1.0, &center, &movement, *other_radius, other_center, other_movement);
+			assert_eq!(full.is_some(), batched, "Mismatch for sphere {}", index);
+		}
+		// Unused lanes never touch, and unbounded ones always do.
+		assert!(!spheres_touch(&center, &movement, 1.0, &batches[1])[3]);
+		let mut unbounded = SphereLanes::new();
+		unbounded.set(0, &Vec3::new(100.0, 0.0, 0.0), &Vec3::zeros(), f32::INFINITY);
+		assert!(spheres_touch(&center, &movement, 1.0, &unbounded)[0]);
+	}
+}
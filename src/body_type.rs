@@ -0,0 +1,11 @@
+/// How an entity takes part in the simulation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BodyType {
+	/// Moved around by forces and collisions (according to its mass). This is what most entities are.
+	Dynamic,
+	/// Never moves. Its velocities are ignored (and cleared), and nothing that hits it can push it.
+	Static,
+	/// Moves at whatever velocities it's given, but forces and collisions never change them. So it pushes dynamic entities around without being pushed back.
+	Kinematic,
+}
@@ -0,0 +1,26 @@
+use crate::types::{EntityHandle, ColliderHandle, JointHandle};
+
+/// What broke in a [BreakEvent].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Broken {
+	/// A collider was hit harder than its break impulse (see [crate::PhysicsSystem::set_collider_break_impulse]). Nothing is done to the collider itself: it's up to the caller to remove it, split it off (see [crate::PhysicsSystem::split_entity]), or swap it for debris.
+	Collider(ColliderHandle),
+	/// An attached entity was knocked loose from its parent (see [crate::PhysicsSystem::set_attachment_break_impulse]). It's already been detached.
+	Attachment {
+		/// The entity that was attached.
+		child : EntityHandle,
+		/// The entity it was attached to.
+		parent : EntityHandle,
+	},
+	/// A joint was pulled harder than its [crate::BallJoint::break_impulse]. It's already been removed.
+	Joint(JointHandle),
+}
+
+/// Something that broke during a step because too much impulse went through it. See [crate::PhysicsSystem::take_break_events] and [crate::XpbdWorld::take_break_events].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BreakEvent {
+	/// What broke.
+	pub broken : Broken,
+	/// The total impulse that went through it during the step (which was more than its break impulse).
+	pub impulse : f32,
+}
@@ -0,0 +1,203 @@
+use std::cell::Cell;
+use std::cmp::Ordering;
+
+use crate::consts::EPSILON;
+use crate::types::Vec3;
+
+/// The most items a leaf node holds before it's split up.
+const BVH_LEAF_SIZE : usize = 4;
+
+/// How deep a [Bvh] can be walked. Nodes are always split in half, so this is far more than will ever be needed.
+const BVH_MAX_DEPTH : usize = 64;
+
+/// A single box in a [Bvh].
+#[derive(Debug, Clone)]
+struct BvhNode {
+	/// The corner with all of the smaller values.
+	min : Vec3,
+	/// The corner with all of the larger values.
+	max : Vec3,
+	/// For leaves, where their items start in [Bvh::items]. Otherwise the index of the second child (the first is always right after this node).
+	first : usize,
+	/// How many items a leaf has. Zero for everything else.
+	count : usize,
+}
+
+/// A bounding volume hierarchy: a tree of axis-aligned boxes around a list of items (i.e. a mesh's triangles), so only the items near a point, box, or line segment need to be looked at.
+///
+/// Built once up front, so is meant for things that never change (like level geometry).
+#[derive(Debug, Clone, Default)]
+pub struct Bvh {
+	/// The nodes, with each parent before its children.
+	nodes : Vec<BvhNode>,
+	/// The item indices, grouped up by leaf.
+	items : Vec<usize>,
+	/// The bounds of each item (padded a little, so flat items like axis-aligned triangles still have some thickness).
+	bounds : Vec<(Vec3, Vec3)>,
+}
+
+impl Bvh {
+	/// Builds the tree around items with the given bounds (as the min and max corners of each).
+	pub fn new(bounds : &[(Vec3, Vec3)]) -> Bvh {
+		let padding = Vec3::repeat(EPSILON);
+		let mut bvh = Bvh {
+			nodes: Vec::new(),
+			items: (0..bounds.len()).collect(),
+			bounds: bounds.iter().map(|(min, max)| (min - padding, max + padding)).collect(),
+		};
+		if !bounds.is_empty() {
+			let centers : Vec<Vec3> = bounds.iter().map(|(min, max)| 0.5 * (min + max)).collect();
+			bvh.build(&centers, 0, bounds.len());
+		}
+		bvh
+	}
+
+	/// Adds the node around `items[start..end]` (and all of its children), returning its index.
+	fn build(&mut self, centers : &[Vec3], start : usize, end : usize) -> usize {
+		let mut min = Vec3::repeat(f32::INFINITY);
+		let mut max = Vec3::repeat(-f32::INFINITY);
+		let mut center_min = min;
+		let mut center_max = max;
+		for item in &self.items[start..end] {
+			min = min.inf(&self.bounds[*item].0);
+			max = max.sup(&self.bounds[*item].1);
+			center_min = center_min.inf(&centers[*item]);
+			center_max = center_max.sup(&centers[*item]);
+		}
+		let index = self.nodes.len();
+		self.nodes.push(BvhNode { min, max, first: start, count: end - start });
+		if end - start <= BVH_LEAF_SIZE { return index; }
+		// Split in half along whichever axis the items are the most spread out on.
+		let axis = (center_max - center_min).imax();
+		let middle = (start + end) / 2;
+		self.items[start..end].select_nth_unstable_by(middle - start, |first, second| {
+			centers[*first][axis].partial_cmp(&centers[*second][axis]).unwrap_or(Ordering::Equal)
+		});
+		self.build(centers, start, middle);
+		let second = self.build(centers, middle, end);
+		self.nodes[index].first = second;
+		self.nodes[index].count = 0;
+		index
+	}
+
+	/// Walks down the tree (without allocating), only going into boxes (given as their min and max corners) that `enter` accepts, and calls `visit` on every item whose own box it accepts.
+	fn walk<E, V>(&self, mut enter : E, mut visit : V) where E : FnMut(&Vec3, &Vec3) -> bool, V : FnMut(usize) {
+		if self.nodes.is_empty() { return; }
+		let mut stack = [0; BVH_MAX_DEPTH];
+		let mut depth = 1;
+		while 0 < depth {
+			depth -= 1;
+			let mut index = stack[depth];
+			loop {
+				let node = &self.nodes[index];
+				if !enter(&node.min, &node.max) { break; }
+				if 0 < node.count {
+					for item in &self.items[node.first..(node.first + node.count)] {
+						let (min, max) = &self.bounds[*item];
+						if enter(min, max) { visit(*item); }
+					}
+					break;
+				}
+				stack[depth] = node.first;
+				depth += 1;
+				index += 1;
+			}
+		}
+	}
+
+	/// Adds every item whose bounds overlap the given box onto the end of `found`.
+	pub fn query_box(&self, min : &Vec3, max : &Vec3, found : &mut Vec<usize>) {
+		self.walk(
+			|node_min, node_max| (0..3).all(|axis| node_min[axis] <= max[axis] && min[axis] <= node_max[axis]),
+			|item| found.push(item),
+		);
+	}
+
+	/// Adds every item whose bounds the line segment from `start` to `end` passes through onto the end of `found`.
+	pub fn query_segment(&self, start : &Vec3, end : &Vec3, found : &mut Vec<usize>) {
+		let delta = end - start;
+		self.walk(|min, max| {
+			// Clip the segment against each pair of sides in turn, and see if any of it is left.
+			let (mut enter, mut exit) = (0.0f32, 1.0f32);
+			for axis in 0..3 {
+				if delta[axis].abs() < EPSILON {
+					if start[axis] < min[axis] || max[axis] < start[axis] { return false; }
+					continue;
+				}
+				let near = (min[axis] - start[axis]) / delta[axis];
+				let far = (max[axis] - start[axis]) / delta[axis];
+				enter = enter.max(near.min(far));
+				exit = exit.min(near.max(far));
+				if exit < enter { return false; }
+			}
+			true
+		}, |item| found.push(item));
+	}
+
+	/// Finds the item closest to `point`, where `distance_squared` gives the (squared) distance from the point to a single item (which is only called for items whose box is closer than the best so far). Returns the item and its squared distance, or None if there aren't any items.
+	pub fn nearest<F>(&self, point : &Vec3, mut distance_squared : F) -> Option<(usize, f32)> where F : FnMut(usize) -> f32 {
+		// Both closures need to see the best distance so far (to skip any nodes that are further away).
+		let best : Cell<Option<(usize, f32)>> = Cell::new(None);
+		let best_distance = || best.get().map_or(f32::INFINITY, |(_, distance)| distance);
+		self.walk(
+			|min, max| (point - point.sup(min).inf(max)).magnitude_squared() < best_distance(),
+			|item| {
+				let distance = distance_squared(item);
+				if distance < best_distance() {
+					best.set(Some((item, distance)));
+				}
+			},
+		);
+		best.get()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Verify the queries find the same items as checking every item one at a time.
+	#[test]
+	fn check_queries() {
+		// A grid of small boxes.
+		let mut bounds = Vec::new();
+		for x in 0..10 {
+			for z in 0..10 {
+				let corner = Vec3::new(x as f32, 0.0, z as f32);
+				bounds.push((corner, corner + Vec3::new(0.5, 0.5, 0.5)));
+			}
+		}
+		let bvh = Bvh::new(&bounds);
+
+		let (min, max) = (Vec3::new(2.2, -1.0, 3.7), Vec3::new(4.1, 1.0, 5.0));
+		let mut found = Vec::new();
+		bvh.query_box(&min, &max, &mut found);
+		found.sort_unstable();
+		let expected : Vec<usize> = (0..bounds.len()).filter(|index| {
+			let (item_min, item_max) = bounds[*index];
+			(0..3).all(|axis| item_min[axis] <= max[axis] && min[axis] <= item_max[axis])
+		}).collect();
+		assert_eq!(found, expected);
+		assert_eq!(found.len(), 6);
+
+		// A segment along a row only hits that row.
+		found.clear();
+		bvh.query_segment(&Vec3::new(-1.0, 0.25, 3.25), &Vec3::new(20.0, 0.25, 3.25), &mut found);
+		found.sort_unstable();
+		assert_eq!(found, (0..10).map(|x| x * 10 + 3).collect::<Vec<usize>>());
+
+		// And one that stops short only hits the start of it.
+		found.clear();
+		bvh.query_segment(&Vec3::new(-1.0, 0.25, 3.25), &Vec3::new(1.2, 0.25, 3.25), &mut found);
+		found.sort_unstable();
+		assert_eq!(found, vec![3, 13]);
+
+		let nearest = bvh.nearest(&Vec3::new(6.7, 0.2, 8.3), |index| {
+			let (item_min, item_max) = bounds[index];
+			let point = Vec3::new(6.7, 0.2, 8.3);
+			(point - point.sup(&item_min).inf(&item_max)).magnitude_squared()
+		});
+		assert_eq!(nearest.map(|(index, _)| index), Some(68));
+		assert!(Bvh::new(&[]).nearest(&Vec3::zeros(), |_| 0.0).is_none());
+	}
+}
@@ -0,0 +1,213 @@
+use crate::consts::{EPSILON, SURFACE_OFFSET};
+use crate::types::Vec3;
+use crate::physics_world::PhysicsWorld;
+
+/// A rectangular sheet of cloth: a grid of particles held together by distance constraints.
+///
+/// Stored in (and stepped by) [PhysicsSystem](crate::PhysicsSystem). See [PhysicsSystem::add_cloth](crate::PhysicsSystem::add_cloth).
+///
+/// The particles collide with all of the colliders in the system, but don't push back on them (the entities never notice the cloth). The cloth also doesn't collide with itself.
+#[derive(Debug, Clone)]
+pub struct Cloth {
+	/// The number of particles along the first side of the grid.
+	width : usize,
+	/// The number of particles along the second side of the grid.
+	height : usize,
+	/// The current particle positions.
+	positions : Vec<Vec3>,
+	/// The current particle velocities.
+	velocities : Vec<Vec3>,
+	/// The inverse of each particle's mass. Zero means the particle is pinned in place.
+	inverse_masses : Vec<f32>,
+	/// The distance constraints as: first index, second index, and distance to hold them at.
+	constraints : Vec<(usize, usize, f32)>,
+
+	/// The acceleration applied to every particle.
+	///
+	/// Defaults to zero.
+	pub gravity : Vec3,
+
+	/// How many times the distance constraints are applied each step. More means stiffer cloth.
+	///
+	/// Defaults to `4`.
+	pub iterations : u8,
+
+	/// The friction coefficient used when a particle hits a collider.
+	///
+	/// Defaults to `0.5`.
+	pub friction_coefficient : f32,
+
+	/// The fraction of velocity lost every second (to keep the cloth from flapping forever).
+	///
+	/// Defaults to `0.1`.
+	pub damping : f32,
+}
+
+impl Cloth {
+	/// Creates a grid of `width` by `height` particles. The grid starts at `corner`, and goes along `width_direction` and `height_direction` (whose lengths are the spacing between particles).
+	///
+	/// Each particle is held at its starting distance from its direct neighbors and its diagonal neighbors.
+	pub fn new(corner : impl Into<Vec3>, width_direction : impl Into<Vec3>, height_direction : impl Into<Vec3>, width : usize, height : usize, particle_mass : f32) -> Result<Cloth, ()> {
+		let (corner, width_direction, height_direction) = (corner.into(), width_direction.into(), height_direction.into());
+		if width < 2 || height < 2 || width_direction.magnitude() < EPSILON || height_direction.magnitude() < EPSILON || !particle_mass.is_finite() || particle_mass < EPSILON {
+			return Err(()); // TODO: An error type.
+		}
+		let mut positions = Vec::with_capacity(width * height);
+		for y in 0..height {
+			for x in 0..width {
+				positions.push(corner + width_direction * x as f32 + height_direction * y as f32);
+			}
+		}
+		let mut constraints = Vec::new();
+		for y in 0..height {
+			for x in 0..width {
+				let index = x + y * width;
+				let mut others = Vec::new();
+				if x + 1 < width { others.push(index + 1); }
+				if y + 1 < height { others.push(index + width); }
+				if x + 1 < width && y + 1 < height { others.push(index + width + 1); }
+				if 0 < x && y + 1 < height { others.push(index + width - 1); }
+				for other in others {
+					constraints.push((index, other, (positions[other] - positions[index]).magnitude()));
+				}
+			}
+		}
+		Ok(Cloth {
+			width,
+			height,
+			velocities: vec![Vec3::zeros(); positions.len()],
+			inverse_masses: vec![1.0 / particle_mass; positions.len()],
+			positions,
+			constraints,
+			gravity: Vec3::zeros(),
+			iterations: 4,
+			friction_coefficient: 0.5,
+			damping: 0.1,
+		})
+	}
+
+	/// Gets the number of particles along the first side of the grid.
+	pub fn get_width(&self) -> usize { self.width }
+
+	/// Gets the number of particles along the second side of the grid.
+	pub fn get_height(&self) -> usize { self.height }
+
+	/// Gets the index of the particle at the given spot in the grid.
+	pub fn index_of(&self, x : usize, y : usize) -> usize { x + y * self.width }
+
+	/// Gets the current position of every particle. Index these using [Cloth::index_of].
+	///
+	/// This is intended to be used directly as the vertices when rendering.
+	pub fn get_positions(&self) -> &Vec<Vec3> { &self.positions }
+
+	/// Gets the current velocity of every particle.
+	pub fn get_velocities(&self) -> &Vec<Vec3> { &self.velocities }
+
+	/// Gets the triangles (as indices into [Cloth::get_positions]) that make up the cloth's surface. Two per grid square.
+	pub fn get_triangles(&self) -> Vec<[usize; 3]> {
+		let mut triangles = Vec::with_capacity(2 * (self.width - 1) * (self.height - 1));
+		for y in 0..(self.height - 1) {
+			for x in 0..(self.width - 1) {
+				let index = self.index_of(x, y);
+				triangles.push([index, index + 1, index + self.width + 1]);
+				triangles.push([index, index + self.width + 1, index + self.width]);
+			}
+		}
+		triangles
+	}
+
+	/// Pins (or unpins) a particle so it doesn't move at all.
+	pub fn set_pinned(&mut self, index : usize, pinned : bool, particle_mass : f32) -> Result<(), ()> {
+		if self.positions.len() <= index || (!pinned && (!particle_mass.is_finite() || particle_mass < EPSILON)) {
+			return Err(()); // TODO: An error type.
+		}
+		self.inverse_masses[index] = if pinned { 0.0 } else { 1.0 / particle_mass };
+		self.velocities[index] = Vec3::zeros();
+		Ok(())
+	}
+
+	/// Moves a particle to a new position (i.e. to drag a pinned corner around).
+	pub fn set_position(&mut self, index : usize, position : impl Into<Vec3>) -> Result<(), ()> {
+		if self.positions.len() <= index {
+			return Err(()); // TODO: An error type.
+		}
+		self.positions[index] = position.into();
+		Ok(())
+	}
+
+	/// Moves the cloth forward by `dt`, colliding it with the colliders in `system`.
+	pub fn step(&mut self, system : &dyn PhysicsWorld, dt : f32) {
+		// First guess where each particle will go.
+		let damping = (1.0 - self.damping * dt).max(0.0);
+		let mut predicted = self.positions.clone();
+		for index in 0..self.positions.len() {
+			if 0.0 == self.inverse_masses[index] { continue; }
+			self.velocities[index] = (self.velocities[index] + self.gravity * dt) * damping;
+			predicted[index] += self.velocities[index] * dt;
+		}
+		// Then pull the guesses back together.
+		for _ in 0..self.iterations {
+			for (first, second, distance) in &self.constraints {
+				let total_inverse_mass = self.inverse_masses[*first] + self.inverse_masses[*second];
+				if total_inverse_mass < EPSILON { continue; }
+				let delta = predicted[*second] - predicted[*first];
+				let length = delta.magnitude();
+				if length < EPSILON { continue; }
+				let correction = delta * ((length - distance) / (length * total_inverse_mass));
+				predicted[*first] += correction * self.inverse_masses[*first];
+				predicted[*second] -= correction * self.inverse_masses[*second];
+			}
+		}
+		// Then stop the particles at the first thing they hit.
+		for index in 0..self.positions.len() {
+			if 0.0 == self.inverse_masses[index] { continue; }
+			let start = self.positions[index];
+			let movement = predicted[index] - start;
+			let distance = movement.magnitude();
+			if EPSILON < distance {
+				if let Some(hit) = system.cast_ray(&start, &movement, distance, None) {
+					let surface_position = hit.position + hit.normal * SURFACE_OFFSET;
+					// Friction removes sliding based on how far the particle would've gone into the surface.
+					let depth = (surface_position - predicted[index]).dot(&hit.normal).max(0.0);
+					let sliding = (predicted[index] - start) - hit.normal * (predicted[index] - start).dot(&hit.normal);
+					let sliding_length = sliding.magnitude();
+					let kept = if EPSILON < sliding_length { (1.0 - self.friction_coefficient * depth / sliding_length).max(0.0) } else { 0.0 };
+					predicted[index] = surface_position + sliding * kept;
+				}
+			}
+			self.velocities[index] = (predicted[index] - start) / dt;
+			self.positions[index] = predicted[index];
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::physics_system::PhysicsSystem;
+
+	/// Check a cloth hung from two corners keeps its shape and stays pinned.
+	#[test]
+	fn hanging() {
+		let mut cloth = Cloth::new(Vec3::zeros(), Vec3::new(0.25, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.25), 5, 5, 0.1).unwrap();
+		assert_eq!(32, cloth.get_triangles().len());
+		cloth.gravity = Vec3::new(0.0, -1.0, 0.0);
+		cloth.iterations = 20;
+		cloth.damping = 2.0; // Settle down quickly.
+		cloth.set_pinned(cloth.index_of(0, 0), true, 0.0).unwrap();
+		cloth.set_pinned(cloth.index_of(4, 0), true, 0.0).unwrap();
+		let system = PhysicsSystem::new();
+		for _ in 0..500 {
+			cloth.step(&system, 0.01);
+		}
+		let positions = cloth.get_positions();
+		assert!(positions[cloth.index_of(0, 0)].magnitude() < EPSILON);
+		assert!((positions[cloth.index_of(4, 0)] - Vec3::new(1.0, 0.0, 0.0)).magnitude() < EPSILON);
+		// The far edge is hanging about 1.0 below the pinned edge.
+		assert!((positions[cloth.index_of(2, 4)].y + 1.0).abs() < 0.1);
+		// And the neighboring particles haven't stretched apart.
+		for y in 0..4 {
+			assert!(((positions[cloth.index_of(2, y + 1)] - positions[cloth.index_of(2, y)]).magnitude() - 0.25).abs() < 0.02);
+		}
+	}
+}
@@ -2,10 +2,11 @@ use core::fmt::Debug;
 
 use downcast_rs::{Downcast, impl_downcast};
 
-use crate::types::{Vec3, Mat3, EntityHandle};
+use crate::types::{Vec3, Mat3, EntityHandle, MaterialHandle};
+use crate::anisotropic_friction::AnisotropicFriction;
 
 /// A way to quickly determine collider type.
-#[derive(PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum ColliderType {
 	/// For the [crate::NullCollider].
 	NULL,
@@ -17,23 +18,40 @@ pub enum ColliderType {
 	MESH,
 	/// For the [crate::AlignedBox].
 	ALIGNED_BOX,
+	/// For the [crate::TriMeshCollider].
+	TRI_MESH,
 }
 
 /// The internal representation of an arbitrary collider.
 /// This generally will have NO data hiding to keep things simple.
-pub trait InternalCollider : Downcast + Debug {
+pub trait InternalCollider : Downcast + Debug + Send + Sync {
 	/// The specific type.
 	fn get_type(&self) -> ColliderType;
 
+	/// Makes a boxed copy (so whole systems can be cloned).
+	fn clone_boxed(&self) -> Box<dyn InternalCollider>;
+
 	/// Sets the entity this is attached to.
 	fn set_entity(&mut self, handle : Option<EntityHandle>) -> Option<EntityHandle>;
 
 	/// Retrieves the stored entity handle that this is attached to.
 	fn get_entity(&mut self) -> Option<EntityHandle>;
 
+	/// Sets if this takes part in collisions and queries (see [crate::PhysicsSystem::set_collider_enabled]).
+	fn set_enabled(&mut self, enabled : bool);
+
+	/// Gets if this takes part in collisions and queries.
+	fn is_enabled(&self) -> bool;
+
 	/// Gets the center of mass for this collider in it's owning entity's local space.
 	fn get_local_center_of_mass(&self) -> Vec3;
 
+	/// Gets where this is placed in its owning entity's local space (i.e. a sphere's center).
+	fn get_local_position(&self) -> Vec3;
+
+	/// Moves this to a new place in its owning entity's local space. The owning entity's mass needs to be recalculated afterwards.
+	fn set_local_position(&mut self, position : &Vec3);
+
 	/// Gets the mass of this collider. Must not be negative.
 	fn get_mass(&self) -> f32;
 
@@ -53,10 +71,32 @@ pub trait InternalCollider : Downcast + Debug {
 
 	/// Gets the dynamic friction coefficient.
 	fn get_dynamic_friction_coefficient(&self) -> f32;
+
+	/// Gets how friction should change with the sliding direction (if at all).
+	fn get_anisotropic_friction(&self) -> Option<AnisotropicFriction>;
+
+	/// Gets the extra thickness around the surface used for collision detection.
+	fn get_margin(&self) -> f32;
+
+	/// Gets what this is made of.
+	fn get_material(&self) -> MaterialHandle;
+
+	/// Gets if this is only seen by queries: it never collides with anything nor adds any mass to its entity.
+	fn is_query_only(&self) -> bool;
+
+	/// Gets the tag copied into collision records.
+	fn get_surface_tag(&self) -> u32;
 }
 
 impl dyn InternalCollider {
-	// Nothing for now.
+	/// If this can collide with things: it's enabled and not query-only.
+	pub fn collides(&self) -> bool {
+		self.is_enabled() && !self.is_query_only()
+	}
+}
+
+impl Clone for Box<dyn InternalCollider> {
+	fn clone(&self) -> Self { self.clone_boxed() }
 }
 
 impl_downcast!(InternalCollider);
@@ -1,16 +1,113 @@
-use crate::null_collider::NullCollider;
-use crate::sphere_collider::SphereCollider;
-use crate::plane_collider::PlaneCollider;
-use crate::mesh_collider::MeshCollider;
-use crate::aligned_box_collider::AlignedBoxCollider;
+use nalgebra::Point3;
+
+use crate::consts::EPSILON;
+use crate::types::{Vec3, Isometry};
+use crate::collider::InternalCollider;
+use crate::anisotropic_friction::AnisotropicFriction;
+use crate::null_collider::{NullCollider, InternalNullCollider};
+use crate::sphere_collider::{SphereCollider, InternalSphereCollider};
+use crate::plane_collider::{PlaneCollider, InternalPlaneCollider};
+use crate::mesh_collider::{MeshCollider, InternalMeshCollider};
+use crate::aligned_box_collider::{AlignedBoxCollider, InternalAlignedBoxCollider};
+use crate::tri_mesh_collider::{TriMeshCollider, InternalTriMeshCollider};
 
 /// How [crate::Collider] generics are passed into [crate::PhysicsSystem].
 ///
 /// As it turns out, an enum is easier to work with than a `Box<dyn ...>`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ColliderWrapper {
 	Null(NullCollider),
 	Sphere(SphereCollider),
 	Plane(PlaneCollider),
 	Mesh(MeshCollider),
 	AlignedBox(AlignedBoxCollider),
+	TriMesh(TriMeshCollider),
+}
+
+impl ColliderWrapper {
+	/// Makes the internal representation of the wrapped collider. Fails if it isn't valid.
+	pub(crate) fn make_internal(&self) -> Result<Box<dyn InternalCollider>, ()> {
+		match self {
+			ColliderWrapper::Null(source) => InternalNullCollider::new_from(source),
+			ColliderWrapper::Sphere(source) => InternalSphereCollider::new_from(source),
+			ColliderWrapper::Plane(source) => InternalPlaneCollider::new_from(source),
+			ColliderWrapper::Mesh(source) => InternalMeshCollider::new_from(source),
+			ColliderWrapper::AlignedBox(source) => InternalAlignedBoxCollider::new_from(source),
+			ColliderWrapper::TriMesh(source) => InternalTriMeshCollider::new_from(source),
+		}
+	}
+
+	/// Whether the wrapped collider is valid, i.e. whether [ColliderWrapper::make_internal] (or updating a collider to it) would succeed.
+	pub(crate) fn is_valid(&self) -> bool {
+		match self {
+			ColliderWrapper::Null(source) => source.is_valid(),
+			ColliderWrapper::Sphere(source) => source.is_valid(),
+			ColliderWrapper::Plane(source) => source.is_valid(),
+			ColliderWrapper::Mesh(source) => source.is_valid(),
+			ColliderWrapper::AlignedBox(source) => source.is_valid(),
+			ColliderWrapper::TriMesh(source) => source.is_valid(),
+		}
+	}
+
+	/// Makes a copy that's been moved (and rotated) into another local space, i.e. so it can be linked to a different entity without moving in world space.
+	///
+	/// Fails for aligned boxes that would need to be rotated, as they always line up with their entity's axes.
+	pub(crate) fn transformed(&self, transform : &Isometry) -> Result<ColliderWrapper, ()> {
+		let move_point = |point : &Vec3| transform.transform_point(&Point3::from(*point)).coords;
+		let rotated = EPSILON < transform.rotation.angle();
+		let rotate_friction = |friction : &Option<AnisotropicFriction>| friction.map(|friction| AnisotropicFriction {
+			direction: transform.rotation * friction.direction,
+			..friction
+		});
+		Ok(match self {
+			ColliderWrapper::Null(source) => {
+				let mut copy = source.clone();
+				copy.position = move_point(&source.position);
+				ColliderWrapper::Null(copy)
+			},
+			ColliderWrapper::Sphere(source) => {
+				let mut copy = source.clone();
+				copy.center = move_point(&source.center);
+				copy.anisotropic_friction = rotate_friction(&source.anisotropic_friction);
+				ColliderWrapper::Sphere(copy)
+			},
+			ColliderWrapper::Plane(source) => {
+				let mut copy = source.clone();
+				copy.position = move_point(&source.position);
+				copy.normal = transform.rotation * source.normal;
+				copy.anisotropic_friction = rotate_friction(&source.anisotropic_friction);
+				ColliderWrapper::Plane(copy)
+			},
+			ColliderWrapper::Mesh(source) => {
+				let mut copy = source.clone();
+				copy.position = move_point(&source.position);
+				copy.anisotropic_friction = rotate_friction(&source.anisotropic_friction);
+				if rotated {
+					copy.clear();
+					for face in source.faces() {
+						copy.add_face(&face.iter().map(|corner| transform.rotation * corner).collect());
+					}
+				}
+				ColliderWrapper::Mesh(copy)
+			},
+			ColliderWrapper::AlignedBox(source) => {
+				if rotated {
+					return Err(()); // TODO: An error type.
+				}
+				let mut copy = source.clone();
+				copy.position = move_point(&source.position);
+				ColliderWrapper::AlignedBox(copy)
+			},
+			ColliderWrapper::TriMesh(source) => {
+				let mut copy = source.clone();
+				copy.position = move_point(&source.position);
+				copy.anisotropic_friction = rotate_friction(&source.anisotropic_friction);
+				if rotated {
+					copy.rotate_vertices(&transform.rotation);
+				}
+				ColliderWrapper::TriMesh(copy)
+			},
+		})
+	}
 }
@@ -1,14 +1,33 @@
 use std::f32::INFINITY;
 
 use crate::consts::EPSILON;
-use crate::types::{Vec3};
+use crate::types::{Vec3, ColliderHandle};
 use crate::range::Range;
+use crate::interval_set::IntervalSet;
 use crate::collider::{ColliderType, InternalCollider};
 use crate::sphere_collider::{InternalSphereCollider};
 use crate::plane_collider::{InternalPlaneCollider};
-use crate::mesh_collider::{InternalMeshCollider};
+use crate::mesh_collider::{InternalMeshCollider, MeshAdjacency, face_normal};
+use crate::mesh_feature::MeshFeature;
 use crate::aligned_box_collider::{InternalAlignedBoxCollider};
+use crate::tri_mesh_collider::{InternalTriMeshCollider};
 use crate::orientation::{Orientation};
+use crate::convex_shape::{ConvexShape, PlacedShape, as_convex_shape, closest_points, penetration, tolerance};
+
+/// The most steps [collide_convex_with_convex] will take before giving up.
+const CONSERVATIVE_ADVANCEMENT_ITERATIONS : usize = 64;
+
+/// How close (in distance) mesh vertices must be to the closest one for [collide_mesh_with_plane] to treat them as hitting the plane together.
+const MESH_PLANE_TOLERANCE : f32 = 0.0001;
+
+/// How close (as a fraction of the movement) hits in [collide_mesh_with_mesh] must be to the earliest one to be treated as happening together.
+const MESH_CLUSTER_TIME_TOLERANCE : f32 = 0.0001;
+
+/// How closely (as the dot product) hits' normals in [collide_mesh_with_mesh] must match the earliest one's to be treated as the same contact.
+const MESH_CLUSTER_NORMAL_TOLERANCE : f32 = 0.999;
+
+/// How closely (as the dot product) neighboring mesh faces' normals must match for the edges and corners between them to be treated as flat. See [crate::MeshCollider::smooth_normals].
+const MESH_FLAT_TOLERANCE : f32 = 0.9999;
 
 /// A structure for storing collision information.
 #[derive(Debug)]
@@ -19,9 +38,23 @@ pub struct Collision {
 	pub position : Vec3,
 	/// The normal of the hit (pointing off the first object).
 	pub normal : Vec3,
+	/// Which part of each object was hit, for any that are meshes (and where it's known).
+	pub features : [Option<MeshFeature>; 2],
 }
 
 impl Collision {
+	/// Swaps which object is first, flipping the normal around to match.
+	pub fn swap_sides(&mut self) {
+		self.normal *= -1.0;
+		self.features.swap(0, 1);
+	}
+
+	/// Sets which part of the second object was hit.
+	fn on_second(mut self, feature : MeshFeature) -> Collision {
+		self.features[1] = Some(feature);
+		self
+	}
+
 	/// Passes the position and normal information through the inverse of the passed in Orientations.
 	/// The start is the orientation at time=0 and the end is at time=1.0.
 	pub fn transform_out_of(&mut self, start : &Orientation, end : &Orientation) {
@@ -31,13 +64,93 @@ impl Collision {
 	}
 }
 
+/// Lists that the mesh collision checks fill in as they go. Passing the same one into many checks lets them reuse its memory rather than allocating their own each time.
+#[derive(Debug, Default)]
+pub struct CollisionScratch {
+	/// The corners of a single face.
+	corners : Vec<Vec3>,
+	/// Where each of a mesh's vertices is relative to a plane.
+	mesh_infos : Vec<_MeshCollisionInfo>,
+	/// Mesh vertices moved into world space. The first two slots are for meshes without a handle (so are recalculated every time), the rest are indexed by the collider handle's slot.
+	meshes : Vec<MeshVertices>,
+	/// Bumped every time the cached mesh vertices should be forgotten. See [CollisionScratch::forget_mesh_vertices].
+	age : usize,
+	/// The triangles of a triangle mesh that are near whatever it's being collided with.
+	triangles : Vec<usize>,
+}
+
+impl CollisionScratch {
+	/// Marks all of the cached mesh vertices as stale. Must be called whenever any of the colliders' owners move (or their movement changes).
+	pub fn forget_mesh_vertices(&mut self) {
+		self.age = self.age.wrapping_add(1);
+	}
+
+	/// Makes sure a mesh's vertices have been moved into world space, and returns the slot in [CollisionScratch::meshes] they're in.
+	/// If there's a handle, then this re-uses the last result for it (unless [CollisionScratch::forget_mesh_vertices] was called since then). Otherwise it always uses (and overwrites) `fallback_slot`.
+	fn mesh_vertices(&mut self, handle : Option<ColliderHandle>, fallback_slot : usize, mesh : &InternalMeshCollider, start : &Orientation, end : &Orientation) -> usize {
+		let slot = match handle {
+			Some(handle) => 2 + handle.into_raw_parts().0,
+			None => fallback_slot,
+		};
+		if self.meshes.len() <= slot {
+			self.meshes.resize_with(slot + 1, MeshVertices::default);
+		}
+		let key = handle.map(|handle| (handle, self.age));
+		let cached = &mut self.meshes[slot];
+		if key.is_none() || cached.key != key {
+			precompute_mesh_point_pairs(&mesh.vertices, &mesh.position, start, end, &mut cached.points);
+			cached.starts.clear();
+			cached.starts.extend(cached.points.iter().map(|points| points.start));
+			cached.key = key;
+		}
+		slot
+	}
+
+	/// Moves an aligned box's eight corners into world space, overwriting `slot` (which must be one of the slots for meshes without a handle). Returns `slot`.
+	fn box_corners(&mut self, slot : usize, aligned_box : &InternalAlignedBoxCollider, start : &Orientation, end : &Orientation) -> usize {
+		if self.meshes.len() <= slot {
+			self.meshes.resize_with(slot + 1, MeshVertices::default);
+		}
+		let (min, max) = (aligned_box.min_corner, aligned_box.max_corner);
+		let corners = [
+			Vec3::new(min.x, min.y, min.z), Vec3::new(max.x, min.y, min.z), Vec3::new(min.x, max.y, min.z), Vec3::new(max.x, max.y, min.z),
+			Vec3::new(min.x, min.y, max.z), Vec3::new(max.x, min.y, max.z), Vec3::new(min.x, max.y, max.z), Vec3::new(max.x, max.y, max.z),
+		];
+		let cached = &mut self.meshes[slot];
+		precompute_mesh_point_pairs(&corners, &aligned_box.position, start, end, &mut cached.points);
+		cached.starts.clear();
+		cached.starts.extend(cached.points.iter().map(|points| points.start));
+		cached.key = None;
+		slot
+	}
+}
+
+/// A mesh's vertices moved into world space, for the start and the end of a movement.
+#[derive(Debug, Default)]
+struct MeshVertices {
+	/// The collider these were calculated for, and the [CollisionScratch::age] when they were. None means they shouldn't be re-used.
+	key : Option<(ColliderHandle, usize)>,
+	/// Where each vertex starts.
+	starts : Vec<Vec3>,
+	/// Where each vertex starts and ends.
+	points : Vec<MeshPointPairs>,
+}
+
 /// Tries to collide any two arbitrary colliders.
 pub fn collide(collider1 : &Box<dyn InternalCollider>, start1 : &Orientation, end1 : &Orientation, collider2 : &Box<dyn InternalCollider>, start2 : &Orientation, end2 : &Orientation) -> Option<Collision> {
+	collide_reusing(None, collider1, start1, end1, None, collider2, start2, end2, &mut CollisionScratch::default())
+}
+
+/// The same as [collide], but uses the passed in lists for any intermediate results (so it doesn't need to allocate any memory once they've grown big enough).
+/// If the colliders' handles are given, then any mesh's world space vertices are also kept in there, and re-used until [CollisionScratch::forget_mesh_vertices] is called.
+pub fn collide_reusing(handle1 : Option<ColliderHandle>, collider1 : &Box<dyn InternalCollider>, start1 : &Orientation, end1 : &Orientation, handle2 : Option<ColliderHandle>, collider2 : &Box<dyn InternalCollider>, start2 : &Orientation, end2 : &Orientation, scratch : &mut CollisionScratch) -> Option<Collision> {
 	// Always ignore a NullCollider.
 	// This is redundant now, but won't be in the future.
 	if ColliderType::NULL == collider1.get_type() || ColliderType::NULL == collider2.get_type() {
 		return None
 	}
+	// Margins are handled by growing one of the shapes by both of them (wherever that's easy to do).
+	let margin = collider1.get_margin() + collider2.get_margin();
 
 	if ColliderType::SPHERE == collider1.get_type() && ColliderType::SPHERE == collider2.get_type() {
 		let col1 = collider1.downcast_ref::<InternalSphereCollider>().unwrap();
@@ -49,7 +162,7 @@ pub fn collide(collider1 : &Box<dyn InternalCollider>, start1 : &Orientation, en
 		let col2_end_position = end2.position_into_world(&col2.center);
 
 		return collide_sphere_with_sphere(
-			col1.radius,
+			col1.radius + margin,
 			&col1_start_position,
 			&(col1_end_position - col1_start_position),
 			col2.radius,
@@ -68,7 +181,7 @@ pub fn collide(collider1 : &Box<dyn InternalCollider>, start1 : &Orientation, en
 		let plane_end_position = end2.position_into_world(&plane.position);
 
 		return collide_sphere_with_plane(
-			sphere.radius,
+			sphere.radius + margin,
 			&sphere_start_position,
 			&(sphere_end_position - sphere_start_position),
 			&plane_start_position,
@@ -86,7 +199,7 @@ pub fn collide(collider1 : &Box<dyn InternalCollider>, start1 : &Orientation, en
 		let sphere_end_position = end2.position_into_world(&sphere.center);
 
 		let collision_option = collide_sphere_with_plane(
-			sphere.radius,
+			sphere.radius + margin,
 			&sphere_start_position,
 			&(sphere_end_position - sphere_start_position),
 			&plane_start_position,
@@ -111,15 +224,22 @@ pub fn collide(collider1 : &Box<dyn InternalCollider>, start1 : &Orientation, en
 		let mesh_start_position = start2.position_into_world(&mesh.position);
 		let mesh_end_position = end2.position_into_world(&mesh.position);
 
-		return collide_sphere_with_mesh(
-			sphere.radius,
+		let slot = scratch.mesh_vertices(handle2, 1, mesh, start2, end2);
+		let vertices = std::mem::take(&mut scratch.meshes[slot].starts);
+		let collision_option = collide_sphere_with_mesh(
+			sphere.radius + margin,
 			&sphere_start_position,
 			&(sphere_end_position - sphere_start_position),
-			&mesh.vertices_in_world(&start2),
+			&vertices,
 			&mesh.edges,
 			&mesh.faces,
+			if mesh.smooth_normals { Some(&mesh.adjacency) } else { None },
+			if mesh.one_sided { Some(&mesh.adjacency) } else { None },
 			&(mesh_end_position - mesh_start_position),
+			scratch,
 		);
+		scratch.meshes[slot].starts = vertices;
+		return collision_option;
 	}
 	if ColliderType::MESH == collider1.get_type() && ColliderType::SPHERE == collider2.get_type() {
 		let mesh  = collider1.downcast_ref::<InternalMeshCollider>().unwrap();
@@ -130,18 +250,24 @@ pub fn collide(collider1 : &Box<dyn InternalCollider>, start1 : &Orientation, en
 		let sphere_start_position = start2.position_into_world(&sphere.center);
 		let sphere_end_position = end2.position_into_world(&sphere.center);
 
+		let slot = scratch.mesh_vertices(handle1, 0, mesh, start1, end1);
+		let vertices = std::mem::take(&mut scratch.meshes[slot].starts);
 		let collision_option = collide_sphere_with_mesh(
-			sphere.radius,
+			sphere.radius + margin,
 			&sphere_start_position,
 			&(sphere_end_position - sphere_start_position),
-			&mesh.vertices_in_world(&start1),
+			&vertices,
 			&mesh.edges,
 			&mesh.faces,
+			if mesh.smooth_normals { Some(&mesh.adjacency) } else { None },
+			if mesh.one_sided { Some(&mesh.adjacency) } else { None },
 			&(mesh_end_position - mesh_start_position),
+			scratch,
 		);
-		// Must negate the normal as the sphere is the second collider.
+		scratch.meshes[slot].starts = vertices;
+		// Must negate the normal (and swap the features) as the sphere is the second collider.
 		if let Some(mut collision) = collision_option {
-			collision.normal *= -1.0;
+			collision.swap_sides();
 			return Some(collision);
 		} else {
 			return None
@@ -155,15 +281,17 @@ pub fn collide(collider1 : &Box<dyn InternalCollider>, start1 : &Orientation, en
 		let plane_start_position = start2.position_into_world(&plane.position);
 		let plane_end_position = end2.position_into_world(&plane.position);
 
-		return collide_mesh_with_plane(
-			&mesh.vertices,
-			&mesh.position,
-			start1,
-			end1,
-			&plane_start_position,
-			&plane_end_position,
+		let slot = scratch.mesh_vertices(handle1, 0, mesh, start1, end1);
+		let points = std::mem::take(&mut scratch.meshes[slot].points);
+		let collision_option = collide_mesh_with_plane(
+			&points,
+			&(plane_start_position + plane.normal * margin),
+			&(plane_end_position + plane.normal * margin),
 			&plane.normal,
+			scratch,
 		);
+		scratch.meshes[slot].points = points;
+		return collision_option;
 	}
 
 	if ColliderType::PLANE == collider1.get_type() && ColliderType::MESH == collider2.get_type() {
@@ -174,35 +302,66 @@ pub fn collide(collider1 : &Box<dyn InternalCollider>, start1 : &Orientation, en
 
 		let mesh  = collider2.downcast_ref::<InternalMeshCollider>().unwrap();
 
+		let slot = scratch.mesh_vertices(handle2, 1, mesh, start2, end2);
+		let points = std::mem::take(&mut scratch.meshes[slot].points);
 		let collision_option = collide_mesh_with_plane(
-			&mesh.vertices,
-			&mesh.position,
-			start2,
-			end2,
-			&plane_start_position,
-			&plane_end_position,
+			&points,
+			&(plane_start_position + plane.normal * margin),
+			&(plane_end_position + plane.normal * margin),
 			&plane.normal,
+			scratch,
 		);
-		// Must negate the normal as the mesh is the second collider.
+		scratch.meshes[slot].points = points;
+		// Must negate the normal (and swap the features) as the mesh is the second collider.
 		if let Some(mut collision) = collision_option {
-			collision.normal *= -1.0;
+			collision.swap_sides();
 			return Some(collision);
 		} else {
 			return None
 		}
 	}
 
+	if (ColliderType::ALIGNED_BOX == collider1.get_type() && ColliderType::PLANE == collider2.get_type()) || (ColliderType::PLANE == collider1.get_type() && ColliderType::ALIGNED_BOX == collider2.get_type()) {
+		// Only the corners can be the first thing to hit a plane, so treat the box like a mesh.
+		let box_is_first = ColliderType::ALIGNED_BOX == collider1.get_type();
+		let (aligned_box, box_start, box_end, plane, plane_start, plane_end) = if box_is_first {
+			(collider1.downcast_ref::<InternalAlignedBoxCollider>().unwrap(), start1, end1, collider2.downcast_ref::<InternalPlaneCollider>().unwrap(), start2, end2)
+		} else {
+			(collider2.downcast_ref::<InternalAlignedBoxCollider>().unwrap(), start2, end2, collider1.downcast_ref::<InternalPlaneCollider>().unwrap(), start1, end1)
+		};
+		let plane_start_position = plane_start.position_into_world(&plane.position);
+		let plane_end_position = plane_end.position_into_world(&plane.position);
+
+		let slot = scratch.box_corners(0, aligned_box, box_start, box_end);
+		let points = std::mem::take(&mut scratch.meshes[slot].points);
+		let collision_option = collide_mesh_with_plane(
+			&points,
+			&(plane_start_position + plane.normal * margin),
+			&(plane_end_position + plane.normal * margin),
+			&plane.normal,
+			scratch,
+		);
+		scratch.meshes[slot].points = points;
+		return collision_option.map(|mut collision| {
+			// A box's corners aren't mesh features.
+			collision.features = [None, None];
+			if !box_is_first { collision.swap_sides(); }
+			collision
+		});
+	}
+
 	if ColliderType::MESH == collider1.get_type() && ColliderType::MESH == collider2.get_type() {
 		let mesh1  = collider1.downcast_ref::<InternalMeshCollider>().unwrap();
 		let mesh2  = collider2.downcast_ref::<InternalMeshCollider>().unwrap();
 
+		let slot1 = scratch.mesh_vertices(handle1, 0, mesh1, start1, end1);
+		let slot2 = scratch.mesh_vertices(handle2, 1, mesh2, start2, end2);
 		return collide_mesh_with_mesh(
-			&mesh1,
-			start1,
-			end1,
-			&mesh2,
-			start2,
-			end2,
+			mesh1,
+			&scratch.meshes[slot1].points,
+			mesh2,
+			&scratch.meshes[slot2].points,
+			&mut scratch.corners,
 		);
 	}
 
@@ -215,7 +374,7 @@ pub fn collide(collider1 : &Box<dyn InternalCollider>, start1 : &Orientation, en
 		let sphere_end_position = end2.position_into_local(&end1.position_into_world(&sphere.center));
 
 		let result_option = collide_sphere_with_aligned_box(
-			sphere.radius,
+			sphere.radius + margin,
 			&sphere_start_position,
 			&(sphere_end_position - sphere_start_position),
 			&aligned_box.min_corner,
@@ -239,7 +398,7 @@ pub fn collide(collider1 : &Box<dyn InternalCollider>, start1 : &Orientation, en
 		let sphere_end_position = end1.position_into_local(&end2.position_into_world(&sphere.center));
 
 		let result_option = collide_sphere_with_aligned_box(
-			sphere.radius,
+			sphere.radius + margin,
 			&sphere_start_position,
 			&(sphere_end_position - sphere_start_position),
 			&aligned_box.min_corner,
@@ -255,11 +414,116 @@ pub fn collide(collider1 : &Box<dyn InternalCollider>, start1 : &Orientation, en
 		}
 	}
 
+	if ColliderType::ALIGNED_BOX == collider1.get_type() && ColliderType::ALIGNED_BOX == collider2.get_type() {
+		// Boxes that are turned the same way stay lined up with each other, so the first can be moved into the second's local space and checked one axis at a time. Any others go through the generic convex path below.
+		if start1.rotation.angle_to(&start2.rotation) < EPSILON && end1.rotation.angle_to(&end2.rotation) < EPSILON {
+			let box1 = collider1.downcast_ref::<InternalAlignedBoxCollider>().unwrap();
+			let box2 = collider2.downcast_ref::<InternalAlignedBoxCollider>().unwrap();
+
+			let min_start = start2.position_into_local(&start1.position_into_world(&(box1.position + box1.min_corner)));
+			let max_start = start2.position_into_local(&start1.position_into_world(&(box1.position + box1.max_corner)));
+			let min_end = end2.position_into_local(&end1.position_into_world(&(box1.position + box1.min_corner)));
+
+			return collide_aligned_box_with_aligned_box(
+				&min_start,
+				&max_start,
+				&(min_end - min_start),
+				margin,
+				&(box2.position + box2.min_corner),
+				&(box2.position + box2.max_corner),
+			).map(|mut collision| {
+				collision.transform_out_of(start2, end2);
+				collision
+			});
+		}
+	}
+
+	// Triangle meshes are only ever hit by convex shapes (as they're static level geometry). Work out which is which, then flip the normal back at the end if the mesh was first.
+	if ColliderType::TRI_MESH == collider1.get_type() || ColliderType::TRI_MESH == collider2.get_type() {
+		let mesh_is_first = ColliderType::TRI_MESH == collider1.get_type();
+		let (mesh, mesh_start, mesh_end, other, other_start, other_end) = if mesh_is_first {
+			(collider1, start1, end1, collider2, start2, end2)
+		} else {
+			(collider2, start2, end2, collider1, start1, end1)
+		};
+		let mesh = mesh.downcast_ref::<InternalTriMeshCollider>().unwrap();
+		let collision_option = if ColliderType::SPHERE == other.get_type() {
+			// Spheres are moved into the mesh's local space, which is much cheaper than checking each triangle in world space.
+			let sphere = other.downcast_ref::<InternalSphereCollider>().unwrap();
+			let sphere_start_position = mesh_start.position_into_local(&other_start.position_into_world(&sphere.center));
+			let sphere_end_position = mesh_end.position_into_local(&other_end.position_into_world(&sphere.center));
+			collide_sphere_with_tri_mesh(
+				sphere.radius + margin,
+				&sphere_start_position,
+				&(sphere_end_position - sphere_start_position),
+				mesh,
+				scratch,
+			).map(|mut collision| {
+				collision.transform_out_of(mesh_start, mesh_end);
+				collision
+			})
+		} else if let Some(shape) = as_convex_shape(other.as_ref()) {
+			collide_convex_with_tri_mesh(shape, other_start, other_end, mesh, mesh_start, mesh_end, margin, scratch)
+		} else {
+			None
+		};
+		// Must negate the normal (and swap the features) if the mesh is the first collider.
+		return collision_option.map(|mut collision| {
+			if mesh_is_first { collision.swap_sides(); }
+			collision
+		});
+	}
+
+	// Any other pair of convex shapes goes through the generic (but slower) path.
+	if let (Some(shape1), Some(shape2)) = (as_convex_shape(collider1.as_ref()), as_convex_shape(collider2.as_ref())) {
+		return collide_convex_with_convex(shape1, start1, end1, shape2, start2, end2, margin);
+	}
+
+	None
+}
+
+/// An upper bound on how far any point on a shape could move while going from one orientation to another.
+fn convex_motion_bound(shape : &dyn ConvexShape, start : &Orientation, end : &Orientation) -> f32 {
+	let (center, radius) = shape.bounding_sphere();
+	// Rotations are about the orientation's position, so the farthest point is the one farthest from that.
+	let reach = (center + start.internal_origin_offset).magnitude() + radius;
+	(end.position - start.position).magnitude() + (end.rotation_vec() - start.rotation_vec()).magnitude() * reach
+}
+
+/// Collides any two convex shapes, by repeatedly moving both forward in time by as much as they can go without possibly touching (i.e. conservative advancement).
+///
+/// The shapes count as touching once they're within `margin` of each other.
+///
+/// This is slower (and less exact) than the checks written for specific pairs of shapes, so is only used for pairs that don't have one.
+pub fn collide_convex_with_convex(shape1 : &dyn ConvexShape, start1 : &Orientation, end1 : &Orientation, shape2 : &dyn ConvexShape, start2 : &Orientation, end2 : &Orientation, margin : f32) -> Option<Collision> {
+	let speed = convex_motion_bound(shape1, start1, end1) + convex_motion_bound(shape2, start2, end2);
+	let mut time = 0.0;
+	for _ in 0..CONSERVATIVE_ADVANCEMENT_ITERATIONS {
+		let placed1 = PlacedShape::new(shape1, &Orientation::lerp(time, start1, end1));
+		let placed2 = PlacedShape::new(shape2, &Orientation::lerp(time, start2, end2));
+		let contact_distance = margin + 10.0 * tolerance(&placed1, &placed2);
+		let (on1, on2) = match closest_points(&placed1, &placed2) {
+			Some(points) => points,
+			None => {
+				// Already overlapping (which should only happen at the very start).
+				let (normal, _, position) = penetration(&placed1, &placed2);
+				return Some(Collision { times: Range::single(time), position, normal, features: [None, None] });
+			},
+		};
+		let distance = (on2 - on1).magnitude();
+		if distance <= contact_distance {
+			return Some(Collision { times: Range::single(time), position: 0.5 * (on1 + on2), normal: (on2 - on1) / distance, features: [None, None] });
+		}
+		// Nothing can touch if the shapes aren't moving relative to each other.
+		if speed < EPSILON { return None; }
+		time += (distance - 0.5 * contact_distance) / speed;
+		if 1.0 < time { return None; }
+	}
 	None
 }
 
 /// A helper to get the time of collision for a sphere overlapping a plane.
-fn sphere_plane_overlap_time(radius1 : f32, center1 : &Vec3, movement1 : &Vec3, position2 : &Vec3, normal2 : &Vec3, movement2 : &Vec3, infinite_backdrop : bool) -> Range {
+pub fn sphere_plane_overlap_time(radius1 : f32, center1 : &Vec3, movement1 : &Vec3, position2 : &Vec3, normal2 : &Vec3, movement2 : &Vec3, infinite_backdrop : bool) -> Range {
 	let start_nearest  = center1 + normal2.scale(-radius1);
 	let start_farthest = center1 + normal2.scale( radius1);
 	let circle_range = Range::range(
@@ -285,11 +549,14 @@ pub fn collide_sphere_with_plane(radius1 : f32, center1 : &Vec3, movement1 : &Ve
 		true,
 	).intersect(&Range::range(0.0, 1.0));
 	if !times.is_empty() {
-		let start_nearest  = center1 + normal2.scale(-radius1); // TODO: Pass this along somehow?
+		// The contact is the point on the plane right under the sphere's center when they hit. That's usually the sphere's nearest point, but not when they already overlap at the start (then the nearest point is buried in the plane).
+		let center = center1 + movement1.scale(times.min());
+		let plane_position = position2 + movement2.scale(times.min());
 		Some(Collision {
 			times,
-			position: start_nearest + movement1.scale(times.min()),
+			position: center - normal2.scale((center - plane_position).dot(normal2)),
 			normal: -normal2,
+			features: [None, None],
 		})
 	} else { None }
 }
@@ -309,11 +576,12 @@ pub fn collide_sphere_with_sphere(radius1 : f32, center1 : &Vec3, movement1 : &V
 			(center1 + movement1.scale(times.min())) * radius2 +
 			(center2 + movement2.scale(times.min())) * radius1
 		).scale(1.0 / radius);
-		let normal = (position - center1).normalize();
+		let normal = (position - (center1 + movement1.scale(times.min()))).normalize();
 		Some(Collision {
 			times,
 			position,
 			normal,
+			features: [None, None],
 		})
 	} else { None }
 }
@@ -334,11 +602,12 @@ pub fn collide_sphere_with_line(radius1 : f32, center1: &Vec3, movement1 : &Vec3
 		let center_position = center1 + movement1.scale(times.min());
 		let along_length = (center_position - line_position).dot(&direction);
 		let position = line_position + direction.scale(along_length);
-		let normal = (position - center1).normalize();
+		let normal = (position - (center1 + movement1.scale(times.min()))).normalize();
 		Some(Collision {
 			times,
 			position,
 			normal,
+			features: [None, None],
 		})
 	} else { None }
 }
@@ -402,16 +671,25 @@ pub fn collide_sphere_with_polygon_surface(radius1: f32, center1: &Vec3, movemen
 			times,
 			position: hit_position,
 			normal,
+			features: [None, None],
 		})
 	} else { None }
 }
 
 /// A helper object to grab the earliest collision of a series of passed in collisions.
+///
+/// It can also cluster hits: any that happen at (about) the same time as the earliest one, with (about) the same normal, have their positions averaged together. So i.e. a flat face landing on another is hit in its middle, rather than at whichever corner was checked first.
 struct EarliestCollisionAccumulator {
 	/// The current earliest.
 	earliest : Option<Collision>,
 	/// The time of the current earliest.
 	earliest_time : f32,
+	/// How close in time hits must be to the earliest to be clustered with it. Negative turns clustering off.
+	cluster_tolerance : f32,
+	/// The sum of the positions of every hit clustered with the earliest (including itself).
+	cluster_sum : Vec3,
+	/// How many hits are clustered with the earliest (including itself).
+	cluster_count : f32,
 }
 
 impl EarliestCollisionAccumulator {
@@ -420,62 +698,255 @@ impl EarliestCollisionAccumulator {
 		EarliestCollisionAccumulator {
 			earliest: None,
 			earliest_time: INFINITY,
+			cluster_tolerance: -1.0,
+			cluster_sum: Vec3::zeros(),
+			cluster_count: 0.0,
+		}
+	}
+
+	/// Creates a new instance with no earliest set, that clusters hits within the given time of the earliest.
+	pub fn clustering(time_tolerance : f32) -> EarliestCollisionAccumulator {
+		EarliestCollisionAccumulator { cluster_tolerance: time_tolerance, ..EarliestCollisionAccumulator::new() }
+	}
+
+	/// Whether a hit at the given time (with the given normal) should be clustered with the current earliest.
+	fn is_clustered(&self, time : f32, normal : &Vec3) -> bool {
+		match &self.earliest {
+			Some(earliest) => (time - self.earliest_time).abs() <= self.cluster_tolerance && MESH_CLUSTER_NORMAL_TOLERANCE < earliest.normal.dot(normal),
+			None => false,
 		}
 	}
 
 	/// Considers storing the given possible collision.
 	pub fn consider(&mut self, possible : Option<Collision>) {
 		if let Some(collision) = possible {
-			if collision.times.min() < self.earliest_time {
-				println!("Got earliest!");
-				self.earliest_time = collision.times.min();
+			let time = collision.times.min();
+			if self.is_clustered(time, &collision.normal) {
+				debug_print!("Got clustered!");
+				self.cluster_sum += collision.position;
+				self.cluster_count += 1.0;
+				if time < self.earliest_time {
+					self.earliest_time = time;
+					self.earliest = Some(collision);
+				}
+			} else if time < self.earliest_time {
+				debug_print!("Got earliest!");
+				self.earliest_time = time;
+				self.cluster_sum = collision.position;
+				self.cluster_count = 1.0;
 				self.earliest = Some(collision);
 			} else {
-				println!("Got later...");
+				debug_print!("Got later...");
 			}
 		} else {
-			println!("Got nothing!");
+			debug_print!("Got nothing!");
 		}
 	}
 
 	/// Yields the closest found.
-	pub fn get(self) -> Option<Collision> { self.earliest }
+	pub fn get(self) -> Option<Collision> {
+		let mut earliest = self.earliest?;
+		if 1.0 < self.cluster_count {
+			earliest.position = self.cluster_sum / self.cluster_count;
+		}
+		Some(earliest)
+	}
+}
+
+/// Finds a face's normal, from the mesh's (already moved) vertices.
+fn mesh_face_normal(face : &[usize], vertices : &[Vec3], corners : &mut Vec<Vec3>) -> Vec3 {
+	corners.clear();
+	corners.extend(face.iter().map(|index| vertices[*index]));
+	face_normal(corners)
+}
+
+/// Corrects the normal of a sphere's hit on a mesh edge, so it's between the normals of the faces on either side. If the faces are on the same plane, the hit is treated as hitting them.
+///
+/// Edges that don't have exactly two faces (or that are hit from behind both) are left alone.
+fn smooth_edge_normal(collision : &mut Collision, edge_faces : &[usize], vertices : &[Vec3], faces : &[Vec<usize>], corners : &mut Vec<Vec3>) {
+	if 2 != edge_faces.len() { return; }
+	let first = mesh_face_normal(&faces[edge_faces[0]], vertices, corners);
+	let second = mesh_face_normal(&faces[edge_faces[1]], vertices, corners);
+	smooth_edge_normal_between(collision, &first, &second);
+}
+
+/// The same as [smooth_edge_normal], given the normals of the faces on either side of the edge.
+fn smooth_edge_normal_between(collision : &mut Collision, first : &Vec3, second : &Vec3) {
+	if first.x.is_nan() || second.x.is_nan() { return; }
+	// The hit's normal points off of the sphere, so flip it to point off of the mesh (like the faces' normals).
+	let outward = -collision.normal;
+	if MESH_FLAT_TOLERANCE < first.dot(second) {
+		collision.normal = if outward.dot(first) < 0.0 { *first } else { -first };
+		return;
+	}
+	if outward.dot(first) < 0.0 && outward.dot(second) < 0.0 { return; }
+	let between = 0.0 <= first.cross(&outward).dot(&first.cross(second)) && 0.0 <= second.cross(&outward).dot(&second.cross(first));
+	if !between {
+		collision.normal = if outward.dot(second) < outward.dot(first) { -first } else { -second };
+	}
+}
+
+/// Corrects the normal of a sphere's hit on a mesh corner that's in the middle of a flat area, so it's treated as hitting the faces around it.
+fn smooth_vertex_normal(collision : &mut Collision, vertex : usize, adjacency : &MeshAdjacency, vertices : &[Vec3], faces : &[Vec<usize>], corners : &mut Vec<Vec3>) {
+	let vertex_faces = &adjacency.vertex_faces[vertex];
+	if adjacency.vertex_on_boundary[vertex] || vertex_faces.is_empty() { return; }
+	let first = mesh_face_normal(&faces[vertex_faces[0]], vertices, corners);
+	if first.x.is_nan() { return; }
+	for face in vertex_faces.iter().skip(1) {
+		if !(MESH_FLAT_TOLERANCE < mesh_face_normal(&faces[*face], vertices, corners).dot(&first)) { return; }
+	}
+	collision.normal = if collision.normal.dot(&first) < 0.0 { -first } else { first };
+}
+
+/// Whether the point is behind every one of the given faces (and there's at least one of them). See [crate::MeshCollider::one_sided].
+fn is_behind_faces(point : &Vec3, face_indices : &[usize], vertices : &[Vec3], faces : &[Vec<usize>], corners : &mut Vec<Vec3>) -> bool {
+	!face_indices.is_empty() && face_indices.iter().all(|face| {
+		let normal = mesh_face_normal(&faces[*face], vertices, corners);
+		// Degenerate faces don't have a front, so never count as being in front of anything.
+		normal.x.is_nan() || (point - corners[0]).dot(&normal) < 0.0
+	})
 }
 
 /// Collides a sphere against a mesh.
-pub fn collide_sphere_with_mesh(radius1 : f32, center1: &Vec3, movement1 : &Vec3, vertices2 : &Vec<Vec3>, edges2 : &Vec<(usize, usize)>, faces2 : &Vec<Vec<usize>>, movement2 : &Vec3) -> Option<Collision> {
+///
+/// If the mesh's adjacency is given as `adjacency2`, then hits on edges and corners have their normals corrected to match the faces around them (see [crate::MeshCollider::smooth_normals]). If it's given as `one_sided2`, then features the sphere starts behind are skipped (see [crate::MeshCollider::one_sided]).
+pub fn collide_sphere_with_mesh(radius1 : f32, center1: &Vec3, movement1 : &Vec3, vertices2 : &Vec<Vec3>, edges2 : &Vec<(usize, usize)>, faces2 : &Vec<Vec<usize>>, adjacency2 : Option<&MeshAdjacency>, one_sided2 : Option<&MeshAdjacency>, movement2 : &Vec3, scratch : &mut CollisionScratch) -> Option<Collision> {
 	let mut accumulator = EarliestCollisionAccumulator::new();
 	// First check all the corners.
-	for vertex in vertices2 {
-		println!("vertex");
-		accumulator.consider(collide_sphere_with_sphere(
+	for (vertex_index, vertex) in vertices2.iter().enumerate() {
+		debug_print!("vertex");
+		if let Some(adjacency) = one_sided2 {
+			if is_behind_faces(center1, &adjacency.vertex_faces[vertex_index], vertices2, faces2, &mut scratch.corners) { continue; }
+		}
+		let mut possible = collide_sphere_with_sphere(
 			radius1, center1, movement1,
 			0.0, vertex, movement2,
-		));
+		).map(|collision| collision.on_second(MeshFeature::Vertex(vertex_index)));
+		if let (Some(collision), Some(adjacency)) = (possible.as_mut(), adjacency2) {
+			smooth_vertex_normal(collision, vertex_index, adjacency, vertices2, faces2, &mut scratch.corners);
+		}
+		accumulator.consider(possible);
 	}
 	// Then check all the edges.
-	for (index1, index2) in edges2 {
-		println!("edge");
-		accumulator.consider(collide_sphere_with_mid_line_segment(
+	for (edge_index, (index1, index2)) in edges2.iter().enumerate() {
+		debug_print!("edge");
+		if let Some(adjacency) = one_sided2 {
+			if is_behind_faces(center1, &adjacency.edge_faces[edge_index], vertices2, faces2, &mut scratch.corners) { continue; }
+		}
+		let mut possible = collide_sphere_with_mid_line_segment(
 			radius1, center1, movement1,
 			&vertices2[*index1], &vertices2[*index2], movement2,
-		));
+		).map(|collision| collision.on_second(MeshFeature::edge(*index1, *index2)));
+		if let (Some(collision), Some(adjacency)) = (possible.as_mut(), adjacency2) {
+			smooth_edge_normal(collision, &adjacency.edge_faces[edge_index], vertices2, faces2, &mut scratch.corners);
+		}
+		accumulator.consider(possible);
 	}
 	// Then check all the planes.
-	for face in faces2 {
-		println!("face");
-		let mut corners = Vec::with_capacity(face.len());
+	for (face_index, face) in faces2.iter().enumerate() {
+		debug_print!("face");
+		if one_sided2.is_some() && is_behind_faces(center1, &[face_index], vertices2, faces2, &mut scratch.corners) { continue; }
+		let corners = &mut scratch.corners;
+		corners.clear();
 		for index in face {
-			corners.push(vertices2[*index].clone()); // TODO: Make this more efficient.
+			corners.push(vertices2[*index]);
 		}
 		accumulator.consider(collide_sphere_with_polygon_surface(
 			radius1, center1, movement1,
-			&corners, movement2,
-		));
+			corners, movement2,
+		).map(|collision| collision.on_second(MeshFeature::Face(face_index))));
+	}
+	accumulator.get()
+}
+
+/// A single triangle (in its owning entity's local space), so it can be collided with the generic convex checks.
+struct TriangleShape([Vec3; 3]);
+
+impl ConvexShape for TriangleShape {
+	fn support(&self, direction : &Vec3) -> Vec3 {
+		let [first, second, third] = self.0;
+		let best = if first.dot(direction) < second.dot(direction) { second } else { first };
+		if best.dot(direction) < third.dot(direction) { third } else { best }
+	}
+
+	fn bounding_sphere(&self) -> (Vec3, f32) {
+		let center = self.0.iter().sum::<Vec3>() / 3.0;
+		(center, self.0.iter().map(|corner| (corner - center).magnitude()).fold(0.0, f32::max))
+	}
+}
+
+/// Collides a sphere against a (one-sided) triangle mesh. The sphere's center and movement must be in the mesh's owning entity's local space, and the resulting collision is too.
+///
+/// Only the triangles near the sphere's path are checked. Triangles the sphere starts (more than the mesh's thickness) behind are skipped, as are any hits that would push the sphere out the back of a triangle. Hits on edges between triangles have their normals corrected to match the triangles on either side (like [crate::MeshCollider::smooth_normals]).
+pub fn collide_sphere_with_tri_mesh(radius1 : f32, center1 : &Vec3, movement1 : &Vec3, mesh : &InternalTriMeshCollider, scratch : &mut CollisionScratch) -> Option<Collision> {
+	let zero_vec = Vec3::zeros();
+	let end1 = center1 + movement1;
+	let reach = Vec3::repeat(radius1);
+	scratch.triangles.clear();
+	mesh.triangles_near(&(center1.inf(&end1) - reach), &(center1.sup(&end1) + reach), &mut scratch.triangles);
+	let mut accumulator = EarliestCollisionAccumulator::new();
+	for triangle in scratch.triangles.iter() {
+		let normal = mesh.lookup.normals[*triangle];
+		let corners = mesh.corners(*triangle);
+		let indices = mesh.triangles[*triangle];
+		if normal.x.is_nan() || (center1 - corners[0]).dot(&normal) < -mesh.thickness { continue; }
+		let mut consider = |possible : Option<Collision>| accumulator.consider(possible.filter(|collision| collision.normal.dot(&normal) <= EPSILON));
+		// First check all the corners.
+		for (corner, vertex) in corners.iter().zip(indices.iter()) {
+			consider(collide_sphere_with_sphere(
+				radius1, center1, movement1,
+				0.0, corner, &zero_vec,
+			).map(|collision| collision.on_second(MeshFeature::Vertex(*vertex))));
+		}
+		// Then check all the edges.
+		for (index, neighbor) in mesh.lookup.neighbors[*triangle].iter().enumerate() {
+			let mut possible = collide_sphere_with_mid_line_segment(
+				radius1, center1, movement1,
+				&corners[index], &corners[(index + 1) % 3], &zero_vec,
+			).map(|collision| collision.on_second(MeshFeature::edge(indices[index], indices[(index + 1) % 3])));
+			if let (Some(collision), Some(neighbor)) = (possible.as_mut(), neighbor) {
+				smooth_edge_normal_between(collision, &normal, &mesh.lookup.normals[*neighbor]);
+			}
+			consider(possible);
+		}
+		// Then check the face itself.
+		scratch.corners.clear();
+		scratch.corners.extend_from_slice(&corners);
+		consider(collide_sphere_with_polygon_surface(
+			radius1, center1, movement1,
+			&scratch.corners, &zero_vec,
+		).map(|collision| collision.on_second(MeshFeature::Face(*triangle))));
+	}
+	accumulator.get()
+}
+
+/// Collides any convex shape against a (one-sided) triangle mesh, by colliding it with each of the nearby triangles in turn.
+///
+/// Triangles the shape's center starts (more than the mesh's thickness) behind are skipped, as are any hits that would push the shape out the back of a triangle. Hits at the same time (and facing the same way) are clustered together, so i.e. a box landing flat across several triangles is hit in its middle.
+pub fn collide_convex_with_tri_mesh(shape1 : &dyn ConvexShape, start1 : &Orientation, end1 : &Orientation, mesh : &InternalTriMeshCollider, start2 : &Orientation, end2 : &Orientation, margin : f32, scratch : &mut CollisionScratch) -> Option<Collision> {
+	// Only look at the triangles near wherever the shape goes (in the mesh's local space).
+	let (center, radius) = shape1.bounding_sphere();
+	let center_start = start2.position_into_local(&start1.position_into_world(&center));
+	let center_end = end2.position_into_local(&end1.position_into_world(&center));
+	let reach = Vec3::repeat(radius + margin);
+	scratch.triangles.clear();
+	mesh.triangles_near(&(center_start.inf(&center_end) - reach), &(center_start.sup(&center_end) + reach), &mut scratch.triangles);
+	let mut accumulator = EarliestCollisionAccumulator::clustering(MESH_CLUSTER_TIME_TOLERANCE);
+	for triangle in scratch.triangles.iter() {
+		let normal = mesh.lookup.normals[*triangle];
+		let corners = mesh.corners(*triangle);
+		if normal.x.is_nan() || (center_start - corners[0]).dot(&normal) < -mesh.thickness { continue; }
+		let world_normal = start2.direction_into_world(&normal);
+		accumulator.consider(
+			collide_convex_with_convex(shape1, start1, end1, &TriangleShape(corners), start2, end2, margin)
+				.filter(|collision| collision.normal.dot(&world_normal) <= EPSILON)
+				.map(|collision| collision.on_second(MeshFeature::Face(*triangle)))
+		);
 	}
 	accumulator.get()
 }
 
+#[derive(Debug)]
 struct _MeshCollisionInfo {
 	start_position : Vec3,
 	end_position : Vec3,
@@ -484,15 +955,15 @@ struct _MeshCollisionInfo {
 	end_distance : f32,
 }
 
-/// Collides a mesh with an (infinite) plane.
-pub fn collide_mesh_with_plane(mesh_vertices : &Vec<Vec3>, mesh_position : &Vec3, mesh_start_orientation : &Orientation, mesh_end_orientation : &Orientation, plane_start_position : &Vec3, plane_end_position : &Vec3, plane_normal : &Vec3) -> Option<Collision> {
+/// Collides a mesh (given as where each of its vertices starts and ends in world space) with an (infinite) plane.
+pub fn collide_mesh_with_plane(mesh_points : &[MeshPointPairs], plane_start_position : &Vec3, plane_end_position : &Vec3, plane_normal : &Vec3, scratch : &mut CollisionScratch) -> Option<Collision> {
 	let mut start_distances = Range::empty();
 	let mut end_distances = Range::empty();
-	let mut calculated  = Vec::new();
-	for vertex in mesh_vertices {
-		let internal_vertex_position = mesh_position + vertex;
-		let mesh_start_position = mesh_start_orientation.position_into_world(&internal_vertex_position);
-		let mesh_end_position = mesh_end_orientation.position_into_world(&internal_vertex_position);
+	let calculated = &mut scratch.mesh_infos;
+	calculated.clear();
+	for points in mesh_points {
+		let mesh_start_position = points.start;
+		let mesh_end_position = points.end;
 
 		let start_distance = (mesh_start_position - plane_start_position).dot(plane_normal);
 		let end_distance   = (mesh_end_position   - plane_end_position).dot(plane_normal);
@@ -518,18 +989,24 @@ pub fn collide_mesh_with_plane(mesh_vertices : &Vec<Vec3>, mesh_position : &Vec3
 		let mut closest_start_position_count : f32 = 0.0;
 		let mut closest_end_position_sum = Vec3::zeros();
 		let mut closest_end_position_count : f32 = 0.0;
-		let start_epsilon = start_distances.size() * 0.01;// Apparently the standard EPSILON is a bit too small...
-		let end_epsilon = end_distances.size() * 0.01;// Apparently the standard EPSILON is a bit too small...
-		for info in calculated {
+		// If the same single vertex is closest at both ends, then that's the one that was hit.
+		let mut closest_indices : [Option<usize>; 2] = [None, None];
+		// Apparently the standard EPSILON is a bit too small... But don't let it get so big that corners a noticeable distance off the plane get averaged in.
+		let start_epsilon = (start_distances.size() * 0.01).min(MESH_PLANE_TOLERANCE);
+		let end_epsilon = (end_distances.size() * 0.01).min(MESH_PLANE_TOLERANCE);
+		for (index, info) in calculated.iter().enumerate() {
 			if start_epsilon > (info.start_distance - start_distances.min()).abs() {
 				closest_start_position_sum += info.start_position;
 				closest_start_position_count += 1.0;
+				closest_indices[0] = Some(index);
 			}
 			if end_epsilon > (info.end_distance - end_distances.min()).abs() {
 				closest_end_position_sum += info.end_position;
 				closest_end_position_count += 1.0;
+				closest_indices[1] = Some(index);
 			}
 		}
+		let single_closest = closest_start_position_count == 1.0 && closest_end_position_count == 1.0 && closest_indices[0] == closest_indices[1];
 		closest_start_position_sum /= closest_start_position_count;
 		closest_end_position_sum /= closest_end_position_count;
 
@@ -538,6 +1015,7 @@ pub fn collide_mesh_with_plane(mesh_vertices : &Vec<Vec3>, mesh_position : &Vec3
 			times: times,
 			position: closest_start_position_sum * (1.0 - time) + closest_end_position_sum * time,
 			normal: -plane_normal,
+			features: [closest_indices[0].filter(|_| single_closest).map(MeshFeature::Vertex), None],
 		})
 	} else {
 		None
@@ -565,7 +1043,7 @@ fn point_is_on_plane(point : &Vec3, plane_normal : &Vec3, plane_point : &Vec3) -
 /// Collides a single moving point with a polygon (that's confined to a plane).
 ///
 /// **WARNING:** This is not really meant to be used on its own. This is intended to be used in the mesh-to-mesh collision checking.
-fn collide_point_with_polygon(point_start : &Vec3, point_end : &Vec3, polygon : &Vec<Vec3>) -> Option<Collision> {
+pub fn collide_point_with_polygon(point_start : &Vec3, point_end : &Vec3, polygon : &Vec<Vec3>) -> Option<Collision> {
 	let point_delta = point_end - point_start;
 	// First: figure out when the point will collide with the (moving) plane.
 	// Then decide whether that point (or point movement) goes into the polygon.
@@ -602,6 +1080,7 @@ fn collide_point_with_polygon(point_start : &Vec3, point_end : &Vec3, polygon :
 				times: Range::single(closest_time),
 				position: closest_position,
 				normal: plane_normal,
+				features: [None, None],
 			})
 		} else {
 			None
@@ -650,6 +1129,7 @@ fn collide_point_with_polygon(point_start : &Vec3, point_end : &Vec3, polygon :
 				times: Range::single(time),
 				position: point,
 				normal: plane_normal,
+				features: [None, None],
 			})
 		} else {
 			None
@@ -657,37 +1137,49 @@ fn collide_point_with_polygon(point_start : &Vec3, point_end : &Vec3, polygon :
 	}
 }
 
-struct MeshPointPairs {
+/// Where a single mesh vertex starts and ends in world space.
+#[derive(Debug)]
+pub struct MeshPointPairs {
 	start : Vec3,
 	end : Vec3,
 }
 
-fn precompute_mesh_point_pairs(mesh : &InternalMeshCollider, start_orientation : &Orientation, end_orientation : &Orientation) -> Vec<MeshPointPairs> {
-	let mut transformed = Vec::with_capacity(mesh.vertices.len());
-	for point in &mesh.vertices {
-		let internal_position = mesh.position + point;
+fn precompute_mesh_point_pairs(vertices : &[Vec3], position : &Vec3, start_orientation : &Orientation, end_orientation : &Orientation, transformed : &mut Vec<MeshPointPairs>) {
+	transformed.clear();
+	for point in vertices {
+		let internal_position = position + point;
 		transformed.push(MeshPointPairs {
 			start: start_orientation.position_into_world(&internal_position),
 			end: end_orientation.position_into_world(&internal_position),
 		});
 	}
-	transformed
 }
 
-fn collide_mesh_points_with_mesh_faces(output : &mut EarliestCollisionAccumulator, mesh1_points : &Vec<MeshPointPairs>, mesh2 : &InternalMeshCollider, mesh2_points : &Vec<MeshPointPairs>, normal_factor : f32) {
-	let mut face_points = Vec::new();
-	let mut accumulator = EarliestCollisionAccumulator::new();
-	for points_info in mesh1_points {
-		for face in &mesh2.faces {
-			face_points.clear();
-			for index in face {
-				face_points.push((mesh2_points[*index].start + mesh2_points[*index].end) / 2.0);
-			}
+fn collide_mesh_points_with_mesh_faces(output : &mut EarliestCollisionAccumulator, mesh1_points : &[MeshPointPairs], mesh2 : &InternalMeshCollider, mesh2_points : &[MeshPointPairs], normal_factor : f32, face_points : &mut Vec<Vec3>) {
+	let mut accumulator = EarliestCollisionAccumulator::clustering(MESH_CLUSTER_TIME_TOLERANCE);
+	for (face_index, face) in mesh2.faces.iter().enumerate() {
+		// Work relative to the face (using where it starts and how its middle moves), so the face's movement is accounted for.
+		face_points.clear();
+		let mut face_movement = Vec3::zeros();
+		for index in face {
+			face_points.push(mesh2_points[*index].start);
+			face_movement += mesh2_points[*index].end - mesh2_points[*index].start;
+		}
+		face_movement /= face.len() as f32;
+		// Only worked out when needed, as it's kinda expensive.
+		let front_normal = if mesh2.one_sided { face_normal(face_points) } else { Vec3::zeros() };
+		for (point_index, points_info) in mesh1_points.iter().enumerate() {
+			if mesh2.one_sided && (points_info.start - face_points[0]).dot(&front_normal) < 0.0 { continue; }
 			accumulator.consider(collide_point_with_polygon(
 				&points_info.start,
-				&points_info.end,
-				&face_points,
-			));
+				&(points_info.end - face_movement),
+				face_points,
+			).map(|mut collision| {
+				// Move the hit back to where the face actually is at that time.
+				collision.position += face_movement * collision.times.min();
+				collision.features = [Some(MeshFeature::Vertex(point_index)), Some(MeshFeature::Face(face_index))];
+				collision
+			}));
 		}
 	}
 
@@ -710,28 +1202,33 @@ fn collide_mesh_points_with_mesh_faces(output : &mut EarliestCollisionAccumulato
 			collision.normal *= -1.0;
 		}
 		collision.normal *= normal_factor;
+		// A positive factor means the points are on the second mesh (and the faces on the first).
+		if 0.0 < normal_factor { collision.features.swap(0, 1); }
 		output.consider(Some(collision));
 	}
 }
 
-pub fn collide_mesh_with_mesh(mesh1 : &InternalMeshCollider, mesh1_start_orientation : &Orientation, mesh1_end_orientation : &Orientation, mesh2 : &InternalMeshCollider, mesh2_start_orientation : &Orientation, mesh2_end_orientation : &Orientation) -> Option<Collision> {
-	let mut accumulator = EarliestCollisionAccumulator::new();
-	let mesh1_points = precompute_mesh_point_pairs(mesh1, mesh1_start_orientation, mesh1_end_orientation);
-	let mesh2_points = precompute_mesh_point_pairs(mesh2, mesh2_start_orientation, mesh2_end_orientation);
+/// Collides two meshes, given where each of their vertices starts and ends in world space.
+///
+/// Corners that hit at the same time (on faces facing the same way) are clustered into one hit in their middle, so i.e. a box landing flat doesn't get spun around one of its corners.
+pub fn collide_mesh_with_mesh(mesh1 : &InternalMeshCollider, mesh1_points : &[MeshPointPairs], mesh2 : &InternalMeshCollider, mesh2_points : &[MeshPointPairs], face_points : &mut Vec<Vec3>) -> Option<Collision> {
+	let mut accumulator = EarliestCollisionAccumulator::clustering(MESH_CLUSTER_TIME_TOLERANCE);
 	// First check all the corners.
 	collide_mesh_points_with_mesh_faces(
 		&mut accumulator,
-		&mesh1_points,
-		&mesh2,
-		&mesh2_points,
+		mesh1_points,
+		mesh2,
+		mesh2_points,
 		-1.0,
+		face_points,
 	);
 	collide_mesh_points_with_mesh_faces(
 		&mut accumulator,
-		&mesh2_points,
-		&mesh1,
-		&mesh1_points,
+		mesh2_points,
+		mesh1,
+		mesh1_points,
 		1.0,
+		face_points,
 	);
 	// Then check if there are any edge-edge intersections. (TODO!)
 	accumulator.get()
@@ -774,6 +1271,34 @@ mod tests {
 			assert!((hit.position - Vec3::new(1.0, -1.0, 1.0)).magnitude() < EPSILON);
 			assert!((hit.normal - Vec3::new(0.0, -1.0, 0.0)).magnitude() < EPSILON);
 		}
+		{ // Coming in at an angle onto an inclined plane (through the origin) that's moving along its normal.
+			let normal = Vec3::new(1.0, 1.0, 0.0).normalize();
+			let hit = collide_sphere_with_plane(
+				1.0,
+				&Vec3::new(0.0, 5.0, 0.0),
+				&Vec3::new(2.0, -8.0, 0.0),
+				&Vec3::zeros(),
+				&normal,
+				&(normal * 0.5),
+			).unwrap();
+			let center = Vec3::new(0.0, 5.0, 0.0) + Vec3::new(2.0, -8.0, 0.0) * hit.times.min();
+			let plane_position = normal * 0.5 * hit.times.min();
+			assert!(((center - plane_position).dot(&normal) - 1.0).abs() < 1e-5);
+			assert!((hit.position - (center - normal)).magnitude() < 1e-5);
+			assert!((hit.position - plane_position).dot(&normal).abs() < 1e-5);
+		}
+		{ // Already overlapping at the start, while moving sideways: the contact is still on the plane (under the center).
+			let hit = collide_sphere_with_plane(
+				1.0,
+				&Vec3::new(0.0, 0.5, 0.0),
+				&Vec3::new(3.0, -1.0, 0.0),
+				&Vec3::zeros(),
+				&Vec3::y(),
+				&Vec3::zeros(),
+			).unwrap();
+			assert_eq!(hit.times.min(), 0.0);
+			assert!((hit.position - Vec3::new(0.0, 0.0, 0.0)).magnitude() < EPSILON);
+		}
 	}
 
 	#[test]
@@ -899,11 +1424,15 @@ mod tests {
 				&vertices,
 				&edges,
 				&faces,
+				None,
+				None,
 				&movement,
+				&mut CollisionScratch::default(),
 			).unwrap();
 			assert!((hit.times.min() - 0.5).abs() < EPSILON);
 			assert!((hit.position - Vec3::new(0.0, 1.0, 1.0)).magnitude() < EPSILON);
 			assert!((hit.normal - Vec3::new(0.0, -1.0, 0.0)).magnitude() < EPSILON);
+			assert_eq!(hit.features, [None, Some(MeshFeature::Vertex(0))]);
 		}
 		{ // The hit an edge.
 			let hit = collide_sphere_with_mesh(
@@ -914,11 +1443,15 @@ mod tests {
 				&vertices,
 				&edges,
 				&faces,
+				None,
+				None,
 				&movement,
+				&mut CollisionScratch::default(),
 			).unwrap();
 			assert!((hit.times.min() - 0.5).abs() < EPSILON);
 			assert!((hit.position - Vec3::new(0.0, -1.0, 1.0)).magnitude() < EPSILON);
 			assert!((hit.normal - Vec3::new(0.0, 1.0, 0.0)).magnitude() < EPSILON);
+			assert_eq!(hit.features, [None, Some(MeshFeature::Edge(1, 2))]);
 		}
 		{ // The hit the flat surface.
 			let hit = collide_sphere_with_mesh(
@@ -929,11 +1462,15 @@ mod tests {
 				&vertices,
 				&edges,
 				&faces,
+				None,
+				None,
 				&movement,
+				&mut CollisionScratch::default(),
 			).unwrap();
 			assert!((hit.times.min() - 0.5).abs() < EPSILON);
 			assert!((hit.position - Vec3::new(0.5, -0.5, 1.0)).magnitude() < EPSILON);
 			assert!((hit.normal - Vec3::new(0.0, 0.0, -1.0)).magnitude() < EPSILON);
+			assert_eq!(hit.features, [None, Some(MeshFeature::Face(0))]);
 		}
 		{ // The no hit case.
 			println!("Start!");
@@ -945,7 +1482,10 @@ mod tests {
 				&vertices,
 				&edges,
 				&faces,
+				None,
+				None,
 				&Vec3::new(0.0, 4.0, 0.0),
+				&mut CollisionScratch::default(),
 			);
 			println!("no hit? {:?}", hit);
 			assert!(hit.is_none());
@@ -953,29 +1493,83 @@ mod tests {
 	}
 
 
+	/// Verify hits on edges and corners between flat faces are treated as hitting the faces, and hits on a box's edge are kept between its faces' normals.
+	#[test]
+	fn check_smooth_mesh_normals() {
+		// Two squares on the y=0 plane (facing up) sharing the edge along x=0, then the two sides of a box's edge (facing +x and +y).
+		let vertices = vec![
+			Vec3::new(-1.0, 0.0, -1.0), Vec3::new(0.0, 0.0, -1.0), Vec3::new(1.0, 0.0, -1.0),
+			Vec3::new(-1.0, 0.0, 1.0), Vec3::new(0.0, 0.0, 1.0), Vec3::new(1.0, 0.0, 1.0),
+			Vec3::new(1.0, -1.0, -1.0), Vec3::new(1.0, -1.0, 1.0),
+		];
+		let faces = vec![vec![0, 3, 4, 1], vec![1, 4, 5, 2], vec![2, 5, 7, 6]];
+		let edges = vec![(0, 1), (1, 2), (0, 3), (1, 4), (2, 5), (3, 4), (4, 5), (5, 7), (6, 7), (2, 6)];
+		let adjacency = MeshAdjacency::new(vertices.len(), &edges, &faces);
+		assert_eq!(adjacency.edge_faces[3], vec![0, 1]);
+		assert_eq!(adjacency.edge_faces[4], vec![1, 2]);
+		assert!(adjacency.vertex_on_boundary.iter().all(|boundary| *boundary));
+		let mut corners = Vec::new();
+		let hit = |normal : Vec3| Collision { times: Range::single(0.0), position: Vec3::zeros(), normal: normal.normalize(), features: [None, None] };
+
+		// Hitting the flat edge from the side (i.e. a ball rolling across it) pushes straight up.
+		let mut collision = hit(Vec3::new(1.0, -0.2, 0.0));
+		smooth_edge_normal(&mut collision, &adjacency.edge_faces[3], &vertices, &faces, &mut corners);
+		assert!((collision.normal - Vec3::new(0.0, -1.0, 0.0)).magnitude() < EPSILON, "{:?}", collision.normal);
+		// Even from underneath.
+		let mut collision = hit(Vec3::new(1.0, 0.2, 0.0));
+		smooth_edge_normal(&mut collision, &adjacency.edge_faces[3], &vertices, &faces, &mut corners);
+		assert!((collision.normal - Vec3::new(0.0, 1.0, 0.0)).magnitude() < EPSILON, "{:?}", collision.normal);
+
+		// The box's edge can push anywhere between its faces' normals...
+		let mut collision = hit(Vec3::new(-1.0, -1.0, 0.0));
+		smooth_edge_normal(&mut collision, &adjacency.edge_faces[4], &vertices, &faces, &mut corners);
+		assert!((collision.normal - Vec3::new(-1.0, -1.0, 0.0).normalize()).magnitude() < EPSILON, "{:?}", collision.normal);
+		// ...but nowhere past them.
+		let mut collision = hit(Vec3::new(-1.0, 0.5, 0.0));
+		smooth_edge_normal(&mut collision, &adjacency.edge_faces[4], &vertices, &faces, &mut corners);
+		assert!((collision.normal - Vec3::new(-1.0, 0.0, 0.0)).magnitude() < EPSILON, "{:?}", collision.normal);
+
+		// Corners are only smoothed when they're in the middle of a flat area.
+		let flat_faces = vec![vec![0, 3, 4, 1], vec![1, 4, 5, 2]];
+		let mut flat_adjacency = MeshAdjacency::new(vertices.len(), &edges, &flat_faces);
+		flat_adjacency.vertex_on_boundary[1] = false;
+		let mut collision = hit(Vec3::new(1.0, -1.0, 0.0));
+		smooth_vertex_normal(&mut collision, 1, &flat_adjacency, &vertices, &flat_faces, &mut corners);
+		assert!((collision.normal - Vec3::new(0.0, -1.0, 0.0)).magnitude() < EPSILON, "{:?}", collision.normal);
+		let mut collision = hit(Vec3::new(1.0, -1.0, 0.0));
+		smooth_vertex_normal(&mut collision, 2, &adjacency, &vertices, &faces, &mut corners);
+		assert!((collision.normal - Vec3::new(1.0, -1.0, 0.0).normalize()).magnitude() < EPSILON, "{:?}", collision.normal);
+	}
+
 	#[test]
 	fn check_collide_mesh_with_plane() {
 		let vertices = vec![
 			Vec3::new(0.0, 0.0, 0.0),
 			Vec3::new(1.0, 1.0, 1.0),
 		];
+		let mut points = Vec::new();
+		precompute_mesh_point_pairs(
+			&vertices,
+			&Vec3::new(0.0, 0.0, 0.0),
+			&Orientation::new(
+				Vec3::new(0.0, 0.0, 0.0),
+				Vec3::zeros(),
+				Vec3::zeros(),
+			),
+			&Orientation::new(
+				Vec3::new(0.0, 0.0, 2.0),
+				Vec3::zeros(),
+				Vec3::zeros(),
+			),
+			&mut points,
+		);
 		{ // A clean hit.
 			let hit = collide_mesh_with_plane(
-				&vertices,
-				&Vec3::new(0.0, 0.0, 0.0),
-				&Orientation::new(
-					&Vec3::new(0.0, 0.0, 0.0),
-					&Vec3::zeros(),
-					&Vec3::zeros(),
-				),
-				&Orientation::new(
-					&Vec3::new(0.0, 0.0, 2.0),
-					&Vec3::zeros(),
-					&Vec3::zeros(),
-				),
+				&points,
 				&Vec3::new(0.0, 0.0, 2.0),
 				&Vec3::new(0.0, 0.0, 2.0),
 				&Vec3::new(0.0, 0.0,-1.0),
+				&mut CollisionScratch::default(),
 			).unwrap();
 			assert!((hit.times.min() - 0.5).abs() < EPSILON);
 			assert!((hit.position - Vec3::new(1.0, 1.0, 2.0)).magnitude() < EPSILON);
@@ -983,41 +1577,21 @@ mod tests {
 		}
 		{ // A miss.
 			let hit = collide_mesh_with_plane(
-				&vertices,
-				&Vec3::new(0.0, 0.0, 0.0),
-				&Orientation::new(
-					&Vec3::new(0.0, 0.0, 0.0),
-					&Vec3::zeros(),
-					&Vec3::zeros(),
-				),
-				&Orientation::new(
-					&Vec3::new(0.0, 0.0, 2.0),
-					&Vec3::zeros(),
-					&Vec3::zeros(),
-				),
+				&points,
 				&Vec3::new(0.0, 0.0, 2.0),
 				&Vec3::new(0.0, 0.0, 8.0),
 				&Vec3::new(0.0, 0.0,-1.0),
+				&mut CollisionScratch::default(),
 			);
 			assert!(hit.is_none());
 		}
 		{ // A hit due to being embedded.
 			let hit = collide_mesh_with_plane(
-				&vertices,
-				&Vec3::new(0.0, 0.0, 0.0),
-				&Orientation::new(
-					&Vec3::new(0.0, 0.0, 0.0),
-					&Vec3::zeros(),
-					&Vec3::zeros(),
-				),
-				&Orientation::new(
-					&Vec3::new(0.0, 0.0, 2.0),
-					&Vec3::zeros(),
-					&Vec3::zeros(),
-				),
+				&points,
 				&Vec3::new(0.0, 0.0,-10.0),
 				&Vec3::new(0.0, 0.0,-10.0),
 				&Vec3::new(0.0, 0.0,-1.0),
+				&mut CollisionScratch::default(),
 			).unwrap();
 			assert!((hit.times.min() - 0.0).abs() < EPSILON);
 			assert!((hit.position - Vec3::new(1.0, 1.0, 1.0)).magnitude() < EPSILON);
@@ -1102,6 +1676,214 @@ mod tests {
 			assert!((hit.position - Vec3::new(1.0, 0.0, 0.0)).magnitude() < EPSILON);
 		}
 	}
+
+	/// Verify a box landing flat on another is hit in the middle of its face, but a tilted one is hit on its lowest corner.
+	#[test]
+	fn check_collide_mesh_with_mesh() {
+		let small = InternalMeshCollider::new_from(&crate::mesh_collider::MeshCollider::new_cuboid(Vec3::repeat(0.5)).unwrap()).unwrap();
+		let large = InternalMeshCollider::new_from(&crate::mesh_collider::MeshCollider::new_cuboid(Vec3::repeat(2.0)).unwrap()).unwrap();
+		let at = |x : f32, y : f32, angle : f32| Orientation::new(Vec3::new(x, y, 0.0), Vec3::new(0.0, 0.0, angle), Vec3::zeros());
+		let floor = at(0.0, -2.0, 0.0);
+
+		let hit = collide(&small, &at(1.0, 4.0, 0.0), &at(1.0, 0.0, 0.0), &large, &floor, &floor).unwrap();
+		assert!((hit.times.min() - 0.875).abs() < 1e-4);
+		assert!((hit.position - Vec3::new(1.0, 0.0, 0.0)).magnitude() < 1e-4, "{:?}", hit.position);
+		assert!((hit.normal + Vec3::y()).magnitude() < EPSILON);
+		// One of the small box's corners hit the large one's top face (the fourth one made by new_cuboid()).
+		assert!(matches!(hit.features[0], Some(MeshFeature::Vertex(_))), "{:?}", hit.features);
+		assert_eq!(hit.features[1], Some(MeshFeature::Face(3)));
+		// The same from the other side.
+		let hit = collide(&large, &floor, &floor, &small, &at(1.0, 4.0, 0.0), &at(1.0, 0.0, 0.0)).unwrap();
+		assert!((hit.position - Vec3::new(1.0, 0.0, 0.0)).magnitude() < 1e-4, "{:?}", hit.position);
+		assert!((hit.normal - Vec3::y()).magnitude() < EPSILON);
+		assert_eq!(hit.features[0], Some(MeshFeature::Face(3)));
+		assert!(matches!(hit.features[1], Some(MeshFeature::Vertex(_))), "{:?}", hit.features);
+
+		let angle = 0.3;
+		let hit = collide(&small, &at(1.0, 4.0, angle), &at(1.0, 0.0, angle), &large, &floor, &floor).unwrap();
+		let lowest = at(1.0, 0.0, angle).position_into_world(&Vec3::new(-0.5, -0.5, 0.0));
+		assert!((hit.position.x - lowest.x).abs() < 1e-4, "{:?} {:?}", hit.position, lowest);
+		assert!(hit.position.y.abs() < 1e-4);
+	}
+
+	/// Verify one-sided meshes are only hit from the front, by both spheres and other meshes.
+	#[test]
+	fn check_one_sided_mesh() {
+		// A square floor facing up.
+		let mut source = crate::mesh_collider::MeshCollider::new();
+		source.add_face(&vec![
+			Vec3::new(-1.0, 0.0, -1.0),
+			Vec3::new(-1.0, 0.0, 1.0),
+			Vec3::new(1.0, 0.0, 1.0),
+			Vec3::new(1.0, 0.0, -1.0),
+		]);
+		let two_sided = InternalMeshCollider::new_from(&source).unwrap();
+		source.one_sided = true;
+		let one_sided = InternalMeshCollider::new_from(&source).unwrap();
+		let sphere = InternalSphereCollider::new_from(&crate::sphere_collider::SphereCollider::new(0.5)).unwrap();
+		let cube = InternalMeshCollider::new_from(&crate::mesh_collider::MeshCollider::new_cuboid(Vec3::repeat(0.25)).unwrap()).unwrap();
+		let at = |x : f32, y : f32| Orientation::new(Vec3::new(x, y, 0.0), Vec3::zeros(), Vec3::zeros());
+		let still = at(0.0, 0.0);
+
+		// Landing on it from above works either way.
+		for floor in [&two_sided, &one_sided].iter() {
+			let hit = collide(&sphere, &at(0.0, 2.0), &at(0.0, 0.0), floor, &still, &still).unwrap();
+			assert!((hit.normal + Vec3::y()).magnitude() < EPSILON, "{:?}", hit.normal);
+			assert!(collide(&cube, &at(0.0, 2.0), &at(0.0, 0.0), floor, &still, &still).is_some());
+		}
+		// But having slipped through it (or coming up from underneath, including at its edge) only hits the two-sided one.
+		for start in [at(0.0, -0.2), at(0.0, -2.0), at(1.2, -0.3)].iter() {
+			let end = Orientation::new(start.position + Vec3::new(0.0, 2.5, 0.0), Vec3::zeros(), Vec3::zeros());
+			assert!(collide(&sphere, start, &end, &two_sided, &still, &still).is_some());
+			assert!(collide(&sphere, start, &end, &one_sided, &still, &still).is_none());
+		}
+		assert!(collide(&cube, &at(0.0, -1.0), &at(0.0, 1.0), &two_sided, &still, &still).is_some());
+		assert!(collide(&cube, &at(0.0, -1.0), &at(0.0, 1.0), &one_sided, &still, &still).is_none());
+		assert!(collide(&one_sided, &still, &still, &cube, &at(0.0, -1.0), &at(0.0, 1.0)).is_none());
+	}
+
+	#[test]
+	fn check_collide_convex_with_convex() {
+		let cube = InternalAlignedBoxCollider::new_from(&crate::aligned_box_collider::AlignedBoxCollider::from_center_half_extents(Vec3::zeros(), Vec3::repeat(0.5))).unwrap();
+		let mesh_cube = InternalMeshCollider::new_from(&crate::mesh_collider::MeshCollider::new_cuboid(Vec3::repeat(0.5)).unwrap()).unwrap();
+		let at = |x : f32, y : f32, angle : f32| Orientation::new(Vec3::new(x, y, 0.0), Vec3::new(0.0, 0.0, angle), Vec3::zeros());
+
+		{ // Two boxes moving toward each other.
+			let hit = collide(&cube, &at(0.0, 0.0, 0.0), &at(2.0, 0.0, 0.0), &cube, &at(2.5, 0.2, 0.0), &at(2.5, 0.2, 0.0)).unwrap();
+			assert!((hit.times.min() - 0.75).abs() < 1e-3);
+			assert!((hit.normal - Vec3::x()).magnitude() < 1e-3);
+			assert!((hit.position.x - 2.0).abs() < 1e-3);
+		}
+		{ // A box and a mesh (going the other way).
+			let hit = collide(&mesh_cube, &at(0.0, 4.0, 0.0), &at(0.0, 0.0, 0.0), &cube, &at(0.0, 0.0, 0.0), &at(0.0, 0.0, 0.0)).unwrap();
+			assert!((hit.times.min() - 0.75).abs() < 1e-3);
+			assert!((hit.normal + Vec3::y()).magnitude() < 1e-3);
+		}
+		{ // Passing by each other.
+			assert!(collide(&cube, &at(0.0, 0.0, 0.0), &at(4.0, 0.0, 0.0), &cube, &at(2.0, 1.1, 0.0), &at(2.0, 1.1, 0.0)).is_none());
+		}
+		{ // Only touching because of the rotation.
+			let rotate = std::f32::consts::FRAC_PI_4;
+			assert!(collide(&cube, &at(0.0, 0.0, 0.0), &at(0.0, 0.0, 0.0), &cube, &at(1.1, 0.0, 0.0), &at(1.1, 0.0, rotate)).is_some());
+			assert!(collide(&cube, &at(0.0, 0.0, 0.0), &at(0.0, 0.0, 0.0), &cube, &at(1.3, 0.0, 0.0), &at(1.3, 0.0, rotate)).is_none());
+		}
+		{ // Already overlapping.
+			let hit = collide(&cube, &at(0.0, 0.0, 0.0), &at(0.0, 0.0, 0.0), &mesh_cube, &at(0.0, 0.9, 0.0), &at(0.0, 1.0, 0.0)).unwrap();
+			assert_eq!(hit.times.min(), 0.0);
+			assert!((hit.normal - Vec3::y()).magnitude() < 1e-3);
+		}
+	}
+
+	/// Verify aligned boxes use their own checks against each other (while lined up) and against planes.
+	#[test]
+	fn check_collide_aligned_boxes() {
+		let cube = InternalAlignedBoxCollider::new_from(&crate::aligned_box_collider::AlignedBoxCollider::from_center_half_extents(Vec3::zeros(), Vec3::repeat(0.5))).unwrap();
+		let floor = InternalPlaneCollider::new_from(&crate::plane_collider::PlaneCollider::new()).unwrap();
+		let at = |x : f32, y : f32| Orientation::new(Vec3::new(x, y, 0.0), Vec3::zeros(), Vec3::zeros());
+
+		{ // Resting right on top of another box.
+			let hit = collide(&cube, &at(0.0, 1.0), &at(0.0, 1.0), &cube, &at(0.0, 0.0), &at(0.0, 0.0)).unwrap();
+			assert_eq!(hit.times.min(), 0.0);
+			assert!((hit.normal + Vec3::y()).magnitude() < EPSILON);
+			assert!((hit.position - Vec3::new(0.0, 0.5, 0.0)).magnitude() < EPSILON);
+		}
+		{ // Only partly on top: the hit is in the middle of the part that touches.
+			let hit = collide(&cube, &at(0.0, 0.0), &at(0.0, 0.0), &cube, &at(0.5, 1.5), &at(0.5, 0.5)).unwrap();
+			assert!((hit.times.min() - 0.5).abs() < EPSILON);
+			assert!((hit.normal - Vec3::y()).magnitude() < EPSILON);
+			assert!((hit.position - Vec3::new(0.25, 0.5, 0.0)).magnitude() < EPSILON);
+		}
+		{ // Landing on the floor, from either side.
+			let hit = collide(&cube, &at(0.0, 2.5), &at(0.0, 0.5), &floor, &at(0.0, 0.0), &at(0.0, 0.0)).unwrap();
+			assert!((hit.times.min() - 1.0).abs() < 1e-3);
+			assert!((hit.normal + Vec3::y()).magnitude() < EPSILON);
+			assert!(hit.position.y.abs() < 1e-3);
+			let hit = collide(&floor, &at(0.0, 0.0), &at(0.0, 0.0), &cube, &at(0.0, 2.5), &at(0.0, 0.0)).unwrap();
+			assert!((hit.times.min() - 0.8).abs() < 1e-3);
+			assert!((hit.normal - Vec3::y()).magnitude() < EPSILON);
+		}
+		{ // Above the floor the whole time.
+			assert!(collide(&cube, &at(0.0, 2.5), &at(0.0, 1.0), &floor, &at(0.0, 0.0), &at(0.0, 0.0)).is_none());
+		}
+	}
+
+	#[test]
+	fn check_mesh_vertices_cached() {
+		let mut source = crate::mesh_collider::MeshCollider::new();
+		source.add_face(&vec![
+			Vec3::new(0.0, 0.0, 0.0),
+			Vec3::new(1.0, 0.0, 0.0),
+			Vec3::new(0.0, 1.0, 0.0),
+		]);
+		let collider = InternalMeshCollider::new_from(&source).unwrap();
+		let mesh = collider.downcast_ref::<InternalMeshCollider>().unwrap();
+		let handle = ColliderHandle::from_raw_parts(3, 0);
+		let here = Orientation::new(Vec3::zeros(), Vec3::zeros(), Vec3::zeros());
+		let there = Orientation::new(Vec3::new(5.0, 0.0, 0.0), Vec3::zeros(), Vec3::zeros());
+
+		let mut scratch = CollisionScratch::default();
+		let slot = scratch.mesh_vertices(Some(handle), 0, mesh, &here, &there);
+		assert!((scratch.meshes[slot].starts[1] - Vec3::new(1.0, 0.0, 0.0)).magnitude() < EPSILON);
+		assert!((scratch.meshes[slot].points[1].end - Vec3::new(6.0, 0.0, 0.0)).magnitude() < EPSILON);
+		{ // Asking again re-uses the last result (even if the orientations are different).
+			let again = scratch.mesh_vertices(Some(handle), 0, mesh, &there, &there);
+			assert_eq!(slot, again);
+			assert!((scratch.meshes[slot].starts[1] - Vec3::new(1.0, 0.0, 0.0)).magnitude() < EPSILON);
+		}
+		{ // Until it's told to forget them.
+			scratch.forget_mesh_vertices();
+			scratch.mesh_vertices(Some(handle), 0, mesh, &there, &there);
+			assert!((scratch.meshes[slot].starts[1] - Vec3::new(6.0, 0.0, 0.0)).magnitude() < EPSILON);
+		}
+		{ // Without a handle, it's always recalculated.
+			let first = scratch.mesh_vertices(None, 1, mesh, &here, &here);
+			let second = scratch.mesh_vertices(None, 1, mesh, &there, &there);
+			assert_eq!(first, second);
+			assert!((scratch.meshes[second].starts[1] - Vec3::new(6.0, 0.0, 0.0)).magnitude() < EPSILON);
+		}
+	}
+
+	/// Verify spheres and other convex shapes land on a triangle mesh's front, but pass through its back.
+	#[test]
+	fn check_collide_with_tri_mesh() {
+		use crate::tri_mesh_collider::TriMeshCollider;
+		use crate::sphere_collider::SphereCollider;
+		use crate::aligned_box_collider::AlignedBoxCollider;
+		// A 4x4 floor facing up, made of two triangles.
+		let floor = TriMeshCollider::from_triangles(
+			vec![Vec3::new(-2.0, 0.0, -2.0), Vec3::new(-2.0, 0.0, 2.0), Vec3::new(2.0, 0.0, 2.0), Vec3::new(2.0, 0.0, -2.0)],
+			vec![[0, 1, 2], [0, 2, 3]],
+		).unwrap();
+		let floor = InternalTriMeshCollider::new_from(&floor).unwrap();
+		let still = Orientation::new(Vec3::zeros(), Vec3::zeros(), Vec3::zeros());
+		let at = |x : f32, y : f32, z : f32| Orientation::new(Vec3::new(x, y, z), Vec3::zeros(), Vec3::zeros());
+		let sphere = InternalSphereCollider::new_from(&SphereCollider::new(0.5)).unwrap();
+
+		// Falling onto the front.
+		let hit = collide(&sphere, &at(0.3, 2.0, 0.1), &at(0.3, 0.0, 0.1), &floor, &still, &still).unwrap();
+		assert!((hit.times.min() - 0.75).abs() < EPSILON);
+		assert!((hit.position - Vec3::new(0.3, 0.0, 0.1)).magnitude() < EPSILON);
+		assert!((hit.normal - Vec3::new(0.0, -1.0, 0.0)).magnitude() < EPSILON);
+		let hit = collide(&floor, &still, &still, &sphere, &at(0.3, 2.0, 0.1), &at(0.3, 0.0, 0.1)).unwrap();
+		assert!((hit.normal - Vec3::new(0.0, 1.0, 0.0)).magnitude() < EPSILON);
+		// Rolling across the edge between the triangles only ever pushes up.
+		let hit = collide(&sphere, &at(-1.0, 0.49, 1.0), &at(1.0, 0.49, -1.0), &floor, &still, &still).unwrap();
+		assert!((hit.normal - Vec3::new(0.0, -1.0, 0.0)).magnitude() < EPSILON);
+		// Coming up from underneath goes right through.
+		assert!(collide(&sphere, &at(0.3, -2.0, 0.1), &at(0.3, 0.0, 0.1), &floor, &still, &still).is_none());
+		// Moving the floor moves where it's hit.
+		let hit = collide(&sphere, &at(0.3, 2.0, 0.1), &at(0.3, 0.0, 0.1), &floor, &at(0.0, 0.5, 0.0), &at(0.0, 0.5, 0.0)).unwrap();
+		assert!((hit.times.min() - 0.5).abs() < EPSILON);
+		assert!((hit.position - Vec3::new(0.3, 0.5, 0.1)).magnitude() < EPSILON);
+
+		// Other convex shapes also land on the front (in the middle of where they touch).
+		let cube = InternalAlignedBoxCollider::new_from(&AlignedBoxCollider::from_center_half_extents(Vec3::zeros(), Vec3::repeat(0.5))).unwrap();
+		let hit = collide(&cube, &at(0.3, 2.0, 0.1), &at(0.3, 0.0, 0.1), &floor, &still, &still).unwrap();
+		assert!((hit.times.min() - 0.75).abs() < 0.01);
+		assert!((hit.normal - Vec3::new(0.0, -1.0, 0.0)).magnitude() < 0.01);
+		assert!(hit.position.y.abs() < 0.01);
+		assert!(collide(&cube, &at(0.3, -2.0, 0.1), &at(0.3, 0.0, 0.1), &floor, &still, &still).is_none());
+	}
 }
 
 /// Collide a sphere with an axis-aligned box.
@@ -1135,10 +1917,10 @@ fn collide_sphere_with_aligned_box(radius : f32, center : &Vec3, movement : &Vec
 		let x_overlap_times = x_broad_overlap_times.intersect(&y_narrow_overlap_times).intersect(&z_narrow_overlap_times);
 		let y_overlap_times = x_narrow_overlap_times.intersect(&y_broad_overlap_times).intersect(&z_narrow_overlap_times);
 		let z_overlap_times = x_narrow_overlap_times.intersect(&y_narrow_overlap_times).intersect(&z_broad_overlap_times);
-		// Then combine everything into one value.
-		let mut minimum_times = x_overlap_times.contain(&y_overlap_times).contain(&z_overlap_times);
-		// Must be between zero and one.
-		minimum_times = minimum_times.intersect(&Range::range(0.0, 1.0));
+		// Then combine everything, keeping any separate windows apart (so the gap between them isn't counted as touching).
+		let overlap_times = IntervalSet::from_range(&x_overlap_times).union(&y_overlap_times).union(&z_overlap_times);
+		// Must be between zero and one. Only the first window matters.
+		let minimum_times = overlap_times.intersect(&Range::range(0.0, 1.0)).first();
 		// Then use the time and normal to submit a full Collision object.
 		if !minimum_times.is_empty() {
 			let center_at_collision = center + movement * minimum_times.min();
@@ -1165,6 +1947,7 @@ fn collide_sphere_with_aligned_box(radius : f32, center : &Vec3, movement : &Vec
 				times : minimum_times,
 				position: center_at_collision + normal * radius,
 				normal,
+				features: [None, None],
 			}));
 		}
 	}
@@ -1213,6 +1996,49 @@ fn collide_sphere_with_aligned_box(radius : f32, center : &Vec3, movement : &Vec
 }
 
 
+/// Detect when and where an aligned box moving by `movement1` hits another (still) aligned box, if ever. Both are given by their corners in the same space, so must be lined up with each other.
+///
+/// The boxes count as touching once they're within `margin` of each other.
+fn collide_aligned_box_with_aligned_box(min1 : &Vec3, max1 : &Vec3, movement1 : &Vec3, margin : f32, min2 : &Vec3, max2 : &Vec3) -> Option<Collision> {
+	// Flatten things along each axis separately. The boxes only touch while they overlap along all three at once.
+	let mut times = Range::range(0.0, 1.0);
+	for axis in 0..3 {
+		let range1 = Range::range(min1[axis] - margin, max1[axis] + margin);
+		let range2 = Range::range(min2[axis], max2[axis]);
+		times = times.intersect(&range2.linear_overlap(&range1, movement1[axis]));
+	}
+	if times.is_empty() {
+		return None;
+	}
+	// The normal is along whichever axis they overlap the least on (when they first touch). That's the one they just came together on, or the quickest way apart if they started out overlapping.
+	let offset = movement1 * times.min();
+	let (lower1, upper1) = (min1.inf(max1) + offset, min1.sup(max1) + offset);
+	let (lower2, upper2) = (min2.inf(max2), min2.sup(max2));
+	let mut axis = 0;
+	let mut least_overlap = f32::INFINITY;
+	let mut position = Vec3::zeros();
+	for index in 0..3 {
+		let overlap_min = (lower1[index] - margin).max(lower2[index]);
+		let overlap_max = (upper1[index] + margin).min(upper2[index]);
+		position[index] = 0.5 * (overlap_min + overlap_max);
+		if overlap_max - overlap_min < least_overlap {
+			least_overlap = overlap_max - overlap_min;
+			axis = index;
+		}
+	}
+	// Points off of the first box, so towards the second one's side of the contact. The hit is on the second box's face.
+	let direction = if lower1[axis] + upper1[axis] <= lower2[axis] + upper2[axis] { 1.0 } else { -1.0 };
+	let mut normal = Vec3::zeros();
+	normal[axis] = direction;
+	position[axis] = if 0.0 < direction { lower2[axis] } else { upper2[axis] };
+	Some(Collision {
+		times,
+		position,
+		normal,
+		features: [None, None],
+	})
+}
+
 #[cfg(test)]
 mod tests2 {
 	use crate::consts::EPSILON;
@@ -1,7 +1,13 @@
 use crate::types::{EntityHandle, Vec3};
+use crate::contact_pair::ContactPair;
+use crate::mesh_feature::MeshFeature;
 
+/// A single collision that happened during the last `step()`.
+///
+/// The first entity is always the one with the lower handle, so the same pair of entities is always reported in the same order.
+#[derive(Debug, Clone)]
 pub struct CollisionRecord {
-	/// The first entity in the collision pair.
+	/// The first entity in the collision pair (i.e. the one with the lower handle).
 	pub first_entity : EntityHandle,
 	/// The second entity in the collision pair.
 	pub second_entity : EntityHandle,
@@ -9,11 +15,56 @@ pub struct CollisionRecord {
 	pub position : Vec3,
 	/// The time when the collision happened. (The time `0.0` is the start of the `step()` call.)
 	pub time : f32,
-	/// The collision normal. **Points off of the first entity**.
+	/// The collision normal. **Points off of the first entity**. Use [CollisionRecord::normal_for] to get it relative to a specific entity.
 	pub normal : Vec3,
+	/// The [surface_tag](crate::SphereCollider::surface_tag) of the first entity's collider.
+	pub first_surface_tag : u32,
+	/// The [surface_tag](crate::SphereCollider::surface_tag) of the second entity's collider.
+	pub second_surface_tag : u32,
+	/// Which part of the first entity's collider was hit, if it's a [crate::MeshCollider] or [crate::TriMeshCollider]. i.e. for giving each face its own surface type, or tracking down bad geometry.
+	///
+	/// This is None for other colliders, and for hits where the exact part isn't known (like a mesh landing flat on a plane, or a box hitting a mesh). When several hits were averaged into one, it's whichever of them was found first.
+	pub first_feature : Option<MeshFeature>,
+	/// Which part of the second entity's collider was hit. See [CollisionRecord::first_feature].
+	pub second_feature : Option<MeshFeature>,
 
 	/// The collision's restitution coefficient.
 	pub restitution_coefficient : f32,
 	/// The magnitude of the resulting impulse.
 	pub impulse_magnitude : f32,
-}
\ No newline at end of file
+}
+
+impl CollisionRecord {
+	/// Gets the other entity in the collision. Returns None if the given entity isn't a part of it.
+	pub fn other_entity(&self, entity : EntityHandle) -> Option<EntityHandle> {
+		if entity == self.first_entity {
+			Some(self.second_entity)
+		} else if entity == self.second_entity {
+			Some(self.first_entity)
+		} else {
+			None
+		}
+	}
+
+	/// Gets the collision normal so it points off of the given entity (i.e. towards the other one). Returns None if the given entity isn't a part of the collision.
+	pub fn normal_for(&self, entity : EntityHandle) -> Option<Vec3> {
+		if entity == self.first_entity {
+			Some(self.normal)
+		} else if entity == self.second_entity {
+			Some(-self.normal)
+		} else {
+			None
+		}
+	}
+
+	/// Gets this collision as seen from the given entity. Returns None if the given entity isn't a part of it.
+	pub fn relative_to(&self, entity : EntityHandle) -> Option<ContactPair> {
+		Some(ContactPair {
+			entity,
+			other_entity: self.other_entity(entity)?,
+			position: self.position,
+			normal: self.normal_for(entity)?,
+			impulse_magnitude: self.impulse_magnitude,
+		})
+	}
+}
@@ -1,3 +1,6 @@
 
 /// An epsilon term for handling small floating point values.
 pub const EPSILON : f32 = 1e-6;
+
+/// How far off of a surface that a point (i.e. a soft body or cloth particle) is put after it hits something. Keeps points from starting their next move exactly on the surface.
+pub const SURFACE_OFFSET : f32 = 0.001;
@@ -0,0 +1,63 @@
+use crate::types::{EntityHandle, Vec3};
+use crate::contact_pair::ContactPair;
+
+/// A summary of how two entities pushed on each other during the last `step()`.
+///
+/// This is a cache that's rebuilt every step. Pairs of entities that have gone to sleep while touching keep their last contact (since they're no longer being simulated, but are still pushing on each other).
+#[derive(Debug, Clone)]
+pub struct Contact {
+	/// The first entity in the contact pair (i.e. the one with the lower handle).
+	pub first_entity : EntityHandle,
+	/// The second entity in the contact pair.
+	pub second_entity : EntityHandle,
+	/// The (impulse-weighted) average position of all collisions between the pair.
+	pub position : Vec3,
+	/// The contact normal. **Points off of the first entity**. Use [Contact::normal_for] to get it relative to a specific entity.
+	pub normal : Vec3,
+	/// The total magnitude of all impulses between the pair.
+	pub impulse_magnitude : f32,
+	/// The approximate magnitude of the normal force between the pair. This is just the total impulse divided by the step's `dt`.
+	///
+	/// For objects resting on each other this is (roughly) the weight being supported.
+	pub force_magnitude : f32,
+}
+
+impl Contact {
+	/// Checks whether this contact is between the given two entities (in either order).
+	pub fn is_between(&self, first : EntityHandle, second : EntityHandle) -> bool {
+		(self.first_entity == first && self.second_entity == second) || (self.first_entity == second && self.second_entity == first)
+	}
+
+	/// Gets the other entity in the contact. Returns None if the given entity isn't a part of it.
+	pub fn other_entity(&self, entity : EntityHandle) -> Option<EntityHandle> {
+		if entity == self.first_entity {
+			Some(self.second_entity)
+		} else if entity == self.second_entity {
+			Some(self.first_entity)
+		} else {
+			None
+		}
+	}
+
+	/// Gets the contact normal so it points off of the given entity (i.e. towards the other one). Returns None if the given entity isn't a part of the contact.
+	pub fn normal_for(&self, entity : EntityHandle) -> Option<Vec3> {
+		if entity == self.first_entity {
+			Some(self.normal)
+		} else if entity == self.second_entity {
+			Some(-self.normal)
+		} else {
+			None
+		}
+	}
+
+	/// Gets this contact as seen from the given entity. Returns None if the given entity isn't a part of it.
+	pub fn relative_to(&self, entity : EntityHandle) -> Option<ContactPair> {
+		Some(ContactPair {
+			entity,
+			other_entity: self.other_entity(entity)?,
+			position: self.position,
+			normal: self.normal_for(entity)?,
+			impulse_magnitude: self.impulse_magnitude,
+		})
+	}
+}
@@ -0,0 +1,176 @@
+use crate::types::EntityHandle;
+use crate::body_type::BodyType;
+use crate::contact::Contact;
+
+/// An entity in a [ContactGraph].
+#[derive(Debug, Clone)]
+pub struct ContactNode {
+	/// The entity.
+	pub entity : EntityHandle,
+
+	/// How the entity actually moves. Unlike [crate::Entity::body_type], dynamic entities that can't be moved (i.e. have infinite mass) show up as kinematic here.
+	pub body_type : BodyType,
+
+	/// Whether the entity was asleep after the step.
+	pub asleep : bool,
+
+	/// Indices (into [ContactGraph::edges]) of every contact this entity is a part of.
+	pub edges : Vec<usize>,
+}
+
+/// A contact in a [ContactGraph], between two of its nodes.
+#[derive(Debug, Clone)]
+pub struct ContactEdge {
+	/// The index (into [ContactGraph::nodes]) of the contact's `first_entity`.
+	pub first_node : usize,
+
+	/// The index (into [ContactGraph::nodes]) of the contact's `second_entity`.
+	pub second_node : usize,
+
+	/// The contact itself (with its point, normal, and impulse).
+	pub contact : Contact,
+}
+
+/// Everything touching everything else after a step: entities are nodes, and the contacts between them are edges. See [crate::PhysicsSystem::contact_graph].
+///
+/// Meant for prototyping other constraint solvers on top of this crate's collision detection. Edges can be matched up with the last step's graph (with [ContactGraph::find_edge]) to warm start a solver with the impulses that were used then.
+#[derive(Debug, Clone, Default)]
+pub struct ContactGraph {
+	/// Every entity, sorted by handle.
+	pub nodes : Vec<ContactNode>,
+
+	/// Every contact, in the same order as [crate::PhysicsSystem::contacts].
+	pub edges : Vec<ContactEdge>,
+}
+
+impl ContactGraph {
+	/// Builds a graph from every entity (with how it moves and whether it's asleep) and the contacts between them. Contacts involving entities that aren't given are left out.
+	pub fn new(entities : &[(EntityHandle, BodyType, bool)], contacts : &[Contact]) -> ContactGraph {
+		let mut nodes : Vec<ContactNode> = entities.iter().map(|(entity, body_type, asleep)| ContactNode {
+			entity: *entity,
+			body_type: *body_type,
+			asleep: *asleep,
+			edges: Vec::new(),
+		}).collect();
+		nodes.sort_by_key(|node| node.entity);
+		let mut graph = ContactGraph { nodes, edges: Vec::new() };
+		for contact in contacts {
+			let (first_node, second_node) = match (graph.find_node(contact.first_entity), graph.find_node(contact.second_entity)) {
+				(Some(first_node), Some(second_node)) => (first_node, second_node),
+				_ => continue,
+			};
+			let index = graph.edges.len();
+			graph.nodes[first_node].edges.push(index);
+			graph.nodes[second_node].edges.push(index);
+			graph.edges.push(ContactEdge { first_node, second_node, contact: contact.clone() });
+		}
+		graph
+	}
+
+	/// Gets the index of an entity's node. Returns None if it isn't in the graph.
+	pub fn find_node(&self, entity : EntityHandle) -> Option<usize> {
+		self.nodes.binary_search_by_key(&entity, |node| node.entity).ok()
+	}
+
+	/// Gets the edge (contact) between two entities (in either order). Returns None if they aren't touching.
+	pub fn find_edge(&self, first : EntityHandle, second : EntityHandle) -> Option<&ContactEdge> {
+		let node = &self.nodes[self.find_node(first)?];
+		node.edges.iter().map(|index| &self.edges[*index]).find(|edge| edge.contact.is_between(first, second))
+	}
+
+	/// Gets the indices of every node touching the given one.
+	pub fn neighbors(&self, node : usize) -> impl Iterator<Item = usize> + '_ {
+		self.nodes[node].edges.iter().map(move |index| {
+			let edge = &self.edges[*index];
+			if edge.first_node == node { edge.second_node } else { edge.first_node }
+		})
+	}
+
+	/// Splits the dynamic nodes into islands: groups that are connected by contacts, so can be solved separately.
+	///
+	/// Islands aren't joined together through non-dynamic nodes (i.e. two things resting on the floor are separate), and those nodes aren't in any island. Each island is a list of node indices, in increasing order.
+	pub fn islands(&self) -> Vec<Vec<usize>> {
+		let mut islands = Vec::new();
+		let mut visited = vec![false; self.nodes.len()];
+		for start in 0..self.nodes.len() {
+			if visited[start] || BodyType::Dynamic != self.nodes[start].body_type { continue; }
+			visited[start] = true;
+			let mut island = Vec::new();
+			let mut pending = vec![start];
+			while let Some(node) = pending.pop() {
+				island.push(node);
+				for neighbor in self.neighbors(node) {
+					if !visited[neighbor] && BodyType::Dynamic == self.nodes[neighbor].body_type {
+						visited[neighbor] = true;
+						pending.push(neighbor);
+					}
+				}
+			}
+			island.sort_unstable();
+			islands.push(island);
+		}
+		islands
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::types::Vec3;
+	use crate::entity::Entity;
+	use crate::sphere_collider::SphereCollider;
+	use crate::plane_collider::PlaneCollider;
+	use crate::collider_wrapper::ColliderWrapper;
+	use crate::gravity_generator::GravityGenerator;
+	use crate::physics_system::PhysicsSystem;
+
+	/// Verify a stack is one island (not joined to another through the floor), and its contacts can be looked up both ways.
+	#[test]
+	fn stack_and_ball() {
+		let mut system = PhysicsSystem::new();
+		let floor = {
+			let mut entity = Entity::new();
+			entity.body_type = BodyType::Static;
+			let entity_handle = system.add_entity(entity).unwrap();
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(PlaneCollider::new())).unwrap();
+			system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		let mut add_ball = |position : Vec3| {
+			let mut entity = Entity::new();
+			entity.position = position;
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 1.0;
+			sphere.restitution_coefficient = 0.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		let stack = [add_ball(Vec3::new(0.0, 0.5, 0.0)), add_ball(Vec3::new(0.0, 1.5, 0.0))];
+		let ball = add_ball(Vec3::new(5.0, 0.5, 0.0));
+		system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -10.0, 0.0)))).unwrap();
+		for _ in 0..10 {
+			system.step(1.0 / 60.0);
+		}
+		let graph = system.contact_graph();
+		assert_eq!(graph.nodes.len(), 4);
+		assert_eq!(graph.edges.len(), 3);
+
+		let floor = graph.find_node(floor).unwrap();
+		assert_eq!(graph.nodes[floor].body_type, BodyType::Static);
+		assert_eq!(graph.nodes[floor].edges.len(), 2);
+		let (bottom, top) = (graph.find_node(stack[0]).unwrap(), graph.find_node(stack[1]).unwrap());
+		assert_eq!(graph.neighbors(top).collect::<Vec<usize>>(), vec![bottom]);
+		let edge = graph.find_edge(stack[1], stack[0]).unwrap();
+		assert!(graph.find_edge(stack[0], stack[1]).is_some());
+		assert!(0.0 < edge.contact.impulse_magnitude);
+		assert!(graph.find_edge(stack[1], ball).is_none());
+
+		let mut islands = graph.islands();
+		islands.sort();
+		let mut expected = vec![vec![bottom.min(top), bottom.max(top)], vec![graph.find_node(ball).unwrap()]];
+		expected.sort();
+		assert_eq!(islands, expected);
+	}
+}
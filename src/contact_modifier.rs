@@ -0,0 +1,47 @@
+use crate::types::{Vec3, EntityHandle, ColliderHandle};
+
+use core::fmt::Debug;
+use downcast_rs::{Downcast, impl_downcast};
+
+/// A contact that's been found (but not yet responded to) which a [ContactModifier] can adjust.
+#[derive(Debug, Clone)]
+pub struct ModifiableContact {
+	/// The first entity in the contact.
+	pub first_entity : EntityHandle,
+	/// The second entity in the contact.
+	pub second_entity : EntityHandle,
+	/// The collider on the first entity that's in the contact.
+	pub first_collider : ColliderHandle,
+	/// The collider on the second entity that's in the contact.
+	pub second_collider : ColliderHandle,
+	/// When the contact happens. This is a fraction (from 0.0 to 1.0) of the time left to simulate in the current `step()` iteration. Changing this does nothing.
+	pub time : f32,
+	/// The point of contact in world space.
+	pub position : Vec3,
+	/// The contact normal. **Points off of the first entity**. Will be re-normalized after modification.
+	pub normal : Vec3,
+	/// The combined restitution coefficient.
+	pub restitution_coefficient : f32,
+	/// The combined static friction coefficient.
+	pub static_friction_coefficient : f32,
+	/// The combined dynamic friction coefficient.
+	pub dynamic_friction_coefficient : f32,
+	/// The combined friction threshold.
+	pub friction_threshold : f32,
+}
+
+/// A callback that can change (or cancel) contacts involving a specific collider.
+///
+/// This is called after a contact has been found, but before it's been decided whether it will be responded to.
+/// So this can be called several times per collider pair per `step()` (each time the motion of either entity changes).
+pub trait ContactModifier : Downcast + Debug + Send + Sync {
+	/// Adjusts the given contact. Returning `false` cancels the contact entirely (so the pair will pass through each other).
+	fn modify(&mut self, contact : &mut ModifiableContact) -> bool;
+
+	/// Makes a boxed copy, for when the whole system is cloned.
+	///
+	/// Modifiers that can't be copied should return `None` (which is the default). They're then left out of the copied system.
+	fn clone_boxed(&self) -> Option<Box<dyn ContactModifier>> { None }
+}
+
+impl_downcast!(ContactModifier);
@@ -0,0 +1,18 @@
+use crate::types::{EntityHandle, Vec3};
+
+/// A collision (or contact) seen from one of the two entities involved.
+///
+/// Use [crate::CollisionRecord::relative_to] or [crate::Contact::relative_to] to make one. Unlike those, there's no question of which entity is "first": the normal always points off of `entity`.
+#[derive(Debug, Clone)]
+pub struct ContactPair {
+	/// The entity this is relative to.
+	pub entity : EntityHandle,
+	/// The entity that `entity` hit.
+	pub other_entity : EntityHandle,
+	/// The point where they touched.
+	pub position : Vec3,
+	/// The normal. **Points off of `entity`** (i.e. towards `other_entity`).
+	pub normal : Vec3,
+	/// The magnitude of the impulse between them.
+	pub impulse_magnitude : f32,
+}
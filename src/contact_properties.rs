@@ -0,0 +1,55 @@
+use crate::contact_modifier::ModifiableContact;
+
+/// Values to use for contacts between a specific pair of materials (or entities), in place of combining the two colliders' own values.
+///
+/// This is for when a pair has measured values (e.g. rubber bouncing off of metal) that no combination of the individual colliders' values would give. See [crate::PhysicsSystem::set_material_pair_properties] and [crate::PhysicsSystem::set_entity_pair_properties].
+///
+/// Anything left as None still uses the colliders' combined value.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContactProperties {
+	/// The restitution coefficient. Must not be negative.
+	///
+	/// Defaults to None.
+	pub restitution_coefficient : Option<f32>,
+
+	/// The static friction coefficient. Must not be negative.
+	///
+	/// Defaults to None.
+	pub static_friction_coefficient : Option<f32>,
+
+	/// The dynamic friction coefficient. Must not be negative.
+	///
+	/// Defaults to None.
+	pub dynamic_friction_coefficient : Option<f32>,
+
+	/// The friction threshold. Must not be negative.
+	///
+	/// Defaults to None.
+	pub friction_threshold : Option<f32>,
+}
+
+impl ContactProperties {
+	/// Creates an instance that doesn't replace anything.
+	pub fn new() -> ContactProperties {
+		ContactProperties {
+			restitution_coefficient: None,
+			static_friction_coefficient: None,
+			dynamic_friction_coefficient: None,
+			friction_threshold: None,
+		}
+	}
+
+	/// If this is in a valid state.
+	pub fn is_valid(&self) -> bool {
+		[self.restitution_coefficient, self.static_friction_coefficient, self.dynamic_friction_coefficient, self.friction_threshold].iter().flatten().all(|value| value.is_finite() && 0.0 <= *value)
+	}
+
+	/// Replaces the contact's values with any that are set here.
+	pub(crate) fn apply_to(&self, contact : &mut ModifiableContact) {
+		if let Some(value) = self.restitution_coefficient { contact.restitution_coefficient = value; }
+		if let Some(value) = self.static_friction_coefficient { contact.static_friction_coefficient = value; }
+		if let Some(value) = self.dynamic_friction_coefficient { contact.dynamic_friction_coefficient = value; }
+		if let Some(value) = self.friction_threshold { contact.friction_threshold = value; }
+	}
+}
@@ -0,0 +1,335 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::consts::EPSILON;
+use crate::types::Vec3;
+use crate::mesh_collider::face_normal;
+
+/// The settings for [crate::MeshCollider::decompose_convex].
+#[derive(Debug, Clone, Copy)]
+pub struct ConvexDecompositionParams {
+	/// How far (in the mesh's local units) a convex piece is allowed to stick out of the original mesh.
+	///
+	/// Defaults to `0.01`.
+	pub max_concavity : f32,
+
+	/// The most pieces to split into. Pieces keep getting merged (even past `max_concavity`) until there are at most this many. Must be at least one.
+	///
+	/// Defaults to `16`.
+	pub max_hulls : usize,
+}
+
+impl ConvexDecompositionParams {
+	/// Creates an instance with all values at default.
+	pub fn new() -> ConvexDecompositionParams {
+		ConvexDecompositionParams {
+			max_concavity: 0.01,
+			max_hulls: 16,
+		}
+	}
+
+	/// If this is in a valid state.
+	pub fn is_valid(&self) -> bool {
+		0.0 <= self.max_concavity && 1 <= self.max_hulls
+	}
+}
+
+/// A plane on the surface of a convex hull.
+#[derive(Debug, Clone, Copy)]
+struct HullFace {
+	/// The corners, as indices into the points the hull was made from. These wind counter-clockwise when seen from outside.
+	corners : [usize; 3],
+	/// The normal (pointing out of the hull).
+	normal : Vec3,
+	/// How far the plane is from the origin along the normal.
+	offset : f32,
+}
+
+impl HullFace {
+	/// Makes a face, using `fallback_normal` if the corners are in a straight line.
+	fn new(points : &[Vec3], corners : [usize; 3], fallback_normal : &Vec3) -> HullFace {
+		let [first, second, third] = corners;
+		let normal = (points[second] - points[first]).cross(&(points[third] - points[first]));
+		let normal = if EPSILON < normal.magnitude() { normal.normalize() } else { *fallback_normal };
+		HullFace { corners, normal, offset: normal.dot(&points[first]) }
+	}
+
+	/// How far the point is in front of the face (negative means behind it).
+	fn distance(&self, point : &Vec3) -> f32 {
+		self.normal.dot(point) - self.offset
+	}
+}
+
+/// Finds the convex hull of the given points, as triangles (indices into `points`, wound counter-clockwise when seen from outside).
+///
+/// Returns None if the points don't enclose any volume (i.e. they're all on a plane or line).
+pub fn convex_hull(points : &[Vec3]) -> Option<Vec<[usize; 3]>> {
+	hull_faces(points).map(|faces| faces.iter().map(|face| face.corners).collect())
+}
+
+/// How far two points have to be apart to not be considered the same, for a set of points.
+fn tolerance(points : &[Vec3]) -> f32 {
+	let mut size : f32 = 0.0;
+	for point in points {
+		size = size.max(point.amax());
+	}
+	EPSILON + size * 1e-5
+}
+
+/// Does the work for [convex_hull], by adding each point to the hull one at a time.
+fn hull_faces(points : &[Vec3]) -> Option<Vec<HullFace>> {
+	if points.len() < 4 { return None; }
+	let tolerance = tolerance(points);
+	// Start with the biggest tetrahedron that's easy to find.
+	let first = (0..points.len()).min_by(|a, b| points[*a].x.total_cmp(&points[*b].x)).unwrap();
+	let second = (0..points.len()).max_by(|a, b| (points[*a] - points[first]).magnitude().total_cmp(&(points[*b] - points[first]).magnitude())).unwrap();
+	let axis = points[second] - points[first];
+	if axis.magnitude() < tolerance { return None; }
+	let line_distance = |index : &usize| (points[*index] - points[first]).cross(&axis).magnitude() / axis.magnitude();
+	let third = (0..points.len()).max_by(|a, b| line_distance(a).total_cmp(&line_distance(b))).unwrap();
+	if line_distance(&third) < tolerance { return None; }
+	let base_normal = axis.cross(&(points[third] - points[first])).normalize();
+	let plane_distance = |index : &usize| (points[*index] - points[first]).dot(&base_normal).abs();
+	let fourth = (0..points.len()).max_by(|a, b| plane_distance(a).total_cmp(&plane_distance(b))).unwrap();
+	if plane_distance(&fourth) < tolerance { return None; }
+
+	let mut faces = Vec::new();
+	let center = (points[first] + points[second] + points[third] + points[fourth]) / 4.0;
+	for corners in [[first, second, third], [first, second, fourth], [first, third, fourth], [second, third, fourth]].iter() {
+		let face = HullFace::new(points, *corners, &Vec3::zeros());
+		// Flip it if it's facing inward.
+		faces.push(if 0.0 < face.distance(&center) {
+			HullFace::new(points, [corners[0], corners[2], corners[1]], &Vec3::zeros())
+		} else {
+			face
+		});
+	}
+
+	// Then grow the hull out to every point that's outside of it.
+	for (index, point) in points.iter().enumerate() {
+		let visible : Vec<bool> = faces.iter().map(|face| tolerance < face.distance(point)).collect();
+		if !visible.contains(&true) { continue; }
+		// The edges on the border between the visible and hidden faces get joined up to the point. These are the visible faces' edges which aren't shared with another visible face.
+		let mut added = Vec::new();
+		for (face, _) in faces.iter().zip(visible.iter()).filter(|(_, visible)| **visible) {
+			for edge in 0..3 {
+				let (start, end) = (face.corners[edge], face.corners[(edge + 1) % 3]);
+				let shared = faces.iter().zip(visible.iter()).any(|(other, visible)| {
+					*visible && (0..3).any(|other_edge| other.corners[other_edge] == end && other.corners[(other_edge + 1) % 3] == start)
+				});
+				if !shared {
+					added.push(HullFace::new(points, [start, end, index], &face.normal));
+				}
+			}
+		}
+		let mut visible_iter = visible.iter();
+		faces.retain(|_| !*visible_iter.next().unwrap());
+		faces.extend(added);
+	}
+	Some(faces)
+}
+
+/// A mesh's surface split into triangles, for checking where points are relative to it.
+struct Surface {
+	triangles : Vec<[Vec3; 3]>,
+}
+
+impl Surface {
+	/// Splits every (convex) face into a fan of triangles.
+	fn new(vertices : &[Vec3], faces : &[Vec<usize>]) -> Surface {
+		let mut triangles = Vec::new();
+		for face in faces {
+			for index in 1..(face.len() - 1) {
+				triangles.push([vertices[face[0]], vertices[face[index]], vertices[face[index + 1]]]);
+			}
+		}
+		Surface { triangles }
+	}
+
+	/// Whether the point is inside of the surface. Uses the winding number (i.e. how much of the view around the point the surface takes up), so it works (roughly) even if the surface has gaps.
+	fn contains(&self, point : &Vec3) -> bool {
+		let mut total_angle = 0.0;
+		for [first, second, third] in &self.triangles {
+			let (a, b, c) = (first - point, second - point, third - point);
+			let (a_length, b_length, c_length) = (a.magnitude(), b.magnitude(), c.magnitude());
+			let numerator = a.dot(&b.cross(&c));
+			let denominator = a_length * b_length * c_length + a.dot(&b) * c_length + a.dot(&c) * b_length + b.dot(&c) * a_length;
+			total_angle += 2.0 * numerator.atan2(denominator);
+		}
+		0.5 < (total_angle / (4.0 * std::f32::consts::PI)).abs()
+	}
+
+	/// How far the point is from the closest spot on the surface.
+	fn distance(&self, point : &Vec3) -> f32 {
+		self.triangles.iter().map(|triangle| (closest_on_triangle(point, triangle) - point).magnitude()).fold(f32::INFINITY, f32::min)
+	}
+}
+
+/// Finds the closest point on a triangle to the given point.
+fn closest_on_triangle(point : &Vec3, [a, b, c] : &[Vec3; 3]) -> Vec3 {
+	let (ab, ac, ap) = (b - a, c - a, point - a);
+	let (d1, d2) = (ab.dot(&ap), ac.dot(&ap));
+	if d1 <= 0.0 && d2 <= 0.0 { return *a; }
+	let bp = point - b;
+	let (d3, d4) = (ab.dot(&bp), ac.dot(&bp));
+	if 0.0 <= d3 && d4 <= d3 { return *b; }
+	let vc = d1 * d4 - d3 * d2;
+	if vc <= 0.0 && 0.0 <= d1 && d3 <= 0.0 { return a + ab * (d1 / (d1 - d3)); }
+	let cp = point - c;
+	let (d5, d6) = (ab.dot(&cp), ac.dot(&cp));
+	if 0.0 <= d6 && d5 <= d6 { return *c; }
+	let vb = d5 * d2 - d1 * d6;
+	if vb <= 0.0 && 0.0 <= d2 && d6 <= 0.0 { return a + ac * (d2 / (d2 - d6)); }
+	let va = d3 * d6 - d5 * d4;
+	if va <= 0.0 && 0.0 <= d4 - d3 && 0.0 <= d5 - d6 { return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6))); }
+	let total = va + vb + vc;
+	a + ab * (vb / total) + ac * (vc / total)
+}
+
+/// How badly the convex hull of the given points sticks out of the surface. This is how far the furthest (sampled) point on the hull is from the surface, ignoring the points that are inside it.
+fn concavity(points : &[Vec3], surface : &Surface) -> f32 {
+	let faces = match hull_faces(points) {
+		Some(faces) => faces,
+		None => return 0.0, // Everything is on a plane, so there's nothing to stick out.
+	};
+	let mut worst : f32 = 0.0;
+	for face in &faces {
+		let [first, second, third] = face.corners;
+		let (a, b, c) = (points[first], points[second], points[third]);
+		// Check the middle of the face, and a bit towards each corner.
+		for point in [(a + b + c) / 3.0, (a * 4.0 + b + c) / 6.0, (a + b * 4.0 + c) / 6.0, (a + b + c * 4.0) / 6.0].iter() {
+			if !surface.contains(point) {
+				worst = worst.max(surface.distance(point));
+			}
+		}
+	}
+	worst
+}
+
+/// A group of faces that are going to become a single convex piece.
+#[derive(Debug)]
+struct Cluster {
+	/// The faces in this cluster, as indices into the mesh's faces.
+	faces : Vec<usize>,
+	/// The other clusters that share an edge with this one.
+	neighbors : BTreeSet<usize>,
+}
+
+/// The points that a group of faces is replaced by: each face's corners, plus a copy of each pushed a little bit inside. The copies give even flat groups some volume.
+fn cluster_points(vertices : &[Vec3], faces : &[Vec<usize>], thickness : f32, face_indices : &mut dyn Iterator<Item = &usize>) -> Vec<Vec3> {
+	let mut points = Vec::new();
+	for face_index in face_indices {
+		let face = &faces[*face_index];
+		let corners : Vec<Vec3> = face.iter().map(|corner| vertices[*corner]).collect();
+		let center = corners.iter().sum::<Vec3>() / (corners.len() as f32);
+		let inward = -face_normal(&corners) * thickness;
+		for corner in &corners {
+			// Also pull the copy toward the face's center, so it's never on the hull of a convex group.
+			for point in [*corner, corner + (center - corner) * 0.01 + inward].iter() {
+				if point.iter().all(|value| value.is_finite()) && !points.contains(point) { points.push(*point); }
+			}
+		}
+	}
+	points
+}
+
+/// Splits a mesh into (nearly) convex pieces, by repeatedly merging the neighboring pair of face groups whose convex hull would stick out of the mesh the least.
+///
+/// Returns each piece's convex hull as a list of triangles (wound counter-clockwise when seen from outside).
+pub fn decompose(vertices : &[Vec3], faces : &[Vec<usize>], params : &ConvexDecompositionParams) -> Vec<Vec<[Vec3; 3]>> {
+	// Start with each face in its own cluster, neighboring the faces it shares an edge with.
+	let mut edge_faces : BTreeMap<(usize, usize), Vec<usize>> = BTreeMap::new();
+	for (face_index, face) in faces.iter().enumerate() {
+		for index in 0..face.len() {
+			let (first, second) = (face[index], face[(index + 1) % face.len()]);
+			edge_faces.entry((first.min(second), first.max(second))).or_default().push(face_index);
+		}
+	}
+	let mut clusters : Vec<Option<Cluster>> = (0..faces.len()).map(|face_index| Some(Cluster { faces: vec![face_index], neighbors: BTreeSet::new() })).collect();
+	for sharing in edge_faces.values() {
+		for first in sharing {
+			for second in sharing {
+				if first != second {
+					clusters[*first].as_mut().unwrap().neighbors.insert(*second);
+				}
+			}
+		}
+	}
+
+	let surface = Surface::new(vertices, faces);
+	let thickness = tolerance(vertices) * 100.0;
+
+	let mut costs : BTreeMap<(usize, usize), f32> = BTreeMap::new();
+	let mut remaining = clusters.len();
+	loop {
+		// Find the cheapest pair to merge.
+		let mut best : Option<(f32, usize, usize)> = None;
+		for (first, cluster) in clusters.iter().enumerate() {
+			let cluster = match cluster { Some(cluster) => cluster, None => continue };
+			for second in cluster.neighbors.iter().copied().filter(|second| first < *second) {
+				let cost = *costs.entry((first, second)).or_insert_with(|| {
+					let other = clusters[second].as_ref().unwrap();
+					concavity(&cluster_points(vertices, faces, thickness, &mut cluster.faces.iter().chain(other.faces.iter())), &surface)
+				});
+				if best.map_or(true, |(best_cost, _, _)| cost < best_cost) {
+					best = Some((cost, first, second));
+				}
+			}
+		}
+		let (cost, first, second) = match best { Some(best) => best, None => break };
+		if params.max_concavity < cost && remaining <= params.max_hulls { break; }
+
+		// Then merge the second cluster into the first.
+		let merged = clusters[second].take().unwrap();
+		for neighbor in &merged.neighbors {
+			if let Some(neighbor_cluster) = clusters[*neighbor].as_mut() {
+				neighbor_cluster.neighbors.remove(&second);
+				if *neighbor != first { neighbor_cluster.neighbors.insert(first); }
+			}
+		}
+		let cluster = clusters[first].as_mut().unwrap();
+		cluster.faces.extend(merged.faces);
+		cluster.neighbors.extend(merged.neighbors);
+		cluster.neighbors.remove(&first);
+		cluster.neighbors.remove(&second);
+		costs.retain(|(a, b), _| *a != first && *b != first && *a != second && *b != second);
+		remaining -= 1;
+	}
+
+	// Then replace each group with its hull.
+	clusters.into_iter().flatten().filter_map(|cluster| {
+		let points = cluster_points(vertices, faces, thickness, &mut cluster.faces.iter());
+		convex_hull(&points).map(|triangles| triangles.iter().map(|[first, second, third]| [points[*first], points[*second], points[*third]]).collect())
+	}).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Verify the hull of a cube (with some extra points inside and on it) is just the cube.
+	#[test]
+	fn check_convex_hull() {
+		let mut points = Vec::new();
+		for x in [-1.0, 1.0].iter() {
+			for y in [-1.0, 1.0].iter() {
+				for z in [-1.0, 1.0].iter() {
+					points.push(Vec3::new(*x, *y, *z));
+				}
+			}
+		}
+		points.push(Vec3::new(0.0, 0.0, 0.0));
+		points.push(Vec3::new(0.5,-0.25, 0.1));
+		points.push(Vec3::new(1.0, 0.0, 0.0));
+		let triangles = convex_hull(&points).unwrap();
+		assert_eq!(triangles.len(), 12);
+		for triangle in &triangles {
+			assert!(triangle.iter().all(|index| *index < 8));
+			// Each should face away from the center.
+			let normal = (points[triangle[1]] - points[triangle[0]]).cross(&(points[triangle[2]] - points[triangle[0]]));
+			assert!(0.0 < normal.dot(&points[triangle[0]]));
+		}
+
+		// Flat sets of points have no hull.
+		assert!(convex_hull(&[Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), Vec3::new(1.0, 1.0, 0.0)]).is_none());
+	}
+}
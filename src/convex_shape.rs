@@ -0,0 +1,328 @@
+use nalgebra::Point3;
+
+use crate::consts::EPSILON;
+use crate::types::{Vec3, Mat3, Isometry};
+use crate::collider::{ColliderType, InternalCollider};
+use crate::sphere_collider::InternalSphereCollider;
+use crate::aligned_box_collider::InternalAlignedBoxCollider;
+use crate::mesh_collider::InternalMeshCollider;
+use crate::orientation::Orientation;
+use crate::convex_decomposition::convex_hull;
+
+/// The most times [closest_points] will refine its guess.
+const GJK_ITERATIONS : usize = 64;
+
+/// The most points [penetration] will add while looking for the nearest surface.
+const EPA_ITERATIONS : usize = 32;
+
+/// Something convex that's described by its support function (i.e. the point on it farthest along any direction).
+///
+/// That's all [closest_points] and [penetration] need, so any two of these can be collided without a function written for that specific pair.
+pub trait ConvexShape {
+	/// The point on the shape that's farthest along `direction` (which doesn't need to be normalized). Both are in the owning entity's local space.
+	fn support(&self, direction : &Vec3) -> Vec3;
+
+	/// A sphere that contains the whole shape, as its center (in the owning entity's local space) and radius.
+	fn bounding_sphere(&self) -> (Vec3, f32);
+}
+
+impl ConvexShape for InternalSphereCollider {
+	fn support(&self, direction : &Vec3) -> Vec3 {
+		let length = direction.magnitude();
+		if length < EPSILON { self.center } else { self.center + direction * (self.radius / length) }
+	}
+
+	fn bounding_sphere(&self) -> (Vec3, f32) { (self.center, self.radius) }
+}
+
+impl ConvexShape for InternalAlignedBoxCollider {
+	fn support(&self, direction : &Vec3) -> Vec3 {
+		self.position + Vec3::new(
+			if direction.x < 0.0 { self.min_corner.x } else { self.max_corner.x },
+			if direction.y < 0.0 { self.min_corner.y } else { self.max_corner.y },
+			if direction.z < 0.0 { self.min_corner.z } else { self.max_corner.z },
+		)
+	}
+
+	fn bounding_sphere(&self) -> (Vec3, f32) {
+		(self.position + 0.5 * (self.min_corner + self.max_corner), 0.5 * (self.max_corner - self.min_corner).magnitude())
+	}
+}
+
+/// Meshes are treated as their convex hull (see [crate::MeshCollider::decompose_convex] for splitting up ones that aren't convex).
+impl ConvexShape for InternalMeshCollider {
+	fn support(&self, direction : &Vec3) -> Vec3 {
+		let mut best = self.vertices[0];
+		for vertex in self.vertices.iter() {
+			if best.dot(direction) < vertex.dot(direction) { best = *vertex; }
+		}
+		self.position + best
+	}
+
+	fn bounding_sphere(&self) -> (Vec3, f32) {
+		let center = self.vertices.iter().sum::<Vec3>() / (self.vertices.len() as f32);
+		let radius = self.vertices.iter().map(|vertex| (vertex - center).magnitude()).fold(0.0, f32::max);
+		(self.position + center, radius)
+	}
+}
+
+/// Gets the [ConvexShape] for a collider, if it has one. (Planes are infinite, triangle meshes aren't convex, and null colliders don't have any shape.)
+pub fn as_convex_shape(collider : &dyn InternalCollider) -> Option<&dyn ConvexShape> {
+	match collider.get_type() {
+		ColliderType::SPHERE => collider.downcast_ref::<InternalSphereCollider>().map(|shape| shape as &dyn ConvexShape),
+		ColliderType::ALIGNED_BOX => collider.downcast_ref::<InternalAlignedBoxCollider>().map(|shape| shape as &dyn ConvexShape),
+		ColliderType::MESH => collider.downcast_ref::<InternalMeshCollider>().map(|shape| shape as &dyn ConvexShape),
+		ColliderType::NULL | ColliderType::PLANE | ColliderType::TRI_MESH => None,
+	}
+}
+
+/// A shape moved into world space (by its owning entity's orientation).
+pub struct PlacedShape<'a> {
+	/// The shape, in its owner's local space.
+	shape : &'a dyn ConvexShape,
+	/// How to get from the owner's local space into world space.
+	into_world : Isometry,
+}
+
+impl<'a> PlacedShape<'a> {
+	/// Creates a new instance.
+	pub fn new(shape : &'a dyn ConvexShape, orientation : &Orientation) -> PlacedShape<'a> {
+		PlacedShape { shape, into_world: orientation.into_world() }
+	}
+}
+
+impl<'a> ConvexShape for PlacedShape<'a> {
+	fn support(&self, direction : &Vec3) -> Vec3 {
+		let local = self.shape.support(&self.into_world.inverse_transform_vector(direction));
+		self.into_world.transform_point(&Point3::from(local)).coords
+	}
+
+	fn bounding_sphere(&self) -> (Vec3, f32) {
+		let (center, radius) = self.shape.bounding_sphere();
+		(self.into_world.transform_point(&Point3::from(center)).coords, radius)
+	}
+}
+
+/// How close two results have to be to count as the same, for a pair of shapes. Scales with how big (and how far from the origin) the shapes are, as that's what limits the precision.
+pub fn tolerance(shape1 : &dyn ConvexShape, shape2 : &dyn ConvexShape) -> f32 {
+	let (center1, radius1) = shape1.bounding_sphere();
+	let (center2, radius2) = shape2.bounding_sphere();
+	EPSILON + (center1.amax() + center2.amax() + radius1 + radius2) * 1e-5
+}
+
+/// A point on the Minkowski difference of two shapes (the first minus the second), along with the point on each shape that made it.
+#[derive(Debug, Clone, Copy, Default)]
+struct SupportPoint {
+	/// The point on the first shape minus the point on the second.
+	point : Vec3,
+	/// The point on the first shape.
+	on1 : Vec3,
+	/// The point on the second shape.
+	on2 : Vec3,
+}
+
+impl SupportPoint {
+	/// The point of the Minkowski difference that's farthest along `direction`.
+	fn new(shape1 : &dyn ConvexShape, shape2 : &dyn ConvexShape, direction : &Vec3) -> SupportPoint {
+		let on1 = shape1.support(direction);
+		let on2 = shape2.support(&-direction);
+		SupportPoint { point: on1 - on2, on1, on2 }
+	}
+}
+
+/// Finds the point closest to the origin within the given (up to four) points, and which of them are needed to describe it.
+///
+/// Returns that point, the needed points (as a bit mask), and the weights to get the point from them. This just tries every combination of the points, which is plenty fast for so few.
+fn closest_on_simplex(points : &[SupportPoint]) -> (Vec3, usize, [f32; 4]) {
+	let mut best = (Vec3::repeat(f32::INFINITY), 0, [0.0; 4]);
+	let mut best_distance = f32::INFINITY;
+	for mask in 1..(1usize << points.len()) {
+		let mut indices = [0; 4];
+		let mut count = 0;
+		for index in 0..points.len() {
+			if mask & (1 << index) != 0 {
+				indices[count] = index;
+				count += 1;
+			}
+		}
+		// Find the closest point on the plane/line through the points, relative to the first one: base + sum(offsets[i] * along[i]).
+		let base = points[indices[0]].point;
+		let mut gram = Mat3::identity();
+		let mut target = Vec3::zeros();
+		let mut scale = 1.0;
+		for row in 1..count {
+			let offset_row = points[indices[row]].point - base;
+			for column in 1..count {
+				gram[(row - 1, column - 1)] = offset_row.dot(&(points[indices[column]].point - base));
+			}
+			target[row - 1] = -offset_row.dot(&base);
+			scale *= gram[(row - 1, row - 1)];
+		}
+		if gram.determinant().abs() <= scale * 1e-6 { continue; } // Degenerate, so a smaller set of the points covers it.
+		let along = match gram.try_inverse() {
+			Some(inverse) => inverse * target,
+			None => continue,
+		};
+		let mut weights = [0.0; 4];
+		weights[indices[0]] = 1.0 - along.iter().take(count - 1).sum::<f32>();
+		for row in 1..count {
+			weights[indices[row]] = along[row - 1];
+		}
+		// Only counts if it's actually within those points.
+		if weights.iter().any(|weight| *weight < 0.0) { continue; }
+		let closest = (0..points.len()).map(|index| points[index].point * weights[index]).sum::<Vec3>();
+		let distance = closest.magnitude_squared();
+		if distance < best_distance {
+			best_distance = distance;
+			best = (closest, mask, weights);
+		}
+	}
+	best
+}
+
+/// Finds the closest points between two (non-overlapping) convex shapes using GJK. Returns the point on each shape, or None if they overlap (or are just touching).
+pub fn closest_points(shape1 : &dyn ConvexShape, shape2 : &dyn ConvexShape) -> Option<(Vec3, Vec3)> {
+	let tolerance = tolerance(shape1, shape2);
+	let mut simplex = [SupportPoint::default(); 4];
+	let mut count = 1;
+	let start_direction = shape2.bounding_sphere().0 - shape1.bounding_sphere().0;
+	simplex[0] = SupportPoint::new(shape1, shape2, &(if start_direction.magnitude() < EPSILON { Vec3::x() } else { start_direction }));
+	let mut witness = (simplex[0].on1, simplex[0].on2);
+	for _ in 0..GJK_ITERATIONS {
+		// Only keep the points needed to describe the closest point.
+		let (closest, mask, weights) = closest_on_simplex(&simplex[0..count]);
+		if !closest.iter().all(|value| value.is_finite()) { return None; }
+		witness = (Vec3::zeros(), Vec3::zeros());
+		let mut kept = 0;
+		for index in 0..count {
+			if mask & (1 << index) != 0 {
+				witness.0 += simplex[index].on1 * weights[index];
+				witness.1 += simplex[index].on2 * weights[index];
+				simplex[kept] = simplex[index];
+				kept += 1;
+			}
+		}
+		count = kept;
+		let distance = closest.magnitude();
+		if 4 == count || distance <= tolerance { return None; }
+		// Then search for a point that's closer to the origin. Stop once nothing is noticeably closer. (This needs to be much tighter than the tolerance, as the closest points on curved shapes are far less precise than the distance between them.)
+		let next = SupportPoint::new(shape1, shape2, &-closest);
+		if distance - next.point.dot(&closest) / distance <= EPSILON + distance * 1e-6 { break; }
+		if simplex[0..count].iter().any(|existing| (existing.point - next.point).magnitude() <= EPSILON) { break; }
+		simplex[count] = next;
+		count += 1;
+	}
+	Some(witness)
+}
+
+/// Describes how two overlapping convex shapes overlap, using EPA. Returns the normal (pointing from the first shape toward the second), how far they overlap along it, and a point in the middle of the overlap.
+///
+/// Moving the first shape backwards along the normal by the depth (or the second forwards) is the shortest way to get them to stop overlapping.
+pub fn penetration(shape1 : &dyn ConvexShape, shape2 : &dyn ConvexShape) -> (Vec3, f32, Vec3) {
+	let tolerance = tolerance(shape1, shape2);
+	// Start with a rough polytope from the corners and faces of a cube, then keep pushing out the face nearest to the origin.
+	let mut points : Vec<SupportPoint> = Vec::new();
+	for x in -1..=1 {
+		for y in -1..=1 {
+			for z in -1..=1 {
+				if 0 == x && 0 == y && 0 == z { continue; }
+				let point = SupportPoint::new(shape1, shape2, &Vec3::new(x as f32, y as f32, z as f32));
+				if !points.iter().any(|existing| (existing.point - point.point).magnitude() <= tolerance) { points.push(point); }
+			}
+		}
+	}
+	let (center1, _) = shape1.bounding_sphere();
+	let (center2, _) = shape2.bounding_sphere();
+	let mut result = ((center2 - center1).try_normalize(EPSILON).unwrap_or_else(Vec3::x), 0.0, 0.5 * (center1 + center2));
+	for _ in 0..EPA_ITERATIONS {
+		let triangles = match convex_hull(&points.iter().map(|point| point.point).collect::<Vec<Vec3>>()) {
+			Some(triangles) => triangles,
+			None => break, // Flat, so can't say much.
+		};
+		let mut nearest : Option<([usize; 3], Vec3, f32)> = None;
+		for corners in triangles {
+			let [first, second, third] = corners.map(|index| points[index].point);
+			let normal = match (second - first).cross(&(third - first)).try_normalize(EPSILON * EPSILON) {
+				Some(normal) => normal,
+				None => continue,
+			};
+			let offset = normal.dot(&first);
+			if nearest.is_none_or(|(_, _, nearest_offset)| offset < nearest_offset) {
+				nearest = Some((corners, normal, offset));
+			}
+		}
+		let (corners, normal, offset) = match nearest {
+			Some(nearest) => nearest,
+			None => break,
+		};
+		// Find where the origin is on that face, and use that to find the matching points on each shape.
+		let [first, second, third] = corners.map(|index| points[index]);
+		let weights = barycentric(&(normal * offset), &first.point, &second.point, &third.point);
+		let on1 = first.on1 * weights.x + second.on1 * weights.y + third.on1 * weights.z;
+		let on2 = first.on2 * weights.x + second.on2 * weights.y + third.on2 * weights.z;
+		result = (normal, offset, 0.5 * (on1 + on2));
+		let next = SupportPoint::new(shape1, shape2, &normal);
+		if next.point.dot(&normal) - offset <= tolerance { break; }
+		if points.iter().any(|existing| (existing.point - next.point).magnitude() <= tolerance) { break; }
+		points.push(next);
+	}
+	result
+}
+
+/// The barycentric coordinates of a point (projected onto the triangle's plane).
+fn barycentric(point : &Vec3, first : &Vec3, second : &Vec3, third : &Vec3) -> Vec3 {
+	let (along1, along2, offset) = (second - first, third - first, point - first);
+	let (dot11, dot12, dot22) = (along1.dot(&along1), along1.dot(&along2), along2.dot(&along2));
+	let (dot1, dot2) = (offset.dot(&along1), offset.dot(&along2));
+	let denominator = dot11 * dot22 - dot12 * dot12;
+	if denominator.abs() < EPSILON * EPSILON { return Vec3::new(1.0, 0.0, 0.0); }
+	let second_weight = (dot22 * dot1 - dot12 * dot2) / denominator;
+	let third_weight = (dot11 * dot2 - dot12 * dot1) / denominator;
+	Vec3::new(1.0 - second_weight - third_weight, second_weight, third_weight)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::sphere_collider::SphereCollider;
+	use crate::aligned_box_collider::AlignedBoxCollider;
+
+	/// Verify the closest points and the overlap are found for boxes and spheres.
+	#[test]
+	fn check_closest_points_and_penetration() {
+		let sphere = InternalSphereCollider::new_for_query(1.0);
+		let cube = InternalAlignedBoxCollider::new_from(&AlignedBoxCollider::from_center_half_extents(Vec3::zeros(), Vec3::repeat(1.0))).unwrap();
+		let cube = cube.downcast_ref::<InternalAlignedBoxCollider>().unwrap();
+		let here = Orientation::new(Vec3::zeros(), Vec3::zeros(), Vec3::zeros());
+
+		// Apart.
+		let there = Orientation::new(Vec3::new(3.0, 0.5, 0.0), Vec3::zeros(), Vec3::zeros());
+		let (on1, on2) = closest_points(&PlacedShape::new(cube, &here), &PlacedShape::new(&sphere, &there)).unwrap();
+		assert!((on1.x - 1.0).abs() < 1e-4);
+		assert!((on2 - Vec3::new(2.0, 0.5, 0.0)).magnitude() < 1e-4);
+
+		// Apart, nearest at a corner (and rotated).
+		let there = Orientation::new(Vec3::new(3.0, 3.0, 3.0), Vec3::zeros(), Vec3::zeros());
+		let (on1, on2) = closest_points(&PlacedShape::new(cube, &here), &PlacedShape::new(cube, &there)).unwrap();
+		assert!((on1 - Vec3::repeat(1.0)).magnitude() < 1e-4);
+		assert!((on2 - Vec3::repeat(2.0)).magnitude() < 1e-4);
+
+		// Overlapping.
+		let there = Orientation::new(Vec3::new(1.5, 0.0, 0.0), Vec3::zeros(), Vec3::zeros());
+		assert!(closest_points(&PlacedShape::new(cube, &here), &PlacedShape::new(&sphere, &there)).is_none());
+		let (normal, depth, position) = penetration(&PlacedShape::new(cube, &here), &PlacedShape::new(&sphere, &there));
+		assert!((normal - Vec3::x()).magnitude() < 1e-3);
+		assert!((depth - 0.5).abs() < 1e-3);
+		assert!((position.x - 0.75).abs() < 1e-3);
+
+		// Also works for shapes that aren't symmetrical.
+		let mut source = SphereCollider::new(0.5);
+		source.center = Vec3::new(0.0, -2.0, 0.0);
+		let offset_sphere = InternalSphereCollider::new_from(&source).unwrap();
+		let offset_sphere = offset_sphere.downcast_ref::<InternalSphereCollider>().unwrap();
+		let there = Orientation::new(Vec3::new(0.0, 3.25, 0.0), Vec3::zeros(), Vec3::zeros());
+		let (normal, depth, _) = penetration(&PlacedShape::new(cube, &here), &PlacedShape::new(offset_sphere, &there));
+		assert!((normal - Vec3::y()).magnitude() < 1e-3);
+		assert!((depth - 0.25).abs() < 1e-3);
+	}
+}
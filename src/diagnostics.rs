@@ -0,0 +1,357 @@
+//! Helpers for checking that a [PhysicsSystem] behaves (i.e. in tests, or to validate a scene).
+//!
+//! Only enabled, dynamic entities are counted: static and kinematic entities act like they have infinite mass, so would swamp everything else. Sleeping entities count as not moving.
+
+use std::ops::{Add, Sub, AddAssign};
+
+use crate::types::{Vec3, EntityHandle};
+use crate::entity::InternalEntity;
+use crate::physics_system::PhysicsSystem;
+
+/// Calls the function with every entity that should be counted.
+fn for_each_counted<F>(system : &PhysicsSystem, mut function : F) where F : FnMut(&InternalEntity) {
+	for (_, entity) in system.entities.borrow().iter() {
+		if entity.enabled && entity.is_dynamic() {
+			function(entity);
+		}
+	}
+}
+
+/// Adds up the linear momentum of every (counted) entity.
+pub fn total_linear_momentum(system : &PhysicsSystem) -> Vec3 {
+	let mut total = Vec3::zeros();
+	for_each_counted(system, |entity| if !entity.asleep {
+		total += entity.velocity * entity.get_total_mass();
+	});
+	total
+}
+
+/// Adds up the angular momentum of every (counted) entity, about the world origin.
+pub fn total_angular_momentum(system : &PhysicsSystem) -> Vec3 {
+	let mut total = Vec3::zeros();
+	for_each_counted(system, |entity| if !entity.asleep {
+		total += entity.get_moment_of_inertia() * entity.angular_velocity;
+		total += entity.orientation.position.cross(&(entity.velocity * entity.get_total_mass()));
+	});
+	total
+}
+
+/// Adds up the kinetic energy (from both moving and rotating) of every (counted) entity.
+pub fn total_energy(system : &PhysicsSystem) -> f32 {
+	let mut total = 0.0;
+	for_each_counted(system, |entity| if !entity.asleep {
+		total += entity.get_linear_energy() + entity.get_angular_energy();
+	});
+	total
+}
+
+/// Adds up the potential energy of every (counted) entity, from a uniform gravitational acceleration (like [crate::GravityGenerator] applies). Zero is at the world origin.
+pub fn potential_energy(system : &PhysicsSystem, gravity : &Vec3) -> f32 {
+	let mut total = 0.0;
+	for_each_counted(system, |entity| {
+		total -= entity.get_total_mass() * gravity.dot(&entity.orientation.position);
+	});
+	total
+}
+
+/// Checks that step() never adds energy to a system (beyond some tolerance).
+///
+/// Energy added from outside between steps (i.e. by applying impulses) isn't counted, since each step is only compared with how things were right before it. Forces (besides gravity, if it's set), kinematic entities, and contact modifiers can all add energy too, so only use this on scenes without those.
+#[derive(Debug, Clone)]
+pub struct ConservationWatcher {
+	/// How much the energy can go up in a single step before it counts as a problem. Must not be negative.
+	///
+	/// Defaults to `0.0001`.
+	pub tolerance : f32,
+
+	/// The gravitational acceleration in the system (if any), so that falling doesn't count as gaining energy. See [potential_energy].
+	///
+	/// Defaults to zero.
+	pub gravity : Vec3,
+
+	/// The largest increase seen in a single step so far (or zero if it's never gone up).
+	largest_increase : f32,
+}
+
+impl Default for ConservationWatcher {
+	fn default() -> ConservationWatcher { ConservationWatcher::new() }
+}
+
+impl ConservationWatcher {
+	/// Creates a new instance.
+	pub fn new() -> ConservationWatcher {
+		ConservationWatcher {
+			tolerance: 0.0001,
+			gravity: Vec3::zeros(),
+			largest_increase: 0.0,
+		}
+	}
+
+	/// The total energy this is watching: kinetic plus the potential energy from `gravity`.
+	pub fn energy(&self, system : &PhysicsSystem) -> f32 {
+		total_energy(system) + potential_energy(system, &self.gravity)
+	}
+
+	/// Steps the system, then checks its energy didn't go up by more than `tolerance`. If it did, the increase is returned as an error (the step still happened).
+	pub fn step(&mut self, system : &mut PhysicsSystem, dt : f32) -> Result<(), f32> {
+		let before = self.energy(system);
+		system.step(dt);
+		let increase = self.energy(system) - before;
+		if self.largest_increase < increase {
+			self.largest_increase = increase;
+		}
+		if increase <= self.tolerance { Ok(()) } else { Err(increase) }
+	}
+
+	/// The largest increase in energy seen in a single step so far (or zero if it's never gone up).
+	pub fn get_largest_increase(&self) -> f32 { self.largest_increase }
+}
+
+/// How much an entity's kinetic energy changed, split into moving and rotating.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EnergyChange {
+	/// The change in energy from moving.
+	pub linear : f32,
+	/// The change in energy from rotating.
+	pub angular : f32,
+}
+
+impl EnergyChange {
+	/// Gets an entity's current kinetic energy (i.e. the change from not moving at all). Anything that isn't dynamic counts as having none, just like the rest of this module.
+	pub(crate) fn of(entity : &InternalEntity) -> EnergyChange {
+		if !entity.is_dynamic() { return EnergyChange::default(); }
+		EnergyChange { linear: entity.get_linear_energy(), angular: entity.get_angular_energy() }
+	}
+
+	/// The change in total energy.
+	pub fn total(&self) -> f32 { self.linear + self.angular }
+}
+
+impl Add for EnergyChange {
+	type Output = EnergyChange;
+	fn add(self, other : EnergyChange) -> EnergyChange {
+		EnergyChange { linear: self.linear + other.linear, angular: self.angular + other.angular }
+	}
+}
+
+impl Sub for EnergyChange {
+	type Output = EnergyChange;
+	fn sub(self, other : EnergyChange) -> EnergyChange {
+		EnergyChange { linear: self.linear - other.linear, angular: self.angular - other.angular }
+	}
+}
+
+impl AddAssign for EnergyChange {
+	fn add_assign(&mut self, other : EnergyChange) { *self = *self + other; }
+}
+
+/// How much an entity's kinetic energy changed during each part of a step. See [PhysicsSystem::track_energy_drift].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EnergyDrift {
+	/// From applying forces (i.e. gravity speeding things up).
+	pub forces : EnergyChange,
+
+	/// From moving and turning entities along their velocities (which stay the same). The moment of inertia turns along with the entity, so this alone can change the rotational energy of lopsided entities.
+	pub movement : EnergyChange,
+
+	/// From responding to collisions (including friction).
+	pub collisions : EnergyChange,
+
+	/// From everything after the collisions are handled (i.e. putting things to sleep).
+	pub other : EnergyChange,
+}
+
+impl EnergyDrift {
+	/// The change from all of the parts together.
+	pub fn total(&self) -> EnergyChange { self.forces + self.movement + self.collisions + self.other }
+}
+
+impl AddAssign for EnergyDrift {
+	fn add_assign(&mut self, other : EnergyDrift) {
+		self.forces += other.forces;
+		self.movement += other.movement;
+		self.collisions += other.collisions;
+		self.other += other.other;
+	}
+}
+
+/// How many buckets are in [EnergyDriftReport::histogram].
+pub const DRIFT_HISTOGRAM_BUCKETS : usize = 15;
+
+/// Gets which bucket of [EnergyDriftReport::histogram] an energy change goes in.
+///
+/// The middle bucket (7) is for changes smaller than a millionth either way. Above that, each bucket holds gains ten times larger than the one before, from 8 for gains of at least a millionth up to 14 for gains of one or more. The buckets below the middle are the same for losses (down to 0 for losing one or more).
+pub fn drift_histogram_bucket(change : f32) -> usize {
+	let middle = DRIFT_HISTOGRAM_BUCKETS / 2;
+	if change.is_nan() || change.abs() < 0.000001 { return middle; }
+	let decade = (change.abs().log10().floor().clamp(-6.0, 0.0) + 6.0) as usize;
+	if 0.0 < change { middle + 1 + decade } else { middle - 1 - decade }
+}
+
+/// Everything that's been tracked about how energy drifts. See [PhysicsSystem::track_energy_drift].
+#[derive(Debug, Clone, Default)]
+pub struct EnergyDriftReport {
+	/// How much each (enabled) entity's energy changed during the last step, in the order they were stepped. Entities that aren't dynamic never change.
+	pub last_step : Vec<(EntityHandle, EnergyDrift)>,
+
+	/// Every entity's drift added up, over every step since tracking was turned on (or reset).
+	pub total : EnergyDrift,
+
+	/// How many steps have been tracked.
+	pub steps : u32,
+
+	/// How many times an entity's total change during a step landed in each bucket (see [drift_histogram_bucket]).
+	pub histogram : [u32; DRIFT_HISTOGRAM_BUCKETS],
+}
+
+impl EnergyDriftReport {
+	/// Replaces the last step's drifts, and adds them to the totals.
+	pub(crate) fn add_step<I>(&mut self, drifts : I) where I : Iterator<Item = (EntityHandle, EnergyDrift)> {
+		self.last_step.clear();
+		self.last_step.extend(drifts);
+		for (_, drift) in &self.last_step {
+			self.total += *drift;
+			self.histogram[drift_histogram_bucket(drift.total().total())] += 1;
+		}
+		self.steps += 1;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::consts::EPSILON;
+	use crate::types::EntityHandle;
+	use crate::entity::Entity;
+	use crate::body_type::BodyType;
+	use crate::sphere_collider::SphereCollider;
+	use crate::plane_collider::PlaneCollider;
+	use crate::aligned_box_collider::AlignedBoxCollider;
+	use crate::collider_wrapper::ColliderWrapper;
+	use crate::gravity_generator::GravityGenerator;
+
+	/// Adds a ball (with a mass of one and a radius of a half) to the system.
+	fn add_ball(system : &mut PhysicsSystem, position : &Vec3, velocity : &Vec3) -> EntityHandle {
+		let mut entity = Entity::new();
+		entity.position = *position;
+		entity.velocity = *velocity;
+		let entity_handle = system.add_entity(entity).unwrap();
+		let mut sphere = SphereCollider::new(0.5);
+		sphere.mass = 1.0;
+		let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+		system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+		entity_handle
+	}
+
+	/// Verify momentum and energy are added up correctly, and are kept through an elastic collision.
+	#[test]
+	fn totals() {
+		let mut system = PhysicsSystem::new();
+		add_ball(&mut system, &Vec3::new(-2.0, 1.0, 0.0), &Vec3::new(2.0, 0.0, 0.0));
+		add_ball(&mut system, &Vec3::new(2.0, 1.0, 0.0), &Vec3::new(-1.0, 0.0, 0.0));
+		{ // Static entities aren't counted.
+			let mut entity = Entity::new();
+			entity.body_type = BodyType::Static;
+			entity.velocity = Vec3::new(5.0, 0.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(PlaneCollider::new())).unwrap();
+			system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+		}
+		assert!((total_linear_momentum(&system) - Vec3::new(1.0, 0.0, 0.0)).magnitude() < EPSILON);
+		assert!((total_angular_momentum(&system) - Vec3::new(0.0, 0.0, -1.0)).magnitude() < EPSILON);
+		assert!((total_energy(&system) - 2.5).abs() < EPSILON);
+		assert!((potential_energy(&system, &Vec3::new(0.0, -10.0, 0.0)) - 20.0).abs() < EPSILON);
+
+		let mut watcher = ConservationWatcher::new();
+		for _ in 0..30 {
+			watcher.step(&mut system, 0.1).unwrap();
+		}
+		assert!((total_linear_momentum(&system) - Vec3::new(1.0, 0.0, 0.0)).magnitude() < 0.001);
+		assert!((total_energy(&system) - 2.5).abs() < 0.001);
+	}
+
+	/// Verify the watcher allows for falling (when told about gravity), but catches energy being added.
+	#[test]
+	fn watcher() {
+		let mut system = PhysicsSystem::new();
+		add_ball(&mut system, &Vec3::new(0.0, 5.0, 0.0), &Vec3::zeros());
+		let gravity = Vec3::new(0.0, -9.8, 0.0);
+		let generator = system.add_unary_force_generator(Box::new(GravityGenerator::new(gravity))).unwrap();
+		let mut watcher = ConservationWatcher::new();
+		watcher.gravity = gravity;
+		for _ in 0..10 {
+			watcher.step(&mut system, 0.05).unwrap();
+		}
+		// Without knowing about gravity, falling looks like gaining energy.
+		watcher.gravity = Vec3::zeros();
+		assert!(watcher.step(&mut system, 0.05).is_err());
+		assert!(1.0 < watcher.get_largest_increase());
+		// Pushing it between steps doesn't count.
+		system.remove_unary_force_generator(generator).unwrap();
+		let ball = system.entities.borrow().iter().map(|(handle, _)| handle).next().unwrap();
+		system.apply_impulse(ball, Vec3::new(0.0, 5.0, 0.0), Vec3::new(3.0, 0.0, 0.0)).unwrap();
+		watcher.step(&mut system, 0.05).unwrap();
+	}
+
+	/// Verify the drift from a lopsided box spinning on its own (with no forces or collisions) is all put down to its movement, and a ball bouncing off the floor has its drift split up between the right parts.
+	#[test]
+	fn energy_drift() {
+		let mut system = PhysicsSystem::new();
+		system.track_energy_drift = true;
+		let spinner = {
+			let mut entity = Entity::new();
+			entity.angular_velocity = Vec3::new(1.0, 3.0, 0.5);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut collider = AlignedBoxCollider::from_center_half_extents(Vec3::zeros(), Vec3::new(1.0, 0.5, 0.25));
+			collider.mass = 1.0;
+			let collider_handle = system.add_collider(ColliderWrapper::AlignedBox(collider)).unwrap();
+			system.link_collider(collider_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		let before = total_energy(&system);
+		for _ in 0..100 {
+			system.step(0.01);
+		}
+		let report = system.get_energy_drift();
+		assert_eq!(report.steps, 100);
+		assert_eq!(report.histogram.iter().sum::<u32>(), 100);
+		assert_eq!(report.last_step.len(), 1);
+		assert!(report.last_step[0].0 == spinner);
+		assert_eq!(report.total.forces, EnergyChange::default());
+		assert_eq!(report.total.collisions, EnergyChange::default());
+		assert_eq!(report.total.other, EnergyChange::default());
+		assert!((report.total.total().total() - (total_energy(&system) - before)).abs() < 0.0001);
+
+		let mut system = PhysicsSystem::new();
+		system.track_energy_drift = true;
+		{
+			let mut entity = Entity::new();
+			entity.body_type = BodyType::Static;
+			let entity_handle = system.add_entity(entity).unwrap();
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(PlaneCollider::new())).unwrap();
+			system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+		}
+		{ // Doesn't bounce, so the collision takes energy away.
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 1.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 1.0;
+			sphere.restitution_coefficient = 0.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+		}
+		system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -10.0, 0.0)))).unwrap();
+		for _ in 0..50 {
+			system.step(0.01);
+		}
+		let report = system.get_energy_drift();
+		assert_eq!(report.last_step.len(), 2);
+		assert_eq!(report.histogram.iter().sum::<u32>(), 100);
+		assert!(0.0 < report.total.forces.linear);
+		assert!(report.total.collisions.linear < 0.0);
+		assert_eq!(report.total.movement.linear, 0.0);
+		system.reset_energy_drift();
+		assert_eq!(system.get_energy_drift().steps, 0);
+	}
+}
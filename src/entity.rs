@@ -7,17 +7,30 @@ use crate::consts::EPSILON;
 use crate::types::{Vec3, Mat3, Quat, ColliderHandle, EntityHandle};
 use crate::collider::InternalCollider;
 use crate::orientation::Orientation;
+use crate::body_type::BodyType;
+#[cfg(feature = "fixed-point")]
+use crate::fixed::FixedVec3;
 
 /// The internal representation of any physical object.
 /// This generally has NO data hiding to keep things simple.
+#[derive(Clone)]
 pub struct InternalEntity {
 	/// The current position and rotation.
 	pub orientation : Orientation,
 
+	/// The position of the center of mass, kept in fixed-point so moving it doesn't lose anything to float rounding.
+	///
+	/// This is the real position; `orientation.position` is just the nearest float to it. If the two don't match at the start of a step(), then `orientation.position` was changed directly and this is reset to it.
+	#[cfg(feature = "fixed-point")]
+	pub fixed_position : FixedVec3,
+
 	/// The mass of this entity at the center of mass (as a point mass).
 	/// This is NOT the total mass.
 	pub own_mass : f32,
 
+	/// How this entity moves. Use get_body_type() to also account for infinite masses.
+	pub body_type : BodyType,
+
 	/// The (cached) total mass (including all colliders).
 	///
 	/// This should only ever be udpated by calling recalculate_mass().
@@ -28,9 +41,18 @@ pub struct InternalEntity {
 	/// This should only ever be udpated by calling recalculate_mass().
 	prepped_moment_of_inertia : Mat3,
 
+	/// Whether the cached mass values are out of date, because a collider was moved with [crate::PhysicsSystem::move_collider]. They're recalculated at the start of the next step.
+	pub mass_dirty : bool,
+
 	/// The current linear velocity.
 	pub velocity : Vec3,
 
+	/// The linear velocity, kept in fixed-point (like [InternalEntity::fixed_position]), so the movement it adds up to is worked out the same way everywhere.
+	///
+	/// This is the real velocity; `velocity` is just the nearest float to it. Only change it through [InternalEntity::change_velocity] (which keeps the two in sync). If they don't match at the start of a step(), then `velocity` was changed directly and this is reset to it.
+	#[cfg(feature = "fixed-point")]
+	pub fixed_velocity : FixedVec3,
+
 	/// The current angular velocity (about the center of mass).
 	pub angular_velocity : Vec3,
 
@@ -44,25 +66,65 @@ pub struct InternalEntity {
 	pub falling_asleep_time : f32,
 	/// Whether this has been put to sleep.
 	pub asleep : bool,
+	/// Whether this was asleep the last time sleep changes were taken (see [crate::PhysicsSystem::take_sleep_changes]).
+	pub reported_asleep : bool,
 
 	/// Other entities to wake up when this entity wakes up.
 	/// These are also the entities that won't wake up this entity if they're colliding with it (and vise versa).
 	/// This should always be empty if the entity isn't asleep.
 	pub neighbors : HashSet<EntityHandle>,
+
+	/// Whether this entity takes part in the simulation at all.
+	/// Disabled entities are skipped by integration and collision detection, but otherwise keep all of their state.
+	pub enabled : bool,
+
+	/// The query groups (as bit flags) that this entity belongs to. See [Entity::query_groups].
+	pub query_groups : u32,
+	/// Whether collisions push this entity around. See [Entity::respond].
+	pub respond : bool,
+	/// Which entities can push this one around. See [Entity::dominance].
+	pub dominance : i32,
+
+	/// A name for finding and identifying this. See [Entity::name].
+	pub name : Option<String>,
+
+	/// How fast time passes for this entity. See [Entity::time_scale].
+	pub time_scale : f32,
+	/// How much longer this entity has before it's removed. See [Entity::lifetime].
+	pub lifetime : Option<f32>,
+
+	/// The average force on this during the last step() it took part in, from both force generators and collisions.
+	pub last_applied_force : Vec3,
+	/// The average torque (about the center of mass) on this during the last step() it took part in, from both force generators and collisions.
+	pub last_applied_torque : Vec3,
+
+	/// Whether this moved between the ends of the last two step() calls (including by being teleported in between). See [crate::PhysicsSystem::awake_entities].
+	pub moved : bool,
+	/// Where this was at the end of the last step() call. None if it hasn't been through one yet.
+	pub last_step_orientation : Option<Orientation>,
+
+	/// Where this entity was at each point that the last step() stopped to handle a collision.
+	/// Each item is a time since the start of that step() and the orientation at that time. These are in time order.
+	pub step_path : Vec<(f32, Orientation)>,
 }
 
 impl InternalEntity {
 	/// Creates a new instance.
 	pub fn new_from(source : Entity) -> Result<InternalEntity, ()> {
-		if 0.0 > source.own_mass { return Err(()); }
+		if !source.is_valid() { return Err(()); }
 		Ok(InternalEntity {
 			orientation: source.make_orientation(),
+			#[cfg(feature = "fixed-point")]
+			fixed_position: FixedVec3::from_vec3(&source.position),
 
 			own_mass: source.own_mass,
+			body_type: source.body_type,
 			total_mass: source.own_mass,
 			prepped_moment_of_inertia: Mat3::zeros(),
 
 			velocity: source.velocity,
+			#[cfg(feature = "fixed-point")]
+			fixed_velocity: FixedVec3::from_vec3(&source.velocity),
 			angular_velocity: source.angular_velocity,
 			colliders: HashSet::new(),
 
@@ -70,7 +132,22 @@ impl InternalEntity {
 			falling_asleep_time: 0.0,
 
 			asleep: false,
+			reported_asleep: false,
 			neighbors: HashSet::new(),
+
+			enabled: true,
+			query_groups: source.query_groups,
+			respond: source.respond,
+			dominance: source.dominance,
+			name: source.name,
+			time_scale: source.time_scale,
+			lifetime: source.lifetime,
+			last_applied_force: Vec3::zeros(),
+			last_applied_torque: Vec3::zeros(),
+			moved: false,
+			last_step_orientation: None,
+			step_path: Vec::new(),
+			mass_dirty: false,
 		})
 	}
 
@@ -83,6 +160,13 @@ impl InternalEntity {
 			last_orientation: self.orientation.clone(),
 
 			own_mass: self.own_mass,
+			body_type: self.body_type,
+			query_groups: self.query_groups,
+			respond: self.respond,
+			dominance: self.dominance,
+			name: self.name.clone(),
+			time_scale: self.time_scale,
+			lifetime: self.lifetime,
 			last_total_mass: self.get_total_mass(),
 
 			velocity: self.velocity.clone(),
@@ -91,14 +175,18 @@ impl InternalEntity {
 			colliders: self.colliders.clone(),
 
 			last_prepped_moment_of_inertia: self.prepped_moment_of_inertia.clone(),
+			last_applied_force: self.last_applied_force,
+			last_applied_torque: self.last_applied_torque,
 
 			asleep: self.asleep,
+
+			enabled: self.enabled,
 		}
 	}
 
 	/// Updates from the passed in Entity object.
 	pub fn update_from(&mut self, source : Entity) -> Result<bool,()> {
-		if 0.0 > source.own_mass { return Err(()); }
+		if !source.is_valid() { return Err(()); }
 		let new_rotation = Quat::from_scaled_axis(source.rotation);
 		let rotation_delta = (
 			(new_rotation.w - self.orientation.rotation.w) * (new_rotation.w - self.orientation.rotation.w) +
@@ -109,6 +197,10 @@ impl InternalEntity {
 		#[allow(unused_parens)]
 		let changed = (
 			self.own_mass != source.own_mass ||
+			self.body_type != source.body_type ||
+			self.time_scale != source.time_scale ||
+			self.respond != source.respond ||
+			self.dominance != source.dominance ||
 			EPSILON < (self.orientation.position - source.position).magnitude() ||
 			EPSILON < rotation_delta ||
 			EPSILON < (self.velocity - source.velocity).magnitude() ||
@@ -116,6 +208,13 @@ impl InternalEntity {
 		);
 
 		self.own_mass = source.own_mass;
+		self.body_type = source.body_type;
+		self.time_scale = source.time_scale;
+		self.query_groups = source.query_groups; // Only matters to queries, so doesn't count as a change.
+		self.respond = source.respond;
+		self.dominance = source.dominance;
+		self.name = source.name; // Same for this.
+		self.lifetime = source.lifetime; // And this (it only matters at the end of each step).
 		self.orientation.position = source.position;
 		self.orientation.rotation = new_rotation;
 
@@ -127,6 +226,7 @@ impl InternalEntity {
 
 	/// Recalculates the (cached) mass and inertia values.
 	pub fn recalculate_mass(&mut self, colliders : &Arena<Box<dyn InternalCollider>>) {
+		self.mass_dirty = false;
 		// First find the center of mass.
 		self.total_mass = self.own_mass;
 		let mut center_of_mass = Vec3::zeros();
@@ -134,6 +234,7 @@ impl InternalEntity {
 		let mut found_infinite = false;
 		for handle in self.colliders.iter() {
 			let collider = colliders.get(*handle).unwrap();
+			if collider.is_query_only() { continue; }
 			let collider_mass = collider.get_mass();
 			if collider_mass.is_infinite() {
 				found_infinite = true;
@@ -167,6 +268,7 @@ impl InternalEntity {
 			// TODO? Do orientation.rotation and angular_velocity need to change since the center-of-mass changed?
 			for handle in self.colliders.iter() {
 				let collider = colliders.get(*handle).unwrap();
+				if collider.is_query_only() { continue; }
 				self.prepped_moment_of_inertia += self.orientation.prep_moment_of_inertia(
 					&collider.get_local_center_of_mass(),
 					collider.get_mass(),
@@ -181,52 +283,132 @@ impl InternalEntity {
 		self.total_mass
 	}
 
+	/// Gets how this entity actually moves.
+	///
+	/// This is just `body_type`, except that some dynamic entities are treated as kinematic:
+	/// * Ones with an infinite total mass (the deprecated way to make something immovable).
+	/// * Ones with colliders but (next to) no total mass. Anything that hits them would give them a junk (i.e. NaN or huge) velocity.
+	pub fn get_body_type(&self) -> BodyType {
+		if BodyType::Dynamic != self.body_type { return self.body_type; }
+		if self.total_mass.is_infinite() || (self.total_mass < EPSILON && !self.colliders.is_empty()) {
+			BodyType::Kinematic
+		} else {
+			BodyType::Dynamic
+		}
+	}
+
+	/// Whether forces and collisions can change this entity's velocities.
+	pub fn is_dynamic(&self) -> bool {
+		BodyType::Dynamic == self.get_body_type()
+	}
+
+	/// Gets one over the total mass. This is zero for anything that isn't dynamic (or has no mass).
+	pub fn get_inverse_mass(&self) -> f32 {
+		if self.is_dynamic() && EPSILON <= self.total_mass { 1.0 / self.total_mass } else { 0.0 }
+	}
+
 	/// Gets the moment of inertia tensor in WORLD space.
 	pub fn get_moment_of_inertia(&self) -> Mat3 {
 		self.orientation.finalize_moment_of_inertia(&self.prepped_moment_of_inertia)
 	}
 
-	/// Gets the moment of inertia tensor in WORLD space.
+	/// Gets the inverse of the moment of inertia tensor in WORLD space. This is all zeros for anything that isn't dynamic.
 	pub fn get_inverse_moment_of_inertia(&self) -> Mat3 {
+		if !self.is_dynamic() { return Mat3::zeros(); }
 		let moment = self.get_moment_of_inertia();
 		if let Some(inverse) = moment.try_inverse() {
 			inverse
 		} else {
 			if EPSILON < moment.magnitude() {
-				println!("WARNING! No inverse found for moment of inertia! {:?}", moment);
+				debug_print!("WARNING! No inverse found for moment of inertia! {:?}", moment);
 			}
 			Mat3::zeros()
 		}
 	}
 
+	/// Gets where this entity was at the given time (relative to the start of the last step() call).
+	///
+	/// Times outside of the last step are clamped to its start or end.
+	pub fn orientation_at(&self, time : f32) -> Orientation {
+		let mut previous = if let Some(first) = self.step_path.first() { first } else { return self.orientation; };
+		if time <= previous.0 { return previous.1; }
+		for next in self.step_path.iter().skip(1) {
+			if time <= next.0 {
+				let span = next.0 - previous.0;
+				if span < EPSILON { return next.1; }
+				return Orientation::lerp((time - previous.0) / span, &previous.1, &next.1);
+			}
+			previous = next;
+		}
+		previous.1
+	}
+
 	/// Gets the velocity at a point (that's specified in world coordinates).
 	pub fn get_velocity_at_world_position(&self, position : &Vec3) -> Vec3 {
 		self.velocity + self.angular_velocity.cross(&(position - self.orientation.position))
 	}
 
-	/// Gets the total energy of this object.
-	pub fn get_total_energy(&self) -> f32 {
-		if self.total_mass.is_infinite() {
-			if self.velocity.magnitude() < EPSILON && self.angular_velocity.magnitude() < EPSILON {
-				0.0
-			} else {
-				INFINITY
-			}
+	/// Gets how fast a point (that's specified in world coordinates) actually moves through the world. This is the velocity sped up or slowed down by `time_scale`.
+	pub fn get_scaled_velocity_at_world_position(&self, position : &Vec3) -> Vec3 {
+		self.get_velocity_at_world_position(position) * self.time_scale
+	}
+
+	/// Gets the energy from this object moving (ignoring its rotation).
+	pub fn get_linear_energy(&self) -> f32 {
+		if !self.is_dynamic() {
+			if self.velocity.magnitude() < EPSILON { 0.0 } else { INFINITY }
+		} else {
+			(self.total_mass * self.velocity).dot(&self.velocity) / 2.0
+		}
+	}
+
+	/// Gets the energy from this object rotating.
+	pub fn get_angular_energy(&self) -> f32 {
+		if !self.is_dynamic() {
+			if self.angular_velocity.magnitude() < EPSILON { 0.0 } else { INFINITY }
 		} else {
-			let linear_energy = (self.total_mass * self.velocity).dot(&self.velocity) / 2.0;
-			let angular_energy = (self.get_moment_of_inertia() * self.angular_velocity).dot(&self.angular_velocity) / 2.0;
-			linear_energy + angular_energy
+			(self.get_moment_of_inertia() * self.angular_velocity).dot(&self.angular_velocity) / 2.0
 		}
 	}
 
+	/// Adds to the linear velocity. With the `fixed-point` feature, the change is added to [InternalEntity::fixed_velocity] (so it's exact), and `velocity` is set to the nearest float.
+	pub fn change_velocity(&mut self, change : &Vec3) {
+		#[cfg(feature = "fixed-point")]
+		{
+			self.fixed_velocity += FixedVec3::from_vec3(change);
+			self.velocity = self.fixed_velocity.to_vec3();
+		}
+		#[cfg(not(feature = "fixed-point"))]
+		{ self.velocity += change; }
+	}
+
+	/// Gets the exact (fixed-point) position of the center of mass and velocity.
+	///
+	/// Either one is reset to its float version if that was changed directly (i.e. not by step()) since they were last in sync.
+	#[cfg(feature = "fixed-point")]
+	pub fn fixed_state(&self) -> (FixedVec3, FixedVec3) {
+		let position = if self.fixed_position.to_vec3() == self.orientation.position { self.fixed_position } else { FixedVec3::from_vec3(&self.orientation.position) };
+		let velocity = if self.fixed_velocity.to_vec3() == self.velocity { self.fixed_velocity } else { FixedVec3::from_vec3(&self.velocity) };
+		(position, velocity)
+	}
+
 	/// Applies an impulse at a (world) position to this instance's linear and angular velocities.
+	///
+	/// Does nothing to anything that isn't dynamic.
 	pub fn apply_impulse(&mut self, position : &Vec3, impulse : &Vec3) {
-		self.velocity += impulse.scale(1.0 / self.get_total_mass());
+		if !self.is_dynamic() { return; }
+		self.change_velocity(&impulse.scale(self.get_inverse_mass()));
 		self.angular_velocity += self.get_inverse_moment_of_inertia() * (position - self.orientation.position).cross(&impulse);
 	}
 
 	/// Wakes up this entity and any neighbors it is in contact with (recursively).
-	pub fn wake_up(start : EntityHandle, all_entities : &mut Arena<InternalEntity>, debug : &mut Vec<String>) {
+	///
+	/// Returns whether anything might have changed (i.e. whether any entity was woken up or had its neighbors changed).
+	pub fn wake_up(start : EntityHandle, all_entities : &mut Arena<InternalEntity>, debug : &mut Vec<String>) -> bool {
+		{ // Most of the time it's already awake and has nobody to pass it on to, so skip setting up the search.
+			let entity = all_entities.get(start).unwrap();
+			if !entity.asleep && entity.neighbors.is_empty() { return false; }
+		}
 		let mut completed = HashSet::new();
 		let mut queue = VecDeque::new();
 		queue.push_back(start);
@@ -236,12 +418,12 @@ impl InternalEntity {
 			for neighbor_handle in target_neighbors {
 				if completed.contains(&neighbor_handle) { continue; }
 				let neighbor = all_entities.get_mut(neighbor_handle).unwrap();
-				if neighbor.total_mass.is_infinite() {
+				if !neighbor.is_dynamic() {
 					// Remove self from neighbor's neighbor set.
-					// Must do this as infinite-mass neighbors can't be woken up when collided with.
-					// But having something in the "neighbor" set means it won't be checked for collision (which is bad as the target just woke up and may need to hit/bounce off of the infinite-mass entity).
+					// Must do this as static/kinematic neighbors can't be woken up when collided with.
+					// But having something in the "neighbor" set means it won't be checked for collision (which is bad as the target just woke up and may need to hit/bounce off of the static/kinematic entity).
 					neighbor.neighbors.remove(&target_handle);
-					println!("Removed {:?} from neighbor set of {:?}.", target_handle, neighbor_handle);
+					debug_print!("Removed {:?} from neighbor set of {:?}.", target_handle, neighbor_handle);
 					debug.push(format!("Removed {:?} from neighbor set of {:?}.", target_handle, neighbor_handle));
 					// Also don't bother trying to wake it up.
 					continue;
@@ -253,18 +435,20 @@ impl InternalEntity {
 			{ // Then wake up the target.
 				let target = all_entities.get_mut(target_handle).unwrap();
 				if target.asleep {
-					println!("Waking up {:?}.", target_handle);
+					debug_print!("Waking up {:?}.", target_handle);
 					debug.push(format!("Waking up {:?}.", target_handle));
 				}
 				target.asleep = false;
 				target.neighbors.clear();
 			}
 		}
+		true
 	}
 }
 
 /// A copy of all of the publicly-accessible properties of a physical object in the world.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Entity {
 	/// The current position of the center of mass in WORLD space.
 	///
@@ -300,6 +484,55 @@ pub struct Entity {
 	/// Defaults to zero.
 	pub own_mass : f32,
 
+	/// How this entity moves (i.e. whether forces and collisions can push it around).
+	///
+	/// Giving a collider infinite mass is the older (deprecated) way to make an entity immovable. Dynamic entities with an infinite total mass are treated as [BodyType::Kinematic] (which is the same as [BodyType::Static] while they aren't moving).
+	///
+	/// Dynamic entities with colliders but (next to) no total mass are also treated as kinematic, as there's no sensible way to push them around.
+	///
+	/// Defaults to [BodyType::Dynamic].
+	pub body_type : BodyType,
+
+	/// The query groups (as bit flags) that this entity belongs to.
+	///
+	/// Masked casts (like [crate::PhysicsSystem::cast_ray_masked]) skip every entity that doesn't share at least one bit with the mask. This has no effect on the simulation itself.
+	///
+	/// Defaults to `1`.
+	pub query_groups : u32,
+
+	/// Whether collisions push this entity around. When this is `false`, collisions with it are still found and reported (in [crate::PhysicsSystem::collision_records] and the like), but no impulse is applied to it, and it just carries on through whatever it hit. i.e. for ghosts, or intangibility power-ups.
+	///
+	/// The other entity in each collision still bounces off like normal, unless it doesn't respond either. Each pair only collides once per step while either doesn't respond, so passing through something doesn't take up every iteration. Unlike query only colliders (i.e. [crate::SphereCollider::query_only]), this covers the whole entity.
+	///
+	/// Defaults to `true`.
+	pub respond : bool,
+
+	/// Decides which entity gives way when two collide. The one with the lower dominance takes all of the collision's response, while the higher one carries on as if it had infinite mass (without its mass actually changing, so forces still move it like normal). Entities with the same dominance push each other around like normal.
+	///
+	/// i.e. give the player a higher dominance than the crowd, so it can shove its way through without being nudged off of a ledge.
+	///
+	/// Defaults to `0`.
+	pub dominance : i32,
+
+	/// A name, i.e. for finding it again with [crate::PhysicsSystem::find_entity_by_name] or telling entities apart while debugging. Names don't need to be unique. This has no effect on the simulation itself.
+	///
+	/// Defaults to None.
+	pub name : Option<String>,
+
+	/// How fast time passes for this entity, relative to the rest of the system. i.e. `0.5` makes it move (and fall, spin, and get pushed around) at half speed, as if it were in a slow-motion bubble.
+	///
+	/// Its velocities stay what they'd be at normal speed, so setting this back to `1.0` has it carry on like nothing happened. Zero freezes it in place: anything that hits it treats it as immovable, but it still keeps the impulses for when it's unfrozen. Must not be negative.
+	///
+	/// Defaults to `1.0`.
+	pub time_scale : f32,
+
+	/// How much longer (in seconds) until the entity is removed, i.e. for debris that should clean itself up. Every step() counts this down by its `dt` (no matter the `time_scale`), and removes the entity (along with its colliders) once it runs out. See [crate::PhysicsSystem::take_expired_entities] for finding out which ones were removed.
+	///
+	/// Disabled entities don't count down. Must not be NaN.
+	///
+	/// Defaults to None (never removed).
+	pub lifetime : Option<f32>,
+
 	/// The last known orientation. This is very much read-only.
 	///
 	/// Defaults to having no offset or transform.
@@ -315,12 +548,29 @@ pub struct Entity {
 	/// Defaults to a zero matrix.
 	last_prepped_moment_of_inertia : Mat3,
 
+	/// The average force on the entity during the last step. This is very much read-only.
+	///
+	/// Defaults to zero.
+	last_applied_force : Vec3,
+
+	/// The average torque on the entity during the last step. This is very much read-only.
+	///
+	/// Defaults to zero.
+	last_applied_torque : Vec3,
+
 	/// Whether the entity has been put to sleep.
 	///
 	/// When asleep, the entity won't receive physics updates until it (or something it's in contact with) is hit.
 	///
 	/// Defaults to `false`.
 	asleep : bool,
+
+	/// Whether the entity is taking part in the simulation.
+	///
+	/// This can only be changed with [crate::PhysicsSystem::set_entity_enabled].
+	///
+	/// Defaults to `true`.
+	enabled : bool,
 }
 
 impl Entity {
@@ -334,18 +584,34 @@ impl Entity {
 			angular_velocity: Vec3::zeros(),
 			colliders: HashSet::new(),
 			own_mass: 0.0,
+			body_type: BodyType::Dynamic,
+			query_groups: 1,
+			respond: true,
+			dominance: 0,
+			name: None,
+			time_scale: 1.0,
+			lifetime: None,
 			last_orientation: Orientation::new(
-				&Vec3::zeros(),
-				&Vec3::zeros(),
-				&Vec3::zeros(),
+				Vec3::zeros(),
+				Vec3::zeros(),
+				Vec3::zeros(),
 			),
 			last_total_mass: 0.0,
 			last_prepped_moment_of_inertia: Mat3::zeros(),
+			last_applied_force: Vec3::zeros(),
+			last_applied_torque: Vec3::zeros(),
 
 			asleep: false,
+
+			enabled: true,
 		}
 	}
 
+	/// If this is in a valid state.
+	pub fn is_valid(&self) -> bool {
+		0.0 <= self.own_mass && 0.0 <= self.time_scale && self.time_scale.is_finite() && !self.lifetime.is_some_and(f32::is_nan)
+	}
+
 	/// Gets all collider handles.
 	///
 	/// Notibly this is just the getter, as this object cannot be used to modify what colliders are attached to this entity (must use [crate::PhysicsSystem::link_collider] for that).
@@ -366,9 +632,9 @@ impl Entity {
 	/// Creates a new orientation using the current values of position and rotation along with the center of mass offset from the last orientation.
 	pub fn make_orientation(&self) -> Orientation {
 		Orientation::new(
-			&self.position,
-			&self.rotation,
-			&self.last_orientation.internal_origin_offset,
+			self.position,
+			self.rotation,
+			self.last_orientation.internal_origin_offset,
 		)
 	}
 
@@ -380,15 +646,36 @@ impl Entity {
 		self.make_orientation().finalize_moment_of_inertia(&self.last_prepped_moment_of_inertia)
 	}
 
+	/// Gets the velocity at a point (that's specified in world coordinates).
+	pub fn get_velocity_at_world_position(&self, position : &Vec3) -> Vec3 {
+		self.velocity + self.angular_velocity.cross(&(position - self.position))
+	}
+
 	/// Gets the total energy of this object.
 	pub fn get_total_energy(&self) -> f32 {
+		if BodyType::Dynamic != self.body_type || self.last_total_mass.is_infinite() {
+			return if self.velocity.magnitude() < EPSILON && self.angular_velocity.magnitude() < EPSILON { 0.0 } else { f32::INFINITY };
+		}
 		let linear_energy = (self.last_total_mass * self.velocity).dot(&self.velocity) / 2.0;
 		let angular_energy = (self.get_last_moment_of_inertia() * self.angular_velocity).dot(&self.angular_velocity) / 2.0;
 		linear_energy + angular_energy
 	}
 
+	/// Gets the average force on the entity during the last step, from both force generators and collisions (i.e. to squash something in proportion to how hard it landed).
+	///
+	/// This is how much its momentum was changed, divided by the step's `dt`. Collisions push on entities that aren't dynamic too (so a floor feels the weight of what's on it), but force generators don't. Zero for dynamic entities that are asleep.
+	pub fn get_last_applied_force(&self) -> Vec3 { self.last_applied_force }
+
+	/// Gets the average torque (about the center of mass) on the entity during the last step, from both force generators and collisions.
+	pub fn get_last_applied_torque(&self) -> Vec3 { self.last_applied_torque }
+
 	/// Checks whether the entity was asleep.
 	pub fn was_asleep(&self) -> bool {
 		self.asleep
 	}
+
+	/// Checks whether the entity was enabled (i.e. taking part in the simulation).
+	pub fn was_enabled(&self) -> bool {
+		self.enabled
+	}
 }
@@ -0,0 +1,54 @@
+use crate::types::{Vec3, EntityHandle};
+use crate::orientation::Orientation;
+use crate::entity::InternalEntity;
+
+/// A read-only snapshot of the entity a force generator is being asked about. See [crate::UnaryForceGenerator::make_force].
+///
+/// This is where the entity is right now (i.e. partway through a step), which isn't always where [crate::PhysicsSystem::get_entity] would say it is.
+#[derive(Debug, Clone)]
+pub struct EntityView {
+	/// The entity's handle. Made up (and not in any system) when the generators are only being sampled (see [crate::PhysicsSystem::sample_forces]).
+	pub handle : EntityHandle,
+	/// Where the entity is, and how it's rotated.
+	pub orientation : Orientation,
+	/// The linear velocity.
+	pub velocity : Vec3,
+	/// The angular velocity (in WORLD space).
+	pub angular_velocity : Vec3,
+	/// The total mass of the entity and all of its colliders.
+	pub mass : f32,
+}
+
+impl EntityView {
+	/// Takes a snapshot of the given entity.
+	pub(crate) fn new(handle : EntityHandle, entity : &InternalEntity) -> EntityView {
+		EntityView {
+			handle,
+			orientation: entity.orientation,
+			velocity: entity.velocity,
+			angular_velocity: entity.angular_velocity,
+			mass: entity.get_total_mass(),
+		}
+	}
+
+	/// Makes up a still entity at the given position, that isn't in any system. Fails if the mass isn't valid.
+	pub(crate) fn probe(position : &Vec3, mass : f32) -> Result<EntityView, ()> {
+		if mass.is_nan() || mass < 0.0 { return Err(()); }
+		Ok(EntityView {
+			// No arena ever gets anywhere near this many slots, so it can't be mistaken for a real entity.
+			handle: EntityHandle::from_raw_parts(usize::MAX, u64::MAX),
+			orientation: Orientation::new(*position, Vec3::zeros(), Vec3::zeros()),
+			velocity: Vec3::zeros(),
+			angular_velocity: Vec3::zeros(),
+			mass,
+		})
+	}
+
+	/// Gets the entity's center of mass (in WORLD space).
+	pub fn position(&self) -> Vec3 { self.orientation.position }
+
+	/// Gets the velocity at a point (that's specified in world coordinates).
+	pub fn get_velocity_at_world_position(&self, position : &Vec3) -> Vec3 {
+		self.velocity + self.angular_velocity.cross(&(position - self.orientation.position))
+	}
+}
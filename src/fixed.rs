@@ -0,0 +1,144 @@
+use std::ops::{Add, Sub, Mul, Div, Neg, AddAssign, SubAssign};
+
+use crate::types::Vec3;
+
+/// A signed Q32.32 fixed-point number: 32 bits for the integer part and 32 bits for the fraction. Only available with the `fixed-point` feature.
+///
+/// Unlike floats, adding these up is exact (as long as nothing goes past about two billion), so the result doesn't depend on the order things happen in or on how the compiler chose to round. That makes it useful for state that lockstep servers need to agree on bit-for-bit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Fixed(i64);
+
+/// How many fractional bits there are.
+const FRACTION_BITS : u32 = 32;
+/// The scale between the raw bits and the value they represent.
+const SCALE : f64 = (1u64 << FRACTION_BITS) as f64;
+
+impl Fixed {
+	/// Zero.
+	pub const ZERO : Fixed = Fixed(0);
+	/// One.
+	pub const ONE : Fixed = Fixed(1 << FRACTION_BITS);
+
+	/// Creates an instance from its raw bits (i.e. the value times 2^32).
+	pub const fn from_bits(bits : i64) -> Fixed { Fixed(bits) }
+	/// The raw bits (i.e. the value times 2^32).
+	pub const fn to_bits(self) -> i64 { self.0 }
+
+	/// Rounds a float to the nearest value this can store. Values past the range saturate, and NaN becomes zero.
+	pub fn from_f32(value : f32) -> Fixed {
+		Fixed((value as f64 * SCALE).round() as i64)
+	}
+
+	/// The nearest float to this value.
+	pub fn to_f32(self) -> f32 {
+		(self.0 as f64 / SCALE) as f32
+	}
+}
+
+impl Add for Fixed {
+	type Output = Fixed;
+	fn add(self, other : Fixed) -> Fixed { Fixed(self.0 + other.0) }
+}
+
+impl Sub for Fixed {
+	type Output = Fixed;
+	fn sub(self, other : Fixed) -> Fixed { Fixed(self.0 - other.0) }
+}
+
+impl Mul for Fixed {
+	type Output = Fixed;
+	/// Rounds towards negative infinity.
+	fn mul(self, other : Fixed) -> Fixed { Fixed(((self.0 as i128 * other.0 as i128) >> FRACTION_BITS) as i64) }
+}
+
+impl Div for Fixed {
+	type Output = Fixed;
+	/// Rounds towards zero. Panics when dividing by zero.
+	fn div(self, other : Fixed) -> Fixed { Fixed((((self.0 as i128) << FRACTION_BITS) / other.0 as i128) as i64) }
+}
+
+impl Neg for Fixed {
+	type Output = Fixed;
+	fn neg(self) -> Fixed { Fixed(-self.0) }
+}
+
+impl AddAssign for Fixed {
+	fn add_assign(&mut self, other : Fixed) { self.0 += other.0; }
+}
+
+impl SubAssign for Fixed {
+	fn sub_assign(&mut self, other : Fixed) { self.0 -= other.0; }
+}
+
+/// A 3D vector of [Fixed] values. Only available with the `fixed-point` feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FixedVec3 {
+	pub x : Fixed,
+	pub y : Fixed,
+	pub z : Fixed,
+}
+
+impl FixedVec3 {
+	/// Creates an instance from its parts.
+	pub fn new(x : Fixed, y : Fixed, z : Fixed) -> FixedVec3 {
+		FixedVec3 { x, y, z }
+	}
+
+	/// Rounds each part of a float vector to the nearest value [Fixed] can store.
+	pub fn from_vec3(value : &Vec3) -> FixedVec3 {
+		FixedVec3::new(Fixed::from_f32(value.x), Fixed::from_f32(value.y), Fixed::from_f32(value.z))
+	}
+
+	/// The nearest float vector to this value.
+	pub fn to_vec3(&self) -> Vec3 {
+		Vec3::new(self.x.to_f32(), self.y.to_f32(), self.z.to_f32())
+	}
+
+	/// Multiplies each part by the same value.
+	pub fn scale(&self, factor : Fixed) -> FixedVec3 {
+		FixedVec3::new(self.x * factor, self.y * factor, self.z * factor)
+	}
+}
+
+impl Add for FixedVec3 {
+	type Output = FixedVec3;
+	fn add(self, other : FixedVec3) -> FixedVec3 { FixedVec3::new(self.x + other.x, self.y + other.y, self.z + other.z) }
+}
+
+impl Sub for FixedVec3 {
+	type Output = FixedVec3;
+	fn sub(self, other : FixedVec3) -> FixedVec3 { FixedVec3::new(self.x - other.x, self.y - other.y, self.z - other.z) }
+}
+
+impl AddAssign for FixedVec3 {
+	fn add_assign(&mut self, other : FixedVec3) { *self = *self + other; }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Verify the conversions and basic math.
+	#[test]
+	fn arithmetic() {
+		assert_eq!(Fixed::from_f32(1.0), Fixed::ONE);
+		assert_eq!(Fixed::from_f32(-2.5).to_f32(), -2.5);
+		assert_eq!(Fixed::from_f32(f32::NAN), Fixed::ZERO);
+		assert_eq!(Fixed::from_f32(0.5).to_bits(), 1 << 31);
+		assert_eq!(Fixed::from_f32(1.5) + Fixed::from_f32(2.25), Fixed::from_f32(3.75));
+		assert_eq!(Fixed::from_f32(1.5) - Fixed::from_f32(2.25), Fixed::from_f32(-0.75));
+		assert_eq!(Fixed::from_f32(1.5) * Fixed::from_f32(-4.0), Fixed::from_f32(-6.0));
+		assert_eq!(Fixed::from_f32(3.0) / Fixed::from_f32(-4.0), Fixed::from_f32(-0.75));
+		assert_eq!(-Fixed::ONE, Fixed::from_f32(-1.0));
+
+		// Adding up small steps is exact, even far from the origin (where an f32 can't even tell the steps apart).
+		let mut position = Fixed::from_f32(100000.0);
+		let step = Fixed::from_bits(1 << 20);
+		for _ in 0..1000 { position += step; }
+		assert_eq!(position - Fixed::from_f32(100000.0), Fixed::from_bits(1000 << 20));
+
+		let vector = FixedVec3::from_vec3(&Vec3::new(1.0, -2.0, 0.25));
+		assert_eq!((vector + vector - vector).to_vec3(), Vec3::new(1.0, -2.0, 0.25));
+		assert_eq!(vector.scale(Fixed::from_f32(-2.0)).to_vec3(), Vec3::new(-2.0, 4.0, -0.5));
+	}
+}
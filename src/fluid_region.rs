@@ -0,0 +1,202 @@
+use std::collections::HashSet;
+
+use crate::consts::EPSILON;
+use crate::types::{Vec3, EntityHandle, ColliderHandle, FluidRegionHandle};
+use crate::orientation::Orientation;
+use crate::collider::InternalCollider;
+use crate::convex_shape::as_convex_shape;
+use crate::query::closest_point_on;
+
+/// How many points are sampled along each axis of a collider's bounds, when estimating how much of it is in a [FluidRegion].
+const SAMPLES_PER_AXIS : usize = 6;
+
+/// A volume of fluid (like a pool or a river) that pushes up on (and drags along) everything that's in it. See [crate::PhysicsSystem::add_fluid_region].
+///
+/// The volume is the shape of a collider: usually a query-only one, so things pass into it rather than bouncing off. It can be linked to an entity (to move the fluid around with it) or loose (in which case it's in world space). Planes work too, for an endless ocean below them.
+///
+/// How much of each collider is in the fluid is estimated by sampling points around it, so small colliders in big regions are only roughly right. Meshes are treated as their convex hull, and planes and null colliders are never pushed on.
+#[derive(Debug, Clone)]
+pub struct FluidRegion {
+	/// The collider whose shape is filled with fluid. The region does nothing while the collider doesn't exist.
+	pub collider : ColliderHandle,
+
+	/// How heavy the fluid is per unit of volume. Colliders that are less dense than this float.
+	///
+	/// Defaults to 1000 (water, in kilograms per cubic meter).
+	pub density : f32,
+
+	/// How fast the fluid is flowing. Things in it are dragged towards this velocity.
+	///
+	/// Defaults to zero.
+	pub flow_velocity : Vec3,
+
+	/// How strongly things are dragged towards the flow velocity. The drag force is this times the weight of the fluid that's pushed aside (its density times volume) times the difference in velocity, so something as dense as the fluid loses this fraction of its relative velocity per second.
+	///
+	/// Defaults to one.
+	pub linear_drag : f32,
+
+	/// How strongly spinning is slowed down, in the same way as `linear_drag`.
+	///
+	/// Defaults to one.
+	pub angular_drag : f32,
+
+	/// The gravity the fluid is under. Buoyancy pushes the opposite way, with the weight of the fluid that's pushed aside. This should usually match the system's [crate::GravityGenerator].
+	///
+	/// Defaults to 9.8 down the Y axis.
+	pub gravity : Vec3,
+
+	/// Every entity that was (at least partly) in the region at the end of the last step.
+	submerged : HashSet<EntityHandle>,
+}
+
+impl FluidRegion {
+	/// Creates a region filled by the given collider, with all the defaults.
+	pub fn new(collider : ColliderHandle) -> FluidRegion {
+		FluidRegion {
+			collider,
+			density: 1000.0,
+			flow_velocity: Vec3::zeros(),
+			linear_drag: 1.0,
+			angular_drag: 1.0,
+			gravity: Vec3::new(0.0, -9.8, 0.0),
+			submerged: HashSet::new(),
+		}
+	}
+
+	/// If this is in a valid state.
+	pub fn is_valid(&self) -> bool {
+		self.density.is_finite() && 0.0 <= self.density &&
+		self.linear_drag.is_finite() && 0.0 <= self.linear_drag &&
+		self.angular_drag.is_finite() && 0.0 <= self.angular_drag &&
+		self.flow_velocity.iter().all(|value| value.is_finite()) &&
+		self.gravity.iter().all(|value| value.is_finite())
+	}
+
+	/// Whether an entity was (at least partly) in the region at the end of the last step.
+	pub fn is_submerged(&self, entity : EntityHandle) -> bool {
+		self.submerged.contains(&entity)
+	}
+
+	/// Replaces the entities that are in the region, returning which ones entered (true) and exited (false) it. Entered ones come first, and both are sorted by handle.
+	pub(crate) fn update_submerged(&mut self, submerged : HashSet<EntityHandle>) -> Vec<(EntityHandle, bool)> {
+		let mut entered : Vec<EntityHandle> = submerged.difference(&self.submerged).copied().collect();
+		let mut exited : Vec<EntityHandle> = self.submerged.difference(&submerged).copied().collect();
+		entered.sort();
+		exited.sort();
+		self.submerged = submerged;
+		entered.into_iter().map(|entity| (entity, true)).chain(exited.into_iter().map(|entity| (entity, false))).collect()
+	}
+
+	/// Forgets that an entity was in the region (i.e. because it was removed), without making an event for it.
+	pub(crate) fn forget(&mut self, entity : EntityHandle) {
+		self.submerged.remove(&entity);
+	}
+}
+
+/// An entity entering or exiting a [FluidRegion] during a step. See [crate::PhysicsSystem::take_fluid_events].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FluidEvent {
+	/// The region.
+	pub region : FluidRegionHandle,
+	/// The entity.
+	pub entity : EntityHandle,
+	/// Whether the entity entered the region (true) or exited it (false).
+	pub entered : bool,
+}
+
+/// Estimates how much of a collider is inside of a region's collider, as the volume that's inside and its center (in world space). Returns None if none of it is (or either has no shape).
+///
+/// Samples a grid of points over the collider's bounding sphere, keeping the ones inside of both.
+pub(crate) fn find_submerged(collider : &Box<dyn InternalCollider>, orientation : &Orientation, region : &Box<dyn InternalCollider>, region_orientation : &Orientation) -> Option<(f32, Vec3)> {
+	let (local_center, radius) = as_convex_shape(collider.as_ref())?.bounding_sphere();
+	let center = orientation.position_into_world(&local_center);
+	// Don't bother sampling when the region doesn't reach the collider at all.
+	if radius < (closest_point_on(region, region_orientation, &center)? - center).magnitude() {
+		return None;
+	}
+	let inside = |shape : &Box<dyn InternalCollider>, orientation : &Orientation, point : &Vec3| {
+		closest_point_on(shape, orientation, point).is_some_and(|closest| (closest - point).magnitude() <= EPSILON + 1e-5 * point.amax())
+	};
+	let spacing = 2.0 * radius / SAMPLES_PER_AXIS as f32;
+	let offset = |index : usize| (index as f32 + 0.5) * spacing - radius;
+	let mut count = 0;
+	let mut total = Vec3::zeros();
+	for x in 0..SAMPLES_PER_AXIS {
+		for y in 0..SAMPLES_PER_AXIS {
+			for z in 0..SAMPLES_PER_AXIS {
+				let point = center + Vec3::new(offset(x), offset(y), offset(z));
+				if inside(collider, orientation, &point) && inside(region, region_orientation, &point) {
+					count += 1;
+					total += point;
+				}
+			}
+		}
+	}
+	let volume = count as f32 * spacing.powi(3);
+	if 0 == count || volume < EPSILON {
+		return None;
+	}
+	Some((volume, total / count as f32))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::entity::Entity;
+	use crate::sphere_collider::SphereCollider;
+	use crate::aligned_box_collider::AlignedBoxCollider;
+	use crate::collider_wrapper::ColliderWrapper;
+	use crate::gravity_generator::GravityGenerator;
+	use crate::physics_system::PhysicsSystem;
+
+	/// Verify a light ball floats at the surface of a pool while a heavy one sinks, and both are reported entering (and the light one exiting) it.
+	#[test]
+	fn floats_and_sinks() {
+		let mut system = PhysicsSystem::new();
+		let pool = {
+			let mut water = AlignedBoxCollider::from_center_half_extents(Vec3::new(0.0, -10.0, 0.0), Vec3::new(10.0, 10.0, 10.0));
+			water.query_only = true;
+			system.add_collider(ColliderWrapper::AlignedBox(water)).unwrap()
+		};
+		let mut add_ball = |x : f32, mass : f32| {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(x, 1.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = mass;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		// A ball is about half a cubic unit, so (with a density of one) these are half and twice as dense as the water.
+		let light = add_ball(-2.0, 0.25);
+		let heavy = add_ball(2.0, 1.0);
+		system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -9.8, 0.0)))).unwrap();
+		let mut region = FluidRegion::new(pool);
+		region.density = 1.0;
+		region.linear_drag = 2.0;
+		let region = system.add_fluid_region(region).unwrap();
+		assert!(system.add_fluid_region(FluidRegion { density: -1.0, ..FluidRegion::new(pool) }).is_err());
+
+		let mut events = Vec::new();
+		for _ in 0..300 {
+			system.step(1.0 / 60.0);
+			events.extend(system.take_fluid_events());
+		}
+		let light_entity = system.get_entity(light).unwrap();
+		assert!(light_entity.position.y.abs() < 0.3, "{:?}", light_entity.position);
+		assert!(light_entity.velocity.magnitude() < 0.2, "{:?}", light_entity.velocity);
+		assert!(system.get_entity(heavy).unwrap().position.y < -2.0);
+		assert_eq!(events.len(), 2, "{:?}", events);
+		assert!(events.iter().all(|event| event.region == region && event.entered));
+		assert!(system.get_fluid_region(region).unwrap().is_submerged(light));
+
+		// Pulling the light ball out reports it exiting.
+		let mut entity = system.get_entity(light).unwrap();
+		entity.position.y = 5.0;
+		system.update_entity(light, entity).unwrap();
+		system.step(1.0 / 60.0);
+		assert_eq!(system.take_fluid_events(), vec![FluidEvent { region, entity: light, entered: false }]);
+		assert!(!system.get_fluid_region(region).unwrap().is_submerged(light));
+	}
+}
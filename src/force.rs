@@ -9,8 +9,8 @@ pub struct Force {
 }
 
 impl Force {
-	/// Creates a new instance by consuming the given vectors.
-	pub fn new(force : Vec3, position : Vec3) -> Force {
-		Force { force, position }
+	/// Creates a new instance by consuming the given vectors. Takes anything that turns into a [Vec3] (i.e. a plain `[f32; 3]`).
+	pub fn new(force : impl Into<Vec3>, position : impl Into<Vec3>) -> Force {
+		Force { force: force.into(), position: position.into() }
 	}
 }
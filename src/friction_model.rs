@@ -0,0 +1,14 @@
+/// How the friction response decides between the static and dynamic friction coefficients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrictionModel {
+	/// Static friction is used when the ratio of the (post-collision) normal speed to the sliding speed is below the colliders' combined `friction_threshold`.
+	/// Otherwise dynamic friction is used.
+	Threshold,
+	/// Standard Coulomb friction. Every pair of entities in contact is either sticking or slipping, and that state is kept from step to step.
+	///
+	/// * A new or sticking contact stays stuck as long as the friction impulse needed to stop all sliding is within the static friction limit (`static_friction_coefficient * normal_impulse`). Otherwise it starts slipping.
+	/// * A slipping contact uses dynamic friction, and only goes back to sticking once dynamic friction alone can stop it.
+	///
+	/// The colliders' `friction_threshold` values are ignored.
+	Coulomb,
+}
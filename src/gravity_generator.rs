@@ -1,28 +1,30 @@
-use crate::types::{Vec3, EntityHandle};
-use crate::physics_system::PhysicsSystem;
+use crate::types::Vec3;
+use crate::world_query::WorldQuery;
+use crate::entity_view::EntityView;
 use crate::force::Force;
 use crate::unary_force_generator::UnaryForceGenerator;
 
 /// A force generator for simulating (simple, mono-direcitonal) gravity.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct GravityGenerator {
 	/// The direction and magnitude of gravitational acceleration.
 	pub acceleration : Vec3,
 }
 
 impl GravityGenerator {
-	/// Creates a new gravitational force.
-	pub fn new(acceleration : Vec3) -> GravityGenerator {
-		GravityGenerator { acceleration }
+	/// Creates a new gravitational force. Takes anything that turns into a [Vec3] (i.e. a plain `[f32; 3]`).
+	pub fn new(acceleration : impl Into<Vec3>) -> GravityGenerator {
+		GravityGenerator { acceleration: acceleration.into() }
 	}
 }
 
 impl UnaryForceGenerator for GravityGenerator {
-	fn make_force(&mut self, _dt : f32, physics : &PhysicsSystem, handle : EntityHandle) -> Force {
-		let entity = physics.get_entity(handle).unwrap();
+	fn make_force(&mut self, _dt : f32, _world : &dyn WorldQuery, entity : &EntityView) -> Force {
 		Force::new(
-			self.acceleration.scale(entity.get_last_total_mass()),
-			entity.position,
+			self.acceleration.scale(entity.mass),
+			entity.position(),
 		)
 	}
+
+	fn clone_boxed(&self) -> Option<Box<dyn UnaryForceGenerator>> { Some(Box::new(self.clone())) }
 }
@@ -0,0 +1,168 @@
+use crate::consts::EPSILON;
+use crate::types::{Vec3, EntityHandle};
+use crate::world_query::WorldQuery;
+use crate::entity_view::EntityView;
+use crate::force::Force;
+use crate::unary_force_generator::UnaryForceGenerator;
+
+/// Holds an entity up at a set height above whatever is below it, i.e. for hovercraft and floating pickups.
+///
+/// Every step, it looks down from a point on the entity (with a ray, or a small sphere to smooth over gaps and edges) to find the ground. Then it cancels out gravity and adds a damped spring to pull the entity back to its height. Nothing is done when there's no ground in reach, so the entity just falls.
+///
+/// Only pushes on `entity`: every other entity gets no force from this.
+#[derive(Debug, Clone)]
+pub struct HoverGenerator {
+	/// The entity being held up.
+	pub entity : EntityHandle,
+
+	/// Where the entity looks down from (and is pushed up at), in the entity's LOCAL space.
+	///
+	/// Defaults to the local origin.
+	pub local_anchor : Vec3,
+
+	/// Which way is down, in WORLD space. Doesn't need to be normalized.
+	///
+	/// Defaults to down the Y axis.
+	pub down : Vec3,
+
+	/// How far above the ground the anchor should be. Must not be negative.
+	///
+	/// Defaults to 1.0.
+	pub height : f32,
+
+	/// How far down to look for the ground. Must be positive.
+	///
+	/// Defaults to 2.0.
+	pub max_distance : f32,
+
+	/// The radius of the sphere that's cast down to find the ground. Zero casts a ray instead. Must not be negative.
+	///
+	/// Defaults to 0.0.
+	pub probe_radius : f32,
+
+	/// The gravity to cancel out. This should usually match the system's [crate::GravityGenerator].
+	///
+	/// Defaults to 9.8 down the Y axis.
+	pub gravity : Vec3,
+
+	/// How hard the entity is pulled back to its height, as acceleration per unit of height that it's off by. Must not be negative.
+	///
+	/// Defaults to 20.0.
+	pub stiffness : f32,
+
+	/// How hard the entity's up and down movement is resisted, as acceleration per unit of speed. Must not be negative.
+	///
+	/// Defaults to 8.0 (which is close to just stopping without overshooting, with the default stiffness).
+	pub damping : f32,
+}
+
+impl HoverGenerator {
+	/// Creates a new hover on the given entity, with everything else at its defaults.
+	pub fn new(entity : EntityHandle) -> HoverGenerator {
+		HoverGenerator {
+			entity,
+			local_anchor: Vec3::zeros(),
+			down: Vec3::new(0.0, -1.0, 0.0),
+			height: 1.0,
+			max_distance: 2.0,
+			probe_radius: 0.0,
+			gravity: Vec3::new(0.0, -9.8, 0.0),
+			stiffness: 20.0,
+			damping: 8.0,
+		}
+	}
+
+	/// If this is in a valid state.
+	pub fn is_valid(&self) -> bool {
+		EPSILON < self.down.magnitude() && self.down.iter().all(|value| value.is_finite()) &&
+		self.gravity.iter().all(|value| value.is_finite()) &&
+		0.0 <= self.height && 0.0 < self.max_distance && 0.0 <= self.probe_radius &&
+		0.0 <= self.stiffness && 0.0 <= self.damping
+	}
+
+	/// Finds how far the ground is below the anchor (at the given world position), if it's in reach.
+	pub fn find_ground_distance(&self, world : &dyn WorldQuery, anchor : &Vec3) -> Option<f32> {
+		if EPSILON < self.probe_radius {
+			// The sphere stops when its edge touches, which is another radius above the ground.
+			world.cast_sphere(anchor, &self.down, self.max_distance, self.probe_radius, Some(self.entity)).map(|hit| hit.distance + self.probe_radius)
+		} else {
+			world.cast_ray(anchor, &self.down, self.max_distance, Some(self.entity)).map(|hit| hit.distance)
+		}
+	}
+}
+
+impl UnaryForceGenerator for HoverGenerator {
+	fn make_force(&mut self, _dt : f32, world : &dyn WorldQuery, entity : &EntityView) -> Force {
+		if entity.handle != self.entity { return Force::new(Vec3::zeros(), Vec3::zeros()); }
+		let anchor = entity.orientation.position_into_world(&self.local_anchor);
+		let distance = if let Some(distance) = self.find_ground_distance(world, &anchor) { distance } else { return Force::new(Vec3::zeros(), anchor); };
+		let up = -self.down.normalize();
+		let rising_speed = entity.get_velocity_at_world_position(&anchor).dot(&up);
+		let acceleration = -self.gravity + up * (self.stiffness * (self.height - distance) - self.damping * rising_speed);
+		Force::new(acceleration * entity.mass, anchor)
+	}
+
+	fn clone_boxed(&self) -> Option<Box<dyn UnaryForceGenerator>> { Some(Box::new(self.clone())) }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::f32::INFINITY;
+	use crate::entity::Entity;
+	use crate::sphere_collider::SphereCollider;
+	use crate::plane_collider::PlaneCollider;
+	use crate::collider_wrapper::ColliderWrapper;
+	use crate::gravity_generator::GravityGenerator;
+	use crate::physics_system::PhysicsSystem;
+
+	/// Verify a ball settles at its hover height above the ground (whether looking down with a ray or a sphere), and falls when the ground is out of reach.
+	#[test]
+	fn hovers() {
+		for probe_radius in [0.0, 0.25].iter() {
+			let mut system = PhysicsSystem::new();
+			system.energy_sleep_threshold = 0.0; // So it doesn't fall asleep before it's finished settling.
+			{
+				let entity_handle = system.add_entity(Entity::new()).unwrap();
+				let mut plane = PlaneCollider::new();
+				plane.mass = INFINITY;
+				let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+				system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+			}
+			let mut add_ball = |x : f32, height : f32| {
+				let mut entity = Entity::new();
+				entity.position = Vec3::new(x, height, 0.0);
+				let entity_handle = system.add_entity(entity).unwrap();
+				let mut sphere = SphereCollider::new(0.5);
+				sphere.mass = 2.0;
+				let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+				system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+				entity_handle
+			};
+			let ball = add_ball(0.0, 1.0);
+			let out_of_reach = add_ball(5.0, 10.0);
+			for entity in [ball, out_of_reach].iter() {
+				let mut hover = HoverGenerator::new(*entity);
+				hover.height = 2.0;
+				hover.max_distance = 3.0;
+				hover.probe_radius = *probe_radius;
+				assert!(hover.is_valid());
+				system.add_unary_force_generator(Box::new(hover)).unwrap();
+			}
+			system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -9.8, 0.0)))).unwrap();
+			for _ in 0..300 {
+				system.step(1.0 / 60.0);
+			}
+			let entity = system.get_entity(ball).unwrap();
+			assert!((entity.position.y - 2.0).abs() < 0.01, "{:?}", entity.position);
+			assert!(entity.velocity.magnitude() < 0.01);
+			// The other one fell until it came in reach, then was caught.
+			let entity = system.get_entity(out_of_reach).unwrap();
+			assert!((entity.position.y - 2.0).abs() < 0.1, "{:?}", entity.position);
+		}
+
+		let mut hover = HoverGenerator::new(EntityHandle::from_raw_parts(0, 0));
+		hover.max_distance = 0.0;
+		assert!(!hover.is_valid());
+	}
+}
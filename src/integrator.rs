@@ -0,0 +1,56 @@
+/// How [crate::PhysicsSystem::step] turns forces into movement.
+///
+/// This only changes how the forces are integrated (once per `force_substeps` piece). Collisions are handled the same way no matter which is picked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Integrator {
+	/// Updates the velocities from the forces, then moves along the new velocities. Only asks the force generators once, but slowly gains or loses energy with stiff forces (i.e. springs).
+	SemiImplicitEuler,
+	/// Moves along the average of the old and new velocities, using the forces from both where things started and where they end up. Asks the force generators twice, but keeps the energy of springs from drifting away over time.
+	VelocityVerlet,
+	/// The classic fourth-order Runge-Kutta method. Asks the force generators four times, but is by far the most accurate for forces that change smoothly.
+	RungeKutta4,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::types::Vec3;
+	use crate::entity::Entity;
+	use crate::sphere_collider::SphereCollider;
+	use crate::collider_wrapper::ColliderWrapper;
+	use crate::anchored_spring_generator::AnchoredSpringGenerator;
+	use crate::physics_system::PhysicsSystem;
+
+	/// Verify each integrator keeps a ball on a spring oscillating like it should, with the fancier ones getting closer.
+	#[test]
+	fn spring() {
+		let stiffness : f32 = 100.0; // So (with a mass of one) it oscillates at 10 radians per second.
+		let errors : Vec<f32> = [Integrator::SemiImplicitEuler, Integrator::VelocityVerlet, Integrator::RungeKutta4].iter().map(|integrator| {
+			let mut system = PhysicsSystem::new();
+			system.integrator = *integrator;
+			system.energy_sleep_threshold = 0.0; // So it doesn't fall asleep at the ends of its swing.
+			let ball = {
+				let mut entity = Entity::new();
+				entity.position = Vec3::new(1.0, 0.0, 0.0);
+				let entity_handle = system.add_entity(entity).unwrap();
+				let mut sphere = SphereCollider::new(0.1);
+				sphere.mass = 1.0;
+				let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+				system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+				entity_handle
+			};
+			let mut spring = AnchoredSpringGenerator::new(ball);
+			spring.stiffness = stiffness;
+			system.add_unary_force_generator(Box::new(spring)).unwrap();
+			let dt = 0.02;
+			for _ in 0..100 {
+				system.step(dt);
+			}
+			let expected = (stiffness.sqrt() * 100.0 * dt).cos();
+			(system.get_entity(ball).unwrap().position.x - expected).abs()
+		}).collect();
+		assert!(errors[0] < 0.5, "{:?}", errors);
+		assert!(errors[1] < errors[0], "{:?}", errors);
+		assert!(errors[2] < errors[1] && errors[2] < 0.01, "{:?}", errors);
+	}
+}
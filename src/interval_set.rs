@@ -0,0 +1,183 @@
+use std::f32::NAN;
+
+use crate::range::Range;
+
+/// The most intervals an [IntervalSet] holds. Past this, the two intervals with the smallest gap between them are merged (so values are never lost, the set just gets a bit bigger than it should be).
+const MAX_INTERVALS : usize = 4;
+
+/// A set of scalar values made up of (a few) disjoint [Range]s, i.e. the separate windows of time that two things overlap.
+///
+/// A single [Range] can only ever be one continuous interval, so combining disjoint ones with [Range::contain] fills in the gap between them. This keeps them apart instead. The intervals are always sorted and never touch each other.
+#[derive(Copy, Clone, Debug)]
+pub struct IntervalSet {
+	/// The intervals, sorted. Only the first `count` are used.
+	intervals : [Range; MAX_INTERVALS],
+	/// How many intervals there are.
+	count : usize,
+}
+
+impl IntervalSet {
+	/// Creates an empty set.
+	pub fn empty() -> IntervalSet {
+		IntervalSet { intervals: [Range::empty(); MAX_INTERVALS], count: 0 }
+	}
+
+	/// Creates a set holding a single range (or nothing, if it's empty).
+	pub fn from_range(range : &Range) -> IntervalSet {
+		IntervalSet::empty().union(range)
+	}
+
+	/// Builds a set out of sorted (but possibly overlapping) intervals, merging them down to at most MAX_INTERVALS.
+	fn from_sorted(sorted : &[Range]) -> IntervalSet {
+		let mut merged = [Range::empty(); MAX_INTERVALS + 1];
+		let mut count = 0;
+		for interval in sorted.iter().filter(|interval| !interval.is_empty()) {
+			if 0 < count && interval.min() <= merged[count - 1].max() {
+				merged[count - 1] = merged[count - 1].contain(interval);
+			} else {
+				merged[count] = *interval;
+				count += 1;
+			}
+		}
+		// Too many, so fill in the smallest gaps until there's few enough.
+		while MAX_INTERVALS < count {
+			let index = (1..count).min_by(|a, b| {
+				let gap = |index : usize| merged[index].min() - merged[index - 1].max();
+				gap(*a).partial_cmp(&gap(*b)).unwrap()
+			}).unwrap();
+			merged[index - 1] = merged[index - 1].contain(&merged[index]);
+			merged.copy_within(index + 1..count, index);
+			count -= 1;
+		}
+		let mut set = IntervalSet::empty();
+		set.intervals[..count].copy_from_slice(&merged[..count]);
+		set.count = count;
+		set
+	}
+
+	/// Whether this is empty.
+	pub fn is_empty(&self) -> bool {
+		0 == self.count
+	}
+
+	/// Gets the intervals, in increasing order.
+	pub fn intervals(&self) -> &[Range] {
+		&self.intervals[..self.count]
+	}
+
+	/// The lowest value in the set. NaN if it's empty (like [Range::min]).
+	#[allow(dead_code)]
+	pub fn min(&self) -> f32 {
+		self.first().min()
+	}
+
+	/// The highest value in the set. NaN if it's empty (like [Range::max]).
+	#[allow(dead_code)]
+	pub fn max(&self) -> f32 {
+		if self.is_empty() { NAN } else { self.intervals[self.count - 1].max() }
+	}
+
+	/// Gets the first (lowest) interval. Empty if the set is.
+	pub fn first(&self) -> Range {
+		if self.is_empty() { Range::empty() } else { self.intervals[0] }
+	}
+
+	/// Gets a single range that covers the whole set (gaps included). The same as combining every interval with [Range::contain].
+	#[allow(dead_code)]
+	pub fn hull(&self) -> Range {
+		self.intervals().iter().fold(Range::empty(), |hull, interval| hull.contain(interval))
+	}
+
+	/// Whether the value is in the set.
+	#[allow(dead_code)]
+	pub fn contains(&self, value : f32) -> bool {
+		self.intervals().iter().any(|interval| interval.min() <= value && value <= interval.max())
+	}
+
+	/// Finds the set holding every value in either this or the range.
+	pub fn union(&self, range : &Range) -> IntervalSet {
+		if range.is_empty() { return *self; }
+		let mut sorted = [Range::empty(); MAX_INTERVALS + 1];
+		let index = self.intervals().iter().position(|interval| range.min() < interval.min()).unwrap_or(self.count);
+		sorted[..index].copy_from_slice(&self.intervals[..index]);
+		sorted[index] = *range;
+		sorted[index + 1..self.count + 1].copy_from_slice(&self.intervals[index..self.count]);
+		IntervalSet::from_sorted(&sorted[..self.count + 1])
+	}
+
+	/// Finds the set holding every value in either set.
+	#[allow(dead_code)]
+	pub fn union_set(&self, other : &IntervalSet) -> IntervalSet {
+		other.intervals().iter().fold(*self, |set, interval| set.union(interval))
+	}
+
+	/// Finds the values that are in both this and the range.
+	pub fn intersect(&self, range : &Range) -> IntervalSet {
+		let mut set = IntervalSet::empty();
+		for interval in self.intervals() {
+			let common = interval.intersect(range);
+			if !common.is_empty() {
+				set.intervals[set.count] = common;
+				set.count += 1;
+			}
+		}
+		set
+	}
+
+	/// Finds the values that are in both sets.
+	#[allow(dead_code)]
+	pub fn intersect_set(&self, other : &IntervalSet) -> IntervalSet {
+		other.intervals().iter().fold(IntervalSet::empty(), |set, interval| set.union_set(&self.intersect(interval)))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Gets a set's intervals as pairs of numbers, so they're easy to compare.
+	fn bounds(set : &IntervalSet) -> Vec<(f32, f32)> {
+		set.intervals().iter().map(|interval| (interval.min(), interval.max())).collect()
+	}
+
+	#[test]
+	fn check_union() {
+		let set = IntervalSet::from_range(&Range::range(4.0, 5.0))
+			.union(&Range::range(0.0, 1.0))
+			.union(&Range::empty())
+			.union(&Range::range(2.0, 3.0));
+		assert_eq!(bounds(&set), vec![(0.0, 1.0), (2.0, 3.0), (4.0, 5.0)]);
+		assert_eq!(set.min(), 0.0);
+		assert_eq!(set.max(), 5.0);
+		assert_eq!((set.hull().min(), set.hull().max()), (0.0, 5.0));
+		assert!(set.contains(2.5) && !set.contains(1.5));
+
+		// Overlapping (and touching) intervals are merged.
+		let merged = set.union(&Range::range(0.5, 2.0));
+		assert_eq!(bounds(&merged), vec![(0.0, 3.0), (4.0, 5.0)]);
+		assert!(IntervalSet::empty().is_empty());
+		assert!(IntervalSet::empty().min().is_nan());
+		assert!(IntervalSet::empty().first().is_empty());
+	}
+
+	#[test]
+	fn check_too_many() {
+		let mut set = IntervalSet::empty();
+		for start in [0.0, 10.0, 20.0, 30.0].iter() {
+			set = set.union(&Range::range(*start, *start + 1.0));
+		}
+		// The smallest gap is filled in.
+		set = set.union(&Range::range(21.5, 22.0));
+		assert_eq!(bounds(&set), vec![(0.0, 1.0), (10.0, 11.0), (20.0, 22.0), (30.0, 31.0)]);
+	}
+
+	#[test]
+	fn check_intersect() {
+		let first = IntervalSet::from_range(&Range::range(0.0, 2.0)).union(&Range::range(4.0, 6.0));
+		let second = IntervalSet::from_range(&Range::range(1.0, 5.0)).union(&Range::range(5.5, 7.0));
+		assert_eq!(bounds(&first.intersect(&Range::range(1.0, 4.5))), vec![(1.0, 2.0), (4.0, 4.5)]);
+		assert_eq!(bounds(&first.intersect_set(&second)), vec![(1.0, 2.0), (4.0, 5.0), (5.5, 6.0)]);
+		assert_eq!(bounds(&first.union_set(&second)), vec![(0.0, 7.0)]);
+		assert!(first.intersect(&Range::range(2.5, 3.5)).is_empty());
+	}
+}
@@ -0,0 +1,168 @@
+//! Tools for finding out how sensitive a [PhysicsSystem] is to tiny changes (i.e. in tests, or while researching why a scene isn't deterministic).
+//!
+//! Run a copy of a scene with a [Jitter] nudging it between steps, then compare it with the untouched original using [max_divergence]. Everything is driven by a seed, so any run can be repeated exactly.
+
+use crate::types::{Vec3, EntityHandle};
+use crate::physics_system::PhysicsSystem;
+
+/// A tiny random number generator (xorshift64*). Good enough for noise, and doesn't need another dependency.
+#[derive(Debug, Clone)]
+pub(crate) struct Xorshift(u64);
+
+impl Xorshift {
+	/// Creates a generator from a seed. Any seed works (even zero).
+	pub fn new(seed : u64) -> Xorshift {
+		// Xorshift gets stuck at zero, so mix the seed up into something that isn't.
+		Xorshift(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+	}
+
+	/// Gets the next number.
+	pub fn next_u64(&mut self) -> u64 {
+		self.0 ^= self.0 >> 12;
+		self.0 ^= self.0 << 25;
+		self.0 ^= self.0 >> 27;
+		self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+	}
+
+	/// Gets a number between -1.0 and 1.0.
+	pub fn next_signed(&mut self) -> f32 {
+		(self.next_u64() >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+	}
+
+	/// Gets a vector with each part between -1.0 and 1.0.
+	pub fn next_vec3(&mut self) -> Vec3 {
+		Vec3::new(self.next_signed(), self.next_signed(), self.next_signed())
+	}
+
+	/// Puts the items in a random order.
+	pub fn shuffle<T>(&mut self, items : &mut [T]) {
+		for index in (1..items.len()).rev() {
+			items.swap(index, (self.next_u64() % (index as u64 + 1)) as usize);
+		}
+	}
+}
+
+/// Nudges a system by tiny random amounts between steps.
+///
+/// Only enabled, dynamic entities that are awake are nudged (so nothing is woken up just by being nudged).
+#[derive(Debug, Clone)]
+pub struct Jitter {
+	/// The most each part of an entity's position is moved by.
+	///
+	/// Defaults to `1e-5`.
+	pub position_noise : f32,
+
+	/// The most each part of an entity's velocity is changed by.
+	///
+	/// Defaults to `1e-5`.
+	pub velocity_noise : f32,
+
+	/// Whether to also change the order that step() goes through pairs of entities in (see [PhysicsSystem::entity_order_seed]). Results shouldn't depend on that order, so any difference this makes is a bug.
+	///
+	/// Defaults to `false`.
+	pub shuffle_pairs : bool,
+
+	/// Where the noise comes from.
+	random : Xorshift,
+}
+
+impl Jitter {
+	/// Creates a jitter with all the defaults. The same seed always gives the same nudges.
+	pub fn new(seed : u64) -> Jitter {
+		Jitter {
+			position_noise: 1e-5,
+			velocity_noise: 1e-5,
+			shuffle_pairs: false,
+			random: Xorshift::new(seed),
+		}
+	}
+
+	/// Nudges every (awake, dynamic) entity in the system, and picks a new order for the next step (if `shuffle_pairs`).
+	pub fn apply(&mut self, system : &mut PhysicsSystem) {
+		let mut entities = system.entities.borrow_mut();
+		let mut handles : Vec<EntityHandle> = entities.iter().map(|(handle, _)| handle).collect();
+		handles.sort(); // So the nudges don't depend on how the arena is laid out.
+		for handle in handles {
+			let entity = entities.get_mut(handle).unwrap();
+			if !entity.enabled || !entity.is_dynamic() || entity.asleep { continue; }
+			entity.orientation.position += self.random.next_vec3() * self.position_noise;
+			entity.velocity += self.random.next_vec3() * self.velocity_noise;
+		}
+		if self.shuffle_pairs {
+			system.entity_order_seed = Some(self.random.next_u64());
+		}
+	}
+
+	/// Nudges the system (see [Jitter::apply]), then steps it.
+	pub fn step(&mut self, system : &mut PhysicsSystem, dt : f32) {
+		self.apply(system);
+		system.step(dt);
+	}
+}
+
+/// Finds the furthest apart that any entity is between two copies of a system (i.e. one that was jittered, and one that wasn't). Entities that are only in one of them are skipped.
+pub fn max_divergence(first : &PhysicsSystem, second : &PhysicsSystem) -> f32 {
+	let first_entities = first.entities.borrow();
+	let second_entities = second.entities.borrow();
+	first_entities.iter()
+		.filter_map(|(handle, entity)| second_entities.get(handle).map(|other| (entity.orientation.position - other.orientation.position).magnitude()))
+		.fold(0.0, f32::max)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::entity::Entity;
+	use crate::body_type::BodyType;
+	use crate::sphere_collider::SphereCollider;
+	use crate::plane_collider::PlaneCollider;
+	use crate::collider_wrapper::ColliderWrapper;
+	use crate::gravity_generator::GravityGenerator;
+
+	/// Verify the same seed always gives the same result, that no noise changes nothing (even with the pairs shuffled), and that noise does make a pile diverge a little.
+	#[test]
+	fn jittered_pile() {
+		let mut system = PhysicsSystem::new();
+		{
+			let mut entity = Entity::new();
+			entity.body_type = BodyType::Static;
+			let entity_handle = system.add_entity(entity).unwrap();
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(PlaneCollider::new())).unwrap();
+			system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+		}
+		for index in 0..6 {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new((index % 3) as f32 * 1.1, 0.5 + (index / 3) as f32 * 1.2, 0.1 * index as f32);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 1.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+		}
+		system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -9.8, 0.0)))).unwrap();
+
+		let run = |jitter : Option<Jitter>| {
+			let mut copy = system.clone();
+			let mut jitter = jitter;
+			for _ in 0..60 {
+				match jitter.as_mut() {
+					Some(jitter) => jitter.step(&mut copy, 1.0 / 60.0),
+					None => copy.step(1.0 / 60.0),
+				}
+			}
+			copy
+		};
+		let original = run(None);
+
+		let mut quiet = Jitter::new(7);
+		quiet.position_noise = 0.0;
+		quiet.velocity_noise = 0.0;
+		quiet.shuffle_pairs = true;
+		assert!(max_divergence(&original, &run(Some(quiet))) < 1e-4);
+
+		let noisy = run(Some(Jitter::new(7)));
+		assert_eq!(max_divergence(&noisy, &run(Some(Jitter::new(7)))), 0.0);
+		let divergence = max_divergence(&original, &noisy);
+		assert!(0.0 < divergence && divergence < 0.1, "{}", divergence);
+	}
+}
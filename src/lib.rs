@@ -6,13 +6,39 @@
 //! * All collision detection is also linearized; so it calculates the starting and ending position and assumes the objects take a linear path between them. As long as time steps are kept small, this should be a good enough approximation (even for rotating bodies).
 ///!
 
+/// Prints what the engine is doing (for debugging), but only with the `debug-print` feature. Otherwise it's far too noisy (and slow).
+///
+/// This writes straight to (unbuffered) stderr rather than using `println!()`, as that can allocate (e.g. when tests capture the output), and `step()` must never allocate.
+macro_rules! debug_print {
+	($($arg:tt)*) => { if cfg!(feature = "debug-print") {
+		use std::io::Write;
+		let mut stderr = std::io::stderr().lock();
+		let _ = stderr.write_fmt(format_args!($($arg)*)).and_then(|_| stderr.write_all(b"\n"));
+	} };
+}
+
 mod consts;
 pub mod types;
-pub use types::{EntityHandle, ColliderHandle, UnaryForceGeneratorHandle};
+pub use types::{EntityHandle, ColliderHandle, UnaryForceGeneratorHandle, ClothHandle, ParticleSetHandle, FluidRegionHandle, JointHandle, MaterialHandle, ShapeHandle, handle_to_id, handle_from_id, quat_from_array, quat_to_array};
+/// The exact nalgebra version [types::Vec3] and [types::Quat] come from, so they can be built without depending on it separately.
+pub use nalgebra;
+#[cfg(feature = "mint")]
+pub use types::quat_from_mint;
+/// The exact `mint` version the conversions are for. Only available with the `mint` feature.
+#[cfg(feature = "mint")]
+pub use mint;
 mod range;
+mod interval_set;
+#[cfg(feature = "fixed-point")]
+mod fixed;
+#[cfg(feature = "fixed-point")]
+pub use fixed::{Fixed, FixedVec3};
+mod lock_cell;
 
 mod orientation;
 pub use orientation::Orientation;
+mod body_type;
+pub use body_type::BodyType;
 mod entity;
 pub use entity::Entity;
 mod collider;
@@ -24,22 +50,120 @@ pub use sphere_collider::SphereCollider;
 mod plane_collider;
 pub use plane_collider::PlaneCollider;
 mod mesh_collider;
-pub use mesh_collider::MeshCollider;
+pub use mesh_collider::{MeshCollider, MeshAdjacency};
+mod mesh_validation;
+pub use mesh_validation::MeshValidation;
+mod mesh_feature;
+pub use mesh_feature::MeshFeature;
+mod shape_library;
+pub use shape_library::ShapeLibrary;
+mod convex_decomposition;
+pub use convex_decomposition::ConvexDecompositionParams;
+mod tri_mesh_collider;
+pub use tri_mesh_collider::TriMeshCollider;
+mod bvh;
 mod aligned_box_collider;
 pub use aligned_box_collider::AlignedBoxCollider;
 mod collider_wrapper;
 pub use collider_wrapper::ColliderWrapper;
+mod convex_shape;
 mod collision;
+mod batch_collision;
+/// The collision checks that the benchmarks compare (see `benches/`). Only public so they can reach them; not part of the API.
+#[doc(hidden)]
+pub mod bench {
+	pub use crate::collision::{collide_sphere_with_sphere, collide_sphere_with_plane};
+	pub use crate::batch_collision::{LANES, SphereLanes, collide_sphere_with_spheres, sphere_with_spheres_collision, collide_plane_with_spheres, plane_with_spheres_collision};
+}
+mod query;
+pub use query::{RayHit, MeshFaceHit, CameraClip};
+mod sweep;
+pub use sweep::{sweep_and_slide, SweepResult};
+mod toi;
+pub use toi::{toi, Motion, TimeOfImpact};
+mod prediction;
+pub use prediction::{PredictedShape, Prediction};
 
 mod force;
 pub use force::Force;
 
 mod collision_record;
 pub use collision_record::CollisionRecord;
+mod record_limit;
+pub use record_limit::RecordLimit;
+mod sleep_change;
+pub use sleep_change::SleepChange;
+mod break_event;
+pub use break_event::{BreakEvent, Broken};
+mod contact;
+pub use contact::Contact;
+mod contact_pair;
+pub use contact_pair::ContactPair;
+mod contact_graph;
+pub use contact_graph::{ContactGraph, ContactNode, ContactEdge};
+mod contact_properties;
+pub use contact_properties::ContactProperties;
+mod contact_modifier;
+pub use contact_modifier::{ContactModifier, ModifiableContact};
+mod anisotropic_friction;
+pub use anisotropic_friction::AnisotropicFriction;
+mod integrator;
+pub use integrator::Integrator;
+mod friction_model;
+pub use friction_model::FrictionModel;
+mod restitution_model;
+pub use restitution_model::{RestitutionModel, RestitutionCurve};
+mod wake_policy;
+pub use wake_policy::WakePolicy;
+mod snapshot;
+pub use snapshot::{Snapshot, SnapshotReader};
+mod recorder;
+pub use recorder::{Recorder, RecordedLog, RecordedCall, replay};
+#[cfg(feature = "scene")]
+mod scene;
+#[cfg(feature = "scene")]
+pub use scene::{Scene, SceneEntity};
 mod physics_system;
 pub use physics_system::PhysicsSystem;
+mod physics_world;
+pub use physics_world::{PhysicsWorld, ImpulseWorld};
+mod ball_joint;
+pub use ball_joint::BallJoint;
+mod xpbd_world;
+pub use xpbd_world::XpbdWorld;
+mod backend;
+pub use backend::Backend;
+
+mod vehicle;
+pub use vehicle::{Vehicle, Wheel};
+mod pick_constraint;
+pub use pick_constraint::PickConstraint;
+mod path_follower;
+pub use path_follower::{PathFollower, PathKeyframe};
+mod soft_body;
+pub use soft_body::{SoftBody, PointMass, Spring, SpringKind};
+mod cloth;
+pub use cloth::Cloth;
+mod particle_set;
+pub use particle_set::ParticleSet;
+mod fluid_region;
+pub use fluid_region::{FluidRegion, FluidEvent};
+mod profiler;
+pub use profiler::{Profiler, StepPhase, PhaseTimer};
 
+mod entity_view;
+pub use entity_view::EntityView;
+mod world_query;
+pub use world_query::WorldQuery;
 mod unary_force_generator;
 pub use unary_force_generator::UnaryForceGenerator;
 mod gravity_generator;
 pub use gravity_generator::GravityGenerator;
+mod anchored_spring_generator;
+pub use anchored_spring_generator::AnchoredSpringGenerator;
+mod hover_generator;
+pub use hover_generator::HoverGenerator;
+
+pub mod scenes;
+pub mod diagnostics;
+pub mod jitter;
@@ -0,0 +1,161 @@
+use std::cell::RefCell;
+use std::ops::{Deref, DerefMut};
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError, PoisonError};
+
+thread_local! {
+	/// Every [LockCell] the current thread has borrowed (as its address, and whether it's borrowed mutably), one entry per borrow. So a borrow that has to wait can tell whether it's waiting on another thread (which is fine) or on this one (which would never finish).
+	static HELD : RefCell<Vec<(usize, bool)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A thread-safe stand-in for `RefCell`, so [crate::PhysicsSystem] can be `Sync`.
+///
+/// The system needs to hand itself to force generators (as a `&dyn PhysicsWorld`) while it's in the middle of changing things, so it can't just use `&mut` everywhere. This keeps the same `borrow()`/`borrow_mut()` interface as a `RefCell`, but is backed by a lock. Just like a `RefCell`, the borrows must never overlap in a way that conflicts (i.e. calling `borrow_mut()` while something else is borrowed) on the same thread: that panics (with the same messages as a `RefCell`) rather than deadlocking. Conflicts with other threads just wait their turn.
+///
+/// Borrowing without any conflict only costs an (uncontended) atomic operation on the lock, plus noting it down in a thread local list. Nothing else can hold the lock while `step()` runs (it takes `&mut self`), so the hot loop never actually waits on it.
+#[derive(Debug, Default)]
+pub struct LockCell<T> {
+	lock : RwLock<T>,
+}
+
+/// Read-only access to what's in a [LockCell]. Released when dropped.
+pub struct LockRef<'a, T> {
+	guard : RwLockReadGuard<'a, T>,
+	address : usize,
+}
+
+/// Exclusive (read-write) access to what's in a [LockCell]. Released when dropped.
+pub struct LockRefMut<'a, T> {
+	guard : RwLockWriteGuard<'a, T>,
+	address : usize,
+}
+
+/// Notes down that the current thread borrowed the cell at the given address.
+fn note_held(address : usize, mutable : bool) {
+	HELD.with(|held| held.borrow_mut().push((address, mutable)));
+}
+
+/// Removes one note that the current thread borrowed the cell at the given address.
+fn note_released(address : usize) {
+	HELD.with(|held| {
+		let mut held = held.borrow_mut();
+		if let Some(index) = held.iter().rposition(|(other, _)| *other == address) {
+			held.swap_remove(index);
+		}
+	});
+}
+
+impl<T> LockCell<T> {
+	/// Wraps the given value.
+	pub fn new(value : T) -> LockCell<T> {
+		LockCell { lock: RwLock::new(value) }
+	}
+
+	/// The address used to track which cells the current thread has borrowed.
+	fn address(&self) -> usize { self as *const LockCell<T> as usize }
+
+	/// How the current thread has this borrowed: None if it doesn't, otherwise whether any of its borrows are mutable.
+	fn held_here(&self) -> Option<bool> {
+		let address = self.address();
+		HELD.with(|held| held.borrow().iter().filter(|(other, _)| *other == address).fold(None, |found, (_, mutable)| Some(found.unwrap_or(false) || *mutable)))
+	}
+
+	/// Gets read-only access.
+	///
+	/// Panics if the current thread already has it borrowed mutably. Waits if another thread does.
+	pub fn borrow(&self) -> LockRef<'_, T> {
+		let guard = match self.lock.try_read() {
+			Ok(guard) => guard,
+			Err(TryLockError::Poisoned(error)) => error.into_inner(),
+			Err(TryLockError::WouldBlock) => match self.held_here() {
+				Some(true) => panic!("already mutably borrowed: BorrowError"),
+				// The lock won't hand out more read access while another thread waits to write, but that thread is waiting on this one, so this would never finish.
+				Some(false) => panic!("already borrowed (and another thread is waiting to borrow mutably): BorrowError"),
+				None => self.lock.read().unwrap_or_else(PoisonError::into_inner),
+			},
+		};
+		note_held(self.address(), false);
+		LockRef { guard, address: self.address() }
+	}
+
+	/// Gets exclusive (read-write) access.
+	///
+	/// Panics if the current thread already has it borrowed. Waits if another thread does.
+	pub fn borrow_mut(&self) -> LockRefMut<'_, T> {
+		let guard = match self.lock.try_write() {
+			Ok(guard) => guard,
+			Err(TryLockError::Poisoned(error)) => error.into_inner(),
+			Err(TryLockError::WouldBlock) => match self.held_here() {
+				Some(_) => panic!("already borrowed: BorrowMutError"),
+				None => self.lock.write().unwrap_or_else(PoisonError::into_inner),
+			},
+		};
+		note_held(self.address(), true);
+		LockRefMut { guard, address: self.address() }
+	}
+}
+
+impl<T : Clone> Clone for LockCell<T> {
+	fn clone(&self) -> LockCell<T> {
+		LockCell::new(self.borrow().clone())
+	}
+}
+
+impl<'a, T> Deref for LockRef<'a, T> {
+	type Target = T;
+	fn deref(&self) -> &T { &self.guard }
+}
+
+impl<'a, T> Drop for LockRef<'a, T> {
+	fn drop(&mut self) { note_released(self.address); }
+}
+
+impl<'a, T> Deref for LockRefMut<'a, T> {
+	type Target = T;
+	fn deref(&self) -> &T { &self.guard }
+}
+
+impl<'a, T> DerefMut for LockRefMut<'a, T> {
+	fn deref_mut(&mut self) -> &mut T { &mut self.guard }
+}
+
+impl<'a, T> Drop for LockRefMut<'a, T> {
+	fn drop(&mut self) { note_released(self.address); }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::Arc;
+
+	/// Verify conflicting borrows on the same thread panic (like a RefCell) rather than deadlocking.
+	#[test]
+	#[should_panic(expected = "already borrowed")]
+	fn conflict_on_same_thread_panics() {
+		let cell = LockCell::new(1);
+		let _first = cell.borrow();
+		let _second = cell.borrow_mut();
+	}
+
+	/// Verify borrows that don't conflict work, and conflicts with other threads just wait.
+	#[test]
+	fn borrows() {
+		let cell = Arc::new(LockCell::new(1));
+		{
+			let first = cell.borrow();
+			let second = cell.borrow();
+			assert_eq!(*first + *second, 2);
+		}
+		*cell.borrow_mut() += 1;
+		let held = cell.borrow_mut();
+		let other = {
+			let cell = cell.clone();
+			std::thread::spawn(move || { *cell.borrow_mut() += 1; })
+		};
+		std::thread::sleep(std::time::Duration::from_millis(10));
+		drop(held);
+		other.join().unwrap();
+		assert_eq!(*cell.borrow(), 3);
+		// Everything was released again.
+		assert_eq!(cell.held_here(), None);
+	}
+}
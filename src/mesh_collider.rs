@@ -1,25 +1,34 @@
+use std::sync::Arc;
+use std::collections::HashMap;
+
 use crate::consts::EPSILON;
-use crate::types::{Vec3, Mat3, EntityHandle};
+use crate::types::{Vec3, Mat3, EntityHandle, MaterialHandle};
 use crate::collider::{ColliderType, Collider, InternalCollider};
+use crate::anisotropic_friction::AnisotropicFriction;
 use crate::orientation::Orientation;
+use crate::convex_decomposition::{ConvexDecompositionParams, decompose};
+use crate::mesh_validation::{self, MeshValidation};
 
 /// The internal representation of a mesh collider.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct InternalMeshCollider {
 	/// The entity that this is linked to (if any).
 	entity : Option<EntityHandle>,
 
+	/// If this takes part in collisions and queries. Only changed with [crate::PhysicsSystem::set_collider_enabled].
+	enabled : bool,
+
 	/// The position of mesh origin.
 	///
 	/// This is in the parent entity's local space.
 	pub position : Vec3,
 
 	/// The vertices.
-	pub vertices : Vec<Vec3>,
+	pub vertices : Arc<Vec<Vec3>>,
 	/// The faces as indices into the `vertices` property.
-	pub faces : Vec<Vec<usize>>,
+	pub faces : Arc<Vec<Vec<usize>>>,
 	/// The lines segments as indices into the `vertices` property.
-	pub edges : Vec<(usize, usize)>,
+	pub edges : Arc<Vec<(usize, usize)>>,
 
 	/// The restituion coefficient.
 	pub restitution_coefficient : f32,
@@ -32,6 +41,67 @@ pub struct InternalMeshCollider {
 
 	/// The dynamic friction coefficient. Should always at or between 0.0 and 1.0.
 	pub dynamic_friction_coefficient : f32,
+
+	/// How friction changes with the sliding direction (if at all).
+	pub anisotropic_friction : Option<AnisotropicFriction>,
+
+	/// Extra thickness around the surface, used for collision detection.
+	pub margin : f32,
+
+	/// What this is made of.
+	pub material : MaterialHandle,
+
+	/// If this is only seen by queries (i.e. ray casts), never colliding with anything nor adding any mass.
+	pub query_only : bool,
+
+	/// A tag copied into collision records.
+	pub surface_tag : u32,
+
+	/// Whether hits on edges and corners between faces are corrected to match the faces around them.
+	pub smooth_normals : bool,
+
+	/// Whether hits from behind faces (based on their winding) are ignored.
+	pub one_sided : bool,
+
+	/// Which faces are around each edge and vertex.
+	pub adjacency : Arc<MeshAdjacency>,
+}
+
+/// Which faces are next to each other in a mesh. Built from (and indexed the same as) a mesh's vertices, edges, and faces.
+#[derive(Debug, Clone, Default)]
+pub struct MeshAdjacency {
+	/// The faces (as indices into the mesh's faces) that use each edge.
+	pub edge_faces : Vec<Vec<usize>>,
+	/// The faces (as indices into the mesh's faces) that use each vertex.
+	pub vertex_faces : Vec<Vec<usize>>,
+	/// Whether each vertex is on an edge that doesn't have a face on both sides (i.e. on the rim of an open mesh).
+	pub vertex_on_boundary : Vec<bool>,
+}
+
+impl MeshAdjacency {
+	/// Works out which faces are next to each other.
+	pub fn new(vertex_count : usize, edges : &[(usize, usize)], faces : &[Vec<usize>]) -> MeshAdjacency {
+		let edge_indices : HashMap<(usize, usize), usize> = edges.iter().enumerate().map(|(index, edge)| (*edge, index)).collect();
+		let mut edge_faces = vec![Vec::new(); edges.len()];
+		let mut vertex_faces = vec![Vec::new(); vertex_count];
+		for (face_index, face) in faces.iter().enumerate() {
+			for index in 0..face.len() {
+				let (first, second) = (face[index], face[(index + 1) % face.len()]);
+				if let Some(edge_index) = edge_indices.get(&if first < second { (first, second) } else { (second, first) }) {
+					edge_faces[*edge_index].push(face_index);
+				}
+				vertex_faces[first].push(face_index);
+			}
+		}
+		let mut vertex_on_boundary = vec![false; vertex_count];
+		for ((first, second), faces) in edges.iter().zip(edge_faces.iter()) {
+			if 2 != faces.len() {
+				vertex_on_boundary[*first] = true;
+				vertex_on_boundary[*second] = true;
+			}
+		}
+		MeshAdjacency { edge_faces, vertex_faces, vertex_on_boundary }
+	}
 }
 
 impl InternalMeshCollider {
@@ -42,6 +112,7 @@ impl InternalMeshCollider {
 		} else {
 			Ok(Box::new(InternalMeshCollider {
 				entity: None,
+				enabled: true,
 				position: source.position.clone(),
 				vertices: source.vertices.clone(),
 				faces: source.faces.clone(),
@@ -50,6 +121,14 @@ impl InternalMeshCollider {
 				friction_threshold: source.friction_threshold,
 				static_friction_coefficient: source.static_friction_coefficient,
 				dynamic_friction_coefficient: source.dynamic_friction_coefficient,
+				anisotropic_friction: source.anisotropic_friction,
+				margin: source.margin,
+				material: source.material,
+				query_only: source.query_only,
+				surface_tag: source.surface_tag,
+				smooth_normals: source.smooth_normals,
+				one_sided: source.one_sided,
+				adjacency: Arc::new(MeshAdjacency::new(source.vertices.len(), &source.edges, &source.faces)),
 			}))
 		}
 	}
@@ -66,6 +145,13 @@ impl InternalMeshCollider {
 			friction_threshold: self.friction_threshold,
 			static_friction_coefficient: self.static_friction_coefficient,
 			dynamic_friction_coefficient: self.dynamic_friction_coefficient,
+			anisotropic_friction: self.anisotropic_friction,
+			margin: self.margin,
+			material: self.material,
+			query_only: self.query_only,
+			surface_tag: self.surface_tag,
+			smooth_normals: self.smooth_normals,
+			one_sided: self.one_sided,
 		}
 	}
 
@@ -75,6 +161,10 @@ impl InternalMeshCollider {
 			Err(()) // TODO: An error type.
 		} else {
 			self.position = source.position;
+			// Only work out the adjacency again if the geometry actually changed.
+			if !(Arc::ptr_eq(&self.vertices, &source.vertices) && Arc::ptr_eq(&self.faces, &source.faces) && Arc::ptr_eq(&self.edges, &source.edges)) {
+				self.adjacency = Arc::new(MeshAdjacency::new(source.vertices.len(), &source.edges, &source.faces));
+			}
 			self.vertices = source.vertices.clone();
 			self.faces = source.faces.clone();
 			self.edges = source.edges.clone();
@@ -82,6 +172,13 @@ impl InternalMeshCollider {
 			self.friction_threshold = source.friction_threshold;
 			self.static_friction_coefficient = source.static_friction_coefficient;
 			self.dynamic_friction_coefficient = source.dynamic_friction_coefficient;
+			self.anisotropic_friction = source.anisotropic_friction;
+			self.margin = source.margin;
+			self.material = source.material;
+			self.query_only = source.query_only;
+			self.surface_tag = source.surface_tag;
+			self.smooth_normals = source.smooth_normals;
+			self.one_sided = source.one_sided;
 			Ok(())
 		}
 	}
@@ -89,10 +186,16 @@ impl InternalMeshCollider {
 	/// Returns all the verticies after being moved into world space. The passed in orientation should be from the owning Entity.
 	pub fn vertices_in_world(&self, orientation : &Orientation) -> Vec<Vec3> {
 		let mut transformed = Vec::with_capacity(self.vertices.len());
-		for vertex in &self.vertices {
+		self.vertices_in_world_into(orientation, &mut transformed);
+		transformed
+	}
+
+	/// The same as [InternalMeshCollider::vertices_in_world], but writes into (and replaces everything in) the passed in list.
+	pub fn vertices_in_world_into(&self, orientation : &Orientation, transformed : &mut Vec<Vec3>) {
+		transformed.clear();
+		for vertex in self.vertices.iter() {
 			transformed.push(orientation.position_into_world(&(self.position + vertex)));
 		}
-		transformed
 	}
 }
 
@@ -100,6 +203,9 @@ impl InternalCollider for InternalMeshCollider {
 	/// The specific type.
 	fn get_type(&self) -> ColliderType { ColliderType::MESH }
 
+	/// Makes a boxed copy.
+	fn clone_boxed(&self) -> Box<dyn InternalCollider> { Box::new(self.clone()) }
+
 	/// Sets the entity this is attached to, returning the previous one.
 	fn set_entity(&mut self, handle : Option<EntityHandle>) -> Option<EntityHandle> {
 		let old = self.entity;
@@ -110,11 +216,19 @@ impl InternalCollider for InternalMeshCollider {
 	/// Retrieves the stored entity handle that this is attached to.
 	fn get_entity(&mut self) -> Option<EntityHandle> { self.entity }
 
+	fn set_enabled(&mut self, enabled : bool) { self.enabled = enabled; }
+
+	fn is_enabled(&self) -> bool { self.enabled }
+
 	/// Gets the center of mass for this collider.
 	/// This is relative to this collider's owning/linked/attached entity.
 	/// This IS NOT relative to this collider's "position" property.
 	fn get_local_center_of_mass(&self) -> Vec3 { self.position }
 
+	fn get_local_position(&self) -> Vec3 { self.position }
+
+	fn set_local_position(&mut self, position : &Vec3) { self.position = *position; }
+
 	fn get_mass(&self) -> f32 { 0.0 }
 
 	fn get_moment_of_inertia_tensor(&self) -> Mat3 { Mat3::zeros() }
@@ -126,10 +240,22 @@ impl InternalCollider for InternalMeshCollider {
 	fn get_static_friction_coefficient(&self) -> f32 { self.static_friction_coefficient }
 
 	fn get_dynamic_friction_coefficient(&self) -> f32 { self.dynamic_friction_coefficient }
+
+	fn get_anisotropic_friction(&self) -> Option<AnisotropicFriction> { self.anisotropic_friction }
+
+	fn get_margin(&self) -> f32 { self.margin }
+	fn get_material(&self) -> MaterialHandle { self.material }
+
+	fn is_query_only(&self) -> bool { self.query_only }
+
+	fn get_surface_tag(&self) -> u32 { self.surface_tag }
 }
 
 /// A copy of all of the publicly-accessible properties of a mesh collider.
-#[derive(Debug)]
+///
+/// The geometry is reference counted, so copying this around (or adding it to a [crate::PhysicsSystem]) doesn't copy the geometry. It's only copied once something changes it. See [crate::ShapeLibrary] for keeping geometry around to share.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MeshCollider {
 	/// The entity, if there is one. This is NOT copied back into InternalSphereCollider, hence why it's not "pub".
 	///
@@ -146,17 +272,17 @@ pub struct MeshCollider {
 	/// Should never contain any duplicates.
 	///
 	/// Defaults to empty.
-	vertices : Vec<Vec3>,
+	vertices : Arc<Vec<Vec3>>,
 	/// The faces as indices into the `vertices` property. May contain duplicates.
 	///
 	/// Defaults to empty.
-	faces : Vec<Vec<usize>>,
+	faces : Arc<Vec<Vec<usize>>>,
 	/// The lines segments as indices into the `vertices` property.
 	///
 	/// Should never contain any duplicates. Lower indicies are first in the tuples.
 	///
 	/// Defaults to empty.
-	edges : Vec<(usize, usize)>,
+	edges : Arc<Vec<(usize, usize)>>,
 
 	/// The restituion coefficient.
 	///
@@ -177,6 +303,47 @@ pub struct MeshCollider {
 	///
 	/// Defaults to `0.3`.
 	pub dynamic_friction_coefficient : f32,
+
+	/// How friction changes with the sliding direction. If this is None, then friction is the same in all directions.
+	///
+	/// Defaults to None.
+	pub anisotropic_friction : Option<AnisotropicFriction>,
+
+	/// Extra thickness around the surface, used for collision detection. Two colliders count as touching once their surfaces are as close as their margins added together (so things resting on each other stay that far apart). Must not be negative.
+	///
+	/// This is ignored when hitting another mesh.
+	///
+	/// Defaults to `0.0`.
+	pub margin : f32,
+
+	/// What this is made of. Only used to look up any properties set for specific pairs of materials (see [crate::PhysicsSystem::set_material_pair_properties]).
+	///
+	/// Defaults to `0`.
+	pub material : MaterialHandle,
+
+	/// If true, this never collides with anything and adds no mass to its entity, but still moves with it and is hit by queries like [crate::PhysicsSystem::cast_ray]. i.e. for a large detection sphere around an enemy.
+	///
+	/// Defaults to `false`.
+	pub query_only : bool,
+
+	/// A tag that's copied into every [crate::CollisionRecord] this is a part of, but otherwise ignored. i.e. for picking which sound to play.
+	///
+	/// Defaults to `0`.
+	pub surface_tag : u32,
+
+	/// Whether a sphere hitting an edge or corner between faces has the hit's normal corrected to match the faces around it. i.e. so a ball rolling across a floor made of many flat faces doesn't bounce off of the edges between them.
+	///
+	/// Edges between faces on the same plane are treated as part of the faces, and other edges can only push along a normal between their two faces' normals. Edges on the rim of an open mesh are left alone.
+	///
+	/// Defaults to `true`.
+	pub smooth_normals : bool,
+
+	/// Whether faces can only be hit from the front (the side their corners wind counter-clockwise around). i.e. so something inside a hollow level mesh that clips a little way through a wall is pushed back inside, rather than out to the other side of it.
+	///
+	/// Edges and corners are only skipped when the thing hitting them starts behind every face around them. This only affects hits with spheres and other meshes (anything else treats the mesh as convex). See [MeshCollider::orient_faces] for fixing up the winding.
+	///
+	/// Defaults to `false`.
+	pub one_sided : bool,
 }
 
 impl MeshCollider {
@@ -187,21 +354,137 @@ impl MeshCollider {
 		MeshCollider {
 			entity: None,
 			position: Vec3::zeros(),
-			vertices: Vec::new(),
-			faces: Vec::new(),
-			edges: Vec::new(),
+			vertices: Arc::new(Vec::new()),
+			faces: Arc::new(Vec::new()),
+			edges: Arc::new(Vec::new()),
 			restitution_coefficient: 1.0,
 			friction_threshold: 0.25,
 			static_friction_coefficient: 1.0,
 			dynamic_friction_coefficient: 0.3,
+			anisotropic_friction: None,
+			margin: 0.0,
+			material: 0,
+			query_only: false,
+			surface_tag: 0,
+			smooth_normals: true,
+			one_sided: false,
+		}
+	}
+
+	/// Creates a box centered on the origin, reaching out `half_extents` along each axis. Everything besides the geometry is at default.
+	pub fn new_cuboid(half_extents : impl Into<Vec3>) -> Result<MeshCollider, ()> {
+		let half_extents = half_extents.into();
+		if !half_extents.iter().all(|value| value.is_finite() && EPSILON < *value) {
+			return Err(()); // TODO: An error type.
+		}
+		let corner = |x : f32, y : f32, z : f32| Vec3::new(x * half_extents.x, y * half_extents.y, z * half_extents.z);
+		let mut collider = MeshCollider::new();
+		for axis in 0..3 {
+			for side in [-1.0, 1.0].iter() {
+				// Walk around the face's plane so the corners wind counter-clockwise when seen from outside.
+				let mut corners : Vec<Vec3> = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)].iter().map(|(first, second)| {
+					let mut values = [0.0; 3];
+					values[axis] = *side;
+					values[(axis + 1) % 3] = *first;
+					values[(axis + 2) % 3] = *second;
+					corner(values[0], values[1], values[2])
+				}).collect();
+				if *side < 0.0 { corners.reverse(); }
+				collider.insert_face(&corners);
+			}
+		}
+		Ok(collider)
+	}
+
+	/// Creates a cylinder centered on the origin, with its ends facing along the y axis. Its round side is approximated with `segments` flat faces. Everything besides the geometry is at default.
+	pub fn new_cylinder(radius : f32, height : f32, segments : usize) -> Result<MeshCollider, ()> {
+		if segments < 3 || !radius.is_finite() || radius < EPSILON || !height.is_finite() || height < EPSILON {
+			return Err(()); // TODO: An error type.
+		}
+		let ring = ring_points(radius, segments);
+		let (bottom, top) = (Vec3::new(0.0, -0.5 * height, 0.0), Vec3::new(0.0, 0.5 * height, 0.0));
+		let mut collider = MeshCollider::new();
+		collider.insert_face(&ring.iter().map(|point| point + top).collect());
+		collider.insert_face(&ring.iter().rev().map(|point| point + bottom).collect());
+		for index in 0..segments {
+			let (first, second) = (ring[index], ring[(index + 1) % segments]);
+			collider.insert_face(&vec![first + bottom, second + bottom, second + top, first + top]);
+		}
+		Ok(collider)
+	}
+
+	/// Creates a capsule centered on the origin: a cylinder `length` long along the y axis, with a half sphere capping each end. The round parts are approximated with `segments` faces around the y axis (and a proportional number of rings on each half sphere). Everything besides the geometry is at default.
+	pub fn new_capsule(radius : f32, length : f32, segments : usize) -> Result<MeshCollider, ()> {
+		if segments < 3 || !radius.is_finite() || radius < EPSILON || !length.is_finite() || length < 0.0 {
+			return Err(()); // TODO: An error type.
+		}
+		// Go from the bottom pole to the top one, building up the rings of points along the way. The middle two rings are at the same latitude, just moved apart by the length.
+		let rings_per_end = (segments + 3) / 4;
+		let mut rings = Vec::with_capacity(2 * rings_per_end + 2);
+		for (sign, range) in [(-1.0f32, (0..rings_per_end).rev().collect::<Vec<usize>>()), (1.0, (0..rings_per_end).collect())].iter() {
+			for ring in range {
+				let angle = (*ring as f32) / (rings_per_end as f32) * std::f32::consts::FRAC_PI_2;
+				let offset = Vec3::new(0.0, sign * (0.5 * length + radius * angle.sin()), 0.0);
+				rings.push(ring_points(radius * angle.cos(), segments).into_iter().map(|point| point + offset).collect::<Vec<Vec3>>());
+			}
+		}
+		let (bottom, top) = (Vec3::new(0.0, -0.5 * length - radius, 0.0), Vec3::new(0.0, 0.5 * length + radius, 0.0));
+		let mut collider = MeshCollider::new();
+		for index in 0..segments {
+			let next = (index + 1) % segments;
+			collider.insert_face(&vec![bottom, rings[0][next], rings[0][index]]);
+			for ring in 1..rings.len() {
+				let (below, above) = (&rings[ring - 1], &rings[ring]);
+				if (below[index] - above[index]).magnitude() < EPSILON { continue; } // The middle rings are the same if there's no length.
+				collider.insert_face(&vec![below[index], below[next], above[next], above[index]]);
+			}
+			collider.insert_face(&vec![top, rings[rings.len() - 1][index], rings[rings.len() - 1][next]]);
+		}
+		Ok(collider)
+	}
+
+	/// Creates a sphere centered on the origin, approximated by an icosahedron whose triangles are each split into four `subdivisions` times. Everything besides the geometry is at default.
+	///
+	/// Each subdivision quadruples the number of faces (starting from 20), so this gets expensive quickly.
+	pub fn new_icosphere(radius : f32, subdivisions : usize) -> Result<MeshCollider, ()> {
+		if !radius.is_finite() || radius < EPSILON {
+			return Err(()); // TODO: An error type.
+		}
+		let golden = (1.0 + 5.0f32.sqrt()) / 2.0;
+		let points : Vec<Vec3> = [
+			(-1.0, golden, 0.0), (1.0, golden, 0.0), (-1.0, -golden, 0.0), (1.0, -golden, 0.0),
+			(0.0, -1.0, golden), (0.0, 1.0, golden), (0.0, -1.0, -golden), (0.0, 1.0, -golden),
+			(golden, 0.0, -1.0), (golden, 0.0, 1.0), (-golden, 0.0, -1.0), (-golden, 0.0, 1.0),
+		].iter().map(|(x, y, z)| Vec3::new(*x, *y, *z).normalize()).collect();
+		let mut triangles : Vec<[Vec3; 3]> = [
+			[0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+			[1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+			[3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+			[4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+		].iter().map(|[first, second, third]| [points[*first], points[*second], points[*third]]).collect();
+		for _ in 0..subdivisions {
+			triangles = triangles.iter().flat_map(|[first, second, third]| {
+				let (first_second, second_third, third_first) = ((first + second).normalize(), (second + third).normalize(), (third + first).normalize());
+				vec![
+					[*first, first_second, third_first],
+					[first_second, *second, second_third],
+					[third_first, second_third, *third],
+					[first_second, second_third, third_first],
+				]
+			}).collect();
+		}
+		let mut collider = MeshCollider::new();
+		for triangle in triangles {
+			collider.insert_face(&triangle.iter().map(|point| point * radius).collect());
 		}
+		Ok(collider)
 	}
 
 	/// Adds a face to the mesh.
 	///
 	/// The points must be coplanar, and should represent a convex polygon on that plane.
 	///
-	/// It's more efficient to try and merge faces that are coplanar (into convex polygons) rather than specifying the triangulated faces into this method separately.
+	/// Faces that duplicate an existing one (i.e. use the same corners) are ignored. And if the face is on the same plane as an existing face that it shares an edge with, the two are merged into one (as long as the result is still convex). So triangulated input ends up with far fewer faces.
 	pub fn add_face(&mut self, points : &Vec<Vec3>) {
 		assert!(3 <= points.len(), "Not enough points to form a face.");
 		// Make sure the points are coplanar and form a convex polygon.
@@ -213,10 +496,26 @@ impl MeshCollider {
 			let current_normal = (second - first).cross(&(third - first)).normalize();
 			assert!((current_normal.dot(&normal) - 1.0).abs() < EPSILON, "Points not coplanar or not convex.");
 		}
-		// TODO: Could try deduplicating faces too...
-		// TODO: Could also try merging faces if on same plane and sharing edges... Though that's fairly non-trivial.
-		// Then start adding things in.
-		let point_indices = self.add_points(points);
+		self.insert_face(points);
+	}
+
+	/// Does the work for [MeshCollider::add_face], without checking the points first (so they can have corners in a straight line).
+	fn insert_face(&mut self, points : &Vec<Vec3>) {
+		let normal = face_normal(points);
+		let mut point_indices = self.add_points(points);
+		// Ignore duplicates.
+		if self.faces.iter().any(|face| face.len() == point_indices.len() && face.iter().all(|index| point_indices.contains(index))) {
+			return
+		}
+		// Merge with any neighboring faces on the same plane. The merged face might then be able to merge with another, so keep going until nothing changes.
+		let mut removed_edges = Vec::new();
+		while let Some((face_index, merged, shared_edge)) = self.faces.iter().enumerate().find_map(|(face_index, face)| {
+			self.try_merging_faces(face, &point_indices, &normal).map(|(merged, shared_edge)| (face_index, merged, shared_edge))
+		}) {
+			Arc::make_mut(&mut self.faces).remove(face_index);
+			point_indices = merged;
+			removed_edges.push(shared_edge);
+		}
 		for index in 0..point_indices.len() {
 			self.add_edge(
 				point_indices[index],
@@ -224,7 +523,50 @@ impl MeshCollider {
 			);
 		}
 		// Finally add the face.
-		self.faces.push(point_indices);
+		Arc::make_mut(&mut self.faces).push(point_indices);
+		// Drop the edges that were merged away (unless some other face still uses them).
+		for (index1, index2) in removed_edges {
+			let still_used = self.faces.iter().any(|face| (0..face.len()).any(|index| {
+				let (first, second) = (face[index], face[(index + 1) % face.len()]);
+				(first == index1 && second == index2) || (first == index2 && second == index1)
+			}));
+			if !still_used {
+				Arc::make_mut(&mut self.edges).retain(|edge| *edge != (index1, index2));
+			}
+		}
+	}
+
+	/// Tries to merge two faces (as indices into `vertices`), where the second has the given normal. Returns the merged face and the (sorted) edge that was removed, or None if they can't be merged.
+	///
+	/// They can only be merged if they're on the same plane (facing the same way), share exactly one edge, and the result is still convex.
+	fn try_merging_faces(&self, existing : &Vec<usize>, added : &Vec<usize>, normal : &Vec3) -> Option<(Vec<usize>, (usize, usize))> {
+		// Must be on the same plane, facing the same way.
+		let existing_normal = face_normal(&existing.iter().map(|index| self.vertices[*index]).collect());
+		if !((existing_normal.dot(normal) - 1.0).abs() < EPSILON) { return None; } // Written this way so NaN normals never match.
+		if !added.iter().all(|index| (self.vertices[*index] - self.vertices[existing[0]]).dot(normal).abs() < EPSILON) { return None; }
+		// Must share exactly one edge (and no other corners). Since they face the same way, the shared edge goes the opposite way around each face.
+		let shared : Vec<usize> = existing.iter().copied().filter(|index| added.contains(index)).collect();
+		if 2 != shared.len() { return None; }
+		let existing_start = (0..existing.len()).find(|index| shared.contains(&existing[*index]) && shared.contains(&existing[(index + 1) % existing.len()]))?;
+		let added_start = added.iter().position(|index| *index == existing[(existing_start + 1) % existing.len()])?;
+		if added[(added_start + 1) % added.len()] != existing[existing_start] { return None; }
+		// Go around the existing face (starting after the shared edge), then around the added face (skipping the shared corners).
+		let mut merged = Vec::with_capacity(existing.len() + added.len() - 2);
+		for offset in 1..=existing.len() {
+			merged.push(existing[(existing_start + offset) % existing.len()]);
+		}
+		for offset in 2..added.len() {
+			merged.push(added[(added_start + offset) % added.len()]);
+		}
+		// Then make sure it's still convex (allowing corners that are in a straight line).
+		for index in 0..merged.len() {
+			let first  = self.vertices[merged[index]];
+			let second = self.vertices[merged[(index + 1) % merged.len()]];
+			let third  = self.vertices[merged[(index + 2) % merged.len()]];
+			if (second - first).cross(&(third - second)).dot(normal) < -EPSILON { return None; }
+		}
+		let edge = (existing[existing_start], existing[(existing_start + 1) % existing.len()]);
+		Some((merged, if edge.0 < edge.1 { edge } else { (edge.1, edge.0) }))
 	}
 
 	/// Stores a single edge using the points at the given indices in the internal `points` vector.
@@ -237,13 +579,13 @@ impl MeshCollider {
 			index1 = index2;
 			index2 = temp;
 		}
-		for (existing1, existing2) in &self.edges {
+		for (existing1, existing2) in self.edges.iter() {
 			if index1 == *existing1 && index2 == *existing2 {
 				return // Give up immediately if see the edge already exists.
 			}
 		}
 		// If didn't find a duplicate, then add the edge.
-		self.edges.push((index1, index2));
+		Arc::make_mut(&mut self.edges).push((index1, index2));
 	}
 
 	/// Stores the given list of points into the internal `points` vector and returns a vec of the indices.
@@ -262,7 +604,7 @@ impl MeshCollider {
 			}
 			if !found {
 				indices.push(self.vertices.len());
-				self.vertices.push(point.clone());
+				Arc::make_mut(&mut self.vertices).push(point.clone());
 			}
 		}
 		indices
@@ -275,15 +617,155 @@ impl MeshCollider {
 	/// The number of (unique) vertices currently stored in this instance.
 	pub fn vertex_count(&self) -> usize { self.vertices.len() }
 
-	// TODO? Some functions to grab triangles/edges/vertices?
-	// TODO? A function to clear the current geometry?
+	/// The (unique) vertices currently stored in this instance.
+	pub fn get_vertices(&self) -> &Vec<Vec3> { &self.vertices }
+	/// The faces currently stored in this instance (in the order they were added), as indices into [MeshCollider::get_vertices].
+	pub fn get_faces(&self) -> &Vec<Vec<usize>> { &self.faces }
+
+	/// Replaces this instance's geometry with the geometry of `source`, without copying it (everything else is left as-is).
+	pub fn share_geometry_from(&mut self, source : &MeshCollider) {
+		self.vertices = source.vertices.clone();
+		self.faces = source.faces.clone();
+		self.edges = source.edges.clone();
+	}
+
+	/// Whether this instance and `other` are using the very same geometry in memory (rather than just equal copies of it).
+	pub fn shares_geometry_with(&self, other : &MeshCollider) -> bool {
+		Arc::ptr_eq(&self.vertices, &other.vertices) && Arc::ptr_eq(&self.faces, &other.faces) && Arc::ptr_eq(&self.edges, &other.edges)
+	}
+
+	/// Iterates over the (unique) vertices currently stored in this instance.
+	pub fn vertices(&self) -> impl Iterator<Item = Vec3> + '_ {
+		self.vertices.iter().copied()
+	}
+
+	/// Iterates over the (unique) edges currently stored in this instance, as the positions of their two ends.
+	pub fn edges(&self) -> impl Iterator<Item = (Vec3, Vec3)> + '_ {
+		self.edges.iter().map(move |(index1, index2)| (self.vertices[*index1], self.vertices[*index2]))
+	}
+
+	/// Iterates over the faces currently stored in this instance (in the order they were added), as the positions of their corners.
+	pub fn faces(&self) -> impl Iterator<Item = Vec<Vec3>> + '_ {
+		(0..self.faces.len()).map(move |index| self.get_face(index).unwrap())
+	}
+
+	/// Gets the corners of a single face (in the same order they were added). Returns None if there's no face at that index.
+	pub fn get_face(&self, index : usize) -> Option<Vec<Vec3>> {
+		self.faces.get(index).map(|face| face.iter().map(|corner| self.vertices[*corner]).collect())
+	}
+
+	/// Removes all of the geometry (leaving everything else as-is).
+	pub fn clear(&mut self) {
+		self.vertices = Arc::new(Vec::new());
+		self.faces = Arc::new(Vec::new());
+		self.edges = Arc::new(Vec::new());
+	}
+
+	/// Removes a single face, along with any edges and vertices that only it used. Returns the face's corners, or None if there's no face at that index.
+	///
+	/// Any faces after it move down one index.
+	pub fn remove_face(&mut self, index : usize) -> Option<Vec<Vec3>> {
+		let removed = self.get_face(index)?;
+		let faces = Arc::make_mut(&mut self.faces);
+		faces.remove(index);
+		// Drop any vertices nothing uses anymore, and shift the indices of the rest down to match.
+		let vertices = Arc::make_mut(&mut self.vertices);
+		let mut new_indices = vec![None; vertices.len()];
+		let mut kept = 0;
+		for (old_index, new_index) in new_indices.iter_mut().enumerate() {
+			if faces.iter().any(|face| face.contains(&old_index)) {
+				vertices[kept] = vertices[old_index];
+				*new_index = Some(kept);
+				kept += 1;
+			}
+		}
+		vertices.truncate(kept);
+		for face in faces.iter_mut() {
+			for corner in face.iter_mut() {
+				*corner = new_indices[*corner].unwrap();
+			}
+		}
+		// Then rebuild the edges from what's left.
+		self.edges = Arc::new(Vec::new());
+		for face_index in 0..self.faces.len() {
+			for index in 0..self.faces[face_index].len() {
+				let face = &self.faces[face_index];
+				let (first, second) = (face[index], face[(index + 1) % face.len()]);
+				self.add_edge(first, second);
+			}
+		}
+		Some(removed)
+	}
+
+	/// Splits this mesh into (approximately) convex pieces, which together cover the same shape. Returns an empty list if `params` isn't valid.
+	///
+	/// The pieces are found by grouping up neighboring faces for as long as the group's convex hull doesn't stick out of this mesh too much. Each group is then replaced by that hull. Every piece copies everything else (position, friction, etc) from this instance.
+	///
+	/// The pieces are plain [MeshCollider]s, as there's no separate convex hull collider (the convex shape queries, like [crate::toi], already treat any mesh as its hull). But each piece is guaranteed to be convex, as it's built straight from a convex hull. So they can be linked to entities as-is.
+	///
+	/// This is fairly slow, so is meant to be done once when loading a model (not every frame).
+	pub fn decompose_convex(&self, params : &ConvexDecompositionParams) -> Vec<MeshCollider> {
+		if !params.is_valid() { return Vec::new(); }
+		decompose(&self.vertices, &self.faces, params).into_iter().map(|triangles| {
+			let mut piece = MeshCollider { entity: None, vertices: Arc::new(Vec::new()), faces: Arc::new(Vec::new()), edges: Arc::new(Vec::new()), ..*self };
+			for triangle in triangles {
+				// Skip any slivers that don't cover anything.
+				let corners = triangle.to_vec();
+				if face_normal(&corners).magnitude().is_nan() { continue; }
+				piece.insert_face(&corners);
+			}
+			piece
+		}).collect()
+	}
+
+	/// Checks this mesh's geometry for anything that would trip up collisions: holes, edges shared by more than two faces, faces wound the wrong way, duplicated faces, and faces that don't cover any area.
+	///
+	/// Unlike [MeshCollider::is_valid], this looks at every face, so it's meant to be done once when loading a model (not every frame).
+	pub fn validate(&self) -> MeshValidation {
+		mesh_validation::validate(&self.vertices, &self.faces)
+	}
+
+	/// Flips faces (i.e. reverses the order of their corners) so that every face winds the same way as its neighbors, and every closed piece of the mesh has its faces pointing outward. Returns how many faces were flipped.
+	///
+	/// Fails (leaving everything as-is) if any edge is shared by more than two faces.
+	pub fn orient_faces(&mut self) -> Result<usize, ()> {
+		let mut faces = (*self.faces).clone();
+		let flipped = mesh_validation::orient_faces(&self.vertices, &mut faces)?;
+		if 0 < flipped {
+			self.faces = Arc::new(faces);
+		}
+		Ok(flipped)
+	}
+
+	/// Works out which faces are next to each other (around each edge and vertex).
+	pub fn adjacency(&self) -> MeshAdjacency {
+		MeshAdjacency::new(self.vertices.len(), &self.edges, &self.faces)
+	}
 
 	/// If this is in a valid state.
 	pub fn is_valid(&self) -> bool {
-		3 <= self.vertices.len() && 1 <= self.faces.len() && 1 <= self.edges.len()
+		3 <= self.vertices.len() && 1 <= self.faces.len() && 1 <= self.edges.len() && self.anisotropic_friction.iter().all(|friction| friction.is_valid()) && 0.0 <= self.margin
 	}
 }
 
+/// The normal of a face (following the right-hand rule). Sums up the normal at every corner, so it still works if some of the corners are in a straight line. Will be NaN if the face doesn't cover any area.
+pub fn face_normal(corners : &Vec<Vec3>) -> Vec3 {
+	let mut normal = Vec3::zeros();
+	for index in 0..corners.len() {
+		let first = corners[index];
+		normal += (corners[(index + 1) % corners.len()] - first).cross(&(corners[(index + 2) % corners.len()] - first));
+	}
+	if EPSILON < normal.magnitude() { normal.normalize() } else { Vec3::repeat(f32::NAN) }
+}
+
+/// Evenly spaced points around a circle on the xz plane (centered on the origin). They go counter-clockwise when seen from above (i.e. from +y).
+fn ring_points(radius : f32, segments : usize) -> Vec<Vec3> {
+	(0..segments).map(|index| {
+		let angle = (index as f32) / (segments as f32) * 2.0 * std::f32::consts::PI;
+		Vec3::new(radius * angle.cos(), 0.0, -radius * angle.sin())
+	}).collect()
+}
+
 impl Collider for MeshCollider {
 	fn get_type(&self) -> ColliderType { ColliderType::MESH }
 
@@ -296,6 +778,7 @@ impl Collider for MeshCollider {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::convex_decomposition::ConvexDecompositionParams;
 
 	/// Verify can create and add faces to a mesh collider.
 	#[test]
@@ -338,15 +821,299 @@ mod tests {
 		assert_eq!(collider.edge_count(), 8);
 		assert_eq!(collider.vertex_count(), 6);
 
-		// Add a triangle that shares two edges.
+		// Add a triangle that shares two edges. It's on the same plane (and facing the same way) as the second triangle, so it gets merged into it.
 		collider.add_face(&vec![
 			Vec3::new( 0.0, 1.0, 0.0),
 			Vec3::new( 1.0,-1.0, 0.0),
 			Vec3::new( 2.0, 1.0, 0.0),
 		]);
 		assert_eq!(collider.is_valid(), true);
-		assert_eq!(collider.face_count(), 4);
-		assert_eq!(collider.edge_count(), 9);
+		assert_eq!(collider.face_count(), 3);
+		assert_eq!(collider.edge_count(), 8);
+		assert_eq!(collider.vertex_count(), 6);
+		assert_eq!(collider.get_face(2).unwrap().len(), 4);
+
+		// Adding the same face again does nothing.
+		collider.add_face(&vec![
+			Vec3::new( 1.0,-1.0, 0.0),
+			Vec3::new(-1.0,-1.0, 0.0),
+			Vec3::new( 0.0, 1.0, 0.0),
+		]);
+		assert_eq!(collider.face_count(), 3);
+		assert_eq!(collider.edge_count(), 8);
+		assert_eq!(collider.vertex_count(), 6);
+	}
+
+	/// Verify a triangulated square becomes a single face, but a non-convex result doesn't.
+	#[test]
+	fn check_merge_faces() {
+		let mut collider = MeshCollider::new();
+		collider.add_face(&vec![
+			Vec3::new(0.0, 0.0, 0.0),
+			Vec3::new(1.0, 0.0, 0.0),
+			Vec3::new(1.0, 1.0, 0.0),
+		]);
+		collider.add_face(&vec![
+			Vec3::new(0.0, 0.0, 0.0),
+			Vec3::new(1.0, 1.0, 0.0),
+			Vec3::new(0.0, 1.0, 0.0),
+		]);
+		assert_eq!(collider.face_count(), 1);
+		assert_eq!(collider.edge_count(), 4);
+		assert_eq!(collider.vertex_count(), 4);
+		assert_eq!(collider.get_face(0).unwrap().len(), 4);
+
+		// A second square next to it merges into a (still convex) rectangle, keeping the corners where they meet.
+		collider.add_face(&vec![
+			Vec3::new(1.0, 0.0, 0.0),
+			Vec3::new(2.0, 0.0, 0.0),
+			Vec3::new(2.0, 1.0, 0.0),
+			Vec3::new(1.0, 1.0, 0.0),
+		]);
+		assert_eq!(collider.face_count(), 1);
+		assert_eq!(collider.edge_count(), 6);
+		assert_eq!(collider.vertex_count(), 6);
+
+		// Then a triangle that would make it an L-shape (so non-convex) is left on its own.
+		collider.add_face(&vec![
+			Vec3::new(0.0, 1.0, 0.0),
+			Vec3::new(1.0, 1.0, 0.0),
+			Vec3::new(0.0, 3.0, 0.0),
+		]);
+		assert_eq!(collider.face_count(), 2);
+		assert_eq!(collider.edge_count(), 8);
+		assert_eq!(collider.vertex_count(), 7);
+
+		// Removing the triangle leaves the merged rectangle as it was.
+		collider.remove_face(1).unwrap();
+		assert_eq!(collider.face_count(), 1);
+		assert_eq!(collider.edge_count(), 6);
 		assert_eq!(collider.vertex_count(), 6);
+		assert!(collider.is_valid());
+	}
+
+	/// Checks a (convex, centered on the origin) mesh is closed up, with every face wound counter-clockwise from outside.
+	fn assert_closed_and_outward(collider : &MeshCollider) {
+		assert!(collider.is_valid());
+		for (start, end) in collider.edges() {
+			let uses = collider.faces().filter(|corners| (0..corners.len()).any(|index| {
+				let (first, second) = (corners[index], corners[(index + 1) % corners.len()]);
+				(first == start && second == end) || (first == end && second == start)
+			})).count();
+			assert_eq!(uses, 2);
+		}
+		for corners in collider.faces() {
+			let center = corners.iter().sum::<Vec3>() / (corners.len() as f32);
+			assert!(0.0 < face_normal(&corners).dot(&center));
+		}
+	}
+
+	/// Verify the primitive shapes come out with the expected (merged) geometry.
+	#[test]
+	fn check_primitives() {
+		let cuboid = MeshCollider::new_cuboid(Vec3::new(1.0, 2.0, 3.0)).unwrap();
+		assert_closed_and_outward(&cuboid);
+		assert_eq!((cuboid.vertex_count(), cuboid.face_count(), cuboid.edge_count()), (8, 6, 12));
+		assert!(cuboid.vertices().any(|vertex| vertex == Vec3::new(-1.0, 2.0, -3.0)));
+		assert!(MeshCollider::new_cuboid(Vec3::new(1.0, 0.0, 1.0)).is_err());
+
+		let cylinder = MeshCollider::new_cylinder(1.0, 2.0, 8).unwrap();
+		assert_closed_and_outward(&cylinder);
+		assert_eq!((cylinder.vertex_count(), cylinder.face_count(), cylinder.edge_count()), (16, 10, 24));
+		assert!(cylinder.vertices().all(|vertex| (vertex.y.abs() - 1.0).abs() < EPSILON));
+		assert!(MeshCollider::new_cylinder(1.0, 2.0, 2).is_err());
+
+		let capsule = MeshCollider::new_capsule(1.0, 2.0, 8).unwrap();
+		assert_closed_and_outward(&capsule);
+		assert_eq!((capsule.vertex_count(), capsule.face_count(), capsule.edge_count()), (34, 40, 72));
+		assert!(capsule.vertices().any(|vertex| vertex == Vec3::new(0.0, 2.0, 0.0)));
+		// Without any length, it's just a sphere.
+		let capsule = MeshCollider::new_capsule(1.0, 0.0, 8).unwrap();
+		assert_closed_and_outward(&capsule);
+		assert_eq!((capsule.vertex_count(), capsule.face_count(), capsule.edge_count()), (26, 32, 56));
+		assert!(MeshCollider::new_capsule(0.0, 2.0, 8).is_err());
+
+		let icosphere = MeshCollider::new_icosphere(2.0, 0).unwrap();
+		assert_closed_and_outward(&icosphere);
+		assert_eq!((icosphere.vertex_count(), icosphere.face_count(), icosphere.edge_count()), (12, 20, 30));
+		let icosphere = MeshCollider::new_icosphere(2.0, 1).unwrap();
+		assert_closed_and_outward(&icosphere);
+		assert_eq!((icosphere.vertex_count(), icosphere.face_count(), icosphere.edge_count()), (42, 80, 120));
+		assert!(icosphere.vertices().all(|vertex| (vertex.magnitude() - 2.0).abs() < 1e-5));
+		assert!(MeshCollider::new_icosphere(-1.0, 1).is_err());
+	}
+
+	/// Verify the geometry can be read back out, and edited.
+	#[test]
+	fn check_edit_mesh() {
+		let mut collider = MeshCollider::new();
+		let first = vec![
+			Vec3::new( 0.0, 1.0, 0.0),
+			Vec3::new( 1.0,-1.0, 0.0),
+			Vec3::new(-1.0,-1.0, 0.0),
+		];
+		let second = vec![
+			Vec3::new( 2.0, 1.0, 0.0),
+			Vec3::new( 1.0,-1.0, 0.0),
+			Vec3::new( 3.0,-1.0, 0.0),
+		];
+		collider.add_face(&first);
+		collider.add_face(&second);
+
+		assert_eq!(collider.vertices().count(), 5);
+		assert!(collider.vertices().any(|vertex| (vertex - Vec3::new(3.0,-1.0, 0.0)).magnitude() < EPSILON));
+		assert_eq!(collider.edges().count(), 6);
+		assert!(collider.edges().all(|(start, end)| EPSILON < (start - end).magnitude()));
+		let faces : Vec<Vec<Vec3>> = collider.faces().collect();
+		assert_eq!(faces, vec![first.clone(), second.clone()]);
+		assert_eq!(collider.get_face(1), Some(second.clone()));
+		assert_eq!(collider.get_face(2), None);
+
+		// Removing a face drops the edges and vertices only it used.
+		assert_eq!(collider.remove_face(0), Some(first.clone()));
+		assert_eq!(collider.remove_face(1), None);
+		assert_eq!(collider.face_count(), 1);
+		assert_eq!(collider.edge_count(), 3);
+		assert_eq!(collider.vertex_count(), 3);
+		assert_eq!(collider.get_face(0), Some(second.clone()));
+		assert_eq!(collider.is_valid(), true);
+
+		// Clearing drops everything.
+		collider.clear();
+		assert_eq!(collider.face_count(), 0);
+		assert_eq!(collider.edge_count(), 0);
+		assert_eq!(collider.vertex_count(), 0);
+		assert_eq!(collider.is_valid(), false);
+	}
+
+	/// Makes a prism (from z=0 to z=1) out of an L-shaped polygon, which is concave.
+	fn make_l_prism() -> MeshCollider {
+		let outline = [
+			Vec3::new(0.0, 0.0, 0.0),
+			Vec3::new(2.0, 0.0, 0.0),
+			Vec3::new(2.0, 1.0, 0.0),
+			Vec3::new(1.0, 1.0, 0.0),
+			Vec3::new(1.0, 2.0, 0.0),
+			Vec3::new(0.0, 2.0, 0.0),
+		];
+		let up = Vec3::new(0.0, 0.0, 1.0);
+		let mut collider = MeshCollider::new();
+		// The ends have to be split into two convex pieces each.
+		for piece in [[0, 1, 2, 3], [0, 3, 4, 5]].iter() {
+			collider.add_face(&piece.iter().map(|index| outline[*index] + up).collect());
+			collider.add_face(&piece.iter().rev().map(|index| outline[*index]).collect());
+		}
+		for index in 0..outline.len() {
+			let (start, end) = (outline[index], outline[(index + 1) % outline.len()]);
+			collider.add_face(&vec![start, end, end + up, start + up]);
+		}
+		collider
+	}
+
+	/// Whether a point is inside a convex mesh (with faces wound counter-clockwise from outside).
+	fn is_inside(collider : &MeshCollider, point : &Vec3) -> bool {
+		collider.faces().all(|corners| (point - corners[0]).dot(&face_normal(&corners)) < 0.0)
+	}
+
+	/// Verify a convex mesh stays as one piece, and a concave one gets split up.
+	#[test]
+	fn check_decompose_convex() {
+		let mut cube = MeshCollider::new();
+		cube.add_face(&vec![Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), Vec3::new(1.0, 1.0, 0.0), Vec3::new(1.0, 0.0, 0.0)]);
+		cube.add_face(&vec![Vec3::new(0.0, 0.0, 1.0), Vec3::new(1.0, 0.0, 1.0), Vec3::new(1.0, 1.0, 1.0), Vec3::new(0.0, 1.0, 1.0)]);
+		cube.add_face(&vec![Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 1.0), Vec3::new(0.0, 0.0, 1.0)]);
+		cube.add_face(&vec![Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 1.0, 1.0), Vec3::new(1.0, 1.0, 1.0), Vec3::new(1.0, 1.0, 0.0)]);
+		cube.add_face(&vec![Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 1.0, 1.0), Vec3::new(0.0, 1.0, 0.0)]);
+		cube.add_face(&vec![Vec3::new(1.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 0.0), Vec3::new(1.0, 1.0, 1.0), Vec3::new(1.0, 0.0, 1.0)]);
+		cube.restitution_coefficient = 0.5;
+		let pieces = cube.decompose_convex(&ConvexDecompositionParams::new());
+		assert_eq!(pieces.len(), 1);
+		assert_eq!(pieces[0].vertex_count(), 8);
+		assert_eq!(pieces[0].face_count(), 6);
+		assert_eq!(pieces[0].restitution_coefficient, 0.5);
+
+		let l_prism = make_l_prism();
+		// Forcing it into one piece fills in the notch.
+		let mut params = ConvexDecompositionParams::new();
+		params.max_hulls = 1;
+		params.max_concavity = 100.0;
+		let pieces = l_prism.decompose_convex(&params);
+		assert_eq!(pieces.len(), 1);
+		assert!(is_inside(&pieces[0], &Vec3::new(1.2, 1.2, 0.5)));
+		assert!(!is_inside(&pieces[0], &Vec3::new(1.8, 1.8, 0.5)));
+
+		// Otherwise it's split up so the notch is left out, but the rest is covered.
+		let pieces = l_prism.decompose_convex(&ConvexDecompositionParams::new());
+		assert!(2 <= pieces.len());
+		assert!(pieces.iter().all(|piece| piece.is_valid()));
+		// Every piece is convex: none of its corners are in front of any of its faces.
+		for piece in pieces.iter() {
+			for corners in piece.faces() {
+				let normal = face_normal(&corners);
+				assert!(piece.vertices.iter().all(|vertex| (vertex - corners[0]).dot(&normal) < EPSILON));
+			}
+		}
+		assert!(!pieces.iter().any(|piece| is_inside(piece, &Vec3::new(1.2, 1.2, 0.5))));
+		for point in [Vec3::new(0.8, 0.4, 0.5), Vec3::new(0.4, 0.8, 0.5), Vec3::new(1.8, 0.5, 0.5), Vec3::new(0.5, 1.8, 0.5)].iter() {
+			assert!(pieces.iter().any(|piece| is_inside(piece, point)));
+		}
+
+		// Invalid settings give nothing.
+		params.max_hulls = 0;
+		assert!(l_prism.decompose_convex(&params).is_empty());
+	}
+
+	/// Verify validating meshes finds what's wrong with them, and that orienting faces fixes up the winding.
+	#[test]
+	fn check_validate_and_orient() {
+		let cuboid = MeshCollider::new_cuboid(Vec3::new(1.0, 2.0, 3.0)).unwrap();
+		assert!(cuboid.validate().is_ok());
+		assert!(make_l_prism().validate().is_ok());
+		let adjacency = cuboid.adjacency();
+		assert!(adjacency.edge_faces.iter().all(|faces| 2 == faces.len()));
+		assert!(adjacency.vertex_faces.iter().all(|faces| 3 == faces.len()));
+
+		// A single face wound the wrong way disagrees with all of its neighbors.
+		let mut flipped = cuboid.clone();
+		Arc::make_mut(&mut flipped.faces)[2].reverse();
+		let validation = flipped.validate();
+		assert_eq!(validation.inconsistent_edges.len(), 4);
+		assert!(!validation.is_manifold());
+		assert_eq!(flipped.orient_faces(), Ok(1));
+		assert!(flipped.validate().is_ok());
+		assert_eq!(flipped.get_faces(), cuboid.get_faces());
+		assert_eq!(flipped.orient_faces(), Ok(0));
+
+		// Every face wound the wrong way is consistent, but inside out.
+		let mut inside_out = cuboid.clone();
+		for face in Arc::make_mut(&mut inside_out.faces).iter_mut() { face.reverse(); }
+		let validation = inside_out.validate();
+		assert!(validation.is_manifold());
+		assert!(validation.inside_out);
+		assert_eq!(inside_out.orient_faces(), Ok(6));
+		assert_closed_and_outward(&inside_out);
+
+		// Missing a face leaves a hole (which orienting can't fix, but doesn't fail on).
+		let mut open = cuboid.clone();
+		open.remove_face(0).unwrap();
+		let validation = open.validate();
+		assert_eq!(validation.open_edges.len(), 4);
+		assert!(!validation.inside_out);
+		assert_eq!(open.orient_faces(), Ok(0));
+
+		// Duplicating a face gives edges with more than two faces, so there's no way to orient it.
+		let mut duplicated = cuboid.clone();
+		let copy = duplicated.faces[0].clone();
+		Arc::make_mut(&mut duplicated.faces).push(copy);
+		let validation = duplicated.validate();
+		assert_eq!(validation.duplicate_faces, vec![(0, 6)]);
+		assert_eq!(validation.non_manifold_edges.len(), 4);
+		assert!(duplicated.orient_faces().is_err());
+
+		// A face with all of its corners on a line doesn't cover anything.
+		let mut degenerate = MeshCollider::new();
+		degenerate.vertices = Arc::new(vec![Vec3::zeros(), Vec3::new(1.0, 0.0, 0.0), Vec3::new(2.0, 0.0, 0.0)]);
+		degenerate.faces = Arc::new(vec![vec![0, 1, 2]]);
+		assert_eq!(degenerate.validate().degenerate_faces, vec![0]);
 	}
 }
@@ -0,0 +1,17 @@
+/// Which part of a [crate::MeshCollider] (or [crate::TriMeshCollider]) was hit. See [crate::CollisionRecord::first_feature].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MeshFeature {
+	/// A corner, as an index into [crate::MeshCollider::get_vertices] (or [crate::TriMeshCollider::get_vertices]).
+	Vertex(usize),
+	/// An edge, as the indices of the vertices at either end (with the lower index first).
+	Edge(usize, usize),
+	/// A face, as an index into [crate::MeshCollider::get_faces]. For triangle meshes, the index of the triangle (into [crate::TriMeshCollider::get_triangles]).
+	Face(usize),
+}
+
+impl MeshFeature {
+	/// Makes an edge between the two given vertices, putting the lower index first.
+	pub fn edge(first : usize, second : usize) -> MeshFeature {
+		if first < second { MeshFeature::Edge(first, second) } else { MeshFeature::Edge(second, first) }
+	}
+}
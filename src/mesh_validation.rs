@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+
+use crate::types::Vec3;
+use crate::mesh_collider::face_normal;
+
+/// What's wrong with a mesh's geometry (if anything). See [crate::MeshCollider::validate].
+///
+/// Edges are given as pairs of vertex indices (into [crate::MeshCollider::get_vertices]) with the lower index first, and faces as indices into [crate::MeshCollider::get_faces]. Everything is sorted.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeshValidation {
+	/// Edges that only one face uses, so the mesh has a hole (or is just a surface, like a floor) there.
+	pub open_edges : Vec<(usize, usize)>,
+	/// Edges that more than two faces use.
+	pub non_manifold_edges : Vec<(usize, usize)>,
+	/// Edges between two faces that wind the opposite way from each other (so one of them faces the wrong way).
+	pub inconsistent_edges : Vec<(usize, usize)>,
+	/// Pairs of faces that use the very same corners.
+	pub duplicate_faces : Vec<(usize, usize)>,
+	/// Faces that don't cover any area (or have fewer than three corners).
+	pub degenerate_faces : Vec<usize>,
+	/// Whether the mesh is closed and consistently wound, but all of its faces point inward.
+	pub inside_out : bool,
+}
+
+impl MeshValidation {
+	/// Whether the mesh is a closed surface where every edge is shared by exactly two faces, and every face points the same way as its neighbors.
+	pub fn is_manifold(&self) -> bool {
+		self.open_edges.is_empty() && self.non_manifold_edges.is_empty() && self.inconsistent_edges.is_empty() && self.duplicate_faces.is_empty()
+	}
+
+	/// Whether nothing at all is wrong: the mesh is manifold, has no degenerate faces, and its faces point outward.
+	pub fn is_ok(&self) -> bool {
+		self.is_manifold() && self.degenerate_faces.is_empty() && !self.inside_out
+	}
+}
+
+/// Every face that goes along each edge (keyed with the lower vertex index first), and whether it goes from the lower index to the higher one.
+fn edge_uses(faces : &[Vec<usize>]) -> BTreeMap<(usize, usize), Vec<(usize, bool)>> {
+	let mut uses : BTreeMap<(usize, usize), Vec<(usize, bool)>> = BTreeMap::new();
+	for (face_index, face) in faces.iter().enumerate() {
+		for index in 0..face.len() {
+			let (first, second) = (face[index], face[(index + 1) % face.len()]);
+			let key = if first < second { (first, second) } else { (second, first) };
+			uses.entry(key).or_default().push((face_index, first < second));
+		}
+	}
+	uses
+}
+
+/// Six times the volume that the given faces enclose (positive when they point outward). Only meaningful for a closed, consistently wound set of faces.
+fn signed_volume(vertices : &[Vec3], faces : &[Vec<usize>], face_indices : &[usize]) -> f32 {
+	let mut volume = 0.0;
+	for face in face_indices.iter().map(|index| &faces[*index]) {
+		for corner in 1..face.len().saturating_sub(1) {
+			volume += vertices[face[0]].dot(&vertices[face[corner]].cross(&vertices[face[corner + 1]]));
+		}
+	}
+	volume
+}
+
+/// Checks a mesh's geometry for anything that would trip up collisions. See [crate::MeshCollider::validate].
+pub fn validate(vertices : &[Vec3], faces : &[Vec<usize>]) -> MeshValidation {
+	let mut result = MeshValidation::default();
+	let mut seen : BTreeMap<Vec<usize>, usize> = BTreeMap::new();
+	for (face_index, face) in faces.iter().enumerate() {
+		if face.len() < 3 || face_normal(&face.iter().map(|index| vertices[*index]).collect()).x.is_nan() {
+			result.degenerate_faces.push(face_index);
+		}
+		let mut sorted = face.clone();
+		sorted.sort_unstable();
+		if let Some(existing) = seen.get(&sorted) {
+			result.duplicate_faces.push((*existing, face_index));
+		} else {
+			seen.insert(sorted, face_index);
+		}
+	}
+	for (edge, uses) in edge_uses(faces) {
+		match uses.len() {
+			1 => result.open_edges.push(edge),
+			2 => if uses[0].1 == uses[1].1 { result.inconsistent_edges.push(edge); },
+			_ => result.non_manifold_edges.push(edge),
+		}
+	}
+	if result.is_manifold() {
+		let all : Vec<usize> = (0..faces.len()).collect();
+		result.inside_out = signed_volume(vertices, faces, &all) < 0.0;
+	}
+	result
+}
+
+/// Flips faces so each winds the same way as its neighbors, and then flips every closed piece that's inside out. See [crate::MeshCollider::orient_faces].
+///
+/// Returns how many faces were flipped. Fails (without changing anything) if any edge is used by more than two faces, since there's no consistent way to wind those.
+pub fn orient_faces(vertices : &[Vec3], faces : &mut Vec<Vec<usize>>) -> Result<usize, ()> {
+	let uses = edge_uses(faces);
+	if uses.values().any(|uses| 2 < uses.len()) {
+		return Err(()); // TODO: An error type.
+	}
+	let mut neighbors : Vec<Vec<((usize, usize), usize)>> = vec![Vec::new(); faces.len()];
+	for (edge, uses) in uses.iter() {
+		if 2 == uses.len() {
+			neighbors[uses[0].0].push((*edge, uses[1].0));
+			neighbors[uses[1].0].push((*edge, uses[0].0));
+		}
+	}
+	// Whether each face goes along the given edge from its lower index to its higher one (after any flips so far).
+	let goes_up = |faces : &Vec<Vec<usize>>, face_index : usize, (low, high) : (usize, usize)| {
+		let face = &faces[face_index];
+		(0..face.len()).any(|index| face[index] == low && face[(index + 1) % face.len()] == high)
+	};
+	let mut flipped = vec![false; faces.len()];
+	let mut visited = vec![false; faces.len()];
+	for start in 0..faces.len() {
+		if visited[start] { continue; }
+		// Walk over everything connected to this face, making each neighbor go the opposite way along their shared edge.
+		visited[start] = true;
+		let mut piece = vec![start];
+		let mut next = 0;
+		let mut closed = true;
+		while next < piece.len() {
+			let current = piece[next];
+			next += 1;
+			if neighbors[current].len() < faces[current].len() { closed = false; }
+			for (edge, neighbor) in neighbors[current].clone() {
+				if visited[neighbor] { continue; }
+				visited[neighbor] = true;
+				if goes_up(faces, current, edge) == goes_up(faces, neighbor, edge) {
+					faces[neighbor].reverse();
+					flipped[neighbor] = !flipped[neighbor];
+				}
+				piece.push(neighbor);
+			}
+		}
+		// Then point closed pieces outward.
+		if closed && signed_volume(vertices, faces, &piece) < 0.0 {
+			for face_index in piece {
+				faces[face_index].reverse();
+				flipped[face_index] = !flipped[face_index];
+			}
+		}
+	}
+	Ok(flipped.iter().filter(|flipped| **flipped).count())
+}
@@ -1,11 +1,14 @@
-use crate::types::{Vec3, Mat3, EntityHandle};
+use crate::types::{Vec3, Mat3, EntityHandle, MaterialHandle};
 use crate::collider::{ColliderType, Collider, InternalCollider};
+use crate::anisotropic_friction::AnisotropicFriction;
 
 /// The internal representation of a null collider.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct InternalNullCollider {
 	/// The entity that this is linked to (if any).
 	entity : Option<EntityHandle>,
+	/// If this takes part in collisions and queries. Only changed with [crate::PhysicsSystem::set_collider_enabled].
+	enabled : bool,
 	/// The position of the mass (relative to the parent's origin).
 	pub position : Vec3,
 	/// The total mass. Must not be negative.
@@ -22,6 +25,7 @@ impl InternalNullCollider {
 		} else {
 			Ok(Box::new(InternalNullCollider {
 				entity: None,
+				enabled: true,
 				position: source.position,
 				mass: source.mass,
 				moment_of_inertia: source.moment_of_inertia,
@@ -56,6 +60,9 @@ impl InternalCollider for InternalNullCollider {
 	/// The specific type.
 	fn get_type(&self) -> ColliderType { ColliderType::NULL }
 
+	/// Makes a boxed copy.
+	fn clone_boxed(&self) -> Box<dyn InternalCollider> { Box::new(self.clone()) }
+
 	/// Sets the entity this is attached to, returning the previous one.
 	fn set_entity(&mut self, handle : Option<EntityHandle>) -> Option<EntityHandle> {
 		let old = self.entity;
@@ -66,8 +73,16 @@ impl InternalCollider for InternalNullCollider {
 	/// Retrieves the stored entity handle that this is attached to.
 	fn get_entity(&mut self) -> Option<EntityHandle> { self.entity }
 
+	fn set_enabled(&mut self, enabled : bool) { self.enabled = enabled; }
+
+	fn is_enabled(&self) -> bool { self.enabled }
+
 	fn get_local_center_of_mass(&self) -> Vec3 { self.position }
 
+	fn get_local_position(&self) -> Vec3 { self.position }
+
+	fn set_local_position(&mut self, position : &Vec3) { self.position = *position; }
+
 	fn get_mass(&self) -> f32 { self.mass }
 
 	fn get_moment_of_inertia_tensor(&self) -> Mat3 { self.moment_of_inertia }
@@ -79,10 +94,20 @@ impl InternalCollider for InternalNullCollider {
 	fn get_static_friction_coefficient(&self) -> f32 { 0.0 }
 
 	fn get_dynamic_friction_coefficient(&self) -> f32 { 0.0 }
+
+	fn get_anisotropic_friction(&self) -> Option<AnisotropicFriction> { None }
+
+	fn get_margin(&self) -> f32 { 0.0 }
+	fn get_material(&self) -> MaterialHandle { 0 }
+
+	fn is_query_only(&self) -> bool { false }
+
+	fn get_surface_tag(&self) -> u32 { 0 }
 }
 
 /// A collider that doesn't collide. Instead it just provides mass and inertia at a point.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NullCollider {
 	/// The entity that this is linked to (if any). This is NOT copied back into InternalSphereCollider, hence why it's not "pub".
 	///
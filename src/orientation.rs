@@ -7,6 +7,7 @@ use crate::types::{Vec3, Mat3, Quat, Isometry};
 ///
 /// Put another way, this is how to get from an entity's local space to world space (and vise versa).
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Orientation {
 	/// The point all rotations are about.
 	///
@@ -49,11 +50,11 @@ fn rotate_moment_of_inertia(moment : &Mat3, rotation : &Quat) -> Mat3 {
 
 impl Orientation {
 	/// Creates a new instance.
-	pub fn new(position : &Vec3, rotation : &Vec3, internal_origin_offset : &Vec3) -> Orientation {
+	pub fn new(position : impl Into<Vec3>, rotation : impl Into<Vec3>, internal_origin_offset : impl Into<Vec3>) -> Orientation {
 		Orientation {
-			position: position.clone(),
-			rotation: Quat::from_scaled_axis(*rotation),
-			internal_origin_offset: internal_origin_offset.clone(),
+			position: position.into(),
+			rotation: Quat::from_scaled_axis(rotation.into()),
+			internal_origin_offset: internal_origin_offset.into(),
 		}
 	}
 
@@ -151,6 +152,14 @@ impl Orientation {
 	}
 }
 
+/// Turns into the matrix that goes from local space to world space (see [Orientation::into_world]), i.e. for handing entity transforms to a renderer. Only available with the `mint` feature.
+#[cfg(feature = "mint")]
+impl From<Orientation> for mint::ColumnMatrix4<f32> {
+	fn from(orientation : Orientation) -> mint::ColumnMatrix4<f32> {
+		orientation.into_world().to_homogeneous().into()
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -160,7 +169,7 @@ mod tests {
 	/// Verify basic transformations work as expected.
 	#[test]
 	fn basic_transforms() {
-		let mut orientation = Orientation::new(&Vec3::new(1.0, 1.0, 1.0), &Vec3::zeros(), &Vec3::new(0.0, 2.0, 0.0));
+		let mut orientation = Orientation::new(Vec3::new(1.0, 1.0, 1.0), Vec3::zeros(), Vec3::new(0.0, 2.0, 0.0));
 		{
 			let origin = orientation.local_origin_in_world();
 			assert_eq!(origin.x, 1.0);
@@ -196,4 +205,16 @@ mod tests {
 			    - point.z * point.x,     - point.z * point.y, len - point.z * point.z,
 		).scale(mass)
 	}*/
+
+	/// Verify the `mint` matrix does the same thing as [Orientation::into_world].
+	#[cfg(feature = "mint")]
+	#[test]
+	fn mint_matrix() {
+		let orientation = Orientation::new(Vec3::new(1.0, 2.0, 3.0), Vec3::new(0.0, PI / 2.0, 0.0), Vec3::new(0.0, 0.5, 0.0));
+		let matrix : mint::ColumnMatrix4<f32> = orientation.into();
+		let point = nalgebra::Matrix4::from(matrix) * nalgebra::Vector4::new(1.0, 0.0, 0.0, 1.0);
+		let expected = orientation.position_into_world(&Vec3::new(1.0, 0.0, 0.0));
+		assert!((point.xyz() - expected).magnitude() < EPSILON, "{:?} vs {:?}", point, expected);
+		assert_eq!(point.w, 1.0);
+	}
 }
@@ -0,0 +1,141 @@
+use crate::consts::{EPSILON, SURFACE_OFFSET};
+use crate::types::Vec3;
+use crate::physics_world::PhysicsWorld;
+
+/// A lightweight set of small spheres (i.e. sparks or debris) that all share the same settings.
+///
+/// Stored in (and stepped by) [PhysicsSystem](crate::PhysicsSystem). See [PhysicsSystem::add_particle_set](crate::PhysicsSystem::add_particle_set).
+///
+/// The particles bounce off of all of the colliders in the system, but don't push back on them (the entities never notice the particles). The particles also don't collide with each other.
+///
+/// Everything is stored as a separate list per property (rather than a list of particles), so the lists can be handed straight to a renderer.
+#[derive(Debug, Clone)]
+pub struct ParticleSet {
+	/// The position of every particle.
+	positions : Vec<Vec3>,
+	/// The velocity of every particle.
+	velocities : Vec<Vec3>,
+
+	/// The radius of every particle. Must be positive.
+	///
+	/// Has no default.
+	pub radius : f32,
+
+	/// The restitution coefficient used when a particle hits a collider.
+	///
+	/// Defaults to `0.5`.
+	pub restitution_coefficient : f32,
+
+	/// The acceleration applied to every particle.
+	///
+	/// Defaults to zero.
+	pub gravity : Vec3,
+}
+
+impl ParticleSet {
+	/// Creates an empty set.
+	pub fn new(radius : f32) -> ParticleSet {
+		ParticleSet {
+			positions: Vec::new(),
+			velocities: Vec::new(),
+			radius,
+			restitution_coefficient: 0.5,
+			gravity: Vec3::zeros(),
+		}
+	}
+
+	/// If this is in a valid state.
+	pub fn is_valid(&self) -> bool {
+		EPSILON < self.radius
+	}
+
+	/// Adds a particle, returning its index.
+	pub fn add(&mut self, position : impl Into<Vec3>, velocity : impl Into<Vec3>) -> usize {
+		self.positions.push(position.into());
+		self.velocities.push(velocity.into());
+		self.positions.len() - 1
+	}
+
+	/// Removes a particle. The last particle is moved into its place (so only that particle's index changes).
+	pub fn remove(&mut self, index : usize) {
+		self.positions.swap_remove(index);
+		self.velocities.swap_remove(index);
+	}
+
+	/// Removes all of the particles.
+	pub fn clear(&mut self) {
+		self.positions.clear();
+		self.velocities.clear();
+	}
+
+	/// Gets how many particles there are.
+	pub fn len(&self) -> usize { self.positions.len() }
+
+	/// Whether there are no particles.
+	pub fn is_empty(&self) -> bool { self.positions.is_empty() }
+
+	/// Gets the position of every particle.
+	pub fn get_positions(&self) -> &Vec<Vec3> { &self.positions }
+
+	/// Gets the velocity of every particle.
+	pub fn get_velocities(&self) -> &Vec<Vec3> { &self.velocities }
+
+	/// Moves all of the particles forward by `dt`, bouncing them off of the colliders in `system`.
+	pub fn step(&mut self, system : &dyn PhysicsWorld, dt : f32) {
+		for (position, velocity) in self.positions.iter_mut().zip(self.velocities.iter_mut()) {
+			*velocity += self.gravity * dt;
+			let movement = *velocity * dt;
+			let distance = movement.magnitude();
+			if distance < EPSILON { continue; }
+			if let Some(hit) = system.cast_sphere(position, &movement, distance, self.radius, None) {
+				// Stop at the hit (dropping the rest of the movement), and bounce.
+				*position += movement * (hit.distance / distance) + hit.normal * SURFACE_OFFSET;
+				let normal_speed = velocity.dot(&hit.normal);
+				if normal_speed < 0.0 {
+					*velocity -= hit.normal * ((1.0 + self.restitution_coefficient) * normal_speed);
+				}
+			} else {
+				*position += movement;
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::physics_system::PhysicsSystem;
+	use std::f32::INFINITY;
+	use crate::entity::Entity;
+	use crate::plane_collider::PlaneCollider;
+	use crate::collider_wrapper::ColliderWrapper;
+
+	/// Check that particles are moved by the system and bounce off of colliders.
+	#[test]
+	fn bounce() {
+		let mut system = PhysicsSystem::new();
+		{
+			let floor = system.add_entity(Entity::new()).unwrap();
+			let mut plane = PlaneCollider::new();
+			plane.mass = INFINITY;
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			system.link_collider(plane_handle, Some(floor)).unwrap();
+		}
+		let mut particles = ParticleSet::new(0.01);
+		particles.restitution_coefficient = 1.0;
+		for index in 0..100 {
+			particles.add(Vec3::new(index as f32 * 0.1, 0.5, 0.0), Vec3::new(0.0, -1.0, 0.0));
+		}
+		let handle = system.add_particle_set(particles).unwrap();
+		for _ in 0..60 {
+			system.step(0.01);
+		}
+		// Should have bounced straight back up, so now be going up from 0.1 above the floor.
+		let particles = system.get_particle_set(handle).unwrap();
+		assert_eq!(100, particles.len());
+		for (position, velocity) in particles.get_positions().iter().zip(particles.get_velocities().iter()) {
+			assert!((position.y - 0.1).abs() < 0.02);
+			assert!((velocity - Vec3::new(0.0, 1.0, 0.0)).magnitude() < EPSILON);
+		}
+	}
+}
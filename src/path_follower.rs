@@ -0,0 +1,201 @@
+use crate::consts::EPSILON;
+use crate::types::{Vec3, Quat, EntityHandle};
+use crate::physics_world::PhysicsWorld;
+
+/// Where a [PathFollower]'s entity should be at a given time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PathKeyframe {
+	/// When the entity should be here (in seconds since the path started). Must be finite and not negative.
+	pub time : f32,
+
+	/// Where the entity's center of mass should be (like [crate::Entity::position]).
+	pub position : Vec3,
+
+	/// How the entity should be rotated, as a scaled axis (like [crate::Entity::rotation]).
+	pub rotation : Vec3,
+}
+
+impl PathKeyframe {
+	/// Creates a new instance.
+	pub fn new(time : f32, position : impl Into<Vec3>, rotation : impl Into<Vec3>) -> PathKeyframe {
+		PathKeyframe {
+			time,
+			position: position.into(),
+			rotation: rotation.into(),
+		}
+	}
+
+	/// If this is in a valid state.
+	pub fn is_valid(&self) -> bool {
+		self.time.is_finite() && 0.0 <= self.time
+	}
+}
+
+/// Moves an entity (usually a kinematic one, like a moving platform) along a path of keyframes.
+///
+/// Rather than moving the entity directly, this sets its velocity and angular velocity to whatever will get it to the next spot on the path by the end of the step. So anything riding on (or hit by) it is pushed around like it would be by any other moving entity.
+///
+/// Call [PathFollower::step] right before every [PhysicsSystem::step](crate::PhysicsSystem::step) (with the same `dt`).
+#[derive(Debug, Clone)]
+pub struct PathFollower {
+	/// The entity being moved.
+	pub entity : EntityHandle,
+
+	/// How far along the path it is (in seconds).
+	///
+	/// Defaults to zero.
+	pub time : f32,
+
+	/// Whether to start over from the beginning after reaching the last keyframe. The last keyframe should then usually match the first.
+	///
+	/// Defaults to `false`.
+	pub looping : bool,
+
+	/// All of the keyframes, sorted by time.
+	///
+	/// Defaults to an empty list.
+	keyframes : Vec<PathKeyframe>,
+}
+
+impl PathFollower {
+	/// Creates a follower without any keyframes.
+	pub fn new(entity : EntityHandle) -> PathFollower {
+		PathFollower {
+			entity,
+			time: 0.0,
+			looping: false,
+			keyframes: Vec::new(),
+		}
+	}
+
+	/// Adds a keyframe (keeping them sorted by time). Fails if the keyframe isn't valid.
+	pub fn add_keyframe(&mut self, keyframe : PathKeyframe) -> Result<(), ()> {
+		if !keyframe.is_valid() {
+			return Err(()); // TODO: An error type.
+		}
+		let index = self.keyframes.iter().position(|other| keyframe.time < other.time).unwrap_or(self.keyframes.len());
+		self.keyframes.insert(index, keyframe);
+		Ok(())
+	}
+
+	/// Gets all of the keyframes, sorted by time.
+	pub fn get_keyframes(&self) -> &[PathKeyframe] { &self.keyframes }
+
+	/// Gets how long the path is (i.e. the last keyframe's time).
+	pub fn get_duration(&self) -> f32 {
+		self.keyframes.last().map_or(0.0, |keyframe| keyframe.time)
+	}
+
+	/// Gets where the entity should be (its position and rotation) at the given time along the path. Returns None if there aren't any keyframes.
+	///
+	/// Positions are linearly interpolated and rotations are spherically interpolated between keyframes. Before the first keyframe it stays at the first one, and after the last it stays at the last (unless `looping`).
+	pub fn sample(&self, time : f32) -> Option<(Vec3, Quat)> {
+		let first = self.keyframes.first()?;
+		let duration = self.get_duration();
+		let time = if self.looping && EPSILON < duration { time.rem_euclid(duration) } else { time };
+		let next_index = self.keyframes.iter().position(|keyframe| time < keyframe.time);
+		let (before, after) = match next_index {
+			Some(0) => return Some((first.position, Quat::from_scaled_axis(first.rotation))),
+			Some(index) => (&self.keyframes[index - 1], &self.keyframes[index]),
+			None => {
+				let last = self.keyframes.last().unwrap();
+				return Some((last.position, Quat::from_scaled_axis(last.rotation)));
+			},
+		};
+		let fraction = (time - before.time) / (after.time - before.time);
+		let position = before.position.lerp(&after.position, fraction);
+		let rotation = Quat::from_scaled_axis(before.rotation).slerp(&Quat::from_scaled_axis(after.rotation), fraction);
+		Some((position, rotation))
+	}
+
+	/// Sets the entity's velocities so it follows the path over the next `dt`, then moves `time` forward by `dt`. Fails if the entity is gone or `dt` isn't positive.
+	///
+	/// Does nothing (besides moving `time` forward) if there aren't any keyframes.
+	pub fn step(&mut self, system : &mut dyn PhysicsWorld, dt : f32) -> Result<(), ()> {
+		if dt < EPSILON {
+			return Err(()); // TODO: An error type.
+		}
+		let mut entity = system.get_entity(self.entity).ok_or(())?; // TODO: An error type.
+		let next_time = self.time + dt;
+		if let Some((position, rotation)) = self.sample(next_time) {
+			// The entity only moves its velocity times its time scale each step.
+			let scaled_dt = dt * entity.time_scale;
+			if EPSILON < scaled_dt {
+				let rotation_delta = rotation * Quat::from_scaled_axis(entity.rotation).inverse();
+				entity.velocity = (position - entity.position) / scaled_dt;
+				entity.angular_velocity = rotation_delta.scaled_axis() / scaled_dt;
+				system.update_entity(self.entity, entity)?;
+			}
+		}
+		self.time = next_time;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::f32::consts::PI;
+	use crate::entity::Entity;
+	use crate::body_type::BodyType;
+	use crate::sphere_collider::SphereCollider;
+	use crate::collider_wrapper::ColliderWrapper;
+	use crate::physics_system::PhysicsSystem;
+
+	/// Verify a kinematic entity is moved and turned along the path, and stops at the end of it.
+	#[test]
+	fn follows_path() {
+		let mut system = PhysicsSystem::new();
+		let platform = {
+			let mut entity = Entity::new();
+			entity.body_type = BodyType::Kinematic;
+			let entity_handle = system.add_entity(entity).unwrap();
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(SphereCollider::new(1.0))).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		let mut follower = PathFollower::new(platform);
+		// Added out of order on purpose.
+		follower.add_keyframe(PathKeyframe::new(2.0, Vec3::new(2.0, 2.0, 0.0), Vec3::new(0.0, PI / 2.0, 0.0))).unwrap();
+		follower.add_keyframe(PathKeyframe::new(0.0, Vec3::zeros(), Vec3::zeros())).unwrap();
+		follower.add_keyframe(PathKeyframe::new(1.0, Vec3::new(2.0, 0.0, 0.0), Vec3::zeros())).unwrap();
+		assert!(follower.add_keyframe(PathKeyframe::new(-1.0, Vec3::zeros(), Vec3::zeros())).is_err());
+		assert_eq!(follower.get_keyframes().iter().map(|keyframe| keyframe.time).collect::<Vec<f32>>(), vec![0.0, 1.0, 2.0]);
+		assert_eq!(follower.get_duration(), 2.0);
+
+		let dt = 1.0 / 60.0;
+		for _ in 0..30 {
+			follower.step(&mut system, dt).unwrap();
+			system.step(dt);
+		}
+		let entity = system.get_entity(platform).unwrap();
+		assert!((entity.position - Vec3::new(1.0, 0.0, 0.0)).magnitude() < 0.001, "{:?}", entity.position);
+		assert!((entity.velocity - Vec3::new(2.0, 0.0, 0.0)).magnitude() < 0.001, "{:?}", entity.velocity);
+		assert!(entity.angular_velocity.magnitude() < 0.001);
+
+		for _ in 30..90 {
+			follower.step(&mut system, dt).unwrap();
+			system.step(dt);
+		}
+		let entity = system.get_entity(platform).unwrap();
+		assert!((entity.position - Vec3::new(2.0, 1.0, 0.0)).magnitude() < 0.001, "{:?}", entity.position);
+		assert!((entity.rotation - Vec3::new(0.0, PI / 4.0, 0.0)).magnitude() < 0.001, "{:?}", entity.rotation);
+		assert!((entity.angular_velocity - Vec3::new(0.0, PI / 2.0, 0.0)).magnitude() < 0.001, "{:?}", entity.angular_velocity);
+
+		// Stops once it runs out of path.
+		for _ in 90..150 {
+			follower.step(&mut system, dt).unwrap();
+			system.step(dt);
+		}
+		let entity = system.get_entity(platform).unwrap();
+		assert!((entity.position - Vec3::new(2.0, 2.0, 0.0)).magnitude() < 0.001, "{:?}", entity.position);
+		assert!((entity.rotation - Vec3::new(0.0, PI / 2.0, 0.0)).magnitude() < 0.001, "{:?}", entity.rotation);
+		assert!(entity.velocity.magnitude() < 0.001 && entity.angular_velocity.magnitude() < 0.001);
+
+		// Looping wraps back around to the start.
+		follower.looping = true;
+		let (position, _) = follower.sample(2.5).unwrap();
+		assert!((position - Vec3::new(1.0, 0.0, 0.0)).magnitude() < EPSILON);
+	}
+}
@@ -1,12 +1,16 @@
-use std::cell::RefCell;
 use std::borrow::BorrowMut;
-use std::collections::HashSet;
+use std::cmp::Ordering;
+use std::collections::{HashSet, HashMap};
+use std::time::{Duration, Instant};
 
 use generational_arena::Arena;
 
-use crate::consts::EPSILON;
-use crate::types::{Vec3, EntityHandle, ColliderHandle, UnaryForceGeneratorHandle};
+use crate::consts::{EPSILON, SURFACE_OFFSET};
+use crate::lock_cell::LockCell;
+use crate::types::{Vec3, Mat3, Quat, Isometry, EntityHandle, ColliderHandle, UnaryForceGeneratorHandle, ClothHandle, ParticleSetHandle, FluidRegionHandle, MaterialHandle};
 use crate::entity::{InternalEntity, Entity};
+use crate::body_type::BodyType;
+use crate::orientation::Orientation;
 use crate::collider::{ColliderType, InternalCollider};
 #[allow(unused_imports)] // Need this trait, but Rust's warning system doesn't seem to understand that.
 use crate::collider::Collider;
@@ -15,68 +19,463 @@ use crate::sphere_collider::{InternalSphereCollider};
 use crate::plane_collider::{InternalPlaneCollider};
 use crate::mesh_collider::{InternalMeshCollider};
 use crate::aligned_box_collider::{InternalAlignedBoxCollider};
+use crate::tri_mesh_collider::InternalTriMeshCollider;
 use crate::collider_wrapper::ColliderWrapper;
-use crate::collision::{collide, Collision};
+use crate::range::Range;
+use crate::collision::{collide, collide_reusing, Collision, CollisionScratch};
+use crate::batch_collision::{LANES, SphereLanes, HitTimes, spheres_touch, collide_sphere_with_spheres, sphere_with_spheres_collision, collide_plane_with_spheres, plane_with_spheres_collision};
+use crate::query::{RayHit, MeshFaceHit, CameraClip, cast_ray_against, closest_point_on};
+use crate::prediction::{PredictedShape, Prediction};
+use crate::snapshot::{DoubleBuffer, SnapshotReader};
 use crate::collision_record::CollisionRecord;
+use crate::record_limit::RecordLimit;
+use crate::sleep_change::SleepChange;
+use crate::fluid_region::{FluidRegion, FluidEvent, find_submerged};
+use crate::profiler::{Profiler, StepPhase, ActiveProfiler};
+use crate::jitter::Xorshift;
+use crate::break_event::{BreakEvent, Broken};
+#[cfg(feature = "scene")]
+use crate::scene::{Scene, SceneEntity};
+#[cfg(feature = "scene")]
+use crate::gravity_generator::GravityGenerator;
+use crate::contact::Contact;
+use crate::contact_pair::ContactPair;
+use crate::contact_graph::ContactGraph;
+use crate::entity_view::EntityView;
+use crate::diagnostics::{EnergyChange, EnergyDrift, EnergyDriftReport};
+use crate::contact_properties::ContactProperties;
+use crate::integrator::Integrator;
+use crate::friction_model::FrictionModel;
+use crate::restitution_model::RestitutionModel;
+use crate::wake_policy::WakePolicy;
+use crate::anisotropic_friction::AnisotropicFriction;
+use crate::contact_modifier::{ContactModifier, ModifiableContact};
+#[cfg(feature = "fixed-point")]
+use crate::fixed::{Fixed, FixedVec3};
 
 use crate::unary_force_generator::UnaryForceGenerator;
+use crate::cloth::Cloth;
+use crate::particle_set::ParticleSet;
 
 /// The entire physics system.
+///
+/// This is `Send + Sync`, so it can be stepped on a background thread (i.e. behind a lock, while other threads read copies of the last step's results). For that, everything put into it (like force generators and contact modifiers) must be `Send + Sync` too.
 pub struct PhysicsSystem {
 	/// All the whole physical objects.
-	entities : RefCell<Arena<InternalEntity>>,
+	pub(crate) entities : LockCell<Arena<InternalEntity>>,
 	/// All of the colliders on the physical objects.
-	colliders : RefCell<Arena<Box<dyn InternalCollider>>>,
+	pub(crate) colliders : LockCell<Arena<Box<dyn InternalCollider>>>,
 	/// All of the unary forces to apply.
-	unary_force_generators : RefCell<Arena<Box<dyn UnaryForceGenerator>>>,
+	pub(crate) unary_force_generators : LockCell<Arena<Box<dyn UnaryForceGenerator>>>,
+	/// The force generators that are turned off. See [PhysicsSystem::set_force_generator_enabled].
+	disabled_generators : HashSet<UnaryForceGeneratorHandle>,
+	/// What each force generator's forces are multiplied by, for the ones that aren't at full strength. See [PhysicsSystem::set_force_generator_strength].
+	generator_strengths : HashMap<UnaryForceGeneratorHandle, f32>,
+	/// All of the cloths. These are moved after all the entities.
+	cloths : Arena<Cloth>,
+	/// All of the particle sets. These are moved after all the entities.
+	particle_sets : Arena<ParticleSet>,
+	/// All of the fluid regions. These push on the entities along with the force generators.
+	fluid_regions : Arena<FluidRegion>,
+	/// Every entity that entered or exited a fluid region since the last [PhysicsSystem::take_fluid_events] call.
+	fluid_events : Vec<FluidEvent>,
+	/// The contact modifiers for each collider that has one.
+	contact_modifiers : HashMap<ColliderHandle, Box<dyn ContactModifier>>,
+	/// How far each collider has been moved (in its entity's local space) with [PhysicsSystem::move_collider] since the last step. Cleared by every step.
+	collider_motions : HashMap<ColliderHandle, Vec3>,
+	/// The contact properties for specific pairs of materials. Keyed by the pair's materials in sorted order.
+	material_pair_properties : HashMap<(MaterialHandle, MaterialHandle), ContactProperties>,
+	/// The contact properties for specific pairs of entities. Keyed by the pair's handles in sorted order.
+	entity_pair_properties : HashMap<(EntityHandle, EntityHandle), ContactProperties>,
+	/// Every entity that's attached to another one (see [PhysicsSystem::attach_entity]), keyed by the attached (child) entity.
+	attachments : HashMap<EntityHandle, Attachment>,
+	/// The break impulse of every collider that has one (see [PhysicsSystem::set_collider_break_impulse]).
+	collider_break_impulses : HashMap<ColliderHandle, f32>,
+	/// Everything that broke since the last [PhysicsSystem::take_break_events] call.
+	break_events : Vec<BreakEvent>,
+	/// Every entity whose lifetime ran out since the last [PhysicsSystem::take_expired_entities] call.
+	expired_entities : Vec<EntityHandle>,
 	/// The max number of physics iterations allowed per step.
 	///
-	/// For now this limits how many collisions can be handled in a step.
+	/// For now this limits how many collisions can be handled in a step. Each iteration handles every collision (between any pair of entities) that happens at the same time, so there's no separate limit for resolving them. Use [PhysicsSystem::step_with_iteration_max] to override it for a single step.
 	///
 	/// Defaults to 5.
-	pub iteration_max : u8,
+	pub iteration_max : u32,
+
+	/// The most (wall-clock) time a single step can spend handling collisions. Once it's used up no more iterations are started, just like running out of `iteration_max`, so the step won't get all the way through `dt` (see `last_step_shortfall`).
+	///
+	/// At least one iteration is always run, so things still move. Use this to keep frame times steady when scenes vary wildly in how many collisions they have.
+	///
+	/// Defaults to None (no limit).
+	pub iteration_time_budget : Option<Duration>,
+
+	/// Whether each step should measure how much every entity's kinetic energy changes during each part of it (see [PhysicsSystem::get_energy_drift]). i.e. for finding out why something slowly speeds up when nothing is pushing it.
+	///
+	/// Defaults to `false`, since it adds work to every step.
+	pub track_energy_drift : bool,
+
+	/// Shuffles the order that each step goes through the entities (and so pairs of them) in, using this seed. i.e. for checking that results don't depend on that order (see [crate::jitter::Jitter::shuffle_pairs]).
+	///
+	/// Defaults to None (the order they're stored in).
+	pub entity_order_seed : Option<u64>,
+
+	/// Everything tracked about energy drift so far.
+	energy_drift : EnergyDriftReport,
+
+	/// How much of the last `step()`'s `dt` wasn't simulated, because it ran out of iterations (or `iteration_time_budget`). Zero if it got through everything.
+	pub last_step_shortfall : f32,
+
+	/// How many pieces each step is split into when applying forces. Force generators are run once per piece (with that piece's `dt`), and see everything moved to where it'd be partway through the step.
+	///
+	/// Collisions are still only handled once per step, using the total movement from all of the pieces. So this is a cheap way to keep stiff forces (i.e. springs) from blowing up without shrinking the whole step. Zero is treated as one.
+	///
+	/// Defaults to 1.
+	pub force_substeps : u8,
 
 	/// A record of all of the collisions that happened last `step()`.
 	///
-	/// These will be ordered such that earlier collisions go first.
+	/// These are ordered such that earlier collisions go first. Collisions at the same time are ordered by their pair of entities, so the order never depends on the order that things were found in (or added to the system).
+	///
+	/// Unless `merge_collision_records` is turned off, there is at most one record per pair of entities.
 	pub collision_records : Vec<CollisionRecord>,
 
-	/// The minimum amount of energy needed to prevent an entity from being put to sleep.
+	/// How many collisions `collision_records` can hold, so it stays a bounded size even when huge piles of things are all touching. See [RecordLimit].
+	///
+	/// This is applied as collisions are found, so before they're merged (see `merge_collision_records`) or filtered (see `min_record_impulse`). The contact cache (see `contacts`) is built from the records, so it's missing any that were dropped.
+	///
+	/// Defaults to [RecordLimit::Unlimited].
+	pub record_limit : RecordLimit,
+
+	/// How many collisions last `step()` didn't fit in `collision_records` (see `record_limit`).
+	pub dropped_collision_records : usize,
+
+	/// Whether every pair of entities gets (at most) one record in `collision_records` per step. i.e. so something resting on the ground that's bumped several times in one step is only reported once.
+	///
+	/// The merged record is the pair's earliest collision (so its time, position, normal, etc), but with the largest impulse of any of them. The contact cache (see `contacts`) is still built from every collision.
+	///
+	/// Defaults to true.
+	pub merge_collision_records : bool,
+
+	/// The smallest impulse a collision can have and still be kept in `collision_records`. Anything weaker is still simulated (and still counts towards `contacts`), it just isn't recorded.
+	///
+	/// Raise this when there are lots of tiny contacts (i.e. things rolling around on the ground) that would just be thrown away.
+	///
+	/// Defaults to 0.0 (so everything is recorded).
+	pub min_record_impulse : f32,
+
+	/// The contact cache: how hard each pair of entities pushed on each other last `step()`.
+	///
+	/// There is one item per pair of entities that collided. Pairs that are asleep and resting on each other keep the contact they had when they fell asleep.
+	pub contacts : Vec<Contact>,
+
+	/// The minimum amount of energy from moving (i.e. not counting rotation) needed to prevent an entity from being put to sleep.
 	///
 	/// Defaults to 0.001
 	pub energy_sleep_threshold : f32,
-	/// The minimum amount of time that an entity needs to be below the energy threshold to be put to sleep.
+	/// The minimum amount of energy from rotating needed to prevent an entity from being put to sleep.
+	///
+	/// This is separate from `energy_sleep_threshold` as nothing slows down a ball spinning in place on the floor, so it can take a very long time to lose the last little bit of rotation.
+	///
+	/// Defaults to 0.01
+	pub angular_energy_sleep_threshold : f32,
+	/// The minimum amount of time that an entity needs to be below the energy thresholds to be put to sleep.
 	///
 	/// Defaults to 0.1.
 	pub sleep_time_threshold : f32,
 
+	/// How forces are turned into movement.
+	///
+	/// Defaults to [Integrator::SemiImplicitEuler].
+	pub integrator : Integrator,
+
+	/// How to pick between static and dynamic friction.
+	///
+	/// Defaults to [FrictionModel::Threshold].
+	pub friction_model : FrictionModel,
+	/// Whether each pair of entities in contact is sticking (true) or slipping (false). Only used by [FrictionModel::Coulomb].
+	///
+	/// Keyed by the pair's handles in sorted order. Pairs that stop touching are dropped at the end of each step.
+	friction_states : HashMap<(EntityHandle, EntityHandle), bool>,
+
+	/// How the restitution coefficient depends on how fast the two entities were coming together.
+	///
+	/// Defaults to [RestitutionModel::Constant].
+	pub restitution_model : RestitutionModel,
+
+	/// How close in time (in seconds) collisions need to be to be considered simultaneous.
+	///
+	/// Simultaneous collisions between the same two entities are resolved as a single contact at their average position.
+	/// Which keeps things like a box landing flat from picking up spin from whichever corner was (barely) found first.
+	///
+	/// Defaults to 0.0001.
+	pub simultaneous_collision_time : f32,
+
+	/// The slowest that two entities can be moving together and still bounce off of each other. Anything slower is treated as if the restitution coefficient were zero.
+	///
+	/// This keeps resting things from bouncing a tiny bit every step (from the speed they pick up from gravity in a single step). Something around 0.5 works well for Earth-like gravity at 60 steps per second.
+	///
+	/// Defaults to 0.0 (so everything bounces as much as its restitution coefficient says).
+	pub restitution_velocity_threshold : f32,
+
+	/// The slowest that two entities can be coming together at a collision without it being absorbed as a resting contact. Slower collisions are treated as perfectly inelastic (no matter the restitution coefficient), and the two are left resting on each other even if the impulse left them drifting apart a little.
+	///
+	/// Meant for low-gravity scenes, where `restitution_velocity_threshold` has to be low enough for slow things to still bounce, but then the tiny approach speeds near a resting contact get reflected over and over. Set this just above the speed things pick up from gravity in a single step.
+	///
+	/// Defaults to 0.0 (so nothing is absorbed).
+	pub resting_contact_threshold : f32,
+
+	/// How far apart (in distance) two entities can be when they're found to be about to collide, and still be treated as already touching. So their collision is handled right away, rather than first moving everything forward by the tiny bit of time until they'd actually touch.
+	///
+	/// Things resting on each other have their collisions found at slightly different (tiny) times every step. Without this each of those times can take up its own iteration. Pair this with the colliders' margins, so resting things don't need to get exactly to touching.
+	///
+	/// Defaults to 0.001.
+	pub contact_slop : f32,
+
+	/// The slowest that two entities can be coming together at a collision and still have it responded to. Collisions where they're approaching any slower (or are already moving apart) are ignored.
+	///
+	/// Defaults to 0.000001.
+	pub approach_velocity_threshold : f32,
+
+	/// The fastest that two entities can be moving apart (along the normal) after a collision and still be considered resting on each other. Entities that are resting on each other can go to sleep together, without needing to keep colliding.
+	///
+	/// Raising this helps stacks settle down (and go to sleep) sooner.
+	///
+	/// Defaults to 0.000001.
+	pub contact_velocity_threshold : f32,
+
+	/// The most that friction can take off of the sliding speed at a single collision, as a fraction of that speed. At `1.0`, friction can completely stop the sliding (but never reverse it).
+	///
+	/// Defaults to 1.0.
+	pub max_friction_percent : f32,
+
+	/// Where step() leaves a copy of every entity's orientation, when double buffering is enabled. See [PhysicsSystem::enable_double_buffering].
+	double_buffer : Option<DoubleBuffer>,
+	/// What step() reports each of its phases to, if anything. See [PhysicsSystem::set_profiler].
+	profiler : Option<Box<dyn Profiler>>,
+
+	/// The lists that step() fills in every call, kept between calls so they don't need to be allocated again.
+	scratch : StepScratch,
+
 	/// A place to store debugging info when things go wrong internally.
 	pub debug : Vec<String>,
 }
 
+/// Makes a deep copy (i.e. to simulate ahead and then throw the copy away). All handles stay valid in the copy.
+///
+/// The copy starts without double buffering (so it never publishes to the original's readers).
+///
+/// Force generators and contact modifiers are copied with their `clone_boxed()`. Any that can't be copied are skipped: see [UnaryForceGenerator::clone_boxed] and [ContactModifier::clone_boxed].
+impl Clone for PhysicsSystem {
+	fn clone(&self) -> PhysicsSystem {
+		PhysicsSystem {
+			entities: self.entities.clone(),
+			colliders : self.colliders.clone(),
+			unary_force_generators : self.unary_force_generators.clone(),
+			disabled_generators : self.disabled_generators.clone(),
+			generator_strengths : self.generator_strengths.clone(),
+			cloths : self.cloths.clone(),
+			particle_sets : self.particle_sets.clone(),
+			fluid_regions : self.fluid_regions.clone(),
+			fluid_events : self.fluid_events.clone(),
+			contact_modifiers : self.contact_modifiers.iter().filter_map(|(handle, modifier)| Some((*handle, modifier.clone_boxed()?))).collect(),
+			collider_motions : self.collider_motions.clone(),
+			material_pair_properties : self.material_pair_properties.clone(),
+			entity_pair_properties : self.entity_pair_properties.clone(),
+			attachments : self.attachments.clone(),
+			collider_break_impulses : self.collider_break_impulses.clone(),
+			break_events : self.break_events.clone(),
+			expired_entities : self.expired_entities.clone(),
+			iteration_max : self.iteration_max,
+			iteration_time_budget : self.iteration_time_budget,
+			last_step_shortfall : self.last_step_shortfall,
+			track_energy_drift : self.track_energy_drift,
+			entity_order_seed : self.entity_order_seed,
+			energy_drift : self.energy_drift.clone(),
+			force_substeps : self.force_substeps,
+			collision_records : self.collision_records.clone(),
+			record_limit : self.record_limit,
+			dropped_collision_records : self.dropped_collision_records,
+			merge_collision_records : self.merge_collision_records,
+			min_record_impulse : self.min_record_impulse,
+			contacts : self.contacts.clone(),
+			energy_sleep_threshold : self.energy_sleep_threshold,
+			angular_energy_sleep_threshold : self.angular_energy_sleep_threshold,
+			sleep_time_threshold : self.sleep_time_threshold,
+			simultaneous_collision_time : self.simultaneous_collision_time,
+			restitution_velocity_threshold : self.restitution_velocity_threshold,
+			resting_contact_threshold : self.resting_contact_threshold,
+			contact_slop : self.contact_slop,
+			approach_velocity_threshold : self.approach_velocity_threshold,
+			contact_velocity_threshold : self.contact_velocity_threshold,
+			max_friction_percent : self.max_friction_percent,
+			integrator : self.integrator,
+			friction_model : self.friction_model,
+			friction_states : self.friction_states.clone(),
+			restitution_model : self.restitution_model.clone(),
+			double_buffer : None, // Copies shouldn't publish to the original's readers.
+			profiler : None, // Nor report to the original's profiler.
+			scratch : StepScratch::default(),
+
+			debug: self.debug.clone(),
+		}
+	}
+}
+
+/// Requires `T` to be safe to share between threads.
+fn assert_send_sync<T : Send + Sync>() {}
+
+// The system must stay `Send + Sync` (see its docs), so this fails to compile if anything stored in it isn't.
+const _ : fn() = assert_send_sync::<PhysicsSystem>;
+
+/// A collision found during step() that hasn't been responded to yet.
+#[derive(Debug)]
+struct PendingCollision {
+	/// The collision itself.
+	collision : Collision,
+	/// The index of the first entity's EntityStepInfo.
+	first_info_index : usize,
+	/// The index of the second entity's EntityStepInfo.
+	second_info_index : usize,
+	/// The combined restitution coefficient.
+	restitution_coefficient : f32,
+	/// The combined static friction coefficient.
+	static_friction_coefficient : f32,
+	/// The combined dynamic friction coefficient.
+	dynamic_friction_coefficient : f32,
+	/// The combined friction threshold.
+	friction_threshold : f32,
+	/// Any anisotropic friction from the two colliders (one slot per collider). These have their direction in world space.
+	anisotropic_frictions : [Option<AnisotropicFriction>; 2],
+	/// How fast the first collider is moving relative to the second because of [PhysicsSystem::move_collider] (on top of their entities' velocities).
+	surface_velocity : Vec3,
+	/// The two colliders' surface tags (for the collision record).
+	surface_tags : [u32; 2],
+	/// The two colliders.
+	colliders : [ColliderHandle; 2],
+}
+
+/// An entity riding along on another one. See [PhysicsSystem::attach_entity].
+#[derive(Debug, Clone)]
+struct Attachment {
+	/// The entity it's attached to.
+	parent : EntityHandle,
+	/// How to get from the attached entity's local space into the parent's local space.
+	offset : Isometry,
+	/// The mass that was moved onto the parent (from the attached entity's `own_mass`).
+	own_mass : f32,
+	/// How much impulse the attached entity's colliders can take in a single step before it's knocked loose. See [PhysicsSystem::set_attachment_break_impulse].
+	break_impulse : Option<f32>,
+	/// The attached entity's colliders, as they were before being moved onto the parent.
+	colliders : Vec<(ColliderHandle, ColliderWrapper)>,
+}
+
+/// The lists that step() needs every call. Each is only used during a single step() call; they're just kept between calls to reuse their memory.
+#[derive(Debug, Default)]
+struct StepScratch {
+	entity_handles : Vec<EntityHandle>,
+	generator_handles : Vec<UnaryForceGeneratorHandle>,
+	entity_info : Vec<EntityStepInfo>,
+	bounds : Vec<(Vec3, Vec3, f32)>,
+	bound_batches : Vec<SphereLanes>,
+	lone_shapes : Vec<Option<LoneShape>>,
+	spheres : Vec<(Vec3, Vec3, f32)>,
+	sphere_batches : Vec<SphereLanes>,
+	candidates : Vec<PendingCollision>,
+	simultaneous : Vec<PendingCollision>,
+	known : Vec<PendingCollision>,
+	contacts : Vec<Contact>,
+	/// Where each pair of entities' record ended up, while merging collision records.
+	record_pairs : HashMap<(EntityHandle, EntityHandle), usize>,
+	/// Emptied neighbor sets, to hand out to the next step()'s `entity_info`.
+	neighbor_sets : Vec<HashSet<EntityHandle>>,
+	collision : CollisionScratch,
+}
+
+/// The only collider an entity has, when it's one that the batched checks handle (see [crate::batch_collision]). Moved into world space for the current iteration.
+#[derive(Debug)]
+enum LoneShape {
+	/// A sphere. Where it is (and how it moves) is also in the entity's lane of the sphere batches.
+	Sphere { radius : f32, center : Vec3, movement : Vec3 },
+	/// A plane.
+	Plane { position : Vec3, normal : Vec3, movement : Vec3 },
+}
+
 #[derive(Debug)]
 struct EntityStepInfo {
 	/// The entity handle.
 	handle : EntityHandle,
 	/// The planned linear motion for the entity.
 	linear_movement : Vec3,
+	/// The planned linear motion for the entity, in fixed-point. This is what the entity actually moves by; `linear_movement` is only used to find collisions.
+	#[cfg(feature = "fixed-point")]
+	fixed_movement : FixedVec3,
 	/// The planned angular motion for the entity.
 	angular_movement : Vec3,
 	/// All of the entities that have been collided with recently.
 	neighbors : HashSet<EntityHandle>,
+	/// Whether this entity's motion changed during the last iteration (so any collisions with it need to be looked for again).
+	changed : bool,
+	/// The entity's energy when it was last measured (only when tracking energy drift).
+	energy : EnergyChange,
+	/// How much the entity's energy changed during each part of the step (only when tracking energy drift).
+	drift : EnergyDrift,
+	/// The total impulse on the entity so far this step.
+	impulse : Vec3,
+	/// The total angular impulse (about the center of mass) on the entity so far this step.
+	angular_impulse : Vec3,
 }
 
 impl PhysicsSystem {
 	/// Creates a new instance.
 	pub fn new() -> PhysicsSystem {
 		PhysicsSystem {
-			entities: RefCell::new(Arena::new()),
-			colliders : RefCell::new(Arena::new()),
-			unary_force_generators : RefCell::new(Arena::new()),
+			entities: LockCell::new(Arena::new()),
+			colliders : LockCell::new(Arena::new()),
+			unary_force_generators : LockCell::new(Arena::new()),
+			disabled_generators : HashSet::new(),
+			generator_strengths : HashMap::new(),
+			cloths : Arena::new(),
+			particle_sets : Arena::new(),
+			fluid_regions : Arena::new(),
+			fluid_events : Vec::new(),
+			contact_modifiers : HashMap::new(),
+			collider_motions : HashMap::new(),
+			material_pair_properties : HashMap::new(),
+			entity_pair_properties : HashMap::new(),
+			attachments : HashMap::new(),
+			collider_break_impulses : HashMap::new(),
+			break_events : Vec::new(),
+			expired_entities : Vec::new(),
 			iteration_max : 5,
+			iteration_time_budget : None,
+			last_step_shortfall : 0.0,
+			track_energy_drift : false,
+			entity_order_seed : None,
+			energy_drift : EnergyDriftReport::default(),
+			force_substeps : 1,
 			collision_records : Vec::new(),
+			record_limit : RecordLimit::Unlimited,
+			dropped_collision_records : 0,
+			merge_collision_records : true,
+			min_record_impulse : 0.0,
+			contacts : Vec::new(),
 			energy_sleep_threshold : 0.001,
+			angular_energy_sleep_threshold : 0.01,
 			sleep_time_threshold : 0.1,
+			simultaneous_collision_time : 0.0001,
+			restitution_velocity_threshold : 0.0,
+			resting_contact_threshold : 0.0,
+			contact_slop : 0.001,
+			approach_velocity_threshold : EPSILON,
+			contact_velocity_threshold : EPSILON,
+			max_friction_percent : 1.0,
+			integrator : Integrator::SemiImplicitEuler,
+			friction_model : FrictionModel::Threshold,
+			friction_states : HashMap::new(),
+			restitution_model : RestitutionModel::Constant,
+			double_buffer : None,
+			profiler : None,
+			scratch : StepScratch::default(),
 
 			debug: Vec::new(),
 		}
@@ -92,8 +491,21 @@ impl PhysicsSystem {
 	///
 	/// Returns if anything changed (i.e. if the entity existed and was removed).
 	pub fn remove_entity(&mut self, handle : EntityHandle) -> bool {
+		// Anything attached to it is let go first, along with the entity itself (so its colliders come back to it before they're removed).
+		let mut children : Vec<EntityHandle> = self.attachments.iter().filter(|(_, attachment)| attachment.parent == handle).map(|(child, _)| *child).collect();
+		children.sort();
+		for child in children {
+			self.detach_entity(child).unwrap();
+		}
+		if self.attachments.contains_key(&handle) {
+			self.detach_entity(handle).unwrap();
+		}
 		let removed = self.entities.borrow_mut().remove(handle);
 		if let Some(entity) = removed {
+			self.entity_pair_properties.retain(|(first, second), _| *first != handle && *second != handle);
+			for (_, region) in self.fluid_regions.iter_mut() {
+				region.forget(handle);
+			}
 			// Also remove all associated colliders.
 			for collider in entity.colliders {
 				self.remove_collider(collider);
@@ -102,6 +514,21 @@ impl PhysicsSystem {
 		} else { false }
 	}
 
+	/// Finds an entity with the given [Entity::name]. If more than one has it, then the one with the lowest handle is returned.
+	pub fn find_entity_by_name(&self, name : &str) -> Option<EntityHandle> {
+		self.entities.borrow().iter().find(|(_, entity)| entity.name.as_deref() == Some(name)).map(|(handle, _)| handle)
+	}
+
+	/// Checks whether a handle refers to an entity that's still in the system (i.e. it wasn't removed).
+	pub fn is_valid_entity(&self, handle : EntityHandle) -> bool {
+		self.entities.borrow().contains(handle)
+	}
+
+	/// Checks whether a handle refers to a collider that's still in the system (i.e. it wasn't removed).
+	pub fn is_valid_collider(&self, handle : ColliderHandle) -> bool {
+		self.colliders.borrow().contains(handle)
+	}
+
 	/// Gets an entity's public interface.
 	///
 	/// These values are all copies of the internal entity.
@@ -109,14 +536,33 @@ impl PhysicsSystem {
 		self.entities.borrow().get(handle).and_then(|internal| Some(internal.make_pub()))
 	}
 
+	/// Gets the exact (fixed-point) position of an entity's center of mass and its velocity. Only available with the `fixed-point` feature.
+	///
+	/// Both are tracked more precisely than [Entity::position] and [Entity::velocity] can show. Lockstep servers can compare (or hash) these to check they're all still in sync.
+	#[cfg(feature = "fixed-point")]
+	pub fn get_fixed_state(&self, handle : EntityHandle) -> Option<(FixedVec3, FixedVec3)> {
+		self.entities.borrow().get(handle).map(|internal| internal.fixed_state())
+	}
+
 	/// Updates an entity with the given values.
 	///
 	/// This does NOT update the list of linked/attached colliders. Must use link_collider() for that.
+	///
+	/// Wakes the entity up if anything changed (see [WakePolicy::IfChanged]).
 	pub fn update_entity(&mut self, handle : EntityHandle, source : Entity) -> Result<(),()> {
+		self.update_entity_with(handle, source, WakePolicy::IfChanged)
+	}
+
+	/// Updates an entity with the given values, with control over whether it's woken up.
+	pub fn update_entity_with(&mut self, handle : EntityHandle, source : Entity, wake : WakePolicy) -> Result<(),()> {
 		let mut entity_woke_up = false;
 		let result = self.entities.borrow_mut().get_mut(handle).ok_or(()).and_then(|internal| {
-			if let Ok(woke_up) = internal.update_from(source) {
-				entity_woke_up = woke_up;
+			if let Ok(changed) = internal.update_from(source) {
+				entity_woke_up = match wake {
+					WakePolicy::IfChanged => changed,
+					WakePolicy::Always => true,
+					WakePolicy::Never => false,
+				};
 				internal.recalculate_mass(&*self.colliders.borrow());
 				Ok(())
 			} else { Err(()) }
@@ -128,58 +574,378 @@ impl PhysicsSystem {
 		result
 	}
 
-	/// Adds a collider to the system.
-	pub fn add_collider(&mut self, source : ColliderWrapper) -> Result<ColliderHandle, ()> {
-		match source {
-			ColliderWrapper::Null(source) => {
-				match InternalNullCollider::new_from(&source) {
-					Ok(internal) => {
-						Ok(self.colliders.borrow_mut().insert(internal))
-					},
-					Err(a) => Err(a)
-				}
+	/// Finds every entity that fell asleep or woke up since the last call (or since it was added), i.e. so a renderer can move sleeping entities to a static batch. Call it after every step() to get each step's changes.
+	///
+	/// Only the overall change is reported, so an entity that woke up and fell back asleep in between calls isn't included.
+	pub fn take_sleep_changes(&mut self) -> Vec<SleepChange> {
+		let mut changes = Vec::new();
+		for (handle, entity) in self.entities.borrow_mut().iter_mut() {
+			if entity.asleep != entity.reported_asleep {
+				entity.reported_asleep = entity.asleep;
+				changes.push(SleepChange { entity: handle, asleep: entity.asleep });
 			}
-			ColliderWrapper::Sphere(source) => {
-				match InternalSphereCollider::new_from(&source) {
-					Ok(internal) => {
-						Ok(self.colliders.borrow_mut().insert(internal))
-					},
-					Err(a) => Err(a)
+		}
+		changes
+	}
+
+	/// Enables or disables (i.e. freezes) an entity.
+	///
+	/// A disabled entity is completely ignored by step(): it isn't integrated, nothing collides with it, and it never goes to sleep or wakes up.
+	/// All of its state (orientation, velocities, colliders, etc) is kept as-is so it can be re-enabled later.
+	///
+	/// Disabling an entity wakes up anything that was resting on it (so they don't float in mid-air).
+	pub fn set_entity_enabled(&mut self, handle : EntityHandle, enabled : bool) -> Result<(),()> {
+		let was_enabled = self.entities.borrow().get(handle).ok_or(())?.enabled;
+		if was_enabled == enabled {
+			return Ok(());
+		}
+		// Either way, the entity's neighbor links are stale. So clear them out (waking up everything it was touching).
+		InternalEntity::wake_up(handle, &mut self.entities.borrow_mut(), &mut self.debug);
+		let mut entities = self.entities.borrow_mut();
+		let entity = entities.get_mut(handle).unwrap();
+		entity.enabled = enabled;
+		entity.step_path.clear(); // The entity won't be a part of the next step, so don't leave the old path around.
+		Ok(())
+	}
+
+	/// Enables or disables a single collider, without unlinking it from its entity.
+	///
+	/// A disabled collider is skipped by step() (nothing collides with it) and by queries like [PhysicsSystem::cast_ray], but still adds its mass to its entity. So toggling one (i.e. a weapon's hitbox while it's put away) never moves the entity's center of mass.
+	///
+	/// Wakes up the collider's entity and anything that was resting on it.
+	pub fn set_collider_enabled(&mut self, handle : ColliderHandle, enabled : bool) -> Result<(),()> {
+		let entity_handle = {
+			let mut colliders = self.colliders.borrow_mut();
+			let collider = colliders.get_mut(handle).ok_or(())?; // TODO: An error type.
+			if collider.is_enabled() == enabled {
+				return Ok(());
+			}
+			collider.set_enabled(enabled);
+			collider.get_entity()
+		};
+		if let Some(entity_handle) = entity_handle {
+			InternalEntity::wake_up(entity_handle, &mut self.entities.borrow_mut(), &mut self.debug);
+		}
+		Ok(())
+	}
+
+	/// Checks whether a collider is enabled (see [PhysicsSystem::set_collider_enabled]). Returns None if there's no such collider.
+	pub fn is_collider_enabled(&self, handle : ColliderHandle) -> Option<bool> {
+		self.colliders.borrow().get(handle).map(|collider| collider.is_enabled())
+	}
+
+	/// Instantly moves an entity to a new position (of its center of mass) and rotation (as a scaled axis, like [Entity::rotation]). Its velocities are kept.
+	///
+	/// Unlike just updating the entity's position, this forgets everything the entity was touching: everything resting on (or under) it is woken up, and any contacts involving it are dropped.
+	///
+	/// If `check_overlap` is set, then this first checks whether the entity's colliders would overlap any other (enabled) entity's colliders at the new spot. If they would, nothing is changed and an error is returned.
+	pub fn teleport_entity(&mut self, handle : EntityHandle, position : impl Into<Vec3>, rotation : impl Into<Vec3>, check_overlap : bool) -> Result<(),()> {
+		let mut destination = self.entities.borrow().get(handle).ok_or(())?.orientation;
+		destination.position = position.into();
+		destination.rotation = Quat::from_scaled_axis(rotation.into());
+		if check_overlap {
+			let entities = self.entities.borrow();
+			let colliders = self.colliders.borrow();
+			for collider_handle in entities.get(handle).unwrap().colliders.iter() {
+				let collider = colliders.get(*collider_handle).unwrap();
+				if !collider.collides() { continue; }
+				for (other_handle, other) in entities.iter() {
+					if other_handle == handle || !other.enabled { continue; }
+					for other_collider_handle in other.colliders.iter() {
+						let other_collider = colliders.get(*other_collider_handle).unwrap();
+						if !other_collider.collides() { continue; }
+						if collide(collider, &destination, &destination, other_collider, &other.orientation, &other.orientation).is_some() {
+							return Err(()); // TODO: An error type.
+						}
+					}
 				}
 			}
-			ColliderWrapper::Plane(source) => {
-				match InternalPlaneCollider::new_from(&source) {
-					Ok(internal) => {
-						Ok(self.colliders.borrow_mut().insert(internal))
-					},
-					Err(a) => Err(a)
+		}
+		// Forget everything it was touching (waking those things up, as they may have been resting on it).
+		InternalEntity::wake_up(handle, &mut self.entities.borrow_mut(), &mut self.debug);
+		self.contacts.retain(|contact| contact.first_entity != handle && contact.second_entity != handle);
+		self.friction_states.retain(|(first, second), _| *first != handle && *second != handle);
+		let mut entities = self.entities.borrow_mut();
+		let entity = entities.get_mut(handle).unwrap();
+		entity.orientation = destination;
+		entity.step_path.clear(); // Didn't get there by moving, so don't leave the old path around.
+		entity.falling_asleep = false;
+		entity.falling_asleep_time = 0.0;
+		Ok(())
+	}
+
+	/// Gets where an entity was at a given time during the last step() call.
+	///
+	/// The time is relative to the start of that step (like [CollisionRecord::time]), so this can be used to find where each entity in a collision was when the collision happened.
+	///
+	/// Returns None if the entity doesn't exist. Entities that weren't a part of the last step (i.e. added since then) just give their current orientation.
+	pub fn orientation_at(&self, handle : EntityHandle, time : f32) -> Option<Orientation> {
+		self.entities.borrow().get(handle).map(|entity| entity.orientation_at(time))
+	}
+
+	/// Gets every entity that moved during the last step() (or was teleported or added before it), along with where it is now. Sorted by handle.
+	///
+	/// Asleep and resting entities are left out, so syncing transforms (i.e. to a renderer) after each step only has to touch these. Entities that have been removed are left out too (see [PhysicsSystem::take_expired_entities] for the ones step() removed).
+	pub fn awake_entities(&self) -> Vec<(EntityHandle, Orientation)> {
+		let mut moved : Vec<(EntityHandle, Orientation)> = self.entities.borrow().iter()
+			.filter(|(_, entity)| entity.moved)
+			.map(|(handle, entity)| (handle, entity.orientation))
+			.collect();
+		moved.sort_by_key(|(handle, _)| *handle);
+		moved
+	}
+
+	/// Gets where an entity was at the start of the last step() call. Along with where it is now (see [Entity::get_last_orientation]), that's both ends of its last move, i.e. for motion blur or velocity buffers (use [Orientation::lerp] for the points in between).
+	///
+	/// Returns None if the entity doesn't exist. Entities that weren't a part of the last step (or were teleported since) just give their current orientation.
+	pub fn get_previous_orientation(&self, handle : EntityHandle) -> Option<Orientation> {
+		self.entities.borrow().get(handle).map(|entity| entity.step_path.first().map_or(entity.orientation, |(_, orientation)| *orientation))
+	}
+
+	/// Applies an impulse at a (world) position to an entity, waking it up (if it was asleep).
+	///
+	/// This is how outside code (i.e. [crate::Vehicle]) should push on entities between step() calls.
+	pub fn apply_impulse(&mut self, handle : EntityHandle, position : impl Into<Vec3>, impulse : impl Into<Vec3>) -> Result<(),()> {
+		self.apply_impulses(handle, &[(position.into(), impulse.into())])
+	}
+
+	/// Applies several impulses (each as a world position and the impulse there) to an entity all at once, waking it up (if it was asleep).
+	fn apply_impulses(&mut self, handle : EntityHandle, impulses : &[(Vec3, Vec3)]) -> Result<(),()> {
+		if !self.entities.borrow().get(handle).ok_or(())?.is_dynamic() {
+			return Ok(()); // Static and kinematic entities can't be pushed, so nothing would change anyways.
+		}
+		InternalEntity::wake_up(handle, &mut self.entities.borrow_mut(), &mut self.debug);
+		let mut entities = self.entities.borrow_mut();
+		let entity = entities.get_mut(handle).unwrap();
+		// Something is actively pushing on it, so restart falling asleep (otherwise it'd just be put right back to sleep).
+		entity.falling_asleep = false;
+		entity.falling_asleep_time = 0.0;
+		for (position, impulse) in impulses {
+			entity.apply_impulse(position, impulse);
+		}
+		Ok(())
+	}
+
+	/// Pushes an entity directly away from `center` (i.e. from an explosion) with an impulse of the given magnitude, waking it up (if it was asleep).
+	///
+	/// Normally the whole impulse pushes on the entity's center of mass, so it doesn't spin the entity. With `split_across_colliders`, the impulse is instead split up between the entity's colliders (by their mass, or evenly if none of them have any). Each part pushes on the point of its collider that's closest to `center` (see [PhysicsSystem::closest_point]), directly away from `center`. That gives compound entities the spin they'd get from their near side taking the blast.
+	///
+	/// Fails if the entity doesn't exist.
+	pub fn apply_radial_impulse(&mut self, handle : EntityHandle, center : impl Into<Vec3>, magnitude : f32, split_across_colliders : bool) -> Result<(),()> {
+		let center = &center.into();
+		let mut impulses = Vec::new();
+		{
+			let entities = self.entities.borrow();
+			let colliders = self.colliders.borrow();
+			let entity = entities.get(handle).ok_or(())?; // TODO: An error type.
+			// Push away from the center, falling back on pushing from the center of mass when the point is right on top of it.
+			let away = |position : &Vec3| {
+				let direction = [position - center, entity.orientation.position - center].iter().copied().find(|direction| EPSILON < direction.magnitude());
+				direction.map_or(Vec3::zeros(), |direction| direction.normalize())
+			};
+			let solid : Vec<&Box<dyn InternalCollider>> = entity.colliders.iter()
+				.map(|collider_handle| colliders.get(*collider_handle).unwrap())
+				.filter(|collider| !collider.is_query_only())
+				.collect();
+			if split_across_colliders && !solid.is_empty() {
+				let total_mass : f32 = solid.iter().map(|collider| collider.get_mass()).sum();
+				for collider in solid.iter() {
+					let share = if EPSILON < total_mass { collider.get_mass() / total_mass } else { 1.0 / solid.len() as f32 };
+					let position = closest_point_on(collider, &entity.orientation, center)
+						.unwrap_or_else(|| entity.orientation.position_into_world(&collider.get_local_center_of_mass()));
+					impulses.push((position, away(&position) * magnitude * share));
 				}
+			} else {
+				let position = entity.orientation.position;
+				impulses.push((position, away(&position) * magnitude));
 			}
-			ColliderWrapper::Mesh(source) => {
-				match InternalMeshCollider::new_from(&source) {
-					Ok(internal) => {
-						Ok(self.colliders.borrow_mut().insert(internal))
-					},
-					Err(a) => Err(a),
+		}
+		self.apply_impulses(handle, &impulses)
+	}
+
+	/// Finds the point on (or in) a collider that's closest to the given (world) point. Colliders are treated as solid, so points inside of them are their own closest point. Meshes are treated as their convex hull.
+	///
+	/// Returns None if the collider doesn't exist, isn't linked to an entity, or has no shape (i.e. is a [crate::NullCollider]).
+	pub fn closest_point(&self, handle : ColliderHandle, point : &Vec3) -> Option<Vec3> {
+		let mut colliders = self.colliders.borrow_mut();
+		let entity_handle = colliders.get_mut(handle)?.get_entity()?;
+		let entities = self.entities.borrow();
+		closest_point_on(colliders.get(handle).unwrap(), &entities.get(entity_handle)?.orientation, point)
+	}
+
+	/// Finds the first collider that a ray hits.
+	///
+	/// The ray starts at `origin`, and goes `max_distance` along `direction` (which doesn't need to be normalized). Disabled entities and the entity passed in as `ignore` (if any) are skipped.
+	///
+	/// If a mesh is hit, the hit's `face` says where on the mesh.
+	pub fn cast_ray(&self, origin : &Vec3, direction : &Vec3, max_distance : f32, ignore : Option<EntityHandle>) -> Option<RayHit> {
+		self.cast_ray_masked(origin, direction, max_distance, ignore, u32::MAX)
+	}
+
+	/// Like [PhysicsSystem::cast_ray], but also skips every entity whose [Entity::query_groups] shares no bits with `mask`.
+	pub fn cast_ray_masked(&self, origin : &Vec3, direction : &Vec3, max_distance : f32, ignore : Option<EntityHandle>, mask : u32) -> Option<RayHit> {
+		let end = origin + direction.normalize() * max_distance;
+		self.cast_against_all(max_distance, ignore, mask, |collider, orientation| cast_ray_against(collider, orientation, origin, &end))
+	}
+
+	/// Finds the first collider that a moving sphere hits.
+	///
+	/// The sphere's center starts at `origin`, and goes `max_distance` along `direction` (which doesn't need to be normalized). The hit's `position` is where the sphere touched the collider, and its `distance` is how far the center moved to get there.
+	///
+	/// Disabled entities and the entity passed in as `ignore` (if any) are skipped.
+	pub fn cast_sphere(&self, origin : &Vec3, direction : &Vec3, max_distance : f32, radius : f32, ignore : Option<EntityHandle>) -> Option<RayHit> {
+		self.cast_sphere_masked(origin, direction, max_distance, radius, ignore, u32::MAX)
+	}
+
+	/// Like [PhysicsSystem::cast_sphere], but also skips every entity whose [Entity::query_groups] shares no bits with `mask`.
+	pub fn cast_sphere_masked(&self, origin : &Vec3, direction : &Vec3, max_distance : f32, radius : f32, ignore : Option<EntityHandle>, mask : u32) -> Option<RayHit> {
+		let sphere : Box<dyn InternalCollider> = Box::new(InternalSphereCollider::new_for_query(radius));
+		let start = Orientation::new(*origin, Vec3::zeros(), Vec3::zeros());
+		let end = Orientation::new(origin + direction.normalize() * max_distance, Vec3::zeros(), Vec3::zeros());
+		self.cast_against_all(max_distance, ignore, mask, |collider, orientation| {
+			let mut collision = collide(&sphere, &start, &end, collider, orientation, orientation)?;
+			collision.normal *= -1.0; // The normal points off of the sphere, but should point off of the thing that was hit.
+			Some((collision, None))
+		})
+	}
+
+	/// Finds where to put a camera so it doesn't clip into anything: sweeps a sphere (of the camera's `radius`) from the pivot (`from`) towards the desired camera position (`to`), and stops just short of the first thing in the way. Only entities whose [Entity::query_groups] share a bit with `mask` can block it.
+	///
+	/// Sweeping a sphere covers the whole capsule between the two points, so thin things between the pivot and the camera still block it.
+	pub fn camera_clip(&self, from : &Vec3, to : &Vec3, radius : f32, mask : u32) -> CameraClip {
+		let offset = to - from;
+		let max_distance = offset.magnitude();
+		if max_distance < EPSILON {
+			return CameraClip { position: *to, hit: None };
+		}
+		match self.cast_sphere_masked(from, &offset, max_distance, radius, None, mask) {
+			Some(hit) => CameraClip {
+				// Back off a little, so the camera's sphere isn't left exactly touching what's in the way.
+				position: from + offset * ((hit.distance - SURFACE_OFFSET).max(0.0) / max_distance),
+				hit: Some(hit),
+			},
+			None => CameraClip { position: *to, hit: None },
+		}
+	}
+
+	/// Predicts where something would go if it was thrown from `position` with `velocity` and then only accelerated by `acceleration` (i.e. gravity), without changing anything in the system.
+	///
+	/// Everything else is treated as staying exactly where it is now, so this only finds hits against the current (mostly static) geometry. The path is sampled every `sample_dt` seconds (up to `duration`), and stops at the first hit.
+	///
+	/// Disabled entities are skipped. Fails if `sample_dt` isn't positive, or if predicting a missing entity.
+	pub fn predict(&self, shape : PredictedShape, position : &Vec3, velocity : &Vec3, acceleration : &Vec3, duration : f32, sample_dt : f32) -> Result<Prediction, ()> {
+		if sample_dt < EPSILON {
+			return Err(()); // TODO: An error type.
+		}
+		let entities = self.entities.borrow();
+		let colliders = self.colliders.borrow();
+		let query_sphere : Box<dyn InternalCollider>;
+		let (mut orientation, moving, skip) = match shape {
+			PredictedShape::Entity(handle) => {
+				let entity = entities.get(handle).ok_or(())?;
+				(entity.orientation, entity.colliders.iter().map(|collider_handle| colliders.get(*collider_handle).unwrap()).filter(|collider| collider.collides()).collect(), Some(handle))
+			},
+			PredictedShape::Sphere(radius) => {
+				query_sphere = Box::new(InternalSphereCollider::new_for_query(radius));
+				(Orientation::new(*position, Vec3::zeros(), Vec3::zeros()), vec![&query_sphere], None)
+			},
+		};
+		orientation.position = *position;
+		let mut velocity = *velocity;
+		let mut positions = vec![*position];
+		let mut time = 0.0;
+		let mut distance = 0.0;
+		while time < duration {
+			let dt = sample_dt.min(duration - time);
+			let mut next = orientation;
+			next.position += velocity * dt + acceleration * (0.5 * dt * dt);
+			// Find the earliest hit over this sample (treating the movement as a straight line).
+			let mut earliest : Option<(Collision, EntityHandle, ColliderHandle)> = None;
+			for collider in moving.iter() {
+				for (other_handle, other) in entities.iter() {
+					if !other.enabled || Some(other_handle) == skip { continue; }
+					for other_collider_handle in other.colliders.iter() {
+						let other_collider = colliders.get(*other_collider_handle).unwrap();
+						if !other_collider.collides() { continue; }
+						if let Some(collision) = collide(collider, &orientation, &next, other_collider, &other.orientation, &other.orientation) {
+							if earliest.as_ref().is_none_or(|(current, _, _)| collision.times.min() < current.times.min()) {
+								earliest = Some((collision, other_handle, *other_collider_handle));
+							}
+						}
+					}
 				}
 			}
-			ColliderWrapper::AlignedBox(source) => {
-				match InternalAlignedBoxCollider::new_from(&source) {
-					Ok(internal) => {
-						Ok(self.colliders.borrow_mut().insert(internal))
-					},
-					Err(a) => Err(a),
+			let movement = next.position - orientation.position;
+			if let Some((collision, entity, collider)) = earliest {
+				let fraction = collision.times.min();
+				positions.push(orientation.position + movement * fraction);
+				return Ok(Prediction {
+					positions,
+					hit: Some((time + dt * fraction, RayHit {
+						entity,
+						collider,
+						position: collision.position,
+						normal: -collision.normal, // The normal points off of the moving shape, but should point off of the thing that was hit.
+						distance: distance + movement.magnitude() * fraction,
+						face: None,
+					})),
+				});
+			}
+			distance += movement.magnitude();
+			velocity += acceleration * dt;
+			time += dt;
+			orientation = next;
+			positions.push(orientation.position);
+		}
+		Ok(Prediction { positions, hit: None })
+	}
+
+	/// Runs a cast against every collider (of entities in the `mask`), keeping the earliest hit. The cast must return a collision with its normal pointing back at the cast, and its time being the fraction of `max_distance` moved before the hit.
+	fn cast_against_all<F>(&self, max_distance : f32, ignore : Option<EntityHandle>, mask : u32, cast : F) -> Option<RayHit>
+		where F : Fn(&Box<dyn InternalCollider>, &Orientation) -> Option<(Collision, Option<MeshFaceHit>)> {
+		let entities = self.entities.borrow();
+		let colliders = self.colliders.borrow();
+		let mut closest : Option<RayHit> = None;
+		for (entity_handle, entity) in entities.iter() {
+			if !entity.enabled || Some(entity_handle) == ignore || 0 == entity.query_groups & mask { continue; }
+			for collider_handle in &entity.colliders {
+				let collider = colliders.get(*collider_handle).unwrap();
+				if !collider.is_enabled() { continue; }
+				if let Some((collision, face)) = cast(collider, &entity.orientation) {
+					let distance = collision.times.min() * max_distance;
+					if closest.as_ref().is_none_or(|hit| distance < hit.distance) {
+						closest = Some(RayHit {
+							entity: entity_handle,
+							collider: *collider_handle,
+							position: collision.position,
+							normal: collision.normal,
+							distance,
+							face,
+						});
+					}
 				}
 			}
 		}
+		closest
+	}
+
+	/// Adds a collider to the system.
+	pub fn add_collider(&mut self, source : ColliderWrapper) -> Result<ColliderHandle, ()> {
+		let internal = source.make_internal()?;
+		Ok(self.colliders.borrow_mut().insert(internal))
 	}
 
 	/// Removes a collider.
 	pub fn remove_collider(&mut self, handle : ColliderHandle) {
-		if let Some(mut remainder) = self.colliders.borrow_mut().remove(handle) {
+		self.contact_modifiers.remove(&handle);
+		self.collider_break_impulses.remove(&handle);
+		self.collider_motions.remove(&handle);
+		let removed = self.colliders.borrow_mut().remove(handle);
+		if let Some(mut remainder) = removed {
 			// Force the associated entity to update (if there is one).
 			if let Some(entity_handle) = remainder.get_entity() {
 				if let Some(entity) = self.entities.borrow_mut().get_mut(entity_handle) {
+					entity.colliders.remove(&handle);
 					entity.recalculate_mass(&*self.colliders.borrow());
 				}
 			}
@@ -207,6 +973,9 @@ impl PhysicsSystem {
 				ColliderType::ALIGNED_BOX => {
 					Some(ColliderWrapper::AlignedBox(collider.downcast_ref::<InternalAlignedBoxCollider>().unwrap().make_pub()))
 				}
+				ColliderType::TRI_MESH => {
+					Some(ColliderWrapper::TriMesh(collider.downcast_ref::<InternalTriMeshCollider>().unwrap().make_pub()))
+				}
 			}
 		} else { None }
 	}
@@ -261,6 +1030,13 @@ impl PhysicsSystem {
 					return Err(());
 				}
 			}
+			ColliderWrapper::TriMesh(typed_source) => {
+				if let Some(typed_dest) = collider.downcast_mut::<InternalTriMeshCollider>() {
+					typed_dest.update_from(&typed_source)
+				} else {
+					return Err(());
+				}
+			}
 		};
 		// Then, because mass might've changed, try to update the associated entity (if it exists).
 		if let Some(entity_handle) = entity_handle_option {
@@ -271,6 +1047,53 @@ impl PhysicsSystem {
 		result
 	}
 
+	/// Moves a collider to a new place in its entity's local space (i.e. a sphere's center), for animating it (like a piston head in a machine) between steps.
+	///
+	/// Unlike [PhysicsSystem::update_collider], this doesn't immediately recalculate the entity's mass (that's done at the start of the next step) nor wake anything up. And the next step sweeps the collider from where it was to where it is now (instead of it just teleporting there), pushing anything in its way.
+	///
+	/// Anything asleep against the entity stays asleep (and so won't be pushed). Wake it first (i.e. with [PhysicsSystem::update_entity_with] and [WakePolicy::Always]) if that matters.
+	pub fn move_collider(&mut self, handle : ColliderHandle, position : impl Into<Vec3>) -> Result<(),()> {
+		let position = position.into();
+		let mut colliders = self.colliders.borrow_mut();
+		let collider = colliders.get_mut(handle).ok_or(())?; // TODO: An error type.
+		let motion = position - collider.get_local_position();
+		collider.set_local_position(&position);
+		if let Some(entity_handle) = collider.get_entity() {
+			*self.collider_motions.entry(handle).or_insert_with(Vec3::zeros) += motion;
+			if let Some(entity) = self.entities.borrow_mut().get_mut(entity_handle) {
+				entity.mass_dirty = true;
+			}
+		}
+		Ok(())
+	}
+
+	/// Gets where a collider is: the orientation of the entity it's linked to (if any), and its local position (i.e. [SphereCollider::center] or [PlaneCollider::position]).
+	fn collider_frame(&self, handle : ColliderHandle) -> Option<(Option<Orientation>, Vec3)> {
+		let (entity_handle, local_position) = {
+			let mut colliders = self.colliders.borrow_mut();
+			let collider = colliders.get_mut(handle)?;
+			(collider.get_entity(), collider.get_local_position())
+		};
+		let orientation = entity_handle.and_then(|entity_handle| self.entities.borrow().get(entity_handle).map(|entity| entity.orientation));
+		Some((orientation, local_position))
+	}
+
+	/// Converts a point relative to a collider's local position (i.e. [SphereCollider::center]) into world space, using where its entity is right now. Returns None if the collider doesn't exist.
+	///
+	/// Colliders that aren't linked to an entity are already in world space, so only their local position is added.
+	pub fn collider_to_world(&self, handle : ColliderHandle, local_point : &Vec3) -> Option<Vec3> {
+		let (orientation, local_position) = self.collider_frame(handle)?;
+		let entity_point = local_position + local_point;
+		Some(orientation.map_or(entity_point, |orientation| orientation.position_into_world(&entity_point)))
+	}
+
+	/// Converts a world space point to be relative to a collider's local position (i.e. [SphereCollider::center]). The opposite of [PhysicsSystem::collider_to_world]. Returns None if the collider doesn't exist.
+	pub fn world_to_collider(&self, handle : ColliderHandle, world_point : &Vec3) -> Option<Vec3> {
+		let (orientation, local_position) = self.collider_frame(handle)?;
+		let entity_point = orientation.map_or(*world_point, |orientation| orientation.position_into_local(world_point));
+		Some(entity_point - local_position)
+	}
+
 	/// Links the collider to the entity.
 	///
 	/// Will unlink it from any existing entity.
@@ -279,6 +1102,8 @@ impl PhysicsSystem {
 		if !self.colliders.borrow().contains(collider_handle) {
 			return Err(());
 		}
+		// Any motion so far was relative to the old entity.
+		self.collider_motions.remove(&collider_handle);
 
 		// Then try to handle the passed in entity_handle, which can be None...
 		// This part is mainly done before anything else so won't touch the collider unless entity_handle is valid.
@@ -313,77 +1138,702 @@ impl PhysicsSystem {
 		Ok(())
 	}
 
-	/// Adds a UnaryForceGenerator to the system.
-	pub fn add_unary_force_generator(&mut self, generator : Box<dyn UnaryForceGenerator>) -> Result<UnaryForceGeneratorHandle, ()> {
-		Ok(self.unary_force_generators.borrow_mut().insert(generator))
-	}
-
-	/// Removes and returns a UnaryForceGenerator from the system.
-	pub fn remove_unary_force_generator(&mut self, handle : UnaryForceGeneratorHandle) -> Option<Box<dyn UnaryForceGenerator>> {
-		self.unary_force_generators.borrow_mut().remove(handle)
-	}
-
-	/// Moves the system forward by the given time step.
+	/// Attaches an entity to another one, so it rides along on it (i.e. a turret on a tank) without needing any constraints. The two stay in the same relative spot from then on.
 	///
-	/// Note that a large `dt` will most likely lead to instability.
+	/// While attached, the child's colliders (and `own_mass`) are moved onto the parent: so they add to the parent's mass, and collisions with them push the parent. The child itself is disabled (see [PhysicsSystem::set_entity_enabled]), but is moved along with the parent every step so it can still be read back.
 	///
-	/// Also this isn't guaranteed to move everything forward by `dt`. It might move things forward less if it hits a computational limit.
-	pub fn step(&mut self, dt : f32) {
-		// Don't let a tiny step cause everything to go to sleep.
-		if dt.abs() < EPSILON {
-			return
+	/// The parent keeps its velocities. Fails if either entity doesn't exist, they're the same, the child is already attached (or has anything attached to it), the parent is itself attached, or the child has an [crate::AlignedBoxCollider] that would need to be rotated.
+	pub fn attach_entity(&mut self, child : EntityHandle, parent : EntityHandle) -> Result<(), ()> {
+		if child == parent || self.is_in_attachment(child) || self.attachments.contains_key(&parent) {
+			return Err(()); // TODO: An error type.
+		}
+		let (child_orientation, offset, own_mass, mut collider_handles) = {
+			let entities = self.entities.borrow();
+			let child_entity = entities.get(child).ok_or(())?; // TODO: An error type.
+			let parent_entity = entities.get(parent).ok_or(())?; // TODO: An error type.
+			(child_entity.orientation, parent_entity.orientation.into_local() * child_entity.orientation.into_world(), child_entity.own_mass, child_entity.colliders.iter().copied().collect::<Vec<ColliderHandle>>())
+		};
+		collider_handles.sort(); // So nothing depends on hashing.
+		// Work out where everything goes before changing anything, so a failure leaves everything as it was.
+		let mut colliders = Vec::with_capacity(collider_handles.len());
+		let mut moved = Vec::with_capacity(collider_handles.len());
+		for handle in collider_handles {
+			let original = self.get_collider(handle).unwrap();
+			moved.push((handle, original.transformed(&offset)?));
+			colliders.push((handle, original));
+		}
+		for (handle, collider) in moved {
+			self.update_collider(handle, collider)?;
+			self.link_collider(handle, Some(parent))?;
+		}
+		self.set_entity_enabled(child, false)?;
+		{
+			let mut entities = self.entities.borrow_mut();
+			// Losing its colliders moved the child's center of mass. Keep it where they are instead, so its position still means the same thing.
+			entities.get_mut(child).unwrap().orientation = child_orientation;
+			let parent_entity = entities.get_mut(parent).unwrap();
+			parent_entity.own_mass += own_mass;
+			parent_entity.recalculate_mass(&self.colliders.borrow());
 		}
+		InternalEntity::wake_up(parent, &mut self.entities.borrow_mut(), &mut self.debug);
+		self.attachments.insert(child, Attachment { parent, offset, own_mass, break_impulse: None, colliders });
+		self.follow_attachments();
+		Ok(())
+	}
 
-		self.collision_records.clear();
-		self.debug.clear();
-		// Go through all entities and perform the initial integration.
-		let mut entity_handles = Vec::with_capacity(self.entities.borrow().len());
-		for (handle, _) in self.entities.borrow().iter() {
-			entity_handles.push(handle);
+	/// Detaches an entity that was attached with [PhysicsSystem::attach_entity], so it's a separate body again. Fails if it isn't attached.
+	///
+	/// Its colliders are moved back to it (as they were when it was attached, so any changes made to them while attached are lost), and it's re-enabled. It starts out moving however its spot on the parent was moving.
+	pub fn detach_entity(&mut self, child : EntityHandle) -> Result<(), ()> {
+		self.follow_attachments();
+		let attachment = self.attachments.remove(&child).ok_or(())?; // TODO: An error type.
+		for (handle, collider) in attachment.colliders {
+			if !self.is_valid_collider(handle) { continue; } // Removed while attached.
+			self.link_collider(handle, Some(child))?;
+			self.update_collider(handle, collider)?;
 		}
-		let mut unary_force_generator_handles = Vec::with_capacity(self.unary_force_generators.borrow().len());
-		for (handle, _) in self.unary_force_generators.borrow().iter() {
-			unary_force_generator_handles.push(handle);
+		let (velocity, angular_velocity, parent_position) = {
+			let mut entities = self.entities.borrow_mut();
+			let parent_entity = entities.get_mut(attachment.parent).unwrap();
+			parent_entity.own_mass -= attachment.own_mass;
+			parent_entity.recalculate_mass(&self.colliders.borrow());
+			(parent_entity.velocity, parent_entity.angular_velocity, parent_entity.orientation.position)
+		};
+		{
+			let mut entities = self.entities.borrow_mut();
+			let child_entity = entities.get_mut(child).unwrap();
+			child_entity.recalculate_mass(&self.colliders.borrow());
+			child_entity.velocity = velocity + angular_velocity.cross(&(child_entity.orientation.position - parent_position));
+			child_entity.angular_velocity = angular_velocity;
 		}
-		let mut entity_info = Vec::with_capacity(self.entities.borrow().len());
-		for handle in entity_handles { // TODO: Optimize this.
-			let mut acceleration = Vec3::zeros();
-			let mut torque = Vec3::zeros();
+		InternalEntity::wake_up(attachment.parent, &mut self.entities.borrow_mut(), &mut self.debug);
+		self.set_entity_enabled(child, true)
+	}
 
-			{
-				let entity_copy = self.get_entity(handle).unwrap();
-				// Since 0.0 * INFINITY becomes NaN, best to NOT integrate acceleration and torque on infinite or zero masses.
-				let total_mass = entity_copy.get_last_total_mass();
-				if total_mass.is_finite() && EPSILON < total_mass {
-					for generator_handle in &unary_force_generator_handles {
-						let mut generators_borrow = self.unary_force_generators.borrow_mut();
-						let generator_borrow = generators_borrow.get_mut(*generator_handle).unwrap();
-						let force = generator_borrow.make_force(dt, &self, handle);
-
-						acceleration += force.force.scale(1.0 / total_mass);
-						torque += entity_copy.get_last_moment_of_inertia() * (force.position - entity_copy.position).cross(&force.force);
-					}
-				}
-			}
+	/// Gets the entity that the given one is attached to (see [PhysicsSystem::attach_entity]). Returns None if it isn't attached to anything.
+	pub fn get_attached_parent(&self, child : EntityHandle) -> Option<EntityHandle> {
+		self.attachments.get(&child).map(|attachment| attachment.parent)
+	}
 
-			let mut entities_borrow = self.entities.borrow_mut();
-			let entity = entities_borrow.get_mut(handle).unwrap();
-			entity.velocity += acceleration.scale(dt);
-			let linear_movement = entity.velocity.scale(dt);
+	/// Gets every entity attached to the given one (see [PhysicsSystem::attach_entity]), sorted by handle.
+	pub fn get_attached_children(&self, parent : EntityHandle) -> Vec<EntityHandle> {
+		let mut children : Vec<EntityHandle> = self.attachments.iter().filter(|(_, attachment)| attachment.parent == parent).map(|(child, _)| *child).collect();
+		children.sort();
+		children
+	}
 
-			entity.angular_velocity += entity.get_inverse_moment_of_inertia() * torque.scale(dt);
-			let angular_movement = entity.angular_velocity.scale(dt);
+	/// Sets (or clears) how much impulse an attached entity's colliders can take in a single step before it's knocked loose from its parent. Once they take more, it's detached (see [PhysicsSystem::detach_entity]) and a [BreakEvent] is reported. Fails if the entity isn't attached, or the impulse is negative (or NaN).
+	///
+	/// Attachments can't be broken until this is set.
+	pub fn set_attachment_break_impulse(&mut self, child : EntityHandle, impulse : Option<f32>) -> Result<(), ()> {
+		if impulse.is_some_and(|impulse| impulse.is_nan() || impulse < 0.0) {
+			return Err(()); // TODO: An error type.
+		}
+		self.attachments.get_mut(&child).ok_or(())?.break_impulse = impulse; // TODO: An error type.
+		Ok(())
+	}
+
+	/// Sets (or clears) how much impulse a collider can take in a single step before it breaks. Returns the prior break impulse (if there was one). Fails if the collider doesn't exist, or the impulse is negative (or NaN).
+	///
+	/// Once it takes more, a [BreakEvent] is reported and its break impulse is cleared (so it's only reported once). Nothing else is done to it.
+	pub fn set_collider_break_impulse(&mut self, handle : ColliderHandle, impulse : Option<f32>) -> Result<Option<f32>, ()> {
+		if !self.colliders.borrow().contains(handle) || impulse.is_some_and(|impulse| impulse.is_nan() || impulse < 0.0) {
+			return Err(()); // TODO: An error type.
+		}
+		Ok(match impulse {
+			Some(impulse) => self.collider_break_impulses.insert(handle, impulse),
+			None => self.collider_break_impulses.remove(&handle),
+		})
+	}
+
+	/// Gets everything that broke since the last call (in the order it broke), i.e. so gameplay code can spawn debris. Call it after every step() to get each step's breaks.
+	pub fn take_break_events(&mut self) -> Vec<BreakEvent> {
+		std::mem::take(&mut self.break_events)
+	}
+
+	/// Gets every entity that was removed because its [Entity::lifetime] ran out since the last call (sorted by handle within each step). Their handles are no longer valid, so this is just for cleaning up whatever was tracking them.
+	pub fn take_expired_entities(&mut self) -> Vec<EntityHandle> {
+		std::mem::take(&mut self.expired_entities)
+	}
+
+	/// Counts down the lifetimes of every enabled entity, removing the ones that run out.
+	fn expire_entities(&mut self, dt : f32) {
+		let mut expired = Vec::new();
+		for (handle, entity) in self.entities.borrow_mut().iter_mut() {
+			if !entity.enabled { continue; }
+			if let Some(lifetime) = entity.lifetime.as_mut() {
+				*lifetime -= dt;
+				if *lifetime <= 0.0 {
+					expired.push(handle);
+				}
+			}
+		}
+		expired.sort();
+		for handle in expired {
+			self.remove_entity(handle);
+			self.expired_entities.push(handle);
+		}
+	}
+
+	/// Gets every collider whose impulse needs to be tracked during step(): any with a break impulse, and any in an attachment that can break.
+	fn breakable_colliders(&self) -> HashSet<ColliderHandle> {
+		let mut breakable : HashSet<ColliderHandle> = self.collider_break_impulses.keys().copied().collect();
+		for attachment in self.attachments.values().filter(|attachment| attachment.break_impulse.is_some()) {
+			breakable.extend(attachment.colliders.iter().map(|(handle, _)| *handle));
+		}
+		breakable
+	}
+
+	/// Breaks (and reports) everything that took more than its break impulse during the step, given how much impulse each breakable collider took.
+	fn apply_breaks(&mut self, collider_impulses : &HashMap<ColliderHandle, f32>) {
+		if collider_impulses.is_empty() { return; }
+		// Sorted so nothing depends on hashing.
+		let mut colliders : Vec<(ColliderHandle, f32)> = collider_impulses.iter()
+			.filter(|(handle, impulse)| self.collider_break_impulses.get(handle).is_some_and(|threshold| threshold < impulse))
+			.map(|(handle, impulse)| (*handle, *impulse))
+			.collect();
+		colliders.sort_by_key(|(handle, _)| *handle);
+		for (handle, impulse) in colliders {
+			self.collider_break_impulses.remove(&handle);
+			self.break_events.push(BreakEvent { broken: Broken::Collider(handle), impulse });
+		}
+		let mut children : Vec<(EntityHandle, EntityHandle, f32)> = self.attachments.iter().filter_map(|(child, attachment)| {
+			let impulse : f32 = attachment.colliders.iter().filter_map(|(handle, _)| collider_impulses.get(handle)).sum();
+			if attachment.break_impulse? < impulse { Some((*child, attachment.parent, impulse)) } else { None }
+		}).collect();
+		children.sort_by_key(|(child, _, _)| *child);
+		for (child, parent, impulse) in children {
+			self.detach_entity(child).unwrap();
+			self.break_events.push(BreakEvent { broken: Broken::Attachment { child, parent }, impulse });
+		}
+	}
+
+	/// Checks whether an entity is attached to something, or has anything attached to it.
+	fn is_in_attachment(&self, handle : EntityHandle) -> bool {
+		self.attachments.contains_key(&handle) || self.attachments.values().any(|attachment| attachment.parent == handle)
+	}
+
+	/// Splits some of an entity's colliders off into new entities (i.e. to break something apart). Each list of colliders becomes its own entity, and any colliders that aren't in a list stay with the original. Returns the new entities' handles (in the same order as the lists).
+	///
+	/// The new entities are copies of the original (minus its name and `own_mass`), so they start out in the same local space. Every piece keeps spinning the same, and moves like the spot its new center of mass was at. So momentum is conserved.
+	///
+	/// Fails (without changing anything) if the entity doesn't exist, is a part of an attachment (see [PhysicsSystem::attach_entity]), or if any list is empty, has a collider that isn't linked to it, or shares a collider with another list.
+	pub fn split_entity(&mut self, handle : EntityHandle, collider_subsets : &[Vec<ColliderHandle>]) -> Result<Vec<EntityHandle>, ()> {
+		if self.is_in_attachment(handle) {
+			return Err(()); // TODO: An error type.
+		}
+		let (old_center, velocity, angular_velocity) = {
+			let entities = self.entities.borrow();
+			let entity = entities.get(handle).ok_or(())?; // TODO: An error type.
+			let mut seen = HashSet::new();
+			for subset in collider_subsets {
+				if subset.is_empty() || !subset.iter().all(|collider| entity.colliders.contains(collider) && seen.insert(*collider)) {
+					return Err(()); // TODO: An error type.
+				}
+			}
+			(entity.orientation.position, entity.velocity, entity.angular_velocity)
+		};
+		let mut source = self.get_entity(handle).unwrap();
+		source.name = None;
+		source.own_mass = 0.0;
+		let mut pieces = Vec::with_capacity(collider_subsets.len());
+		for subset in collider_subsets {
+			let piece = self.add_entity(source.clone())?;
+			for collider in subset {
+				self.link_collider(*collider, Some(piece))?;
+			}
+			pieces.push(piece);
+		}
+		{
+			let mut entities = self.entities.borrow_mut();
+			for piece in pieces.iter().chain(std::iter::once(&handle)) {
+				let entity = entities.get_mut(*piece).unwrap();
+				entity.velocity = velocity + angular_velocity.cross(&(entity.orientation.position - old_center));
+				entity.angular_velocity = angular_velocity;
+			}
+		}
+		InternalEntity::wake_up(handle, &mut self.entities.borrow_mut(), &mut self.debug);
+		Ok(pieces)
+	}
+
+	/// Merges entities into the first one given: every other entity's colliders (and `own_mass`) are moved onto it (without moving in world space), then the other entities are removed. Returns the first entity's handle.
+	///
+	/// If every entity is dynamic, the merged entity's velocities are set so both linear and angular momentum are conserved. Otherwise the first entity's velocities are kept.
+	///
+	/// Fails (without changing anything) if no entities are given, any is given twice, doesn't exist, or is a part of an attachment (see [PhysicsSystem::attach_entity]), or if any has an [crate::AlignedBoxCollider] that would need to be rotated (or any other collider that wouldn't be valid once moved).
+	pub fn merge_entities(&mut self, handles : &[EntityHandle]) -> Result<EntityHandle, ()> {
+		let target = *handles.first().ok_or(())?; // TODO: An error type.
+		let mut seen = HashSet::new();
+		if !handles.iter().all(|handle| seen.insert(*handle) && !self.is_in_attachment(*handle)) {
+			return Err(()); // TODO: An error type.
+		}
+		// Work out where everything goes (and the total momentum) before changing anything, so a failure leaves everything as it was.
+		let mut momentum = Vec3::zeros();
+		let mut angular_momentum = Vec3::zeros(); // About the world origin.
+		let mut all_dynamic = true;
+		let mut own_mass = 0.0;
+		let mut moved = Vec::new();
+		{
+			let entities = self.entities.borrow();
+			let target_entity = entities.get(target).ok_or(())?; // TODO: An error type.
+			for handle in handles {
+				let entity = entities.get(*handle).ok_or(())?; // TODO: An error type.
+				momentum += entity.get_total_mass() * entity.velocity;
+				angular_momentum += entity.get_moment_of_inertia() * entity.angular_velocity + entity.orientation.position.cross(&(entity.get_total_mass() * entity.velocity));
+				all_dynamic = all_dynamic && entity.is_dynamic();
+				if target == *handle { continue; }
+				own_mass += entity.own_mass;
+				let offset = target_entity.orientation.into_local() * entity.orientation.into_world();
+				let mut collider_handles : Vec<ColliderHandle> = entity.colliders.iter().copied().collect();
+				collider_handles.sort(); // So nothing depends on hashing.
+				for collider_handle in collider_handles {
+					let collider = self.get_collider(collider_handle).unwrap().transformed(&offset)?;
+					if !collider.is_valid() {
+						return Err(()); // TODO: An error type.
+					}
+					moved.push((collider_handle, collider));
+				}
+			}
+		}
+		// Everything was checked above, so none of this can fail (and leave things half merged).
+		for (collider_handle, collider) in moved {
+			self.update_collider(collider_handle, collider).unwrap();
+			self.link_collider(collider_handle, Some(target)).unwrap();
+		}
+		for handle in &handles[1..] {
+			self.remove_entity(*handle);
+		}
+		{
+			let mut entities = self.entities.borrow_mut();
+			let entity = entities.get_mut(target).unwrap();
+			entity.own_mass += own_mass;
+			entity.recalculate_mass(&self.colliders.borrow());
+			if all_dynamic && EPSILON < entity.get_total_mass() {
+				entity.velocity = momentum / entity.get_total_mass();
+				entity.angular_velocity = entity.get_inverse_moment_of_inertia() * (angular_momentum - entity.orientation.position.cross(&momentum));
+			}
+		}
+		InternalEntity::wake_up(target, &mut self.entities.borrow_mut(), &mut self.debug);
+		Ok(target)
+	}
+
+	/// Moves every attached entity to its spot on its parent, and gives it the velocities of that spot.
+	fn follow_attachments(&mut self) {
+		let mut entities = self.entities.borrow_mut();
+		for (child, attachment) in self.attachments.iter() {
+			let (into_world, velocity, angular_velocity, parent_position) = match entities.get(attachment.parent) {
+				Some(parent_entity) => (parent_entity.orientation.into_world() * attachment.offset, parent_entity.velocity, parent_entity.angular_velocity, parent_entity.orientation.position),
+				None => continue,
+			};
+			if let Some(child_entity) = entities.get_mut(*child) {
+				child_entity.orientation.rotation = into_world.rotation;
+				child_entity.orientation.position = into_world.transform_point(&(-child_entity.orientation.internal_origin_offset).into()).coords;
+				child_entity.velocity = velocity + angular_velocity.cross(&(child_entity.orientation.position - parent_position));
+				child_entity.angular_velocity = angular_velocity;
+			}
+		}
+	}
+
+	/// Sets (or clears) the ContactModifier for a collider. Returns the prior modifier (if there was one).
+	///
+	/// The modifier will be called for every contact the collider is in before it's responded to.
+	/// If both colliders in a contact have modifiers, then the first collider's is called first.
+	pub fn set_contact_modifier(&mut self, handle : ColliderHandle, modifier : Option<Box<dyn ContactModifier>>) -> Result<Option<Box<dyn ContactModifier>>, ()> {
+		if !self.colliders.borrow().contains(handle) {
+			return Err(());
+		}
+		Ok(match modifier {
+			Some(modifier) => self.contact_modifiers.insert(handle, modifier),
+			None => self.contact_modifiers.remove(&handle),
+		})
+	}
+
+	/// Sets (or clears) the contact properties used between colliders made of the two given materials (in either order). Returns the prior properties (if there were any).
+	///
+	/// These replace the colliders' combined values before any [ContactModifier] is called. Any properties set for the pair of entities (see [PhysicsSystem::set_entity_pair_properties]) win over these.
+	pub fn set_material_pair_properties(&mut self, first : MaterialHandle, second : MaterialHandle, properties : Option<ContactProperties>) -> Result<Option<ContactProperties>, ()> {
+		let key = if first < second { (first, second) } else { (second, first) };
+		Ok(match properties {
+			Some(properties) => {
+				if !properties.is_valid() { return Err(()); } // TODO: An error type.
+				self.material_pair_properties.insert(key, properties)
+			},
+			None => self.material_pair_properties.remove(&key),
+		})
+	}
+
+	/// Sets (or clears) the contact properties used between the two given entities (in either order). Returns the prior properties (if there were any).
+	///
+	/// These replace the colliders' combined values (and anything from [PhysicsSystem::set_material_pair_properties]) before any [ContactModifier] is called.
+	pub fn set_entity_pair_properties(&mut self, first : EntityHandle, second : EntityHandle, properties : Option<ContactProperties>) -> Result<Option<ContactProperties>, ()> {
+		{
+			let entities = self.entities.borrow();
+			if !entities.contains(first) || !entities.contains(second) {
+				return Err(()); // TODO: An error type.
+			}
+		}
+		let key = PhysicsSystem::pair_key(first, second);
+		Ok(match properties {
+			Some(properties) => {
+				if !properties.is_valid() { return Err(()); } // TODO: An error type.
+				self.entity_pair_properties.insert(key, properties)
+			},
+			None => self.entity_pair_properties.remove(&key),
+		})
+	}
+
+	/// Adds a UnaryForceGenerator to the system.
+	pub fn add_unary_force_generator(&mut self, generator : Box<dyn UnaryForceGenerator>) -> Result<UnaryForceGeneratorHandle, ()> {
+		Ok(self.unary_force_generators.borrow_mut().insert(generator))
+	}
+
+	/// Removes and returns a UnaryForceGenerator from the system.
+	pub fn remove_unary_force_generator(&mut self, handle : UnaryForceGeneratorHandle) -> Option<Box<dyn UnaryForceGenerator>> {
+		self.disabled_generators.remove(&handle);
+		self.generator_strengths.remove(&handle);
+		self.unary_force_generators.borrow_mut().remove(handle)
+	}
+
+	/// Turns a force generator off or on, without removing it (so it keeps all of its settings). i.e. to turn gravity off for a cutscene.
+	///
+	/// Generators that are off aren't asked for forces at all. Fails if there's no such generator.
+	pub fn set_force_generator_enabled(&mut self, handle : UnaryForceGeneratorHandle, enabled : bool) -> Result<(), ()> {
+		if !self.unary_force_generators.borrow().contains(handle) { return Err(()); } // TODO: An error type.
+		if enabled {
+			self.disabled_generators.remove(&handle);
+		} else {
+			self.disabled_generators.insert(handle);
+		}
+		Ok(())
+	}
+
+	/// Checks whether a force generator is on. See [PhysicsSystem::set_force_generator_enabled]. Fails if there's no such generator.
+	pub fn is_force_generator_enabled(&self, handle : UnaryForceGeneratorHandle) -> Result<bool, ()> {
+		if !self.unary_force_generators.borrow().contains(handle) { return Err(()); } // TODO: An error type.
+		Ok(!self.disabled_generators.contains(&handle))
+	}
+
+	/// Sets what a force generator's forces are multiplied by. i.e. to ramp gravity up or down smoothly over a few steps.
+	///
+	/// Defaults to 1.0. Negative values flip the forces around. Fails if there's no such generator, or the strength isn't finite.
+	pub fn set_force_generator_strength(&mut self, handle : UnaryForceGeneratorHandle, strength : f32) -> Result<(), ()> {
+		if !self.unary_force_generators.borrow().contains(handle) || !strength.is_finite() { return Err(()); } // TODO: An error type.
+		if 1.0 == strength {
+			self.generator_strengths.remove(&handle);
+		} else {
+			self.generator_strengths.insert(handle, strength);
+		}
+		Ok(())
+	}
+
+	/// Gets what a force generator's forces are multiplied by. See [PhysicsSystem::set_force_generator_strength]. Fails if there's no such generator.
+	pub fn get_force_generator_strength(&self, handle : UnaryForceGeneratorHandle) -> Result<f32, ()> {
+		if !self.unary_force_generators.borrow().contains(handle) { return Err(()); } // TODO: An error type.
+		Ok(self.generator_strengths.get(&handle).copied().unwrap_or(1.0))
+	}
+
+	/// Gets what a force generator's forces should be multiplied by right now, or `None` if it's turned off (so shouldn't be asked for forces at all).
+	pub(crate) fn generator_scale(&self, handle : UnaryForceGeneratorHandle) -> Option<f32> {
+		if self.disabled_generators.contains(&handle) { return None; }
+		Some(self.generator_strengths.get(&handle).copied().unwrap_or(1.0))
+	}
+
+	/// Adds a cloth to the system, returning its handle. It'll be moved (and collided with all the colliders) by step().
+	pub fn add_cloth(&mut self, cloth : Cloth) -> ClothHandle {
+		self.cloths.insert(cloth)
+	}
+
+	/// Removes and returns a cloth from the system.
+	pub fn remove_cloth(&mut self, handle : ClothHandle) -> Option<Cloth> {
+		self.cloths.remove(handle)
+	}
+
+	/// Gets a cloth (i.e. to read its positions for rendering).
+	pub fn get_cloth(&self, handle : ClothHandle) -> Option<&Cloth> {
+		self.cloths.get(handle)
+	}
+
+	/// Gets a cloth so it can be changed (i.e. to move a pinned particle).
+	pub fn get_cloth_mut(&mut self, handle : ClothHandle) -> Option<&mut Cloth> {
+		self.cloths.get_mut(handle)
+	}
+
+	/// Adds a set of particles to the system, returning its handle. They'll be moved (and bounced off of all the colliders) by step().
+	pub fn add_particle_set(&mut self, particles : ParticleSet) -> Result<ParticleSetHandle, ()> {
+		if !particles.is_valid() {
+			return Err(()); // TODO: An error type.
+		}
+		Ok(self.particle_sets.insert(particles))
+	}
+
+	/// Removes and returns a set of particles from the system.
+	pub fn remove_particle_set(&mut self, handle : ParticleSetHandle) -> Option<ParticleSet> {
+		self.particle_sets.remove(handle)
+	}
+
+	/// Gets a set of particles (i.e. to read its positions for rendering).
+	pub fn get_particle_set(&self, handle : ParticleSetHandle) -> Option<&ParticleSet> {
+		self.particle_sets.get(handle)
+	}
+
+	/// Gets a set of particles so it can be changed (i.e. to add more particles).
+	pub fn get_particle_set_mut(&mut self, handle : ParticleSetHandle) -> Option<&mut ParticleSet> {
+		self.particle_sets.get_mut(handle)
+	}
+
+	/// Adds a fluid region to the system, returning its handle. Every step it pushes on (and drags) the dynamic entities that are in it, and reports the entities that enter or exit it (see [PhysicsSystem::take_fluid_events]).
+	///
+	/// Fails if the region isn't valid, or its collider doesn't exist.
+	pub fn add_fluid_region(&mut self, region : FluidRegion) -> Result<FluidRegionHandle, ()> {
+		if !region.is_valid() || !self.colliders.borrow().contains(region.collider) {
+			return Err(()); // TODO: An error type.
+		}
+		Ok(self.fluid_regions.insert(region))
+	}
+
+	/// Removes and returns a fluid region from the system. Its collider is left alone, and nothing is reported as exiting it.
+	pub fn remove_fluid_region(&mut self, handle : FluidRegionHandle) -> Option<FluidRegion> {
+		self.fluid_regions.remove(handle)
+	}
+
+	/// Gets a fluid region (i.e. to check what's in it).
+	pub fn get_fluid_region(&self, handle : FluidRegionHandle) -> Option<&FluidRegion> {
+		self.fluid_regions.get(handle)
+	}
+
+	/// Gets a fluid region so it can be changed (i.e. to change how fast it flows).
+	pub fn get_fluid_region_mut(&mut self, handle : FluidRegionHandle) -> Option<&mut FluidRegion> {
+		self.fluid_regions.get_mut(handle)
+	}
+
+	/// Gets every entity that entered or exited a fluid region since the last call (in the order it happened, by step). Call it after every step() to get each step's events.
+	pub fn take_fluid_events(&mut self) -> Vec<FluidEvent> {
+		std::mem::take(&mut self.fluid_events)
+	}
+
+	/// Estimates how much of each of an entity's colliders is in a fluid region (where everything is right now), as the volume that's in it and its center. Colliders that aren't in it are left out.
+	///
+	/// The region's own entity (if any) is never in it. Neither are query-only or disabled colliders.
+	fn find_fluid_submerged(&self, region : &FluidRegion, handle : EntityHandle) -> Vec<(f32, Vec3)> {
+		let mut colliders = self.colliders.borrow_mut();
+		let region_entity = match colliders.get_mut(region.collider) {
+			Some(region_collider) => region_collider.get_entity(),
+			None => return Vec::new(),
+		};
+		if Some(handle) == region_entity {
+			return Vec::new();
+		}
+		let entities = self.entities.borrow();
+		let entity = match entities.get(handle) {
+			Some(entity) => entity,
+			None => return Vec::new(),
+		};
+		// Loose colliders are already in world space.
+		let region_orientation = match region_entity {
+			Some(region_entity) => entities.get(region_entity).unwrap().orientation,
+			None => Orientation::new(Vec3::zeros(), Vec3::zeros(), Vec3::zeros()),
+		};
+		let region_collider = colliders.get(region.collider).unwrap();
+		entity.colliders.iter()
+			.map(|collider_handle| colliders.get(*collider_handle).unwrap())
+			.filter(|collider| collider.collides())
+			.filter_map(|collider| find_submerged(collider, &entity.orientation, region_collider, &region_orientation))
+			.collect()
+	}
+
+	/// Works out which entities are in each fluid region after a step, and records the ones that entered or exited them.
+	fn update_fluid_regions(&mut self) {
+		if self.fluid_regions.is_empty() {
+			return;
+		}
+		let handles : Vec<EntityHandle> = self.entities.borrow().iter().filter(|(_, entity)| entity.enabled).map(|(handle, _)| handle).collect();
+		let mut fluid_regions = std::mem::take(&mut self.fluid_regions);
+		for (region_handle, region) in fluid_regions.iter_mut() {
+			let submerged = handles.iter().copied().filter(|handle| !self.find_fluid_submerged(region, *handle).is_empty()).collect();
+			for (entity, entered) in region.update_submerged(submerged) {
+				self.fluid_events.push(FluidEvent { region: region_handle, entity, entered });
+			}
+		}
+		self.fluid_regions = fluid_regions;
+	}
+
+	/// Moves the system forward by the given time step.
+	///
+	/// Note that a large `dt` will most likely lead to instability.
+	///
+	/// Also this isn't guaranteed to move everything forward by `dt`. It might move things forward less if it hits a computational limit.
+	pub fn step(&mut self, dt : f32) {
+		self.step_with_iteration_max(dt, self.iteration_max)
+	}
+
+	/// Moves the system forward like [PhysicsSystem::step], but with a different `iteration_max` for just this step. i.e. for when a lot is known to be piled up.
+	pub fn step_with_iteration_max(&mut self, dt : f32, iteration_max : u32) {
+		// Don't let a tiny step cause everything to go to sleep.
+		if dt.abs() < EPSILON {
+			return
+		}
+
+		// Taken out for the whole step, so reporting to it doesn't get in the way of borrowing everything else.
+		let mut profiler = ActiveProfiler(self.profiler.take());
+		profiler.begin_step();
+		self.collision_records.clear();
+		self.dropped_collision_records = 0;
+		self.debug.clear();
+		// Go through all entities and perform the initial integration.
+		let mut entity_handles = std::mem::take(&mut self.scratch.entity_handles);
+		entity_handles.clear();
+		for (handle, entity) in self.entities.borrow().iter() {
+			// Disabled entities are completely left out of the step.
+			if !entity.enabled { continue; }
+			entity_handles.push(handle);
+		}
+		if let Some(seed) = self.entity_order_seed {
+			Xorshift::new(seed).shuffle(&mut entity_handles);
+		}
+		let mut unary_force_generator_handles = std::mem::take(&mut self.scratch.generator_handles);
+		unary_force_generator_handles.clear();
+		for (handle, _) in self.unary_force_generators.borrow().iter() {
+			// Generators that are turned off are left out of the whole step.
+			if self.disabled_generators.contains(&handle) { continue; }
+			unary_force_generator_handles.push(handle);
+		}
+		let mut entity_info = std::mem::take(&mut self.scratch.entity_info);
+		entity_info.clear();
+		for handle in entity_handles.iter().copied() {
+			let mut entities_borrow = self.entities.borrow_mut();
+			let entity = entities_borrow.get_mut(handle).unwrap();
+			if entity.mass_dirty {
+				entity.recalculate_mass(&self.colliders.borrow());
+			}
+			#[cfg(feature = "fixed-point")]
+			{ // Anything changed by something besides step() is tracked from there.
+				(entity.fixed_position, entity.fixed_velocity) = entity.fixed_state();
+			}
+			entity.step_path.clear();
+			entity.step_path.push((0.0, entity.orientation));
+			if BodyType::Static == entity.get_body_type() {
+				// Static entities never move, no matter what they were given.
+				entity.velocity = Vec3::zeros();
+				#[cfg(feature = "fixed-point")]
+				{ entity.fixed_velocity = FixedVec3::default(); }
+				entity.angular_velocity = Vec3::zeros();
+			}
 
 			// NOTE: Allowing velocities to be set even on sleeping entities so that if they're woken up during this step(), they will still have the basic velocities setup.
 			// This should help insure that the newly-woken entities have a shot at being awake for a bit before being pushed back to sleep.
 
 			entity_info.push(EntityStepInfo {
 				handle,
-				linear_movement,
-				angular_movement,
-				neighbors: HashSet::new(),
+				linear_movement: Vec3::zeros(),
+				#[cfg(feature = "fixed-point")]
+				fixed_movement: FixedVec3::default(),
+				angular_movement: Vec3::zeros(),
+				neighbors: self.scratch.neighbor_sets.pop().unwrap_or_default(),
+				changed: true,
+				energy: if self.track_energy_drift { EnergyChange::of(entity) } else { EnergyChange::default() },
+				drift: EnergyDrift::default(),
+				impulse: Vec3::zeros(),
+				angular_impulse: Vec3::zeros(),
 			});
 		}
+		let entity_count = entity_info.len() as u64;
+		profiler.begin(StepPhase::Forces);
+		// Then integrate the forces, possibly over several substeps (so stiff forces see how things moved partway through).
+		// Everything is moved to where it'd be after each substep (so the force generators can see it), but then put back at the end. The collision handling below moves things along the total movement.
+		let substeps = self.force_substeps.max(1);
+		let substep_dt = dt / substeps as f32;
+		for substep in 0..substeps {
+			let last_substep = substep + 1 == substeps;
+			for info in entity_info.iter_mut() { // TODO: Optimize this.
+				let handle = info.handle;
+				let (start, velocity, angular_velocity, scaled_dt) = {
+					let entities = self.entities.borrow();
+					let entity = entities.get(handle).unwrap();
+					// Everything about the entity happens at its own speed (including how much the forces push it), so it just sees a different dt.
+					(entity.orientation, entity.velocity, entity.angular_velocity, substep_dt * entity.time_scale)
+				};
+				let (velocity, angular_velocity, linear_movement, angular_movement) = match self.integrator {
+					Integrator::SemiImplicitEuler => {
+						let (acceleration, torque) = self.find_acceleration(handle, &unary_force_generator_handles, substep_dt);
+						let inverse_moment_of_inertia = self.entities.borrow().get(handle).unwrap().get_inverse_moment_of_inertia();
+						let velocity = velocity + acceleration.scale(scaled_dt);
+						let angular_velocity = angular_velocity + inverse_moment_of_inertia * torque.scale(scaled_dt);
+						(velocity, angular_velocity, velocity.scale(scaled_dt), angular_velocity.scale(scaled_dt))
+					},
+					Integrator::VelocityVerlet => {
+						let half_dt = scaled_dt / 2.0;
+						let (acceleration, angular_acceleration) = self.find_acceleration_at(handle, &unary_force_generator_handles, substep_dt, &start, (Vec3::zeros(), Vec3::zeros()), (velocity, angular_velocity));
+						let linear_movement = (velocity + acceleration * half_dt) * scaled_dt;
+						let angular_movement = (angular_velocity + angular_acceleration * half_dt) * scaled_dt;
+						let (end_acceleration, end_angular_acceleration) = self.find_acceleration_at(
+							handle, &unary_force_generator_handles, substep_dt, &start,
+							(linear_movement, angular_movement),
+							(velocity + acceleration * scaled_dt, angular_velocity + angular_acceleration * scaled_dt),
+						);
+						(
+							velocity + (acceleration + end_acceleration) * half_dt,
+							angular_velocity + (angular_acceleration + end_angular_acceleration) * half_dt,
+							linear_movement,
+							angular_movement,
+						)
+					},
+					Integrator::RungeKutta4 => {
+						let half_dt = scaled_dt / 2.0;
+						let find = |movements : (Vec3, Vec3), velocities : (Vec3, Vec3)| self.find_acceleration_at(handle, &unary_force_generator_handles, substep_dt, &start, movements, velocities);
+						let (k1, k1_angular) = find((Vec3::zeros(), Vec3::zeros()), (velocity, angular_velocity));
+						let (v2, w2) = (velocity + k1 * half_dt, angular_velocity + k1_angular * half_dt);
+						let (k2, k2_angular) = find((velocity * half_dt, angular_velocity * half_dt), (v2, w2));
+						let (v3, w3) = (velocity + k2 * half_dt, angular_velocity + k2_angular * half_dt);
+						let (k3, k3_angular) = find((v2 * half_dt, w2 * half_dt), (v3, w3));
+						let (v4, w4) = (velocity + k3 * scaled_dt, angular_velocity + k3_angular * scaled_dt);
+						let (k4, k4_angular) = find((v3 * scaled_dt, w3 * scaled_dt), (v4, w4));
+						let sixth_dt = scaled_dt / 6.0;
+						(
+							velocity + (k1 + k2 * 2.0 + k3 * 2.0 + k4) * sixth_dt,
+							angular_velocity + (k1_angular + k2_angular * 2.0 + k3_angular * 2.0 + k4_angular) * sixth_dt,
+							(velocity + v2 * 2.0 + v3 * 2.0 + v4) * sixth_dt,
+							(angular_velocity + w2 * 2.0 + w3 * 2.0 + w4) * sixth_dt,
+						)
+					},
+				};
+
+				let mut entities_borrow = self.entities.borrow_mut();
+				let entity = entities_borrow.get_mut(handle).unwrap();
+				if entity.is_dynamic() {
+					info.impulse += (velocity - entity.velocity) * entity.get_total_mass();
+					info.angular_impulse += entity.get_moment_of_inertia() * (angular_velocity - entity.angular_velocity);
+				}
+				#[cfg(not(feature = "fixed-point"))]
+				{ entity.velocity = velocity; }
+				#[cfg(feature = "fixed-point")]
+				let linear_movement = {
+					entity.change_velocity(&(velocity - entity.velocity));
+					// Semi-implicit Euler just moves at the new velocity, so that can be done entirely in fixed-point. The others' movements are a mix of velocities along the way, so just round them.
+					let movement = match self.integrator {
+						Integrator::SemiImplicitEuler => entity.fixed_velocity.scale(Fixed::from_f32(scaled_dt)),
+						_ => FixedVec3::from_vec3(&linear_movement),
+					};
+					info.fixed_movement += movement;
+					movement.to_vec3() // So collisions are looked for along the movement that will actually happen.
+				};
+				entity.angular_velocity = angular_velocity;
+
+				info.linear_movement += linear_movement;
+				info.angular_movement += angular_movement;
+				if !last_substep && !entity.asleep {
+					entity.orientation.affect_with(&linear_movement, &angular_movement);
+				}
+			}
+		}
+		if 1 < substeps {
+			let mut entities = self.entities.borrow_mut();
+			for info in &entity_info {
+				let entity = entities.get_mut(info.handle).unwrap();
+				entity.orientation = entity.step_path[0].1;
+			}
+		}
+		self.track_energy_phase(&mut entity_info, |drift| &mut drift.forces);
+		profiler.end(StepPhase::Forces);
 
 		// TODO: Setup a broad-phase that checks AABBs.
 		// That should be able to split the world into islands of boxes that collide
@@ -391,27 +1841,89 @@ impl PhysicsSystem {
 		let mut time_left = dt;
 		let mut current_time_percent : f32 = 0.0;
 		let mut concluded = false;
-		for iteration in 0..self.iteration_max {
+		let iteration_deadline = self.iteration_time_budget.map(|budget| Instant::now() + budget);
+		let mut bounds = std::mem::take(&mut self.scratch.bounds);
+		let mut bound_batches = std::mem::take(&mut self.scratch.bound_batches);
+		let mut lone_shapes = std::mem::take(&mut self.scratch.lone_shapes);
+		let mut spheres = std::mem::take(&mut self.scratch.spheres);
+		let mut sphere_batches = std::mem::take(&mut self.scratch.sphere_batches);
+		let mut candidates = std::mem::take(&mut self.scratch.candidates);
+		let mut simultaneous = std::mem::take(&mut self.scratch.simultaneous);
+		let mut known = std::mem::take(&mut self.scratch.known);
+		known.clear();
+		let mut collision_scratch = std::mem::take(&mut self.scratch.collision);
+		// How much impulse each collider that can break has taken (see PhysicsSystem::apply_breaks()). Usually nothing can break, so this is usually skipped.
+		let breakable = self.breakable_colliders();
+		let mut collider_impulses : HashMap<ColliderHandle, f32> = HashMap::new();
+		// Pairs that have already collided this step, where at least one doesn't respond. They'd just keep finding the same collision as they pass through each other, so are skipped for the rest of the step.
+		let mut phased : HashSet<(EntityHandle, EntityHandle)> = HashSet::new();
+		let (mut iterations, mut pairs_tested, mut collisions) = (0, 0, 0);
+		for iteration in 0..iteration_max {
+			if 0 < iteration && iteration_deadline.is_some_and(|deadline| deadline <= Instant::now()) {
+				self.debug.push(format!("Ran out of time after {} iterations!", iteration));
+				break;
+			}
+			iterations += 1;
 			// The simplest start is to find the closest collision, handle it, then move the simulation up to that point, and repeat looking for a collision.
 			// Will be "done" once no collisions left or run out of iterations.
 
-			// So start by going through every unique pair of handles and finding the first collision.
-			let mut earliest_collision_percent = 1.0; // Collisions must happen before 100% of time_left.
-			let mut earliest_collision = None;
-			let mut earliest_collision_restitution = 1.0;
-			let mut earliest_collision_static_friction_coefficient : f32 = 0.0;
-			let mut earliest_collision_dynamic_friction_coefficient : f32 = 0.0;
-			let mut earliest_collision_friction_threshold : f32 = 0.0;
-			let mut earliest_collision_first_entity_handle = None;
-			let mut earliest_collision_second_entity_handle = None;
-			let mut earliest_collision_first_info_index = 0;
-			let mut earliest_collision_second_info_index = 0;
-			// TODO: Someday optimize so it keeps track of collisions, and only calculates new collisions if one of the associated bodies has been modified by the last iteration.
+			profiler.begin(StepPhase::BroadPhase);
+			// Bound each entity with a moving sphere, so most pairs can be ruled out a batch at a time.
+			// Entities with only a single sphere (or plane) are also collided a batch at a time, so those are moved into world space too.
+			bounds.clear();
+			lone_shapes.clear();
+			spheres.clear();
+			{
+				let entities = self.entities.borrow();
+				let colliders = self.colliders.borrow();
+				for info in entity_info.iter() {
+					let entity = entities.get(info.handle).unwrap();
+					bounds.push((entity.orientation.position, info.linear_movement, Self::bounding_radius(entity, &colliders, &self.collider_motions)));
+					let lone_shape = Self::lone_shape(entity, info, &colliders, &self.collider_motions);
+					spheres.push(match lone_shape {
+						Some(LoneShape::Sphere { radius, center, movement, .. }) => (center, movement, radius),
+						_ => (Vec3::zeros(), Vec3::zeros(), f32::NEG_INFINITY), // Never touches anything.
+					});
+					lone_shapes.push(lone_shape);
+				}
+			}
+			SphereLanes::pack_into(&bounds, &mut bound_batches);
+			SphereLanes::pack_into(&spheres, &mut sphere_batches);
+			// Everything has moved since the last iteration, so none of the meshes' world space vertices can be re-used.
+			collision_scratch.forget_mesh_vertices();
+			profiler.end(StepPhase::BroadPhase);
+
+			profiler.begin(StepPhase::NarrowPhase);
+			// So start by going through every unique pair of handles and finding all of the collisions.
+			// Only pairs where at least one of the entities changed during the last iteration need to be checked again. The rest keep what was found last time.
+			candidates.clear();
+			let mut known_iter = known.drain(..).peekable();
 			for first_index in 0..entity_info.len() {
 				let (lower_entity_infos, upper_entity_infos) = entity_info.split_at_mut(first_index+1);
 				let first_entity_info = &mut lower_entity_infos[first_index];
+				let (first_center, first_movement, first_radius) = bounds[first_index];
+				let mut touching = [false; LANES];
+				let mut batched = HitTimes::new();
 				for second_offset_index in 0..upper_entity_infos.len() {
 					let second_index = first_index + second_offset_index + 1;
+					if 0 == second_offset_index || 0 == second_index % LANES {
+						touching = spheres_touch(&first_center, &first_movement, first_radius, &bound_batches[second_index / LANES]);
+						let others = &sphere_batches[second_index / LANES];
+						batched = match &lone_shapes[first_index] {
+							Some(LoneShape::Sphere { radius, center, movement, .. }) => collide_sphere_with_spheres(*radius, center, movement, others),
+							Some(LoneShape::Plane { position, normal, movement, .. }) => collide_plane_with_spheres(position, normal, movement, others),
+							None => HitTimes::new(),
+						};
+					}
+					// Pairs of lone shapes are fully collided along with the rest of their batch, so that alone says whether they hit. Everything else is only ruled out by the bounds.
+					let lone_pair = lone_shapes[first_index].is_some() && matches!(lone_shapes[second_index], Some(LoneShape::Sphere { .. }));
+					if !first_entity_info.changed && !upper_entity_infos[second_offset_index].changed {
+						while let Some(pending) = known_iter.next_if(|pending| pending.first_info_index == first_index && pending.second_info_index == second_index) {
+							candidates.push(pending);
+						}
+						continue;
+					}
+					if !(if lone_pair { batched.hits(second_index % LANES) } else { touching[second_index % LANES] }) { continue; }
 					let second_entity_info = &upper_entity_infos[second_offset_index];
 					let mut entities = self.entities.borrow_mut();
 					let (first_option, second_option) = entities.get2_mut(first_entity_info.handle, second_entity_info.handle);
@@ -420,13 +1932,16 @@ impl PhysicsSystem {
 
 					// Ignore the possible collisions if they're a part of the known collisions that were detected when the entity went to sleep.
 					if first.neighbors.contains(&second_entity_info.handle) {
-						println!("Skipping {:?} due to {:?}", second_entity_info.handle, first_entity_info.handle);
+						debug_print!("Skipping {:?} due to {:?}", second_entity_info.handle, first_entity_info.handle);
 						continue;
 					}
 					if second.neighbors.contains(&first_entity_info.handle) {
-						println!("Skipping {:?} due to {:?}", first_entity_info.handle, second_entity_info.handle);
+						debug_print!("Skipping {:?} due to {:?}", first_entity_info.handle, second_entity_info.handle);
 						continue;
 					}
+					// Nothing can happen when two things that can't be pushed around hit each other.
+					if !first.is_dynamic() && !second.is_dynamic() { continue; }
+					if phased.contains(&(first_entity_info.handle, second_entity_info.handle)) { continue; }
 
 					// Then check all colliders between the two entities.
 					for first_collider_handle in first.colliders.iter() {
@@ -434,77 +1949,169 @@ impl PhysicsSystem {
 							let colliders = self.colliders.borrow();
 							let first_collider_box  = colliders.get(*first_collider_handle ).unwrap();
 							let second_collider_box = colliders.get(*second_collider_handle).unwrap();
+							if !first_collider_box.collides() || !second_collider_box.collides() { continue; }
 
-							let first_start_orientation = first.orientation;
 							let first_end_orientation = first.orientation.after_affected(
 								&first_entity_info.linear_movement, &first_entity_info.angular_movement
 							);
-
-							let second_start_orientation = second.orientation;
 							let second_end_orientation = second.orientation.after_affected(
 								&second_entity_info.linear_movement, &second_entity_info.angular_movement
 							);
 
-							let collision_option = collide(
-								first_collider_box,
-								&first_start_orientation,
-								&first_end_orientation,
-								second_collider_box,
-								&second_start_orientation,
-								&second_end_orientation,
-							);
+							// Colliders moved by move_collider() start the rest of the step as far back along their motion as there's time left, which is the same as the whole entity starting back there (but only for this collider).
+							let first_motion  = self.collider_motions.get(first_collider_handle ).map_or(Vec3::zeros(), |motion| first.orientation.direction_into_world(motion));
+							let second_motion = self.collider_motions.get(second_collider_handle).map_or(Vec3::zeros(), |motion| second.orientation.direction_into_world(motion));
+							let mut first_start_orientation = first.orientation;
+							first_start_orientation.position -= first_motion * (1.0 - current_time_percent);
+							let mut second_start_orientation = second.orientation;
+							second_start_orientation.position -= second_motion * (1.0 - current_time_percent);
+							let surface_velocity = (first_motion - second_motion) / dt;
+
+							pairs_tested += 1;
+							// Pairs of lone shapes were already collided along with the rest of their batch (and each only has the one collider that collides).
+							let collision_option = if lone_pair {
+								let (others, lane) = (&sphere_batches[second_index / LANES], second_index % LANES);
+								match &lone_shapes[first_index] {
+									Some(LoneShape::Sphere { radius, center, movement, .. }) => sphere_with_spheres_collision(*radius, center, movement, others, &batched, lane),
+									Some(LoneShape::Plane { position, normal, movement, .. }) => plane_with_spheres_collision(position, normal, movement, others, &batched, lane),
+									None => None,
+								}
+							} else {
+								collide_reusing(
+									Some(*first_collider_handle),
+									first_collider_box,
+									&first_start_orientation,
+									&first_end_orientation,
+									Some(*second_collider_handle),
+									second_collider_box,
+									&second_start_orientation,
+									&second_end_orientation,
+									&mut collision_scratch,
+								)
+							};
 
-							if let Some(collision) = collision_option {
-								let time = collision.times.min();
+							if let Some(mut collision) = collision_option {
+								// Collisions must happen before 100% of time_left.
+								if 1.0 <= collision.times.min() { continue; }
+								let mut contact = ModifiableContact {
+									first_entity: first_entity_info.handle,
+									second_entity: second_entity_info.handle,
+									first_collider: *first_collider_handle,
+									second_collider: *second_collider_handle,
+									time: collision.times.min(),
+									position: collision.position,
+									normal: collision.normal,
+									restitution_coefficient: first_collider_box.get_restitution_coefficient() * second_collider_box.get_restitution_coefficient(),
+									static_friction_coefficient: first_collider_box.get_static_friction_coefficient() * second_collider_box.get_static_friction_coefficient(),
+									dynamic_friction_coefficient: first_collider_box.get_dynamic_friction_coefficient() * second_collider_box.get_dynamic_friction_coefficient(),
+									friction_threshold: first_collider_box.get_friction_threshold() * second_collider_box.get_friction_threshold(),
+								};
+								// Then swap in anything set for the pair of materials, and then the pair of entities.
+								let (first_material, second_material) = (first_collider_box.get_material(), second_collider_box.get_material());
+								let material_key = if first_material < second_material { (first_material, second_material) } else { (second_material, first_material) };
+								if let Some(properties) = self.material_pair_properties.get(&material_key) {
+									properties.apply_to(&mut contact);
+								}
+								if let Some(properties) = self.entity_pair_properties.get(&PhysicsSystem::pair_key(first_entity_info.handle, second_entity_info.handle)) {
+									properties.apply_to(&mut contact);
+								}
+								// Give any contact modifiers a chance to change things.
+								let mut keep = true;
+								for collider_handle in [*first_collider_handle, *second_collider_handle].iter() {
+									if let Some(modifier) = self.contact_modifiers.get_mut(collider_handle) {
+										keep = keep && modifier.modify(&mut contact);
+									}
+								}
+								if !keep || contact.normal.magnitude() < EPSILON { continue; }
+								collision.position = contact.position;
+								collision.normal = contact.normal.normalize();
 								// If the objects are (already) moving away from the point of contact, then ignore the collision.
-								let first_full_velocity = first.get_velocity_at_world_position(&collision.position);
-								let second_full_velocity = second.get_velocity_at_world_position(&collision.position);
-								let velocity_delta = first_full_velocity - second_full_velocity;
-								if EPSILON > velocity_delta.dot(&collision.normal) {
+								// Sleeping entities aren't actually moving (even though they pick up velocities while asleep).
+								let first_full_velocity = if first.asleep { Vec3::zeros() } else { first.get_scaled_velocity_at_world_position(&collision.position) };
+								let second_full_velocity = if second.asleep { Vec3::zeros() } else { second.get_scaled_velocity_at_world_position(&collision.position) };
+								let velocity_delta = first_full_velocity - second_full_velocity + surface_velocity;
+								if self.approach_velocity_threshold > velocity_delta.dot(&collision.normal) {
 									//self.debug.push(format!("Dropping collision at: {:?} between {:?} (velocity: {:?}) and {:?} (velocity: {:?}) normal={:?}", collision.position, first_collider_handle, first_full_velocity, second_collider_handle, second_full_velocity, collision.normal));
 									continue;
 								}
+								// If they're close enough to touching already, then treat it like they are.
+								if velocity_delta.dot(&collision.normal) * collision.times.min() * time_left <= self.contact_slop {
+									// The contact point was found for when they touch, so move it back to the start along with them. It mostly follows whichever is easier to push (e.g. a ball rolling on the ground keeps it right under its center).
+									let (first_weight, second_weight) = (first.get_inverse_mass(), second.get_inverse_mass());
+									if EPSILON < first_weight + second_weight {
+										let movement = (first_entity_info.linear_movement * first_weight + second_entity_info.linear_movement * second_weight) / (first_weight + second_weight);
+										collision.position -= movement * collision.times.min();
+									}
+									collision.times = Range::range(0.0, collision.times.max());
+								}
 
-								// Otherwise check if this collision is the closest.
-								if time < earliest_collision_percent {
-									earliest_collision_percent = time;
-									earliest_collision = Some(collision);
-									earliest_collision_restitution = first_collider_box.get_restitution_coefficient() *  second_collider_box.get_restitution_coefficient();
-									earliest_collision_static_friction_coefficient = first_collider_box.get_static_friction_coefficient() *  second_collider_box.get_static_friction_coefficient();
-									earliest_collision_dynamic_friction_coefficient = first_collider_box.get_dynamic_friction_coefficient() *  second_collider_box.get_dynamic_friction_coefficient();
-									earliest_collision_friction_threshold = first_collider_box.get_friction_threshold() *  second_collider_box.get_friction_threshold();
-									earliest_collision_first_entity_handle = Some(first_entity_info.handle);
-									earliest_collision_second_entity_handle = Some(second_entity_info.handle);
-									earliest_collision_first_info_index = first_index;
-									earliest_collision_second_info_index = second_index;
+								let mut anisotropic_frictions = [None; 2];
+								for ((collider_box, orientation), slot) in [(first_collider_box, &first_start_orientation), (second_collider_box, &second_start_orientation)].iter().zip(anisotropic_frictions.iter_mut()) {
+									*slot = collider_box.get_anisotropic_friction().map(|friction| AnisotropicFriction {
+										direction: orientation.direction_into_world(&friction.direction),
+										..friction
+									});
 								}
+								candidates.push(PendingCollision {
+									collision,
+									first_info_index: first_index,
+									second_info_index: second_index,
+									restitution_coefficient: contact.restitution_coefficient,
+									static_friction_coefficient: contact.static_friction_coefficient,
+									dynamic_friction_coefficient: contact.dynamic_friction_coefficient,
+									friction_threshold: contact.friction_threshold,
+									anisotropic_frictions,
+									surface_velocity,
+									surface_tags: [first_collider_box.get_surface_tag(), second_collider_box.get_surface_tag()],
+									colliders: [*first_collider_handle, *second_collider_handle],
+								});
 							}
 						}
 					}
 				}
 			}
 
-			// Wake up any entities that should be woken up due to the collision.
-			if let Some(entity_handle) = earliest_collision_first_entity_handle.clone() {
-				// Don't try to wake up any entities that have infinite mass.
-				let has_finite_mass = {
-					let entities = self.entities.borrow_mut();
-					let entity = entities.get(entity_handle).unwrap();
-					entity.get_total_mass().is_finite()
-				};
-				if has_finite_mass {
-					InternalEntity::wake_up(entity_handle, &mut self.entities.borrow_mut(), &mut self.debug);
+			drop(known_iter);
+			profiler.end(StepPhase::NarrowPhase);
+			profiler.begin(StepPhase::Response);
+			for info in &mut entity_info {
+				info.changed = false;
+			}
+
+			// Find the earliest collision. Then gather up every other collision that happens close enough to it to be considered simultaneous.
+			// The rest are kept around for the next iteration (in case the entities involved aren't changed by this one).
+			let mut earliest_collision_percent = 1.0;
+			for candidate in &candidates {
+				earliest_collision_percent = candidate.collision.times.min().min(earliest_collision_percent);
+			}
+			let simultaneous_percent = earliest_collision_percent + self.simultaneous_collision_time / time_left;
+			simultaneous.clear();
+			for candidate in candidates.drain(..) {
+				if candidate.collision.times.min() <= simultaneous_percent {
+					simultaneous.push(candidate);
+				} else {
+					known.push(candidate);
 				}
 			}
-			if let Some(entity_handle) = earliest_collision_second_entity_handle.clone() {
-				// Don't try to wake up any entities that have infinite mass.
-				let has_finite_mass = {
-					let entities = self.entities.borrow_mut();
-					let entity = entities.get(entity_handle).unwrap();
-					entity.get_total_mass().is_finite()
-				};
-				if has_finite_mass {
-					InternalEntity::wake_up(entity_handle, &mut self.entities.borrow_mut(), &mut self.debug);
+			// Group them by entity pair (keeping the order they were found in). They were found pair-by-pair, so each pair's collisions are already next to each other.
+			let same_pair = |a : &PendingCollision, b : &PendingCollision| a.first_info_index == b.first_info_index && a.second_info_index == b.second_info_index;
+
+			// Wake up any entities that should be woken up due to the collisions.
+			let mut woke_any = false;
+			for group in simultaneous.chunk_by(same_pair) {
+				for info_index in [group[0].first_info_index, group[0].second_info_index].iter() {
+					let entity_handle = entity_info[*info_index].handle;
+					// Don't try to wake up any entities that can't be pushed around.
+					let dynamic = self.entities.borrow().get(entity_handle).unwrap().is_dynamic();
+					if dynamic {
+						woke_any |= InternalEntity::wake_up(entity_handle, &mut self.entities.borrow_mut(), &mut self.debug);
+					}
+				}
+			}
+			// Waking things up changes which pairs are skipped (as neighbors), so just check everything again.
+			if woke_any {
+				for info in &mut entity_info {
+					info.changed = true;
 				}
 			}
 
@@ -513,144 +2120,291 @@ impl PhysicsSystem {
 			let after_collision_percent = 1.0 - earliest_collision_percent;
 			current_time_percent += (1.0 - current_time_percent) * earliest_collision_percent;
 			let time_after_collision = time_left * after_collision_percent;
-			println!("Iteration {} -> Advanced time by {}.", iteration, time_left - time_after_collision);
+			debug_print!("Iteration {} -> Advanced time by {}.", iteration, time_left - time_after_collision);
 			for info in &mut entity_info {
 				// Always advance the actual entity forward by time (to keep all the movement values in lock-step).
 				let entity = entities.get_mut(info.handle).unwrap();
 				// Don't bother if the entity is asleep.
 				if !entity.asleep {
+					let energy_before = if self.track_energy_drift { EnergyChange::of(entity) } else { EnergyChange::default() };
 					entity.orientation.affect_with(
 						&(info.linear_movement  * earliest_collision_percent),
 						&(info.angular_movement * earliest_collision_percent),
 					);
+					#[cfg(feature = "fixed-point")]
+					{
+						entity.fixed_position += info.fixed_movement.scale(Fixed::from_f32(earliest_collision_percent));
+						entity.orientation.position = entity.fixed_position.to_vec3();
+					}
+					if self.track_energy_drift {
+						let change = EnergyChange::of(entity) - energy_before;
+						info.drift.movement += change;
+						info.energy += change;
+					}
+				} else {
+					info.changed = true; // It didn't actually follow the movement its collisions were found with.
 				}
+				entity.step_path.push((current_time_percent * dt, entity.orientation));
 				info.linear_movement *= after_collision_percent;
+				#[cfg(feature = "fixed-point")]
+				{
+					info.fixed_movement = info.fixed_movement.scale(Fixed::from_f32(after_collision_percent));
+					info.linear_movement = info.fixed_movement.to_vec3();
+				}
 				info.angular_movement *= after_collision_percent;
 			}
 			time_left = time_after_collision;
 
-			// Then respond to the collision.
-			if let Some(collision) = earliest_collision {
-				println!("Iteration {} -> Found collision with {:?} and {:?}. {} time left.", iteration, earliest_collision_first_entity_handle, earliest_collision_second_entity_handle, time_left);
-				let first_entity_handle  = earliest_collision_first_entity_handle.unwrap();
-				let second_entity_handle = earliest_collision_second_entity_handle.unwrap();
-
-				let mut record = CollisionRecord {
-					first_entity : first_entity_handle,
-					second_entity : second_entity_handle,
-					position : collision.position.clone(),
-					time : current_time_percent * dt,
-					normal : collision.normal.clone(),
-
-					restitution_coefficient : earliest_collision_restitution,
-					impulse_magnitude : 0.0,
+			if simultaneous.is_empty() {
+				//self.debug.push(format!("Collisions handled after {} iterations.", iteration+1));
+				concluded = true;
+				profiler.end(StepPhase::Response);
+				break; // No collision means done handling the entire step. So quit out of this loop.
+			}
+			collisions += simultaneous.len();
+
+			// Then respond to the collisions. Each entity pair is handled as a single contact (so simultaneous hits don't add torque that shouldn't be there).
+			for group in simultaneous.chunk_by(same_pair) {
+				let first_info_index = group[0].first_info_index;
+				let second_info_index = group[0].second_info_index;
+				let first_entity_handle  = entity_info[first_info_index].handle;
+				let second_entity_handle = entity_info[second_info_index].handle;
+				debug_print!("Iteration {} -> Found {} collision(s) with {:?} and {:?}. {} time left.", iteration, group.len(), first_entity_handle, second_entity_handle, time_left);
+
+				// Merge all of the contacts into one.
+				let mut position = Vec3::zeros();
+				let mut normal = Vec3::zeros();
+				for pending in group {
+					position += pending.collision.position;
+					normal += pending.collision.normal;
+				}
+				position /= group.len() as f32;
+				normal = if EPSILON < normal.magnitude() { normal.normalize() } else { group[0].collision.normal };
+				let collision = Collision {
+					times: group[0].collision.times,
+					position,
+					normal,
+					features: group[0].collision.features,
 				};
+				let mut restitution_coefficient = group[0].restitution_coefficient;
+				let static_friction_coefficient = group[0].static_friction_coefficient;
+				let dynamic_friction_coefficient = group[0].dynamic_friction_coefficient;
+				let friction_threshold = group[0].friction_threshold;
+				let anisotropic_frictions = group[0].anisotropic_frictions;
+				let surface_velocity = group[0].surface_velocity;
 
 				let (first_option, second_option) = entities.get2_mut(first_entity_handle, second_entity_handle);
 				let mut first  = first_option.unwrap();
 				let mut second = second_option.unwrap();
 
-				// Then calculate the impulse.
-				let impulse = PhysicsSystem::calc_collision_impulse(
-					&first,
-					&second,
-					earliest_collision_restitution,
-					&collision,
-				);
-				record.impulse_magnitude = impulse.magnitude();
+				// An earlier contact this iteration might've already pushed the two apart.
+				let approaching_speed = (first.get_scaled_velocity_at_world_position(&collision.position) - second.get_scaled_velocity_at_world_position(&collision.position) + surface_velocity).dot(&collision.normal);
+				let still_approaching = self.approach_velocity_threshold < approaching_speed;
+				let is_resting = approaching_speed < self.resting_contact_threshold;
+				if is_resting || approaching_speed < self.restitution_velocity_threshold {
+					restitution_coefficient = 0.0;
+				} else {
+					restitution_coefficient = self.restitution_model.apply(restitution_coefficient, approaching_speed);
+				}
+
+				// Then solve for the impulse. The part along the normal stops (or bounces back) the approach, and friction resists the sliding. They're solved together, since an impulse at an offset spins things, so each part changes the velocity the other one works on.
+				let first_velocity  = first.get_scaled_velocity_at_world_position(&collision.position);
+				let second_velocity = second.get_scaled_velocity_at_world_position(&collision.position);
+				let sliding = {
+					let velocity_delta = first_velocity - second_velocity;
+					velocity_delta - collision.normal * velocity_delta.dot(&collision.normal)
+				};
+				let mut impulse = Vec3::zeros(); // Just the part along the normal.
+				let mut friction_impulse = Vec3::zeros();
+				if still_approaching {
+					let matrix = PhysicsSystem::calc_collision_matrix(first, second, &collision.position);
+					let normal_change = -(1.0 + restitution_coefficient) * approaching_speed;
+					// The impulse that stops all of the sliding (or as much of it as max_friction_percent allows), if friction were unlimited.
+					let sticking_impulse = matrix.try_inverse()
+						.map(|inverse| inverse * (collision.normal * normal_change - sliding * self.max_friction_percent))
+						.filter(|sticking| sticking.iter().all(|value| value.is_finite()));
+					if let Some(sticking_impulse) = sticking_impulse {
+						// The normal points from the first towards the second, so the first is pushed back against it.
+						let sticking_normal = -sticking_impulse.dot(&collision.normal);
+						let sticking_friction = sticking_impulse + collision.normal * sticking_normal;
+						// Anisotropic friction splits the friction into the part along the (first) friction direction and the part across it. Each part is limited separately, with its own scale on the friction coefficients.
+						let mut friction_parts_storage = [sticking_friction, Vec3::zeros()];
+						let mut friction_part_count = 1;
+						if let Some(anisotropic_friction) = anisotropic_frictions.iter().flatten().next() {
+							let direction = anisotropic_friction.direction - collision.normal * anisotropic_friction.direction.dot(&collision.normal);
+							if EPSILON < direction.magnitude() {
+								let along = direction.normalize() * sticking_friction.dot(&direction.normalize());
+								friction_parts_storage = [along, sticking_friction - along];
+								friction_part_count = 2;
+							}
+						}
+						let friction_parts = &friction_parts_storage[..friction_part_count];
+						let mut friction_scales = [1.0; 2];
+						for (part, scale) in friction_parts.iter().zip(friction_scales.iter_mut()) {
+							if part.magnitude() < EPSILON { continue; }
+							let part_direction = part.normalize();
+							*scale = anisotropic_frictions.iter().flatten().map(|friction| friction.get_scale(&part_direction)).product();
+						}
+						// Whether the sticking impulse is inside of the friction cone (i.e. friction with the coefficient can provide it).
+						let within_cone = |coefficient : f32| 0.0 < sticking_normal && friction_parts.iter().zip(friction_scales.iter()).all(
+							|(part, scale)| part.magnitude() <= sticking_normal * coefficient * scale
+						);
+						let (friction_coefficient, sticking) = match self.friction_model {
+							FrictionModel::Threshold => {
+								// NOTE: The below defaults to the dynamic friction coefficient if the ratio is junk.
+								let friction_coefficient = if (restitution_coefficient * approaching_speed).abs() / sliding.magnitude() < friction_threshold {
+									static_friction_coefficient
+								} else {
+									dynamic_friction_coefficient
+								};
+								(friction_coefficient, within_cone(friction_coefficient))
+							}
+							FrictionModel::Coulomb => {
+								let key = PhysicsSystem::pair_key(first_entity_handle, second_entity_handle);
+								let limit_coefficient = match self.friction_states.get(&key) {
+									Some(false) => dynamic_friction_coefficient,
+									_ => static_friction_coefficient,
+								};
+								let sticking = within_cone(limit_coefficient);
+								self.friction_states.insert(key, sticking);
+								(if sticking { static_friction_coefficient } else { dynamic_friction_coefficient }, sticking)
+							}
+						};
+						if sticking {
+							impulse = collision.normal * -sticking_normal;
+							friction_impulse = sticking_friction;
+						} else {
+							// Slipping, so the sticking impulse is projected onto the friction cone: friction pushes the way it would to stick, but only with the coefficient times the normal impulse (and never more than sticking needs, so it can't reverse the sliding). The normal impulse is then found again, so the normal velocity still comes out right with that friction.
+							let friction_per_normal : Vec3 = friction_parts.iter().zip(friction_scales.iter()).map(|(part, scale)| {
+								if part.magnitude() < EPSILON { return Vec3::zeros(); }
+								let mut amount = friction_coefficient * scale;
+								if 0.0 < sticking_normal { amount = amount.min(part.magnitude() / sticking_normal); }
+								part.normalize() * amount
+							}).sum();
+							let weight = collision.normal.dot(&(matrix * (collision.normal - friction_per_normal)));
+							let normal_magnitude = -normal_change / weight;
+							if EPSILON < weight && normal_magnitude.is_finite() && 0.0 <= normal_magnitude {
+								impulse = collision.normal * -normal_magnitude;
+								friction_impulse = friction_per_normal * normal_magnitude;
+							} else {
+								// The friction would pull them together (i.e. something spinning really fast), so just leave it out.
+								impulse = PhysicsSystem::calc_collision_impulse(&first, &second, restitution_coefficient, &collision, &surface_velocity);
+							}
+						}
+					} else {
+						// Can't solve for friction (i.e. neither can be spun), so just push them apart.
+						impulse = PhysicsSystem::calc_collision_impulse(&first, &second, restitution_coefficient, &collision, &surface_velocity);
+					}
+				}
 
 				//self.debug.push(format!("Before collision at {:?}: {:?} {:?}", collision.position, first.velocity, second.velocity));
 
+				let (first_pushed, second_pushed) = PhysicsSystem::pushed_by_each_other(first, second);
 				PhysicsSystem::apply_collision_impulse(
 					&mut first,
-					&mut entity_info[earliest_collision_first_info_index],
+					&mut entity_info[first_info_index],
 					&collision.position,
-					&impulse,
+					&(impulse + friction_impulse),
 					time_after_collision,
+					first_pushed,
 				);
 				PhysicsSystem::apply_collision_impulse(
 					&mut second,
-					&mut entity_info[earliest_collision_second_info_index],
+					&mut entity_info[second_info_index],
 					&collision.position,
-					&-impulse,
+					&-(impulse + friction_impulse),
 					time_after_collision,
+					second_pushed,
 				);
 
 				//self.debug.push(format!("After collision at {:?}: {:?} {:?}", collision.position, first.velocity, second.velocity));
 
-				let are_left_in_contact;
-				{// Then figure out friction and resting.
-					let first_velocity  = first.get_velocity_at_world_position(&collision.position);
-					let second_velocity = second.get_velocity_at_world_position(&collision.position);
-					let velocity_delta = first_velocity - second_velocity;
-					let normal_coincidence = velocity_delta.dot(&collision.normal);
-					are_left_in_contact = normal_coincidence.abs() < EPSILON; // If the resulting motion isn't moving much apart, then the two are considered "in contact" for the rest of the time step.
-					let sliding = velocity_delta - collision.normal * normal_coincidence;
-					let sliding_magnitude = sliding.magnitude();
-					// NOTE: The below defaults to the dynamic friction coefficient if the ratio is junk.
-					let friction_coefficient = if normal_coincidence.abs() / sliding_magnitude < earliest_collision_friction_threshold {
-						earliest_collision_static_friction_coefficient
-					} else {
-						earliest_collision_dynamic_friction_coefficient
-					};
-					let denominator = PhysicsSystem::calc_collision_impulse_denominator(first, second, &collision);
-					let max_friction_impulse = sliding_magnitude / denominator; // Divide by denominator so the mass/inertia split is reasonable.
-					let mut friction_percent : f32 = (impulse.magnitude() * friction_coefficient) / max_friction_impulse;
-					if friction_percent > 1.0 { friction_percent = 1.0; }
-					if !friction_percent.is_finite() { friction_percent = 0.0; }
-					let friction_impulse = sliding * -friction_percent;
-
-					PhysicsSystem::apply_collision_impulse(
-						&mut first,
-						&mut entity_info[earliest_collision_first_info_index],
-						&collision.position,
-						&friction_impulse,
-						time_after_collision,
-					);
-					PhysicsSystem::apply_collision_impulse(
-						&mut second,
-						&mut entity_info[earliest_collision_second_info_index],
-						&collision.position,
-						&-friction_impulse,
-						time_after_collision,
-					);
-				}
+				// If the resulting motion isn't moving much apart, then the two are considered "in contact" for the rest of the time step.
+				let normal_coincidence = (first.get_scaled_velocity_at_world_position(&collision.position) - second.get_scaled_velocity_at_world_position(&collision.position)).dot(&collision.normal);
+				let are_left_in_contact = is_resting || normal_coincidence.abs() < self.contact_velocity_threshold;
 
-				// Update the neighbors set.
-				if are_left_in_contact {
-					entity_info[earliest_collision_first_info_index].neighbors.insert(second_entity_handle);
-					entity_info[earliest_collision_second_info_index].neighbors.insert(first_entity_handle);
+				// Update the neighbors set. Things passing through each other can't rest on each other.
+				if !first.respond || !second.respond {
+					phased.insert((first_entity_handle, second_entity_handle));
+				} else if are_left_in_contact {
+					entity_info[first_info_index].neighbors.insert(second_entity_handle);
+					entity_info[second_info_index].neighbors.insert(first_entity_handle);
 				}
 
-				self.collision_records.push(record);
+				// Report every one of the contacts, splitting the impulse evenly between them.
+				// Always put the lower handle first, so the order doesn't depend on where the pair was found.
+				let impulse_magnitude = impulse.magnitude() / group.len() as f32;
+				let swapped = second_entity_handle < first_entity_handle;
+				for pending in group {
+					for collider in &pending.colliders {
+						if breakable.contains(collider) {
+							*collider_impulses.entry(*collider).or_insert(0.0) += impulse_magnitude;
+						}
+					}
+					let record = CollisionRecord {
+						first_entity : if swapped { second_entity_handle } else { first_entity_handle },
+						second_entity : if swapped { first_entity_handle } else { second_entity_handle },
+						position : pending.collision.position,
+						time : current_time_percent * dt,
+						normal : if swapped { -pending.collision.normal } else { pending.collision.normal },
+						first_surface_tag : pending.surface_tags[if swapped { 1 } else { 0 }],
+						second_surface_tag : pending.surface_tags[if swapped { 0 } else { 1 }],
+						first_feature : pending.collision.features[if swapped { 1 } else { 0 }],
+						second_feature : pending.collision.features[if swapped { 0 } else { 1 }],
 
-				//self.debug.push(format!("After friction energies: {:?} {:?}", first.get_total_energy(), second.get_total_energy()));
-			} else {
-				//self.debug.push(format!("Collisions handled after {} iterations.", iteration+1));
-				concluded = true;
-				break; // No collision means done handling the entire step. So quit out of this loop.
+						restitution_coefficient,
+						impulse_magnitude,
+					};
+					if self.record_limit.push(&mut self.collision_records, self.dropped_collision_records, record) {
+						self.dropped_collision_records += 1;
+					}
+				}
+
+				//self.debug.push(format!("After friction energies: {:?} {:?}", first.get_total_energy(), second.get_total_energy()));
 			}
+
+			// Any later collisions between entities that weren't changed will still happen, just relative to the time that's left now.
+			known.retain(|pending| !entity_info[pending.first_info_index].changed && !entity_info[pending.second_info_index].changed);
+			for pending in &mut known {
+				pending.collision.times = pending.collision.times.remap(earliest_collision_percent, 1.0);
+			}
+			profiler.end(StepPhase::Response);
 		}
 		if !concluded {
 			self.debug.push(format!("Ran out of iterations!"));
 		}
+		self.last_step_shortfall = if concluded { 0.0 } else { time_left };
+		self.track_energy_phase(&mut entity_info, |drift| &mut drift.collisions);
 
+		profiler.begin(StepPhase::Sleep);
 		// Put any entities to sleep if they have too little energy left.
 		for info in &mut entity_info {
 			let mut entities = self.entities.borrow_mut();
 			{
 				let entity = entities.get_mut(info.handle).unwrap();
+				if entity.asleep && entity.is_dynamic() {
+					// Whatever pushed on it was thrown away (along with its velocity).
+					entity.last_applied_force = Vec3::zeros();
+					entity.last_applied_torque = Vec3::zeros();
+				} else {
+					entity.last_applied_force = info.impulse / dt;
+					entity.last_applied_torque = info.angular_impulse / dt;
+				}
 				// Ignore entities that are already asleep.
 				if entity.asleep {
 					// Clear out any accumulated velocity.
 					entity.velocity = Vec3::zeros();
+					#[cfg(feature = "fixed-point")]
+					{ entity.fixed_velocity = FixedVec3::default(); }
 					entity.angular_velocity = Vec3::zeros();
 					continue;
 				}
 				// Then check if the energy left is small enough to put it to sleep.
-				let energy = entity.get_total_energy(); // TODO: Allow a way to calculate the energy relative to a reference frame. I.e. what if a box was "at rest" on the back of a car moving at a constant speed?
-				if energy > self.energy_sleep_threshold {
-					println!("Energy for {:?} is too high: {:?} > {:?} (velocity={:?}; angular_velocity={:?})", info.handle, energy, self.energy_sleep_threshold, entity.velocity, entity.angular_velocity);
+				// TODO: Allow a way to calculate the energy relative to a reference frame. I.e. what if a box was "at rest" on the back of a car moving at a constant speed?
+				let linear_energy = entity.get_linear_energy();
+				let angular_energy = entity.get_angular_energy();
+				if linear_energy > self.energy_sleep_threshold || angular_energy > self.angular_energy_sleep_threshold {
+					debug_print!("Energy for {:?} is too high: {:?} > {:?} or {:?} > {:?} (velocity={:?}; angular_velocity={:?})", info.handle, linear_energy, self.energy_sleep_threshold, angular_energy, self.angular_energy_sleep_threshold, entity.velocity, entity.angular_velocity);
 					// Make sure it's not considering falling asleep.
 					entity.falling_asleep = false;
 					entity.falling_asleep_time = 0.0;
@@ -660,73 +2414,602 @@ impl PhysicsSystem {
 
 				if entity.falling_asleep {
 					entity.falling_asleep_time += dt; // TODO: Could make this more precise and store time since started during this step() call...
-					println!("For {:?}: Adding {:?} to get {:?}", info.handle, dt, entity.falling_asleep_time);
+					debug_print!("For {:?}: Adding {:?} to get {:?}", info.handle, dt, entity.falling_asleep_time);
 				}
 				entity.falling_asleep = true;
 				if self.sleep_time_threshold > entity.falling_asleep_time {
-					println!("Entity {:?} is falling asleep. (Taken {:?} of {:?} seconds so far.)", info.handle, entity.falling_asleep_time, self.sleep_time_threshold);
+					debug_print!("Entity {:?} is falling asleep. (Taken {:?} of {:?} seconds so far.)", info.handle, entity.falling_asleep_time, self.sleep_time_threshold);
 					continue;
 				}
 
 				entity.asleep = true;
-				entity.neighbors = info.neighbors.clone();
-				println!("Putting {:?} to sleep", info.handle);
-				self.debug.push(format!("Putting {:?} to sleep (energy={:?}; neighbors={:?}; velocity={:?}; angular_velocity={:?}; position={:?})", info.handle, energy, info.neighbors.len(), entity.velocity, entity.angular_velocity, entity.orientation.position));
+				debug_print!("Putting {:?} to sleep", info.handle);
+				self.debug.push(format!("Putting {:?} to sleep (energy={:?} + {:?}; neighbors={:?}; velocity={:?}; angular_velocity={:?}; position={:?})", info.handle, linear_energy, angular_energy, info.neighbors.len(), entity.velocity, entity.angular_velocity, entity.orientation.position));
 			}
-			// If the entity went to sleep, then add it as a neighbor to the entities it neighbors.
+			// If the entity went to sleep, then link it with the (sleeping) entities it neighbors.
+			// Awake entities are left out, as they need to keep colliding with this one. They'll link up with this entity if/when they go to sleep too.
+			info.neighbors.retain(|handle| entities.get(*handle).unwrap().asleep);
 			for neighbor_handle in &info.neighbors {
 				let neighbor = entities.get_mut(*neighbor_handle).unwrap();
 				neighbor.neighbors.insert(info.handle);
 			}
+			std::mem::swap(&mut entities.get_mut(info.handle).unwrap().neighbors, &mut info.neighbors);
+		}
+		profiler.end(StepPhase::Sleep);
+		profiler.begin(StepPhase::Finish);
+		if self.track_energy_drift {
+			self.track_energy_phase(&mut entity_info, |drift| &mut drift.other);
+			self.energy_drift.add_step(entity_info.iter().map(|info| (info.handle, info.drift)));
+		}
+
+		// Hand everything step() used back to the scratch space, so the next call can reuse it.
+		for mut info in entity_info.drain(..).rev() { // Reversed so the next step() hands each entity the same set (assuming the entities haven't changed).
+			info.neighbors.clear();
+			self.scratch.neighbor_sets.push(info.neighbors);
+		}
+		self.scratch.entity_handles = entity_handles;
+		self.scratch.generator_handles = unary_force_generator_handles;
+		self.scratch.entity_info = entity_info;
+		self.scratch.bounds = bounds;
+		self.scratch.bound_batches = bound_batches;
+		self.scratch.lone_shapes = lone_shapes;
+		self.scratch.spheres = spheres;
+		self.scratch.sphere_batches = sphere_batches;
+		self.scratch.candidates = candidates;
+		self.scratch.simultaneous = simultaneous;
+		self.scratch.known = known;
+		self.scratch.collision = collision_scratch;
+
+		// Collisions at the same time are ordered by their pair of entities, so the records don't depend on the order that things were found in.
+		self.collision_records.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap_or(Ordering::Equal).then_with(|| (a.first_entity, a.second_entity).cmp(&(b.first_entity, b.second_entity))));
+		self.update_contacts(dt);
+		self.collider_motions.clear();
+		if self.merge_collision_records {
+			self.merge_records();
+		}
+		if 0.0 < self.min_record_impulse {
+			let min_record_impulse = self.min_record_impulse;
+			self.collision_records.retain(|record| min_record_impulse <= record.impulse_magnitude);
+		}
+		// Forget the stick/slip state of anything that's no longer touching.
+		let contacts = &self.contacts;
+		self.friction_states.retain(|(first, second), _| contacts.iter().any(|contact| contact.is_between(*first, *second)));
+
+		self.apply_breaks(&collider_impulses);
+		self.follow_attachments();
+		self.expire_entities(dt);
+		self.update_fluid_regions();
+
+		// Then move the cloths and particles against where everything ended up.
+		// (Taking an arena out allocates a new empty one, so skip that when there's nothing to move.)
+		if !self.cloths.is_empty() {
+			let mut cloths = std::mem::take(&mut self.cloths);
+			for (_, cloth) in cloths.iter_mut() {
+				cloth.step(self, dt);
+			}
+			self.cloths = cloths;
+		}
+		if !self.particle_sets.is_empty() {
+			let mut particle_sets = std::mem::take(&mut self.particle_sets);
+			for (_, particles) in particle_sets.iter_mut() {
+				particles.step(self, dt);
+			}
+			self.particle_sets = particle_sets;
+		}
+
+		// Mark everything that moved since the end of the last step (including anything teleported in between, or added since).
+		for (_, entity) in self.entities.borrow_mut().iter_mut() {
+			entity.moved = entity.last_step_orientation.is_none_or(|last| last.position != entity.orientation.position || last.rotation != entity.orientation.rotation);
+			entity.last_step_orientation = Some(entity.orientation);
+		}
+
+		if let Some(buffer) = self.double_buffer.as_mut() {
+			buffer.write(self.entities.borrow().iter().filter(|(_, entity)| entity.enabled).map(|(handle, entity)| (handle, entity.orientation)));
+		}
+		profiler.end(StepPhase::Finish);
+		profiler.count("entities", entity_count);
+		profiler.count("iterations", iterations);
+		profiler.count("pairs_tested", pairs_tested);
+		profiler.count("collisions", collisions as u64);
+		profiler.end_step();
+		self.profiler = profiler.0;
+	}
+
+	/// Adds up the forces from every generator and fluid region on an entity (where it is right now), as its linear acceleration and the torque on it.
+	fn find_acceleration(&self, handle : EntityHandle, generator_handles : &[UnaryForceGeneratorHandle], dt : f32) -> (Vec3, Vec3) {
+		let mut acceleration = Vec3::zeros();
+		let mut torque = Vec3::zeros();
+		let (dynamic, moment_of_inertia, view) = {
+			let entities = self.entities.borrow();
+			let entity = entities.get(handle).unwrap();
+			(entity.is_dynamic(), entity.get_moment_of_inertia(), EntityView::new(handle, entity))
+		};
+		let (total_mass, position, velocity, angular_velocity) = (view.mass, view.position(), view.velocity, view.angular_velocity);
+		// Forces only push dynamic entities around. Also skip zero masses (as there's no sensible acceleration for them).
+		if dynamic && EPSILON < total_mass {
+			for generator_handle in generator_handles {
+				let mut generators_borrow = self.unary_force_generators.borrow_mut();
+				let generator_borrow = generators_borrow.get_mut(*generator_handle).unwrap();
+				let mut force = generator_borrow.make_force(dt, self, &view);
+				force.force *= self.generator_scale(*generator_handle).unwrap_or(0.0);
+
+				acceleration += force.force.scale(1.0 / total_mass);
+				torque += moment_of_inertia * (force.position - position).cross(&force.force);
+			}
+			// Each submerged part is pushed up by the weight of the fluid it pushes aside, and dragged along with the flow.
+			for (_, region) in self.fluid_regions.iter() {
+				for (volume, center) in self.find_fluid_submerged(region, handle) {
+					let displaced = region.density * volume;
+					let point_velocity = velocity + angular_velocity.cross(&(center - position));
+					let force = -region.gravity * displaced + (region.flow_velocity - point_velocity) * (region.linear_drag * displaced);
+					acceleration += force.scale(1.0 / total_mass);
+					torque += moment_of_inertia * (center - position).cross(&force);
+					torque -= moment_of_inertia * angular_velocity * (region.angular_drag * displaced / total_mass);
+				}
+			}
+		}
+		(acceleration, torque)
+	}
+
+	/// Finds an entity's linear and ANGULAR acceleration as if it were moved from `start` by the given (linear and angular) movements, and had the given (linear and angular) velocities. The entity is put back how it was afterwards.
+	fn find_acceleration_at(&self, handle : EntityHandle, generator_handles : &[UnaryForceGeneratorHandle], dt : f32, start : &Orientation, movements : (Vec3, Vec3), velocities : (Vec3, Vec3)) -> (Vec3, Vec3) {
+		let saved = {
+			let mut entities = self.entities.borrow_mut();
+			let entity = entities.get_mut(handle).unwrap();
+			let saved = (entity.orientation, entity.velocity, entity.angular_velocity);
+			entity.orientation = *start;
+			entity.orientation.affect_with(&movements.0, &movements.1);
+			entity.velocity = velocities.0;
+			entity.angular_velocity = velocities.1;
+			saved
+		};
+		let (acceleration, torque) = self.find_acceleration(handle, generator_handles, dt);
+		let mut entities = self.entities.borrow_mut();
+		let entity = entities.get_mut(handle).unwrap();
+		let angular_acceleration = entity.get_inverse_moment_of_inertia() * torque;
+		entity.orientation = saved.0;
+		entity.velocity = saved.1;
+		entity.angular_velocity = saved.2;
+		(acceleration, angular_acceleration)
+	}
+
+	/// When tracking energy drift, adds how much each entity's energy has changed since it was last measured to one part of its drift (picked by `phase`).
+	fn track_energy_phase<F>(&self, entity_info : &mut [EntityStepInfo], phase : F) where F : Fn(&mut EnergyDrift) -> &mut EnergyChange {
+		if !self.track_energy_drift { return; }
+		let entities = self.entities.borrow();
+		for info in entity_info {
+			let energy = EnergyChange::of(entities.get(info.handle).unwrap());
+			*phase(&mut info.drift) += energy - info.energy;
+			info.energy = energy;
+		}
+	}
+
+	/// Gets everything tracked about energy drift so far. Empty unless `track_energy_drift` is on.
+	pub fn get_energy_drift(&self) -> &EnergyDriftReport { &self.energy_drift }
+
+	/// Forgets everything tracked about energy drift so far (i.e. to start over after things have settled).
+	pub fn reset_energy_drift(&mut self) {
+		self.energy_drift = EnergyDriftReport::default();
+	}
+
+	/// Sets (or clears) what step() reports each of its phases to, i.e. to find out which part of it is slow. Returns the prior profiler (if there was one).
+	///
+	/// Copies of the system (made with `clone()`) start without one.
+	pub fn set_profiler(&mut self, profiler : Option<Box<dyn Profiler>>) -> Option<Box<dyn Profiler>> {
+		std::mem::replace(&mut self.profiler, profiler)
+	}
+
+	/// Gets the profiler (if any), i.e. to read a [crate::PhaseTimer]'s totals with `downcast_ref()`.
+	pub fn get_profiler(&self) -> Option<&dyn Profiler> {
+		self.profiler.as_deref()
+	}
+
+	/// Gets the profiler (if any) so it can be changed, i.e. to reset it.
+	pub fn get_profiler_mut(&mut self) -> Option<&mut dyn Profiler> {
+		self.profiler.as_deref_mut()
+	}
+
+	/// Turns on double buffering (if it isn't already on), and returns a new reader for it.
+	///
+	/// While on, each step() writes every enabled entity's final orientation into a back buffer, and [PhysicsSystem::publish] swaps that to the front where the readers can see it. So other threads (i.e. a renderer) can read a consistent set of orientations without waiting on step().
+	pub fn enable_double_buffering(&mut self) -> SnapshotReader {
+		self.double_buffer.get_or_insert_with(DoubleBuffer::new).make_reader()
+	}
+
+	/// Turns off double buffering. Existing readers keep the last snapshot that was published.
+	pub fn disable_double_buffering(&mut self) {
+		self.double_buffer = None;
+	}
+
+	/// Makes the last step()'s results visible to all of the double buffering readers. Does nothing if there hasn't been a step() since the last call.
+	///
+	/// Fails if double buffering isn't on.
+	pub fn publish(&mut self) -> Result<(),()> {
+		self.double_buffer.as_mut().ok_or(())?.publish(); // TODO: An error type.
+		Ok(())
+	}
+
+	/// Writes out all of the entities and colliders (along with gravity and material properties) as a [Scene]. Only available with the `scene` feature.
+	#[cfg(feature = "scene")]
+	pub fn to_scene(&self) -> Scene {
+		let mut scene = Scene::default();
+		let entity_handles : Vec<EntityHandle> = self.entities.borrow().iter().map(|(handle, _)| handle).collect();
+		for handle in entity_handles {
+			let mut scene_entity = SceneEntity::from_entity(&self.get_entity(handle).unwrap());
+			let mut collider_handles : Vec<ColliderHandle> = self.entities.borrow().get(handle).unwrap().colliders.iter().copied().collect();
+			collider_handles.sort(); // So the text doesn't depend on hashing.
+			scene_entity.colliders = collider_handles.into_iter().map(|collider_handle| self.get_collider(collider_handle).unwrap()).collect();
+			scene.entities.push(scene_entity);
+		}
+		let loose_handles : Vec<ColliderHandle> = self.colliders.borrow_mut().iter_mut().filter_map(|(handle, collider)| if collider.get_entity().is_none() { Some(handle) } else { None }).collect();
+		scene.loose_colliders = loose_handles.into_iter().map(|handle| self.get_collider(handle).unwrap()).collect();
+		for (_, generator) in self.unary_force_generators.borrow().iter() {
+			if let Some(gravity) = generator.downcast_ref::<GravityGenerator>() {
+				scene.gravity.push(gravity.acceleration);
+			}
+		}
+		scene.material_pairs = self.material_pair_properties.iter().map(|((first, second), properties)| (*first, *second, *properties)).collect();
+		scene.material_pairs.sort_by_key(|(first, second, _)| (*first, *second));
+		scene
+	}
+
+	/// Creates a new system holding everything in the [Scene]. Fails if anything in it isn't valid. Only available with the `scene` feature.
+	#[cfg(feature = "scene")]
+	pub fn from_scene(scene : &Scene) -> Result<PhysicsSystem, ()> {
+		let mut system = PhysicsSystem::new();
+		for scene_entity in &scene.entities {
+			let entity_handle = system.add_entity(scene_entity.make_entity())?;
+			for source in &scene_entity.colliders {
+				let collider_handle = system.add_collider(source.clone())?;
+				system.link_collider(collider_handle, Some(entity_handle))?;
+			}
+			system.set_entity_enabled(entity_handle, scene_entity.enabled)?;
+		}
+		for source in &scene.loose_colliders {
+			system.add_collider(source.clone())?;
+		}
+		for acceleration in &scene.gravity {
+			system.add_unary_force_generator(Box::new(GravityGenerator::new(*acceleration)))?;
+		}
+		for (first, second, properties) in &scene.material_pairs {
+			system.set_material_pair_properties(*first, *second, Some(*properties))?;
+		}
+		Ok(system)
+	}
+
+	/// Writes out all of the entities and colliders (along with gravity and material properties) as JSON text. See [PhysicsSystem::to_scene]. Only available with the `scene` feature.
+	#[cfg(feature = "scene")]
+	pub fn to_scene_string(&self) -> String {
+		serde_json::to_string_pretty(&self.to_scene()).unwrap() // Nothing in a scene can fail to serialize.
+	}
+
+	/// Creates a new system from JSON text (i.e. from [PhysicsSystem::to_scene_string]). Fails if the text can't be parsed or anything in it isn't valid. Only available with the `scene` feature.
+	#[cfg(feature = "scene")]
+	pub fn from_scene_str(source : &str) -> Result<PhysicsSystem, ()> {
+		let scene : Scene = serde_json::from_str(source).map_err(|_| ())?; // TODO: An error type.
+		PhysicsSystem::from_scene(&scene)
+	}
+
+	/// Gets the radius of a sphere (around the entity's center of mass) that holds all of the entity's colliders, no matter how it rotates.
+	///
+	/// Spheres, aligned boxes, and meshes are bounded. Entities with any other kind of collider (i.e. planes and triangle meshes, which are usually huge) get an infinite radius. Disabled and query-only colliders are left out, as they never collide. Colliders moved with [PhysicsSystem::move_collider] are bounded along their whole motion.
+	fn bounding_radius(entity : &InternalEntity, colliders : &Arena<Box<dyn InternalCollider>>, motions : &HashMap<ColliderHandle, Vec3>) -> f32 {
+		// How far a point (in the entity's local space) is from the center of mass.
+		let reach = |point : &Vec3| (entity.orientation.position_into_world(point) - entity.orientation.position).magnitude();
+		let mut radius : f32 = 0.0;
+		for collider_handle in entity.colliders.iter() {
+			let collider = colliders.get(*collider_handle).unwrap();
+			if !collider.collides() { continue; }
+			let size = if let Some(sphere) = collider.downcast_ref::<InternalSphereCollider>() {
+				reach(&sphere.center) + sphere.radius
+			} else if let Some(aligned_box) = collider.downcast_ref::<InternalAlignedBoxCollider>() {
+				let (low, high) = (aligned_box.min_corner, aligned_box.max_corner);
+				(0..8).map(|corner| reach(&Vec3::new(
+					if 0 == corner & 1 { low.x } else { high.x },
+					if 0 == corner & 2 { low.y } else { high.y },
+					if 0 == corner & 4 { low.z } else { high.z },
+				))).fold(0.0, f32::max)
+			} else if let Some(mesh) = collider.downcast_ref::<InternalMeshCollider>() {
+				mesh.vertices.iter().map(|vertex| reach(&(mesh.position + vertex))).fold(0.0, f32::max)
+			} else {
+				return f32::INFINITY;
+			};
+			let motion = motions.get(collider_handle).map_or(0.0, |motion| motion.magnitude());
+			radius = radius.max(size + collider.get_margin() + motion);
+		}
+		radius
+	}
+
+	/// Gets the entity's only collider (moved into world space for the rest of the step), if it's one that the batched checks handle: a sphere or plane without a margin that isn't being moved with [PhysicsSystem::move_collider].
+	fn lone_shape(entity : &InternalEntity, info : &EntityStepInfo, colliders : &Arena<Box<dyn InternalCollider>>, motions : &HashMap<ColliderHandle, Vec3>) -> Option<LoneShape> {
+		let mut found = None;
+		for collider_handle in entity.colliders.iter() {
+			if !colliders.get(*collider_handle).unwrap().collides() { continue; }
+			if found.is_some() { return None; }
+			found = Some(*collider_handle);
+		}
+		let handle = found?;
+		let collider = colliders.get(handle).unwrap();
+		if 0.0 != collider.get_margin() || motions.contains_key(&handle) { return None; }
+		let start = entity.orientation;
+		let end = start.after_affected(&info.linear_movement, &info.angular_movement);
+		if let Some(sphere) = collider.downcast_ref::<InternalSphereCollider>() {
+			let center = start.position_into_world(&sphere.center);
+			Some(LoneShape::Sphere { radius: sphere.radius, center, movement: end.position_into_world(&sphere.center) - center })
+		} else if let Some(plane) = collider.downcast_ref::<InternalPlaneCollider>() {
+			let position = start.position_into_world(&plane.position);
+			Some(LoneShape::Plane { position, normal: plane.normal, movement: end.position_into_world(&plane.position) - position })
+		} else {
+			None
+		}
+	}
+
+	/// Checks whether the contact between two entities is sticking (true) or slipping (false).
+	///
+	/// Only tracked with [FrictionModel::Coulomb]. Returns None if the pair isn't in contact (or the state isn't being tracked).
+	pub fn is_sticking(&self, first : EntityHandle, second : EntityHandle) -> Option<bool> {
+		self.friction_states.get(&PhysicsSystem::pair_key(first, second)).copied()
+	}
+
+	/// Gets all of the collisions that the given entity had during the last `step()`, as seen from that entity (i.e. so their normals point off of it).
+	///
+	/// These are in the same order as [PhysicsSystem::collision_records].
+	pub fn collisions_for(&self, entity : EntityHandle) -> Vec<ContactPair> {
+		self.collision_records.iter().filter_map(|record| record.relative_to(entity)).collect()
+	}
+
+	/// Gets all of the contacts that the given entity is a part of, as seen from that entity (i.e. so their normals point off of it).
+	pub fn contacts_for(&self, entity : EntityHandle) -> Vec<ContactPair> {
+		self.contacts.iter().filter_map(|contact| contact.relative_to(entity)).collect()
+	}
+
+	/// Gets the contact graph from the last `step()`: every entity, along with the contacts between them. See [ContactGraph].
+	pub fn contact_graph(&self) -> ContactGraph {
+		let entities : Vec<(EntityHandle, BodyType, bool)> = self.entities.borrow().iter().map(|(handle, entity)| (handle, entity.get_body_type(), entity.asleep)).collect();
+		ContactGraph::new(&entities, &self.contacts)
+	}
+
+	/// Gets the total force that the force generators would put on something with the given mass at the given position, without needing an entity there. i.e. for drawing gravity and wind volumes, or checking that generators are set up right.
+	///
+	/// Each generator is asked about a made up entity (that isn't in the system) with a `dt` of zero. So generators that only push on specific entities (like [crate::AnchoredSpringGenerator]) give nothing. Generators that are turned off are skipped, and the rest are scaled by their strengths. Fails if the mass isn't valid.
+	pub fn sample_forces(&self, position : &Vec3, mass : f32) -> Result<Vec3, ()> {
+		let probe = EntityView::probe(position, mass)?;
+		let mut total = Vec3::zeros();
+		for (handle, generator) in self.unary_force_generators.borrow_mut().iter_mut() {
+			if let Some(scale) = self.generator_scale(handle) {
+				total += generator.make_force(0.0, self, &probe).force * scale;
+			}
+		}
+		Ok(total)
+	}
+
+	/// Samples the force generators (see [PhysicsSystem::sample_forces]) at every point of an evenly spaced grid, i.e. to export them as a vector field.
+	///
+	/// The grid goes from `min` to `max` (inclusive), with `counts` points along each axis (a count of one puts them all at `min` along that axis). Returns each point along with the force there, in X then Y then Z order (so X changes fastest). Fails if the mass isn't valid.
+	pub fn sample_force_grid(&self, min : &Vec3, max : &Vec3, counts : [usize; 3], mass : f32) -> Result<Vec<(Vec3, Vec3)>, ()> {
+		let step = |axis : usize| if 1 < counts[axis] { (max[axis] - min[axis]) / (counts[axis] - 1) as f32 } else { 0.0 };
+		let steps = Vec3::new(step(0), step(1), step(2));
+		let mut samples = Vec::with_capacity(counts[0] * counts[1] * counts[2]);
+		for z in 0..counts[2] {
+			for y in 0..counts[1] {
+				for x in 0..counts[0] {
+					let position = min + steps.component_mul(&Vec3::new(x as f32, y as f32, z as f32));
+					samples.push((position, self.sample_forces(&position, mass)?));
+				}
+			}
+		}
+		Ok(samples)
+	}
+
+	/// Writes out a readable listing of every entity, collider, and contact. Meant for printing when a test fails, so the format may change.
+	pub fn dump_state(&self) -> String {
+		fn vec(value : &Vec3) -> String { format!("({:.4}, {:.4}, {:.4})", value.x, value.y, value.z) }
+		let mut out = String::new();
+		{
+			let entities = self.entities.borrow();
+			out += &format!("Entities ({}):\n", entities.len());
+			for (handle, entity) in entities.iter() {
+				out += &format!("  {:?}", handle);
+				if let Some(name) = &entity.name { out += &format!(" {:?}", name); }
+				out += &format!(" {:?}{}{}\n", entity.body_type, if entity.asleep { " asleep" } else { "" }, if entity.enabled { "" } else { " disabled" });
+				out += &format!("    position: {}  rotation: {}\n", vec(&entity.orientation.position), vec(&entity.orientation.rotation.scaled_axis()));
+				out += &format!("    velocity: {}  angular_velocity: {}\n", vec(&entity.velocity), vec(&entity.angular_velocity));
+				let mut colliders : Vec<ColliderHandle> = entity.colliders.iter().copied().collect();
+				colliders.sort();
+				out += &format!("    mass: {}  colliders: {:?}\n", entity.get_total_mass(), colliders);
+			}
+		}
+		{
+			let mut colliders = self.colliders.borrow_mut();
+			out += &format!("Colliders ({}):\n", colliders.len());
+			for (handle, collider) in colliders.iter_mut() {
+				out += &format!("  {:?} {:?} entity: {:?}  local_position: {}  mass: {}  material: {}{}{}\n",
+					handle,
+					collider.get_type(),
+					collider.get_entity(),
+					vec(&collider.get_local_position()),
+					collider.get_mass(),
+					collider.get_material(),
+					if collider.is_enabled() { "" } else { " disabled" },
+					if collider.is_query_only() { " query_only" } else { "" },
+				);
+			}
+		}
+		out += &format!("Contacts ({}):\n", self.contacts.len());
+		for contact in &self.contacts {
+			out += &format!("  {:?} <-> {:?}  position: {}  normal: {}  force: {}\n", contact.first_entity, contact.second_entity, vec(&contact.position), vec(&contact.normal), contact.force_magnitude);
+		}
+		out
+	}
+
+	/// Makes a key for a pair of handles that doesn't depend on their order.
+	fn pair_key(first : EntityHandle, second : EntityHandle) -> (EntityHandle, EntityHandle) {
+		if first < second { (first, second) } else { (second, first) }
+	}
+
+	/// Merges all of the collision records for each pair of entities into the pair's earliest one (keeping the largest impulse). The records must already be sorted.
+	fn merge_records(&mut self) {
+		let kept = &mut self.scratch.record_pairs;
+		kept.clear();
+		// Done in place (moving each kept record down over the merged ones), so nothing's allocated.
+		let mut count = 0;
+		for index in 0..self.collision_records.len() {
+			let record = &self.collision_records[index];
+			if let Some(earliest) = kept.get(&(record.first_entity, record.second_entity)) {
+				let impulse_magnitude = record.impulse_magnitude;
+				let earliest = &mut self.collision_records[*earliest];
+				earliest.impulse_magnitude = earliest.impulse_magnitude.max(impulse_magnitude);
+			} else {
+				kept.insert((record.first_entity, record.second_entity), count);
+				self.collision_records.swap(count, index);
+				count += 1;
+			}
 		}
+		self.collision_records.truncate(count);
+	}
+
+	/// Rebuilds the contact cache from this step's collision records.
+	fn update_contacts(&mut self, dt : f32) {
+		let mut contacts = std::mem::take(&mut self.scratch.contacts);
+		contacts.clear();
+		for record in &self.collision_records {
+			let index = if let Some(index) = contacts.iter().position(|contact| contact.is_between(record.first_entity, record.second_entity)) {
+				index
+			} else {
+				contacts.push(Contact {
+					first_entity: record.first_entity,
+					second_entity: record.second_entity,
+					position: Vec3::zeros(),
+					normal: record.normal,
+					impulse_magnitude: 0.0,
+					force_magnitude: 0.0,
+				});
+				contacts.len() - 1
+			};
+			let contact = &mut contacts[index];
+			contact.position += record.position * record.impulse_magnitude;
+			contact.impulse_magnitude += record.impulse_magnitude;
+			if contact.first_entity == record.first_entity {
+				contact.normal = record.normal;
+			} else {
+				contact.normal = -record.normal;
+			}
+		}
+		for contact in &mut contacts {
+			if EPSILON < contact.impulse_magnitude {
+				contact.position /= contact.impulse_magnitude;
+			} else {
+				// No impulse to weight by, so just use the last position.
+				contact.position = self.collision_records.iter().rev().find(|record| contact.is_between(record.first_entity, record.second_entity)).unwrap().position;
+			}
+			contact.force_magnitude = contact.impulse_magnitude / dt;
+		}
+		// Keep the old contacts between pairs that are still resting on each other (but weren't simulated).
+		let entities = self.entities.borrow();
+		for old in self.contacts.drain(..) {
+			if contacts.iter().any(|contact| contact.is_between(old.first_entity, old.second_entity)) { continue; }
+			let still_resting = match (entities.get(old.first_entity), entities.get(old.second_entity)) {
+				(Some(first), Some(second)) => first.neighbors.contains(&old.second_entity) || second.neighbors.contains(&old.first_entity),
+				_ => false,
+			};
+			if still_resting {
+				contacts.push(old);
+			}
+		}
+		self.scratch.contacts = std::mem::replace(&mut self.contacts, contacts);
 	}
 
 	fn calc_collision_impulse_denominator(first : &InternalEntity, second : &InternalEntity, collision : &Collision) -> f32 {
 		let first_offset  = collision.position - first.orientation.position;
 		let second_offset = collision.position - second.orientation.position;
 
-		let first_linear_weight   = 1.0 / first.get_total_mass();
-		let second_linear_weight  = 1.0 / second.get_total_mass();
+		let first_linear_weight   = first.get_inverse_mass();
+		let second_linear_weight  = second.get_inverse_mass();
 		let first_angular_amount = first.get_inverse_moment_of_inertia()   * first_offset.cross( &collision.normal);
 		let first_angular_weight  = first_angular_amount.cross(&first_offset).dot( &collision.normal);
 		let second_angular_amount = second.get_inverse_moment_of_inertia() * second_offset.cross(&collision.normal);
 		let second_angular_weight = second_angular_amount.cross(&second_offset).dot(&collision.normal);
-		first_linear_weight + second_linear_weight + first_angular_weight + second_angular_weight
+		// Collisions work with how fast things actually move (see get_scaled_velocity_at_world_position()), and an impulse only changes that by the entity's time scale.
+		// Anything with a higher dominance is treated as if it had infinite mass.
+		let first_weight  = if first.dominance  > second.dominance { 0.0 } else { (first_linear_weight  + first_angular_weight ) * first.time_scale  };
+		let second_weight = if second.dominance > first.dominance  { 0.0 } else { (second_linear_weight + second_angular_weight) * second.time_scale };
+		first_weight + second_weight
+	}
+
+	/// Calculates how an impulse at a position changes how fast the first entity is moving relative to the second there (when the first is pushed by the impulse and the second by the opposite). The change is this matrix times the impulse.
+	///
+	/// Along the normal, this is the same as [PhysicsSystem::calc_collision_impulse_denominator].
+	fn calc_collision_matrix(first : &InternalEntity, second : &InternalEntity, position : &Vec3) -> Mat3 {
+		let weight = |entity : &InternalEntity, other : &InternalEntity| {
+			// Anything with a higher dominance is treated as if it had infinite mass.
+			if entity.dominance > other.dominance { return Mat3::zeros(); }
+			let offset = (position - entity.orientation.position).cross_matrix();
+			(Mat3::identity() * entity.get_inverse_mass() - offset * entity.get_inverse_moment_of_inertia() * offset) * entity.time_scale
+		};
+		weight(first, second) + weight(second, first)
+	}
+
+	/// Whether a collision's impulse should change each of the two entities' velocities: not if it doesn't respond, or it has a higher dominance than the other.
+	fn pushed_by_each_other(first : &InternalEntity, second : &InternalEntity) -> (bool, bool) {
+		(
+			first.respond  && first.dominance  <= second.dominance,
+			second.respond && second.dominance <= first.dominance,
+		)
 	}
 
 	/// Calculates the collision impulse between two entities.
-	fn calc_collision_impulse(first : &InternalEntity, second : &InternalEntity, restitution_coefficient : f32, collision : &Collision) -> Vec3 {
+	///
+	/// The `surface_velocity` is added to how fast the first is moving relative to the second (see [PendingCollision::surface_velocity]).
+	fn calc_collision_impulse(first : &InternalEntity, second : &InternalEntity, restitution_coefficient : f32, collision : &Collision, surface_velocity : &Vec3) -> Vec3 {
 
-		let first_full_velocity  = first.get_velocity_at_world_position( &collision.position);
-		let second_full_velocity = second.get_velocity_at_world_position(&collision.position);
-		let velocity_delta = first_full_velocity - second_full_velocity;
+		let first_full_velocity  = first.get_scaled_velocity_at_world_position( &collision.position);
+		let second_full_velocity = second.get_scaled_velocity_at_world_position(&collision.position);
+		let velocity_delta = first_full_velocity - second_full_velocity + surface_velocity;
 
 		// First find the collision response along the normal.
 		let normal_coincidence = velocity_delta.dot(&collision.normal);
 		let numerator = -(1.0 + restitution_coefficient) * normal_coincidence;
 		let denominator = PhysicsSystem::calc_collision_impulse_denominator(first, second, collision);
+		// Neither can be pushed (or the masses are junk), so don't give them junk velocities.
+		if !(EPSILON < denominator && denominator.is_finite()) { return Vec3::zeros(); }
 		let normal_impulse_magnitude = numerator / denominator;
 		collision.normal.scale(normal_impulse_magnitude)
 	}
 
-	/// Applies a collision impulse.
-	fn apply_collision_impulse(entity : &mut InternalEntity, entity_step_info : &mut EntityStepInfo, collision_position : &Vec3, impulse : &Vec3, remaining_time : f32) {
-
-		entity.apply_impulse(&collision_position, &impulse);
+	/// Applies a collision impulse. Entities that aren't `pushed` (see [PhysicsSystem::pushed_by_each_other]) just carry on.
+	fn apply_collision_impulse(entity : &mut InternalEntity, entity_step_info : &mut EntityStepInfo, collision_position : &Vec3, impulse : &Vec3, remaining_time : f32, pushed : bool) {
+		if pushed {
+			entity.apply_impulse(&collision_position, &impulse);
+			entity_step_info.impulse += impulse;
+			entity_step_info.angular_impulse += (collision_position - entity.orientation.position).cross(impulse);
+		}
 
-		entity_step_info.linear_movement = entity.velocity * remaining_time;
-		entity_step_info.angular_movement = entity.angular_velocity * remaining_time;
+		entity_step_info.linear_movement = entity.velocity * (remaining_time * entity.time_scale);
+		#[cfg(feature = "fixed-point")]
+		{
+			entity_step_info.fixed_movement = entity.fixed_velocity.scale(Fixed::from_f32(remaining_time * entity.time_scale));
+			entity_step_info.linear_movement = entity_step_info.fixed_movement.to_vec3();
+		}
+		entity_step_info.angular_movement = entity.angular_velocity * (remaining_time * entity.time_scale);
+		entity_step_info.changed = true;
 	}
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use std::f32::INFINITY;
+	use std::f32::{INFINITY, NAN};
+	use std::sync::Arc;
 	use crate::null_collider::NullCollider;
 	use crate::sphere_collider::SphereCollider;
 	use crate::plane_collider::PlaneCollider;
+	use crate::mesh_collider::MeshCollider;
+	use crate::mesh_feature::MeshFeature;
 	use crate::gravity_generator::GravityGenerator;
+	use crate::anchored_spring_generator::AnchoredSpringGenerator;
+	use crate::world_query::WorldQuery;
+	use crate::force::Force;
+	use crate::restitution_model::RestitutionCurve;
 
 	/// Verify can create/store/remove entities.
 	#[test]
@@ -1413,6 +3696,164 @@ mod tests {
 		}
 	}
 
+	/// Check that collider margins keep a resting ball that far above the ground.
+	#[test]
+	fn margins_keep_things_apart() {
+		const RADIUS : f32 = 1.0;
+		const MARGIN : f32 = 0.05;
+		let mut system = PhysicsSystem::new();
+		let handle = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 3.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			//
+			let mut sphere = SphereCollider::new(RADIUS);
+			sphere.mass = 1.0;
+			sphere.restitution_coefficient = 0.0;
+			sphere.margin = MARGIN;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+
+			entity_handle
+		};
+		{
+			let entity_handle = system.add_entity(Entity::new()).unwrap();
+			let mut plane = PlaneCollider::new();
+			plane.normal = Vec3::y();
+			plane.mass = INFINITY;
+			plane.margin = MARGIN;
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+		}
+
+		system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -1.0, 0.0)))).unwrap();
+
+		for _ in 0..250 {
+			system.step(0.1);
+		}
+
+		{
+			let position = system.get_entity(handle).unwrap().position;
+			println!("Final position: {:?}", position);
+			assert!((position - Vec3::new(0.0, RADIUS + 2.0 * MARGIN, 0.0)).magnitude() < system.contact_slop + EPSILON);
+		}
+		// Negative margins aren't allowed.
+		let mut sphere = SphereCollider::new(RADIUS);
+		sphere.margin = -1.0;
+		assert!(system.add_collider(ColliderWrapper::Sphere(sphere)).is_err());
+	}
+
+	/// Check that static and kinematic entities aren't pushed around (by impulses or collisions) even with finite masses.
+	#[test]
+	fn body_types() {
+		const RADIUS : f32 = 1.0;
+		let mut system = PhysicsSystem::new();
+		let add_ball = |system : &mut PhysicsSystem, position : Vec3, velocity : Vec3, body_type : BodyType| {
+			let mut entity = Entity::new();
+			entity.position = position;
+			entity.velocity = velocity;
+			entity.body_type = body_type;
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(RADIUS);
+			sphere.mass = 1.0;
+			sphere.restitution_coefficient = 0.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		let floor = {
+			let mut entity = Entity::new();
+			entity.body_type = BodyType::Static;
+			entity.velocity = Vec3::new(0.0, 1.0, 0.0); // Should be ignored.
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut plane = PlaneCollider::new();
+			plane.normal = Vec3::y();
+			plane.mass = 1.0;
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		// Falls onto the floor, and stops there.
+		let ball = add_ball(&mut system, Vec3::new(0.0, 3.0, 0.0), Vec3::new(0.0, -1.0, 0.0), BodyType::Dynamic);
+		// Floats along, pushing the target out of its way.
+		let pusher = add_ball(&mut system, Vec3::new(-5.0, 5.0, 0.0), Vec3::new(1.0, 0.0, 0.0), BodyType::Kinematic);
+		let target = add_ball(&mut system, Vec3::new(0.0, 5.0, 0.0), Vec3::zeros(), BodyType::Dynamic);
+
+		system.apply_impulse(pusher, Vec3::new(-5.0, 5.0, 0.0), Vec3::new(0.0, 10.0, 0.0)).unwrap();
+
+		for _ in 0..50 {
+			system.step(0.1);
+		}
+
+		{
+			let floor = system.get_entity(floor).unwrap();
+			assert!(floor.position.magnitude() < EPSILON);
+			assert!(floor.velocity.magnitude() < EPSILON);
+		}
+		{
+			let pusher = system.get_entity(pusher).unwrap();
+			println!("Pusher: {:?} {:?}", pusher.position, pusher.velocity);
+			assert!((pusher.position - Vec3::new(0.0, 5.0, 0.0)).magnitude() < 0.01);
+			assert!((pusher.velocity - Vec3::new(1.0, 0.0, 0.0)).magnitude() < EPSILON);
+		}
+		{
+			let target = system.get_entity(target).unwrap();
+			println!("Target: {:?} {:?}", target.position, target.velocity);
+			assert!(2.0 * RADIUS - 0.01 < target.position.x);
+			assert!((target.position.y - 5.0).abs() < 0.01);
+		}
+		{
+			let ball = system.get_entity(ball).unwrap();
+			println!("Ball: {:?} {:?}", ball.position, ball.velocity);
+			assert!((ball.position - Vec3::new(0.0, RADIUS, 0.0)).magnitude() < 0.01);
+		}
+	}
+
+	/// Check that entities with colliders but (next to) no mass act like kinematic ones when hit, rather than getting NaN velocities.
+	#[test]
+	fn zero_mass_entities() {
+		for mass in [0.0, EPSILON / 10.0].iter() {
+			let mut system = PhysicsSystem::new();
+			let add_ball = |system : &mut PhysicsSystem, position : Vec3, velocity : Vec3, mass : f32| {
+				let mut entity = Entity::new();
+				entity.position = position;
+				entity.velocity = velocity;
+				let entity_handle = system.add_entity(entity).unwrap();
+				let mut sphere = SphereCollider::new(1.0);
+				sphere.mass = mass;
+				sphere.restitution_coefficient = 1.0;
+				let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+				system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+				entity_handle
+			};
+			let massless = add_ball(&mut system, Vec3::zeros(), Vec3::zeros(), *mass);
+			let ball = add_ball(&mut system, Vec3::new(-3.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 1.0);
+
+			for _ in 0..20 {
+				system.step(0.1);
+			}
+
+			let massless = system.get_entity(massless).unwrap();
+			println!("Massless ({:?}): {:?} {:?}", mass, massless.position, massless.velocity);
+			assert!(massless.position.magnitude() < EPSILON);
+			assert!(massless.velocity.magnitude() < EPSILON);
+			// The ball bounces right off of it.
+			let ball = system.get_entity(ball).unwrap();
+			println!("Ball: {:?} {:?}", ball.position, ball.velocity);
+			assert!((ball.velocity - Vec3::new(-1.0, 0.0, 0.0)).magnitude() < 0.01);
+			assert!((ball.position - Vec3::new(-3.0, 0.0, 0.0)).magnitude() < 0.01);
+		}
+		// Impulses do nothing to them either.
+		let mut system = PhysicsSystem::new();
+		let handle = system.add_entity(Entity::new()).unwrap();
+		let collider = system.add_collider(ColliderWrapper::Sphere(SphereCollider::new(1.0))).unwrap();
+		system.link_collider(collider, Some(handle)).unwrap();
+		system.apply_impulse(handle, Vec3::new(0.0, 1.0, 0.0), Vec3::new(1.0, 0.0, 0.0)).unwrap();
+		let entity = system.get_entity(handle).unwrap();
+		assert!(entity.velocity.magnitude() < EPSILON);
+		assert!(entity.angular_velocity.magnitude() < EPSILON);
+	}
+
 	/// Check that putting things to sleep on infinite masses works correctly.
 	#[test]
 	fn go_to_sleep() {
@@ -1502,35 +3943,159 @@ mod tests {
 		assert!(system.get_entity(wall).unwrap().was_asleep());
 	}
 
-	/// Check that two separate entities falling asleep against an infinite mass won't wake eachother up.
+	/// Check that a ball slowly spinning in place on the floor still goes to sleep.
 	#[test]
-	fn dual_sleeping() {
-		const RADIUS : f32 = 1.0;
+	fn spinning_sleep() {
 		let mut system = PhysicsSystem::new();
-		let ball1 = {
+		let ball = {
 			let mut entity = Entity::new();
-			entity.position = Vec3::new(0.0, 3.0, 0.0); // Will take 2 seconds to hit the ground.
+			entity.position = Vec3::new(0.0, 1.0, 0.0);
+			entity.angular_velocity = Vec3::new(0.0, 0.2, 0.0); // Has 0.008 of energy, so would never sleep if counted with the linear energy.
 			let entity_handle = system.add_entity(entity).unwrap();
-			//
-			let mut sphere = SphereCollider::new(RADIUS);
+			let mut sphere = SphereCollider::new(1.0);
 			sphere.mass = 1.0;
 			sphere.restitution_coefficient = 0.0;
 			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
 			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
-
 			entity_handle
 		};
-		let ball2 = {
-			let mut entity = Entity::new();
-			entity.position = Vec3::new(5.0, 5.5, 0.0); // Will take 3 seconds to hit the ground.
-			let entity_handle = system.add_entity(entity).unwrap();
-			//
-			let mut sphere = SphereCollider::new(RADIUS);
-			sphere.mass = 1.0;
-			sphere.restitution_coefficient = 0.0;
-			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
-			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
-
+		{
+			let entity_handle = system.add_entity(Entity::new()).unwrap();
+			let mut plane = PlaneCollider::new();
+			plane.mass = INFINITY;
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+		}
+		system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -1.0, 0.0)))).unwrap();
+		for _ in 0..10 {
+			system.step(0.1);
+		}
+		assert!(system.get_entity(ball).unwrap().was_asleep());
+		// But not if the angular threshold is lowered.
+		system.angular_energy_sleep_threshold = 0.001;
+		let mut entity = system.get_entity(ball).unwrap();
+		entity.angular_velocity = Vec3::new(0.0, 0.2, 0.0);
+		system.update_entity(ball, entity).unwrap();
+		for _ in 0..10 {
+			system.step(0.1);
+		}
+		assert!(!system.get_entity(ball).unwrap().was_asleep());
+	}
+
+	/// Check that the wake policy decides whether updating a sleeping entity wakes it.
+	#[test]
+	fn update_wake_policy() {
+		let mut system = PhysicsSystem::new();
+		let ball = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 1.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(1.0);
+			sphere.mass = 1.0;
+			sphere.restitution_coefficient = 0.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		{
+			let entity_handle = system.add_entity(Entity::new()).unwrap();
+			let mut plane = PlaneCollider::new();
+			plane.mass = INFINITY;
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+		}
+		system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -1.0, 0.0)))).unwrap();
+		for _ in 0..5 {
+			system.step(0.1);
+		}
+		assert!(system.get_entity(ball).unwrap().was_asleep());
+		// Sliding it over without waking it leaves it asleep where it was put.
+		let mut entity = system.get_entity(ball).unwrap();
+		entity.position.x = 0.5;
+		system.update_entity_with(ball, entity.clone(), WakePolicy::Never).unwrap();
+		system.step(0.1);
+		assert!(system.get_entity(ball).unwrap().was_asleep());
+		assert!((system.get_entity(ball).unwrap().position.x - 0.5).abs() < EPSILON);
+		// Nothing changing doesn't normally wake it.
+		system.update_entity(ball, entity.clone()).unwrap();
+		assert!(system.get_entity(ball).unwrap().was_asleep());
+		// Unless asked to.
+		system.update_entity_with(ball, entity, WakePolicy::Always).unwrap();
+		assert!(!system.get_entity(ball).unwrap().was_asleep());
+	}
+
+	/// Check that teleporting wakes an entity up (so it falls from its new spot) and can refuse to teleport into something.
+	#[test]
+	fn teleport() {
+		let mut system = PhysicsSystem::new();
+		let mut balls = Vec::new();
+		for x in [0.0, 5.0].iter() {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(*x, 1.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(1.0);
+			sphere.mass = 1.0;
+			sphere.restitution_coefficient = 0.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			balls.push(entity_handle);
+		}
+		{
+			let entity_handle = system.add_entity(Entity::new()).unwrap();
+			let mut plane = PlaneCollider::new();
+			plane.mass = INFINITY;
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+		}
+		system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -1.0, 0.0)))).unwrap();
+		for _ in 0..5 {
+			system.step(0.1);
+		}
+		assert!(system.get_entity(balls[0]).unwrap().was_asleep());
+		// Can't teleport into the other ball (or the floor) when checking.
+		assert!(system.teleport_entity(balls[0], Vec3::new(4.0, 1.0, 0.0), Vec3::zeros(), true).is_err());
+		assert!(system.teleport_entity(balls[0], Vec3::new(0.0, 0.5, 0.0), Vec3::zeros(), true).is_err());
+		assert!((system.get_entity(balls[0]).unwrap().position - Vec3::new(0.0, 1.0, 0.0)).magnitude() < EPSILON);
+		assert!(system.get_entity(balls[0]).unwrap().was_asleep());
+		// But can teleport up into the air, where it's woken up and falls.
+		system.teleport_entity(balls[0], Vec3::new(0.0, 3.0, 0.0), Vec3::zeros(), true).unwrap();
+		assert!(!system.get_entity(balls[0]).unwrap().was_asleep());
+		assert!((system.get_entity(balls[0]).unwrap().position - Vec3::new(0.0, 3.0, 0.0)).magnitude() < EPSILON);
+		system.step(0.1);
+		assert!(system.get_entity(balls[0]).unwrap().position.y < 3.0);
+		// The other ball wasn't touching it, so it's left asleep.
+		assert!(system.get_entity(balls[1]).unwrap().was_asleep());
+	}
+
+	/// Check that two separate entities falling asleep against an infinite mass won't wake eachother up.
+	#[test]
+	fn dual_sleeping() {
+		const RADIUS : f32 = 1.0;
+		let mut system = PhysicsSystem::new();
+		let ball1 = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 3.0, 0.0); // Will take 2 seconds to hit the ground.
+			let entity_handle = system.add_entity(entity).unwrap();
+			//
+			let mut sphere = SphereCollider::new(RADIUS);
+			sphere.mass = 1.0;
+			sphere.restitution_coefficient = 0.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+
+			entity_handle
+		};
+		let ball2 = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(5.0, 5.5, 0.0); // Will take 3 seconds to hit the ground.
+			let entity_handle = system.add_entity(entity).unwrap();
+			//
+			let mut sphere = SphereCollider::new(RADIUS);
+			sphere.mass = 1.0;
+			sphere.restitution_coefficient = 0.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+
 			entity_handle
 		};
 		let wall = {
@@ -1571,6 +4136,2416 @@ mod tests {
 		//assert!(false); // It's also a good idea to manually check the logging to make sure that ball1 doesn't wake up and then immediately go to sleep.
 	}
 
-	// TODO? Only angular inertia into a collision.
-	// TODO? Check attaching a collider with mass after rotation has already begun -> verify doesn't look weird.
-}
\ No newline at end of file
+	/// Check that perfectly bouncy spheres resting on each other don't keep bouncing from gravity.
+	#[test]
+	fn resting_without_bounce() {
+		let mut system = PhysicsSystem::new();
+		system.restitution_velocity_threshold = 0.5;
+		let mut balls = Vec::new();
+		for height in [0.5, 1.5].iter() {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, *height, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 1.0;
+			sphere.restitution_coefficient = 1.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			balls.push(entity_handle);
+		}
+		{
+			let entity_handle = system.add_entity(Entity::new()).unwrap();
+			let mut plane = PlaneCollider::new();
+			plane.restitution_coefficient = 1.0;
+			plane.mass = INFINITY;
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+		}
+		system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -9.8, 0.0)))).unwrap();
+		for _ in 0..60 {
+			system.step(1.0 / 60.0);
+			for (ball, height) in balls.iter().zip([0.5, 1.5].iter()) {
+				let entity = system.get_entity(*ball).unwrap();
+				assert!((entity.position.y - height).abs() < 0.01);
+				// Should never be going faster than what gravity adds in a single step.
+				assert!(entity.velocity.magnitude() < 9.8 / 60.0);
+			}
+		}
+	}
+
+	/// Check that a disabled entity neither moves nor collides, and picks up where it left off once re-enabled.
+	#[test]
+	fn disabled_entity_frozen() {
+		let mut system = PhysicsSystem::new();
+		let ball = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 3.0, 0.0);
+			entity.velocity = Vec3::new(0.0, -1.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(1.0);
+			sphere.mass = 1.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		let floor = {
+			let entity_handle = system.add_entity(Entity::new()).unwrap();
+			let mut plane = PlaneCollider::new();
+			plane.normal = Vec3::y();
+			plane.mass = INFINITY;
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		// A frozen ball doesn't move at all, but keeps its velocity.
+		system.set_entity_enabled(ball, false).unwrap();
+		assert!(!system.get_entity(ball).unwrap().was_enabled());
+		system.step(1.0);
+		{
+			let entity = system.get_entity(ball).unwrap();
+			assert!((entity.position - Vec3::new(0.0, 3.0, 0.0)).magnitude() < EPSILON);
+			assert!((entity.velocity - Vec3::new(0.0, -1.0, 0.0)).magnitude() < EPSILON);
+		}
+		// Re-enabling lets it continue. Then disabling the floor lets it fall straight through.
+		system.set_entity_enabled(ball, true).unwrap();
+		system.set_entity_enabled(floor, false).unwrap();
+		system.step(3.0);
+		{
+			let entity = system.get_entity(ball).unwrap();
+			assert!((entity.position - Vec3::new(0.0, 0.0, 0.0)).magnitude() < EPSILON);
+			assert!((entity.velocity - Vec3::new(0.0, -1.0, 0.0)).magnitude() < EPSILON);
+		}
+		assert!(system.collision_records.is_empty());
+		// Missing entities can't be changed.
+		system.remove_entity(ball);
+		assert_eq!(system.set_entity_enabled(ball, true), Err(()));
+	}
+
+	/// Check that collisions can be looked at relative to either entity, no matter which one the record has first.
+	#[test]
+	fn contact_pairs() {
+		let mut system = PhysicsSystem::new();
+		// Added first, so has the lower handle (and so is always first in the records).
+		let floor = {
+			let entity_handle = system.add_entity(Entity::new()).unwrap();
+			let mut plane = PlaneCollider::new();
+			plane.normal = Vec3::y();
+			plane.mass = INFINITY;
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		let ball = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 3.0, 0.0);
+			entity.velocity = Vec3::new(0.0, -2.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(1.0);
+			sphere.mass = 1.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		let bystander = system.add_entity(Entity::new()).unwrap();
+		system.step(2.0);
+
+		assert_eq!(system.collision_records.len(), 1);
+		let record = &system.collision_records[0];
+		assert_eq!(record.first_entity, floor);
+		assert_eq!(record.other_entity(ball), Some(floor));
+		assert!((record.normal_for(floor).unwrap() - Vec3::y()).magnitude() < EPSILON);
+		assert!((record.normal_for(ball).unwrap() + Vec3::y()).magnitude() < EPSILON);
+		assert!(record.normal_for(bystander).is_none());
+
+		let collisions = system.collisions_for(ball);
+		assert_eq!(collisions.len(), 1);
+		assert_eq!(collisions[0].entity, ball);
+		assert_eq!(collisions[0].other_entity, floor);
+		assert!((collisions[0].normal + Vec3::y()).magnitude() < EPSILON);
+		assert!((collisions[0].position - record.position).magnitude() < EPSILON);
+		assert!(system.collisions_for(bystander).is_empty());
+
+		let contacts = system.contacts_for(floor);
+		assert_eq!(contacts.len(), 1);
+		assert_eq!(contacts[0].other_entity, ball);
+		assert!((contacts[0].normal - Vec3::y()).magnitude() < EPSILON);
+	}
+
+	/// Check that the properties set for pairs of materials and entities replace the colliders' combined values.
+	#[test]
+	fn pair_properties() {
+		let make_system = || {
+			let mut system = PhysicsSystem::new();
+			let ball = {
+				let mut entity = Entity::new();
+				entity.position = Vec3::new(0.0, 3.0, 0.0);
+				entity.velocity = Vec3::new(0.0, -2.0, 0.0);
+				let entity_handle = system.add_entity(entity).unwrap();
+				let mut sphere = SphereCollider::new(1.0);
+				sphere.mass = 1.0;
+				sphere.material = 1;
+				let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+				system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+				entity_handle
+			};
+			let floor = {
+				let entity_handle = system.add_entity(Entity::new()).unwrap();
+				let mut plane = PlaneCollider::new();
+				plane.normal = Vec3::y();
+				plane.mass = INFINITY;
+				plane.material = 2;
+				let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+				system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+				entity_handle
+			};
+			(system, ball, floor)
+		};
+		let mut bouncy = ContactProperties::new();
+		bouncy.restitution_coefficient = Some(0.5);
+		let mut dead = ContactProperties::new();
+		dead.restitution_coefficient = Some(0.0);
+		{ // Just the materials.
+			let (mut system, ball, _) = make_system();
+			assert_eq!(system.set_material_pair_properties(2, 1, Some(bouncy)), Ok(None));
+			system.step(1.5);
+			assert_eq!(system.collision_records.len(), 1);
+			assert!((system.collision_records[0].restitution_coefficient - 0.5).abs() < EPSILON);
+			assert!((system.get_entity(ball).unwrap().velocity - Vec3::new(0.0, 1.0, 0.0)).magnitude() < EPSILON);
+		}
+		{ // The entities' properties win over the materials'.
+			let (mut system, ball, floor) = make_system();
+			system.set_material_pair_properties(1, 2, Some(bouncy)).unwrap();
+			assert_eq!(system.set_entity_pair_properties(floor, ball, Some(dead)), Ok(None));
+			system.step(1.5);
+			assert_eq!(system.collision_records.len(), 1);
+			assert!(system.collision_records[0].restitution_coefficient.abs() < EPSILON);
+			assert!(system.get_entity(ball).unwrap().velocity.magnitude() < EPSILON);
+			// Can clear them again.
+			assert_eq!(system.set_entity_pair_properties(ball, floor, None), Ok(Some(dead)));
+			assert_eq!(system.set_material_pair_properties(1, 2, None), Ok(Some(bouncy)));
+			// Can't set junk values, or use missing entities.
+			let mut junk = ContactProperties::new();
+			junk.static_friction_coefficient = Some(-1.0);
+			assert_eq!(system.set_material_pair_properties(1, 2, Some(junk)), Err(()));
+			assert_eq!(system.set_entity_pair_properties(ball, floor, Some(junk)), Err(()));
+			system.remove_entity(ball);
+			assert_eq!(system.set_entity_pair_properties(ball, floor, Some(dead)), Err(()));
+		}
+	}
+
+	/// Check that can find where entities were at the time of a collision.
+	#[test]
+	fn orientation_at_collision() {
+		let mut system = PhysicsSystem::new();
+		let ball = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 3.0, 0.0);
+			entity.velocity = Vec3::new(0.0, -2.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(1.0);
+			sphere.mass = 1.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		let floor = {
+			let entity_handle = system.add_entity(Entity::new()).unwrap();
+			let mut plane = PlaneCollider::new();
+			plane.normal = Vec3::y();
+			plane.mass = INFINITY;
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		// Nothing has been stepped yet, so just get the current position.
+		assert!((system.orientation_at(ball, 1.0).unwrap().position - Vec3::new(0.0, 3.0, 0.0)).magnitude() < EPSILON);
+		system.step(2.0);
+		assert_eq!(system.collision_records.len(), 1);
+		let time = system.collision_records[0].time;
+		assert!((time - 1.0).abs() < EPSILON);
+		assert!((system.orientation_at(ball, time).unwrap().position - Vec3::new(0.0, 1.0, 0.0)).magnitude() < EPSILON);
+		assert!((system.orientation_at(ball, 0.5).unwrap().position - Vec3::new(0.0, 2.0, 0.0)).magnitude() < EPSILON);
+		assert!((system.orientation_at(ball, 1.5).unwrap().position - Vec3::new(0.0, 2.0, 0.0)).magnitude() < EPSILON);
+		assert!((system.orientation_at(ball, 5.0).unwrap().position - Vec3::new(0.0, 3.0, 0.0)).magnitude() < EPSILON);
+		assert!((system.orientation_at(floor, time).unwrap().position - Vec3::new(0.0, 0.0, 0.0)).magnitude() < EPSILON);
+		system.remove_entity(ball);
+		assert!(system.orientation_at(ball, time).is_none());
+	}
+
+	/// Check that a dumbbell landing flat bounces straight back up (instead of picking up spin from whichever end was found first).
+	#[test]
+	fn simultaneous_collisions() {
+		let mut system = PhysicsSystem::new();
+		let dumbbell = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 3.0, 0.0);
+			entity.velocity = Vec3::new(0.0, -2.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			for x in [-2.0, 2.0].iter() {
+				let mut sphere = SphereCollider::new(1.0);
+				sphere.mass = 1.0;
+				sphere.center = Vec3::new(*x, 0.0, 0.0);
+				let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+				system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			}
+			entity_handle
+		};
+		{
+			let entity_handle = system.add_entity(Entity::new()).unwrap();
+			let mut plane = PlaneCollider::new();
+			plane.normal = Vec3::y();
+			plane.mass = INFINITY;
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+		}
+		// By default, they're merged into one record (but the contact cache still has both of their impulses).
+		let mut merged = system.clone();
+		merged.step(2.0);
+		assert_eq!(merged.collision_records.len(), 1);
+		assert!((merged.collision_records[0].time - 1.0).abs() < EPSILON);
+		assert!((merged.collision_records[0].impulse_magnitude - 4.0).abs() < EPSILON);
+		assert_eq!(merged.contacts.len(), 1);
+		assert!((merged.contacts[0].impulse_magnitude - 8.0).abs() < EPSILON);
+		system.merge_collision_records = false;
+		system.step(2.0);
+		// Both contacts are reported.
+		assert_eq!(system.collision_records.len(), 2);
+		for record in &system.collision_records {
+			assert!((record.time - 1.0).abs() < EPSILON);
+			assert!((record.impulse_magnitude - 4.0).abs() < EPSILON);
+		}
+		let entity = system.get_entity(dumbbell).unwrap();
+		assert!((entity.position - Vec3::new(0.0, 3.0, 0.0)).magnitude() < EPSILON);
+		assert!((entity.velocity - Vec3::new(0.0, 2.0, 0.0)).magnitude() < EPSILON);
+		assert!(entity.angular_velocity.magnitude() < EPSILON);
+	}
+
+	/// Check that the contact cache reports the weight of a ball resting on the floor (both before and after it goes to sleep).
+	#[test]
+	fn resting_contact_force() {
+		let mut system = PhysicsSystem::new();
+		let ball = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 1.5, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(1.0);
+			sphere.mass = 2.0;
+			sphere.restitution_coefficient = 0.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		let floor = {
+			let entity_handle = system.add_entity(Entity::new()).unwrap();
+			let mut plane = PlaneCollider::new();
+			plane.normal = Vec3::y();
+			plane.mass = INFINITY;
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -3.0, 0.0)))).unwrap();
+		for _ in 0..30 {
+			system.step(0.1);
+		}
+		// Once asleep, the last contact is kept around.
+		assert!(system.get_entity(ball).unwrap().was_asleep());
+		assert_eq!(system.contacts.len(), 1);
+		let contact = &system.contacts[0];
+		assert!(contact.is_between(ball, floor));
+		assert!((contact.force_magnitude - 6.0).abs() < 0.001);
+		assert!((contact.position - Vec3::new(0.0, 0.0, 0.0)).magnitude() < EPSILON);
+		// The normal always points off of the first entity.
+		let expected_normal = if contact.first_entity == ball { -Vec3::y() } else { Vec3::y() };
+		assert!((contact.normal - expected_normal).magnitude() < EPSILON);
+	}
+
+	/// Verify that in low gravity, slow approaches are absorbed as resting contact instead of bouncing forever.
+	#[test]
+	fn resting_contact_threshold() {
+		for resting_contact_threshold in [0.0, 0.01].iter() {
+			let mut system = PhysicsSystem::new();
+			system.restitution_velocity_threshold = 0.0; // So slow things still bounce.
+			system.resting_contact_threshold = *resting_contact_threshold;
+			system.energy_sleep_threshold = 0.0; // So nothing is hidden by going to sleep.
+			system.angular_energy_sleep_threshold = 0.0;
+			let ball = {
+				let mut entity = Entity::new();
+				entity.position = Vec3::new(0.0, 0.501, 0.0);
+				let entity_handle = system.add_entity(entity).unwrap();
+				let mut sphere = SphereCollider::new(0.5);
+				sphere.mass = 1.0;
+				sphere.restitution_coefficient = 1.0;
+				let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+				system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+				entity_handle
+			};
+			{
+				let mut entity = Entity::new();
+				entity.body_type = BodyType::Static;
+				let entity_handle = system.add_entity(entity).unwrap();
+				let mut plane = PlaneCollider::new();
+				plane.restitution_coefficient = 1.0;
+				let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+				system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+			}
+			system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -0.01, 0.0)))).unwrap();
+			for _ in 0..5000 {
+				system.step(1.0 / 60.0);
+			}
+			let mut highest = 0.0f32;
+			for _ in 0..1000 {
+				system.step(1.0 / 60.0);
+				highest = highest.max(system.get_entity(ball).unwrap().position.y);
+			}
+			if *resting_contact_threshold == 0.0 {
+				assert!(0.501 < highest, "{}", highest); // Still bouncing (and even picking up a little height).
+			} else {
+				assert!((highest - 0.5).abs() < 0.001, "{}", highest);
+				assert!(system.get_entity(ball).unwrap().velocity.magnitude() < EPSILON);
+			}
+		}
+	}
+
+	/// Verify weak collisions are left out of the records, but still count towards the contacts.
+	#[test]
+	fn min_record_impulse() {
+		let mut system = PhysicsSystem::new();
+		system.min_record_impulse = 1.0;
+		let mut add_ball = |x : f32, height : f32| {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(x, height, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(1.0);
+			sphere.mass = 1.0;
+			sphere.restitution_coefficient = 0.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		let resting = add_ball(-5.0, 1.0);
+		let falling = add_ball(5.0, 3.0);
+		{
+			let mut entity = Entity::new();
+			entity.body_type = BodyType::Static;
+			let entity_handle = system.add_entity(entity).unwrap();
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(PlaneCollider::new())).unwrap();
+			system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+		}
+		system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -3.0, 0.0)))).unwrap();
+		let mut falling_records = 0;
+		for _ in 0..20 {
+			system.step(0.1);
+			assert!(system.collision_records.iter().all(|record| 1.0 <= record.impulse_magnitude));
+			assert!(system.collisions_for(resting).is_empty());
+			falling_records += system.collisions_for(falling).len();
+		}
+		// Only the landing is hard enough to be recorded.
+		assert_eq!(falling_records, 1);
+		assert_eq!(system.contacts_for(resting).len(), 1);
+		assert_eq!(system.contacts_for(falling).len(), 1);
+	}
+
+	/// Verify the record limit keeps the earliest or latest collisions, and counts the rest.
+	#[test]
+	fn record_limit() {
+		let mut system = PhysicsSystem::new();
+		// Each ball lands a second after the last.
+		for index in 0..4 {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(3.0 * index as f32, 2.0 + index as f32, 0.0);
+			entity.velocity = Vec3::new(0.0, -1.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(1.0);
+			sphere.mass = 1.0;
+			sphere.restitution_coefficient = 0.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+		}
+		{
+			let mut entity = Entity::new();
+			entity.body_type = BodyType::Static;
+			let entity_handle = system.add_entity(entity).unwrap();
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(PlaneCollider::new())).unwrap();
+			system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+		}
+		for (limit, times) in [
+			(RecordLimit::Unlimited, vec![1.0, 2.0, 3.0, 4.0]),
+			(RecordLimit::KeepFirst(2), vec![1.0, 2.0]),
+			(RecordLimit::KeepLast(3), vec![2.0, 3.0, 4.0]),
+		].iter() {
+			let mut limited = system.clone();
+			limited.record_limit = *limit;
+			limited.step(5.0);
+			let found : Vec<f32> = limited.collision_records.iter().map(|record| record.time).collect();
+			assert_eq!(found.len(), times.len(), "{:?}", found);
+			assert!(found.iter().zip(times.iter()).all(|(found, time)| (found - time).abs() < EPSILON), "{:?}", found);
+			assert_eq!(limited.dropped_collision_records, 4 - times.len());
+			// Every ball still landed.
+			assert!(limited.entities.borrow().iter().all(|(_, entity)| (entity.orientation.position.y - 1.0).abs() < EPSILON || entity.orientation.position.y == 0.0));
+		}
+	}
+
+	/// Verify the camera stops just short of a wall between it and the pivot, unless the wall is masked out.
+	#[test]
+	fn camera_clip() {
+		let mut system = PhysicsSystem::new();
+		let (wall, wall_mesh) = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 1.0, 5.0);
+			entity.query_groups = 2;
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mesh_handle = system.add_collider(ColliderWrapper::Mesh(MeshCollider::new_cuboid(Vec3::new(3.0, 3.0, 0.25)).unwrap())).unwrap();
+			system.link_collider(mesh_handle, Some(entity_handle)).unwrap();
+			(entity_handle, mesh_handle)
+		};
+		let pivot = Vec3::new(0.0, 1.0, 0.0);
+		let desired = Vec3::new(1.0, 2.0, 10.0);
+		let clip = system.camera_clip(&pivot, &desired, 0.3, u32::MAX);
+		let hit = clip.hit.unwrap();
+		assert!(hit.entity == wall && hit.collider == wall_mesh);
+		assert!((hit.normal - Vec3::new(0.0, 0.0, -1.0)).magnitude() < 0.001);
+		// The sphere's front touches the wall's near side (at z = 4.75), minus the little bit it's backed off by.
+		assert!((clip.position.z - (4.45 - SURFACE_OFFSET * 10.0 / (desired - pivot).magnitude())).abs() < 0.001);
+		assert!(((clip.position - pivot).normalize() - (desired - pivot).normalize()).magnitude() < EPSILON);
+
+		let clip = system.camera_clip(&pivot, &desired, 0.3, 1);
+		assert!(clip.hit.is_none());
+		assert_eq!(clip.position, desired);
+		let clip = system.camera_clip(&pivot, &pivot, 0.3, u32::MAX);
+		assert!(clip.hit.is_none());
+		assert_eq!(clip.position, pivot);
+	}
+
+	/// Verify entities with a time scale move (and get pushed around) at their own speed.
+	#[test]
+	fn time_scale() {
+		fn add_ball(system : &mut PhysicsSystem, position : Vec3, velocity : Vec3, time_scale : f32) -> EntityHandle {
+			let mut entity = Entity::new();
+			entity.position = position;
+			entity.velocity = velocity;
+			entity.time_scale = time_scale;
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 1.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		}
+		{
+			let mut system = PhysicsSystem::new();
+			let normal = add_ball(&mut system, Vec3::new(-10.0, 0.0, 0.0), Vec3::zeros(), 1.0);
+			let slow = add_ball(&mut system, Vec3::new(10.0, 0.0, 0.0), Vec3::zeros(), 0.5);
+			let mut entity = system.get_entity(normal).unwrap();
+			entity.time_scale = -1.0;
+			assert!(system.update_entity(normal, entity).is_err());
+			system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -2.0, 0.0)))).unwrap();
+			for _ in 0..10 {
+				system.step(0.1);
+			}
+			let normal = system.get_entity(normal).unwrap();
+			let slow = system.get_entity(slow).unwrap();
+			// Only half a second passed for the slow one, so it falls a quarter as far. Its velocity is what it would be at normal speed.
+			assert!((normal.position.y + 1.1).abs() < 0.001);
+			assert!((slow.position.y - normal.position.y / 4.0).abs() < 0.001);
+			assert!((slow.velocity.y + 1.0).abs() < EPSILON);
+		}
+
+		let mut system = PhysicsSystem::new();
+		let hitter = add_ball(&mut system, Vec3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 0.0, 0.0), 1.0);
+		let target = add_ball(&mut system, Vec3::new(2.0, 0.0, 0.0), Vec3::zeros(), 0.5);
+		let frozen = add_ball(&mut system, Vec3::new(0.0, 10.0, 0.0), Vec3::zeros(), 0.0);
+		let frozen_hitter = add_ball(&mut system, Vec3::new(-2.0, 10.0, 0.0), Vec3::new(2.0, 0.0, 0.0), 1.0);
+		for _ in 0..10 {
+			system.step(0.1);
+		}
+		// The target moves at half speed, so an impulse only changes how fast it looks like it's going by half.
+		let hitter = system.get_entity(hitter).unwrap();
+		let target = system.get_entity(target).unwrap();
+		assert!((hitter.velocity.x + 2.0 / 3.0).abs() < 0.01, "{:?}", hitter.velocity);
+		assert!((target.velocity.x - 8.0 / 3.0).abs() < 0.01, "{:?}", target.velocity);
+		// Momentum is kept, and (as seen from outside) it's still a perfectly elastic bounce.
+		assert!((hitter.velocity.x + target.velocity.x - 2.0).abs() < 0.01);
+		assert!((target.velocity.x * 0.5 - hitter.velocity.x - 2.0).abs() < 0.01);
+
+		// Frozen things act like walls, but keep the impulse for later.
+		let frozen = system.get_entity(frozen).unwrap();
+		let frozen_hitter = system.get_entity(frozen_hitter).unwrap();
+		assert_eq!(frozen.position, Vec3::new(0.0, 10.0, 0.0));
+		assert!((frozen_hitter.velocity.x + 2.0).abs() < 0.01, "{:?}", frozen_hitter.velocity);
+		assert!((frozen.velocity.x - 4.0).abs() < 0.01, "{:?}", frozen.velocity);
+	}
+
+	/// Verify a radial impulse only spins a compound entity when it's split across the colliders.
+	#[test]
+	fn radial_impulse() {
+		for split_across_colliders in [false, true].iter() {
+			let mut system = PhysicsSystem::new();
+			let entity = system.add_entity(Entity::new()).unwrap();
+			let mut sphere_handles = Vec::new();
+			for x in [-1.0, 1.0].iter() {
+				let mut sphere = SphereCollider::new(0.5);
+				sphere.center = Vec3::new(*x, 0.0, 0.0);
+				sphere.mass = 1.0;
+				let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+				system.link_collider(sphere_handle, Some(entity)).unwrap();
+				sphere_handles.push(sphere_handle);
+			}
+			// Right above the left sphere.
+			let center = Vec3::new(-1.0, 2.0, 0.0);
+			assert!((system.closest_point(sphere_handles[0], &center).unwrap() - Vec3::new(-1.0, 0.5, 0.0)).magnitude() < EPSILON);
+			system.apply_radial_impulse(entity, center, 2.0, *split_across_colliders).unwrap();
+			let result = system.get_entity(entity).unwrap();
+			if *split_across_colliders {
+				// The left sphere is pushed straight down and the right one down and to the right. So the left side goes down more.
+				assert!(0.01 < result.angular_velocity.z, "{:?}", result.angular_velocity);
+				let expected_velocity = (Vec3::new(0.0, -1.0, 0.0) + Vec3::new(1.0, -1.0, 0.0).normalize()) * 0.5;
+				assert!((result.velocity - expected_velocity).magnitude() < 0.001, "{:?}", result.velocity);
+			} else {
+				assert!(result.angular_velocity.magnitude() < EPSILON);
+				assert!((result.velocity - Vec3::new(1.0, -2.0, 0.0).normalize()).magnitude() < EPSILON);
+			}
+		}
+		let mut system = PhysicsSystem::new();
+		let entity = system.add_entity(Entity::new()).unwrap();
+		system.remove_entity(entity);
+		assert!(system.apply_radial_impulse(entity, Vec3::zeros(), 1.0, true).is_err());
+	}
+
+	/// Check that query-only colliders move with their entity and are hit by ray casts, but never collide nor add any mass.
+	#[test]
+	fn query_only_collider() {
+		let mut system = PhysicsSystem::new();
+		system.energy_sleep_threshold = 0.0; // So the falling ball can't fall asleep early.
+		{
+			let mut entity = Entity::new();
+			entity.body_type = BodyType::Static;
+			let floor = system.add_entity(entity).unwrap();
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(PlaneCollider::new())).unwrap();
+			system.link_collider(plane_handle, Some(floor)).unwrap();
+		}
+		let (enemy, detector) = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 2.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut body = SphereCollider::new(0.5);
+			body.mass = 1.0;
+			body.restitution_coefficient = 0.0;
+			let body_handle = system.add_collider(ColliderWrapper::Sphere(body)).unwrap();
+			system.link_collider(body_handle, Some(entity_handle)).unwrap();
+			let mut detector = SphereCollider::new(3.0);
+			detector.center = Vec3::new(1.0, 0.0, 0.0);
+			detector.mass = 100.0;
+			detector.query_only = true;
+			let detector_handle = system.add_collider(ColliderWrapper::Sphere(detector)).unwrap();
+			system.link_collider(detector_handle, Some(entity_handle)).unwrap();
+			(entity_handle, detector_handle)
+		};
+		// Sitting well inside of the detector, but not touching the enemy's body.
+		let bystander = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(2.0, 2.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 1.0;
+			sphere.restitution_coefficient = 0.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		{
+			let enemy = system.get_entity(enemy).unwrap();
+			assert_eq!(enemy.get_last_total_mass(), 1.0);
+			assert!((enemy.position - Vec3::new(0.0, 2.0, 0.0)).magnitude() < EPSILON);
+		}
+		system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -10.0, 0.0)))).unwrap();
+		for _ in 0..120 {
+			system.step(1.0 / 60.0);
+		}
+		// Both land on the floor, without the detector getting in the way of anything.
+		let enemy_position = system.get_entity(enemy).unwrap().position;
+		assert!((enemy_position - Vec3::new(0.0, 0.5, 0.0)).magnitude() < 0.01, "{:?}", enemy_position);
+		let bystander_position = system.get_entity(bystander).unwrap().position;
+		assert!((bystander_position - Vec3::new(2.0, 0.5, 0.0)).magnitude() < 0.01, "{:?}", bystander_position);
+		// But ray casts still hit the detector where it moved to.
+		let hit = system.cast_ray(&Vec3::new(10.0, 0.5, 0.0), &Vec3::new(-1.0, 0.0, 0.0), 20.0, None).unwrap();
+		assert!(hit.entity == enemy && hit.collider == detector);
+		assert!((hit.position - Vec3::new(4.0, 0.5, 0.0)).magnitude() < 0.01, "{:?}", hit.position);
+	}
+
+	/// Check that a disabled collider keeps its mass, but is skipped by collisions and ray casts until it's enabled again.
+	#[test]
+	fn set_collider_enabled() {
+		let mut system = PhysicsSystem::new();
+		let (character, hitbox) = {
+			let mut entity = Entity::new();
+			entity.body_type = BodyType::Static;
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut body = SphereCollider::new(0.5);
+			body.mass = 1.0;
+			let body_handle = system.add_collider(ColliderWrapper::Sphere(body)).unwrap();
+			system.link_collider(body_handle, Some(entity_handle)).unwrap();
+			let mut hitbox = SphereCollider::new(0.5);
+			hitbox.center = Vec3::new(2.0, 0.0, 0.0);
+			hitbox.mass = 1.0;
+			let hitbox_handle = system.add_collider(ColliderWrapper::Sphere(hitbox)).unwrap();
+			system.link_collider(hitbox_handle, Some(entity_handle)).unwrap();
+			(entity_handle, hitbox_handle)
+		};
+		let ball = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(2.0, 2.0, 0.0);
+			entity.velocity = Vec3::new(0.0, -5.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 1.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		let before = system.get_entity(character).unwrap();
+		assert_eq!(system.is_collider_enabled(hitbox), Some(true));
+		system.set_collider_enabled(hitbox, false).unwrap();
+		assert_eq!(system.is_collider_enabled(hitbox), Some(false));
+		// Nothing about the entity's mass changed.
+		let after = system.get_entity(character).unwrap();
+		assert_eq!(after.get_last_total_mass(), before.get_last_total_mass());
+		assert!((after.position - before.position).magnitude() < EPSILON);
+		// Ray casts go right through it.
+		let origin = Vec3::new(2.0, 0.0, 5.0);
+		let direction = Vec3::new(0.0, 0.0, -1.0);
+		assert!(system.cast_ray(&origin, &direction, 10.0, Some(ball)).is_none());
+		// And so does the ball.
+		for _ in 0..30 {
+			system.step(1.0 / 60.0);
+		}
+		let ball_position = system.get_entity(ball).unwrap().position;
+		assert!(ball_position.y < 0.0, "{:?}", ball_position);
+		// Once it's back on, it's hit again.
+		system.set_collider_enabled(hitbox, true).unwrap();
+		let hit = system.cast_ray(&origin, &direction, 10.0, Some(ball)).unwrap();
+		assert!(hit.entity == character && hit.collider == hitbox);
+		// Missing colliders fail.
+		system.remove_collider(hitbox);
+		assert_eq!(system.set_collider_enabled(hitbox, false), Err(()));
+		assert_eq!(system.is_collider_enabled(hitbox), None);
+	}
+
+	/// Check that a collider moved with move_collider() sweeps through the step (pushing what's in the way), and only changes its entity's mass once stepped.
+	#[test]
+	fn move_collider() {
+		let mut system = PhysicsSystem::new();
+		let (machine, piston) = {
+			let mut entity = Entity::new();
+			entity.body_type = BodyType::Static;
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut base = SphereCollider::new(0.5);
+			base.center = Vec3::new(0.0, -2.0, 0.0);
+			base.mass = 1.0;
+			let base_handle = system.add_collider(ColliderWrapper::Sphere(base)).unwrap();
+			system.link_collider(base_handle, Some(entity_handle)).unwrap();
+			let mut piston = SphereCollider::new(0.5);
+			piston.mass = 1.0;
+			piston.restitution_coefficient = 0.0;
+			let piston_handle = system.add_collider(ColliderWrapper::Sphere(piston)).unwrap();
+			system.link_collider(piston_handle, Some(entity_handle)).unwrap();
+			(entity_handle, piston_handle)
+		};
+		let ball = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 1.2, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 1.0;
+			sphere.restitution_coefficient = 0.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		assert!((system.get_entity(machine).unwrap().position - Vec3::new(0.0, -1.0, 0.0)).magnitude() < EPSILON);
+		system.move_collider(piston, Vec3::new(0.0, 0.5, 0.0)).unwrap();
+		match system.get_collider(piston).unwrap() {
+			ColliderWrapper::Sphere(sphere) => assert_eq!(sphere.center, Vec3::new(0.0, 0.5, 0.0)),
+			_ => panic!("Wrong collider type."),
+		}
+		// The center of mass only moves once stepped.
+		assert!((system.get_entity(machine).unwrap().position - Vec3::new(0.0, -1.0, 0.0)).magnitude() < EPSILON);
+		let dt = 1.0 / 60.0;
+		system.step(dt);
+		assert!((system.get_entity(machine).unwrap().position - Vec3::new(0.0, -0.75, 0.0)).magnitude() < EPSILON);
+		// The piston head hit the ball on the way up, so the ball was pushed along with it (instead of being left overlapping it).
+		let ball = system.get_entity(ball).unwrap();
+		assert!(1.5 - 0.01 < ball.position.y, "{:?}", ball.position);
+		assert!((ball.velocity - Vec3::new(0.0, 0.5 / dt, 0.0)).magnitude() < 0.1, "{:?}", ball.velocity);
+		assert_eq!(system.collision_records.len(), 1);
+		// Missing colliders fail.
+		system.remove_collider(piston);
+		assert_eq!(system.move_collider(piston, Vec3::zeros()), Err(()));
+	}
+
+	/// Check that falling asleep and waking up are each reported once.
+	#[test]
+	fn take_sleep_changes() {
+		let mut system = PhysicsSystem::new();
+		{
+			let mut entity = Entity::new();
+			entity.body_type = BodyType::Static;
+			let floor = system.add_entity(entity).unwrap();
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(PlaneCollider::new())).unwrap();
+			system.link_collider(plane_handle, Some(floor)).unwrap();
+		}
+		let ball = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 0.5, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 1.0;
+			sphere.restitution_coefficient = 0.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -10.0, 0.0)))).unwrap();
+		assert!(system.take_sleep_changes().is_empty());
+		let mut changes = Vec::new();
+		for _ in 0..30 {
+			system.step(1.0 / 60.0);
+			changes.extend(system.take_sleep_changes().into_iter().filter(|change| change.entity == ball));
+		}
+		assert!(system.get_entity(ball).unwrap().was_asleep());
+		assert_eq!(changes, vec![SleepChange { entity: ball, asleep: true }]);
+		// Waking up outside of step() is picked up too.
+		system.apply_impulse(ball, Vec3::new(0.0, 0.5, 0.0), Vec3::new(1.0, 0.0, 0.0)).unwrap();
+		assert_eq!(system.take_sleep_changes(), vec![SleepChange { entity: ball, asleep: false }]);
+		assert!(system.take_sleep_changes().is_empty());
+	}
+
+	/// Check that handles can be checked for still being in the system, and round trip through ids.
+	#[test]
+	fn handle_validity_and_ids() {
+		use crate::types::{handle_to_id, handle_from_id};
+		let mut system = PhysicsSystem::new();
+		let entity = system.add_entity(Entity::new()).unwrap();
+		let collider = system.add_collider(ColliderWrapper::Sphere(SphereCollider::new(1.0))).unwrap();
+		system.link_collider(collider, Some(entity)).unwrap();
+		assert!(system.is_valid_entity(entity));
+		assert!(system.is_valid_collider(collider));
+		assert_eq!(handle_from_id(handle_to_id(entity)), entity);
+		assert_eq!(handle_from_id(handle_to_id(collider)), collider);
+
+		// Removing the entity removes its collider too. A new entity re-uses the slot, but with a new generation (so gets a new id).
+		system.remove_entity(entity);
+		assert!(!system.is_valid_entity(entity));
+		assert!(!system.is_valid_collider(collider));
+		let replacement = system.add_entity(Entity::new()).unwrap();
+		assert_ne!(handle_to_id(replacement), handle_to_id(entity));
+		assert!(!system.is_valid_entity(handle_from_id(handle_to_id(entity))));
+		assert!(system.is_valid_entity(handle_from_id(handle_to_id(replacement))));
+	}
+
+	/// Check that entities can be found by their names, which can be changed and cleared.
+	#[test]
+	fn find_entity_by_name() {
+		let mut system = PhysicsSystem::new();
+		let mut add_named = |name : Option<&str>| {
+			let mut entity = Entity::new();
+			entity.name = name.map(String::from);
+			system.add_entity(entity).unwrap()
+		};
+		let unnamed = add_named(None);
+		let player = add_named(Some("player"));
+		let crate_handle = add_named(Some("crate"));
+		assert_eq!(system.get_entity(unnamed).unwrap().name, None);
+		assert_eq!(system.get_entity(player).unwrap().name.as_deref(), Some("player"));
+		assert_eq!(system.find_entity_by_name("player"), Some(player));
+		assert_eq!(system.find_entity_by_name("crate"), Some(crate_handle));
+		assert_eq!(system.find_entity_by_name("door"), None);
+
+		let mut entity = system.get_entity(player).unwrap();
+		entity.name = Some("door".to_string());
+		system.update_entity(player, entity).unwrap();
+		assert_eq!(system.find_entity_by_name("player"), None);
+		assert_eq!(system.find_entity_by_name("door"), Some(player));
+		system.remove_entity(player);
+		assert_eq!(system.find_entity_by_name("door"), None);
+	}
+
+	/// Check that a system written out as a scene reads back in the same, and that hand-written scenes can leave out defaults.
+	#[cfg(feature = "scene")]
+	#[test]
+	fn scene_round_trip() {
+		let mut system = PhysicsSystem::new();
+		{
+			let mut entity = Entity::new();
+			entity.body_type = BodyType::Static;
+			let floor = system.add_entity(entity).unwrap();
+			let mut plane = PlaneCollider::new();
+			plane.material = 1;
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			system.link_collider(plane_handle, Some(floor)).unwrap();
+		}
+		let ball = {
+			let mut entity = Entity::new();
+			entity.name = Some("ball".to_string());
+			entity.position = Vec3::new(0.0, 2.0, 0.0);
+			entity.angular_velocity = Vec3::new(0.0, 0.0, 1.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			// Off-center, so the center of mass isn't the local origin.
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.center = Vec3::new(0.5, 0.0, 0.0);
+			sphere.mass = 1.0;
+			sphere.material = 2;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		let frozen = system.add_entity(Entity::new()).unwrap();
+		system.set_entity_enabled(frozen, false).unwrap();
+		system.add_collider(ColliderWrapper::Sphere(SphereCollider::new(1.0))).unwrap();
+		system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -10.0, 0.0)))).unwrap();
+		let bouncy = ContactProperties { restitution_coefficient: Some(0.5), ..ContactProperties::default() };
+		system.set_material_pair_properties(2, 1, Some(bouncy)).unwrap();
+		for _ in 0..10 {
+			system.step(1.0 / 60.0);
+		}
+
+		let text = system.to_scene_string();
+		let mut loaded = PhysicsSystem::from_scene_str(&text).unwrap();
+		// Moving the origin to the center of mass and back can round the last bit, so not compared exactly.
+		let (original, rewritten) = (system.to_scene(), loaded.to_scene());
+		assert_eq!(original.entities.len(), rewritten.entities.len());
+		for (before, after) in original.entities.iter().zip(rewritten.entities.iter()) {
+			assert_eq!(before.name, after.name);
+			assert!((before.origin - after.origin).magnitude() < EPSILON);
+			assert_eq!(before.colliders.len(), after.colliders.len());
+		}
+		let loaded_ball = loaded.find_entity_by_name("ball").unwrap();
+		assert!((loaded.get_entity(loaded_ball).unwrap().position - system.get_entity(ball).unwrap().position).magnitude() < EPSILON);
+		assert!(!loaded.get_entity(frozen).unwrap().was_enabled());
+		assert_eq!(loaded.to_scene().loose_colliders.len(), 1);
+		assert_eq!(loaded.set_material_pair_properties(1, 2, None), Ok(Some(bouncy)));
+		system.set_material_pair_properties(1, 2, None).unwrap();
+		// Both carry on the same way.
+		for _ in 0..60 {
+			system.step(1.0 / 60.0);
+			loaded.step(1.0 / 60.0);
+		}
+		assert!((loaded.get_entity(loaded_ball).unwrap().position - system.get_entity(ball).unwrap().position).magnitude() < 0.001);
+
+		// Anything left out is a default.
+		let small = PhysicsSystem::from_scene_str(r#"{ "entities": [ { "name": "a", "origin": [0.0, 1.0, 0.0] } ], "gravity": [[0.0, -10.0, 0.0]] }"#).unwrap();
+		let a = small.get_entity(small.find_entity_by_name("a").unwrap()).unwrap();
+		assert_eq!(a.position, Vec3::new(0.0, 1.0, 0.0));
+		assert_eq!(a.body_type, BodyType::Dynamic);
+		assert_eq!(a.time_scale, 1.0);
+		assert!(PhysicsSystem::from_scene_str("not a scene").is_err());
+	}
+
+	/// Check that the state dump lists everything (and doesn't deadlock on the locks it takes).
+	#[test]
+	fn dump_state() {
+		let mut system = PhysicsSystem::new();
+		system.restitution_velocity_threshold = 0.5; // So the ball settles down (and falls asleep).
+		{
+			let mut entity = Entity::new();
+			entity.body_type = BodyType::Static;
+			let floor = system.add_entity(entity).unwrap();
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(PlaneCollider::new())).unwrap();
+			system.link_collider(plane_handle, Some(floor)).unwrap();
+		}
+		{
+			let mut entity = Entity::new();
+			entity.name = Some("ball".to_string());
+			entity.position = Vec3::new(0.0, 1.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(1.0);
+			sphere.mass = 2.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+		}
+		system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -10.0, 0.0)))).unwrap();
+		for _ in 0..60 {
+			system.step(1.0 / 60.0);
+		}
+		let dump = system.dump_state();
+		assert!(dump.contains("Entities (2):"), "{}", dump);
+		assert!(dump.contains("\"ball\" Dynamic asleep"), "{}", dump);
+		assert!(dump.contains("mass: 2"), "{}", dump);
+		assert!(dump.contains("Colliders (2):"), "{}", dump);
+		assert!(dump.contains("SPHERE"), "{}", dump);
+		assert!(dump.contains("Contacts (1):"), "{}", dump);
+	}
+
+	/// Check that running out of the iteration time budget stops the step short (and reports by how much).
+	#[test]
+	fn iteration_time_budget() {
+		let make_system = || {
+			let mut system = PhysicsSystem::new();
+			let mut entity = Entity::new();
+			entity.body_type = BodyType::Static;
+			let floor = system.add_entity(entity).unwrap();
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(PlaneCollider::new())).unwrap();
+			system.link_collider(plane_handle, Some(floor)).unwrap();
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 1.05, 0.0);
+			entity.velocity = Vec3::new(0.0, -6.0, 0.0);
+			let ball = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(1.0);
+			sphere.mass = 1.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(ball)).unwrap();
+			(system, ball)
+		};
+		let dt = 1.0 / 60.0;
+		// Without a budget the bounce takes two iterations, and the whole step is simulated.
+		let (mut unlimited, unlimited_ball) = make_system();
+		unlimited.step(dt);
+		assert_eq!(unlimited.last_step_shortfall, 0.0);
+		// With no time at all, only the first iteration runs: up to the bounce.
+		let (mut limited, limited_ball) = make_system();
+		limited.iteration_time_budget = Some(Duration::from_secs(0));
+		limited.step(dt);
+		assert!(0.5 * dt < limited.last_step_shortfall && limited.last_step_shortfall < dt, "{}", limited.last_step_shortfall);
+		let unlimited_ball = unlimited.get_entity(unlimited_ball).unwrap();
+		let limited_ball = limited.get_entity(limited_ball).unwrap();
+		assert!(0.0 < unlimited_ball.velocity.y && 0.0 < limited_ball.velocity.y);
+		assert!(limited_ball.position.y < unlimited_ball.position.y);
+	}
+
+	/// Check that a step can be given more iterations than the system normally allows.
+	#[test]
+	fn step_with_iteration_max() {
+		let make_system = || {
+			let mut system = PhysicsSystem::new();
+			system.iteration_max = 1;
+			let mut entity = Entity::new();
+			entity.body_type = BodyType::Static;
+			let floor = system.add_entity(entity).unwrap();
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(PlaneCollider::new())).unwrap();
+			system.link_collider(plane_handle, Some(floor)).unwrap();
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 1.05, 0.0);
+			entity.velocity = Vec3::new(0.0, -6.0, 0.0);
+			let ball = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(1.0);
+			sphere.mass = 1.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(ball)).unwrap();
+			system
+		};
+		let dt = 1.0 / 60.0;
+		// One iteration only gets up to the bounce.
+		let mut system = make_system();
+		system.step(dt);
+		assert!(0.0 < system.last_step_shortfall);
+		// Overriding it gets all the way through, without changing the setting.
+		let mut system = make_system();
+		system.step_with_iteration_max(dt, 1000);
+		assert_eq!(system.last_step_shortfall, 0.0);
+		assert_eq!(system.iteration_max, 1);
+	}
+
+	/// Check that the force on an entity during a step includes both gravity and collisions, and that what it lands on feels it too.
+	#[test]
+	fn last_applied_force() {
+		let mut system = PhysicsSystem::new();
+		let floor = {
+			let mut entity = Entity::new();
+			entity.body_type = BodyType::Static;
+			let entity_handle = system.add_entity(entity).unwrap();
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(PlaneCollider::new())).unwrap();
+			system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		let ball = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 1.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 2.0;
+			sphere.restitution_coefficient = 0.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -10.0, 0.0)))).unwrap();
+		let dt = 1.0 / 60.0;
+		// Falling is just gravity.
+		system.step(dt);
+		assert!((system.get_entity(ball).unwrap().get_last_applied_force() - Vec3::new(0.0, -20.0, 0.0)).magnitude() < 0.001);
+		assert_eq!(system.get_entity(floor).unwrap().get_last_applied_force(), Vec3::zeros());
+		// Landing stops it all at once, which takes a lot more than its weight. The floor feels that (plus the weight).
+		let mut landed = false;
+		for _ in 0..60 {
+			system.step(dt);
+			let (ball, floor) = (system.get_entity(ball).unwrap(), system.get_entity(floor).unwrap());
+			let force = ball.get_last_applied_force();
+			if !landed {
+				if force.y < 0.0 { continue; }
+				landed = true;
+				assert!(10.0 * 20.0 < force.y, "{:?}", force);
+				assert!((floor.get_last_applied_force() + force + Vec3::new(0.0, 20.0, 0.0)).magnitude() < 0.01, "{:?}", floor.get_last_applied_force());
+			} else {
+				// Then gravity and the floor cancel out (while the floor holds up its weight), until it falls asleep and nothing pushes on it at all.
+				assert!(force.magnitude() < 0.01, "{:?}", force);
+				if !ball.was_asleep() {
+					assert!((floor.get_last_applied_force() - Vec3::new(0.0, -20.0, 0.0)).magnitude() < 0.01);
+				}
+			}
+			assert!(ball.get_last_applied_torque().magnitude() < EPSILON);
+		}
+		assert!(landed && system.get_entity(ball).unwrap().was_asleep());
+	}
+
+	/// Check that forces can be sampled without an entity, on their own or over a grid.
+	#[test]
+	fn sample_forces() {
+		let mut system = PhysicsSystem::new();
+		let ball = system.add_entity(Entity::new()).unwrap();
+		system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -10.0, 0.0)))).unwrap();
+		// Only pulls on the ball, so doesn't show up.
+		system.add_unary_force_generator(Box::new(AnchoredSpringGenerator::new(ball))).unwrap();
+		assert_eq!(system.sample_forces(&Vec3::new(1.0, 2.0, 3.0), 2.0), Ok(Vec3::new(0.0, -20.0, 0.0)));
+		assert!(system.sample_forces(&Vec3::zeros(), -1.0).is_err());
+
+		let grid = system.sample_force_grid(&Vec3::new(0.0, 0.0, 0.0), &Vec3::new(2.0, 1.0, 5.0), [3, 2, 1], 1.0).unwrap();
+		assert_eq!(grid.len(), 6);
+		assert_eq!(grid[1].0, Vec3::new(1.0, 0.0, 0.0));
+		assert_eq!(grid[3].0, Vec3::new(0.0, 1.0, 0.0));
+		assert_eq!(grid[5].0, Vec3::new(2.0, 1.0, 0.0));
+		assert!(grid.iter().all(|(_, force)| *force == Vec3::new(0.0, -10.0, 0.0)));
+		// Sampling doesn't add anything.
+		assert_eq!(system.entities.borrow().len(), 1);
+	}
+
+	/// Check that turning a generator off (or down) changes its forces without losing it, both when stepping and sampling.
+	#[test]
+	fn generator_enabled_and_strength() {
+		let mut system = PhysicsSystem::new();
+		let ball = {
+			let mut entity = Entity::new();
+			entity.own_mass = 1.0;
+			system.add_entity(entity).unwrap()
+		};
+		let gravity = system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -10.0, 0.0)))).unwrap();
+		let calls = system.add_unary_force_generator(Box::new(SpringGenerator { stiffness: 0.0, calls: 0 })).unwrap();
+		assert_eq!(system.is_force_generator_enabled(gravity), Ok(true));
+		assert_eq!(system.get_force_generator_strength(gravity), Ok(1.0));
+
+		system.set_force_generator_enabled(gravity, false).unwrap();
+		system.set_force_generator_enabled(calls, false).unwrap();
+		assert_eq!(system.is_force_generator_enabled(gravity), Ok(false));
+		system.step(1.0);
+		assert_eq!(system.get_entity(ball).unwrap().velocity, Vec3::zeros());
+		assert_eq!(system.sample_forces(&Vec3::zeros(), 1.0), Ok(Vec3::zeros()));
+
+		// Back on at a quarter of the strength.
+		system.set_force_generator_enabled(gravity, true).unwrap();
+		system.set_force_generator_strength(gravity, 0.25).unwrap();
+		system.step(1.0);
+		assert!((system.get_entity(ball).unwrap().velocity - Vec3::new(0.0, -2.5, 0.0)).magnitude() < EPSILON);
+		assert_eq!(system.sample_forces(&Vec3::zeros(), 2.0), Ok(Vec3::new(0.0, -5.0, 0.0)));
+		assert!(system.set_force_generator_strength(gravity, NAN).is_err());
+
+		// The one that's still off was never asked for a force.
+		assert_eq!(system.remove_unary_force_generator(calls).unwrap().downcast_ref::<SpringGenerator>().unwrap().calls, 0);
+		assert!(system.set_force_generator_enabled(calls, true).is_err());
+		assert!(system.get_force_generator_strength(calls).is_err());
+	}
+
+	/// Check that each collider's surface tag ends up on the right side of the collision record.
+	#[test]
+	fn surface_tag() {
+		let mut system = PhysicsSystem::new();
+		// Added first, so the ball is always first in the records.
+		let ball = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 3.0, 0.0);
+			entity.velocity = Vec3::new(0.0, -2.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(1.0);
+			sphere.mass = 1.0;
+			sphere.surface_tag = 7;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		let floor = {
+			let entity_handle = system.add_entity(Entity::new()).unwrap();
+			let mut plane = PlaneCollider::new();
+			plane.normal = Vec3::y();
+			plane.mass = INFINITY;
+			plane.surface_tag = 3;
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		system.step(2.0);
+
+		assert_eq!(system.collision_records.len(), 1);
+		let record = &system.collision_records[0];
+		assert_eq!((record.first_entity, record.second_entity), (ball, floor));
+		assert_eq!((record.first_surface_tag, record.second_surface_tag), (7, 3));
+		assert_eq!((record.first_feature, record.second_feature), (None, None));
+	}
+
+	/// Verify collision records say which part of a mesh was hit.
+	#[test]
+	fn mesh_feature() {
+		let mut system = PhysicsSystem::new();
+		// Added first, so the floor is always first in the records.
+		let floor = {
+			let entity_handle = system.add_entity(Entity::new()).unwrap();
+			let mut mesh = MeshCollider::new_cuboid(Vec3::new(2.0, 1.0, 2.0)).unwrap();
+			mesh.position = Vec3::new(0.0, -1.0, 0.0);
+			let mesh_handle = system.add_collider(ColliderWrapper::Mesh(mesh)).unwrap();
+			system.link_collider(mesh_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		let ball = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(1.0, 3.0, 0.0);
+			entity.velocity = Vec3::new(0.0, -2.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(1.0);
+			sphere.mass = 1.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		system.step(2.0);
+
+		assert_eq!(system.collision_records.len(), 1);
+		let record = &system.collision_records[0];
+		assert_eq!((record.first_entity, record.second_entity), (floor, ball));
+		// The top is the fourth face that new_cuboid() makes.
+		assert_eq!(record.first_feature, Some(MeshFeature::Face(3)));
+		assert_eq!(record.second_feature, None);
+	}
+
+	/// Verify points can be moved between a collider's local space and world space, whether it's linked to an entity or not.
+	#[test]
+	fn collider_to_world() {
+		let mut system = PhysicsSystem::new();
+		let mut entity = Entity::new();
+		entity.position = Vec3::new(1.0, 0.0, 0.0);
+		entity.rotation = Vec3::new(0.0, 0.0, std::f32::consts::PI / 2.0);
+		let entity_handle = system.add_entity(entity).unwrap();
+		let mut sphere = SphereCollider::new(0.5);
+		sphere.center = Vec3::new(1.0, 0.0, 0.0);
+		sphere.mass = 1.0;
+		let linked = system.add_collider(ColliderWrapper::Sphere(sphere.clone())).unwrap();
+		system.link_collider(linked, Some(entity_handle)).unwrap();
+		let loose = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+
+		// The sphere is the entity's only mass, so its center is the entity's position. And the entity is turned a quarter turn around Z.
+		let local_point = Vec3::new(0.0, 1.0, 0.0);
+		let world_point = system.collider_to_world(linked, &local_point).unwrap();
+		let expected = system.get_entity(entity_handle).unwrap().position + Vec3::new(-1.0, 0.0, 0.0);
+		assert!((world_point - expected).magnitude() < EPSILON, "{:?} vs {:?}", world_point, expected);
+		assert!((system.world_to_collider(linked, &world_point).unwrap() - local_point).magnitude() < EPSILON);
+
+		assert!((system.collider_to_world(loose, &local_point).unwrap() - Vec3::new(1.0, 1.0, 0.0)).magnitude() < EPSILON);
+		assert!((system.world_to_collider(loose, &Vec3::new(1.0, 1.0, 0.0)).unwrap() - local_point).magnitude() < EPSILON);
+
+		system.remove_collider(loose);
+		assert!(system.collider_to_world(loose, &local_point).is_none());
+	}
+
+	/// Verify an attached entity adds to its parent, rides along with it, and leaves with its spot's velocity.
+	#[test]
+	fn attach_entity() {
+		let mut system = PhysicsSystem::new();
+		let tank = {
+			let mut entity = Entity::new();
+			entity.velocity = Vec3::new(1.0, 0.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(1.0);
+			sphere.mass = 1.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		// Turned a quarter turn around Z, so its collider (off to its local X) ends up right above the tank.
+		let mut turret = Entity::new();
+		turret.position = Vec3::new(0.0, 1.0, 0.0);
+		turret.rotation = Vec3::new(0.0, 0.0, std::f32::consts::PI / 2.0);
+		let turret = system.add_entity(turret).unwrap();
+		let mut sphere = SphereCollider::new(0.5);
+		sphere.center = Vec3::new(1.0, 0.0, 0.0);
+		sphere.mass = 1.0;
+		let barrel = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+		system.link_collider(barrel, Some(turret)).unwrap();
+		let barrel_center = system.collider_to_world(barrel, &Vec3::zeros()).unwrap();
+		let turret_position = system.get_entity(turret).unwrap().position;
+		assert!((barrel_center - turret_position).magnitude() < EPSILON);
+
+		assert!(system.attach_entity(turret, turret).is_err());
+		system.attach_entity(turret, tank).unwrap();
+		assert!(system.attach_entity(tank, turret).is_err());
+		assert_eq!(system.get_attached_parent(turret), Some(tank));
+		assert_eq!(system.get_attached_children(tank), vec![turret]);
+		assert!(!system.get_entity(turret).unwrap().was_enabled());
+		assert_eq!(system.get_entity(tank).unwrap().get_last_total_mass(), 2.0);
+		assert!((system.collider_to_world(barrel, &Vec3::zeros()).unwrap() - barrel_center).magnitude() < EPSILON);
+
+		system.step(1.0);
+		let moved = system.get_entity(turret).unwrap();
+		assert!((moved.position - (turret_position + Vec3::new(1.0, 0.0, 0.0))).magnitude() < 0.001, "{:?}", moved.position);
+		assert!((system.collider_to_world(barrel, &Vec3::zeros()).unwrap() - moved.position).magnitude() < 0.001);
+
+		system.detach_entity(turret).unwrap();
+		assert!(system.detach_entity(turret).is_err());
+		assert_eq!(system.get_attached_parent(turret), None);
+		let detached = system.get_entity(turret).unwrap();
+		assert!(detached.was_enabled());
+		assert!((detached.position - moved.position).magnitude() < 0.001);
+		assert!((detached.velocity - Vec3::new(1.0, 0.0, 0.0)).magnitude() < 0.001);
+		assert_eq!(system.get_entity(tank).unwrap().get_last_total_mass(), 1.0);
+		match system.get_collider(barrel).unwrap() {
+			ColliderWrapper::Sphere(sphere) => assert_eq!(sphere.center, Vec3::new(1.0, 0.0, 0.0)),
+			_ => panic!("Wrong collider type!"),
+		}
+
+		// Aligned boxes can't be turned to match a rotated parent.
+		let aligned_box = system.add_collider(ColliderWrapper::AlignedBox(crate::aligned_box_collider::AlignedBoxCollider::new())).unwrap();
+		system.link_collider(aligned_box, Some(turret)).unwrap();
+		assert!(system.attach_entity(turret, tank).is_err());
+		assert!(system.get_entity(turret).unwrap().was_enabled());
+	}
+
+	/// Verify splitting a spinning entity apart (and merging it back together) conserves its momentum.
+	#[test]
+	fn split_and_merge_entities() {
+		let mut system = PhysicsSystem::new();
+		let mut entity = Entity::new();
+		entity.velocity = Vec3::new(1.0, 0.0, 0.0);
+		entity.angular_velocity = Vec3::new(0.0, 0.0, 2.0);
+		let whole = system.add_entity(entity).unwrap();
+		let mut add_sphere = |x : f32, mass : f32| {
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.center = Vec3::new(x, 0.0, 0.0);
+			sphere.mass = mass;
+			let handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(handle, Some(whole)).unwrap();
+			handle
+		};
+		let left = add_sphere(-1.0, 1.0);
+		let right = add_sphere(1.0, 3.0);
+		let momentum = |system : &PhysicsSystem, handles : &[EntityHandle]| handles.iter().fold(Vec3::zeros(), |sum, handle| {
+			let entity = system.get_entity(*handle).unwrap();
+			sum + entity.get_last_total_mass() * entity.velocity
+		});
+		let before = momentum(&system, &[whole]);
+
+		assert!(system.split_entity(whole, &[vec![]]).is_err());
+		assert!(system.split_entity(whole, &[vec![right], vec![right]]).is_err());
+		let pieces = system.split_entity(whole, &[vec![right]]).unwrap();
+		assert_eq!(pieces.len(), 1);
+		let piece = pieces[0];
+		assert_eq!(system.get_entity(piece).unwrap().get_colliders().into_iter().collect::<Vec<ColliderHandle>>(), vec![right]);
+		assert!((momentum(&system, &[whole, piece]) - before).magnitude() < 0.001);
+		// The center of mass was at x = 0.5, so the right piece (at x = 1) is moving up and the left (at x = -1) is moving down.
+		let (left_entity, right_entity) = (system.get_entity(whole).unwrap(), system.get_entity(piece).unwrap());
+		assert!((left_entity.velocity - Vec3::new(1.0, -3.0, 0.0)).magnitude() < 0.001, "{:?}", left_entity.velocity);
+		assert!((right_entity.velocity - Vec3::new(1.0, 1.0, 0.0)).magnitude() < 0.001, "{:?}", right_entity.velocity);
+		assert_eq!(right_entity.angular_velocity, Vec3::new(0.0, 0.0, 2.0));
+
+		assert!(system.merge_entities(&[whole, whole]).is_err());
+		assert_eq!(system.merge_entities(&[whole, piece]), Ok(whole));
+		assert!(!system.is_valid_entity(piece));
+		let merged = system.get_entity(whole).unwrap();
+		assert_eq!(merged.get_colliders().len(), 2);
+		assert!(system.is_valid_collider(left));
+		assert!((merged.velocity - Vec3::new(1.0, 0.0, 0.0)).magnitude() < 0.001, "{:?}", merged.velocity);
+		assert!((merged.angular_velocity - Vec3::new(0.0, 0.0, 2.0)).magnitude() < 0.001, "{:?}", merged.angular_velocity);
+	}
+
+	/// Verify a merge that fails part way through (after some colliders were already worked out) leaves everything as it was.
+	#[test]
+	fn merge_entities_failure() {
+		let mut system = PhysicsSystem::new();
+		let mut add_entity = |position : Vec3, rotation : Vec3, collider : ColliderWrapper| {
+			let mut entity = Entity::new();
+			entity.position = position;
+			entity.rotation = rotation;
+			let entity_handle = system.add_entity(entity).unwrap();
+			let collider_handle = system.add_collider(collider).unwrap();
+			system.link_collider(collider_handle, Some(entity_handle)).unwrap();
+			(entity_handle, collider_handle)
+		};
+		let (target, target_sphere) = add_entity(Vec3::zeros(), Vec3::zeros(), ColliderWrapper::Sphere(SphereCollider::new(0.5)));
+		let (sphere_entity, sphere) = add_entity(Vec3::new(2.0, 0.0, 0.0), Vec3::zeros(), ColliderWrapper::Sphere(SphereCollider::new(0.5)));
+		// Aligned boxes can't be turned to match the target, so this fails after the sphere above was already worked out.
+		let (box_entity, aligned_box) = add_entity(Vec3::new(-2.0, 0.0, 0.0), Vec3::new(0.0, 0.5, 0.0), ColliderWrapper::AlignedBox(crate::aligned_box_collider::AlignedBoxCollider::new()));
+
+		assert!(system.merge_entities(&[target, sphere_entity, box_entity]).is_err());
+		for (entity_handle, collider_handle) in [(target, target_sphere), (sphere_entity, sphere), (box_entity, aligned_box)] {
+			assert!(system.is_valid_entity(entity_handle));
+			assert_eq!(system.get_entity(entity_handle).unwrap().get_colliders().into_iter().collect::<Vec<ColliderHandle>>(), vec![collider_handle]);
+		}
+		match system.get_collider(sphere).unwrap() {
+			ColliderWrapper::Sphere(sphere) => assert_eq!(sphere.center, Vec3::zeros()),
+			_ => panic!("Wrong collider type!"),
+		}
+	}
+
+	/// Verify colliders and attachments break (and are reported) once they're hit harder than their break impulse.
+	#[test]
+	fn break_events() {
+		let mut system = PhysicsSystem::new();
+		let add_ball = |system : &mut PhysicsSystem, position : Vec3, velocity : Vec3| {
+			let mut entity = Entity::new();
+			entity.position = position;
+			entity.velocity = velocity;
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 1.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			(entity_handle, sphere_handle)
+		};
+		let (wall_entity, wall) = add_ball(&mut system, Vec3::zeros(), Vec3::zeros());
+		system.update_entity(wall_entity, { let mut entity = system.get_entity(wall_entity).unwrap(); entity.body_type = BodyType::Static; entity }).unwrap();
+		assert!(system.set_collider_break_impulse(wall, Some(-1.0)).is_err());
+		assert_eq!(system.set_collider_break_impulse(wall, Some(5.0)), Ok(None));
+		// A gentle hit (an impulse of about one) doesn't break it.
+		let (soft, _) = add_ball(&mut system, Vec3::new(0.0, 1.5, 0.0), Vec3::new(0.0, -1.0, 0.0));
+		system.step(1.0);
+		assert!(system.take_break_events().is_empty());
+		system.remove_entity(soft);
+		// But a hard one does, and it's only reported once.
+		add_ball(&mut system, Vec3::new(0.0, 3.0, 0.0), Vec3::new(0.0, -10.0, 0.0));
+		system.step(0.25);
+		let events = system.take_break_events();
+		assert_eq!(events.len(), 1);
+		assert_eq!(events[0].broken, Broken::Collider(wall));
+		assert!(5.0 < events[0].impulse);
+		assert_eq!(system.set_collider_break_impulse(wall, None), Ok(None));
+
+		// Knock a turret off of a tank.
+		let mut system = PhysicsSystem::new();
+		let (tank, _) = add_ball(&mut system, Vec3::zeros(), Vec3::zeros());
+		let (turret, _) = add_ball(&mut system, Vec3::new(0.0, 1.0, 0.0), Vec3::zeros());
+		system.attach_entity(turret, tank).unwrap();
+		assert!(system.set_attachment_break_impulse(tank, Some(1.0)).is_err());
+		system.set_attachment_break_impulse(turret, Some(1.0)).unwrap();
+		add_ball(&mut system, Vec3::new(2.0, 1.0, 0.0), Vec3::new(-10.0, 0.0, 0.0));
+		system.step(0.25);
+		let events = system.take_break_events();
+		assert_eq!(events.len(), 1);
+		assert_eq!(events[0].broken, Broken::Attachment { child: turret, parent: tank });
+		assert_eq!(system.get_attached_parent(turret), None);
+		assert!(system.get_entity(turret).unwrap().was_enabled());
+	}
+
+	/// Verify an entity that doesn't respond passes through what it hits (while the other still bounces off), and the collision is still recorded.
+	#[test]
+	fn respond() {
+		let add_ball = |system : &mut PhysicsSystem, position : Vec3, velocity : Vec3, respond : bool| {
+			let mut entity = Entity::new();
+			entity.position = position;
+			entity.velocity = velocity;
+			entity.respond = respond;
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 1.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		for other_responds in [true, false].iter() {
+			let mut system = PhysicsSystem::new();
+			let ghost = add_ball(&mut system, Vec3::zeros(), Vec3::new(4.0, 0.0, 0.0), false);
+			let other = add_ball(&mut system, Vec3::new(2.0, 0.0, 0.0), Vec3::zeros(), *other_responds);
+			system.step(1.0);
+			assert_eq!(system.collision_records.len(), 1);
+			let ghost_entity = system.get_entity(ghost).unwrap();
+			assert!((ghost_entity.velocity - Vec3::new(4.0, 0.0, 0.0)).magnitude() < EPSILON, "{:?}", ghost_entity.velocity);
+			assert!((ghost_entity.position - Vec3::new(4.0, 0.0, 0.0)).magnitude() < 0.001, "{:?}", ghost_entity.position);
+			let other_velocity = system.get_entity(other).unwrap().velocity;
+			let expected = if *other_responds { Vec3::new(4.0, 0.0, 0.0) } else { Vec3::zeros() };
+			assert!((other_velocity - expected).magnitude() < 0.001, "{:?}", other_velocity);
+		}
+	}
+
+	/// Verify the entity with the higher dominance isn't pushed around by collisions, but still pushes the other as if it were immovable.
+	#[test]
+	fn dominance() {
+		let add_ball = |system : &mut PhysicsSystem, position : Vec3, velocity : Vec3, dominance : i32| {
+			let mut entity = Entity::new();
+			entity.position = position;
+			entity.velocity = velocity;
+			entity.dominance = dominance;
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 1.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		// The player runs into someone standing still.
+		let mut system = PhysicsSystem::new();
+		let player = add_ball(&mut system, Vec3::zeros(), Vec3::new(2.0, 0.0, 0.0), 1);
+		let npc = add_ball(&mut system, Vec3::new(2.0, 0.0, 0.0), Vec3::zeros(), 0);
+		system.step(1.0);
+		assert_eq!(system.collision_records.len(), 1);
+		assert!((system.get_entity(player).unwrap().velocity - Vec3::new(2.0, 0.0, 0.0)).magnitude() < 0.001);
+		assert!((system.get_entity(npc).unwrap().velocity - Vec3::new(4.0, 0.0, 0.0)).magnitude() < 0.001);
+
+		// Someone runs into the player standing still.
+		let mut system = PhysicsSystem::new();
+		let npc = add_ball(&mut system, Vec3::zeros(), Vec3::new(2.0, 0.0, 0.0), 0);
+		let player = add_ball(&mut system, Vec3::new(2.0, 0.0, 0.0), Vec3::zeros(), 1);
+		system.step(1.0);
+		assert!(system.get_entity(player).unwrap().velocity.magnitude() < 0.001);
+		assert!((system.get_entity(npc).unwrap().velocity - Vec3::new(-2.0, 0.0, 0.0)).magnitude() < 0.001);
+	}
+
+	/// Verify the previous orientation is where the entity started the last step, and that it's dropped by a teleport.
+	#[test]
+	fn previous_orientation() {
+		let mut system = PhysicsSystem::new();
+		let ball = {
+			let mut entity = Entity::new();
+			entity.velocity = Vec3::new(2.0, 0.0, 0.0);
+			entity.angular_velocity = Vec3::new(0.0, 1.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 1.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		assert!(system.get_previous_orientation(ball).unwrap().position.magnitude() < EPSILON);
+		system.step(0.5);
+		system.step(0.5);
+		let previous = system.get_previous_orientation(ball).unwrap();
+		let current = *system.get_entity(ball).unwrap().get_last_orientation();
+		assert!((previous.position - Vec3::new(1.0, 0.0, 0.0)).magnitude() < EPSILON, "{:?}", previous.position);
+		assert!((previous.rotation_vec() - Vec3::new(0.0, 0.5, 0.0)).magnitude() < EPSILON);
+		assert!((current.position - Vec3::new(2.0, 0.0, 0.0)).magnitude() < EPSILON);
+		let halfway = Orientation::lerp(0.5, &previous, &current);
+		assert!((halfway.position - Vec3::new(1.5, 0.0, 0.0)).magnitude() < EPSILON);
+
+		system.teleport_entity(ball, Vec3::new(5.0, 0.0, 0.0), Vec3::zeros(), false).unwrap();
+		assert!((system.get_previous_orientation(ball).unwrap().position - Vec3::new(5.0, 0.0, 0.0)).magnitude() < EPSILON);
+		assert!(system.get_previous_orientation(EntityHandle::from_raw_parts(100, 0)).is_none());
+	}
+
+	/// Verify entities are removed (and reported) once their lifetime runs out, but not while they're disabled.
+	#[test]
+	fn lifetime() {
+		let mut system = PhysicsSystem::new();
+		let add_debris = |system : &mut PhysicsSystem, lifetime : Option<f32>| {
+			let mut entity = Entity::new();
+			entity.lifetime = lifetime;
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 1.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			(entity_handle, sphere_handle)
+		};
+		let (short, short_sphere) = add_debris(&mut system, Some(0.25));
+		let (long, _) = add_debris(&mut system, Some(0.45));
+		let (forever, _) = add_debris(&mut system, None);
+		let (paused, _) = add_debris(&mut system, Some(0.25));
+		system.set_entity_enabled(paused, false).unwrap();
+		let mut invalid = Entity::new();
+		invalid.lifetime = Some(f32::NAN);
+		assert!(system.add_entity(invalid).is_err());
+
+		system.step(0.1);
+		system.step(0.1);
+		assert!(system.take_expired_entities().is_empty());
+		assert!((system.get_entity(short).unwrap().lifetime.unwrap() - 0.05).abs() < EPSILON);
+		system.step(0.1);
+		assert_eq!(system.take_expired_entities(), vec![short]);
+		assert!(!system.is_valid_entity(short));
+		assert!(!system.is_valid_collider(short_sphere));
+		system.step(0.1);
+		system.step(0.1);
+		assert_eq!(system.take_expired_entities(), vec![long]);
+		assert!(system.is_valid_entity(forever));
+		assert_eq!(system.get_entity(paused).unwrap().lifetime, Some(0.25));
+	}
+
+	/// Verify only entities that moved during the last step (or were teleported before it) are listed as awake.
+	#[test]
+	fn awake_entities() {
+		let mut system = PhysicsSystem::new();
+		let add_ball = |system : &mut PhysicsSystem, position : Vec3, velocity : Vec3| {
+			let mut entity = Entity::new();
+			entity.position = position;
+			entity.velocity = velocity;
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 1.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		let moving = add_ball(&mut system, Vec3::zeros(), Vec3::new(1.0, 0.0, 0.0));
+		let resting = add_ball(&mut system, Vec3::new(0.0, 5.0, 0.0), Vec3::zeros());
+		assert!(system.awake_entities().is_empty());
+		// Everything is new the first time.
+		system.step(0.1);
+		assert_eq!(system.awake_entities().len(), 2);
+
+		system.step(0.1);
+		let awake = system.awake_entities();
+		assert_eq!(awake.iter().map(|(handle, _)| *handle).collect::<Vec<EntityHandle>>(), vec![moving]);
+		assert!((awake[0].1.position - Vec3::new(0.2, 0.0, 0.0)).magnitude() < EPSILON);
+
+		system.teleport_entity(resting, Vec3::new(0.0, 6.0, 0.0), Vec3::zeros(), false).unwrap();
+		let mut entity = system.get_entity(moving).unwrap();
+		entity.velocity = Vec3::zeros();
+		system.update_entity(moving, entity).unwrap();
+		system.step(0.1);
+		assert_eq!(system.awake_entities().iter().map(|(handle, _)| *handle).collect::<Vec<EntityHandle>>(), vec![resting]);
+		system.step(0.1);
+		assert!(system.awake_entities().is_empty());
+	}
+
+	/// Check that friction stops a grazing ball's contact point from sliding without reversing it (which needs the spin it gets to be solved along with the friction), and that a sliding box slows down to a stop without ever sliding backwards.
+	#[test]
+	fn friction_never_reverses() {
+		let add_floor = |system : &mut PhysicsSystem| {
+			let entity_handle = system.add_entity(Entity::new()).unwrap();
+			let mut plane = PlaneCollider::new();
+			plane.normal = Vec3::y();
+			plane.mass = INFINITY;
+			plane.static_friction_coefficient = 1.0;
+			plane.dynamic_friction_coefficient = 1.0;
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+		};
+		{ // A ball that hits the floor while sliding fast. Stopping the contact point only takes a bit of friction (since the ball starts rolling), so more than that would spin it backwards.
+			let mut system = PhysicsSystem::new();
+			add_floor(&mut system);
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 0.55, 0.0);
+			entity.velocity = Vec3::new(5.0, -3.0, 0.0);
+			let ball = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 1.0;
+			sphere.restitution_coefficient = 0.0;
+			sphere.static_friction_coefficient = 1.0;
+			sphere.dynamic_friction_coefficient = 1.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(ball)).unwrap();
+			system.step(0.1);
+			let entity = system.get_entity(ball).unwrap();
+			let contact_velocity = entity.velocity + entity.angular_velocity.cross(&Vec3::new(0.0, -0.5, 0.0));
+			println!("Ball: {:?} {:?} {:?}", entity.velocity, entity.angular_velocity, contact_velocity);
+			assert!(contact_velocity.magnitude() < 0.001);
+			// Rolling takes 2.22 of friction impulse (out of the 3 allowed), since a sliding impulse of one changes the contact point's speed by 1/mass + radius^2/moment = 2.25.
+			assert!((entity.velocity.x - (5.0 - 5.0 / 2.25)).abs() < 0.001);
+		}
+		{ // A box sliding along the floor.
+			let mut system = PhysicsSystem::new();
+			add_floor(&mut system);
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 0.501, 0.0);
+			entity.velocity = Vec3::new(3.0, 0.0, 0.0);
+			entity.own_mass = 1.0;
+			let block = system.add_entity(entity).unwrap();
+			let mut mesh = MeshCollider::new_cuboid(Vec3::repeat(0.5)).unwrap();
+			mesh.restitution_coefficient = 0.0;
+			mesh.static_friction_coefficient = 0.5;
+			mesh.dynamic_friction_coefficient = 0.5;
+			let mesh_handle = system.add_collider(ColliderWrapper::Mesh(mesh)).unwrap();
+			system.link_collider(mesh_handle, Some(block)).unwrap();
+			system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -9.8, 0.0)))).unwrap();
+			let mut last_speed = 3.0;
+			for _ in 0..90 {
+				system.step(1.0 / 60.0);
+				let speed = system.get_entity(block).unwrap().velocity.x;
+				assert!(-EPSILON < speed && speed <= last_speed + EPSILON, "{} after {}", speed, last_speed);
+				last_speed = speed;
+			}
+			// Slows down at about 4.9, so it's had plenty of time to stop.
+			assert!(last_speed < 0.01);
+			assert!(system.get_entity(block).unwrap().position.x < 1.0);
+		}
+	}
+
+	/// A restitution curve for testing: halves the coefficient (or gives junk).
+	#[derive(Debug)]
+	struct HalfRestitution(bool);
+
+	impl RestitutionCurve for HalfRestitution {
+		fn restitution(&self, coefficient : f32, _approaching_speed : f32) -> f32 {
+			if self.0 { NAN } else { coefficient / 2.0 }
+		}
+	}
+
+	/// Check each restitution model changes how fast a perfectly bouncy ball bounces off the floor.
+	#[test]
+	fn restitution_models() {
+		let bounce = |model : RestitutionModel, speed : f32| {
+			let mut system = PhysicsSystem::new();
+			system.restitution_model = model;
+			system.restitution_velocity_threshold = 0.5;
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 0.51, 0.0);
+			entity.velocity = Vec3::new(0.0, -speed, 0.0);
+			let ball = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 1.0;
+			sphere.restitution_coefficient = 1.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(ball)).unwrap();
+			let entity_handle = system.add_entity(Entity::new()).unwrap();
+			let mut plane = PlaneCollider::new();
+			plane.restitution_coefficient = 1.0;
+			plane.mass = INFINITY;
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+			system.step(0.1);
+			system.get_entity(ball).unwrap().velocity.y
+		};
+		assert!((bounce(RestitutionModel::Constant, 2.0) - 2.0).abs() < 0.001);
+		let tapered = RestitutionModel::Tapered { zero_speed: 1.0, full_speed: 3.0 };
+		assert!(bounce(tapered.clone(), 0.8).abs() < 0.001);
+		assert!((bounce(tapered.clone(), 2.0) - 1.0).abs() < 0.001);
+		assert!((bounce(tapered, 4.0) - 4.0).abs() < 0.001);
+		assert!((bounce(RestitutionModel::Custom(Arc::new(HalfRestitution(false))), 2.0) - 1.0).abs() < 0.001);
+		assert!(bounce(RestitutionModel::Custom(Arc::new(HalfRestitution(true))), 2.0).abs() < 0.001);
+		// Still never bounces below the threshold.
+		assert!(bounce(RestitutionModel::Constant, 0.4).abs() < 0.001);
+	}
+
+	/// A contact modifier for testing: either cancels every contact or makes it perfectly inelastic.
+	#[derive(Debug)]
+	struct TestModifier {
+		cancel : bool,
+		calls : usize,
+	}
+
+	impl ContactModifier for TestModifier {
+		fn modify(&mut self, contact : &mut ModifiableContact) -> bool {
+			self.calls += 1;
+			contact.restitution_coefficient = 0.0;
+			!self.cancel
+		}
+	}
+
+	/// Check that contact modifiers can change and cancel contacts.
+	#[test]
+	fn contact_modifier() {
+		let mut system = PhysicsSystem::new();
+		let (ball, sphere_handle) = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 3.0, 0.0);
+			entity.velocity = Vec3::new(0.0, -2.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(1.0);
+			sphere.mass = 1.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			(entity_handle, sphere_handle)
+		};
+		{
+			let entity_handle = system.add_entity(Entity::new()).unwrap();
+			let mut plane = PlaneCollider::new();
+			plane.normal = Vec3::y();
+			plane.mass = INFINITY;
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+		}
+		// Make the (normally perfectly elastic) bounce stick.
+		assert!(system.set_contact_modifier(sphere_handle, Some(Box::new(TestModifier { cancel: false, calls: 0 }))).unwrap().is_none());
+		system.step(2.0);
+		{
+			let entity = system.get_entity(ball).unwrap();
+			assert!((entity.position - Vec3::new(0.0, 1.0, 0.0)).magnitude() < EPSILON);
+			assert!(entity.velocity.magnitude() < EPSILON);
+			assert_eq!(system.collision_records.len(), 1);
+			assert!(system.collision_records[0].restitution_coefficient.abs() < EPSILON);
+		}
+		// Then cancel everything so it falls through.
+		let old = system.set_contact_modifier(sphere_handle, Some(Box::new(TestModifier { cancel: true, calls: 0 }))).unwrap().unwrap();
+		assert!(0 < old.downcast::<TestModifier>().unwrap().calls);
+		{
+			let mut entity = system.get_entity(ball).unwrap();
+			entity.velocity = Vec3::new(0.0, -1.0, 0.0);
+			system.update_entity(ball, entity).unwrap();
+		}
+		system.step(2.0);
+		{
+			let entity = system.get_entity(ball).unwrap();
+			assert!((entity.position - Vec3::new(0.0, -1.0, 0.0)).magnitude() < EPSILON);
+			assert!(system.collision_records.is_empty());
+		}
+		// Removing the collider removes the modifier.
+		system.remove_collider(sphere_handle);
+		assert!(system.set_contact_modifier(sphere_handle, None).is_err());
+	}
+
+	/// Check that collisions between entities that weren't changed by an iteration aren't looked for again.
+	#[test]
+	fn only_changed_pairs_rechecked() {
+		let mut system = PhysicsSystem::new();
+		// Two pairs of balls, far enough apart to not interfere. The first pair hits a quarter of the way through the step, the second pair three quarters of the way.
+		let mut sphere_handles = Vec::new();
+		for (x, speed) in [(0.0, 3.0), (100.0, 2.0)].iter() {
+			for side in [-1.0, 1.0].iter() {
+				let mut entity = Entity::new();
+				entity.position = Vec3::new(*x + side * 1.75, 0.0, 0.0);
+				entity.velocity = Vec3::new(-side * speed, 0.0, 0.0);
+				let entity_handle = system.add_entity(entity).unwrap();
+				let mut sphere = SphereCollider::new(1.0);
+				sphere.mass = 1.0;
+				let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+				system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+				sphere_handles.push(sphere_handle);
+			}
+		}
+		for handle in [sphere_handles[0], sphere_handles[2]].iter() {
+			system.set_contact_modifier(*handle, Some(Box::new(TestModifier { cancel: false, calls: 0 }))).unwrap();
+		}
+		system.step(0.5);
+		assert_eq!(system.collision_records.len(), 2);
+		let calls : Vec<usize> = [sphere_handles[0], sphere_handles[2]].iter().map(|handle| {
+			system.set_contact_modifier(*handle, None).unwrap().unwrap().downcast::<TestModifier>().unwrap().calls
+		}).collect();
+		// Each pair is checked when the step starts, and again right after it bounces. The second pair isn't checked again while the first pair's bounce is handled.
+		assert_eq!(calls, vec![2, 2]);
+	}
+
+	/// Sets up a (non-rotating) block resting on a slope with a 0.5 rise over run. Returns the system and the block's handle.
+	fn make_block_on_slope(static_friction_coefficient : f32, dynamic_friction_coefficient : f32, anisotropic_friction : Option<AnisotropicFriction>) -> (PhysicsSystem, EntityHandle) {
+		let mut system = PhysicsSystem::new();
+		let normal = Vec3::new(-1.0, 2.0, 0.0).normalize();
+		let block = {
+			let mut entity = Entity::new();
+			entity.own_mass = 1.0;
+			// Place the lowest edge of the cube just above the slope.
+			entity.position = normal * (Vec3::new(0.5, -0.5, 0.0).dot(&-normal) + 0.001);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut mesh = MeshCollider::new_cuboid(Vec3::repeat(0.5)).unwrap();
+			mesh.restitution_coefficient = 0.0;
+			mesh.static_friction_coefficient = static_friction_coefficient;
+			mesh.dynamic_friction_coefficient = dynamic_friction_coefficient;
+			mesh.anisotropic_friction = anisotropic_friction;
+			let mesh_handle = system.add_collider(ColliderWrapper::Mesh(mesh)).unwrap();
+			system.link_collider(mesh_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		{
+			let entity_handle = system.add_entity(Entity::new()).unwrap();
+			let mut plane = PlaneCollider::new();
+			plane.normal = normal;
+			plane.mass = INFINITY;
+			plane.static_friction_coefficient = 1.0;
+			plane.dynamic_friction_coefficient = 1.0;
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+		}
+		system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -1.0, 0.0)))).unwrap();
+		(system, block)
+	}
+
+	/// Check that a ball resting on another ball stays put (and goes to sleep), whether or not the solver is loose about what counts as resting.
+	#[test]
+	fn stacked_balls() {
+		for contact_velocity_threshold in [EPSILON, 0.01].iter() {
+			let mut system = PhysicsSystem::new();
+			system.contact_velocity_threshold = *contact_velocity_threshold;
+			{
+				let entity_handle = system.add_entity(Entity::new()).unwrap();
+				let mut plane = PlaneCollider::new();
+				plane.normal = Vec3::y();
+				plane.mass = INFINITY;
+				let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+				system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+			}
+			let mut balls = Vec::new();
+			for index in 0..2 {
+				let mut entity = Entity::new();
+				entity.position = Vec3::new(0.0, 0.5 + index as f32 + 0.01 * (index + 1) as f32, 0.0);
+				let entity_handle = system.add_entity(entity).unwrap();
+				let mut sphere = SphereCollider::new(0.5);
+				sphere.mass = 1.0;
+				sphere.restitution_coefficient = 0.0;
+				let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+				system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+				balls.push(entity_handle);
+			}
+			system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -1.0, 0.0)))).unwrap();
+
+			for _ in 0..100 {
+				system.step(0.05);
+			}
+			for (index, handle) in balls.iter().enumerate() {
+				let entity = system.get_entity(*handle).unwrap();
+				println!("Ball {} ({:?}): {:?} {:?}", index, contact_velocity_threshold, entity.position, entity.velocity);
+				assert!((entity.position - Vec3::new(0.0, 0.5 + index as f32, 0.0)).magnitude() < 0.01);
+				assert!(entity.was_asleep());
+			}
+		}
+	}
+
+	/// Check that limiting how much friction can do lets a block slide down a slope that'd otherwise hold it.
+	#[test]
+	fn max_friction_percent() {
+		let (mut system, block) = make_block_on_slope(1.0, 1.0, None);
+		system.max_friction_percent = 0.0;
+		system.energy_sleep_threshold = 0.0;
+		let start = system.get_entity(block).unwrap().position;
+		for _ in 0..50 {
+			system.step(0.05);
+		}
+		let entity = system.get_entity(block).unwrap();
+		println!("Moved {:?}", start - entity.position);
+		assert!(0.1 < (start - entity.position).x);
+	}
+
+	/// Check that Coulomb friction holds a block on a slope below the friction angle, and lets it slide above it.
+	#[test]
+	fn coulomb_stick_slip() {
+		{ // Static friction is enough to hold it.
+			let (mut system, block) = make_block_on_slope(0.6, 0.4, None);
+			system.friction_model = FrictionModel::Coulomb;
+			let start = system.get_entity(block).unwrap().position;
+			for _ in 0..100 {
+				system.step(0.05);
+			}
+			let entity = system.get_entity(block).unwrap();
+			assert!((entity.position - start).magnitude() < 0.01);
+			assert!(entity.velocity.magnitude() < EPSILON);
+		}
+		{ // Static friction isn't enough to hold it, so it slides down (and keeps slipping).
+			let (mut system, block) = make_block_on_slope(0.4, 0.4, None);
+			system.friction_model = FrictionModel::Coulomb;
+			system.energy_sleep_threshold = 0.0; // It slides slowly enough that it would otherwise be put to sleep.
+			let start = system.get_entity(block).unwrap().position;
+			for _ in 0..100 {
+				system.step(0.05);
+			}
+			let entity = system.get_entity(block).unwrap();
+			// Down the slope is towards -X. Should accelerate at 0.089 (i.e. the difference between gravity and friction along the slope).
+			assert!(0.05 < (start - entity.position).x);
+			assert!(0.2 < -entity.velocity.x);
+			let floor = system.collision_records.last().unwrap().other_entity(block).unwrap();
+			assert_eq!(system.is_sticking(block, floor), Some(false));
+		}
+	}
+
+	/// Check that something going to sleep doesn't stop things that are still awake from colliding with it. i.e. a ball sliding across the (static, so immediately asleep) ground mustn't drop through it.
+	#[test]
+	fn sleeping_only_links_to_sleeping() {
+		let mut system = PhysicsSystem::new();
+		let ground = {
+			let entity_handle = system.add_entity(Entity::new()).unwrap();
+			let mut plane = PlaneCollider::new();
+			plane.normal = Vec3::y();
+			plane.mass = INFINITY;
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		let ball = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 0.5, 0.0);
+			entity.velocity = Vec3::new(0.5, 0.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 1.0;
+			sphere.restitution_coefficient = 0.0;
+			// No friction, so it keeps sliding along (and stays awake).
+			sphere.static_friction_coefficient = 0.0;
+			sphere.dynamic_friction_coefficient = 0.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -9.8, 0.0)))).unwrap();
+		for _ in 0..(60 * 5) {
+			system.step(1.0 / 60.0);
+		}
+		assert!(system.get_entity(ground).unwrap().was_asleep());
+		let entity = system.get_entity(ball).unwrap();
+		assert!(!entity.was_asleep());
+		assert!((entity.position.y - 0.5).abs() < 0.01, "{:?}", entity.position);
+		assert!(2.0 < entity.position.x, "{:?}", entity.position);
+	}
+
+	/// Check that anisotropic friction lets a block slide freely along its friction direction, but not across it.
+	#[test]
+	fn anisotropic_friction() {
+		{ // Pointed down the slope, so nothing stops it from sliding.
+			let (mut system, block) = make_block_on_slope(1.0, 1.0, Some(AnisotropicFriction::new(Vec3::new(1.0, 0.0, 0.0), 0.0, 1.0)));
+			system.energy_sleep_threshold = 0.0;
+			let start = system.get_entity(block).unwrap().position;
+			for _ in 0..50 {
+				system.step(0.05);
+			}
+			let entity = system.get_entity(block).unwrap();
+			assert!(0.1 < (start - entity.position).x);
+			assert!(entity.velocity.z.abs() < EPSILON);
+		}
+		{ // Pointed across the slope, so it's held in place like normal.
+			let (mut system, block) = make_block_on_slope(1.0, 1.0, Some(AnisotropicFriction::new(Vec3::new(0.0, 0.0, 1.0), 0.0, 1.0)));
+			let start = system.get_entity(block).unwrap().position;
+			for _ in 0..50 {
+				system.step(0.05);
+			}
+			let entity = system.get_entity(block).unwrap();
+			assert!((entity.position - start).magnitude() < 0.01);
+		}
+	}
+
+	/// Check that cloths are moved by step(), and land on colliders rather than going through them.
+	#[test]
+	fn cloth_lands() {
+		let mut system = PhysicsSystem::new();
+		{
+			let entity_handle = system.add_entity(Entity::new()).unwrap();
+			let mut plane = PlaneCollider::new();
+			plane.mass = INFINITY;
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+		}
+		let mut cloth = Cloth::new(Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.25, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.25), 5, 5, 0.1).unwrap();
+		cloth.gravity = Vec3::new(0.0, -1.0, 0.0);
+		let cloth_handle = system.add_cloth(cloth);
+		for _ in 0..200 {
+			system.step(0.01);
+		}
+		for position in system.get_cloth(cloth_handle).unwrap().get_positions() {
+			assert!(0.0 < position.y && position.y < 0.01);
+		}
+		assert!(system.remove_cloth(cloth_handle).is_some());
+		assert!(system.get_cloth(cloth_handle).is_none());
+	}
+
+	/// Check that ray casts can be masked by query groups, and that mesh hits say where on the mesh they were.
+	#[test]
+	fn masked_ray_cast_with_face() {
+		let mut system = PhysicsSystem::new();
+		let (cube, cube_mesh) = {
+			let entity_handle = system.add_entity(Entity::new()).unwrap();
+			let mesh_handle = system.add_collider(ColliderWrapper::Mesh(MeshCollider::new_cuboid(Vec3::repeat(1.0)).unwrap())).unwrap();
+			system.link_collider(mesh_handle, Some(entity_handle)).unwrap();
+			(entity_handle, mesh_handle)
+		};
+		let ball = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 3.0, 0.0);
+			entity.query_groups = 2;
+			let entity_handle = system.add_entity(entity).unwrap();
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(SphereCollider::new(0.5))).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		let origin = Vec3::new(0.2, 10.0, 0.3);
+		let down = Vec3::new(0.0, -1.0, 0.0);
+		let hit = system.cast_ray(&origin, &down, 20.0, None).unwrap();
+		assert!(hit.entity == ball);
+		assert!(hit.face.is_none());
+		assert!(system.cast_ray_masked(&origin, &down, 20.0, None, 4).is_none());
+		// Skipping the ball hits the top of the cube.
+		let hit = system.cast_ray_masked(&origin, &down, 20.0, None, 1).unwrap();
+		assert!(hit.entity == cube);
+		assert!((hit.position - Vec3::new(0.2, 1.0, 0.3)).magnitude() < EPSILON);
+		let face = hit.face.unwrap();
+		assert_eq!(3, face.face); // The +Y side.
+		let mesh = match system.get_collider(cube_mesh).unwrap() {
+			ColliderWrapper::Mesh(mesh) => mesh,
+			_ => panic!("Should be a mesh."),
+		};
+		assert!(face.triangle.iter().all(|vertex| mesh.get_faces()[face.face].contains(vertex)));
+		assert!((face.barycentric.sum() - 1.0).abs() < EPSILON);
+		assert!(-EPSILON < face.barycentric.min());
+		let rebuilt = (0..3).fold(Vec3::zeros(), |total, index| total + mesh.get_vertices()[face.triangle[index]] * face.barycentric[index]);
+		assert!((rebuilt - hit.position).magnitude() < EPSILON);
+	}
+
+	/// Check that predicting a throw follows the arc and stops at the floor, without changing anything.
+	#[test]
+	fn predict_arc() {
+		let mut system = PhysicsSystem::new();
+		let floor = system.add_entity(Entity::new()).unwrap();
+		{
+			let mut plane = PlaneCollider::new();
+			plane.mass = INFINITY;
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			system.link_collider(plane_handle, Some(floor)).unwrap();
+		}
+		let ball = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(-3.0, 0.5, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 1.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		let gravity = Vec3::new(0.0, -10.0, 0.0);
+		// Falls 5 units in one second, so should land at x = 1.
+		let start = Vec3::new(0.0, 5.5, 0.0);
+		let velocity = Vec3::new(1.0, 0.0, 0.0);
+		let prediction = system.predict(PredictedShape::Sphere(0.5), &start, &velocity, &gravity, 3.0, 0.1).unwrap();
+		let (time, hit) = prediction.hit.unwrap();
+		assert!((time - 1.0).abs() < 0.01);
+		assert!(hit.entity == floor);
+		assert!((hit.position - Vec3::new(1.0, 0.0, 0.0)).magnitude() < 0.01);
+		assert!((hit.normal - Vec3::new(0.0, 1.0, 0.0)).magnitude() < EPSILON);
+		assert!(11 <= prediction.positions.len() && prediction.positions.len() <= 12);
+		assert!((prediction.positions[5] - Vec3::new(0.5, 5.5 - 1.25, 0.0)).magnitude() < 0.01);
+		// Predicting an entity uses its colliders, and never hits itself.
+		let prediction = system.predict(PredictedShape::Entity(ball), &Vec3::new(-3.0, 5.5, 0.0), &velocity, &gravity, 3.0, 0.1).unwrap();
+		assert!(prediction.hit.unwrap().1.entity == floor);
+		assert!((system.get_entity(ball).unwrap().position - Vec3::new(-3.0, 0.5, 0.0)).magnitude() < EPSILON);
+		// Nothing to hit.
+		let prediction = system.predict(PredictedShape::Sphere(0.5), &start, &velocity, &Vec3::zeros(), 1.0, 0.25).unwrap();
+		assert!(prediction.hit.is_none());
+		assert_eq!(5, prediction.positions.len());
+		assert!((prediction.positions[4] - Vec3::new(1.0, 5.5, 0.0)).magnitude() < EPSILON);
+		assert!(system.predict(PredictedShape::Sphere(0.5), &start, &velocity, &gravity, 1.0, 0.0).is_err());
+	}
+
+	/// A stiff spring pulling everything towards the origin.
+	#[derive(Debug)]
+	struct SpringGenerator {
+		stiffness : f32,
+		calls : usize,
+	}
+
+	impl UnaryForceGenerator for SpringGenerator {
+		fn make_force(&mut self, _dt : f32, _world : &dyn WorldQuery, entity : &EntityView) -> Force {
+			self.calls += 1;
+			let position = entity.position();
+			Force::new(-position * self.stiffness, position)
+		}
+	}
+
+	/// Verify splitting up the forces keeps a stiff spring from blowing up.
+	#[test]
+	fn force_substeps() {
+		for force_substeps in [1, 4].iter() {
+			let mut system = PhysicsSystem::new();
+			system.force_substeps = *force_substeps;
+			let ball = {
+				let mut entity = Entity::new();
+				entity.position = Vec3::new(1.0, 0.0, 0.0);
+				entity.own_mass = 1.0;
+				system.add_entity(entity).unwrap()
+			};
+			let spring = system.add_unary_force_generator(Box::new(SpringGenerator { stiffness: 1000.0, calls: 0 })).unwrap();
+			let mut furthest = 0.0f32;
+			for _ in 0..8 {
+				system.step(0.1);
+				furthest = furthest.max(system.get_entity(ball).unwrap().position.magnitude());
+			}
+			let calls = system.remove_unary_force_generator(spring).unwrap().downcast_ref::<SpringGenerator>().unwrap().calls;
+			assert_eq!(calls, 8 * *force_substeps as usize);
+			if 1 == *force_substeps {
+				assert!(1000.0 < furthest, "{}", furthest);
+			} else {
+				assert!(furthest < 1.1, "{}", furthest);
+			}
+		}
+	}
+
+	/// A generator that can't be cloned, pushing everything along +X.
+	#[derive(Debug)]
+	struct PushGenerator;
+
+	impl UnaryForceGenerator for PushGenerator {
+		fn make_force(&mut self, _dt : f32, _world : &dyn WorldQuery, entity : &EntityView) -> Force {
+			Force::new(Vec3::new(1.0, 0.0, 0.0), entity.position())
+		}
+	}
+
+	/// Check that a cloned system simulates the same as the original (without affecting it), and that generators that can't be cloned are skipped.
+	#[test]
+	fn clone_world() {
+		let mut system = PhysicsSystem::new();
+		let ball = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 2.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 1.0;
+			sphere.restitution_coefficient = 0.5;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		{
+			let entity_handle = system.add_entity(Entity::new()).unwrap();
+			let mut plane = PlaneCollider::new();
+			plane.mass = INFINITY;
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+		}
+		system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -1.0, 0.0)))).unwrap();
+		let mut copy = system.clone();
+		for _ in 0..30 {
+			copy.step(0.1);
+		}
+		assert!((system.get_entity(ball).unwrap().position - Vec3::new(0.0, 2.0, 0.0)).magnitude() < EPSILON);
+		for _ in 0..30 {
+			system.step(0.1);
+		}
+		assert!((system.get_entity(ball).unwrap().position - copy.get_entity(ball).unwrap().position).magnitude() < EPSILON);
+		assert!((system.get_entity(ball).unwrap().velocity - copy.get_entity(ball).unwrap().velocity).magnitude() < EPSILON);
+		// The push isn't copied, so only the original is pushed. (Lifting the ball up so it's awake and off of the floor.)
+		let push = system.add_unary_force_generator(Box::new(PushGenerator)).unwrap();
+		system.teleport_entity(ball, Vec3::new(0.0, 5.0, 0.0), Vec3::zeros(), false).unwrap();
+		let mut copy = system.clone();
+		for _ in 0..5 {
+			system.step(0.1);
+			copy.step(0.1);
+		}
+		assert!(0.05 < system.get_entity(ball).unwrap().position.x);
+		assert!(copy.get_entity(ball).unwrap().position.x.abs() < EPSILON);
+		assert!(copy.remove_unary_force_generator(push).is_some());
+	}
+
+	/// Check that a system can be stepped on another thread while this one reads from it.
+	#[test]
+	fn background_thread() {
+		use std::sync::{Arc, RwLock};
+		let mut system = PhysicsSystem::new();
+		let ball = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 100.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 1.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -1.0, 0.0)))).unwrap();
+		let shared = Arc::new(RwLock::new(system));
+		let stepper = {
+			let shared = shared.clone();
+			std::thread::spawn(move || {
+				for _ in 0..100 {
+					shared.write().unwrap().step(0.01);
+				}
+			})
+		};
+		let mut last_height = 100.0;
+		for _ in 0..100 {
+			let height = shared.read().unwrap().get_entity(ball).unwrap().position.y;
+			assert!(height <= last_height);
+			last_height = height;
+		}
+		stepper.join().unwrap();
+		let entity = shared.read().unwrap().get_entity(ball).unwrap();
+		assert!((entity.velocity.y + 1.0).abs() < 0.001);
+	}
+
+	/// Check that readers only see what step() did after it's been published.
+	#[test]
+	fn double_buffering() {
+		let mut system = PhysicsSystem::new();
+		let ball = {
+			let mut entity = Entity::new();
+			entity.velocity = Vec3::new(1.0, 0.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 1.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			entity_handle
+		};
+		assert!(system.publish().is_err());
+		let reader = system.enable_double_buffering();
+		system.step(0.5);
+		assert_eq!(0, reader.latest().version);
+		assert!(reader.latest().orientations.is_empty());
+		system.publish().unwrap();
+		let first = reader.latest();
+		assert_eq!(1, first.version);
+		assert!((first.orientations[&ball].position - Vec3::new(0.5, 0.0, 0.0)).magnitude() < EPSILON);
+		// Stepping doesn't change what's visible, and publishing again without stepping doesn't either.
+		system.step(0.5);
+		assert_eq!(1, reader.latest().version);
+		system.publish().unwrap();
+		system.publish().unwrap();
+		let other_thread = reader.clone();
+		let second = std::thread::spawn(move || other_thread.latest()).join().unwrap();
+		assert_eq!(2, second.version);
+		assert!((second.orientations[&ball].position - Vec3::new(1.0, 0.0, 0.0)).magnitude() < EPSILON);
+		// Old snapshots aren't changed.
+		assert!((first.orientations[&ball].position - Vec3::new(0.5, 0.0, 0.0)).magnitude() < EPSILON);
+	}
+
+	/// Counts the allocations made on each thread, so tests can check that something doesn't allocate.
+	struct CountingAllocator;
+
+	thread_local! {
+		static ALLOCATIONS : std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+	}
+
+	unsafe impl std::alloc::GlobalAlloc for CountingAllocator {
+		unsafe fn alloc(&self, layout : std::alloc::Layout) -> *mut u8 {
+			let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+			std::alloc::System.alloc(layout)
+		}
+
+		unsafe fn dealloc(&self, pointer : *mut u8, layout : std::alloc::Layout) {
+			std::alloc::System.dealloc(pointer, layout)
+		}
+	}
+
+	#[global_allocator]
+	static ALLOCATOR : CountingAllocator = CountingAllocator;
+
+	/// Check that once things have settled into a routine, step() doesn't allocate anything.
+	#[test]
+	fn step_without_allocating() {
+		let mut system = PhysicsSystem::new();
+		{
+			let entity_handle = system.add_entity(Entity::new()).unwrap();
+			let mut plane = PlaneCollider::new();
+			plane.mass = INFINITY;
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			system.link_collider(plane_handle, Some(entity_handle)).unwrap();
+		}
+		// A ball that settles down and goes to sleep, a ball that keeps bouncing, and a cube that keeps bouncing.
+		for (x, restitution_coefficient) in [(-3.0, 0.0), (0.0, 1.0)].iter() {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(*x, 1.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 1.0;
+			sphere.restitution_coefficient = *restitution_coefficient;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+		}
+		{
+			let mut entity = Entity::new();
+			entity.own_mass = 1.0;
+			entity.position = Vec3::new(3.0, 1.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut mesh = MeshCollider::new_cuboid(Vec3::repeat(0.5)).unwrap();
+			mesh.restitution_coefficient = 1.0;
+			let mesh_handle = system.add_collider(ColliderWrapper::Mesh(mesh)).unwrap();
+			system.link_collider(mesh_handle, Some(entity_handle)).unwrap();
+		}
+		system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -9.8, 0.0)))).unwrap();
+
+		for _ in 0..300 {
+			system.step(1.0 / 60.0);
+		}
+		system.debug.clear();
+		ALLOCATIONS.with(|count| count.set(0));
+		let mut collided = 0;
+		for _ in 0..120 {
+			system.step(1.0 / 60.0);
+			collided += system.collision_records.len();
+		}
+		assert_eq!(0, ALLOCATIONS.with(|count| count.get()));
+		// Make sure that actually covered collisions (for both bouncing things), and sleeping (the plane counts as asleep too).
+		assert!(2 <= collided);
+		assert_eq!(2, system.entities.borrow().iter().filter(|(_, entity)| entity.asleep).count());
+	}
+
+	/// Verify the fixed-point positions keep track of movements too small for an f32 (far from the origin), and pick up on positions being set directly.
+	#[cfg(feature = "fixed-point")]
+	#[test]
+	fn fixed_point_positions() {
+		use crate::fixed::Fixed;
+
+		let mut system = PhysicsSystem::new();
+		let handle = {
+			let mut entity = Entity::new();
+			entity.own_mass = 1.0;
+			entity.position = Vec3::new(100000.0, 0.0, 0.0);
+			entity.velocity = Vec3::new(1.0, 0.0, 0.0);
+			system.add_entity(entity).unwrap()
+		};
+		// Each step moves far less than the gap between f32 values out there, so doing this in floats would never move at all.
+		for _ in 0..1000 {
+			system.step(0.00001);
+		}
+		let (position, velocity) = system.get_fixed_state(handle).unwrap();
+		assert!(((position.x - Fixed::from_f32(100000.0)).to_f32() - 0.01).abs() < 0.0001);
+		assert_eq!(position.y, Fixed::ZERO);
+		assert_eq!(velocity.to_vec3(), Vec3::new(1.0, 0.0, 0.0));
+		assert!(100000.0 < system.get_entity(handle).unwrap().position.x);
+
+		let mut entity = system.get_entity(handle).unwrap();
+		entity.position = Vec3::new(5.0, 0.0, 0.0);
+		system.update_entity(handle, entity).unwrap();
+		assert_eq!(system.get_fixed_state(handle).unwrap().0, FixedVec3::from_vec3(&Vec3::new(5.0, 0.0, 0.0)));
+		system.step(0.5);
+		assert_eq!(system.get_fixed_state(handle).unwrap().0, FixedVec3::from_vec3(&Vec3::new(5.5, 0.0, 0.0)));
+	}
+
+	/// Verify the fixed-point state is bit-for-bit the same after many steps with collisions, and that velocity times dt is added up in fixed-point.
+	#[cfg(feature = "fixed-point")]
+	#[test]
+	fn fixed_point_determinism() {
+		use crate::fixed::Fixed;
+
+		let dt = 1.0 / 60.0;
+		let build = || {
+			let mut system = PhysicsSystem::new();
+			let floor = system.add_entity(Entity::new()).unwrap();
+			let mut plane = PlaneCollider::new();
+			plane.mass = f32::INFINITY;
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			system.link_collider(plane_handle, Some(floor)).unwrap();
+			let mut handles = Vec::new();
+			for index in 0..4 {
+				let mut entity = Entity::new();
+				entity.position = Vec3::new(index as f32 * 0.7, 1.0 + index as f32, 0.0);
+				entity.velocity = Vec3::new(0.3, 0.0, -0.2 * index as f32);
+				let handle = system.add_entity(entity).unwrap();
+				let mut sphere = SphereCollider::new(0.5);
+				sphere.mass = 1.0;
+				sphere.restitution_coefficient = 0.8;
+				let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+				system.link_collider(sphere_handle, Some(handle)).unwrap();
+				handles.push(handle);
+			}
+			system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -9.8, 0.0)))).unwrap();
+			(system, handles)
+		};
+		let (mut first, handles) = build();
+		let (mut second, _) = build();
+		let mut collided = 0;
+		for _ in 0..600 {
+			first.step(dt);
+			second.step(dt);
+			collided += first.collision_records.len();
+			for handle in &handles {
+				assert_eq!(first.get_fixed_state(*handle), second.get_fixed_state(*handle));
+			}
+		}
+		assert!(0 < collided);
+
+		// Without any collisions, the position is exactly the sum of each step's (fixed-point) velocity times dt.
+		let mut system = PhysicsSystem::new();
+		let handle = {
+			let mut entity = Entity::new();
+			entity.own_mass = 1.0;
+			entity.position = Vec3::new(0.0, 1000.0, 0.0);
+			entity.velocity = Vec3::new(0.5, 0.0, 0.0);
+			system.add_entity(entity).unwrap()
+		};
+		system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -9.8, 0.0)))).unwrap();
+		let (mut expected, _) = system.get_fixed_state(handle).unwrap();
+		for _ in 0..600 {
+			system.step(dt);
+			let (position, velocity) = system.get_fixed_state(handle).unwrap();
+			expected += velocity.scale(Fixed::from_f32(dt));
+			assert_eq!(position, expected);
+		}
+		assert!(system.get_entity(handle).unwrap().velocity.y < -90.0);
+	}
+
+	// TODO? Only angular inertia into a collision.
+	// TODO? Check attaching a collider with mass after rotation has already begun -> verify doesn't look weird.
+}
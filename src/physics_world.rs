@@ -0,0 +1,227 @@
+use crate::types::{Vec3, EntityHandle, ColliderHandle, UnaryForceGeneratorHandle, JointHandle};
+use crate::entity::Entity;
+use crate::collider_wrapper::ColliderWrapper;
+use crate::unary_force_generator::UnaryForceGenerator;
+use crate::query::RayHit;
+use crate::ball_joint::BallJoint;
+use crate::physics_system::PhysicsSystem;
+
+/// Everything a physics solver backend needs to provide so it can be swapped out for another.
+///
+/// Entities, colliders, and force generators are all described the same way for every backend (i.e. with [Entity] and [ColliderWrapper]). Helpers like [crate::Vehicle] only work through this trait, so they work with any backend.
+pub trait PhysicsWorld {
+	/// Adds an entity and returns its handle.
+	fn add_entity(&mut self, source : Entity) -> Result<EntityHandle, ()>;
+
+	/// Removes an entity and all of it's associated colliders. Returns if anything changed.
+	fn remove_entity(&mut self, handle : EntityHandle) -> bool;
+
+	/// Gets a copy of an entity's public interface.
+	fn get_entity(&self, handle : EntityHandle) -> Option<Entity>;
+
+	/// Gets just an entity's total mass and (center of mass) position. Meant for force generators, which run for every entity on every step and shouldn't need to copy the whole entity.
+	fn get_entity_mass_and_position(&self, handle : EntityHandle) -> Option<(f32, Vec3)> {
+		self.get_entity(handle).map(|entity| (entity.get_last_total_mass(), entity.position))
+	}
+
+	/// Updates an entity with the given values.
+	fn update_entity(&mut self, handle : EntityHandle, source : Entity) -> Result<(), ()>;
+
+	/// Adds a collider.
+	fn add_collider(&mut self, source : ColliderWrapper) -> Result<ColliderHandle, ()>;
+
+	/// Removes a collider.
+	fn remove_collider(&mut self, handle : ColliderHandle);
+
+	/// Gets a copy of a collider's public interface.
+	fn get_collider(&self, handle : ColliderHandle) -> Option<ColliderWrapper>;
+
+	/// Updates a collider with the given values.
+	fn update_collider(&mut self, handle : ColliderHandle, source : ColliderWrapper) -> Result<(), ()>;
+
+	/// Links a collider to an entity (or unlinks it if `entity_handle` is None).
+	fn link_collider(&mut self, collider_handle : ColliderHandle, entity_handle : Option<EntityHandle>) -> Result<(), ()>;
+
+	/// Adds a UnaryForceGenerator.
+	fn add_unary_force_generator(&mut self, generator : Box<dyn UnaryForceGenerator>) -> Result<UnaryForceGeneratorHandle, ()>;
+
+	/// Removes and returns a UnaryForceGenerator.
+	fn remove_unary_force_generator(&mut self, handle : UnaryForceGeneratorHandle) -> Option<Box<dyn UnaryForceGenerator>>;
+
+	/// Turns a force generator off or on, without removing it.
+	fn set_force_generator_enabled(&mut self, handle : UnaryForceGeneratorHandle, enabled : bool) -> Result<(), ()>;
+
+	/// Sets what a force generator's forces are multiplied by.
+	fn set_force_generator_strength(&mut self, handle : UnaryForceGeneratorHandle, strength : f32) -> Result<(), ()>;
+
+	/// Moves everything forward by the given time step.
+	fn step(&mut self, dt : f32);
+
+	/// Applies an impulse at a (world) position to an entity.
+	fn apply_impulse(&mut self, handle : EntityHandle, position : &Vec3, impulse : &Vec3) -> Result<(), ()>;
+
+	/// Adds a joint, returning its handle. Not every backend has joints, and the ones that don't always fail.
+	fn add_joint(&mut self, _joint : BallJoint) -> Result<JointHandle, ()> {
+		Err(()) // TODO: An error type.
+	}
+
+	/// Finds the first collider that a ray hits.
+	fn cast_ray(&self, origin : &Vec3, direction : &Vec3, max_distance : f32, ignore : Option<EntityHandle>) -> Option<RayHit>;
+
+	/// Finds the first collider that a moving sphere hits.
+	fn cast_sphere(&self, origin : &Vec3, direction : &Vec3, max_distance : f32, radius : f32, ignore : Option<EntityHandle>) -> Option<RayHit>;
+
+	/// Finds the first collider that a ray hits, skipping entities whose query groups share no bits with `mask`.
+	fn cast_ray_masked(&self, origin : &Vec3, direction : &Vec3, max_distance : f32, ignore : Option<EntityHandle>, mask : u32) -> Option<RayHit>;
+
+	/// Finds the first collider that a moving sphere hits, skipping entities whose query groups share no bits with `mask`.
+	fn cast_sphere_masked(&self, origin : &Vec3, direction : &Vec3, max_distance : f32, radius : f32, ignore : Option<EntityHandle>, mask : u32) -> Option<RayHit>;
+}
+
+/// The original backend: continuous collision detection, with impulses applied at each time of impact.
+pub type ImpulseWorld = PhysicsSystem;
+
+impl PhysicsWorld for PhysicsSystem {
+	fn add_entity(&mut self, source : Entity) -> Result<EntityHandle, ()> { PhysicsSystem::add_entity(self, source) }
+
+	fn remove_entity(&mut self, handle : EntityHandle) -> bool { PhysicsSystem::remove_entity(self, handle) }
+
+	fn get_entity(&self, handle : EntityHandle) -> Option<Entity> { PhysicsSystem::get_entity(self, handle) }
+
+	fn get_entity_mass_and_position(&self, handle : EntityHandle) -> Option<(f32, Vec3)> {
+		self.entities.borrow().get(handle).map(|entity| (entity.get_total_mass(), entity.orientation.position))
+	}
+
+	fn update_entity(&mut self, handle : EntityHandle, source : Entity) -> Result<(), ()> { PhysicsSystem::update_entity(self, handle, source) }
+
+	fn add_collider(&mut self, source : ColliderWrapper) -> Result<ColliderHandle, ()> { PhysicsSystem::add_collider(self, source) }
+
+	fn remove_collider(&mut self, handle : ColliderHandle) { PhysicsSystem::remove_collider(self, handle) }
+
+	fn get_collider(&self, handle : ColliderHandle) -> Option<ColliderWrapper> { PhysicsSystem::get_collider(self, handle) }
+
+	fn update_collider(&mut self, handle : ColliderHandle, source : ColliderWrapper) -> Result<(), ()> { PhysicsSystem::update_collider(self, handle, source) }
+
+	fn link_collider(&mut self, collider_handle : ColliderHandle, entity_handle : Option<EntityHandle>) -> Result<(), ()> { PhysicsSystem::link_collider(self, collider_handle, entity_handle) }
+
+	fn add_unary_force_generator(&mut self, generator : Box<dyn UnaryForceGenerator>) -> Result<UnaryForceGeneratorHandle, ()> { PhysicsSystem::add_unary_force_generator(self, generator) }
+
+	fn remove_unary_force_generator(&mut self, handle : UnaryForceGeneratorHandle) -> Option<Box<dyn UnaryForceGenerator>> { PhysicsSystem::remove_unary_force_generator(self, handle) }
+
+	fn set_force_generator_enabled(&mut self, handle : UnaryForceGeneratorHandle, enabled : bool) -> Result<(), ()> { PhysicsSystem::set_force_generator_enabled(self, handle, enabled) }
+
+	fn set_force_generator_strength(&mut self, handle : UnaryForceGeneratorHandle, strength : f32) -> Result<(), ()> { PhysicsSystem::set_force_generator_strength(self, handle, strength) }
+
+	fn step(&mut self, dt : f32) { PhysicsSystem::step(self, dt) }
+
+	fn apply_impulse(&mut self, handle : EntityHandle, position : &Vec3, impulse : &Vec3) -> Result<(), ()> { PhysicsSystem::apply_impulse(self, handle, *position, *impulse) }
+
+	fn cast_ray(&self, origin : &Vec3, direction : &Vec3, max_distance : f32, ignore : Option<EntityHandle>) -> Option<RayHit> { PhysicsSystem::cast_ray(self, origin, direction, max_distance, ignore) }
+
+	fn cast_sphere(&self, origin : &Vec3, direction : &Vec3, max_distance : f32, radius : f32, ignore : Option<EntityHandle>) -> Option<RayHit> { PhysicsSystem::cast_sphere(self, origin, direction, max_distance, radius, ignore) }
+
+	fn cast_ray_masked(&self, origin : &Vec3, direction : &Vec3, max_distance : f32, ignore : Option<EntityHandle>, mask : u32) -> Option<RayHit> { PhysicsSystem::cast_ray_masked(self, origin, direction, max_distance, ignore, mask) }
+
+	fn cast_sphere_masked(&self, origin : &Vec3, direction : &Vec3, max_distance : f32, radius : f32, ignore : Option<EntityHandle>, mask : u32) -> Option<RayHit> { PhysicsSystem::cast_sphere_masked(self, origin, direction, max_distance, radius, ignore, mask) }
+}
+
+/// Tests that every backend should pass. Each takes a fresh world, so each backend just needs to call them.
+#[cfg(test)]
+pub mod shared_tests {
+	use super::*;
+	use std::f32::INFINITY;
+	use crate::consts::EPSILON;
+	use crate::sphere_collider::SphereCollider;
+	use crate::plane_collider::PlaneCollider;
+	use crate::gravity_generator::GravityGenerator;
+	use crate::mesh_collider::MeshCollider;
+	use crate::tri_mesh_collider::TriMeshCollider;
+	use crate::body_type::BodyType;
+
+	/// Check that a ball falls under gravity, and comes to rest on a floor.
+	pub fn ball_lands_on_floor(world : &mut dyn PhysicsWorld) {
+		{
+			let floor = world.add_entity(Entity::new()).unwrap();
+			let mut plane = PlaneCollider::new();
+			plane.mass = INFINITY;
+			let plane_handle = world.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			world.link_collider(plane_handle, Some(floor)).unwrap();
+		}
+		let ball = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 2.0, 0.0);
+			let handle = world.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 1.0;
+			sphere.restitution_coefficient = 0.0;
+			let sphere_handle = world.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			world.link_collider(sphere_handle, Some(handle)).unwrap();
+			handle
+		};
+		world.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -1.0, 0.0)))).unwrap();
+		for _ in 0..10 {
+			world.step(0.1);
+		}
+		// Falling freely: 0.5 * 1.0 * 1.0^2 = 0.5 (give or take integration error).
+		assert!((world.get_entity(ball).unwrap().position.y - 1.5).abs() < 0.06);
+		for _ in 0..50 {
+			world.step(0.1);
+		}
+		let entity = world.get_entity(ball).unwrap();
+		assert!((entity.position.y - 0.5).abs() < 0.01);
+		assert!(entity.velocity.magnitude() < 0.01);
+		// The floor should be found by queries.
+		let hit = world.cast_ray(&Vec3::new(2.0, 1.0, 0.0), &Vec3::new(0.0, -1.0, 0.0), 10.0, None).unwrap();
+		assert!((hit.distance - 1.0).abs() < EPSILON);
+		assert!(world.cast_ray(&Vec3::new(0.0, 2.0, 0.0), &Vec3::new(0.0, -1.0, 0.0), 10.0, None).unwrap().entity == ball);
+	}
+
+	/// Check that a ball inside a hollow room (made of a one-sided triangle mesh facing inward) lands on its floor, rather than being pushed out.
+	pub fn ball_rests_inside_room(world : &mut dyn PhysicsWorld) {
+		let room = {
+			let mut entity = Entity::new();
+			entity.body_type = BodyType::Static;
+			let handle = world.add_entity(entity).unwrap();
+			let mut mesh = TriMeshCollider::from_mesh(&MeshCollider::new_cuboid(Vec3::repeat(2.0)).unwrap());
+			mesh.flip();
+			mesh.restitution_coefficient = 0.0;
+			let mesh_handle = world.add_collider(ColliderWrapper::TriMesh(mesh)).unwrap();
+			world.link_collider(mesh_handle, Some(handle)).unwrap();
+			handle
+		};
+		let ball = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.5, 0.0, 0.3);
+			let handle = world.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 1.0;
+			sphere.restitution_coefficient = 0.0;
+			let sphere_handle = world.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			world.link_collider(sphere_handle, Some(handle)).unwrap();
+			handle
+		};
+		world.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -1.0, 0.0)))).unwrap();
+		for _ in 0..60 {
+			world.step(0.1);
+		}
+		let entity = world.get_entity(ball).unwrap();
+		assert!((entity.position - Vec3::new(0.5, -1.5, 0.3)).magnitude() < 0.01, "{:?}", entity.position);
+		assert!(entity.velocity.magnitude() < 0.01);
+		// Rays only hit the inside: one from above goes through the roof, and then hits the floor.
+		let hit = world.cast_ray(&Vec3::new(1.5, 5.0, 0.0), &Vec3::new(0.0, -1.0, 0.0), 10.0, None).unwrap();
+		assert!(hit.entity == room);
+		assert!((hit.distance - 7.0).abs() < EPSILON);
+		assert!((hit.normal - Vec3::new(0.0, 1.0, 0.0)).magnitude() < EPSILON);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn impulse_world() {
+		shared_tests::ball_lands_on_floor(&mut ImpulseWorld::new());
+		shared_tests::ball_rests_inside_room(&mut ImpulseWorld::new());
+	}
+}
@@ -0,0 +1,148 @@
+use std::f32::INFINITY;
+use std::f32::consts::PI;
+
+use crate::consts::EPSILON;
+use crate::types::{Vec3, EntityHandle};
+use crate::physics_world::PhysicsWorld;
+use crate::query::RayHit;
+
+/// Softly pulls a point on an entity towards a target, i.e. for picking things up and dragging them around with the mouse.
+///
+/// Make one from a ray cast hit with [PickConstraint::from_hit] when the mouse is pressed, move `target` around while it's held, and call [PickConstraint::step] right before every [PhysicsSystem::step](crate::PhysicsSystem::step) (with the same `dt`). Drop it when the mouse is released.
+///
+/// The pull acts like a damped spring, but is scaled by the entity's mass so that it feels the same no matter what's picked up.
+#[derive(Debug, Clone)]
+pub struct PickConstraint {
+	/// The entity that was picked.
+	pub entity : EntityHandle,
+
+	/// Where the entity was picked, in the entity's LOCAL space.
+	pub local_anchor : Vec3,
+
+	/// Where to pull the picked point towards, in WORLD space.
+	///
+	/// Defaults to where the entity was picked.
+	pub target : Vec3,
+
+	/// How quickly (in oscillations per second) the picked point catches up to the target. Must be positive.
+	///
+	/// Defaults to `5.0`.
+	pub frequency : f32,
+
+	/// How much the pull resists overshooting the target: zero lets it bounce around the target forever, and one stops it without any overshoot. Must not be negative.
+	///
+	/// Defaults to `0.7`.
+	pub damping_ratio : f32,
+
+	/// The most force the pull can use. Must not be negative.
+	///
+	/// Defaults to infinity.
+	pub max_force : f32,
+}
+
+impl PickConstraint {
+	/// Picks up the entity that a ray cast hit (see [crate::PhysicsSystem::cast_ray]), at the point it was hit. Fails if the entity is gone.
+	pub fn from_hit(system : &dyn PhysicsWorld, hit : &RayHit) -> Result<PickConstraint, ()> {
+		let entity = system.get_entity(hit.entity).ok_or(())?; // TODO: An error type.
+		Ok(PickConstraint {
+			entity: hit.entity,
+			local_anchor: entity.get_last_orientation().position_into_local(&hit.position),
+			target: hit.position,
+			frequency: 5.0,
+			damping_ratio: 0.7,
+			max_force: INFINITY,
+		})
+	}
+
+	/// If this is in a valid state.
+	pub fn is_valid(&self) -> bool {
+		EPSILON < self.frequency && 0.0 <= self.damping_ratio && 0.0 <= self.max_force
+	}
+
+	/// Gets where the picked point currently is (in WORLD space). Returns None if the entity is gone.
+	pub fn get_anchor(&self, system : &dyn PhysicsWorld) -> Option<Vec3> {
+		system.get_entity(self.entity).map(|entity| entity.get_last_orientation().position_into_world(&self.local_anchor))
+	}
+
+	/// Applies the pull (as an impulse over `dt`) to the entity. Fails if the entity is gone or this isn't valid.
+	pub fn step(&self, system : &mut dyn PhysicsWorld, dt : f32) -> Result<(), ()> {
+		if !self.is_valid() {
+			return Err(()); // TODO: An error type.
+		}
+		let entity = system.get_entity(self.entity).ok_or(())?; // TODO: An error type.
+		let mass = entity.get_last_total_mass();
+		if !(EPSILON < mass && mass.is_finite()) { return Ok(()); } // Nothing sensible to pull on.
+		let anchor = entity.get_last_orientation().position_into_world(&self.local_anchor);
+		let velocity = entity.get_velocity_at_world_position(&anchor);
+		let angular_frequency = 2.0 * PI * self.frequency;
+		let stiffness = mass * angular_frequency * angular_frequency;
+		let damping = 2.0 * mass * self.damping_ratio * angular_frequency;
+		let mut force = (self.target - anchor) * stiffness - velocity * damping;
+		if self.max_force < force.magnitude() {
+			force = force.normalize() * self.max_force;
+		}
+		system.apply_impulse(self.entity, &anchor, &(force * dt))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::entity::Entity;
+	use crate::body_type::BodyType;
+	use crate::sphere_collider::SphereCollider;
+	use crate::plane_collider::PlaneCollider;
+	use crate::collider_wrapper::ColliderWrapper;
+	use crate::gravity_generator::GravityGenerator;
+	use crate::physics_system::PhysicsSystem;
+
+	/// Verify a ball resting on the floor can be picked up and dragged, but only if the pull is strong enough.
+	#[test]
+	fn drag() {
+		for max_force in [INFINITY, 5.0].iter() {
+			let mut system = PhysicsSystem::new();
+			{
+				let mut entity = Entity::new();
+				entity.body_type = BodyType::Static;
+				let floor = system.add_entity(entity).unwrap();
+				let plane_handle = system.add_collider(ColliderWrapper::Plane(PlaneCollider::new())).unwrap();
+				system.link_collider(plane_handle, Some(floor)).unwrap();
+			}
+			let ball = {
+				let mut entity = Entity::new();
+				entity.position = Vec3::new(0.0, 0.5, 0.0);
+				let entity_handle = system.add_entity(entity).unwrap();
+				let mut sphere = SphereCollider::new(0.5);
+				sphere.mass = 1.0;
+				sphere.restitution_coefficient = 0.0;
+				let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+				system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+				entity_handle
+			};
+			system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -10.0, 0.0)))).unwrap();
+			for _ in 0..30 {
+				system.step(1.0 / 60.0);
+			}
+			assert!(system.get_entity(ball).unwrap().was_asleep());
+
+			let hit = system.cast_ray(&Vec3::new(0.0, 5.0, 0.0), &Vec3::new(0.0, -1.0, 0.0), 10.0, None).unwrap();
+			assert!(hit.entity == ball);
+			let mut pick = PickConstraint::from_hit(&system, &hit).unwrap();
+			assert!((pick.local_anchor - Vec3::new(0.0, 0.5, 0.0)).magnitude() < EPSILON);
+			pick.max_force = *max_force;
+			pick.target = Vec3::new(1.0, 3.0, 0.0);
+			for _ in 0..120 {
+				pick.step(&mut system, 1.0 / 60.0).unwrap();
+				system.step(1.0 / 60.0);
+			}
+			let anchor = pick.get_anchor(&system).unwrap();
+			if max_force.is_infinite() {
+				// Sags a little under its own weight (and hangs from the top, so can tip over a bit).
+				assert!((anchor - pick.target).magnitude() < 0.05, "{:?}", anchor);
+			} else {
+				// Half its weight isn't enough to lift it off the floor.
+				assert!(system.get_entity(ball).unwrap().position.y < 0.51);
+			}
+		}
+	}
+}
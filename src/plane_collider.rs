@@ -1,13 +1,17 @@
 use crate::consts::EPSILON;
-use crate::types::{Vec3, Mat3, EntityHandle};
+use crate::types::{Vec3, Mat3, EntityHandle, MaterialHandle};
 use crate::collider::{ColliderType, Collider, InternalCollider};
+use crate::anisotropic_friction::AnisotropicFriction;
 
 /// The internal representation of a plane collider.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct InternalPlaneCollider {
 	/// The entity that this is linked to (if any).
 	entity : Option<EntityHandle>,
 
+	/// If this takes part in collisions and queries. Only changed with [crate::PhysicsSystem::set_collider_enabled].
+	enabled : bool,
+
 	/// The position of a point on the plane.
 	///
 	/// This is in the parent entity's local space.
@@ -30,6 +34,21 @@ pub struct InternalPlaneCollider {
 
 	/// The dynamic friction coefficient. Should always at or between 0.0 and 1.0.
 	pub dynamic_friction_coefficient : f32,
+
+	/// How friction changes with the sliding direction (if at all).
+	pub anisotropic_friction : Option<AnisotropicFriction>,
+
+	/// Extra thickness around the surface, used for collision detection.
+	pub margin : f32,
+
+	/// What this is made of.
+	pub material : MaterialHandle,
+
+	/// If this is only seen by queries (i.e. ray casts), never colliding with anything nor adding any mass.
+	pub query_only : bool,
+
+	/// A tag copied into collision records.
+	pub surface_tag : u32,
 }
 
 impl InternalPlaneCollider {
@@ -40,6 +59,7 @@ impl InternalPlaneCollider {
 		} else {
 			Ok(Box::new(InternalPlaneCollider {
 				entity: None,
+				enabled: true,
 				position: source.position.clone(),
 				normal: source.normal.normalize(),
 				mass: source.mass,
@@ -47,6 +67,11 @@ impl InternalPlaneCollider {
 				friction_threshold: source.friction_threshold,
 				static_friction_coefficient: source.static_friction_coefficient,
 				dynamic_friction_coefficient: source.dynamic_friction_coefficient,
+				anisotropic_friction: source.anisotropic_friction,
+				margin: source.margin,
+				material: source.material,
+				query_only: source.query_only,
+				surface_tag: source.surface_tag,
 			}))
 		}
 	}
@@ -62,6 +87,11 @@ impl InternalPlaneCollider {
 			friction_threshold: self.friction_threshold,
 			static_friction_coefficient: self.static_friction_coefficient,
 			dynamic_friction_coefficient: self.dynamic_friction_coefficient,
+			anisotropic_friction: self.anisotropic_friction,
+			margin: self.margin,
+			material: self.material,
+			query_only: self.query_only,
+			surface_tag: self.surface_tag,
 		}
 	}
 
@@ -77,6 +107,11 @@ impl InternalPlaneCollider {
 			self.friction_threshold = source.friction_threshold;
 			self.static_friction_coefficient = source.static_friction_coefficient;
 			self.dynamic_friction_coefficient = source.dynamic_friction_coefficient;
+			self.anisotropic_friction = source.anisotropic_friction;
+			self.margin = source.margin;
+			self.material = source.material;
+			self.query_only = source.query_only;
+			self.surface_tag = source.surface_tag;
 			Ok(())
 		}
 	}
@@ -86,6 +121,9 @@ impl InternalCollider for InternalPlaneCollider {
 	/// The specific type.
 	fn get_type(&self) -> ColliderType { ColliderType::PLANE }
 
+	/// Makes a boxed copy.
+	fn clone_boxed(&self) -> Box<dyn InternalCollider> { Box::new(self.clone()) }
+
 	/// Sets the entity this is attached to, returning the previous one.
 	fn set_entity(&mut self, handle : Option<EntityHandle>) -> Option<EntityHandle> {
 		let old = self.entity;
@@ -96,11 +134,19 @@ impl InternalCollider for InternalPlaneCollider {
 	/// Retrieves the stored entity handle that this is attached to.
 	fn get_entity(&mut self) -> Option<EntityHandle> { self.entity }
 
+	fn set_enabled(&mut self, enabled : bool) { self.enabled = enabled; }
+
+	fn is_enabled(&self) -> bool { self.enabled }
+
 	/// Gets the center of mass for this collider.
 	///
 	/// This is relative to this collider's owning/linked/attached entity.
 	fn get_local_center_of_mass(&self) -> Vec3 { self.position }
 
+	fn get_local_position(&self) -> Vec3 { self.position }
+
+	fn set_local_position(&mut self, position : &Vec3) { self.position = *position; }
+
 	fn get_mass(&self) -> f32 { self.mass }
 
 	fn get_moment_of_inertia_tensor(&self) -> Mat3 { Mat3::zeros() }
@@ -112,6 +158,15 @@ impl InternalCollider for InternalPlaneCollider {
 	fn get_static_friction_coefficient(&self) -> f32 { self.static_friction_coefficient }
 
 	fn get_dynamic_friction_coefficient(&self) -> f32 { self.dynamic_friction_coefficient }
+
+	fn get_anisotropic_friction(&self) -> Option<AnisotropicFriction> { self.anisotropic_friction }
+
+	fn get_margin(&self) -> f32 { self.margin }
+	fn get_material(&self) -> MaterialHandle { self.material }
+
+	fn is_query_only(&self) -> bool { self.query_only }
+
+	fn get_surface_tag(&self) -> u32 { self.surface_tag }
 }
 
 /// A copy of all of the publicly-accessible properties of an infinite plane collider.
@@ -121,7 +176,8 @@ impl InternalCollider for InternalPlaneCollider {
 /// This means that even if an object starts embedded in the collision geometry, it should always collide "against" the plane at a point that's furthest into the plane. So generally things shouldn't ever be able to "glitch past" one of these planes.
 ///
 /// **WARNING:** This collider does not currently implement collision handling for rotation. The collision handling assumes the normal is constant (over the course of any motion).
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlaneCollider {
 	/// The entity, if there is one. This is NOT copied back into InternalSphereCollider, hence why it's not "pub".
 	///
@@ -166,6 +222,31 @@ pub struct PlaneCollider {
 	///
 	/// Defaults to `0.3`.
 	pub dynamic_friction_coefficient : f32,
+
+	/// How friction changes with the sliding direction. If this is None, then friction is the same in all directions.
+	///
+	/// Defaults to None.
+	pub anisotropic_friction : Option<AnisotropicFriction>,
+
+	/// Extra thickness around the surface, used for collision detection. Two colliders count as touching once their surfaces are as close as their margins added together (so things resting on each other stay that far apart). Must not be negative.
+	///
+	/// Defaults to `0.0`.
+	pub margin : f32,
+
+	/// What this is made of. Only used to look up any properties set for specific pairs of materials (see [crate::PhysicsSystem::set_material_pair_properties]).
+	///
+	/// Defaults to `0`.
+	pub material : MaterialHandle,
+
+	/// If true, this never collides with anything and adds no mass to its entity, but still moves with it and is hit by queries like [crate::PhysicsSystem::cast_ray]. i.e. for a large detection sphere around an enemy.
+	///
+	/// Defaults to `false`.
+	pub query_only : bool,
+
+	/// A tag that's copied into every [crate::CollisionRecord] this is a part of, but otherwise ignored. i.e. for picking which sound to play.
+	///
+	/// Defaults to `0`.
+	pub surface_tag : u32,
 }
 
 impl PlaneCollider {
@@ -180,12 +261,17 @@ impl PlaneCollider {
 			friction_threshold: 0.25,
 			static_friction_coefficient: 1.0,
 			dynamic_friction_coefficient: 0.3,
+			anisotropic_friction: None,
+			margin: 0.0,
+			material: 0,
+			query_only: false,
+			surface_tag: 0,
 		}
 	}
 
 	/// If this is in a valid state.
 	pub fn is_valid(&self) -> bool {
-		0.0 <= self.mass && EPSILON < self.normal.magnitude()
+		0.0 <= self.mass && EPSILON < self.normal.magnitude() && self.anisotropic_friction.iter().all(|friction| friction.is_valid()) && 0.0 <= self.margin
 	}
 }
 
@@ -0,0 +1,22 @@
+use crate::types::{Vec3, EntityHandle};
+use crate::query::RayHit;
+
+/// What to move in a [crate::PhysicsSystem::predict] call.
+#[derive(Debug, Clone)]
+pub enum PredictedShape {
+	/// An existing entity's colliders (keeping the entity's current rotation). The entity itself is never hit.
+	Entity(EntityHandle),
+	/// A free-floating sphere with the given radius (which may be zero, for a point).
+	Sphere(f32),
+}
+
+/// The result of a [crate::PhysicsSystem::predict] call.
+#[derive(Debug, Clone)]
+pub struct Prediction {
+	/// The (center of mass) position at the start, at each sample after that, and finally where it was when it hit something (if it did).
+	pub positions : Vec<Vec3>,
+	/// The time (since the start) and details of the first hit (if there was one).
+	///
+	/// The hit's `distance` is how far along the path the shape moved before hitting.
+	pub hit : Option<(f32, RayHit)>,
+}
@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use core::fmt::Debug;
+use downcast_rs::{Downcast, impl_downcast};
+
+/// One of the parts of [crate::PhysicsSystem::step] that a [Profiler] is told about.
+///
+/// The collision phases (`BroadPhase`, `NarrowPhase`, and `Response`) happen once per iteration, so show up several times in a step that has lots of collisions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StepPhase {
+	/// Asking the force generators (and fluid regions) for their forces, and integrating them.
+	Forces,
+	/// Bounding every entity, so most pairs can be ruled out without looking at their colliders.
+	BroadPhase,
+	/// Finding the collisions between the pairs that weren't ruled out (including running the contact modifiers).
+	NarrowPhase,
+	/// Moving everything up to the earliest collisions, and responding to them.
+	Response,
+	/// Putting entities that have settled down to sleep.
+	Sleep,
+	/// Everything after that: updating contacts, breaking things, following attachments, removing expired entities, fluid regions, cloths, and particles.
+	Finish,
+}
+
+/// Something that's told when each phase of [crate::PhysicsSystem::step] begins and ends, i.e. to find out why steps are slow. See [crate::PhysicsSystem::set_profiler].
+///
+/// Forwarding these to a profiling crate (like `puffin` or `tracy`) as spans only takes a few lines. Or use [PhaseTimer] to just add up how long each phase took.
+pub trait Profiler : Downcast + Debug + Send + Sync {
+	/// Called at the very start of each step, before any phase.
+	fn begin_step(&mut self) {}
+
+	/// Called at the very end of each step, after every phase.
+	fn end_step(&mut self) {}
+
+	/// Called when a phase begins.
+	fn begin_phase(&mut self, phase : StepPhase);
+
+	/// Called when a phase ends. Phases never overlap, so this always ends the last phase that began.
+	fn end_phase(&mut self, phase : StepPhase);
+
+	/// Called with how many of something the step went through, once per step (before `end_step`). The counters are:
+	///
+	/// * `"entities"`: How many entities took part in the step.
+	/// * `"iterations"`: How many collision iterations were run.
+	/// * `"pairs_tested"`: How many pairs of colliders were checked for collisions.
+	/// * `"collisions"`: How many collisions were responded to.
+	///
+	/// Does nothing by default.
+	fn count(&mut self, _counter : &'static str, _value : u64) {}
+}
+
+impl_downcast!(Profiler);
+
+/// A [Profiler] that adds up how long each phase took (and every counter) over however many steps it's installed for.
+#[derive(Debug, Clone, Default)]
+pub struct PhaseTimer {
+	/// The total time spent in each phase.
+	totals : HashMap<StepPhase, Duration>,
+	/// When the current phase started (if one has).
+	started : Option<Instant>,
+	/// The total of each counter.
+	counters : HashMap<&'static str, u64>,
+}
+
+impl PhaseTimer {
+	/// Creates a timer with nothing recorded yet.
+	pub fn new() -> PhaseTimer {
+		PhaseTimer::default()
+	}
+
+	/// Gets the total time spent in a phase.
+	pub fn get_total(&self, phase : StepPhase) -> Duration {
+		self.totals.get(&phase).copied().unwrap_or_default()
+	}
+
+	/// Gets the total of a counter (see [Profiler::count]).
+	pub fn get_counter(&self, counter : &str) -> u64 {
+		self.counters.get(counter).copied().unwrap_or(0)
+	}
+
+	/// Forgets everything recorded so far.
+	pub fn reset(&mut self) {
+		self.totals.clear();
+		self.started = None;
+		self.counters.clear();
+	}
+}
+
+impl Profiler for PhaseTimer {
+	fn begin_phase(&mut self, _phase : StepPhase) {
+		self.started = Some(Instant::now());
+	}
+
+	fn end_phase(&mut self, phase : StepPhase) {
+		if let Some(started) = self.started.take() {
+			*self.totals.entry(phase).or_default() += started.elapsed();
+		}
+	}
+
+	fn count(&mut self, counter : &'static str, value : u64) {
+		*self.counters.entry(counter).or_default() += value;
+	}
+}
+
+/// The profiler (if any) that step() is reporting to. Does nothing without one, so step() doesn't need to check every time.
+pub(crate) struct ActiveProfiler(pub Option<Box<dyn Profiler>>);
+
+impl ActiveProfiler {
+	/// Tells the profiler a step began.
+	pub fn begin_step(&mut self) {
+		if let Some(profiler) = self.0.as_mut() { profiler.begin_step(); }
+	}
+
+	/// Tells the profiler a step ended.
+	pub fn end_step(&mut self) {
+		if let Some(profiler) = self.0.as_mut() { profiler.end_step(); }
+	}
+
+	/// Tells the profiler a phase began.
+	pub fn begin(&mut self, phase : StepPhase) {
+		if let Some(profiler) = self.0.as_mut() { profiler.begin_phase(phase); }
+	}
+
+	/// Tells the profiler a phase ended.
+	pub fn end(&mut self, phase : StepPhase) {
+		if let Some(profiler) = self.0.as_mut() { profiler.end_phase(phase); }
+	}
+
+	/// Tells the profiler a counter's value.
+	pub fn count(&mut self, counter : &'static str, value : u64) {
+		if let Some(profiler) = self.0.as_mut() { profiler.count(counter, value); }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::types::Vec3;
+	use crate::entity::Entity;
+	use crate::sphere_collider::SphereCollider;
+	use crate::collider_wrapper::ColliderWrapper;
+	use crate::physics_system::PhysicsSystem;
+
+	/// A profiler for testing: writes down everything it's told.
+	#[derive(Debug, Default)]
+	struct Log(Vec<String>);
+
+	impl Profiler for Log {
+		fn begin_step(&mut self) { self.0.push("step".to_string()); }
+		fn end_step(&mut self) { self.0.push("/step".to_string()); }
+		fn begin_phase(&mut self, phase : StepPhase) { self.0.push(format!("{:?}", phase)); }
+		fn end_phase(&mut self, phase : StepPhase) { self.0.push(format!("/{:?}", phase)); }
+		fn count(&mut self, counter : &'static str, value : u64) { self.0.push(format!("{}={}", counter, value)); }
+	}
+
+	/// Verify every phase of a step is reported in order (with one collision taking an extra iteration, which tests the pair again), and the phase timer adds up the counters.
+	#[test]
+	fn reports_phases() {
+		let mut system = PhysicsSystem::new();
+		for (x, velocity) in [(-1.0, 10.0), (1.0, -10.0)].iter() {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(*x, 0.0, 0.0);
+			entity.velocity = Vec3::new(*velocity, 0.0, 0.0);
+			let entity_handle = system.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.5);
+			sphere.mass = 1.0;
+			let sphere_handle = system.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			system.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+		}
+		assert!(system.set_profiler(Some(Box::new(Log::default()))).is_none());
+		system.step(0.1);
+		let log = system.set_profiler(Some(Box::new(PhaseTimer::new()))).unwrap();
+		let iteration = ["BroadPhase", "/BroadPhase", "NarrowPhase", "/NarrowPhase", "Response", "/Response"];
+		let mut expected = vec!["step", "Forces", "/Forces"];
+		expected.extend(iteration.iter());
+		expected.extend(iteration.iter());
+		expected.extend(["Sleep", "/Sleep", "Finish", "/Finish", "entities=2", "iterations=2", "pairs_tested=2", "collisions=1", "/step"].iter());
+		assert_eq!(log.downcast_ref::<Log>().unwrap().0, expected);
+
+		system.step(0.1);
+		system.step(0.1);
+		let timer = system.get_profiler().unwrap().downcast_ref::<PhaseTimer>().unwrap();
+		assert_eq!(timer.get_counter("entities"), 4);
+		assert_eq!(timer.get_counter("unknown"), 0);
+		assert!(system.clone().get_profiler().is_none());
+		system.get_profiler_mut().unwrap().downcast_mut::<PhaseTimer>().unwrap().reset();
+		assert_eq!(system.get_profiler().unwrap().downcast_ref::<PhaseTimer>().unwrap().get_counter("entities"), 0);
+	}
+}
@@ -0,0 +1,306 @@
+use crate::consts::EPSILON;
+use crate::types::{Vec3, EntityHandle, ColliderHandle};
+use crate::collider::{ColliderType, InternalCollider};
+use crate::sphere_collider::InternalSphereCollider;
+use crate::plane_collider::InternalPlaneCollider;
+use crate::mesh_collider::InternalMeshCollider;
+use crate::aligned_box_collider::InternalAlignedBoxCollider;
+use crate::tri_mesh_collider::InternalTriMeshCollider;
+use crate::collision::{Collision, collide_sphere_with_sphere, collide_sphere_with_plane, collide_point_with_polygon};
+use crate::convex_shape::{ConvexShape, PlacedShape, closest_points};
+use crate::orientation::Orientation;
+
+/// Where a ray cast (see [crate::PhysicsSystem::cast_ray]) or sphere cast (see [crate::PhysicsSystem::cast_sphere]) first hit something.
+#[derive(Debug, Clone)]
+pub struct RayHit {
+	/// The entity that was hit.
+	pub entity : EntityHandle,
+	/// The collider that was hit.
+	pub collider : ColliderHandle,
+	/// The point that was hit (in world space).
+	pub position : Vec3,
+	/// The surface normal at the hit. **Points back towards the ray's origin**.
+	pub normal : Vec3,
+	/// How far along the ray (or how far the sphere's center moved) before the hit happened.
+	pub distance : f32,
+	/// Which part of a [crate::MeshCollider] (or [crate::TriMeshCollider]) a ray hit. Only set for ray casts that hit a mesh.
+	pub face : Option<MeshFaceHit>,
+}
+
+/// The result of a [crate::PhysicsSystem::camera_clip] call.
+#[derive(Debug, Clone)]
+pub struct CameraClip {
+	/// The furthest point (from the pivot towards the desired position) that a sphere of the given radius can be without touching anything.
+	pub position : Vec3,
+	/// What was in the way (if anything). Its `distance` is how far the sphere got before touching it.
+	pub hit : Option<RayHit>,
+}
+
+/// Where on a [crate::MeshCollider]'s face (or a [crate::TriMeshCollider]'s triangle) a ray hit.
+///
+/// Faces can have any number of corners, so each is treated as a fan of triangles (all sharing the face's first corner), and the hit is located within one of those triangles.
+#[derive(Debug, Clone)]
+pub struct MeshFaceHit {
+	/// The index of the face (into [crate::MeshCollider::get_faces], so in the order they were added). For triangle meshes, the index of the triangle (into [crate::TriMeshCollider::get_triangles]).
+	pub face : usize,
+	/// The vertex indices (into [crate::MeshCollider::get_vertices]) of the triangle within the face that was hit.
+	pub triangle : [usize; 3],
+	/// The barycentric coordinates of the hit within `triangle`: each is how much the matching vertex contributes. These add up to one.
+	pub barycentric : Vec3,
+}
+
+/// Finds which triangle of the given face (as a fan) contains the given point, and the point's barycentric coordinates within it.
+///
+/// The point should already be on (or very close to) the face.
+fn locate_in_face(face_index : usize, face : &[usize], vertices : &[Vec3], point : &Vec3) -> MeshFaceHit {
+	let mut best : Option<(f32, MeshFaceHit)> = None;
+	for corner in 1..(face.len() - 1) {
+		let triangle = [face[0], face[corner], face[corner + 1]];
+		let edge1 = vertices[triangle[1]] - vertices[triangle[0]];
+		let edge2 = vertices[triangle[2]] - vertices[triangle[0]];
+		let offset = point - vertices[triangle[0]];
+		let (dot11, dot12, dot22) = (edge1.dot(&edge1), edge1.dot(&edge2), edge2.dot(&edge2));
+		let (dot1o, dot2o) = (edge1.dot(&offset), edge2.dot(&offset));
+		let denominator = dot11 * dot22 - dot12 * dot12;
+		if denominator.abs() < EPSILON { continue; } // Degenerate triangle.
+		let second = (dot22 * dot1o - dot12 * dot2o) / denominator;
+		let third = (dot11 * dot2o - dot12 * dot1o) / denominator;
+		let barycentric = Vec3::new(1.0 - second - third, second, third);
+		// Keep the triangle that the point is the furthest inside of (so rounding along shared edges doesn't matter).
+		let inside = barycentric.min();
+		if best.as_ref().is_none_or(|(best_inside, _)| *best_inside < inside) {
+			best = Some((inside, MeshFaceHit { face: face_index, triangle, barycentric }));
+		}
+	}
+	best.map(|(_, hit)| hit).unwrap_or(MeshFaceHit {
+		face: face_index,
+		triangle: [face[0], face[1], face[2]],
+		barycentric: Vec3::new(1.0, 0.0, 0.0),
+	})
+}
+
+/// Casts a ray (i.e. a moving point) from `start` to `end` against a single collider that's at the given orientation.
+///
+/// The resulting collision's normal points back towards `start`. Its time is the fraction of the way from `start` to `end` that the hit happened.
+///
+/// If a mesh was hit, this also returns where on the mesh.
+pub fn cast_ray_against(collider : &Box<dyn InternalCollider>, orientation : &Orientation, start : &Vec3, end : &Vec3) -> Option<(Collision, Option<MeshFaceHit>)> {
+	let zero_vec = Vec3::zeros();
+	match collider.get_type() {
+		ColliderType::NULL => None,
+		ColliderType::SPHERE => {
+			let sphere = collider.downcast_ref::<InternalSphereCollider>().unwrap();
+			let center = orientation.position_into_world(&sphere.center);
+			let mut collision = collide_sphere_with_sphere(0.0, start, &(end - start), sphere.radius, &center, &zero_vec)?;
+			collision.normal = (collision.position - center).normalize();
+			Some((collision, None))
+		},
+		ColliderType::PLANE => {
+			let plane = collider.downcast_ref::<InternalPlaneCollider>().unwrap();
+			let mut collision = collide_sphere_with_plane(0.0, start, &(end - start), &orientation.position_into_world(&plane.position), &plane.normal, &zero_vec)?;
+			collision.normal *= -1.0;
+			Some((collision, None))
+		},
+		ColliderType::MESH => {
+			let mesh = collider.downcast_ref::<InternalMeshCollider>().unwrap();
+			let vertices = mesh.vertices_in_world(orientation);
+			let mut earliest : Option<(usize, Collision)> = None;
+			for (face_index, face) in mesh.faces.iter().enumerate() {
+				let polygon = face.iter().map(|index| vertices[*index]).collect();
+				if let Some(collision) = collide_point_with_polygon(start, end, &polygon) {
+					if earliest.as_ref().is_none_or(|(_, current)| collision.times.min() < current.times.min()) {
+						earliest = Some((face_index, collision));
+					}
+				}
+			}
+			let (face_index, collision) = earliest?;
+			let face_hit = locate_in_face(face_index, &mesh.faces[face_index], &vertices, &collision.position);
+			Some((collision, Some(face_hit)))
+		},
+		ColliderType::ALIGNED_BOX => {
+			// Cast against each of the box's sides in the box's local space.
+			let aligned_box = collider.downcast_ref::<InternalAlignedBoxCollider>().unwrap();
+			let local_start = orientation.position_into_local(start);
+			let local_end = orientation.position_into_local(end);
+			let min_corner = aligned_box.position + aligned_box.min_corner;
+			let max_corner = aligned_box.position + aligned_box.max_corner;
+			let mut earliest : Option<Collision> = None;
+			for axis in 0..3 {
+				let first = (axis + 1) % 3;
+				let second = (axis + 2) % 3;
+				for side in [min_corner[axis], max_corner[axis]].iter() {
+					let mut polygon = vec![min_corner, min_corner, max_corner, max_corner];
+					polygon[1][first] = max_corner[first];
+					polygon[3][first] = min_corner[first];
+					for corner in polygon.iter_mut() {
+						corner[axis] = *side;
+					}
+					polygon[0][second] = min_corner[second];
+					polygon[1][second] = min_corner[second];
+					polygon[2][second] = max_corner[second];
+					polygon[3][second] = max_corner[second];
+					if let Some(collision) = collide_point_with_polygon(&local_start, &local_end, &polygon) {
+						if earliest.as_ref().is_none_or(|current| collision.times.min() < current.times.min()) {
+							earliest = Some(collision);
+						}
+					}
+				}
+			}
+			let mut collision = earliest?;
+			collision.position = orientation.position_into_world(&collision.position);
+			collision.normal = orientation.direction_into_world(&collision.normal);
+			Some((collision, None))
+		},
+		ColliderType::TRI_MESH => {
+			// Cast against the triangles along the ray in the mesh's local space. They're one-sided, so rays starting behind them go right through.
+			let mesh = collider.downcast_ref::<InternalTriMeshCollider>().unwrap();
+			let local_start = orientation.position_into_local(start);
+			let local_end = orientation.position_into_local(end);
+			let mut found = Vec::new();
+			mesh.triangles_along(&local_start, &local_end, &mut found);
+			let mut earliest : Option<(usize, Collision)> = None;
+			for triangle in found {
+				let normal = mesh.lookup.normals[triangle];
+				let polygon = mesh.corners(triangle).to_vec();
+				if normal.x.is_nan() || (local_start - polygon[0]).dot(&normal) <= 0.0 { continue; }
+				if let Some(collision) = collide_point_with_polygon(&local_start, &local_end, &polygon) {
+					if earliest.as_ref().is_none_or(|(_, current)| collision.times.min() < current.times.min()) {
+						earliest = Some((triangle, collision));
+					}
+				}
+			}
+			let (triangle, mut collision) = earliest?;
+			let face_hit = locate_in_face(triangle, &mesh.triangles[triangle], &mesh.vertices, &(collision.position - mesh.position));
+			collision.position = orientation.position_into_world(&collision.position);
+			collision.normal = orientation.direction_into_world(&collision.normal);
+			Some((collision, Some(face_hit)))
+		},
+	}
+}
+
+/// A single point, as a [ConvexShape] (so it can be used with [closest_points]).
+struct PointShape(Vec3);
+
+impl ConvexShape for PointShape {
+	fn support(&self, _direction : &Vec3) -> Vec3 { self.0 }
+
+	fn bounding_sphere(&self) -> (Vec3, f32) { (self.0, 0.0) }
+}
+
+/// Finds the point on (or in) a single collider that's closest to the given point, when the collider is at the given orientation. Colliders are treated as solid, so points inside of them are their own closest point.
+///
+/// Meshes are treated as their convex hull. Triangle meshes aren't solid, so the closest point is always on their surface. Returns None for null colliders (as they have no shape).
+pub fn closest_point_on(collider : &Box<dyn InternalCollider>, orientation : &Orientation, point : &Vec3) -> Option<Vec3> {
+	match collider.get_type() {
+		ColliderType::NULL => None,
+		ColliderType::SPHERE => {
+			let sphere = collider.downcast_ref::<InternalSphereCollider>().unwrap();
+			let center = orientation.position_into_world(&sphere.center);
+			let offset = point - center;
+			let distance = offset.magnitude();
+			Some(if distance <= sphere.radius { *point } else { center + offset * (sphere.radius / distance) })
+		},
+		ColliderType::PLANE => {
+			let plane = collider.downcast_ref::<InternalPlaneCollider>().unwrap();
+			let height = (point - orientation.position_into_world(&plane.position)).dot(&plane.normal);
+			Some(if height <= 0.0 { *point } else { point - plane.normal * height })
+		},
+		ColliderType::MESH => {
+			let mesh = collider.downcast_ref::<InternalMeshCollider>().unwrap();
+			Some(closest_points(&PlacedShape::new(mesh, orientation), &PointShape(*point)).map_or(*point, |(on_mesh, _)| on_mesh))
+		},
+		ColliderType::ALIGNED_BOX => {
+			// Clamp to the box in the box's local space.
+			let aligned_box = collider.downcast_ref::<InternalAlignedBoxCollider>().unwrap();
+			let local = orientation.position_into_local(point) - aligned_box.position;
+			let clamped = local.zip_zip_map(&aligned_box.min_corner, &aligned_box.max_corner, |value, min, max| value.max(min).min(max));
+			Some(orientation.position_into_world(&(clamped + aligned_box.position)))
+		},
+		ColliderType::TRI_MESH => {
+			// Only the surface counts, as the mesh doesn't have to be closed.
+			let mesh = collider.downcast_ref::<InternalTriMeshCollider>().unwrap();
+			let (_, closest) = mesh.closest_point(&orientation.position_into_local(point))?;
+			Some(orientation.position_into_world(&closest))
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::aligned_box_collider::AlignedBoxCollider;
+	use crate::sphere_collider::SphereCollider;
+	use crate::plane_collider::PlaneCollider;
+	use crate::mesh_collider::MeshCollider;
+	use crate::tri_mesh_collider::TriMeshCollider;
+
+	/// Verify casting against each side of a box hits the near side with the right normal.
+	#[test]
+	fn ray_against_box() {
+		let source = AlignedBoxCollider::from_center_half_extents(Vec3::zeros(), Vec3::repeat(1.0));
+		let collider = InternalAlignedBoxCollider::new_from(&source).unwrap();
+		let orientation = Orientation::new(Vec3::new(0.0, 2.0, 0.0), Vec3::zeros(), Vec3::zeros());
+		for axis in 0..3 {
+			let mut start = Vec3::new(0.1, 2.2, 0.3);
+			start[axis] += 5.0;
+			let mut end = start;
+			end[axis] -= 10.0;
+			let (hit, face) = cast_ray_against(&collider, &orientation, &start, &end).unwrap();
+			assert!(face.is_none());
+			let top_side = [1.0, 3.0, 1.0][axis];
+			assert!((hit.times.min() - (start[axis] - top_side) / 10.0).abs() < EPSILON);
+			let mut expected_normal = Vec3::zeros();
+			expected_normal[axis] = 1.0;
+			assert!((hit.normal - expected_normal).magnitude() < EPSILON);
+		}
+		assert!(cast_ray_against(&collider, &orientation, &Vec3::new(5.0, 0.0, 0.0), &Vec3::new(-5.0, 0.0, 0.0)).is_none());
+	}
+
+	/// Verify the closest point on each kind of collider is on its surface (or is the point itself, when inside).
+	#[test]
+	fn closest_points_on_colliders() {
+		let orientation = Orientation::new(Vec3::new(0.0, 2.0, 0.0), Vec3::zeros(), Vec3::zeros());
+		let point = Vec3::new(3.0, 2.5, 0.0);
+		let inside = Vec3::new(0.2, 2.1, 0.0);
+		let colliders = [
+			InternalSphereCollider::new_from(&SphereCollider::new(1.0)).unwrap(),
+			InternalAlignedBoxCollider::new_from(&AlignedBoxCollider::from_center_half_extents(Vec3::zeros(), Vec3::repeat(1.0))).unwrap(),
+			InternalMeshCollider::new_from(&MeshCollider::new_cuboid(Vec3::repeat(1.0)).unwrap()).unwrap(),
+		];
+		let expected = [
+			Vec3::new(0.0, 2.0, 0.0) + Vec3::new(3.0, 0.5, 0.0).normalize(),
+			Vec3::new(1.0, 2.5, 0.0),
+			Vec3::new(1.0, 2.5, 0.0),
+		];
+		for (collider, expected) in colliders.iter().zip(expected.iter()) {
+			assert!((closest_point_on(collider, &orientation, &point).unwrap() - expected).magnitude() < 0.001, "{:?}", collider);
+			assert!((closest_point_on(collider, &orientation, &inside).unwrap() - inside).magnitude() < EPSILON, "{:?}", collider);
+		}
+		let plane = InternalPlaneCollider::new_from(&PlaneCollider::new()).unwrap();
+		assert!((closest_point_on(&plane, &orientation, &point).unwrap() - Vec3::new(3.0, 2.0, 0.0)).magnitude() < EPSILON);
+		assert_eq!(closest_point_on(&plane, &orientation, &Vec3::new(3.0, 1.0, 0.0)).unwrap(), Vec3::new(3.0, 1.0, 0.0));
+	}
+
+	/// Verify rays hit the front of a triangle mesh (and say which triangle), but go through its back.
+	#[test]
+	fn ray_against_tri_mesh() {
+		let mut source = TriMeshCollider::from_triangles(
+			vec![Vec3::new(-2.0, 0.0, -2.0), Vec3::new(-2.0, 0.0, 2.0), Vec3::new(2.0, 0.0, 2.0), Vec3::new(2.0, 0.0, -2.0)],
+			vec![[0, 1, 2], [0, 2, 3]],
+		).unwrap();
+		source.position = Vec3::new(0.0, 1.0, 0.0);
+		let collider = InternalTriMeshCollider::new_from(&source).unwrap();
+		let orientation = Orientation::new(Vec3::new(0.0, 2.0, 0.0), Vec3::zeros(), Vec3::zeros());
+		let (hit, face) = cast_ray_against(&collider, &orientation, &Vec3::new(1.0, 5.0, -0.5), &Vec3::new(1.0, 1.0, -0.5)).unwrap();
+		assert!((hit.times.min() - 0.5).abs() < EPSILON);
+		assert!((hit.position - Vec3::new(1.0, 3.0, -0.5)).magnitude() < EPSILON);
+		assert!((hit.normal - Vec3::new(0.0, 1.0, 0.0)).magnitude() < EPSILON);
+		let face = face.unwrap();
+		assert_eq!((face.face, face.triangle), (1, [0, 2, 3]));
+		assert!((face.barycentric.sum() - 1.0).abs() < EPSILON);
+		assert!(cast_ray_against(&collider, &orientation, &Vec3::new(1.0, 1.0, -0.5), &Vec3::new(1.0, 5.0, -0.5)).is_none());
+
+		// The closest point is always on the surface.
+		assert!((closest_point_on(&collider, &orientation, &Vec3::new(1.0, 2.0, 3.0)).unwrap() - Vec3::new(1.0, 3.0, 2.0)).magnitude() < EPSILON);
+	}
+}
@@ -60,6 +60,13 @@ impl Range {
 		if self.is_empty() { 0.0 } else { self.max - self.min }
 	}
 
+	/// Re-expresses this range relative to the part between `start` and `end`; so `start` becomes 0.0 and `end` becomes 1.0.
+	pub fn remap(&self, start : f32, end : f32) -> Range {
+		if self.is_empty() { return Range::empty(); }
+		let size = end - start;
+		Range::range((self.min - start) / size, (self.max - start) / size)
+	}
+
 	/// Finds the range common between two ranges.
 	pub fn intersect(&self, other : &Range) -> Range {
 		if self.is_empty() || other.is_empty() { return Range::empty(); }
@@ -85,6 +92,8 @@ impl Range {
 
 	/// Creates a range that's got end points at the zeros of a quadratic.
 	/// Can also have no end points if the quadratic has no zeros.
+	///
+	/// Uses the numerically stable form of the quadratic formula: the larger zero comes from adding two numbers of the same sign, and the smaller from `c / (a * larger)`. The naive form subtracts two nearly equal numbers for the smaller zero whenever `b * b` is much bigger than `4 * a * c` (i.e. small, fast spheres), which loses nearly all of its precision.
 	pub fn quadratic_zeros(a : f32, b : f32, c : f32) -> Range {
 		if a.abs() < EPSILON {
 			// Degenerates to a linear equation.
@@ -95,15 +104,15 @@ impl Range {
 				Range::single(-c / b)
 			}
 		} else {
-			let mut det = b * b - 4.0 * a * c;
+			// The two products can be nearly equal, so subtract them with more precision.
+			let det = ((b as f64) * (b as f64) - 4.0 * (a as f64) * (c as f64)) as f32;
 			if det < -EPSILON { // TODO: Could use a relative epsilon to keep things stable even in tiny cases.
 				Range::empty()
 			} else if det < EPSILON {
 				Range::single(-0.5 * b / a)
 			} else {
-				det = det.sqrt();
-				let denom = 2.0 * a;
-				Range::range((-b + det) / denom, (-b - det) / denom)
+				let q = -0.5 * (b + b.signum() * det.sqrt());
+				Range::range(q / a, c / q)
 			}
 		}
 	}
@@ -256,6 +265,32 @@ mod tests {
 			let zeros = Range::quadratic_zeros(1.0, 0.0, 1.0);
 			assert!(zeros.is_empty());
 		}
+		{ // (x - 2) * (x + 2) = x^2 - 4
+			let zeros = Range::quadratic_zeros(1.0, 0.0, -4.0);
+			assert!((zeros.min() - -2.0).abs() < EPSILON);
+			assert!((zeros.max() -  2.0).abs() < EPSILON);
+		}
+	}
+
+	/// Verify both zeros keep their precision when `b * b` is much bigger than `4 * a * c` (where the naive formula gives zero for the smaller one).
+	#[test]
+	fn check_quadratic_extremes() {
+		let close_to = |value : f32, expected : f32| ((value - expected) / expected).abs() < 1e-4;
+		{ // About (x + 1e-6) * (x + 1e6) / 1000
+			let zeros = Range::quadratic_zeros(1e-3, 1e3, 1e-3);
+			assert!(close_to(zeros.min(), -1e6), "{:?}", zeros);
+			assert!(close_to(zeros.max(), -1e-6), "{:?}", zeros);
+		}
+		{ // About (x - 5e-5) * (x - 2e4)
+			let zeros = Range::quadratic_zeros(1.0, -2e4, 1.0);
+			assert!(close_to(zeros.min(), 5e-5), "{:?}", zeros);
+			assert!(close_to(zeros.max(), 2e4), "{:?}", zeros);
+		}
+		{ // A tiny, fast sphere: (x - 1e-3) * (x - 1e3) * 1e-4
+			let zeros = Range::quadratic_zeros(1e-4, -0.1000001, 1e-4);
+			assert!(close_to(zeros.min(), 1e-3), "{:?}", zeros);
+			assert!(close_to(zeros.max(), 1e3), "{:?}", zeros);
+		}
 	}
 
 	#[test]
@@ -0,0 +1,37 @@
+/// How many collisions [crate::PhysicsSystem::collision_records] can hold in one step, so huge piles of things can't make it grow without bound. See [crate::PhysicsSystem::record_limit].
+///
+/// Collisions that don't fit are still simulated (and still push on things). They're just not recorded, and are counted in [crate::PhysicsSystem::dropped_collision_records].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordLimit {
+	/// Every collision is recorded.
+	Unlimited,
+	/// Only the first this many collisions that are found are recorded. The rest are dropped.
+	KeepFirst(usize),
+	/// Only the last this many collisions that are found are recorded. Once full, each new one replaces the oldest (like a ring buffer).
+	KeepLast(usize),
+}
+
+impl RecordLimit {
+	/// Adds an item to the list if there's room for it, given how many were already dropped. Returns whether anything was dropped.
+	pub(crate) fn push<T>(&self, items : &mut Vec<T>, dropped : usize, item : T) -> bool {
+		match *self {
+			RecordLimit::Unlimited => { items.push(item); false },
+			RecordLimit::KeepFirst(limit) => {
+				if items.len() < limit { items.push(item); false } else { true }
+			},
+			RecordLimit::KeepLast(limit) => {
+				if items.len() < limit {
+					items.push(item);
+					false
+				} else {
+					if 0 < limit {
+						// Everything that's been added so far went around the ring in order, so the oldest is right after the newest.
+						items[(limit + dropped) % limit] = item;
+					}
+					true
+				}
+			},
+		}
+	}
+}
+
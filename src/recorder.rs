@@ -0,0 +1,316 @@
+use crate::types::{Vec3, EntityHandle, ColliderHandle, UnaryForceGeneratorHandle, MaterialHandle};
+use crate::entity::Entity;
+use crate::collider_wrapper::ColliderWrapper;
+use crate::contact_properties::ContactProperties;
+use crate::wake_policy::WakePolicy;
+use crate::gravity_generator::GravityGenerator;
+use crate::physics_system::PhysicsSystem;
+
+/// A single change made to a [PhysicsSystem] from outside (or a step() call), as captured by a [Recorder].
+///
+/// The handles are the ones the recorded system handed out. Replaying the calls (in order) on a new system hands out the very same handles, so they don't need to be translated.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RecordedCall {
+	AddEntity(Entity),
+	RemoveEntity(EntityHandle),
+	UpdateEntity(EntityHandle, Entity, WakePolicy),
+	SetEntityEnabled(EntityHandle, bool),
+	TeleportEntity { handle : EntityHandle, position : Vec3, rotation : Vec3, check_overlap : bool },
+	ApplyImpulse { handle : EntityHandle, position : Vec3, impulse : Vec3 },
+	AddCollider(ColliderWrapper),
+	RemoveCollider(ColliderHandle),
+	UpdateCollider(ColliderHandle, ColliderWrapper),
+	LinkCollider(ColliderHandle, Option<EntityHandle>),
+	SetColliderEnabled(ColliderHandle, bool),
+	MoveCollider(ColliderHandle, Vec3),
+	SetMaterialPairProperties(MaterialHandle, MaterialHandle, Option<ContactProperties>),
+	SetEntityPairProperties(EntityHandle, EntityHandle, Option<ContactProperties>),
+	/// Adds a [GravityGenerator] with the given acceleration.
+	AddGravity(Vec3),
+	RemoveUnaryForceGenerator(UnaryForceGeneratorHandle),
+	/// A step() call, with its `dt`.
+	Step(f32),
+	/// A step_with_iteration_max() call, with its `dt` and `iteration_max`.
+	StepWithIterationMax(f32, u32),
+}
+
+/// Everything that was done to a [PhysicsSystem] while it was being recorded, in order. Pass it to [replay] to run it all again.
+///
+/// With the `serde` feature, this can be serialized (i.e. to save it along with a bug report).
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecordedLog {
+	/// The calls, in the order they were made.
+	pub calls : Vec<RecordedCall>,
+}
+
+impl RecordedLog {
+	/// Creates an empty instance.
+	pub fn new() -> RecordedLog {
+		RecordedLog { calls: Vec::new() }
+	}
+}
+
+/// Wraps a new [PhysicsSystem], recording every change made through it (see [RecordedLog]).
+///
+/// Only the changes listed in [RecordedCall] can be recorded, so the system's settings are left at their defaults and other force generators (or contact modifiers, cloths, etc) can't be added.
+pub struct Recorder {
+	/// The system being recorded.
+	system : PhysicsSystem,
+	/// Everything done to `system` so far.
+	log : RecordedLog,
+}
+
+impl Default for Recorder {
+	fn default() -> Recorder { Recorder::new() }
+}
+
+impl Recorder {
+	/// Creates an instance with a new (empty) system and log.
+	pub fn new() -> Recorder {
+		Recorder { system: PhysicsSystem::new(), log: RecordedLog::new() }
+	}
+
+	/// The system being recorded. Use this for anything that doesn't change it (i.e. getting entities or colliders).
+	pub fn get_system(&self) -> &PhysicsSystem { &self.system }
+
+	/// Everything recorded so far.
+	pub fn get_log(&self) -> &RecordedLog { &self.log }
+
+	/// Stops recording, giving back the system and everything that was recorded.
+	pub fn finish(self) -> (PhysicsSystem, RecordedLog) { (self.system, self.log) }
+
+	/// Records and does a [PhysicsSystem::add_entity] call.
+	pub fn add_entity(&mut self, source : Entity) -> Result<EntityHandle, ()> {
+		self.log.calls.push(RecordedCall::AddEntity(source.clone()));
+		self.system.add_entity(source)
+	}
+
+	/// Records and does a [PhysicsSystem::remove_entity] call.
+	pub fn remove_entity(&mut self, handle : EntityHandle) -> bool {
+		self.log.calls.push(RecordedCall::RemoveEntity(handle));
+		self.system.remove_entity(handle)
+	}
+
+	/// Records and does a [PhysicsSystem::update_entity] call.
+	pub fn update_entity(&mut self, handle : EntityHandle, source : Entity) -> Result<(),()> {
+		self.update_entity_with(handle, source, WakePolicy::IfChanged)
+	}
+
+	/// Records and does a [PhysicsSystem::update_entity_with] call.
+	pub fn update_entity_with(&mut self, handle : EntityHandle, source : Entity, wake : WakePolicy) -> Result<(),()> {
+		self.log.calls.push(RecordedCall::UpdateEntity(handle, source.clone(), wake));
+		self.system.update_entity_with(handle, source, wake)
+	}
+
+	/// Records and does a [PhysicsSystem::set_entity_enabled] call.
+	pub fn set_entity_enabled(&mut self, handle : EntityHandle, enabled : bool) -> Result<(),()> {
+		self.log.calls.push(RecordedCall::SetEntityEnabled(handle, enabled));
+		self.system.set_entity_enabled(handle, enabled)
+	}
+
+	/// Records and does a [PhysicsSystem::teleport_entity] call.
+	pub fn teleport_entity(&mut self, handle : EntityHandle, position : impl Into<Vec3>, rotation : impl Into<Vec3>, check_overlap : bool) -> Result<(),()> {
+		let (position, rotation) = (position.into(), rotation.into());
+		self.log.calls.push(RecordedCall::TeleportEntity { handle, position, rotation, check_overlap });
+		self.system.teleport_entity(handle, position, rotation, check_overlap)
+	}
+
+	/// Records and does a [PhysicsSystem::apply_impulse] call.
+	pub fn apply_impulse(&mut self, handle : EntityHandle, position : impl Into<Vec3>, impulse : impl Into<Vec3>) -> Result<(),()> {
+		let (position, impulse) = (position.into(), impulse.into());
+		self.log.calls.push(RecordedCall::ApplyImpulse { handle, position, impulse });
+		self.system.apply_impulse(handle, position, impulse)
+	}
+
+	/// Records and does a [PhysicsSystem::add_collider] call.
+	pub fn add_collider(&mut self, source : ColliderWrapper) -> Result<ColliderHandle, ()> {
+		self.log.calls.push(RecordedCall::AddCollider(source.clone()));
+		self.system.add_collider(source)
+	}
+
+	/// Records and does a [PhysicsSystem::remove_collider] call.
+	pub fn remove_collider(&mut self, handle : ColliderHandle) {
+		self.log.calls.push(RecordedCall::RemoveCollider(handle));
+		self.system.remove_collider(handle)
+	}
+
+	/// Records and does a [PhysicsSystem::update_collider] call.
+	pub fn update_collider(&mut self, handle : ColliderHandle, source : ColliderWrapper) -> Result<(), ()> {
+		self.log.calls.push(RecordedCall::UpdateCollider(handle, source.clone()));
+		self.system.update_collider(handle, source)
+	}
+
+	/// Records and does a [PhysicsSystem::link_collider] call.
+	pub fn link_collider(&mut self, collider_handle : ColliderHandle, entity_handle : Option<EntityHandle>) -> Result<(), ()> {
+		self.log.calls.push(RecordedCall::LinkCollider(collider_handle, entity_handle));
+		self.system.link_collider(collider_handle, entity_handle)
+	}
+
+	/// Records and does a [PhysicsSystem::set_collider_enabled] call.
+	pub fn set_collider_enabled(&mut self, handle : ColliderHandle, enabled : bool) -> Result<(),()> {
+		self.log.calls.push(RecordedCall::SetColliderEnabled(handle, enabled));
+		self.system.set_collider_enabled(handle, enabled)
+	}
+
+	/// Records and does a [PhysicsSystem::move_collider] call.
+	pub fn move_collider(&mut self, handle : ColliderHandle, position : impl Into<Vec3>) -> Result<(),()> {
+		let position = position.into();
+		self.log.calls.push(RecordedCall::MoveCollider(handle, position));
+		self.system.move_collider(handle, position)
+	}
+
+	/// Records and does a [PhysicsSystem::set_material_pair_properties] call.
+	pub fn set_material_pair_properties(&mut self, first : MaterialHandle, second : MaterialHandle, properties : Option<ContactProperties>) -> Result<Option<ContactProperties>, ()> {
+		self.log.calls.push(RecordedCall::SetMaterialPairProperties(first, second, properties));
+		self.system.set_material_pair_properties(first, second, properties)
+	}
+
+	/// Records and does a [PhysicsSystem::set_entity_pair_properties] call.
+	pub fn set_entity_pair_properties(&mut self, first : EntityHandle, second : EntityHandle, properties : Option<ContactProperties>) -> Result<Option<ContactProperties>, ()> {
+		self.log.calls.push(RecordedCall::SetEntityPairProperties(first, second, properties));
+		self.system.set_entity_pair_properties(first, second, properties)
+	}
+
+	/// Records and adds a [GravityGenerator] with the given acceleration.
+	pub fn add_gravity(&mut self, acceleration : impl Into<Vec3>) -> Result<UnaryForceGeneratorHandle, ()> {
+		let acceleration = acceleration.into();
+		self.log.calls.push(RecordedCall::AddGravity(acceleration));
+		self.system.add_unary_force_generator(Box::new(GravityGenerator::new(acceleration)))
+	}
+
+	/// Records and does a [PhysicsSystem::remove_unary_force_generator] call. Returns whether anything was removed.
+	pub fn remove_unary_force_generator(&mut self, handle : UnaryForceGeneratorHandle) -> bool {
+		self.log.calls.push(RecordedCall::RemoveUnaryForceGenerator(handle));
+		self.system.remove_unary_force_generator(handle).is_some()
+	}
+
+	/// Records and does a [PhysicsSystem::step] call.
+	pub fn step(&mut self, dt : f32) {
+		self.log.calls.push(RecordedCall::Step(dt));
+		self.system.step(dt)
+	}
+
+	/// Records and does a [PhysicsSystem::step_with_iteration_max] call.
+	pub fn step_with_iteration_max(&mut self, dt : f32, iteration_max : u32) {
+		self.log.calls.push(RecordedCall::StepWithIterationMax(dt, iteration_max));
+		self.system.step_with_iteration_max(dt, iteration_max)
+	}
+}
+
+/// Creates a new system and runs everything in the log on it (in order), to end up where the recorded system did.
+///
+/// Calls that failed while recording fail the same way here, so their results are ignored.
+pub fn replay(log : &RecordedLog) -> PhysicsSystem {
+	let mut system = PhysicsSystem::new();
+	for call in &log.calls {
+		match call {
+			RecordedCall::AddEntity(source) => { let _ = system.add_entity(source.clone()); },
+			RecordedCall::RemoveEntity(handle) => { system.remove_entity(*handle); },
+			RecordedCall::UpdateEntity(handle, source, wake) => { let _ = system.update_entity_with(*handle, source.clone(), *wake); },
+			RecordedCall::SetEntityEnabled(handle, enabled) => { let _ = system.set_entity_enabled(*handle, *enabled); },
+			RecordedCall::TeleportEntity { handle, position, rotation, check_overlap } => { let _ = system.teleport_entity(*handle, *position, *rotation, *check_overlap); },
+			RecordedCall::ApplyImpulse { handle, position, impulse } => { let _ = system.apply_impulse(*handle, *position, *impulse); },
+			RecordedCall::AddCollider(source) => { let _ = system.add_collider(source.clone()); },
+			RecordedCall::RemoveCollider(handle) => system.remove_collider(*handle),
+			RecordedCall::UpdateCollider(handle, source) => { let _ = system.update_collider(*handle, source.clone()); },
+			RecordedCall::LinkCollider(collider_handle, entity_handle) => { let _ = system.link_collider(*collider_handle, *entity_handle); },
+			RecordedCall::SetColliderEnabled(handle, enabled) => { let _ = system.set_collider_enabled(*handle, *enabled); },
+			RecordedCall::MoveCollider(handle, position) => { let _ = system.move_collider(*handle, *position); },
+			RecordedCall::SetMaterialPairProperties(first, second, properties) => { let _ = system.set_material_pair_properties(*first, *second, *properties); },
+			RecordedCall::SetEntityPairProperties(first, second, properties) => { let _ = system.set_entity_pair_properties(*first, *second, *properties); },
+			RecordedCall::AddGravity(acceleration) => { let _ = system.add_unary_force_generator(Box::new(GravityGenerator::new(*acceleration))); },
+			RecordedCall::RemoveUnaryForceGenerator(handle) => { system.remove_unary_force_generator(*handle); },
+			RecordedCall::Step(dt) => system.step(*dt),
+			RecordedCall::StepWithIterationMax(dt, iteration_max) => system.step_with_iteration_max(*dt, *iteration_max),
+		}
+	}
+	system
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::sphere_collider::SphereCollider;
+	use crate::plane_collider::PlaneCollider;
+	use crate::body_type::BodyType;
+
+	/// Records a ball bouncing around a box made of planes (getting pushed around along the way).
+	fn record() -> (Recorder, Vec<EntityHandle>) {
+		let mut recorder = Recorder::new();
+		let mut handles = Vec::new();
+		for (position, normal) in [(Vec3::zeros(), Vec3::y()), (Vec3::new(-2.0, 0.0, 0.0), Vec3::x()), (Vec3::new(2.0, 0.0, 0.0), -Vec3::x())].iter() {
+			let mut entity = Entity::new();
+			entity.position = *position;
+			entity.body_type = BodyType::Static;
+			let entity_handle = recorder.add_entity(entity).unwrap();
+			let mut plane = PlaneCollider::new();
+			plane.normal = *normal;
+			let plane_handle = recorder.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			recorder.link_collider(plane_handle, Some(entity_handle)).unwrap();
+		}
+		for x in [-1.0, 0.5].iter() {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(*x, 2.0, 0.0);
+			entity.velocity = Vec3::new(3.0, 0.0, 0.0);
+			let entity_handle = recorder.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.25);
+			sphere.mass = 1.0;
+			sphere.restitution_coefficient = 0.8;
+			let sphere_handle = recorder.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			recorder.link_collider(sphere_handle, Some(entity_handle)).unwrap();
+			handles.push(entity_handle);
+		}
+		let gravity = recorder.add_gravity(Vec3::new(0.0, -9.8, 0.0)).unwrap();
+		for index in 0..120 {
+			recorder.step(1.0 / 60.0);
+			if 30 == index {
+				recorder.apply_impulse(handles[0], Vec3::new(-1.0, 2.0, 0.0), Vec3::new(0.0, 2.0, 1.0)).unwrap();
+			}
+			if 60 == index {
+				let mut entity = recorder.get_system().get_entity(handles[1]).unwrap();
+				entity.velocity.x = -entity.velocity.x;
+				recorder.update_entity(handles[1], entity).unwrap();
+			}
+			if 90 == index {
+				assert!(recorder.remove_unary_force_generator(gravity));
+			}
+		}
+		(recorder, handles)
+	}
+
+	/// Makes sure both systems ended up in exactly the same place.
+	fn assert_same(first : &PhysicsSystem, second : &PhysicsSystem, handles : &Vec<EntityHandle>) {
+		for handle in handles {
+			let (first, second) = (first.get_entity(*handle).unwrap(), second.get_entity(*handle).unwrap());
+			assert_eq!(first.position, second.position);
+			assert_eq!(first.velocity, second.velocity);
+			assert_eq!(first.angular_velocity, second.angular_velocity);
+		}
+	}
+
+	/// Verify replaying a log ends up exactly where the recording did.
+	#[test]
+	fn replays() {
+		let (recorder, handles) = record();
+		assert_eq!(recorder.get_log().calls.iter().filter(|call| matches!(call, RecordedCall::Step(_))).count(), 120);
+		let (system, log) = recorder.finish();
+		// Make sure the balls actually did something.
+		assert!(handles.iter().all(|handle| 0.01 < (system.get_entity(*handle).unwrap().position - Vec3::new(0.0, 2.0, 0.0)).magnitude()));
+		assert_same(&system, &replay(&log), &handles);
+	}
+
+	/// Verify a log can be saved (and loaded back) without changing the replay.
+	///
+	/// Note that JSON can't store infinite values, so the planes are made static rather than given infinite mass.
+	#[cfg(feature = "serde")]
+	#[test]
+	fn serializes() {
+		let (recorder, handles) = record();
+		let (system, log) = recorder.finish();
+		let loaded : RecordedLog = serde_json::from_str(&serde_json::to_string(&log).unwrap()).unwrap();
+		assert_eq!(loaded.calls.len(), log.calls.len());
+		assert_same(&system, &replay(&loaded), &handles);
+	}
+}
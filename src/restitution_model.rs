@@ -0,0 +1,51 @@
+use std::sync::Arc;
+
+use core::fmt::Debug;
+
+/// Works out the restitution coefficient for a collision from how fast the two were coming together. See [RestitutionModel::Custom].
+pub trait RestitutionCurve : Debug + Send + Sync {
+	/// Gets the restitution coefficient to use, given the colliders' combined one and how fast the two were coming together (along the normal, before the collision).
+	///
+	/// Anything that isn't a finite number at or above zero is treated as zero.
+	fn restitution(&self, coefficient : f32, approaching_speed : f32) -> f32;
+}
+
+/// How the restitution coefficient of a collision depends on how fast the two were coming together.
+///
+/// Whichever is picked, collisions slower than [crate::PhysicsSystem::restitution_velocity_threshold] (or [crate::PhysicsSystem::resting_contact_threshold]) never bounce.
+#[derive(Debug, Clone)]
+pub enum RestitutionModel {
+	/// The colliders' combined restitution coefficient is used as is, no matter the speed.
+	Constant,
+	/// The restitution coefficient tapers off at low speeds, like a real rubber ball that eventually stops bouncing.
+	///
+	/// Collisions at or below `zero_speed` don't bounce at all, ones at or above `full_speed` use the whole coefficient, and ones in between are scaled linearly.
+	Tapered {
+		/// The approaching speed at (and below) which nothing bounces.
+		zero_speed : f32,
+		/// The approaching speed at (and above) which the whole coefficient is used.
+		full_speed : f32,
+	},
+	/// The restitution coefficient comes from a [RestitutionCurve].
+	Custom(Arc<dyn RestitutionCurve>),
+}
+
+impl RestitutionModel {
+	/// Gets the restitution coefficient for a collision, from the colliders' combined coefficient and how fast the two were coming together.
+	pub(crate) fn apply(&self, coefficient : f32, approaching_speed : f32) -> f32 {
+		let restitution = match self {
+			RestitutionModel::Constant => coefficient,
+			RestitutionModel::Tapered { zero_speed, full_speed } => {
+				if approaching_speed <= *zero_speed {
+					0.0
+				} else if *full_speed <= approaching_speed {
+					coefficient
+				} else {
+					coefficient * (approaching_speed - zero_speed) / (full_speed - zero_speed)
+				}
+			}
+			RestitutionModel::Custom(curve) => curve.restitution(coefficient, approaching_speed),
+		};
+		if restitution.is_finite() && 0.0 <= restitution { restitution } else { 0.0 }
+	}
+}
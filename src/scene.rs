@@ -0,0 +1,117 @@
+use crate::types::{Vec3, MaterialHandle};
+use crate::entity::Entity;
+use crate::body_type::BodyType;
+use crate::collider_wrapper::ColliderWrapper;
+use crate::contact_properties::ContactProperties;
+
+/// A whole [crate::PhysicsSystem] written out as plain data, i.e. so test cases and bug reports can be shared as small text files. See [crate::PhysicsSystem::to_scene_string] and [crate::PhysicsSystem::from_scene_str].
+///
+/// Only what's needed to rebuild the entities and colliders is kept. Handles aren't: everything is rebuilt in order, so gets new ones. Only available with the `scene` feature.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Scene {
+	/// Every entity, along with the colliders linked to it.
+	pub entities : Vec<SceneEntity>,
+
+	/// Every collider that isn't linked to any entity.
+	pub loose_colliders : Vec<ColliderWrapper>,
+
+	/// The acceleration of every [crate::GravityGenerator]. No other kind of force generator can be written out, so the rest are left out.
+	pub gravity : Vec<Vec3>,
+
+	/// The contact properties set for pairs of materials (see [crate::PhysicsSystem::set_material_pair_properties]).
+	pub material_pairs : Vec<(MaterialHandle, MaterialHandle, ContactProperties)>,
+}
+
+/// A single entity in a [Scene]. Anything left out of the text is set to its default (the same as [Entity::new]).
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SceneEntity {
+	/// See [Entity::name].
+	pub name : Option<String>,
+
+	/// Where the entity's local origin is in world space. Unlike [Entity::position], this ISN'T the center of mass: that's worked out from the colliders once they're linked.
+	pub origin : Vec3,
+
+	/// See [Entity::rotation].
+	pub rotation : Vec3,
+
+	/// See [Entity::velocity].
+	pub velocity : Vec3,
+
+	/// See [Entity::angular_velocity].
+	pub angular_velocity : Vec3,
+
+	/// See [Entity::own_mass].
+	pub own_mass : f32,
+
+	/// See [Entity::body_type].
+	pub body_type : BodyType,
+
+	/// See [Entity::query_groups].
+	pub query_groups : u32,
+
+	/// See [Entity::respond].
+	pub respond : bool,
+
+	/// See [Entity::dominance].
+	pub dominance : i32,
+
+	/// See [Entity::time_scale].
+	pub time_scale : f32,
+
+	/// See [Entity::lifetime].
+	pub lifetime : Option<f32>,
+
+	/// Whether the entity is enabled (see [crate::PhysicsSystem::set_entity_enabled]).
+	pub enabled : bool,
+
+	/// The colliders linked to this entity.
+	pub colliders : Vec<ColliderWrapper>,
+}
+
+impl SceneEntity {
+	/// Copies an entity's values (without any colliders).
+	pub fn from_entity(entity : &Entity) -> SceneEntity {
+		SceneEntity {
+			name: entity.name.clone(),
+			origin: entity.get_last_orientation().local_origin_in_world(),
+			rotation: entity.rotation,
+			velocity: entity.velocity,
+			angular_velocity: entity.angular_velocity,
+			own_mass: entity.own_mass,
+			body_type: entity.body_type,
+			query_groups: entity.query_groups,
+			respond: entity.respond,
+			dominance: entity.dominance,
+			time_scale: entity.time_scale,
+			lifetime: entity.lifetime,
+			enabled: entity.was_enabled(),
+			colliders: Vec::new(),
+		}
+	}
+
+	/// Makes the entity to add to a system. Its position is the local origin, which is only right until its colliders are linked.
+	pub fn make_entity(&self) -> Entity {
+		let mut entity = Entity::new();
+		entity.name = self.name.clone();
+		entity.position = self.origin;
+		entity.rotation = self.rotation;
+		entity.velocity = self.velocity;
+		entity.angular_velocity = self.angular_velocity;
+		entity.own_mass = self.own_mass;
+		entity.body_type = self.body_type;
+		entity.query_groups = self.query_groups;
+		entity.respond = self.respond;
+		entity.dominance = self.dominance;
+		entity.time_scale = self.time_scale;
+		entity.lifetime = self.lifetime;
+		entity
+	}
+}
+
+impl Default for SceneEntity {
+	fn default() -> SceneEntity {
+		SceneEntity::from_entity(&Entity::new())
+	}
+}
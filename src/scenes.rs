@@ -0,0 +1,166 @@
+//! Pre-built scenes, for regression testing solvers (and for trying things out in examples).
+//!
+//! Each scene adds a floor (an infinite-mass plane at y = 0), gravity, and then its own entities to whatever world it's given.
+
+use std::f32::INFINITY;
+
+use crate::types::{Vec3, EntityHandle};
+use crate::entity::Entity;
+use crate::sphere_collider::SphereCollider;
+use crate::plane_collider::PlaneCollider;
+use crate::aligned_box_collider::AlignedBoxCollider;
+use crate::collider_wrapper::ColliderWrapper;
+use crate::gravity_generator::GravityGenerator;
+use crate::physics_world::PhysicsWorld;
+use crate::ball_joint::BallJoint;
+
+/// The gravitational acceleration used by every scene.
+pub const GRAVITY : f32 = -9.8;
+
+/// The gap left between neighboring boxes in a [pyramid].
+pub const PYRAMID_GAP : f32 = 0.05;
+
+/// Adds the floor and gravity.
+fn add_floor(world : &mut dyn PhysicsWorld) {
+	let floor = world.add_entity(Entity::new()).unwrap();
+	let mut plane = PlaneCollider::new();
+	plane.mass = INFINITY;
+	let plane_handle = world.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+	world.link_collider(plane_handle, Some(floor)).unwrap();
+	world.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, GRAVITY, 0.0)))).unwrap();
+}
+
+/// Adds a unit box (with a mass of one) centered on the given position.
+fn add_box(world : &mut dyn PhysicsWorld, position : &Vec3) -> EntityHandle {
+	let mut entity = Entity::new();
+	entity.position = *position;
+	let handle = world.add_entity(entity).unwrap();
+	let mut collider = AlignedBoxCollider::from_center_half_extents(Vec3::zeros(), Vec3::repeat(0.5));
+	collider.mass = 1.0;
+	collider.restitution_coefficient = 0.0;
+	let collider_handle = world.add_collider(ColliderWrapper::AlignedBox(collider)).unwrap();
+	world.link_collider(collider_handle, Some(handle)).unwrap();
+	handle
+}
+
+/// A single column of `count` unit boxes, resting on each other. Box N starts centered at (0, N + 0.5, 0).
+///
+/// Returns the boxes, from the bottom up.
+pub fn box_stack(world : &mut dyn PhysicsWorld, count : usize) -> Vec<EntityHandle> {
+	add_floor(world);
+	(0..count).map(|index| add_box(world, &Vec3::new(0.0, 0.5 + index as f32, 0.0))).collect()
+}
+
+/// A 2D pyramid of unit boxes (in the XY plane) with `rows` boxes on the bottom row, one fewer on the next, and so on up to a single box.
+///
+/// Neighbors in a row are [PYRAMID_GAP] apart, so each box rests on the two below it.
+///
+/// Returns the boxes, row by row from the bottom up (and left to right within each row).
+pub fn pyramid(world : &mut dyn PhysicsWorld, rows : usize) -> Vec<EntityHandle> {
+	add_floor(world);
+	let spacing = 1.0 + PYRAMID_GAP;
+	let mut boxes = Vec::with_capacity(rows * (rows + 1) / 2);
+	for row in 0..rows {
+		let row_count = rows - row;
+		let left = -0.5 * spacing * (row_count - 1) as f32;
+		for column in 0..row_count {
+			boxes.push(add_box(world, &Vec3::new(left + spacing * column as f32, 0.5 + row as f32, 0.0)));
+		}
+	}
+	boxes
+}
+
+/// A chain of `count` balls (each with a radius of 0.25 and a mass of one) hanging from an infinite-mass anchor at (0, `count` + 1, 0).
+///
+/// The chain starts out stretched sideways (along +X) with its links one unit apart, so it swings down and hits the floor if it's long enough. Neighboring balls are held together by [BallJoint]s halfway between them.
+///
+/// Returns the anchor followed by the balls, in order along the chain. Fails if the world's backend doesn't have joints (see [PhysicsWorld::add_joint]).
+pub fn ball_joint_chain(world : &mut dyn PhysicsWorld, count : usize) -> Result<Vec<EntityHandle>, ()> {
+	add_floor(world);
+	let height = count as f32 + 1.0;
+	let mut handles = Vec::with_capacity(count + 1);
+	for index in 0..=count {
+		let mut entity = Entity::new();
+		entity.position = Vec3::new(index as f32, height, 0.0);
+		let handle = world.add_entity(entity).unwrap();
+		let mut sphere = SphereCollider::new(0.25);
+		sphere.mass = if 0 == index { INFINITY } else { 1.0 };
+		let sphere_handle = world.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+		world.link_collider(sphere_handle, Some(handle)).unwrap();
+		if let Some(previous) = handles.last() {
+			let mut joint = BallJoint::new(*previous, handle);
+			joint.first_anchor = Vec3::new(0.5, 0.0, 0.0);
+			joint.second_anchor = Vec3::new(-0.5, 0.0, 0.0);
+			world.add_joint(joint)?;
+		}
+		handles.push(handle);
+	}
+	Ok(handles)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::backend::Backend;
+
+	/// Check that every box is still (close to) where it started, and (if `stopped`) not moving.
+	fn assert_still(world : &dyn PhysicsWorld, boxes : &[EntityHandle], starts : &[Vec3], stopped : bool) {
+		for (handle, start) in boxes.iter().zip(starts.iter()) {
+			let entity = world.get_entity(*handle).unwrap();
+			assert!((entity.position - start).magnitude() < 0.05, "{:?} vs {:?}", entity.position, start);
+			if stopped {
+				assert!(entity.velocity.magnitude() < 0.05, "{:?}", entity.velocity);
+			}
+		}
+	}
+
+	/// Check that a stack of 10 boxes stands still for 30 seconds, with either backend.
+	///
+	/// The impulse backend handles a stack's resting contacts one pair at a time, and runs out of iterations (see [crate::PhysicsSystem::iteration_max]) long before that settles the boxes' velocities. So it's only expected to hold them in place.
+	#[test]
+	fn box_stack_stands_still() {
+		for backend in [Backend::Impulse, Backend::Xpbd] {
+			let mut world = backend.create();
+			let boxes = box_stack(world.as_mut(), 10);
+			let starts : Vec<Vec3> = boxes.iter().map(|handle| world.get_entity(*handle).unwrap().position).collect();
+			for _ in 0..(30 * 60) {
+				world.step(1.0 / 60.0);
+			}
+			assert_still(world.as_ref(), &boxes, &starts, Backend::Xpbd == backend);
+		}
+	}
+
+	/// Check that a pyramid settles without sliding apart, with either backend. (See [box_stack_stands_still] for why the impulse backend's boxes can still be moving.)
+	#[test]
+	fn pyramid_stands_still() {
+		for backend in [Backend::Impulse, Backend::Xpbd] {
+			let mut world = backend.create();
+			let boxes = pyramid(world.as_mut(), 4);
+			assert_eq!(10, boxes.len());
+			let starts : Vec<Vec3> = boxes.iter().map(|handle| world.get_entity(*handle).unwrap().position).collect();
+			for _ in 0..(5 * 60) {
+				world.step(1.0 / 60.0);
+			}
+			assert_still(world.as_ref(), &boxes, &starts, Backend::Xpbd == backend);
+		}
+	}
+
+	/// Check that a chain swings without coming apart, and that it can't be built in a backend without joints.
+	#[test]
+	fn ball_joint_chain_holds_together() {
+		assert!(ball_joint_chain(Backend::Impulse.create().as_mut(), 5).is_err());
+
+		let mut world = Backend::Xpbd.create();
+		let links = ball_joint_chain(world.as_mut(), 5).unwrap();
+		assert_eq!(6, links.len());
+		for _ in 0..(3 * 60) {
+			world.step(1.0 / 60.0);
+			for pair in links.windows(2) {
+				let distance = (world.get_entity(pair[1]).unwrap().position - world.get_entity(pair[0]).unwrap().position).magnitude();
+				assert!(distance < 1.01);
+			}
+		}
+		// Should have swung down.
+		assert!(world.get_entity(links[5]).unwrap().position.y < 3.0);
+	}
+}
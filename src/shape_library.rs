@@ -0,0 +1,108 @@
+use generational_arena::Arena;
+
+use crate::types::ShapeHandle;
+use crate::mesh_collider::MeshCollider;
+
+/// Stores mesh geometry once, so it can be shared by any number of colliders (even ones in different [crate::PhysicsSystem] instances).
+///
+/// Colliders made from this only reference the stored geometry, so running many systems with the same (large) level mesh doesn't copy it for each one. Changing a collider's geometry later only copies it for that collider. To share this between threads, wrap it in an `Arc`.
+#[derive(Debug, Default)]
+pub struct ShapeLibrary {
+	/// The stored geometry. Everything besides the geometry is left at default.
+	meshes : Arena<MeshCollider>,
+}
+
+impl ShapeLibrary {
+	/// Creates an empty instance.
+	pub fn new() -> ShapeLibrary {
+		ShapeLibrary { meshes: Arena::new() }
+	}
+
+	/// Stores the geometry of the passed in mesh (everything else about it is ignored). Fails if the mesh isn't valid.
+	pub fn add_mesh(&mut self, source : &MeshCollider) -> Result<ShapeHandle, ()> {
+		if !source.is_valid() {
+			return Err(()); // TODO: An error type.
+		}
+		let mut mesh = MeshCollider::new();
+		mesh.share_geometry_from(source);
+		Ok(self.meshes.insert(mesh))
+	}
+
+	/// Removes the stored geometry. Any colliders already made from it are unaffected. Returns whether there was anything to remove.
+	pub fn remove_mesh(&mut self, handle : ShapeHandle) -> bool {
+		self.meshes.remove(handle).is_some()
+	}
+
+	/// Gets the stored geometry (as a mesh with everything else at default).
+	pub fn get_mesh(&self, handle : ShapeHandle) -> Option<&MeshCollider> {
+		self.meshes.get(handle)
+	}
+
+	/// Makes a new mesh collider that uses the stored geometry, with everything else at default. Returns None if there's nothing stored for the handle.
+	pub fn make_mesh_collider(&self, handle : ShapeHandle) -> Option<MeshCollider> {
+		self.meshes.get(handle).map(|stored| {
+			let mut mesh = MeshCollider::new();
+			mesh.share_geometry_from(stored);
+			mesh
+		})
+	}
+
+	/// The number of meshes stored.
+	pub fn mesh_count(&self) -> usize { self.meshes.len() }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::Arc;
+	use std::thread;
+	use crate::types::Vec3;
+	use crate::entity::Entity;
+	use crate::collider_wrapper::ColliderWrapper;
+	use crate::physics_system::PhysicsSystem;
+
+	/// Verify several systems (on different threads) can all use the same geometry without copying it.
+	#[test]
+	fn shared_between_systems() {
+		let mut library = ShapeLibrary::new();
+		assert!(library.add_mesh(&MeshCollider::new()).is_err());
+		let level = library.add_mesh(&MeshCollider::new_cuboid(Vec3::new(10.0, 0.5, 10.0)).unwrap()).unwrap();
+		assert_eq!(library.mesh_count(), 1);
+		let library = Arc::new(library);
+
+		let threads : Vec<_> = (0..3).map(|_| {
+			let library = library.clone();
+			thread::spawn(move || {
+				let mut system = PhysicsSystem::new();
+				let entity = system.add_entity(Entity::new()).unwrap();
+				let mut mesh = library.make_mesh_collider(level).unwrap();
+				mesh.restitution_coefficient = 0.5;
+				let collider = system.add_collider(ColliderWrapper::Mesh(mesh)).unwrap();
+				system.link_collider(collider, Some(entity)).unwrap();
+				system.step(0.1);
+				match system.get_collider(collider).unwrap() {
+					ColliderWrapper::Mesh(mesh) => {
+						assert_eq!(mesh.restitution_coefficient, 0.5);
+						assert!(mesh.shares_geometry_with(library.get_mesh(level).unwrap()));
+						// Changing the copy's geometry shouldn't touch the shared version.
+						let mut changed = mesh.clone();
+						changed.remove_face(0).unwrap();
+						assert!(!changed.shares_geometry_with(&mesh));
+						assert_eq!(mesh.face_count(), 6);
+						assert_eq!(changed.face_count(), 5);
+					},
+					_ => panic!("Expected a mesh collider."),
+				}
+			})
+		}).collect();
+		for thread in threads {
+			thread.join().unwrap();
+		}
+		assert_eq!(library.get_mesh(level).unwrap().face_count(), 6);
+
+		let mut library = Arc::try_unwrap(library).unwrap();
+		assert!(library.remove_mesh(level));
+		assert!(!library.remove_mesh(level));
+		assert!(library.make_mesh_collider(level).is_none());
+	}
+}
@@ -0,0 +1,10 @@
+use crate::types::EntityHandle;
+
+/// An entity that fell asleep or woke up. See [crate::PhysicsSystem::take_sleep_changes].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SleepChange {
+	/// The entity that changed.
+	pub entity : EntityHandle,
+	/// Whether it's now asleep (true) or awake (false).
+	pub asleep : bool,
+}
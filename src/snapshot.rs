@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+use crate::types::EntityHandle;
+use crate::orientation::Orientation;
+
+/// Where every (enabled) entity was at the end of a step. See [crate::PhysicsSystem::enable_double_buffering].
+#[derive(Debug, Clone, Default)]
+pub struct Snapshot {
+	/// Counts up by one with each snapshot that's published. The (empty) snapshot that readers start out with is zero.
+	pub version : u64,
+	/// The orientation of every enabled entity.
+	pub orientations : HashMap<EntityHandle, Orientation>,
+}
+
+/// Where readers pick up the front snapshot. Shared between the system and all of its readers.
+#[derive(Debug)]
+struct Front {
+	/// The current front snapshot (from `Arc::into_raw`). This holds one strong reference to it.
+	current : AtomicPtr<Snapshot>,
+	/// How many readers are in the middle of picking up `current`.
+	reading : AtomicUsize,
+	/// Old front snapshots that a reader might still be picking up, so can't be let go of yet. Readers never touch this, only publish() does.
+	retired : Mutex<Vec<Arc<Snapshot>>>,
+}
+
+impl Front {
+	/// Starts out with the given snapshot in front.
+	fn new(snapshot : Snapshot) -> Front {
+		Front {
+			current: AtomicPtr::new(Arc::into_raw(Arc::new(snapshot)) as *mut Snapshot),
+			reading: AtomicUsize::new(0),
+			retired: Mutex::new(Vec::new()),
+		}
+	}
+}
+
+impl Drop for Front {
+	fn drop(&mut self) {
+		// Safe as this came from Arc::into_raw(), and nothing else can be reading it anymore.
+		drop(unsafe { Arc::from_raw(*self.current.get_mut()) });
+	}
+}
+
+/// Reads the snapshots published by a [crate::PhysicsSystem] (i.e. from a render thread).
+///
+/// Cheap to clone, and safe to send to other threads. Reading is lock-free: it never waits on `step()` or on publishing, it just picks up a pointer (and bumps its reference count).
+#[derive(Debug, Clone)]
+pub struct SnapshotReader {
+	front : Arc<Front>,
+}
+
+impl SnapshotReader {
+	/// Gets the most recently published snapshot. It won't change, even as newer ones are published.
+	pub fn latest(&self) -> Arc<Snapshot> {
+		self.front.reading.fetch_add(1, Ordering::SeqCst);
+		let pointer = self.front.current.load(Ordering::SeqCst);
+		// Safe as publish() never lets go of a front snapshot while any reader is in the middle of picking one up, so the pointer is still alive until the reference count is bumped.
+		let snapshot = unsafe {
+			Arc::increment_strong_count(pointer);
+			Arc::from_raw(pointer)
+		};
+		self.front.reading.fetch_sub(1, Ordering::SeqCst);
+		snapshot
+	}
+}
+
+/// The system's side of the double buffer.
+#[derive(Debug)]
+pub(crate) struct DoubleBuffer {
+	/// What step() writes into.
+	back : Snapshot,
+	/// Whether `back` has been written since the last publish().
+	dirty : bool,
+	/// The version of the current front snapshot.
+	version : u64,
+	/// What readers read from.
+	front : Arc<Front>,
+}
+
+impl DoubleBuffer {
+	/// Creates a buffer where readers start out with an empty snapshot.
+	pub fn new() -> DoubleBuffer {
+		DoubleBuffer {
+			back: Snapshot::default(),
+			dirty: false,
+			version: 0,
+			front: Arc::new(Front::new(Snapshot::default())),
+		}
+	}
+
+	/// Makes a new reader of this buffer.
+	pub fn make_reader(&self) -> SnapshotReader {
+		SnapshotReader { front: self.front.clone() }
+	}
+
+	/// Replaces everything in the back buffer with the given orientations.
+	pub fn write<I>(&mut self, orientations : I) where I : Iterator<Item = (EntityHandle, Orientation)> {
+		self.back.orientations.clear();
+		self.back.orientations.extend(orientations);
+		self.dirty = true;
+	}
+
+	/// Swaps the back buffer to the front (if it's been written to since the last call). Reuses an old front buffer's memory for the new back buffer if no readers are still holding on to it.
+	pub fn publish(&mut self) {
+		if !self.dirty { return; }
+		self.version += 1;
+		self.back.version = self.version;
+		let snapshot = Arc::into_raw(Arc::new(std::mem::take(&mut self.back))) as *mut Snapshot;
+		let old_front = self.front.current.swap(snapshot, Ordering::SeqCst);
+		let mut retired = self.front.retired.lock().unwrap();
+		// Safe as this came from Arc::into_raw(), and the front slot's reference is being handed over.
+		retired.push(unsafe { Arc::from_raw(old_front) });
+		// Any reader that starts after this will see the new front, so if none are reading right now, nothing can still be picking up an old one.
+		if 0 == self.front.reading.load(Ordering::SeqCst) {
+			for old in retired.drain(..) {
+				if let Ok(old) = Arc::try_unwrap(old) {
+					self.back = old;
+				}
+			}
+		}
+		self.dirty = false;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Verify readers on other threads only ever see whole snapshots, in order, while new ones keep being published.
+	#[test]
+	fn read_while_publishing() {
+		let mut buffer = DoubleBuffer::new();
+		let readers : Vec<_> = (0..4).map(|_| {
+			let reader = buffer.make_reader();
+			std::thread::spawn(move || {
+				let mut last = 0;
+				while last < 1000 {
+					let snapshot = reader.latest();
+					assert!(last <= snapshot.version);
+					// Each snapshot has `version % 8` entries, so a half-written one would show.
+					assert_eq!(snapshot.orientations.len() as u64, snapshot.version % 8);
+					last = snapshot.version;
+				}
+			})
+		}).collect();
+		let origin = Orientation::new(crate::types::Vec3::zeros(), crate::types::Vec3::zeros(), crate::types::Vec3::zeros());
+		for version in 1..=1000 {
+			buffer.write((0..(version % 8)).map(|index| (crate::types::handle_from_id(index), origin)));
+			buffer.publish();
+		}
+		for reader in readers {
+			reader.join().unwrap();
+		}
+		assert_eq!(buffer.make_reader().latest().version, 1000);
+	}
+}
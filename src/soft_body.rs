@@ -0,0 +1,252 @@
+use crate::consts::{EPSILON, SURFACE_OFFSET};
+use crate::types::Vec3;
+use crate::physics_world::PhysicsWorld;
+
+/// A single point mass in a [SoftBody].
+#[derive(Debug, Clone)]
+pub struct PointMass {
+	/// The position in WORLD space.
+	pub position : Vec3,
+	/// The velocity in WORLD space.
+	pub velocity : Vec3,
+	/// The mass. Must be positive.
+	pub mass : f32,
+}
+
+/// What sort of job a [Spring] does in a [SoftBody]. This doesn't change how the spring behaves, it's just to make it easier to find/tweak springs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpringKind {
+	/// Links directly neighboring points. Holds the overall shape.
+	Structural,
+	/// Links points diagonally. Resists shearing.
+	Shear,
+	/// Links points two apart. Resists bending.
+	Bend,
+}
+
+/// A damped spring between two points in a [SoftBody].
+#[derive(Debug, Clone)]
+pub struct Spring {
+	/// The index of the first point.
+	pub first : usize,
+	/// The index of the second point.
+	pub second : usize,
+	/// The length that the spring doesn't push or pull at.
+	pub rest_length : f32,
+	/// How hard the spring pushes/pulls per unit of stretch.
+	pub stiffness : f32,
+	/// How hard the spring resists per unit of stretching speed.
+	pub damping : f32,
+	/// What sort of job this spring does.
+	pub kind : SpringKind,
+}
+
+/// An experimental soft body: point masses held together by springs.
+///
+/// The points collide with all of the colliders in a [PhysicsSystem](crate::PhysicsSystem), but don't push back on them (the entities never notice the soft body). The points also don't collide with each other.
+///
+/// Call [SoftBody::step] alongside every [PhysicsSystem::step](crate::PhysicsSystem::step) (with the same `dt`).
+#[derive(Debug, Clone)]
+pub struct SoftBody {
+	/// All of the point masses.
+	///
+	/// Defaults to an empty list.
+	pub points : Vec<PointMass>,
+
+	/// All of the springs.
+	///
+	/// Defaults to an empty list.
+	pub springs : Vec<Spring>,
+
+	/// The acceleration applied to every point.
+	///
+	/// Defaults to zero.
+	pub gravity : Vec3,
+
+	/// The restitution coefficient used when a point hits a collider.
+	///
+	/// Defaults to `0.0`.
+	pub restitution_coefficient : f32,
+
+	/// The friction coefficient used when a point hits a collider.
+	///
+	/// Defaults to `0.5`.
+	pub friction_coefficient : f32,
+}
+
+impl SoftBody {
+	/// Creates an empty soft body.
+	pub fn new() -> SoftBody {
+		SoftBody {
+			points: Vec::new(),
+			springs: Vec::new(),
+			gravity: Vec3::zeros(),
+			restitution_coefficient: 0.0,
+			friction_coefficient: 0.5,
+		}
+	}
+
+	/// Creates a box-shaped lattice of points, `counts` points along each axis starting from `min_corner` and `spacing` apart.
+	///
+	/// Every point is linked to its direct neighbors (structural), its diagonal neighbors (shear), and the points two away along each axis (bend). All springs use the same stiffness and damping.
+	pub fn new_lattice(min_corner : impl Into<Vec3>, counts : [usize; 3], spacing : f32, point_mass : f32, stiffness : f32, damping : f32) -> Result<SoftBody, ()> {
+		if counts.contains(&0) || spacing < EPSILON {
+			return Err(()); // TODO: An error type.
+		}
+		let min_corner = min_corner.into();
+		let mut body = SoftBody::new();
+		let index_of = |x : usize, y : usize, z : usize| (x * counts[1] + y) * counts[2] + z;
+		for x in 0..counts[0] {
+			for y in 0..counts[1] {
+				for z in 0..counts[2] {
+					body.add_point(min_corner + Vec3::new(x as f32, y as f32, z as f32) * spacing, point_mass)?;
+				}
+			}
+		}
+		for x in 0..counts[0] {
+			for y in 0..counts[1] {
+				for z in 0..counts[2] {
+					// Only link "forward" along each axis, so each pair is only linked once.
+					for dx in -1i32..=2 {
+						for dy in -1i32..=2 {
+							for dz in -1i32..=2 {
+								let offset = [dx, dy, dz];
+								let kind = match offset.iter().map(|value| value.abs()).collect::<Vec<i32>>()[..] {
+									[1, 0, 0] | [0, 1, 0] | [0, 0, 1] => SpringKind::Structural,
+									[2, 0, 0] | [0, 2, 0] | [0, 0, 2] => SpringKind::Bend,
+									[a, b, c] if a <= 1 && b <= 1 && c <= 1 && 2 <= a + b + c => SpringKind::Shear,
+									_ => continue,
+								};
+								if offset <= [0, 0, 0] { continue; } // Only link to points "after" this one.
+								let (other_x, other_y, other_z) = (x as i32 + dx, y as i32 + dy, z as i32 + dz);
+								if other_x < 0 || other_y < 0 || other_z < 0 || counts[0] as i32 <= other_x || counts[1] as i32 <= other_y || counts[2] as i32 <= other_z { continue; }
+								body.add_spring(index_of(x, y, z), index_of(other_x as usize, other_y as usize, other_z as usize), kind, stiffness, damping)?;
+							}
+						}
+					}
+				}
+			}
+		}
+		Ok(body)
+	}
+
+	/// Adds a point, returning its index.
+	pub fn add_point(&mut self, position : impl Into<Vec3>, mass : f32) -> Result<usize, ()> {
+		if !mass.is_finite() || mass < EPSILON {
+			return Err(()); // TODO: An error type.
+		}
+		self.points.push(PointMass {
+			position: position.into(),
+			velocity: Vec3::zeros(),
+			mass,
+		});
+		Ok(self.points.len() - 1)
+	}
+
+	/// Adds a spring between two points, returning its index. The spring's rest length is the current distance between the points.
+	pub fn add_spring(&mut self, first : usize, second : usize, kind : SpringKind, stiffness : f32, damping : f32) -> Result<usize, ()> {
+		if first == second || self.points.len() <= first || self.points.len() <= second {
+			return Err(()); // TODO: An error type.
+		}
+		self.springs.push(Spring {
+			first,
+			second,
+			rest_length: (self.points[second].position - self.points[first].position).magnitude(),
+			stiffness,
+			damping,
+			kind,
+		});
+		Ok(self.springs.len() - 1)
+	}
+
+	/// Gets the (mass-weighted) center of all of the points.
+	pub fn get_center_of_mass(&self) -> Vec3 {
+		let mut total = Vec3::zeros();
+		let mut mass = 0.0;
+		for point in &self.points {
+			total += point.position * point.mass;
+			mass += point.mass;
+		}
+		if EPSILON < mass { total / mass } else { total }
+	}
+
+	/// Moves all of the points forward by `dt`, colliding them with the colliders in `system`.
+	pub fn step(&mut self, system : &dyn PhysicsWorld, dt : f32) {
+		// First accumulate all the forces.
+		let mut forces : Vec<Vec3> = self.points.iter().map(|point| self.gravity * point.mass).collect();
+		for spring in &self.springs {
+			let first = &self.points[spring.first];
+			let second = &self.points[spring.second];
+			let delta = second.position - first.position;
+			let length = delta.magnitude();
+			if length < EPSILON { continue; }
+			let direction = delta / length;
+			let stretch_speed = (second.velocity - first.velocity).dot(&direction);
+			let force = direction * (spring.stiffness * (length - spring.rest_length) + spring.damping * stretch_speed);
+			forces[spring.first] += force;
+			forces[spring.second] -= force;
+		}
+		// Then move each point, stopping it at the first thing it hits.
+		for (point, force) in self.points.iter_mut().zip(forces.iter()) {
+			point.velocity += force * (dt / point.mass);
+			let movement = point.velocity * dt;
+			let distance = movement.magnitude();
+			let hit = if EPSILON < distance { system.cast_ray(&point.position, &movement, distance, None) } else { None };
+			if let Some(hit) = hit {
+				point.position = hit.position + hit.normal * SURFACE_OFFSET;
+				let normal_speed = point.velocity.dot(&hit.normal);
+				if normal_speed < 0.0 {
+					let tangent_velocity = point.velocity - hit.normal * normal_speed;
+					let tangent_speed = tangent_velocity.magnitude();
+					// Friction can at most stop all the sliding.
+					let friction_speed = (-normal_speed * (1.0 + self.restitution_coefficient) * self.friction_coefficient).min(tangent_speed);
+					point.velocity = hit.normal * (-normal_speed * self.restitution_coefficient);
+					if EPSILON < tangent_speed {
+						point.velocity += tangent_velocity * ((tangent_speed - friction_speed) / tangent_speed);
+					}
+				}
+			} else {
+				point.position += movement;
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::physics_system::PhysicsSystem;
+	use std::f32::INFINITY;
+	use crate::entity::Entity;
+	use crate::plane_collider::PlaneCollider;
+	use crate::collider_wrapper::ColliderWrapper;
+
+	/// Check that a lattice dropped onto the floor lands on it and keeps its shape.
+	#[test]
+	fn lattice_lands() {
+		let mut system = PhysicsSystem::new();
+		{
+			let floor = system.add_entity(Entity::new()).unwrap();
+			let mut plane = PlaneCollider::new();
+			plane.mass = INFINITY;
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			system.link_collider(plane_handle, Some(floor)).unwrap();
+		}
+		let mut body = SoftBody::new_lattice(Vec3::new(0.0, 1.0, 0.0), [3, 3, 3], 0.5, 0.1, 50.0, 0.5).unwrap();
+		assert_eq!(27, body.points.len());
+		assert_eq!(3 * 18, body.springs.iter().filter(|spring| SpringKind::Structural == spring.kind).count());
+		assert_eq!(3 * 9, body.springs.iter().filter(|spring| SpringKind::Bend == spring.kind).count());
+		body.gravity = Vec3::new(0.0, -1.0, 0.0);
+		for _ in 0..500 {
+			system.step(0.01);
+			body.step(&system, 0.01);
+		}
+		for point in &body.points {
+			assert!(0.0 < point.position.y);
+			assert!(point.velocity.magnitude() < 0.05);
+		}
+		// The bottom shouldn't sag much, so the top is still about 1.0 up.
+		let top = body.points.iter().map(|point| point.position.y).fold(0.0, f32::max);
+		assert!((top - 1.0).abs() < 0.1);
+	}
+}
@@ -1,15 +1,19 @@
-use crate::types::{Vec3, Mat3, EntityHandle};
+use crate::types::{Vec3, Mat3, EntityHandle, MaterialHandle};
 use crate::collider::{ColliderType, Collider, InternalCollider};
+use crate::anisotropic_friction::AnisotropicFriction;
 
 /// The minimum radius
 const MINIMUM_RADIUS : f32 = 0.05;
 
 /// The internal representation of a sphere collider.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct InternalSphereCollider {
 	/// The entity that this is linked to (if any).
 	entity : Option<EntityHandle>,
 
+	/// If this takes part in collisions and queries. Only changed with [crate::PhysicsSystem::set_collider_enabled].
+	enabled : bool,
+
 	/// The position of the center.
 	///
 	/// This is in the parent entity's local space.
@@ -32,6 +36,21 @@ pub struct InternalSphereCollider {
 
 	/// The dynamic friction coefficient. Should always at or between 0.0 and 1.0.
 	pub dynamic_friction_coefficient : f32,
+
+	/// How friction changes with the sliding direction (if at all).
+	pub anisotropic_friction : Option<AnisotropicFriction>,
+
+	/// Extra thickness around the surface, used for collision detection.
+	pub margin : f32,
+
+	/// What this is made of.
+	pub material : MaterialHandle,
+
+	/// If this is only seen by queries (i.e. ray casts), never colliding with anything nor adding any mass.
+	pub query_only : bool,
+
+	/// A tag copied into collision records.
+	pub surface_tag : u32,
 }
 
 impl InternalSphereCollider {
@@ -42,6 +61,7 @@ impl InternalSphereCollider {
 		} else {
 			Ok(Box::new(InternalSphereCollider {
 				entity: None,
+				enabled: true,
 				center: source.center.clone(),
 				radius: source.radius,
 				mass: source.mass,
@@ -49,10 +69,37 @@ impl InternalSphereCollider {
 				friction_threshold: source.friction_threshold,
 				static_friction_coefficient: source.static_friction_coefficient,
 				dynamic_friction_coefficient: source.dynamic_friction_coefficient,
+				anisotropic_friction: source.anisotropic_friction,
+				margin: source.margin,
+				material: source.material,
+				query_only: source.query_only,
+				surface_tag: source.surface_tag,
 			}))
 		}
 	}
 
+	/// Creates a free-floating sphere that's only used for queries (i.e. [crate::PhysicsSystem::cast_sphere]).
+	///
+	/// Unlike new_from(), this allows any radius.
+	pub fn new_for_query(radius : f32) -> InternalSphereCollider {
+		InternalSphereCollider {
+			entity: None,
+			enabled: true,
+			center: Vec3::zeros(),
+			radius,
+			mass: 0.0,
+			restitution_coefficient: 0.0,
+			friction_threshold: 0.0,
+			static_friction_coefficient: 0.0,
+			dynamic_friction_coefficient: 0.0,
+			anisotropic_friction: None,
+			margin: 0.0,
+			material: 0,
+			query_only: false,
+			surface_tag: 0,
+		}
+	}
+
 	/// Makes a SphereCollider copying this instance's values.
 	pub fn make_pub(&self) -> SphereCollider {
 		SphereCollider {
@@ -64,6 +111,11 @@ impl InternalSphereCollider {
 			friction_threshold: self.friction_threshold,
 			static_friction_coefficient: self.static_friction_coefficient,
 			dynamic_friction_coefficient: self.dynamic_friction_coefficient,
+			anisotropic_friction: self.anisotropic_friction,
+			margin: self.margin,
+			material: self.material,
+			query_only: self.query_only,
+			surface_tag: self.surface_tag,
 		}
 	}
 
@@ -79,6 +131,11 @@ impl InternalSphereCollider {
 			self.friction_threshold = source.friction_threshold;
 			self.static_friction_coefficient = source.static_friction_coefficient;
 			self.dynamic_friction_coefficient = source.dynamic_friction_coefficient;
+			self.anisotropic_friction = source.anisotropic_friction;
+			self.margin = source.margin;
+			self.material = source.material;
+			self.query_only = source.query_only;
+			self.surface_tag = source.surface_tag;
 			Ok(())
 		}
 	}
@@ -88,6 +145,9 @@ impl InternalCollider for InternalSphereCollider {
 	/// The specific type.
 	fn get_type(&self) -> ColliderType { ColliderType::SPHERE }
 
+	/// Makes a boxed copy.
+	fn clone_boxed(&self) -> Box<dyn InternalCollider> { Box::new(self.clone()) }
+
 	/// Sets the entity this is attached to, returning the previous one.
 	fn set_entity(&mut self, handle : Option<EntityHandle>) -> Option<EntityHandle> {
 		let old = self.entity;
@@ -98,11 +158,19 @@ impl InternalCollider for InternalSphereCollider {
 	/// Retrieves the stored entity handle that this is attached to.
 	fn get_entity(&mut self) -> Option<EntityHandle> { self.entity }
 
+	fn set_enabled(&mut self, enabled : bool) { self.enabled = enabled; }
+
+	fn is_enabled(&self) -> bool { self.enabled }
+
 	/// Gets the center of mass for this collider.
 	/// This is relative to this collider's owning/linked/attached entity.
 	/// This IS NOT relative to this collider's "center" property.
 	fn get_local_center_of_mass(&self) -> Vec3 { self.center }
 
+	fn get_local_position(&self) -> Vec3 { self.center }
+
+	fn set_local_position(&mut self, position : &Vec3) { self.center = *position; }
+
 	fn get_mass(&self) -> f32 { self.mass }
 
 	fn get_moment_of_inertia_tensor(&self) -> Mat3 {
@@ -117,10 +185,20 @@ impl InternalCollider for InternalSphereCollider {
 	fn get_static_friction_coefficient(&self) -> f32 { self.static_friction_coefficient }
 
 	fn get_dynamic_friction_coefficient(&self) -> f32 { self.dynamic_friction_coefficient }
+
+	fn get_anisotropic_friction(&self) -> Option<AnisotropicFriction> { self.anisotropic_friction }
+
+	fn get_margin(&self) -> f32 { self.margin }
+	fn get_material(&self) -> MaterialHandle { self.material }
+
+	fn is_query_only(&self) -> bool { self.query_only }
+
+	fn get_surface_tag(&self) -> u32 { self.surface_tag }
 }
 
 /// A copy of all of the publicly-accessible properties of a spherical collider.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SphereCollider {
 	/// The entity, if there is one. This is NOT copied back into InternalSphereCollider, hence why it's not "pub".
 	///
@@ -161,6 +239,31 @@ pub struct SphereCollider {
 	///
 	/// Defaults to `0.3`.
 	pub dynamic_friction_coefficient : f32,
+
+	/// How friction changes with the sliding direction. If this is None, then friction is the same in all directions.
+	///
+	/// Defaults to None.
+	pub anisotropic_friction : Option<AnisotropicFriction>,
+
+	/// Extra thickness around the surface, used for collision detection. Two colliders count as touching once their surfaces are as close as their margins added together (so things resting on each other stay that far apart). Must not be negative.
+	///
+	/// Defaults to `0.0`.
+	pub margin : f32,
+
+	/// What this is made of. Only used to look up any properties set for specific pairs of materials (see [crate::PhysicsSystem::set_material_pair_properties]).
+	///
+	/// Defaults to `0`.
+	pub material : MaterialHandle,
+
+	/// If true, this never collides with anything and adds no mass to its entity, but still moves with it and is hit by queries like [crate::PhysicsSystem::cast_ray]. i.e. for a large detection sphere around an enemy.
+	///
+	/// Defaults to `false`.
+	pub query_only : bool,
+
+	/// A tag that's copied into every [crate::CollisionRecord] this is a part of, but otherwise ignored. i.e. for picking which sound to play.
+	///
+	/// Defaults to `0`.
+	pub surface_tag : u32,
 }
 
 impl SphereCollider {
@@ -175,12 +278,17 @@ impl SphereCollider {
 			friction_threshold: 0.25,
 			static_friction_coefficient: 1.0,
 			dynamic_friction_coefficient: 0.3,
+			anisotropic_friction: None,
+			margin: 0.0,
+			material: 0,
+			query_only: false,
+			surface_tag: 0,
 		}
 	}
 
 	/// If this is in a valid state.
 	pub fn is_valid(&self) -> bool {
-		MINIMUM_RADIUS < self.radius && 0.0 <= self.mass
+		MINIMUM_RADIUS < self.radius && 0.0 <= self.mass && self.anisotropic_friction.iter().all(|friction| friction.is_valid()) && 0.0 <= self.margin
 	}
 }
 
@@ -0,0 +1,86 @@
+use crate::consts::{EPSILON, SURFACE_OFFSET};
+use crate::types::{Vec3, EntityHandle};
+use crate::query::RayHit;
+use crate::physics_world::PhysicsWorld;
+
+/// The result of a [sweep_and_slide] call.
+#[derive(Debug, Clone)]
+pub struct SweepResult {
+	/// Where the moving shape ended up.
+	pub position : Vec3,
+	/// Everything that was hit along the way, in order.
+	pub hits : Vec<RayHit>,
+}
+
+/// Moves a sphere (or a point, if `radius` is zero) from `from` towards `to` through the colliders in `world`, without creating any entities.
+///
+/// Whenever it hits something, whatever movement is left is deflected along the surface that was hit (so it slides along walls and floors rather than stopping). This happens at most `max_bounces` times; the movement left after that is dropped. Entities and colliders in the world aren't changed.
+///
+/// Disabled entities and the entity passed in as `ignore` (if any) are skipped.
+pub fn sweep_and_slide(world : &dyn PhysicsWorld, radius : f32, from : &Vec3, to : &Vec3, max_bounces : usize, ignore : Option<EntityHandle>) -> SweepResult {
+	let mut position = *from;
+	let mut remaining = to - from;
+	let mut hits = Vec::new();
+	for _ in 0..=max_bounces {
+		let distance = remaining.magnitude();
+		if distance < EPSILON { break; }
+		let hit = if radius < EPSILON {
+			world.cast_ray(&position, &remaining, distance, ignore)
+		} else {
+			world.cast_sphere(&position, &remaining, distance, radius, ignore)
+		};
+		let hit = match hit {
+			Some(hit) => hit,
+			None => {
+				position += remaining;
+				break;
+			},
+		};
+		// Move up to the hit (staying just off of the surface), then drop the part of the remaining movement that goes into the surface.
+		let fraction = hit.distance / distance;
+		position += remaining * fraction + hit.normal * SURFACE_OFFSET;
+		remaining *= 1.0 - fraction;
+		let into_surface = remaining.dot(&hit.normal);
+		if into_surface < 0.0 {
+			remaining -= hit.normal * into_surface;
+		}
+		hits.push(hit);
+	}
+	SweepResult { position, hits }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::f32::INFINITY;
+	use crate::physics_system::PhysicsSystem;
+	use crate::entity::Entity;
+	use crate::plane_collider::PlaneCollider;
+	use crate::collider_wrapper::ColliderWrapper;
+
+	/// Check that a sweep slides along the floor, and stops at the first hit if it isn't allowed to bounce.
+	#[test]
+	fn slide_along_floor() {
+		let mut system = PhysicsSystem::new();
+		let floor = system.add_entity(Entity::new()).unwrap();
+		let mut plane = PlaneCollider::new();
+		plane.mass = INFINITY;
+		let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+		system.link_collider(plane_handle, Some(floor)).unwrap();
+
+		let from = Vec3::new(0.0, 1.5, 0.0);
+		let to = Vec3::new(4.0, -0.5, 0.0);
+		let result = sweep_and_slide(&system, 0.5, &from, &to, 4, None);
+		assert_eq!(1, result.hits.len());
+		assert!(result.hits[0].entity == floor);
+		assert!((result.position - Vec3::new(4.0, 0.5 + SURFACE_OFFSET, 0.0)).magnitude() < 0.01);
+
+		let result = sweep_and_slide(&system, 0.5, &from, &to, 0, None);
+		assert_eq!(1, result.hits.len());
+		assert!((result.position - Vec3::new(2.0, 0.5 + SURFACE_OFFSET, 0.0)).magnitude() < 0.01);
+
+		// Points work too.
+		let result = sweep_and_slide(&system, 0.0, &from, &to, 4, None);
+		assert!((result.position - Vec3::new(4.0, SURFACE_OFFSET, 0.0)).magnitude() < 0.01);
+	}
+}
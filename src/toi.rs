@@ -0,0 +1,187 @@
+use crate::types::Vec3;
+use crate::orientation::Orientation;
+use crate::collider_wrapper::ColliderWrapper;
+use crate::collision::collide;
+
+/// How a shape moves over the time being checked by [toi]: it goes from `start` (at time zero) to `end` (at time one), at a steady speed.
+#[derive(Debug, Clone, Copy)]
+pub struct Motion {
+	/// Where the shape's owner starts.
+	pub start : Orientation,
+	/// Where the shape's owner ends up.
+	pub end : Orientation,
+}
+
+impl Motion {
+	/// Creates a new instance.
+	pub fn new(start : Orientation, end : Orientation) -> Motion {
+		Motion { start, end }
+	}
+
+	/// Creates a motion that moves in a straight line (without rotating) between the two positions.
+	pub fn linear(start : impl Into<Vec3>, end : impl Into<Vec3>) -> Motion {
+		Motion::new(
+			Orientation::new(start, Vec3::zeros(), Vec3::zeros()),
+			Orientation::new(end, Vec3::zeros(), Vec3::zeros()),
+		)
+	}
+
+	/// Creates a motion that doesn't move at all.
+	pub fn stationary(orientation : Orientation) -> Motion {
+		Motion::new(orientation, orientation)
+	}
+}
+
+/// When and where two shapes first touch (see [toi]).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeOfImpact {
+	/// When the shapes start touching, from zero (the start of the motions) to one (the end).
+	pub start_time : f32,
+	/// When the shapes stop touching (or one, if they never do before the motions end).
+	pub end_time : f32,
+	/// Where the shapes first touch (in world space).
+	pub position : Vec3,
+	/// The normal of the hit, pointing off of the first shape (towards the second).
+	pub normal : Vec3,
+}
+
+/// Checks when (if ever) two shapes touch as they follow the given motions. This is the same check that [crate::PhysicsSystem::step] uses, so is mainly for testing those checks (or for sweeping shapes around without a system).
+///
+/// Fails if either shape isn't valid.
+pub fn toi(shape_a : &ColliderWrapper, motion_a : &Motion, shape_b : &ColliderWrapper, motion_b : &Motion) -> Result<Option<TimeOfImpact>, ()> {
+	let (collider_a, collider_b) = (shape_a.make_internal()?, shape_b.make_internal()?);
+	Ok(collide(&collider_a, &motion_a.start, &motion_a.end, &collider_b, &motion_b.start, &motion_b.end).map(|collision| TimeOfImpact {
+		start_time: collision.times.min(),
+		end_time: collision.times.max(),
+		position: collision.position,
+		normal: collision.normal,
+	}))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use proptest::prelude::*;
+	use crate::sphere_collider::SphereCollider;
+	use crate::plane_collider::PlaneCollider;
+	use crate::mesh_collider::MeshCollider;
+	use crate::aligned_box_collider::AlignedBoxCollider;
+
+	/// How far off the checks are allowed to be. The generic convex check (used for meshes against boxes) only gets within about this much.
+	const TOLERANCE : f32 = 0.001;
+
+	/// A shape to fuzz with, which is easy to measure the distance to.
+	#[derive(Debug, Clone)]
+	enum Shape {
+		Sphere(f32),
+		Plane(Vec3),
+		/// A mesh cuboid with the given half extents.
+		Cuboid(Vec3),
+		/// An aligned box with the given half extents.
+		AlignedBox(Vec3),
+	}
+
+	impl Shape {
+		fn make_wrapper(&self) -> ColliderWrapper {
+			match self {
+				Shape::Sphere(radius) => ColliderWrapper::Sphere(SphereCollider::new(*radius)),
+				Shape::Plane(normal) => {
+					let mut plane = PlaneCollider::new();
+					plane.normal = *normal;
+					ColliderWrapper::Plane(plane)
+				},
+				Shape::Cuboid(half_extents) => ColliderWrapper::Mesh(MeshCollider::new_cuboid(*half_extents).unwrap()),
+				Shape::AlignedBox(half_extents) => ColliderWrapper::AlignedBox(AlignedBoxCollider::from_center_half_extents(Vec3::zeros(), *half_extents)),
+			}
+		}
+
+		/// The signed distance from the shape's surface to the point, when the shape's origin is at `center`.
+		fn distance(&self, center : &Vec3, point : &Vec3) -> f32 {
+			let offset = point - center;
+			match self {
+				Shape::Sphere(radius) => offset.magnitude() - radius,
+				Shape::Plane(normal) => offset.dot(normal),
+				Shape::Cuboid(half_extents) | Shape::AlignedBox(half_extents) => {
+					let outside = offset.abs() - half_extents;
+					outside.map(|value| value.max(0.0)).magnitude() + outside.max().min(0.0)
+				},
+			}
+		}
+
+		/// How far the shape reaches out from its origin along the (unit) direction. Infinite for planes.
+		fn reach(&self, direction : &Vec3) -> f32 {
+			match self {
+				Shape::Sphere(radius) => *radius,
+				Shape::Plane(_) => f32::INFINITY,
+				Shape::Cuboid(half_extents) | Shape::AlignedBox(half_extents) => direction.abs().dot(half_extents),
+			}
+		}
+
+		/// Whether the two shapes are within `gap` of each other, when their origins are at the given positions.
+		fn within(&self, center : &Vec3, other : &Shape, other_center : &Vec3, gap : f32) -> bool {
+			match (self, other) {
+				(Shape::Plane(_), Shape::Plane(_)) => true, // Never checked, so treat them like they're always touching.
+				(Shape::Plane(normal), _) => (other_center - center).dot(normal) <= other.reach(normal) + gap,
+				(_, Shape::Plane(_)) => other.within(other_center, self, center, gap),
+				(Shape::Sphere(radius), _) => other.distance(other_center, center) <= radius + gap,
+				(_, Shape::Sphere(_)) => other.within(other_center, self, center, gap),
+				_ => (0..3).all(|axis| {
+					let axis = Vec3::ith(axis, 1.0);
+					(other_center - center).dot(&axis).abs() <= self.reach(&axis) + other.reach(&axis) + gap
+				}),
+			}
+		}
+	}
+
+	fn vector(range : f32) -> impl Strategy<Value = Vec3> {
+		(-range..range, -range..range, -range..range).prop_map(|(x, y, z)| Vec3::new(x, y, z))
+	}
+
+	fn shape() -> impl Strategy<Value = Shape> {
+		prop_oneof![
+			(0.1f32..1.0).prop_map(Shape::Sphere),
+			vector(1.0).prop_filter("normal must have a direction", |normal| 0.1 < normal.magnitude()).prop_map(|normal| Shape::Plane(normal.normalize())),
+			vector(1.0).prop_map(|half_extents| Shape::Cuboid(half_extents.abs().add_scalar(0.1))),
+			vector(1.0).prop_map(|half_extents| Shape::AlignedBox(half_extents.abs().add_scalar(0.1))),
+		]
+	}
+
+	/// A shape, along with where it starts and ends.
+	fn moving_shape() -> impl Strategy<Value = (Shape, Vec3, Vec3)> {
+		(shape(), vector(3.0), vector(3.0))
+	}
+
+	/// Two moving shapes that start out (at least a little) apart. Things that start out overlapping (or touching) don't have a meaningful time, position, or normal to check (and the mesh checks can't even tell they're overlapping).
+	fn moving_pair() -> impl Strategy<Value = ((Shape, Vec3, Vec3), (Shape, Vec3, Vec3))> {
+		(moving_shape(), moving_shape()).prop_filter("shapes must start apart", |((shape_a, start_a, _), (shape_b, start_b, _))| !shape_a.within(start_a, shape_b, start_b, TOLERANCE))
+	}
+
+	proptest! {
+		/// Verify swapping the shapes gives the same hit, just with the normal flipped.
+		#[test]
+		fn symmetric(((shape_a, start_a, end_a), (shape_b, start_b, end_b)) in moving_pair()) {
+			let (motion_a, motion_b) = (Motion::linear(start_a, end_a), Motion::linear(start_b, end_b));
+			let forward = toi(&shape_a.make_wrapper(), &motion_a, &shape_b.make_wrapper(), &motion_b).unwrap();
+			let backward = toi(&shape_b.make_wrapper(), &motion_b, &shape_a.make_wrapper(), &motion_a).unwrap();
+			prop_assert_eq!(forward.is_some(), backward.is_some(), "{:?} vs {:?}", forward, backward);
+			if let (Some(forward), Some(backward)) = (forward, backward) {
+				prop_assert!((forward.start_time - backward.start_time).abs() < TOLERANCE, "{:?} vs {:?}", forward, backward);
+				prop_assert!((forward.normal + backward.normal).magnitude() < TOLERANCE, "{:?} vs {:?}", forward, backward);
+			}
+		}
+
+		/// Verify hits are within the motions' time, and are on the surface of both shapes.
+		#[test]
+		fn on_both_surfaces(((shape_a, start_a, end_a), (shape_b, start_b, end_b)) in moving_pair()) {
+			let (motion_a, motion_b) = (Motion::linear(start_a, end_a), Motion::linear(start_b, end_b));
+			if let Some(hit) = toi(&shape_a.make_wrapper(), &motion_a, &shape_b.make_wrapper(), &motion_b).unwrap() {
+				prop_assert!(0.0 <= hit.start_time && hit.start_time <= hit.end_time && hit.end_time <= 1.0, "{:?}", hit);
+				prop_assert!((hit.normal.magnitude() - 1.0).abs() < TOLERANCE, "{:?}", hit);
+				let center_a = start_a.lerp(&end_a, hit.start_time);
+				let center_b = start_b.lerp(&end_b, hit.start_time);
+				prop_assert!(shape_a.distance(&center_a, &hit.position).abs() < TOLERANCE, "{:?}", hit);
+				prop_assert!(shape_b.distance(&center_b, &hit.position).abs() < TOLERANCE, "{:?}", hit);
+			}
+		}
+	}
+}
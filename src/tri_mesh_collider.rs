@@ -0,0 +1,533 @@
+use std::sync::Arc;
+use std::collections::HashMap;
+
+use crate::types::{Vec3, Mat3, EntityHandle, MaterialHandle, Quat};
+use crate::collider::{ColliderType, Collider, InternalCollider};
+use crate::anisotropic_friction::AnisotropicFriction;
+use crate::mesh_collider::{MeshCollider, face_normal};
+use crate::bvh::Bvh;
+
+/// Everything worked out from a triangle mesh's geometry up front, so collisions don't need to.
+#[derive(Debug, Default)]
+pub struct TriMeshLookup {
+	/// The normal of each triangle (following the right-hand rule). NaN for triangles that don't cover any area.
+	pub normals : Vec<Vec3>,
+	/// For each triangle, the triangle on the other side of each of its edges (the edge from corner N to corner N+1). Only set when exactly one other triangle uses that edge, and winds the same way.
+	pub neighbors : Vec<[Option<usize>; 3]>,
+	/// A tree of boxes around the triangles (relative to the collider's position).
+	pub bvh : Bvh,
+}
+
+impl TriMeshLookup {
+	/// Works everything out for the given geometry.
+	pub fn new(vertices : &[Vec3], triangles : &[[usize; 3]]) -> TriMeshLookup {
+		let normals = triangles.iter().map(|triangle| face_normal(&triangle.iter().map(|index| vertices[*index]).collect())).collect();
+		// Neighbors that wind the same way go along their shared edge in opposite directions.
+		let mut uses : HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+		for (triangle_index, triangle) in triangles.iter().enumerate() {
+			for corner in 0..3 {
+				let (first, second) = (triangle[corner], triangle[(corner + 1) % 3]);
+				uses.entry((first.min(second), first.max(second))).or_default().push(triangle_index);
+			}
+		}
+		let neighbors = triangles.iter().enumerate().map(|(triangle_index, triangle)| {
+			let mut neighbors = [None; 3];
+			for (corner, neighbor) in neighbors.iter_mut().enumerate() {
+				let (first, second) = (triangle[corner], triangle[(corner + 1) % 3]);
+				let edge_uses = &uses[&(first.min(second), first.max(second))];
+				if 2 != edge_uses.len() { continue; }
+				let other = if edge_uses[0] == triangle_index { edge_uses[1] } else { edge_uses[0] };
+				let other_triangle = &triangles[other];
+				if (0..3).any(|other_corner| other_triangle[other_corner] == second && other_triangle[(other_corner + 1) % 3] == first) {
+					*neighbor = Some(other);
+				}
+			}
+			neighbors
+		}).collect();
+		let bounds : Vec<(Vec3, Vec3)> = triangles.iter().map(|triangle| {
+			let [first, second, third] = triangle.map(|index| vertices[index]);
+			(first.inf(&second).inf(&third), first.sup(&second).sup(&third))
+		}).collect();
+		TriMeshLookup { normals, neighbors, bvh: Bvh::new(&bounds) }
+	}
+}
+
+/// The internal representation of a triangle mesh collider.
+#[derive(Debug, Clone)]
+pub struct InternalTriMeshCollider {
+	/// The entity that this is linked to (if any).
+	entity : Option<EntityHandle>,
+
+	/// If this takes part in collisions and queries. Only changed with [crate::PhysicsSystem::set_collider_enabled].
+	enabled : bool,
+
+	/// The position of mesh origin.
+	///
+	/// This is in the parent entity's local space.
+	pub position : Vec3,
+
+	/// The vertices.
+	pub vertices : Arc<Vec<Vec3>>,
+	/// The triangles as indices into the `vertices` property.
+	pub triangles : Arc<Vec<[usize; 3]>>,
+
+	/// How far behind its triangles things still count as touching.
+	pub thickness : f32,
+
+	/// The restituion coefficient.
+	pub restitution_coefficient : f32,
+
+	/// The ratio used to decide whether to use static friction or dynamic friction.
+	pub friction_threshold : f32,
+
+	/// The static friction coefficient. Should always at or between 0.0 and 1.0.
+	pub static_friction_coefficient : f32,
+
+	/// The dynamic friction coefficient. Should always at or between 0.0 and 1.0.
+	pub dynamic_friction_coefficient : f32,
+
+	/// How friction changes with the sliding direction (if at all).
+	pub anisotropic_friction : Option<AnisotropicFriction>,
+
+	/// Extra thickness around the surface, used for collision detection.
+	pub margin : f32,
+
+	/// What this is made of.
+	pub material : MaterialHandle,
+
+	/// If this is only seen by queries (i.e. ray casts), never colliding with anything.
+	pub query_only : bool,
+
+	/// A tag copied into collision records.
+	pub surface_tag : u32,
+
+	/// The normals, neighbors, and tree of boxes for the triangles.
+	pub lookup : Arc<TriMeshLookup>,
+}
+
+impl InternalTriMeshCollider {
+	/// Creates a new instance.
+	pub fn new_from(source : &TriMeshCollider) -> Result<Box<dyn InternalCollider>, ()> {
+		if !source.is_valid() {
+			Err(()) // TODO: An error type.
+		} else {
+			Ok(Box::new(InternalTriMeshCollider {
+				entity: None,
+				enabled: true,
+				position: source.position.clone(),
+				vertices: source.vertices.clone(),
+				triangles: source.triangles.clone(),
+				thickness: source.thickness,
+				restitution_coefficient: source.restitution_coefficient,
+				friction_threshold: source.friction_threshold,
+				static_friction_coefficient: source.static_friction_coefficient,
+				dynamic_friction_coefficient: source.dynamic_friction_coefficient,
+				anisotropic_friction: source.anisotropic_friction,
+				margin: source.margin,
+				material: source.material,
+				query_only: source.query_only,
+				surface_tag: source.surface_tag,
+				lookup: Arc::new(TriMeshLookup::new(&source.vertices, &source.triangles)),
+			}))
+		}
+	}
+
+	/// Makes a TriMeshCollider copying this instance's values.
+	pub fn make_pub(&self) -> TriMeshCollider {
+		TriMeshCollider {
+			entity: self.entity.clone(),
+			position: self.position.clone(),
+			vertices: self.vertices.clone(),
+			triangles: self.triangles.clone(),
+			thickness: self.thickness,
+			restitution_coefficient: self.restitution_coefficient,
+			friction_threshold: self.friction_threshold,
+			static_friction_coefficient: self.static_friction_coefficient,
+			dynamic_friction_coefficient: self.dynamic_friction_coefficient,
+			anisotropic_friction: self.anisotropic_friction,
+			margin: self.margin,
+			material: self.material,
+			query_only: self.query_only,
+			surface_tag: self.surface_tag,
+		}
+	}
+
+	/// Updates from the passed in TriMeshCollider object.
+	pub fn update_from(&mut self, source : &TriMeshCollider) -> Result<(),()> {
+		if !source.is_valid() {
+			Err(()) // TODO: An error type.
+		} else {
+			self.position = source.position;
+			// Only rebuild the lookup if the geometry actually changed.
+			if !(Arc::ptr_eq(&self.vertices, &source.vertices) && Arc::ptr_eq(&self.triangles, &source.triangles)) {
+				self.lookup = Arc::new(TriMeshLookup::new(&source.vertices, &source.triangles));
+			}
+			self.vertices = source.vertices.clone();
+			self.triangles = source.triangles.clone();
+			self.thickness = source.thickness;
+			self.restitution_coefficient = source.restitution_coefficient;
+			self.friction_threshold = source.friction_threshold;
+			self.static_friction_coefficient = source.static_friction_coefficient;
+			self.dynamic_friction_coefficient = source.dynamic_friction_coefficient;
+			self.anisotropic_friction = source.anisotropic_friction;
+			self.margin = source.margin;
+			self.material = source.material;
+			self.query_only = source.query_only;
+			self.surface_tag = source.surface_tag;
+			Ok(())
+		}
+	}
+
+	/// The corners of a triangle, in the owning entity's local space.
+	pub fn corners(&self, triangle : usize) -> [Vec3; 3] {
+		self.triangles[triangle].map(|index| self.position + self.vertices[index])
+	}
+
+	/// Adds every triangle whose bounds overlap the given box (in the owning entity's local space) onto the end of `found`.
+	pub fn triangles_near(&self, min : &Vec3, max : &Vec3, found : &mut Vec<usize>) {
+		self.lookup.bvh.query_box(&(min - self.position), &(max - self.position), found);
+	}
+
+	/// Adds every triangle whose bounds the line segment from `start` to `end` (in the owning entity's local space) passes through onto the end of `found`.
+	pub fn triangles_along(&self, start : &Vec3, end : &Vec3, found : &mut Vec<usize>) {
+		self.lookup.bvh.query_segment(&(start - self.position), &(end - self.position), found);
+	}
+
+	/// Finds the closest point on the surface to the given point (both in the owning entity's local space), along with which triangle it's on. None if there are no triangles that cover any area.
+	pub fn closest_point(&self, point : &Vec3) -> Option<(usize, Vec3)> {
+		let local = point - self.position;
+		let (triangle, _) = self.lookup.bvh.nearest(&local, |triangle| {
+			if self.lookup.normals[triangle].x.is_nan() { return f32::INFINITY; }
+			let [first, second, third] = self.triangles[triangle].map(|index| self.vertices[index]);
+			(local - closest_point_on_triangle(&local, &first, &second, &third)).magnitude_squared()
+		})?;
+		let [first, second, third] = self.corners(triangle);
+		Some((triangle, closest_point_on_triangle(point, &first, &second, &third)))
+	}
+}
+
+impl InternalCollider for InternalTriMeshCollider {
+	/// The specific type.
+	fn get_type(&self) -> ColliderType { ColliderType::TRI_MESH }
+
+	/// Makes a boxed copy.
+	fn clone_boxed(&self) -> Box<dyn InternalCollider> { Box::new(self.clone()) }
+
+	/// Sets the entity this is attached to, returning the previous one.
+	fn set_entity(&mut self, handle : Option<EntityHandle>) -> Option<EntityHandle> {
+		let old = self.entity;
+		self.entity = handle;
+		old
+	}
+
+	/// Retrieves the stored entity handle that this is attached to.
+	fn get_entity(&mut self) -> Option<EntityHandle> { self.entity }
+
+	fn set_enabled(&mut self, enabled : bool) { self.enabled = enabled; }
+
+	fn is_enabled(&self) -> bool { self.enabled }
+
+	/// Gets the center of mass for this collider.
+	/// This is relative to this collider's owning/linked/attached entity.
+	/// This IS NOT relative to this collider's "position" property.
+	fn get_local_center_of_mass(&self) -> Vec3 { self.position }
+
+	fn get_local_position(&self) -> Vec3 { self.position }
+
+	fn set_local_position(&mut self, position : &Vec3) { self.position = *position; }
+
+	fn get_mass(&self) -> f32 { 0.0 }
+
+	fn get_moment_of_inertia_tensor(&self) -> Mat3 { Mat3::zeros() }
+
+	fn get_restitution_coefficient(&self) -> f32 { self.restitution_coefficient }
+
+	fn get_friction_threshold(&self) -> f32 { self.friction_threshold }
+
+	fn get_static_friction_coefficient(&self) -> f32 { self.static_friction_coefficient }
+
+	fn get_dynamic_friction_coefficient(&self) -> f32 { self.dynamic_friction_coefficient }
+
+	fn get_anisotropic_friction(&self) -> Option<AnisotropicFriction> { self.anisotropic_friction }
+
+	fn get_margin(&self) -> f32 { self.margin }
+	fn get_material(&self) -> MaterialHandle { self.material }
+
+	fn is_query_only(&self) -> bool { self.query_only }
+
+	fn get_surface_tag(&self) -> u32 { self.surface_tag }
+}
+
+/// A copy of all of the publicly-accessible properties of a triangle mesh collider.
+///
+/// Unlike [crate::MeshCollider] (which is always treated as convex, and so is for things that move around), this is for static level geometry: it can be any shape (concave, open, or hollow), but it has no mass so should only be linked to [crate::BodyType::Static] entities. Its triangles are one-sided: things only collide with their front (the side they wind counter-clockwise around), so anything that ends up behind one is let through rather than yanked back out.
+///
+/// It can collide with anything convex (spheres, boxes, and meshes), but not with planes or other triangle meshes.
+///
+/// The geometry is reference counted, so copying this around doesn't copy the geometry. A tree of boxes around the triangles is built when this is added to a [crate::PhysicsSystem] (or its geometry changes), so only the triangles near something are ever checked.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TriMeshCollider {
+	/// The entity, if there is one. This is NOT copied back into InternalTriMeshCollider, hence why it's not "pub".
+	///
+	/// Defaults to None.
+	entity : Option<EntityHandle>,
+
+	/// The position of the collider's origin relative to the parent entity's origin (in the parent entity's local space).
+	///
+	/// Defaults to origin.
+	pub position : Vec3,
+
+	/// The points that make up the mesh.
+	///
+	/// Defaults to empty.
+	vertices : Arc<Vec<Vec3>>,
+	/// The triangles as indices into the `vertices` property. Each faces the side its corners wind counter-clockwise around.
+	///
+	/// Defaults to empty.
+	triangles : Arc<Vec<[usize; 3]>>,
+
+	/// How far behind its triangles things still count as touching (and so get pushed back out the front). Anything further behind than this is let through. Must not be negative.
+	///
+	/// Defaults to `0.1`.
+	pub thickness : f32,
+
+	/// The restituion coefficient.
+	///
+	/// Defaults to one.
+	pub restitution_coefficient : f32,
+
+	/// The ratio used to threshold whether to use static or dynamic friction for a given collision.
+	///
+	/// Defaults to `1.0`.
+	pub friction_threshold : f32,
+
+	/// The static friction coefficient. Should always at or between 0.0 and 1.0.
+	///
+	/// Defaults to `0.25`.
+	pub static_friction_coefficient : f32,
+
+	/// The dynamic friction coefficient. Should always at or between 0.0 and 1.0.
+	///
+	/// Defaults to `0.3`.
+	pub dynamic_friction_coefficient : f32,
+
+	/// How friction changes with the sliding direction. If this is None, then friction is the same in all directions.
+	///
+	/// Defaults to None.
+	pub anisotropic_friction : Option<AnisotropicFriction>,
+
+	/// Extra thickness around the surface, used for collision detection. Two colliders count as touching once their surfaces are as close as their margins added together (so things resting on each other stay that far apart). Must not be negative.
+	///
+	/// Defaults to `0.0`.
+	pub margin : f32,
+
+	/// What this is made of. Only used to look up any properties set for specific pairs of materials (see [crate::PhysicsSystem::set_material_pair_properties]).
+	///
+	/// Defaults to `0`.
+	pub material : MaterialHandle,
+
+	/// If true, this never collides with anything, but is still hit by queries like [crate::PhysicsSystem::cast_ray].
+	///
+	/// Defaults to `false`.
+	pub query_only : bool,
+
+	/// A tag that's copied into every [crate::CollisionRecord] this is a part of, but otherwise ignored. i.e. for picking which sound to play.
+	///
+	/// Defaults to `0`.
+	pub surface_tag : u32,
+}
+
+impl TriMeshCollider {
+	/// Creates an instance with all values at default.
+	///
+	/// Starts with no geometry.
+	pub fn new() -> TriMeshCollider {
+		TriMeshCollider {
+			entity: None,
+			position: Vec3::zeros(),
+			vertices: Arc::new(Vec::new()),
+			triangles: Arc::new(Vec::new()),
+			thickness: 0.1,
+			restitution_coefficient: 1.0,
+			friction_threshold: 0.25,
+			static_friction_coefficient: 1.0,
+			dynamic_friction_coefficient: 0.3,
+			anisotropic_friction: None,
+			margin: 0.0,
+			material: 0,
+			query_only: false,
+			surface_tag: 0,
+		}
+	}
+
+	/// Creates an instance from a list of vertices, and triangles as indices into them. Everything besides the geometry is at default.
+	///
+	/// Fails if any triangle uses a vertex that doesn't exist.
+	pub fn from_triangles(vertices : Vec<Vec3>, triangles : Vec<[usize; 3]>) -> Result<TriMeshCollider, ()> {
+		let mut collider = TriMeshCollider::new();
+		collider.set_geometry(vertices, triangles)?;
+		Ok(collider)
+	}
+
+	/// Creates an instance with the same geometry and surface properties as a [MeshCollider]. Each face is split into a fan of triangles (all sharing the face's first corner).
+	pub fn from_mesh(mesh : &MeshCollider) -> TriMeshCollider {
+		let mut triangles = Vec::new();
+		for face in mesh.get_faces() {
+			for corner in 1..face.len().saturating_sub(1) {
+				triangles.push([face[0], face[corner], face[corner + 1]]);
+			}
+		}
+		TriMeshCollider {
+			position: mesh.position,
+			vertices: Arc::new(mesh.get_vertices().clone()),
+			triangles: Arc::new(triangles),
+			restitution_coefficient: mesh.restitution_coefficient,
+			friction_threshold: mesh.friction_threshold,
+			static_friction_coefficient: mesh.static_friction_coefficient,
+			dynamic_friction_coefficient: mesh.dynamic_friction_coefficient,
+			anisotropic_friction: mesh.anisotropic_friction,
+			margin: mesh.margin,
+			material: mesh.material,
+			query_only: mesh.query_only,
+			surface_tag: mesh.surface_tag,
+			..TriMeshCollider::new()
+		}
+	}
+
+	/// Replaces all of the geometry (leaving everything else as-is).
+	///
+	/// Fails (without changing anything) if any triangle uses a vertex that doesn't exist.
+	pub fn set_geometry(&mut self, vertices : Vec<Vec3>, triangles : Vec<[usize; 3]>) -> Result<(), ()> {
+		if triangles.iter().any(|triangle| triangle.iter().any(|index| vertices.len() <= *index)) {
+			return Err(()); // TODO: An error type.
+		}
+		self.vertices = Arc::new(vertices);
+		self.triangles = Arc::new(triangles);
+		Ok(())
+	}
+
+	/// Reverses the winding of every triangle, so they all face the other way. i.e. to turn a closed mesh into a hollow room that things can be inside of.
+	pub fn flip(&mut self) {
+		for triangle in Arc::make_mut(&mut self.triangles).iter_mut() {
+			triangle.swap(1, 2);
+		}
+	}
+
+	/// Rotates every vertex about the collider's position.
+	pub(crate) fn rotate_vertices(&mut self, rotation : &Quat) {
+		self.vertices = Arc::new(self.vertices.iter().map(|vertex| rotation * vertex).collect());
+	}
+
+	/// The number of triangles currently stored in this instance.
+	pub fn triangle_count(&self) -> usize { self.triangles.len() }
+	/// The number of vertices currently stored in this instance.
+	pub fn vertex_count(&self) -> usize { self.vertices.len() }
+
+	/// The vertices currently stored in this instance.
+	pub fn get_vertices(&self) -> &Vec<Vec3> { &self.vertices }
+	/// The triangles currently stored in this instance, as indices into [TriMeshCollider::get_vertices].
+	pub fn get_triangles(&self) -> &Vec<[usize; 3]> { &self.triangles }
+
+	/// Gets the corners of a single triangle. Returns None if there's no triangle at that index.
+	pub fn get_triangle(&self, index : usize) -> Option<[Vec3; 3]> {
+		self.triangles.get(index).map(|triangle| triangle.map(|corner| self.vertices[corner]))
+	}
+
+	/// If this is in a valid state.
+	pub fn is_valid(&self) -> bool {
+		!self.triangles.is_empty() && self.triangles.iter().all(|triangle| triangle.iter().all(|index| *index < self.vertices.len())) && self.anisotropic_friction.iter().all(|friction| friction.is_valid()) && 0.0 <= self.margin && 0.0 <= self.thickness
+	}
+}
+
+impl Collider for TriMeshCollider {
+	fn get_type(&self) -> ColliderType { ColliderType::TRI_MESH }
+
+	fn get_entity(&self) -> Option<EntityHandle> { self.entity }
+
+	fn get_center_of_mass(&self) -> Vec3 { self.position }
+}
+
+/// The point on a triangle (including its inside) that's closest to the given point.
+pub fn closest_point_on_triangle(point : &Vec3, first : &Vec3, second : &Vec3, third : &Vec3) -> Vec3 {
+	// Work out which of the triangle's corners, edges, or inside the point is closest to, by where it is relative to each corner.
+	let edge1 = second - first;
+	let edge2 = third - first;
+	let offset1 = point - first;
+	let (dot1, dot2) = (edge1.dot(&offset1), edge2.dot(&offset1));
+	if dot1 <= 0.0 && dot2 <= 0.0 { return *first; }
+	let offset2 = point - second;
+	let (dot3, dot4) = (edge1.dot(&offset2), edge2.dot(&offset2));
+	if 0.0 <= dot3 && dot4 <= dot3 { return *second; }
+	let area3 = dot1 * dot4 - dot3 * dot2;
+	if area3 <= 0.0 && 0.0 <= dot1 && dot3 <= 0.0 {
+		return first + edge1 * (dot1 / (dot1 - dot3));
+	}
+	let offset3 = point - third;
+	let (dot5, dot6) = (edge1.dot(&offset3), edge2.dot(&offset3));
+	if 0.0 <= dot6 && dot5 <= dot6 { return *third; }
+	let area2 = dot5 * dot2 - dot1 * dot6;
+	if area2 <= 0.0 && 0.0 <= dot2 && dot6 <= 0.0 {
+		return first + edge2 * (dot2 / (dot2 - dot6));
+	}
+	let area1 = dot3 * dot6 - dot5 * dot4;
+	if area1 <= 0.0 && 0.0 <= dot4 - dot3 && 0.0 <= dot5 - dot6 {
+		return second + (third - second) * ((dot4 - dot3) / ((dot4 - dot3) + (dot5 - dot6)));
+	}
+	let total = area1 + area2 + area3;
+	first + edge1 * (area2 / total) + edge2 * (area3 / total)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Verify triangle meshes can be built (including from a mesh collider), and that the lookup finds neighbors and the closest point.
+	#[test]
+	fn check_create_tri_mesh() {
+		let mut collider = TriMeshCollider::new();
+		assert!(!collider.is_valid());
+		assert!(collider.set_geometry(vec![Vec3::zeros()], vec![[0, 1, 2]]).is_err());
+		assert!(!collider.is_valid());
+
+		// A 2x2 square floor (facing up) made of two triangles.
+		let floor = TriMeshCollider::from_triangles(
+			vec![Vec3::new(-1.0, 0.0, -1.0), Vec3::new(-1.0, 0.0, 1.0), Vec3::new(1.0, 0.0, 1.0), Vec3::new(1.0, 0.0, -1.0)],
+			vec![[0, 1, 2], [0, 2, 3]],
+		).unwrap();
+		assert!(floor.is_valid());
+		assert_eq!((floor.vertex_count(), floor.triangle_count()), (4, 2));
+		assert_eq!(floor.get_triangle(1), Some([Vec3::new(-1.0, 0.0, -1.0), Vec3::new(1.0, 0.0, 1.0), Vec3::new(1.0, 0.0, -1.0)]));
+		let internal = InternalTriMeshCollider::new_from(&floor).unwrap();
+		let internal = internal.downcast_ref::<InternalTriMeshCollider>().unwrap();
+		assert!((internal.lookup.normals[0] - Vec3::y()).magnitude() < 0.0001);
+		assert_eq!(internal.lookup.neighbors, vec![[None, None, Some(1)], [Some(0), None, None]]);
+		let (triangle, closest) = internal.closest_point(&Vec3::new(0.5, 2.0, -0.8)).unwrap();
+		assert_eq!(triangle, 1);
+		assert!((closest - Vec3::new(0.5, 0.0, -0.8)).magnitude() < 0.0001);
+		let (_, closest) = internal.closest_point(&Vec3::new(3.0, -1.0, 0.0)).unwrap();
+		assert!((closest - Vec3::new(1.0, 0.0, 0.0)).magnitude() < 0.0001);
+
+		// Flipping one of the triangles means it no longer lines up with its neighbor.
+		let mut flipped = floor.clone();
+		Arc::make_mut(&mut flipped.triangles)[1].swap(1, 2);
+		assert_eq!(TriMeshLookup::new(&flipped.vertices, &flipped.triangles).neighbors, vec![[None; 3]; 2]);
+
+		// Meshes are split up into fans of triangles.
+		let mut cuboid = MeshCollider::new_cuboid(Vec3::new(1.0, 2.0, 3.0)).unwrap();
+		cuboid.restitution_coefficient = 0.5;
+		let mut from_mesh = TriMeshCollider::from_mesh(&cuboid);
+		assert_eq!((from_mesh.vertex_count(), from_mesh.triangle_count()), (8, 12));
+		assert_eq!(from_mesh.restitution_coefficient, 0.5);
+		let lookup = TriMeshLookup::new(&from_mesh.vertices, &from_mesh.triangles);
+		assert!(lookup.neighbors.iter().all(|neighbors| neighbors.iter().all(|neighbor| neighbor.is_some())));
+		for (triangle, normal) in from_mesh.triangles.iter().zip(lookup.normals.iter()) {
+			assert!(0.0 < normal.dot(&from_mesh.vertices[triangle[0]]));
+		}
+		// Flipping it turns it inside out.
+		from_mesh.flip();
+		for (triangle, normal) in from_mesh.triangles.iter().zip(TriMeshLookup::new(&from_mesh.vertices, &from_mesh.triangles).normals.iter()) {
+			assert!(normal.dot(&from_mesh.vertices[triangle[0]]) < 0.0);
+		}
+	}
+}
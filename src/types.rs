@@ -1,6 +1,7 @@
-use nalgebra::{Vector3, Matrix3, UnitQuaternion, Isometry3};
+use nalgebra::{Vector3, Matrix3, Quaternion, UnitQuaternion, Isometry3};
 
 pub type Mat3 = Matrix3<f32>;
+/// Constructors and the methods that move or push things (i.e. [crate::PhysicsSystem::teleport_entity]) take `impl Into<Vec3>`, so a plain `[f32; 3]` works for them too. Queries (i.e. [crate::PhysicsSystem::cast_ray]), the low-level collision functions, and [crate::PhysicsWorld] (whose methods can't be generic) still take `&Vec3`.
 pub type Vec3 = Vector3<f32>;
 pub type Quat = UnitQuaternion<f32>;
 pub type Isometry = Isometry3<f32>;
@@ -16,6 +17,47 @@ pub type ColliderHandle = Index;
 /// A way to reference a [crate::UnaryForceGenerator] stored in [crate::PhysicsSystem] without actually having a ref to it.
 pub type UnaryForceGeneratorHandle = Index;
 
+/// A way to reference a [crate::Cloth] stored in [crate::PhysicsSystem] without actually having a ref to it.
+pub type ClothHandle = Index;
+
+/// A way to reference a [crate::ParticleSet] stored in [crate::PhysicsSystem] without actually having a ref to it.
+pub type ParticleSetHandle = Index;
+
+/// A way to reference a [crate::FluidRegion] stored in [crate::PhysicsSystem] without actually having a ref to it.
+pub type FluidRegionHandle = Index;
+
+/// A way to reference a [crate::BallJoint] stored in [crate::XpbdWorld] without actually having a ref to it.
+pub type JointHandle = Index;
+
+/// A way to reference mesh geometry stored in a [crate::ShapeLibrary] without actually having a ref to it.
+pub type ShapeHandle = Index;
+
+/// Names what a collider is made of (see [crate::SphereCollider::material] and the like).
+///
+/// Unlike the other handles, these are picked by the user. The system only uses them to look up properties set for specific pairs of materials.
+pub type MaterialHandle = u32;
+
+/// Packs a handle (of any kind besides [MaterialHandle]) into a single number, i.e. for storing references in save files and network messages. Use [handle_from_id] to turn it back into a handle.
+///
+/// The handle's slot goes in the top 32 bits and its generation in the bottom 32 bits. So the id only round trips while both fit in 32 bits (which is far more than any system will ever get to).
+pub fn handle_to_id(handle : Index) -> u64 {
+	let (slot, generation) = handle.into_raw_parts();
+	((slot as u64) << 32) | (generation & 0xFFFF_FFFF)
+}
+
+/// Turns an id from [handle_to_id] back into a handle. Check the handle still refers to something (i.e. with [crate::PhysicsSystem::is_valid_entity]) before relying on it.
+pub fn handle_from_id(id : u64) -> Index {
+	Index::from_raw_parts((id >> 32) as usize, id & 0xFFFF_FFFF)
+}
+
+/// Turns a `mint` quaternion into a [Quat], normalizing it along the way. Only available with the `mint` feature.
+///
+/// Everything else converts with plain `From`/`Into` (i.e. `entity.position = mint_vector.into()`, or `let rotation : mint::Quaternion<f32> = quat.into()`). This is the one direction nalgebra doesn't provide, since not every quaternion is a valid rotation.
+#[cfg(feature = "mint")]
+pub fn quat_from_mint(quat : mint::Quaternion<f32>) -> Quat {
+	Quat::from_quaternion(quat.into())
+}
+
 /// Gets the minimum of two float values.
 pub fn min(val1 : f32, val2: f32) -> f32 {
 	if val1 < val2 { val1 } else { val2 }
@@ -25,3 +67,58 @@ pub fn min(val1 : f32, val2: f32) -> f32 {
 pub fn max(val1 : f32, val2: f32) -> f32 {
 	if val1 > val2 { val1 } else { val2 }
 }
+
+/// Turns a plain `[x, y, z, w]` quaternion into a [Quat], normalizing it along the way.
+///
+/// Vectors don't need a helper like this: a [Vec3] converts to and from a plain `[f32; 3]` with `From`/`Into`.
+pub fn quat_from_array(quat : [f32; 4]) -> Quat {
+	Quat::from_quaternion(Quaternion::new(quat[3], quat[0], quat[1], quat[2]))
+}
+
+/// Turns a [Quat] into a plain `[x, y, z, w]` quaternion.
+pub fn quat_to_array(quat : &Quat) -> [f32; 4] {
+	let coords = quat.coords;
+	[coords.x, coords.y, coords.z, coords.w]
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::consts::EPSILON;
+
+	/// Verify vectors and rotations round trip through plain arrays.
+	#[test]
+	fn array_round_trip() {
+		let position : [f32; 3] = Vec3::new(1.0, 2.0, 3.0).into();
+		assert_eq!(position, [1.0, 2.0, 3.0]);
+		assert_eq!(Vec3::from(position), Vec3::new(1.0, 2.0, 3.0));
+		assert_eq!(crate::Orientation::new([1.0, 2.0, 3.0], [0.0; 3], Vec3::zeros()).position, Vec3::new(1.0, 2.0, 3.0));
+
+		let rotation = Quat::from_scaled_axis(Vec3::new(0.0, 1.0, 0.0));
+		assert!(rotation.angle_to(&quat_from_array(quat_to_array(&rotation))) < EPSILON);
+		// Not normalized on the way in.
+		assert!((quat_from_array([0.0, 0.0, 2.0, 0.0]).angle() - std::f32::consts::PI).abs() < EPSILON);
+	}
+
+	/// Verify vectors and rotations round trip through `mint` types.
+	#[cfg(feature = "mint")]
+	#[test]
+	fn mint_round_trip() {
+		let position : mint::Vector3<f32> = Vec3::new(1.0, 2.0, 3.0).into();
+		assert_eq!((position.x, position.y, position.z), (1.0, 2.0, 3.0));
+		assert_eq!(Vec3::from(position), Vec3::new(1.0, 2.0, 3.0));
+
+		let rotation = Quat::from_scaled_axis(Vec3::new(0.0, 1.0, 0.0));
+		let converted : mint::Quaternion<f32> = rotation.into();
+		assert!(rotation.angle_to(&quat_from_mint(converted)) < EPSILON);
+		// Not normalized on the way in.
+		let scaled = mint::Quaternion { v: mint::Vector3 { x: 0.0, y: 0.0, z: 2.0 }, s: 0.0 };
+		assert!((quat_from_mint(scaled).angle() - std::f32::consts::PI).abs() < EPSILON);
+
+		// Anything that takes `impl Into<Vec3>` takes them directly.
+		let force = crate::Force::new(position, mint::Vector3 { x: 0.0, y: 0.0, z: 0.0 });
+		assert_eq!(force.force, Vec3::new(1.0, 2.0, 3.0));
+		let collider = crate::AlignedBoxCollider::from_center_half_extents(position, mint::Vector3 { x: 1.0, y: 1.0, z: 1.0 });
+		assert_eq!(collider.center(), Vec3::new(1.0, 2.0, 3.0));
+	}
+}
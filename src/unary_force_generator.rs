@@ -1,5 +1,6 @@
-use crate::physics_system::PhysicsSystem;
-use crate::types::EntityHandle;
+use crate::world_query::WorldQuery;
+use crate::entity_view::EntityView;
+use crate::types::Vec3;
 use crate::force::Force;
 
 use core::fmt::Debug;
@@ -7,9 +8,34 @@ use downcast_rs::{Downcast, impl_downcast};
 
 /// A way to send forces into the system that are applied to each object separately (i.e. rather than applying them to pairs of colliding pairs or anything else).
 /// This mainly intended to implement gravity, thought it could apply other things too (i.e. springs).
-pub trait UnaryForceGenerator : Downcast + Debug {
+pub trait UnaryForceGenerator : Downcast + Debug + Send + Sync {
 	/// The function to decide force based on the given Entity.
-	fn make_force(&mut self, dt : f32, physics : &PhysicsSystem, entity : EntityHandle) -> Force;
+	///
+	/// The entity is passed in as a snapshot of where it is right now (which can be partway through a step), and the rest of the world can only be looked at. So this can't change anything but the generator itself.
+	fn make_force(&mut self, dt : f32, world : &dyn WorldQuery, entity : &EntityView) -> Force;
+
+	/// Makes a boxed copy, for when the whole system is cloned.
+	///
+	/// Generators that can't be copied should return `None` (which is the default). They're then skipped: the copied system keeps a placeholder under the same handle that never applies any force.
+	fn clone_boxed(&self) -> Option<Box<dyn UnaryForceGenerator>> { None }
 }
 
 impl_downcast!(UnaryForceGenerator);
+
+impl Clone for Box<dyn UnaryForceGenerator> {
+	fn clone(&self) -> Self {
+		self.clone_boxed().unwrap_or_else(|| Box::new(SkippedGenerator))
+	}
+}
+
+/// Stands in for a generator that couldn't be copied when a system was cloned. Never applies any force.
+#[derive(Debug, Clone)]
+pub(crate) struct SkippedGenerator;
+
+impl UnaryForceGenerator for SkippedGenerator {
+	fn make_force(&mut self, _dt : f32, _world : &dyn WorldQuery, _entity : &EntityView) -> Force {
+		Force::new(Vec3::zeros(), Vec3::zeros())
+	}
+
+	fn clone_boxed(&self) -> Option<Box<dyn UnaryForceGenerator>> { Some(Box::new(self.clone())) }
+}
@@ -0,0 +1,275 @@
+use crate::consts::EPSILON;
+use crate::types::{Vec3, Mat3, Quat, EntityHandle};
+use crate::physics_world::PhysicsWorld;
+use crate::query::RayHit;
+
+/// A single wheel on a [Vehicle].
+///
+/// There is no wheel entity. Instead the wheel is a ray cast along the suspension, and pushes on the chassis (and whatever it's touching) with spring/damper and tire forces.
+#[derive(Debug, Clone)]
+pub struct Wheel {
+	/// Where the top of the suspension is attached to the chassis. This is in the chassis entity's local space.
+	///
+	/// Has no default.
+	pub mount_point : Vec3,
+
+	/// The direction that the suspension extends in. This is in the chassis entity's local space. Doesn't need to be normalized.
+	///
+	/// Defaults to -Y.
+	pub down : Vec3,
+
+	/// The direction that the wheel rolls in when it isn't being steered. This is in the chassis entity's local space. Doesn't need to be normalized.
+	///
+	/// Defaults to +X.
+	pub forward : Vec3,
+
+	/// The wheel's radius.
+	///
+	/// Has no default.
+	pub radius : f32,
+
+	/// How far the suspension extends from `mount_point` when it's not being compressed. (This doesn't include the wheel's radius.)
+	///
+	/// Defaults to `0.5`.
+	pub rest_length : f32,
+
+	/// How hard the suspension pushes back per unit of compression.
+	///
+	/// Defaults to `50.0`.
+	pub spring_constant : f32,
+
+	/// How hard the suspension pushes back per unit of compression speed.
+	///
+	/// Defaults to `5.0`.
+	pub damping : f32,
+
+	/// How far the wheel is turned (in radians) about the suspension's "up" direction.
+	///
+	/// Defaults to zero.
+	pub steering_angle : f32,
+
+	/// The torque driving the wheel forward (or backwards if negative).
+	///
+	/// Defaults to zero.
+	pub drive_torque : f32,
+
+	/// The torque the brake resists rolling with. Must not be negative.
+	///
+	/// Defaults to zero.
+	pub brake_torque : f32,
+
+	/// The friction coefficient between the tire and the ground. Limits how hard the tire can push along the ground (relative to how hard the suspension is pushing).
+	///
+	/// Defaults to `1.0`.
+	pub grip : f32,
+
+	/// How much the suspension was compressed during the last [Vehicle::step]. This is very much read-only.
+	///
+	/// Defaults to zero.
+	compression : f32,
+
+	/// What the wheel was touching during the last [Vehicle::step]. This is very much read-only.
+	///
+	/// Defaults to None.
+	contact : Option<RayHit>,
+}
+
+impl Wheel {
+	/// Creates a wheel with all values at default.
+	pub fn new(mount_point : impl Into<Vec3>, radius : f32) -> Wheel {
+		Wheel {
+			mount_point: mount_point.into(),
+			down: Vec3::new(0.0, -1.0, 0.0),
+			forward: Vec3::new(1.0, 0.0, 0.0),
+			radius,
+			rest_length: 0.5,
+			spring_constant: 50.0,
+			damping: 5.0,
+			steering_angle: 0.0,
+			drive_torque: 0.0,
+			brake_torque: 0.0,
+			grip: 1.0,
+			compression: 0.0,
+			contact: None,
+		}
+	}
+
+	/// If this is in a valid state.
+	pub fn is_valid(&self) -> bool {
+		EPSILON < self.radius && 0.0 <= self.rest_length && EPSILON < self.down.magnitude() && EPSILON < self.forward.magnitude() && 0.0 <= self.brake_torque
+	}
+
+	/// Gets how much the suspension was compressed during the last [Vehicle::step].
+	pub fn get_compression(&self) -> f32 { self.compression }
+
+	/// Gets what the wheel was touching during the last [Vehicle::step] (if anything).
+	pub fn get_contact(&self) -> Option<&RayHit> { self.contact.as_ref() }
+}
+
+/// A set of wheels attached to a chassis entity.
+///
+/// Call [Vehicle::step] right before every [PhysicsSystem::step](crate::PhysicsSystem::step) (with the same `dt`).
+#[derive(Debug, Clone)]
+pub struct Vehicle {
+	/// The entity that all the wheels are attached to.
+	pub chassis : EntityHandle,
+
+	/// All of the wheels.
+	///
+	/// Defaults to an empty list.
+	pub wheels : Vec<Wheel>,
+}
+
+impl Vehicle {
+	/// Creates a vehicle without any wheels.
+	pub fn new(chassis : EntityHandle) -> Vehicle {
+		Vehicle {
+			chassis,
+			wheels: Vec::new(),
+		}
+	}
+
+	/// Adds a wheel, returning its index into `wheels`.
+	pub fn add_wheel(&mut self, wheel : Wheel) -> Result<usize, ()> {
+		if !wheel.is_valid() {
+			return Err(()); // TODO: An error type.
+		}
+		self.wheels.push(wheel);
+		Ok(self.wheels.len() - 1)
+	}
+
+	/// Applies all the wheel forces (as impulses over `dt`) to the chassis and whatever the wheels are touching.
+	pub fn step(&mut self, system : &mut dyn PhysicsWorld, dt : f32) -> Result<(), ()> {
+		let chassis = system.get_entity(self.chassis).ok_or(())?; // TODO: An error type.
+		let orientation = *chassis.get_last_orientation();
+		let inverse_mass = 1.0 / chassis.get_last_total_mass();
+		let inverse_moment = chassis.get_last_moment_of_inertia().try_inverse().unwrap_or_else(Mat3::zeros);
+		// How much of an impulse it takes to change the chassis's speed at a point along a direction.
+		let effective_mass = |position : &Vec3, direction : &Vec3| {
+			let offset = position - chassis.position;
+			let angular = inverse_moment * offset.cross(direction);
+			1.0 / (inverse_mass + angular.cross(&offset).dot(direction))
+		};
+
+		// First find what all the wheels are touching.
+		let mut touching_count = 0;
+		for wheel in &mut self.wheels {
+			let mount_point = orientation.position_into_world(&wheel.mount_point);
+			let down = orientation.direction_into_world(&wheel.down);
+			wheel.contact = system.cast_ray(&mount_point, &down, wheel.rest_length + wheel.radius, Some(self.chassis));
+			wheel.compression = wheel.contact.as_ref().map_or(0.0, |hit| wheel.rest_length + wheel.radius - hit.distance);
+			if wheel.contact.is_some() { touching_count += 1; }
+		}
+
+		// Then push on things.
+		for wheel in &self.wheels {
+			let hit = if let Some(hit) = &wheel.contact { hit } else { continue; };
+			let ground = system.get_entity(hit.entity).unwrap();
+			let down = orientation.direction_into_world(&wheel.down).normalize();
+			let velocity = chassis.get_velocity_at_world_position(&hit.position) - ground.get_velocity_at_world_position(&hit.position);
+
+			// The suspension only ever pushes. It pushes straight off of the ground, as the wheel is free to roll along it.
+			let suspension_force = (wheel.spring_constant * wheel.compression + wheel.damping * velocity.dot(&down)).max(0.0);
+			let mut impulse = hit.normal * suspension_force * dt;
+
+			// Then the tire pushes along the ground.
+			let steered = Quat::from_scaled_axis(wheel.down.normalize() * -wheel.steering_angle) * wheel.forward;
+			let mut forward = orientation.direction_into_world(&steered);
+			forward -= hit.normal * forward.dot(&hit.normal);
+			if EPSILON < forward.magnitude() {
+				forward = forward.normalize();
+				let side = hit.normal.cross(&forward).normalize();
+				let forward_speed = velocity.dot(&forward);
+				let side_speed = velocity.dot(&side);
+				let mut tire_impulse = forward * (wheel.drive_torque / wheel.radius * dt);
+				// Braking resists rolling, but can never reverse it.
+				let stopping_impulse = effective_mass(&hit.position, &forward) * forward_speed.abs() / touching_count as f32;
+				tire_impulse -= forward * forward_speed.signum() * (wheel.brake_torque / wheel.radius * dt).min(stopping_impulse);
+				// The tire doesn't roll sideways, so try to stop all sideways sliding. Split that between all the wheels on the ground.
+				tire_impulse -= side * effective_mass(&hit.position, &side) * side_speed / touching_count as f32;
+				// Then limit it to what the tire's grip allows.
+				let max_tire_impulse = wheel.grip * suspension_force * dt;
+				if max_tire_impulse < tire_impulse.magnitude() {
+					tire_impulse = tire_impulse.normalize() * max_tire_impulse;
+				}
+				impulse += tire_impulse;
+			}
+
+			system.apply_impulse(self.chassis, &hit.position, &impulse)?;
+			system.apply_impulse(hit.entity, &hit.position, &-impulse)?;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::physics_system::PhysicsSystem;
+	use std::f32::INFINITY;
+	use crate::entity::Entity;
+	use crate::null_collider::NullCollider;
+	use crate::plane_collider::PlaneCollider;
+	use crate::collider_wrapper::ColliderWrapper;
+	use crate::gravity_generator::GravityGenerator;
+
+	/// Makes a box-car sitting above a flat floor.
+	fn make_car() -> (PhysicsSystem, Vehicle) {
+		let mut system = PhysicsSystem::new();
+		{
+			let floor = system.add_entity(Entity::new()).unwrap();
+			let mut plane = PlaneCollider::new();
+			plane.mass = INFINITY;
+			let plane_handle = system.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+			system.link_collider(plane_handle, Some(floor)).unwrap();
+		}
+		let chassis = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 1.0, 0.0);
+			let handle = system.add_entity(entity).unwrap();
+			let mut body = NullCollider::new();
+			body.mass = 4.0;
+			body.moment_of_inertia = Mat3::from_diagonal(&Vec3::new(2.0, 4.0, 2.0));
+			let body_handle = system.add_collider(ColliderWrapper::Null(body)).unwrap();
+			system.link_collider(body_handle, Some(handle)).unwrap();
+			handle
+		};
+		system.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -10.0, 0.0)))).unwrap();
+		let mut vehicle = Vehicle::new(chassis);
+		for x in [-1.0, 1.0].iter() {
+			for z in [-0.5, 0.5].iter() {
+				vehicle.add_wheel(Wheel::new(Vec3::new(*x, 0.0, *z), 0.3)).unwrap();
+			}
+		}
+		(system, vehicle)
+	}
+
+	/// Check that the car settles onto its suspension, and then drives forward.
+	#[test]
+	fn drive() {
+		let (mut system, mut vehicle) = make_car();
+		for _ in 0..300 {
+			vehicle.step(&mut system, 0.01).unwrap();
+			system.step(0.01);
+		}
+		{ // Each spring should hold up a quarter of the weight.
+			let chassis = system.get_entity(vehicle.chassis).unwrap();
+			assert!(chassis.velocity.magnitude() < 0.01);
+			for wheel in &vehicle.wheels {
+				assert!((wheel.get_compression() - 0.2).abs() < 0.01);
+			}
+			assert!((chassis.position.y - 0.6).abs() < 0.01);
+		}
+		for wheel in &mut vehicle.wheels {
+			wheel.drive_torque = 0.3;
+		}
+		for _ in 0..100 {
+			vehicle.step(&mut system, 0.01).unwrap();
+			system.step(0.01);
+		}
+		let chassis = system.get_entity(vehicle.chassis).unwrap();
+		// Four wheels at 1.0 force each should accelerate the car at 1.0 for a second.
+		assert!((chassis.velocity.x - 1.0).abs() < 0.05);
+		assert!(chassis.velocity.z.abs() < 0.01);
+	}
+}
@@ -0,0 +1,15 @@
+/// Whether changing an entity (with [crate::PhysicsSystem::update_entity_with]) wakes it up.
+///
+/// Waking an entity also wakes everything resting on (or under) it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WakePolicy {
+	/// Only wake the entity if its mass, position, rotation, or velocities changed by more than a tiny amount. This is what [crate::PhysicsSystem::update_entity] does.
+	IfChanged,
+	/// Always wake the entity, even if nothing changed.
+	Always,
+	/// Never wake the entity. If it was asleep, it stays asleep (and so stays put) wherever it was moved to.
+	///
+	/// Anything it was resting on (or that was resting on it) is still treated as touching it, so only use this for small adjustments.
+	Never,
+}
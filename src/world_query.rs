@@ -0,0 +1,42 @@
+use crate::types::{Vec3, EntityHandle, ColliderHandle};
+use crate::entity::Entity;
+use crate::collider_wrapper::ColliderWrapper;
+use crate::query::RayHit;
+use crate::physics_world::PhysicsWorld;
+
+/// The read-only parts of a [PhysicsWorld]: looking things up and casting rays. This is all that force generators get (see [crate::UnaryForceGenerator::make_force]), so they can't change the world while it's being stepped.
+///
+/// Every [PhysicsWorld] is one of these.
+pub trait WorldQuery {
+	/// Gets a copy of an entity's public interface.
+	fn get_entity(&self, handle : EntityHandle) -> Option<Entity>;
+
+	/// Gets a copy of a collider's public interface.
+	fn get_collider(&self, handle : ColliderHandle) -> Option<ColliderWrapper>;
+
+	/// Finds the first collider that a ray hits.
+	fn cast_ray(&self, origin : &Vec3, direction : &Vec3, max_distance : f32, ignore : Option<EntityHandle>) -> Option<RayHit>;
+
+	/// Finds the first collider that a moving sphere hits.
+	fn cast_sphere(&self, origin : &Vec3, direction : &Vec3, max_distance : f32, radius : f32, ignore : Option<EntityHandle>) -> Option<RayHit>;
+
+	/// Finds the first collider that a ray hits, skipping entities whose query groups share no bits with `mask`.
+	fn cast_ray_masked(&self, origin : &Vec3, direction : &Vec3, max_distance : f32, ignore : Option<EntityHandle>, mask : u32) -> Option<RayHit>;
+
+	/// Finds the first collider that a moving sphere hits, skipping entities whose query groups share no bits with `mask`.
+	fn cast_sphere_masked(&self, origin : &Vec3, direction : &Vec3, max_distance : f32, radius : f32, ignore : Option<EntityHandle>, mask : u32) -> Option<RayHit>;
+}
+
+impl<T : PhysicsWorld + ?Sized> WorldQuery for T {
+	fn get_entity(&self, handle : EntityHandle) -> Option<Entity> { PhysicsWorld::get_entity(self, handle) }
+
+	fn get_collider(&self, handle : ColliderHandle) -> Option<ColliderWrapper> { PhysicsWorld::get_collider(self, handle) }
+
+	fn cast_ray(&self, origin : &Vec3, direction : &Vec3, max_distance : f32, ignore : Option<EntityHandle>) -> Option<RayHit> { PhysicsWorld::cast_ray(self, origin, direction, max_distance, ignore) }
+
+	fn cast_sphere(&self, origin : &Vec3, direction : &Vec3, max_distance : f32, radius : f32, ignore : Option<EntityHandle>) -> Option<RayHit> { PhysicsWorld::cast_sphere(self, origin, direction, max_distance, radius, ignore) }
+
+	fn cast_ray_masked(&self, origin : &Vec3, direction : &Vec3, max_distance : f32, ignore : Option<EntityHandle>, mask : u32) -> Option<RayHit> { PhysicsWorld::cast_ray_masked(self, origin, direction, max_distance, ignore, mask) }
+
+	fn cast_sphere_masked(&self, origin : &Vec3, direction : &Vec3, max_distance : f32, radius : f32, ignore : Option<EntityHandle>, mask : u32) -> Option<RayHit> { PhysicsWorld::cast_sphere_masked(self, origin, direction, max_distance, radius, ignore, mask) }
+}
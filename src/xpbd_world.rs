@@ -0,0 +1,1098 @@
+use std::collections::{HashSet, HashMap};
+
+use generational_arena::Arena;
+use nalgebra::Point3;
+
+use crate::consts::EPSILON;
+use crate::types::{Vec3, Mat3, Isometry, EntityHandle, ColliderHandle, UnaryForceGeneratorHandle, JointHandle};
+use crate::entity::Entity;
+use crate::entity_view::EntityView;
+use crate::body_type::BodyType;
+use crate::orientation::Orientation;
+use crate::collider::{ColliderType, InternalCollider};
+use crate::sphere_collider::InternalSphereCollider;
+use crate::plane_collider::InternalPlaneCollider;
+use crate::mesh_collider::InternalMeshCollider;
+use crate::aligned_box_collider::InternalAlignedBoxCollider;
+use crate::tri_mesh_collider::InternalTriMeshCollider;
+use crate::collider_wrapper::ColliderWrapper;
+use crate::unary_force_generator::UnaryForceGenerator;
+use crate::query::RayHit;
+use crate::ball_joint::BallJoint;
+use crate::break_event::{BreakEvent, Broken};
+use crate::physics_system::PhysicsSystem;
+use crate::physics_world::PhysicsWorld;
+
+/// An experimental backend using XPBD (extended position-based dynamics).
+///
+/// Each step is split into `substeps` substeps. Each substep moves everything, then pushes apart whatever overlaps (and holds joints together) by directly changing positions, then works out the velocities from how far everything moved.
+/// This handles tall stacks and chains of joints far better than [crate::ImpulseWorld], at the cost of letting things overlap very slightly while resting.
+///
+/// Some things aren't supported (yet): sleeping, contact modifiers, anisotropic friction, and collision records. Collision detection is discrete, so very fast/thin things can pass through each other.
+/// Mesh colliders are treated as if they're convex.
+///
+/// This is one of the backends [crate::Backend] picks between. It can only be used through [PhysicsWorld] (plus the joint methods here), so everything else [crate::PhysicsSystem] has (cloth, fluids, contact graphs, snapshots, etc.) needs an [crate::ImpulseWorld].
+pub struct XpbdWorld {
+	/// Holds all of the entities, colliders, and force generators. This is never stepped, it's just so the management and query code is shared with [crate::ImpulseWorld].
+	storage : PhysicsSystem,
+	/// All of the joints.
+	joints : Arena<BallJoint>,
+	/// Every joint that broke since the last [XpbdWorld::take_break_events] call.
+	break_events : Vec<BreakEvent>,
+
+	/// How many substeps each step is split into. More means stiffer contacts and joints.
+	///
+	/// Tall stacks need more than the default: the contacts at the bottom are too soft to hold more than a dozen or so boxes still. `60` keeps a stack of 30 boxes standing still.
+	///
+	/// Defaults to `20`.
+	pub substeps : u8,
+
+	/// How many times the contacts are solved each substep. More means less jitter in large piles, but costs more.
+	///
+	/// Defaults to `2`.
+	pub position_iterations : u8,
+
+	/// How much contacts can give per unit of force (i.e. the inverse of stiffness). Zero is perfectly rigid.
+	///
+	/// Defaults to zero.
+	pub contact_compliance : f32,
+}
+
+/// The state of an entity while it's being stepped.
+#[derive(Debug)]
+struct Body {
+	/// Where the entity is now.
+	orientation : Orientation,
+	/// Where the entity was at the start of the current substep.
+	previous_orientation : Orientation,
+	/// How far the center of mass has moved this substep. Kept separately as taking the difference between positions loses a lot of precision far from the origin.
+	movement : Vec3,
+	/// The current linear velocity.
+	velocity : Vec3,
+	/// The current angular velocity.
+	angular_velocity : Vec3,
+	/// The linear velocity at the start of the current substep.
+	previous_velocity : Vec3,
+	/// The angular velocity at the start of the current substep.
+	previous_angular_velocity : Vec3,
+	/// The linear acceleration from all of the force generators.
+	acceleration : Vec3,
+	/// The angular acceleration from all of the force generators.
+	angular_acceleration : Vec3,
+	/// One over the total mass. Zero for things that can't be pushed.
+	inverse_mass : f32,
+	/// The inverse moment of inertia in the entity's (unrotated) frame.
+	local_inverse_moment : Mat3,
+	/// All of the entity's colliders.
+	colliders : Vec<ColliderHandle>,
+}
+
+impl Body {
+	/// Gets the inverse moment of inertia in world space.
+	fn inverse_moment(&self) -> Mat3 {
+		let rotation = self.orientation.rotation.to_rotation_matrix().into_inner();
+		rotation * self.local_inverse_moment * rotation.transpose()
+	}
+
+	/// Gets how easily pushing at `offset` (from the center of mass) along `direction` moves that point.
+	fn generalized_inverse_mass(&self, offset : &Vec3, direction : &Vec3) -> f32 {
+		let arm = offset.cross(direction);
+		self.inverse_mass + arm.dot(&(self.inverse_moment() * arm))
+	}
+
+	/// Moves (and rotates) this by a position-level impulse at `offset` (from the center of mass).
+	fn apply_positional_impulse(&mut self, offset : &Vec3, impulse : &Vec3) {
+		let rotation = self.inverse_moment() * offset.cross(impulse);
+		self.movement += impulse * self.inverse_mass;
+		self.orientation.affect_with(&(impulse * self.inverse_mass), &rotation);
+	}
+
+	/// Changes this's velocities by an impulse at `offset` (from the center of mass).
+	fn apply_velocity_impulse(&mut self, offset : &Vec3, impulse : &Vec3) {
+		self.velocity += impulse * self.inverse_mass;
+		self.angular_velocity += self.inverse_moment() * offset.cross(impulse);
+	}
+
+	/// Gets the velocity at `offset` (from the center of mass).
+	fn velocity_at(&self, offset : &Vec3) -> Vec3 {
+		self.velocity + self.angular_velocity.cross(offset)
+	}
+
+	/// Moves the body by its velocity (after applying acceleration) over `dt`.
+	fn integrate(&mut self, dt : f32) {
+		self.previous_orientation = self.orientation;
+		self.previous_velocity = self.velocity;
+		self.previous_angular_velocity = self.angular_velocity;
+		if 0.0 < self.inverse_mass {
+			self.velocity += self.acceleration * dt;
+			self.angular_velocity += self.angular_acceleration * dt;
+		}
+		self.movement = self.velocity * dt;
+		self.orientation.affect_with(&self.movement, &(self.angular_velocity * dt));
+	}
+
+	/// Works out the velocities from how far the body moved this substep. Things that can't be pushed keep their velocities.
+	fn update_velocities(&mut self, dt : f32) {
+		if 0.0 == self.inverse_mass { return; }
+		self.velocity = self.movement / dt;
+		self.angular_velocity = (self.orientation.rotation * self.previous_orientation.rotation.inverse()).scaled_axis() / dt;
+	}
+}
+
+/// Gets two different bodies at once.
+fn get_pair(bodies : &mut [Body], first : usize, second : usize) -> (&mut Body, &mut Body) {
+	if first < second {
+		let (lower, upper) = bodies.split_at_mut(second);
+		(&mut lower[first], &mut upper[0])
+	} else {
+		let (lower, upper) = bodies.split_at_mut(first);
+		(&mut upper[0], &mut lower[second])
+	}
+}
+
+/// Works out how much position-level impulse it takes to move the point at `first_offset` on `first` by `correction` relative to the point at `second_offset` on `second`, splitting the work between them by how easy each is to move.
+///
+/// The `scaled_compliance` is the compliance divided by the substep's duration squared. The `lambda` is how much position-level impulse the constraint has already used this substep.
+///
+/// Returns the direction of the correction and the change in the constraint's lambda (i.e. the position-level impulse needed). The change is zero if nothing can move.
+fn get_correction(first : &Body, first_offset : &Vec3, second : &Body, second_offset : &Vec3, correction : &Vec3, scaled_compliance : f32, lambda : f32) -> (Vec3, f32) {
+	// Not using EPSILON here, as substeps are short enough that slow drifting is often less than it.
+	let error = correction.magnitude();
+	if 0.0 == error { return (Vec3::zeros(), 0.0); }
+	let direction = correction / error;
+	let weight = first.generalized_inverse_mass(first_offset, &direction) + second.generalized_inverse_mass(second_offset, &direction) + scaled_compliance;
+	if weight < EPSILON { return (direction, 0.0); }
+	(direction, (error - scaled_compliance * lambda) / weight)
+}
+
+/// Applies a position-level impulse to the point at `first_offset` on `first`, and the opposite to the point at `second_offset` on `second`.
+fn apply_positional_impulses(first : &mut Body, first_offset : &Vec3, second : &mut Body, second_offset : &Vec3, impulse : &Vec3) {
+	first.apply_positional_impulse(first_offset, impulse);
+	second.apply_positional_impulse(second_offset, &-impulse);
+}
+
+/// Solves the contacts' positions: pushes everything apart, then stops things sliding where static friction can.
+///
+/// The contacts between each pair of bodies are pushed apart deepest first. Always going in the same order makes whichever contact goes first tip the body a little the same way every time, so resting things slowly walk across each other.
+fn solve_contact_positions(contacts : &mut [ContactConstraint], bodies : &mut [Body], compliance : f32, dt : f32) {
+	for group in contacts.chunk_by_mut(|a, b| a.first == b.first && a.second == b.second) {
+		for contact in group.iter_mut() {
+			contact.depth = contact.get_depth(bodies);
+		}
+		group.sort_by(|a, b| b.depth.partial_cmp(&a.depth).unwrap());
+		for contact in group.iter_mut() {
+			let push = contact.get_push(bodies, compliance, dt);
+			if 0.0 < push {
+				contact.normal_lambda += push;
+				contact.apply(bodies, &(contact.normal * push));
+			}
+		}
+	}
+	for contact in contacts.iter_mut() {
+		if let Some((direction, delta_lambda)) = contact.get_static_friction(bodies) {
+			// Static friction can only hold if it doesn't take too much force.
+			if contact.tangent_lambda + delta_lambda < contact.static_friction_coefficient * contact.normal_lambda {
+				contact.tangent_lambda += delta_lambda;
+				contact.apply(bodies, &(direction * delta_lambda));
+			}
+		}
+	}
+}
+
+/// A convex polyhedron in world space.
+#[derive(Debug)]
+struct Polyhedron {
+	/// The corners.
+	vertices : Vec<Vec3>,
+	/// Each face as the indices of its corners (in order around the face) and its outward normal.
+	faces : Vec<(Vec<usize>, Vec3)>,
+	/// Every edge as the indices of its ends.
+	edges : Vec<(usize, usize)>,
+	/// The (normalized) direction of every edge, without any repeats.
+	edge_directions : Vec<Vec3>,
+	/// The average of all the corners.
+	center : Vec3,
+}
+
+impl Polyhedron {
+	/// Creates an instance, working out the face normals and edge directions.
+	fn new(vertices : Vec<Vec3>, face_indices : &[Vec<usize>], edges : Vec<(usize, usize)>) -> Polyhedron {
+		let center = vertices.iter().fold(Vec3::zeros(), |total, vertex| total + vertex) / vertices.len().max(1) as f32;
+		let mut faces = Vec::with_capacity(face_indices.len());
+		for face in face_indices {
+			if face.len() < 3 { continue; }
+			let point = vertices[face[0]];
+			let mut normal = (vertices[face[1]] - point).cross(&(vertices[face[2]] - point));
+			if normal.magnitude() < EPSILON { continue; }
+			normal = normal.normalize();
+			if (point - center).dot(&normal) < 0.0 { normal = -normal; }
+			faces.push((face.clone(), normal));
+		}
+		let mut edge_directions : Vec<Vec3> = Vec::new();
+		for (start, end) in &edges {
+			let direction = (vertices[*end] - vertices[*start]).normalize();
+			if edge_directions.iter().all(|existing| EPSILON < existing.cross(&direction).magnitude()) {
+				edge_directions.push(direction);
+			}
+		}
+		Polyhedron { vertices, faces, edges, edge_directions, center }
+	}
+
+	/// Gets the range the polyhedron covers along an axis.
+	fn project(&self, axis : &Vec3) -> (f32, f32) {
+		self.vertices.iter().map(|vertex| vertex.dot(axis)).fold((f32::INFINITY, -f32::INFINITY), |(low, high), value| (low.min(value), high.max(value)))
+	}
+
+	/// Gets how far apart `self` and `other` are along `axis`, which should point from `self` towards `other`. Negative if they overlap.
+	fn separation(&self, other : &Polyhedron, axis : &Vec3) -> f32 {
+		other.project(axis).0 - self.project(axis).1
+	}
+
+	/// Finds the edge (as its two ends) along `direction` that's furthest along `axis`.
+	fn furthest_edge(&self, direction : &Vec3, axis : &Vec3) -> (Vec3, Vec3) {
+		let mut best = (-f32::INFINITY, Vec3::zeros(), Vec3::zeros());
+		for (start, end) in &self.edges {
+			let (start, end) = (self.vertices[*start], self.vertices[*end]);
+			if EPSILON < (end - start).normalize().cross(direction).magnitude() { continue; }
+			let distance = (start + end).dot(axis);
+			if best.0 < distance { best = (distance, start, end); }
+		}
+		(best.1, best.2)
+	}
+}
+
+/// Cuts away the part of a polygon that's in front of a plane.
+fn clip_polygon(polygon : &[Vec3], plane_point : &Vec3, plane_normal : &Vec3) -> Vec<Vec3> {
+	let mut clipped = Vec::with_capacity(polygon.len() + 1);
+	for (index, current) in polygon.iter().enumerate() {
+		let next = polygon[(index + 1) % polygon.len()];
+		let current_distance = (current - plane_point).dot(plane_normal);
+		let next_distance = (next - plane_point).dot(plane_normal);
+		if current_distance <= 0.0 { clipped.push(*current); }
+		if (current_distance < 0.0 && 0.0 < next_distance) || (0.0 < current_distance && next_distance < 0.0) {
+			clipped.push(current + (next - current) * (current_distance / (current_distance - next_distance)));
+		}
+	}
+	clipped
+}
+
+/// Finds the closest points between two line segments.
+fn closest_points_on_segments(start1 : &Vec3, end1 : &Vec3, start2 : &Vec3, end2 : &Vec3) -> (Vec3, Vec3) {
+	let direction1 = end1 - start1;
+	let direction2 = end2 - start2;
+	let offset = start1 - start2;
+	let length1 = direction1.dot(&direction1);
+	let length2 = direction2.dot(&direction2);
+	let along2 = direction2.dot(&offset);
+	let (mut time1, mut time2);
+	if length1 < EPSILON && length2 < EPSILON { return (*start1, *start2); }
+	if length1 < EPSILON {
+		time1 = 0.0;
+		time2 = (along2 / length2).max(0.0).min(1.0);
+	} else {
+		let along1 = direction1.dot(&offset);
+		if length2 < EPSILON {
+			time2 = 0.0;
+			time1 = (-along1 / length1).max(0.0).min(1.0);
+		} else {
+			let cross = direction1.dot(&direction2);
+			let denominator = length1 * length2 - cross * cross;
+			time1 = if EPSILON < denominator { ((cross * along2 - along1 * length2) / denominator).max(0.0).min(1.0) } else { 0.0 };
+			time2 = (cross * time1 + along2) / length2;
+			if time2 < 0.0 {
+				time2 = 0.0;
+				time1 = (-along1 / length1).max(0.0).min(1.0);
+			} else if 1.0 < time2 {
+				time2 = 1.0;
+				time1 = ((cross - along1) / length1).max(0.0).min(1.0);
+			}
+		}
+	}
+	(start1 + direction1 * time1, start2 + direction2 * time2)
+}
+
+/// A point where one shape presses into another, as: whether the pressing point is on the first shape, the pressing point, the point it's pressing on, and the direction to push the pressing shape.
+type ShapeContact = (bool, Vec3, Vec3, Vec3);
+
+/// Finds where two convex polyhedra overlap using the separating axis test.
+///
+/// When a face is the axis of least overlap, the other polyhedron's most opposed face is clipped against it to get (up to several) contact points. Otherwise it's two edges crossing, and the closest points on them are used.
+fn collide_polyhedra(first : &Polyhedron, second : &Polyhedron) -> Vec<ShapeContact> {
+	// Faces are preferred over edges unless an edge is clearly better (keeps resting contacts stable).
+	const EDGE_TOLERANCE : f32 = 0.001;
+	// How close together contact points can be before they're considered the same point.
+	const DUPLICATE_DISTANCE : f32 = 0.001;
+	let mut best_face : Option<(f32, bool, usize)> = None;
+	for (first_is_reference, reference, other) in [(true, first, second), (false, second, first)].iter() {
+		for (index, (_, normal)) in reference.faces.iter().enumerate() {
+			let separation = reference.separation(other, normal);
+			if 0.0 < separation { return Vec::new(); }
+			if best_face.is_none_or(|(best, _, _)| best < separation) {
+				best_face = Some((separation, *first_is_reference, index));
+			}
+		}
+	}
+	let mut best_edge : Option<(f32, Vec3, Vec3, Vec3)> = None;
+	for first_direction in &first.edge_directions {
+		for second_direction in &second.edge_directions {
+			let mut axis = first_direction.cross(second_direction);
+			if axis.magnitude() < EPSILON { continue; }
+			axis = axis.normalize();
+			if (second.center - first.center).dot(&axis) < 0.0 { axis = -axis; }
+			let separation = first.separation(second, &axis);
+			if 0.0 < separation { return Vec::new(); }
+			if best_edge.is_none_or(|(best, _, _, _)| best < separation) {
+				best_edge = Some((separation, axis, *first_direction, *second_direction));
+			}
+		}
+	}
+	let (face_separation, first_is_reference, face_index) = if let Some(found) = best_face { found } else { return Vec::new(); };
+
+	if let Some((edge_separation, axis, first_direction, second_direction)) = best_edge {
+		if face_separation + EDGE_TOLERANCE < edge_separation {
+			let (first_start, first_end) = first.furthest_edge(&first_direction, &axis);
+			let (second_start, second_end) = second.furthest_edge(&second_direction, &-axis);
+			let (on_first, on_second) = closest_points_on_segments(&first_start, &first_end, &second_start, &second_end);
+			return vec![(false, on_second, on_first, axis)];
+		}
+	}
+
+	let (reference, incident) = if first_is_reference { (first, second) } else { (second, first) };
+	let (reference_indices, normal) = &reference.faces[face_index];
+	let incident_indices = &incident.faces.iter().min_by(|a, b| a.1.dot(normal).partial_cmp(&b.1.dot(normal)).unwrap()).unwrap().0;
+	let reference_polygon : Vec<Vec3> = reference_indices.iter().map(|index| reference.vertices[*index]).collect();
+	let reference_center = reference_polygon.iter().fold(Vec3::zeros(), |total, vertex| total + vertex) / reference_polygon.len() as f32;
+	let mut polygon : Vec<Vec3> = incident_indices.iter().map(|index| incident.vertices[*index]).collect();
+	for (index, start) in reference_polygon.iter().enumerate() {
+		if polygon.is_empty() { break; }
+		let end = reference_polygon[(index + 1) % reference_polygon.len()];
+		let mut side_normal = (end - start).cross(normal);
+		if (reference_center - start).dot(&side_normal) > 0.0 { side_normal = -side_normal; }
+		polygon = clip_polygon(&polygon, start, &side_normal);
+	}
+	let mut contacts : Vec<ShapeContact> = Vec::with_capacity(polygon.len());
+	for point in &polygon {
+		let depth = (point - reference_polygon[0]).dot(normal);
+		// Clipping right along a corner can leave (nearly) repeated points. Those would get pushed on twice.
+		if depth < 0.0 && contacts.iter().all(|contact| DUPLICATE_DISTANCE < (contact.1 - point).magnitude()) {
+			contacts.push((!first_is_reference, *point, point - normal * depth, *normal));
+		}
+	}
+	contacts
+}
+
+/// A collider moved into world space (for a single substep).
+#[derive(Debug)]
+enum Shape {
+	/// Doesn't collide with anything.
+	Null,
+	/// A sphere.
+	Sphere { center : Vec3, radius : f32 },
+	/// An infinite plane. The normal is normalized.
+	Plane { position : Vec3, normal : Vec3 },
+	/// A box. The corners are in the entity's local space.
+	AlignedBox { into_local : Isometry, into_world : Isometry, min_corner : Vec3, max_corner : Vec3, polyhedron : Polyhedron },
+	/// A mesh (which is assumed to be convex).
+	Mesh { polyhedron : Polyhedron },
+	/// A (one-sided) triangle mesh. It's left in the entity's local space, as it's only ever pressed into.
+	TriMesh { into_local : Isometry, into_world : Isometry, mesh : InternalTriMeshCollider },
+}
+
+impl Shape {
+	/// Moves the collider into world space.
+	fn new(collider : &dyn InternalCollider, orientation : &Orientation) -> Shape {
+		match collider.get_type() {
+			ColliderType::NULL => Shape::Null,
+			ColliderType::SPHERE => {
+				let sphere = collider.downcast_ref::<InternalSphereCollider>().unwrap();
+				Shape::Sphere { center: orientation.position_into_world(&sphere.center), radius: sphere.radius }
+			},
+			ColliderType::PLANE => {
+				let plane = collider.downcast_ref::<InternalPlaneCollider>().unwrap();
+				Shape::Plane { position: orientation.position_into_world(&plane.position), normal: plane.normal.normalize() }
+			},
+			ColliderType::ALIGNED_BOX => {
+				let aligned_box = collider.downcast_ref::<InternalAlignedBoxCollider>().unwrap();
+				let into_world = orientation.into_world();
+				let min_corner = aligned_box.position + aligned_box.min_corner;
+				let max_corner = aligned_box.position + aligned_box.max_corner;
+				// Corner N uses the max corner's values for each axis whose bit is set in N.
+				let vertices = (0..8).map(|index : usize| into_world.transform_point(&Point3::new(
+					if 0 == index & 1 { min_corner.x } else { max_corner.x },
+					if 0 == index & 2 { min_corner.y } else { max_corner.y },
+					if 0 == index & 4 { min_corner.z } else { max_corner.z },
+				)).coords).collect();
+				let mut faces = Vec::with_capacity(6);
+				let mut edges = Vec::with_capacity(12);
+				for axis in 0..3 {
+					let (bit, first_bit, second_bit) = (1 << axis, 1 << ((axis + 1) % 3), 1 << ((axis + 2) % 3));
+					for side in [0, bit].iter() {
+						faces.push(vec![*side, side | first_bit, side | first_bit | second_bit, side | second_bit]);
+					}
+					for start in 0..8 {
+						if 0 == start & bit { edges.push((start, start | bit)); }
+					}
+				}
+				Shape::AlignedBox {
+					into_local: orientation.into_local(),
+					into_world,
+					min_corner,
+					max_corner,
+					polyhedron: Polyhedron::new(vertices, &faces, edges),
+				}
+			},
+			ColliderType::MESH => {
+				let mesh = collider.downcast_ref::<InternalMeshCollider>().unwrap();
+				Shape::Mesh { polyhedron: Polyhedron::new(mesh.vertices_in_world(orientation), &mesh.faces, mesh.edges.to_vec()) }
+			},
+			ColliderType::TRI_MESH => {
+				let mesh = collider.downcast_ref::<InternalTriMeshCollider>().unwrap();
+				Shape::TriMesh { into_local: orientation.into_local(), into_world: orientation.into_world(), mesh: mesh.clone() }
+			},
+		}
+	}
+
+	/// Gets the shape as a polyhedron (if it is one).
+	fn get_polyhedron(&self) -> Option<&Polyhedron> {
+		match self {
+			Shape::AlignedBox { polyhedron, .. } | Shape::Mesh { polyhedron } => Some(polyhedron),
+			_ => None,
+		}
+	}
+
+	/// Gets a sphere (as a center and radius) that contains the whole shape. None if the shape is infinite (or is a triangle mesh, which is usually big enough that bounding it would rule nothing out).
+	fn get_bounds(&self) -> Option<(Vec3, f32)> {
+		match self {
+			Shape::Null => Some((Vec3::zeros(), 0.0)),
+			Shape::Sphere { center, radius } => Some((*center, *radius)),
+			Shape::Plane { .. } | Shape::TriMesh { .. } => None,
+			Shape::AlignedBox { polyhedron, .. } | Shape::Mesh { polyhedron } => {
+				Some((polyhedron.center, polyhedron.vertices.iter().map(|vertex| (vertex - polyhedron.center).magnitude()).fold(0.0, f32::max)))
+			},
+		}
+	}
+
+	/// Gets the points that can press into other shapes, as world positions along with how far around them counts as touching.
+	fn get_points(&self) -> Vec<(Vec3, f32)> {
+		match self {
+			Shape::Null | Shape::Plane { .. } | Shape::TriMesh { .. } => Vec::new(),
+			Shape::Sphere { center, radius } => vec![(*center, *radius)],
+			Shape::AlignedBox { polyhedron, .. } | Shape::Mesh { polyhedron } => polyhedron.vertices.iter().map(|vertex| (*vertex, 0.0)).collect(),
+		}
+	}
+
+	/// Gets how far a point is outside of the shape (negative if inside), along with the direction to push the point to get it out. None if the shape has no surface.
+	fn get_signed_distance(&self, point : &Vec3) -> Option<(f32, Vec3)> {
+		match self {
+			Shape::Null => None,
+			Shape::Sphere { center, radius } => {
+				let offset = point - center;
+				let distance = offset.magnitude();
+				if distance < EPSILON {
+					Some((-radius, Vec3::y()))
+				} else {
+					Some((distance - radius, offset / distance))
+				}
+			},
+			Shape::Plane { position, normal } => Some(((point - position).dot(normal), *normal)),
+			Shape::AlignedBox { into_local, into_world, min_corner, max_corner, .. } => {
+				let local = into_local.transform_point(&Point3::from(*point)).coords;
+				let clamped = Vec3::new(
+					local.x.max(min_corner.x).min(max_corner.x),
+					local.y.max(min_corner.y).min(max_corner.y),
+					local.z.max(min_corner.z).min(max_corner.z),
+				);
+				let outside = local - clamped;
+				let distance = outside.magnitude();
+				if EPSILON < distance {
+					return Some((distance, into_world.transform_vector(&(outside / distance))));
+				}
+				// Inside, so push out through the closest side.
+				let mut closest = (-f32::INFINITY, Vec3::zeros());
+				for axis in 0..3 {
+					for (side, sign) in [(min_corner[axis], -1.0), (max_corner[axis], 1.0)].iter() {
+						let side_distance = (local[axis] - side) * sign;
+						if closest.0 < side_distance {
+							let mut normal = Vec3::zeros();
+							normal[axis] = *sign;
+							closest = (side_distance, normal);
+						}
+					}
+				}
+				Some((closest.0, into_world.transform_vector(&closest.1)))
+			},
+			Shape::Mesh { polyhedron } => {
+				let mut closest : Option<(f32, Vec3)> = None;
+				for (indices, normal) in &polyhedron.faces {
+					let distance = (point - polyhedron.vertices[indices[0]]).dot(normal);
+					if closest.is_none_or(|(current, _)| current < distance) {
+						closest = Some((distance, *normal));
+					}
+				}
+				closest
+			},
+			Shape::TriMesh { into_local, into_world, mesh } => {
+				let local = into_local.transform_point(&Point3::from(*point)).coords;
+				let (triangle, closest) = mesh.closest_point(&local)?;
+				let normal = mesh.lookup.normals[triangle];
+				let offset = local - closest;
+				let distance = offset.magnitude();
+				if offset.dot(&normal) < 0.0 {
+					// Behind the surface, which only counts if it's not too far behind.
+					if mesh.thickness < distance { return None; }
+					return Some((-distance, into_world.transform_vector(&normal)));
+				}
+				let direction = if EPSILON < distance { offset / distance } else { normal };
+				Some((distance, into_world.transform_vector(&direction)))
+			},
+		}
+	}
+
+	/// Finds everywhere the two shapes overlap.
+	fn collide(&self, other : &Shape) -> Vec<ShapeContact> {
+		let bounds = (self.get_bounds(), other.get_bounds());
+		if let (Some((center, radius)), Some((other_center, other_radius))) = bounds {
+			if radius + other_radius < (center - other_center).magnitude() { return Vec::new(); }
+		}
+		if let (Some(polyhedron), Some(other_polyhedron)) = (self.get_polyhedron(), other.get_polyhedron()) {
+			return collide_polyhedra(polyhedron, other_polyhedron);
+		}
+		// Otherwise check each shape's points against the other shape.
+		let mut contacts = Vec::new();
+		for (pressing_is_self, pressing, pressed) in [(true, self, other), (false, other, self)].iter() {
+			for (point, radius) in pressing.get_points() {
+				if let Some((distance, normal)) = pressed.get_signed_distance(&point) {
+					if distance < radius {
+						contacts.push((*pressing_is_self, point - normal * radius, point - normal * distance, normal));
+					}
+				}
+			}
+		}
+		contacts
+	}
+}
+/// A collider's shape along with the surface properties needed to respond to contacts.
+#[derive(Debug)]
+struct ColliderInfo {
+	/// The shape in world space.
+	shape : Shape,
+	/// The restitution coefficient.
+	restitution_coefficient : f32,
+	/// The static friction coefficient.
+	static_friction_coefficient : f32,
+	/// The dynamic friction coefficient.
+	dynamic_friction_coefficient : f32,
+}
+
+/// A point on one body pressing into another body, found during a single substep.
+#[derive(Debug)]
+struct ContactConstraint {
+	/// The index of the first body.
+	first : usize,
+	/// The index of the second body.
+	second : usize,
+	/// The contact point on the first body. Relative to its center of mass, in its unrotated frame.
+	first_offset : Vec3,
+	/// The contact point on the second body. Relative to its center of mass, in its unrotated frame.
+	second_offset : Vec3,
+	/// The direction to push the first body (in world space). The second body is pushed the other way.
+	normal : Vec3,
+	/// The combined restitution coefficient.
+	restitution_coefficient : f32,
+	/// The combined static friction coefficient.
+	static_friction_coefficient : f32,
+	/// The combined dynamic friction coefficient.
+	dynamic_friction_coefficient : f32,
+	/// How fast the points were moving apart (along `normal`) at the start of the substep.
+	normal_speed : f32,
+	/// The total position-level impulse used pushing the bodies apart.
+	normal_lambda : f32,
+	/// The total position-level impulse used to stop sliding.
+	tangent_lambda : f32,
+	/// How deep the contact was at the start of the current position iteration. Only used for ordering.
+	depth : f32,
+}
+
+impl ContactConstraint {
+	/// Gets the world space offsets of the contact points from their bodies' centers of mass.
+	fn get_arms(&self, bodies : &[Body]) -> (Vec3, Vec3) {
+		(bodies[self.first].orientation.rotation * self.first_offset, bodies[self.second].orientation.rotation * self.second_offset)
+	}
+
+	/// Gets how far the contact points are pressed into each other along the normal (negative if they've separated).
+	fn get_depth(&self, bodies : &[Body]) -> f32 {
+		let (first_arm, second_arm) = self.get_arms(bodies);
+		((bodies[self.second].orientation.position + second_arm) - (bodies[self.first].orientation.position + first_arm)).dot(&self.normal)
+	}
+
+	/// Gets the position-level impulse needed to push the bodies apart (zero if they aren't overlapping).
+	fn get_push(&self, bodies : &[Body], compliance : f32, dt : f32) -> f32 {
+		let depth = self.get_depth(bodies);
+		if depth <= 0.0 { return 0.0; }
+		let (first_arm, second_arm) = self.get_arms(bodies);
+		get_correction(&bodies[self.first], &first_arm, &bodies[self.second], &second_arm, &(self.normal * depth), compliance / (dt * dt), self.normal_lambda).1
+	}
+
+	/// Gets the direction and position-level impulse needed to undo all of the sliding this substep. None if the bodies aren't pressing together (or aren't sliding).
+	fn get_static_friction(&self, bodies : &[Body]) -> Option<(Vec3, f32)> {
+		if self.normal_lambda <= 0.0 { return None; }
+		let (first, second) = (&bodies[self.first], &bodies[self.second]);
+		let (first_arm, second_arm) = self.get_arms(bodies);
+		let first_movement = first.movement + first_arm - first.previous_orientation.rotation * self.first_offset;
+		let second_movement = second.movement + second_arm - second.previous_orientation.rotation * self.second_offset;
+		let movement = first_movement - second_movement;
+		let sliding = movement - self.normal * movement.dot(&self.normal);
+		let (direction, delta_lambda) = get_correction(first, &first_arm, second, &second_arm, &-sliding, 0.0, 0.0);
+		if 0.0 < delta_lambda { Some((direction, delta_lambda)) } else { None }
+	}
+
+	/// Applies a position-level impulse to the first body's contact point (and the opposite to the second's).
+	fn apply(&self, bodies : &mut [Body], impulse : &Vec3) {
+		let (first_arm, second_arm) = self.get_arms(bodies);
+		let (first, second) = get_pair(bodies, self.first, self.second);
+		apply_positional_impulses(first, &first_arm, second, &second_arm, impulse);
+	}
+
+	/// Applies dynamic friction and restitution (if the bodies were pushed apart this substep).
+	fn solve_velocity(&self, bodies : &mut [Body], dt : f32) {
+		if self.normal_lambda <= 0.0 { return; }
+		let (first, second) = get_pair(bodies, self.first, self.second);
+		let first_arm = first.orientation.rotation * self.first_offset;
+		let second_arm = second.orientation.rotation * self.second_offset;
+		let velocity = first.velocity_at(&first_arm) - second.velocity_at(&second_arm);
+		let normal_speed = velocity.dot(&self.normal);
+		let tangent_velocity = velocity - self.normal * normal_speed;
+		let tangent_speed = tangent_velocity.magnitude();
+		let mut change = Vec3::zeros();
+		if EPSILON < tangent_speed {
+			let normal_force = self.normal_lambda / (dt * dt);
+			change -= tangent_velocity * ((dt * self.dynamic_friction_coefficient * normal_force).min(tangent_speed) / tangent_speed);
+		}
+		// Skip restitution for slow contacts, otherwise resting things would jitter from gravity.
+		let gravity = first.acceleration.magnitude().max(second.acceleration.magnitude());
+		let restitution_coefficient = if normal_speed.abs() <= 2.0 * gravity * dt { 0.0 } else { self.restitution_coefficient };
+		change += self.normal * (-normal_speed + (-restitution_coefficient * self.normal_speed).max(0.0));
+		let change_length = change.magnitude();
+		if change_length < EPSILON { return; }
+		let direction = change / change_length;
+		let weight = first.generalized_inverse_mass(&first_arm, &direction) + second.generalized_inverse_mass(&second_arm, &direction);
+		if weight < EPSILON { return; }
+		let impulse = direction * (change_length / weight);
+		first.apply_velocity_impulse(&first_arm, &impulse);
+		second.apply_velocity_impulse(&second_arm, &-impulse);
+	}
+}
+
+/// A [BallJoint] between two bodies, ready to be solved.
+#[derive(Debug)]
+struct JointConstraint {
+	/// The joint this is for.
+	handle : JointHandle,
+	/// The index of the first body.
+	first : usize,
+	/// The anchor on the first body. Relative to its center of mass, in its unrotated frame.
+	first_offset : Vec3,
+	/// The index of the second body.
+	second : usize,
+	/// The anchor on the second body. Relative to its center of mass, in its unrotated frame.
+	second_offset : Vec3,
+	/// The joint's compliance.
+	compliance : f32,
+}
+
+impl JointConstraint {
+	/// Pulls the anchors back together. Returns how much position-level impulse that took.
+	fn solve_position(&self, bodies : &mut [Body], dt : f32) -> f32 {
+		let (first, second) = get_pair(bodies, self.first, self.second);
+		let first_arm = first.orientation.rotation * self.first_offset;
+		let second_arm = second.orientation.rotation * self.second_offset;
+		let offset = (second.orientation.position + second_arm) - (first.orientation.position + first_arm);
+		let (direction, delta_lambda) = get_correction(first, &first_arm, second, &second_arm, &offset, self.compliance / (dt * dt), 0.0);
+		apply_positional_impulses(first, &first_arm, second, &second_arm, &(direction * delta_lambda));
+		delta_lambda.abs()
+	}
+}
+
+impl XpbdWorld {
+	/// Creates a new instance.
+	pub fn new() -> XpbdWorld {
+		XpbdWorld {
+			storage: PhysicsSystem::new(),
+			joints: Arena::new(),
+			break_events: Vec::new(),
+			substeps: 20,
+			position_iterations: 2,
+			contact_compliance: 0.0,
+		}
+	}
+
+	/// Adds a joint, returning its handle. Both entities must exist.
+	///
+	/// The two entities won't collide with each other while the joint exists.
+	pub fn add_joint(&mut self, joint : BallJoint) -> Result<JointHandle, ()> {
+		let entities = self.storage.entities.borrow();
+		if !joint.is_valid() || !entities.contains(joint.first) || !entities.contains(joint.second) {
+			return Err(()); // TODO: An error type.
+		}
+		Ok(self.joints.insert(joint))
+	}
+
+	/// Removes and returns a joint.
+	pub fn remove_joint(&mut self, handle : JointHandle) -> Option<BallJoint> {
+		self.joints.remove(handle)
+	}
+
+	/// Gets a joint.
+	pub fn get_joint(&self, handle : JointHandle) -> Option<&BallJoint> {
+		self.joints.get(handle)
+	}
+
+	/// Gets every joint that broke (see [BallJoint::break_impulse]) since the last call, in the order they broke. Call it after every step() to get each step's breaks.
+	pub fn take_break_events(&mut self) -> Vec<BreakEvent> {
+		std::mem::take(&mut self.break_events)
+	}
+
+	/// Copies all of the enabled entities out into bodies (working out the accelerations from the force generators along the way).
+	fn make_bodies(&self, dt : f32) -> (Vec<Body>, Vec<EntityHandle>) {
+		let handles : Vec<EntityHandle> = self.storage.entities.borrow().iter().filter(|(_, entity)| entity.enabled).map(|(handle, _)| handle).collect();
+		let mut bodies = Vec::with_capacity(handles.len());
+		for handle in &handles {
+			let (orientation, body_type, total_mass, moment, view) = {
+				let entities = self.storage.entities.borrow();
+				let entity = entities.get(*handle).unwrap();
+				(entity.orientation, entity.get_body_type(), entity.get_total_mass(), entity.get_moment_of_inertia(), EntityView::new(*handle, entity))
+			};
+			// Kinematic (and zero-mass) entities just move along at their current velocity. Static ones don't move at all.
+			let movable = BodyType::Dynamic == body_type && EPSILON < total_mass;
+			let still = BodyType::Static == body_type;
+			let rotation = orientation.rotation.to_rotation_matrix().into_inner();
+			let local_inverse_moment = if movable { (rotation.transpose() * moment * rotation).try_inverse().unwrap_or_else(Mat3::zeros) } else { Mat3::zeros() };
+			let mut force = Vec3::zeros();
+			let mut torque = Vec3::zeros();
+			if movable {
+				let mut generators = self.storage.unary_force_generators.borrow_mut();
+				for (generator_handle, generator) in generators.iter_mut() {
+					let scale = if let Some(scale) = self.storage.generator_scale(generator_handle) { scale } else { continue; };
+					let generated = generator.make_force(dt, self, &view);
+					force += generated.force * scale;
+					torque += (generated.position - orientation.position).cross(&generated.force) * scale;
+				}
+			}
+			let entities = self.storage.entities.borrow();
+			let entity = entities.get(*handle).unwrap();
+			let body = Body {
+				orientation,
+				previous_orientation: orientation,
+				movement: Vec3::zeros(),
+				velocity: if still { Vec3::zeros() } else { entity.velocity },
+				angular_velocity: if still { Vec3::zeros() } else { entity.angular_velocity },
+				previous_velocity: if still { Vec3::zeros() } else { entity.velocity },
+				previous_angular_velocity: if still { Vec3::zeros() } else { entity.angular_velocity },
+				acceleration: if movable { force / total_mass } else { Vec3::zeros() },
+				angular_acceleration: rotation * local_inverse_moment * rotation.transpose() * torque,
+				inverse_mass: if movable { 1.0 / total_mass } else { 0.0 },
+				local_inverse_moment,
+				colliders: entity.colliders.iter().copied().collect(),
+			};
+			bodies.push(body);
+		}
+		(bodies, handles)
+	}
+
+	/// Finds every point where two bodies overlap.
+	fn find_contacts(&self, bodies : &[Body], ignored_pairs : &HashSet<(usize, usize)>) -> Vec<ContactConstraint> {
+		let colliders = self.storage.colliders.borrow();
+		let infos : Vec<Vec<ColliderInfo>> = bodies.iter().map(|body| {
+			body.colliders.iter().map(|handle| colliders.get(*handle).unwrap()).filter(|collider| collider.collides()).map(|collider| {
+				ColliderInfo {
+					shape: Shape::new(collider.as_ref(), &body.orientation),
+					restitution_coefficient: collider.get_restitution_coefficient(),
+					static_friction_coefficient: collider.get_static_friction_coefficient(),
+					dynamic_friction_coefficient: collider.get_dynamic_friction_coefficient(),
+				}
+			}).collect()
+		}).collect();
+		let mut contacts = Vec::new();
+		for first in 0..bodies.len() {
+			for second in (first + 1)..bodies.len() {
+				if 0.0 == bodies[first].inverse_mass && 0.0 == bodies[second].inverse_mass { continue; }
+				if ignored_pairs.contains(&(first, second)) { continue; }
+				for first_info in &infos[first] {
+					for second_info in &infos[second] {
+						for (first_is_pressing, pressing_point, pressed_point, normal) in first_info.shape.collide(&second_info.shape) {
+							let (pressing, pressed) = if first_is_pressing { (first, second) } else { (second, first) };
+							let pressing_body = &bodies[pressing];
+							let pressed_body = &bodies[pressed];
+							let pressing_arm = pressing_point - pressing_body.orientation.position;
+							let pressed_arm = pressed_point - pressed_body.orientation.position;
+							contacts.push(ContactConstraint {
+								first: pressing,
+								second: pressed,
+								first_offset: pressing_body.orientation.rotation.inverse() * pressing_arm,
+								second_offset: pressed_body.orientation.rotation.inverse() * pressed_arm,
+								normal,
+								restitution_coefficient: first_info.restitution_coefficient * second_info.restitution_coefficient,
+								static_friction_coefficient: first_info.static_friction_coefficient * second_info.static_friction_coefficient,
+								dynamic_friction_coefficient: first_info.dynamic_friction_coefficient * second_info.dynamic_friction_coefficient,
+								normal_speed: (pressing_body.previous_velocity + pressing_body.previous_angular_velocity.cross(&pressing_arm) - pressed_body.previous_velocity - pressed_body.previous_angular_velocity.cross(&pressed_arm)).dot(&normal),
+								normal_lambda: 0.0,
+								tangent_lambda: 0.0,
+								depth: 0.0,
+							});
+						}
+					}
+				}
+			}
+		}
+		contacts
+	}
+}
+
+impl PhysicsWorld for XpbdWorld {
+	fn add_entity(&mut self, source : Entity) -> Result<EntityHandle, ()> { self.storage.add_entity(source) }
+
+	fn remove_entity(&mut self, handle : EntityHandle) -> bool {
+		self.joints.retain(|_, joint| joint.first != handle && joint.second != handle);
+		self.storage.remove_entity(handle)
+	}
+
+	fn get_entity(&self, handle : EntityHandle) -> Option<Entity> { self.storage.get_entity(handle) }
+
+	fn get_entity_mass_and_position(&self, handle : EntityHandle) -> Option<(f32, Vec3)> { self.storage.get_entity_mass_and_position(handle) }
+
+	fn update_entity(&mut self, handle : EntityHandle, source : Entity) -> Result<(), ()> { self.storage.update_entity(handle, source) }
+
+	fn add_collider(&mut self, source : ColliderWrapper) -> Result<ColliderHandle, ()> { self.storage.add_collider(source) }
+
+	fn remove_collider(&mut self, handle : ColliderHandle) { self.storage.remove_collider(handle) }
+
+	fn get_collider(&self, handle : ColliderHandle) -> Option<ColliderWrapper> { self.storage.get_collider(handle) }
+
+	fn update_collider(&mut self, handle : ColliderHandle, source : ColliderWrapper) -> Result<(), ()> { self.storage.update_collider(handle, source) }
+
+	fn link_collider(&mut self, collider_handle : ColliderHandle, entity_handle : Option<EntityHandle>) -> Result<(), ()> { self.storage.link_collider(collider_handle, entity_handle) }
+
+	fn add_unary_force_generator(&mut self, generator : Box<dyn UnaryForceGenerator>) -> Result<UnaryForceGeneratorHandle, ()> { self.storage.add_unary_force_generator(generator) }
+
+	fn remove_unary_force_generator(&mut self, handle : UnaryForceGeneratorHandle) -> Option<Box<dyn UnaryForceGenerator>> { self.storage.remove_unary_force_generator(handle) }
+
+	fn set_force_generator_enabled(&mut self, handle : UnaryForceGeneratorHandle, enabled : bool) -> Result<(), ()> { self.storage.set_force_generator_enabled(handle, enabled) }
+
+	fn set_force_generator_strength(&mut self, handle : UnaryForceGeneratorHandle, strength : f32) -> Result<(), ()> { self.storage.set_force_generator_strength(handle, strength) }
+
+	fn step(&mut self, dt : f32) {
+		if dt.abs() < EPSILON || 0 == self.substeps {
+			return
+		}
+		let (mut bodies, handles) = self.make_bodies(dt);
+		let indices : HashMap<EntityHandle, usize> = handles.iter().enumerate().map(|(index, handle)| (*handle, index)).collect();
+		let mut joints = Vec::with_capacity(self.joints.len());
+		let mut ignored_pairs = HashSet::new();
+		for (handle, joint) in self.joints.iter() {
+			let (first, second) = if let (Some(first), Some(second)) = (indices.get(&joint.first), indices.get(&joint.second)) { (*first, *second) } else { continue; };
+			joints.push(JointConstraint {
+				handle,
+				first,
+				first_offset: joint.first_anchor + bodies[first].orientation.internal_origin_offset,
+				second,
+				second_offset: joint.second_anchor + bodies[second].orientation.internal_origin_offset,
+				compliance: joint.compliance,
+			});
+			ignored_pairs.insert((first.min(second), first.max(second)));
+		}
+
+		let substep_dt = dt / self.substeps as f32;
+		let mut joint_impulses = vec![0.0; joints.len()];
+		for _ in 0..self.substeps {
+			for body in &mut bodies {
+				body.integrate(substep_dt);
+			}
+			let mut contacts = self.find_contacts(&bodies, &ignored_pairs);
+			for _ in 0..self.position_iterations {
+				solve_contact_positions(&mut contacts, &mut bodies, self.contact_compliance, substep_dt);
+			}
+			for (joint, impulse) in joints.iter().zip(joint_impulses.iter_mut()) {
+				*impulse += joint.solve_position(&mut bodies, substep_dt) / substep_dt; // Moving by a position-level impulse over the substep takes this much (velocity-level) impulse.
+			}
+			for body in &mut bodies {
+				body.update_velocities(substep_dt);
+			}
+			for contact in &contacts {
+				contact.solve_velocity(&mut bodies, substep_dt);
+			}
+		}
+
+		let mut entities = self.storage.entities.borrow_mut();
+		for (handle, body) in handles.iter().zip(bodies.iter()) {
+			let entity = entities.get_mut(*handle).unwrap();
+			entity.step_path.clear();
+			entity.step_path.push((0.0, entity.orientation));
+			entity.step_path.push((dt, body.orientation));
+			entity.orientation = body.orientation;
+			entity.velocity = body.velocity;
+			entity.angular_velocity = body.angular_velocity;
+		}
+		drop(entities);
+
+		for (joint, impulse) in joints.iter().zip(joint_impulses.iter()) {
+			if self.joints[joint.handle].break_impulse < *impulse {
+				self.joints.remove(joint.handle);
+				self.break_events.push(BreakEvent { broken: Broken::Joint(joint.handle), impulse: *impulse });
+			}
+		}
+	}
+
+	fn apply_impulse(&mut self, handle : EntityHandle, position : &Vec3, impulse : &Vec3) -> Result<(), ()> { self.storage.apply_impulse(handle, *position, *impulse) }
+
+	fn add_joint(&mut self, joint : BallJoint) -> Result<JointHandle, ()> { XpbdWorld::add_joint(self, joint) }
+
+	fn cast_ray(&self, origin : &Vec3, direction : &Vec3, max_distance : f32, ignore : Option<EntityHandle>) -> Option<RayHit> { self.storage.cast_ray(origin, direction, max_distance, ignore) }
+
+	fn cast_sphere(&self, origin : &Vec3, direction : &Vec3, max_distance : f32, radius : f32, ignore : Option<EntityHandle>) -> Option<RayHit> { self.storage.cast_sphere(origin, direction, max_distance, radius, ignore) }
+
+	fn cast_ray_masked(&self, origin : &Vec3, direction : &Vec3, max_distance : f32, ignore : Option<EntityHandle>, mask : u32) -> Option<RayHit> { self.storage.cast_ray_masked(origin, direction, max_distance, ignore, mask) }
+
+	fn cast_sphere_masked(&self, origin : &Vec3, direction : &Vec3, max_distance : f32, radius : f32, ignore : Option<EntityHandle>, mask : u32) -> Option<RayHit> { self.storage.cast_sphere_masked(origin, direction, max_distance, radius, ignore, mask) }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::f32::INFINITY;
+	use crate::physics_world::shared_tests;
+	use crate::sphere_collider::SphereCollider;
+	use crate::plane_collider::PlaneCollider;
+	use crate::aligned_box_collider::AlignedBoxCollider;
+	use crate::gravity_generator::GravityGenerator;
+
+	#[test]
+	fn shared() {
+		shared_tests::ball_lands_on_floor(&mut XpbdWorld::new());
+		shared_tests::ball_rests_inside_room(&mut XpbdWorld::new());
+	}
+
+	/// Makes a world with a floor and gravity.
+	fn make_world() -> XpbdWorld {
+		let mut world = XpbdWorld::new();
+		let floor = world.add_entity(Entity::new()).unwrap();
+		let mut plane = PlaneCollider::new();
+		plane.mass = INFINITY;
+		let plane_handle = world.add_collider(ColliderWrapper::Plane(plane)).unwrap();
+		world.link_collider(plane_handle, Some(floor)).unwrap();
+		world.add_unary_force_generator(Box::new(GravityGenerator::new(Vec3::new(0.0, -10.0, 0.0)))).unwrap();
+		world
+	}
+
+	/// Check that a tall stack of boxes settles and then just stands there.
+	#[test]
+	fn box_stack() {
+		let mut world = make_world();
+		world.substeps = 60;
+		let mut boxes = Vec::new();
+		for index in 0..30 {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 0.5 + index as f32, 0.0);
+			let handle = world.add_entity(entity).unwrap();
+			let mut collider = AlignedBoxCollider::new();
+			collider.min_corner = Vec3::new(-0.5, -0.5, -0.5);
+			collider.max_corner = Vec3::new( 0.5,  0.5,  0.5);
+			collider.mass = 1.0;
+			collider.restitution_coefficient = 0.0;
+			let collider_handle = world.add_collider(ColliderWrapper::AlignedBox(collider)).unwrap();
+			world.link_collider(collider_handle, Some(handle)).unwrap();
+			boxes.push(handle);
+		}
+		// Let it settle, then check it stays put for a few more seconds.
+		for _ in 0..60 {
+			world.step(1.0 / 60.0);
+		}
+		let settled : Vec<Vec3> = boxes.iter().map(|handle| world.get_entity(*handle).unwrap().position).collect();
+		for _ in 0..(60 * 4) {
+			world.step(1.0 / 60.0);
+		}
+		for ((index, handle), settled) in boxes.iter().enumerate().zip(settled.iter()) {
+			let entity = world.get_entity(*handle).unwrap();
+			assert!((entity.position - Vec3::new(0.0, 0.5 + index as f32, 0.0)).magnitude() < 0.05, "box {} is at {:?}", index, entity.position);
+			assert!((entity.position - settled).magnitude() < 0.01, "box {} moved from {:?} to {:?}", index, settled, entity.position);
+			assert!(entity.velocity.magnitude() < 0.05, "box {} is moving at {:?}", index, entity.velocity);
+		}
+	}
+
+	/// Check that a jointed pendulum swings without its rod stretching.
+	#[test]
+	fn pendulum() {
+		let mut world = make_world();
+		let pivot = world.add_entity(Entity::new()).unwrap();
+		{
+			let mut sphere = SphereCollider::new(0.1);
+			sphere.mass = INFINITY;
+			let sphere_handle = world.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			world.link_collider(sphere_handle, Some(pivot)).unwrap();
+		}
+		world.update_entity(pivot, { let mut entity = world.get_entity(pivot).unwrap(); entity.position = Vec3::new(0.0, 3.0, 0.0); entity }).unwrap();
+		let bob = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(1.0, 3.0, 0.0);
+			let handle = world.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.1);
+			sphere.mass = 1.0;
+			let sphere_handle = world.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			world.link_collider(sphere_handle, Some(handle)).unwrap();
+			handle
+		};
+		let mut joint = BallJoint::new(pivot, bob);
+		joint.second_anchor = Vec3::new(-1.0, 0.0, 0.0);
+		world.add_joint(joint).unwrap();
+		let mut lowest : f32 = 3.0;
+		for _ in 0..60 {
+			world.step(1.0 / 60.0);
+			let position = world.get_entity(bob).unwrap().position;
+			assert!(((position - Vec3::new(0.0, 3.0, 0.0)).magnitude() - 1.0).abs() < 0.01);
+			lowest = lowest.min(position.y);
+		}
+		// Should have swung through the bottom (without losing much energy).
+		assert!((lowest - 2.0).abs() < 0.01);
+		assert!(world.remove_joint(world.joints.iter().next().unwrap().0).is_some());
+	}
+
+	/// Check that a joint breaks (and is reported) once it's pulled harder than its break impulse.
+	#[test]
+	fn joint_breaks() {
+		let mut world = make_world();
+		let ceiling = world.add_entity({ let mut entity = Entity::new(); entity.position = Vec3::new(0.0, 5.0, 0.0); entity.body_type = BodyType::Static; entity }).unwrap();
+		let weight = {
+			let mut entity = Entity::new();
+			entity.position = Vec3::new(0.0, 4.0, 0.0);
+			let handle = world.add_entity(entity).unwrap();
+			let mut sphere = SphereCollider::new(0.1);
+			sphere.mass = 1.0;
+			let sphere_handle = world.add_collider(ColliderWrapper::Sphere(sphere)).unwrap();
+			world.link_collider(sphere_handle, Some(handle)).unwrap();
+			handle
+		};
+		let mut joint = BallJoint::new(ceiling, weight);
+		joint.second_anchor = Vec3::new(0.0, 1.0, 0.0);
+		// Holding the weight up takes 10 * (1 / 60) of impulse each step.
+		joint.break_impulse = 0.3;
+		let strong = world.add_joint(joint.clone()).unwrap();
+		for _ in 0..30 {
+			world.step(1.0 / 60.0);
+		}
+		assert!(world.take_break_events().is_empty());
+		assert!((world.get_entity(weight).unwrap().position.y - 4.0).abs() < 0.01);
+
+		world.remove_joint(strong);
+		joint.break_impulse = 0.1;
+		let weak = world.add_joint(joint).unwrap();
+		world.step(1.0 / 60.0);
+		let events = world.take_break_events();
+		assert_eq!(events.len(), 1);
+		assert_eq!(events[0].broken, Broken::Joint(weak));
+		assert!(0.1 < events[0].impulse && events[0].impulse < 0.3, "{}", events[0].impulse);
+		assert!(world.get_joint(weak).is_none());
+		for _ in 0..30 {
+			world.step(1.0 / 60.0);
+		}
+		assert!(world.get_entity(weight).unwrap().position.y < 3.5);
+	}
+}